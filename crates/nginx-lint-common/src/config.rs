@@ -5,6 +5,8 @@
 //! loaded from a file with [`LintConfig::from_file`] or discovered
 //! automatically with [`LintConfig::find_and_load`].
 
+use crate::linter::Severity;
+use crate::parser::format::{BraceStyle, FormatStyle};
 use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer};
 use std::collections::{HashMap, HashSet};
@@ -111,6 +113,13 @@ pub const DEFAULT_CONFIG_TEMPLATE: &str = r#"# nginx-lint configuration file
 # Per-rule `skip_version_check = true` forces a rule to run regardless.
 # target_nginx_version = "1.31.0"
 
+# Rule set to start from: "minimal" (errors that break nginx or open
+# well-known holes), "recommended" (adds likely bugs, security and
+# deprecation warnings) or "strict" (every rule, the default). Rules with a
+# [rules.<name>] section below keep their settings. --preset on the command
+# line takes precedence.
+# preset = "recommended"
+
 # Cache directory for nginx-lint. Cacheable artifacts are stored in
 # subdirectories beneath it (e.g. the WASM plugin compilation cache under
 # "plugins/"). Defaults to the per-user cache directory (e.g.
@@ -118,6 +127,24 @@ pub const DEFAULT_CONFIG_TEMPLATE: &str = r#"# nginx-lint configuration file
 # directory containing this file.
 # cache_dir = ".nginx-lint-cache"
 
+# How far the linted configuration is trusted. "untrusted" disables
+# third-party plugins, the compilation cache, checks that read files outside
+# the configuration, and plugin downloads; use it when linting configurations
+# written by others (e.g. on shared CI runners). A stricter --trust-policy on
+# the command line takes precedence; a looser one does not.
+# trust_policy = "untrusted"
+
+# Where the config is deployed. Rules that depend on the environment (e.g.
+# whether a load balancer sits in front of nginx) use these instead of
+# guessing. Every key is optional.
+# [environment]
+# Target operating system: "linux", "freebsd", "windows", ...
+# os = "linux"
+# Whether nginx runs in a container rather than on a host of its own
+# container = true
+# Whether nginx sits behind a load balancer, CDN or another proxy
+# behind_load_balancer = true
+
 # Color output settings
 [color]
 # Color mode: "auto", "always", or "never"
@@ -203,12 +230,18 @@ enabled = true
 [rules.include-path-exists]
 enabled = true
 
+[rules.include-cycle]
+enabled = true
+
 # =============================================================================
 # Security Rules
 # =============================================================================
 
 [rules.deprecated-ssl-protocol]
 enabled = true
+# Any rule accepts severity = "error" or "warning" to override how its
+# findings are reported
+# severity = "error"
 # Allowed protocols for auto-fix (default: ["TLSv1.2", "TLSv1.3"])
 allowed_protocols = ["TLSv1.2", "TLSv1.3"]
 
@@ -250,6 +283,31 @@ required_exclusions = ["!aNULL", "!eNULL", "!EXPORT", "!DES", "!RC4", "!MD5"]
 enabled = true
 # skip_version_check = true
 
+[rules.redirect-to-http]
+enabled = true
+
+[rules.allow-deny-order]
+enabled = true
+
+[rules.unsafe-x-forwarded-for]
+enabled = true
+# Where nginx sits: "edge" (clients connect directly) or "internal" (behind
+# another proxy). Also reports X-Forwarded-For forwarded without set_real_ip_from.
+# deployment = "internal"
+
+[rules.permissive-cors]
+enabled = true
+
+[rules.alias-traversal]
+enabled = true
+
+[rules.missing-security-headers]
+# Disabled by default: which headers to send is an application policy
+enabled = false
+# Headers every server must send (Strict-Transport-Security only applies to
+# servers listening with ssl)
+# required_headers = ["Strict-Transport-Security", "X-Content-Type-Options", "X-Frame-Options", "Content-Security-Policy"]
+
 # =============================================================================
 # Best Practices
 # =============================================================================
@@ -257,14 +315,164 @@ enabled = true
 [rules.gzip-not-enabled]
 # Disabled by default: gzip is not always appropriate (CDN, CPU constraints, BREACH attack)
 enabled = false
+# Also report gzip_types that is missing or omits common text types
+# check_gzip_types = true
 
 [rules.missing-error-log]
 # Disabled by default: error_log is typically set at top level in main config
 enabled = false
 
+[rules.large-client-header-buffers-not-set]
+# Disabled by default: advisory for API servers that forward Authorization
+# headers (large JWTs/cookies exceed the default 4 8k buffers)
+enabled = false
+
+[rules.precompressed-assets-not-served]
+# Disabled by default: scans the document root on disk for .gz/.br files
+enabled = false
+# Scan this directory instead of the `root` paths in the configuration
+# docroot = "public"
+
+[rules.auth-basic-user-file-missing]
+# Disabled by default: checks auth_basic_user_file on disk (--check-paths
+# turns it on)
+enabled = false
+
+[rules.include-layering]
+# Disabled by default: enforces a project's own file layout, set in `layers`
+enabled = false
+# Each layer selects directives (wildcards allowed), optionally only those
+# directly inside `context`, and lists the files they may (allowed_paths) or
+# must not (denied_paths) appear in. Path globs match the end of the path.
+# [[rules.include-layering.layers]]
+# directives = ["server"]
+# context = "http"
+# allowed_paths = ["sites-enabled/*.conf"]
+# [[rules.include-layering.layers]]
+# directives = ["ssl_*"]
+# allowed_paths = ["snippets/ssl-*.conf"]
+# [[rules.include-layering.layers]]
+# directives = ["proxy_pass"]
+# denied_paths = ["nginx.conf"]
+
+[rules.lua-block-lint]
+# Disabled by default: runs an external Lua linter on *_by_lua_block bodies
+enabled = false
+# Program and arguments; the Lua code is written to its stdin and
+# `name:line:column: message` lines of its output are reported
+# command = ["luacheck", "--formatter", "plain", "--codes", "--std", "ngx_lua", "-"]
+
+[rules.rego-policy]
+# Disabled by default: evaluates Rego policies with `opa eval` against the
+# AST of each file (`input.config`, with `input.path`), reporting the values
+# of the query: messages, or objects with `msg` and optional `line`,
+# `column` and `severity`
+enabled = false
+# Policy files or directories, passed to opa with --data
+# policies = ["policy/nginx"]
+# query = "data.nginx.deny"
+# Program and arguments run; options, policies and query are appended
+# command = ["opa", "eval"]
+
+[rules.generated-region-modified]
+enabled = true
+
 [rules.proxy-pass-domain]
 enabled = true
 
+[rules.proxy-pass-hardcoded-ip]
+# Disabled by default: enforces upstream blocks / DNS names for backends
+enabled = false
+# Addresses or CIDR ranges that may be used directly (default: loopback)
+# allowed_addresses = ["127.0.0.0/8", "::1"]
+
+[rules.proxy-timeout-not-set]
+# Disabled by default: flags external proxy_pass targets using the 60s defaults
+enabled = false
+# Largest acceptable timeouts (nginx time syntax); also used by the fix
+# max_connect_timeout = "5s"
+# max_read_timeout = "60s"
+
+[rules.suspicious-proxy-settings]
+# Disabled by default: suitable buffering and timeouts depend on the application
+enabled = false
+# Largest acceptable proxy_read_timeout (default: "300s")
+# max_read_timeout = "300s"
+
+[rules.charset-conflict]
+enabled = true
+
+[rules.location-access-bypass]
+enabled = true
+
+[rules.upstream-zone-too-small]
+enabled = true
+
+[rules.http2-keepalive-not-tuned]
+# Disabled by default: flags HTTP/2 and gRPC servers keeping the keepalive defaults
+enabled = false
+# Smallest acceptable keepalive_requests; also the value the fix sets
+# min_keepalive_requests = 10000
+# Shortest acceptable keepalive_time (nginx time syntax)
+# min_keepalive_time = "1h"
+
+[rules.auth-request-body-forwarded]
+enabled = true
+
+[rules.plaintext-sensitive-upstream]
+# Disabled by default: flags proxy_pass http:// to remote backends from sensitive locations
+enabled = false
+# Path fragments that make a location sensitive (case-insensitive)
+# sensitive_paths = ["/login", "/signin", "/auth", "/account", "/password", "/payment", "/checkout", "/admin"]
+
+[rules.limit-req-not-customized]
+# Disabled by default: flags limit_req without limit_req_status or limit_req_log_level
+enabled = false
+
+[rules.worker-settings-mistuned]
+# Disabled by default: flags hard-coded worker_processes, a low worker_rlimit_nofile and a missing events block
+enabled = false
+# Largest worker_processes accepted as a hard-coded number
+# max_worker_processes = 16
+
+[rules.listen-conflict]
+enabled = true
+
+[rules.suspicious-server-name]
+enabled = true
+
+[rules.suspicious-rewrite]
+enabled = true
+
+[rules.if-host-comparison]
+enabled = true
+
+[rules.client-max-body-size-mismatch]
+# Disabled by default: flags upload locations with a small or lowered client_max_body_size
+enabled = false
+# Smallest acceptable client_max_body_size on upload locations; also the value the fix sets
+# min_upload_size = "10m"
+# Path fragments that make a proxying location an upload location (case-insensitive)
+# upload_paths = ["upload"]
+# Report upload locations setting a smaller value than an enclosing block
+# check_overrides = true
+
+[rules.ssl-performance-not-tuned]
+# Disabled by default: flags TLS servers without session caching, and OCSP stapling without a resolver
+enabled = false
+# Value the fix sets for ssl_session_cache
+# session_cache = "shared:SSL:10m"
+# Value the fix sets for ssl_session_timeout
+# session_timeout = "1d"
+# Also report TLS servers without ssl_stapling
+# require_stapling = false
+
+[rules.deprecated-directive]
+enabled = true
+
+[rules.njs-module-reference]
+enabled = true
+
 [rules.upstream-server-no-resolve]
 enabled = true
 
@@ -295,6 +503,56 @@ enabled = true
 [rules.if-is-evil-in-location]
 enabled = true
 
+# The rules below are checked across all included files, and are skipped for
+# partial configs linted with a context
+
+[rules.undefined-upstream]
+enabled = true
+
+[rules.unused-upstream]
+enabled = true
+
+[rules.upstream-keepalive-incomplete]
+enabled = true
+
+[rules.proxy-pass-upstream-mismatch]
+enabled = true
+
+[rules.missing-resolver]
+enabled = true
+
+[rules.access-log-misconfigured]
+enabled = true
+
+[rules.undefined-limit-zone]
+enabled = true
+
+[rules.unused-limit-zone]
+enabled = true
+
+[rules.proxy-cache-incomplete]
+enabled = true
+
+[rules.undefined-variable]
+enabled = true
+# Variables of third-party modules, without the `$`
+# known_variables = ["ssl_ja3", "geoip2_data_country_code"]
+# known_prefixes = ["geoip2_"]
+
+[rules.unused-variable]
+enabled = true
+
+[rules.missing-server-name]
+enabled = true
+
+[rules.https-redirect-misconfigured]
+enabled = true
+# Report HTTPS server names that no plain HTTP server redirects
+# require_redirect_server = true
+
+[rules.duplicate-default-server]
+enabled = true
+
 # =============================================================================
 # Parser Settings
 # =============================================================================
@@ -304,6 +562,54 @@ enabled = true
 # These are added to the built-in list (http, server, location, etc.)
 # Example for nginx-rtmp-module:
 # block_directives = ["rtmp", "application"]
+# Additional raw block directives, whose block holds code of another language
+# that is kept verbatim instead of being parsed (*_by_lua_block, js_body and
+# perl are built in)
+# raw_block_directives = ["js_inline"]
+
+# =============================================================================
+# Formatter Settings (used by `nginx-lint fmt`)
+# =============================================================================
+
+[format]
+# Columns per indentation level (also used by the indent rule)
+# indent_width = 4
+# Indent with tabs instead of spaces
+# use_tabs = false
+# Wrap directive arguments onto continuation lines beyond this width
+# max_line_length = 100
+# Opening brace placement: "same-line" or "next-line"
+# brace_style = "same-line"
+# Align the values of consecutive proxy_set_header directives
+# align_proxy_set_header = false
+
+# =============================================================================
+# Custom Rules
+# =============================================================================
+
+# Rules of your own, matching directives by name, arguments (a regular
+# expression over the arguments joined by spaces) and the block they are in
+# ("main" for the top level). {args}, {directive} and the groups of `args`
+# ({1}, {name}) are replaced in `message` and `fix`.
+#
+# Report locations under /internal that do not allow the internal network:
+# [[custom_rules]]
+# name = "internal-location-allow"
+# directive = "location"
+# args = "^/internal"
+# context = ["server"]
+# require = { directive = "allow", args = "^10\\.0\\.0\\.0/8$" }
+# message = "location {args} must allow 10.0.0.0/8"
+# severity = "error"
+# fix = "allow 10.0.0.0/8;"   # inserted into the block
+#
+# Report a directive, with a fix replacing it:
+# [[custom_rules]]
+# name = "no-proxy-buffering-off"
+# directive = "proxy_buffering"
+# args = "^off$"
+# message = "proxy_buffering off is not allowed here"
+# fix = "proxy_buffering on;"
 "#;
 
 /// Configuration for nginx-lint loaded from `.nginx-lint.toml`.
@@ -326,6 +632,9 @@ pub struct LintConfig {
     /// Include resolution settings (e.g. path mappings for include directives).
     #[serde(default)]
     pub include: IncludeConfig,
+    /// Formatter style settings used by `nginx-lint fmt` (and the `indent` rule).
+    #[serde(default)]
+    pub format: FormatConfig,
     /// Target nginx version (e.g. `"1.31.0"`).
     ///
     /// When set, rules whose declared version range does not include this
@@ -334,6 +643,9 @@ pub struct LintConfig {
     /// lazily and emits a single warning if invalid.
     #[serde(default)]
     pub target_nginx_version: Option<String>,
+    /// Rule set to start from (see [`RulePreset`]); every rule when unset.
+    #[serde(default)]
+    pub preset: Option<RulePreset>,
     /// Cache directory for nginx-lint.
     ///
     /// Cacheable artifacts are stored in subdirectories beneath it (e.g.
@@ -343,6 +655,83 @@ pub struct LintConfig {
     /// config file.
     #[serde(default)]
     pub cache_dir: Option<String>,
+    /// How far the linted configuration is trusted (see [`TrustPolicy`]).
+    ///
+    /// Combined with `--trust-policy` so that the stricter of the two applies.
+    #[serde(default)]
+    pub trust_policy: Option<TrustPolicy>,
+    /// Where the config is deployed (`[environment]` section), passed to every rule.
+    #[serde(default)]
+    pub environment: Environment,
+    /// Rules defined in the config (`[[custom_rules]]` tables).
+    #[serde(default)]
+    pub custom_rules: Vec<CustomRuleConfig>,
+}
+
+/// A rule defined in the config (`[[custom_rules]]`)
+///
+/// Every directive named `directive` whose arguments match `args` and whose
+/// enclosing block is in `context` is reported; with `require`, only those
+/// whose block lacks a matching directive are. `message` and `fix` are
+/// templates: `{directive}`, `{args}` and the groups of `args` (`{1}`,
+/// `{name}`) are replaced.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct CustomRuleConfig {
+    /// Rule name, used in diagnostics, ignore comments and `[rules.<name>]`
+    pub name: String,
+    /// Name of the directives to match
+    pub directive: String,
+    /// Regular expression the arguments, joined by single spaces, must match
+    #[serde(default)]
+    pub args: Option<String>,
+    /// Names of the blocks the directive may be directly in (`main` for the
+    /// top level); any block when empty
+    #[serde(default)]
+    pub context: Vec<String>,
+    /// Directive the block of a match must contain
+    #[serde(default)]
+    pub require: Option<DirectivePattern>,
+    /// Diagnostic message
+    pub message: String,
+    /// Severity of the diagnostics (default: warning)
+    #[serde(default)]
+    pub severity: Option<RuleSeverity>,
+    /// Fix: the directive replacing the match, or with `require`, the one
+    /// inserted at the start of its block
+    #[serde(default)]
+    pub fix: Option<String>,
+}
+
+/// A directive name, and optionally a regular expression its arguments
+/// (joined by single spaces) must match
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct DirectivePattern {
+    /// Directive name
+    pub directive: String,
+    /// Regular expression over the arguments
+    #[serde(default)]
+    pub args: Option<String>,
+}
+
+/// Deployment environment of the linted config (`[environment]` section).
+///
+/// Every field is optional: rules fall back to their own defaults for what
+/// is not set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct Environment {
+    /// Target operating system (e.g. `"linux"`, `"freebsd"`, `"windows"`)
+    pub os: Option<String>,
+    /// Whether nginx runs in a container rather than on a host of its own
+    pub container: Option<bool>,
+    /// Whether nginx sits behind a load balancer, CDN or another proxy
+    pub behind_load_balancer: Option<bool>,
+}
+
+impl Environment {
+    /// Whether nothing is set
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
 }
 
 /// Parser configuration
@@ -352,6 +741,58 @@ pub struct ParserConfig {
     /// These are added to the built-in list of block directives
     #[serde(default)]
     pub block_directives: Vec<String>,
+    /// Additional raw block directives, whose block holds code of another
+    /// language and is kept verbatim instead of being parsed as directives.
+    /// These are added to `*_by_lua_block`, `js_body` and `perl`
+    #[serde(default)]
+    pub raw_block_directives: Vec<String>,
+}
+
+/// Formatter style configuration (`[format]` section).
+///
+/// Converted to a parser [`FormatStyle`] with [`style`](Self::style).
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct FormatConfig {
+    /// Number of columns per indentation level (default: 4).
+    /// Also used by the `indent` rule when `rules.indent.indent_size` is not set.
+    pub indent_width: Option<usize>,
+    /// Indent with one tab per level instead of spaces (default: false)
+    #[serde(default)]
+    pub use_tabs: bool,
+    /// Wrap directive arguments onto continuation lines when a line would be
+    /// longer than this many columns (default: no limit)
+    pub max_line_length: Option<usize>,
+    /// Placement of the opening brace of blocks: "same-line" (default) or "next-line"
+    #[serde(default)]
+    #[schemars(schema_with = "brace_style_schema")]
+    pub brace_style: BraceStyle,
+    /// Align the values of consecutive `proxy_set_header` directives (default: false)
+    #[serde(default)]
+    pub align_proxy_set_header: bool,
+}
+
+impl FormatConfig {
+    /// Build the formatter style, filling unset options with defaults
+    pub fn style(&self) -> FormatStyle {
+        let defaults = FormatStyle::default();
+        FormatStyle {
+            indent_width: self.indent_width.unwrap_or(defaults.indent_width),
+            use_tabs: self.use_tabs,
+            max_line_length: self.max_line_length,
+            brace_style: self.brace_style,
+            align_proxy_set_header: self.align_proxy_set_header,
+        }
+    }
+}
+
+fn brace_style_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    serde_json::from_value(serde_json::json!({
+        "type": "string",
+        "description": "Placement of the opening brace of blocks",
+        "default": "same-line",
+        "enum": ["same-line", "next-line"]
+    }))
+    .unwrap()
 }
 
 /// A single path mapping rule for include directive resolution.
@@ -497,6 +938,90 @@ impl<'de> Deserialize<'de> for Color {
     }
 }
 
+/// How far a run trusts the configuration it lints
+///
+/// Ordered from the most to the least permissive, so the stricter of two
+/// policies is their maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustPolicy {
+    /// Everything is allowed (default)
+    #[default]
+    Trusted,
+    /// No third-party plugins, no compilation cache, no checks reading files
+    /// outside the configuration, and no network access
+    Untrusted,
+}
+
+impl TrustPolicy {
+    /// The name used in the config file and on the command line
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TrustPolicy::Trusted => "trusted",
+            TrustPolicy::Untrusted => "untrusted",
+        }
+    }
+}
+
+/// A rule set, from the rules nearly every configuration should pass to
+/// every rule
+///
+/// Rules declare the smallest set they belong to (see
+/// [`LintRule::preset`](crate::linter::LintRule::preset)), and each set
+/// includes the smaller ones, so the sets are ordered by size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RulePreset {
+    /// Errors that make nginx fail or misbehave, and well-known
+    /// vulnerabilities
+    Minimal,
+    /// Adds likely bugs, security problems and deprecations
+    Recommended,
+    /// Every rule, including style and tuning suggestions
+    Strict,
+}
+
+impl RulePreset {
+    /// Every rule set, from the smallest
+    pub const ALL: &'static [RulePreset] = &[
+        RulePreset::Minimal,
+        RulePreset::Recommended,
+        RulePreset::Strict,
+    ];
+
+    /// The name used in the config file, on the command line and in rule
+    /// declarations
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RulePreset::Minimal => "minimal",
+            RulePreset::Recommended => "recommended",
+            RulePreset::Strict => "strict",
+        }
+    }
+
+    /// The rule set called `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|p| p.as_str() == name)
+    }
+
+    /// Whether a rule whose smallest set is `declared` belongs to this set;
+    /// rules declaring none are only in `strict`
+    pub fn includes(self, declared: Option<RulePreset>) -> bool {
+        declared.unwrap_or(RulePreset::Strict) <= self
+    }
+
+    /// The names of the rule sets a rule whose smallest set is `declared`
+    /// belongs to, for display (e.g. `recommended, strict`)
+    pub fn describe_membership(declared: Option<RulePreset>) -> String {
+        Self::ALL
+            .iter()
+            .filter(|p| p.includes(declared))
+            .map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
 /// Color mode for output
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ColorMode {
@@ -545,19 +1070,23 @@ impl<'de> Deserialize<'de> for ColorMode {
     }
 }
 
-/// An additional directive to check for inheritance issues.
+/// A layer of the include-layering rule: which files the selected
+/// directives may appear in.
 ///
-/// Used in `[rules.directive-inheritance]` configuration.
+/// Used in `[[rules.include-layering.layers]]` configuration.
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
-pub struct AdditionalDirective {
-    /// The directive name (e.g., "proxy_set_cookie")
-    pub name: String,
-    /// Whether the first argument key comparison is case-insensitive (default: false)
+pub struct LayerPolicy {
+    /// Directive names to select; `*` and `?` wildcards are allowed (e.g. "ssl_*")
+    pub directives: Vec<String>,
+    /// Only select directives directly inside this block (e.g. "http", so
+    /// that `server` in `upstream` blocks is not selected)
+    pub context: Option<String>,
+    /// Globs of the files the selected directives may appear in (default: any)
     #[serde(default)]
-    pub case_insensitive: bool,
-    /// If true, all numeric arguments are separate keys like error_page (default: false)
+    pub allowed_paths: Vec<String>,
+    /// Globs of the files the selected directives must not appear in
     #[serde(default)]
-    pub multi_key: bool,
+    pub denied_paths: Vec<String>,
 }
 
 /// Configuration for a specific lint rule.
@@ -576,6 +1105,9 @@ pub struct RuleConfig {
     /// otherwise be filtered out as not applicable to the target version.
     #[serde(default)]
     pub skip_version_check: bool,
+    /// Report this rule's diagnostics with the given severity instead of
+    /// the one the rule chose
+    pub severity: Option<RuleSeverity>,
     /// For indent rule: number or "auto" for auto-detection
     pub indent_size: Option<IndentSize>,
     /// For deprecated-ssl-protocol rule: allowed protocols (default: ["TLSv1.2", "TLSv1.3"])
@@ -589,16 +1121,72 @@ pub struct RuleConfig {
     pub additional_contexts: Option<HashMap<String, Vec<String>>>,
     /// For block-lines rule: maximum number of lines allowed in a block
     pub max_block_lines: Option<usize>,
-    /// For directive-inheritance rule: directives to exclude from checking
-    pub excluded_directives: Option<Vec<String>>,
-    /// For directive-inheritance rule: additional directives to check
-    pub additional_directives: Option<Vec<AdditionalDirective>>,
+    /// For precompressed-assets-not-served rule: local directory to scan in
+    /// place of the `root` paths in the configuration
+    pub docroot: Option<String>,
+    /// For proxy-pass-hardcoded-ip rule: addresses or CIDR ranges allowed in
+    /// proxy_pass (default: ["127.0.0.0/8", "::1"])
+    pub allowed_addresses: Option<Vec<String>>,
+    /// For missing-security-headers rule: headers every server must send
+    /// (default: Strict-Transport-Security, X-Content-Type-Options,
+    /// X-Frame-Options, Content-Security-Policy)
+    pub required_headers: Option<Vec<String>>,
+    /// For proxy-timeout-not-set rule: largest acceptable
+    /// proxy_connect_timeout (nginx time syntax, e.g. "5s")
+    pub max_connect_timeout: Option<String>,
+    /// For proxy-timeout-not-set and suspicious-proxy-settings rules: largest
+    /// acceptable proxy_read_timeout
+    pub max_read_timeout: Option<String>,
+    /// For unsafe-x-forwarded-for rule: where nginx sits, "edge" or "internal"
+    pub deployment: Option<String>,
+    /// For include-layering rule: where directives may appear
+    pub layers: Option<Vec<LayerPolicy>>,
+    /// For https-redirect-misconfigured rule: whether names served over
+    /// HTTPS need a plain HTTP server redirecting them (default: true)
+    pub require_redirect_server: Option<bool>,
+    /// For undefined-variable rule: variables defined outside the
+    /// configuration, e.g. by third-party modules (without the `$`)
+    pub known_variables: Option<Vec<String>>,
+    /// For undefined-variable rule: prefixes of such variable families
+    /// (e.g. "geoip2_")
+    pub known_prefixes: Option<Vec<String>>,
+    /// For lua-block-lint rule: program and arguments of the Lua linter,
+    /// which reads the code on stdin (default: luacheck). For rego-policy
+    /// rule: program and arguments evaluating the policies (default:
+    /// `opa eval`)
+    pub command: Option<Vec<String>>,
+    /// For rego-policy rule: Rego files or directories to evaluate
+    pub policies: Option<Vec<String>>,
+    /// For rego-policy rule: query whose values are reported (default:
+    /// `data.nginx.deny`)
+    pub query: Option<String>,
+    /// Any other key: options declared by the rule's plugin (see
+    /// [`LintConfig::rule_options`])
+    #[serde(flatten)]
+    pub options: HashMap<String, serde_json::Value>,
 }
 
 fn default_true() -> bool {
     true
 }
 
+/// Severity override for a rule: "error" or "warning"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSeverity {
+    Error,
+    Warning,
+}
+
+impl From<RuleSeverity> for Severity {
+    fn from(severity: RuleSeverity) -> Self {
+        match severity {
+            RuleSeverity::Error => Severity::Error,
+            RuleSeverity::Warning => Severity::Warning,
+        }
+    }
+}
+
 impl LintConfig {
     /// Load configuration from a file
     pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
@@ -644,6 +1232,32 @@ impl LintConfig {
     pub const DISABLED_BY_DEFAULT: &'static [&'static str] = &[
         "gzip-not-enabled", // gzip is not always appropriate (CDN, CPU constraints, security)
         "missing-error-log", // error_log is typically set at top level in main config
+        "large-client-header-buffers-not-set", // advisory: only matters when clients send large tokens
+        "precompressed-assets-not-served",     // reads the document root from disk
+        "include-layering",                    // needs the project's layers in the config
+        "lua-block-lint",                      // runs an external program
+        "rego-policy",                         // runs opa on the configured policies
+        "proxy-pass-hardcoded-ip",             // service-discovery convention, not an error
+        "missing-security-headers",            // header policy is often set by the backend
+        "proxy-timeout-not-set",               // advisory: suitable timeouts depend on the backend
+        "suspicious-proxy-settings",           // advisory: thresholds depend on the application
+        "http2-keepalive-not-tuned",           // advisory: only matters for busy HTTP/2 clients
+        "plaintext-sensitive-upstream",        // backend network may be trusted
+        "limit-req-not-customized",            // advisory: nginx's defaults still work
+        "worker-settings-mistuned",            // advisory: tuning depends on the host
+        "client-max-body-size-mismatch",       // advisory: upload sizes depend on the application
+        "ssl-performance-not-tuned", // advisory: resumption and stapling are optimizations
+        "auth-basic-user-file-missing", // reads the filesystem, turned on by --check-paths
+    ];
+
+    /// Rules of [`DISABLED_BY_DEFAULT`](Self::DISABLED_BY_DEFAULT) that need
+    /// settings or tools of their own, so the `strict` preset, which turns
+    /// on the other ones, leaves them off too
+    pub const NEEDS_SETUP: &'static [&'static str] = &[
+        "include-layering",
+        "lua-block-lint",
+        "rego-policy",
+        "auth-basic-user-file-missing",
     ];
 
     /// Native lint rules implemented directly in the top-level crate
@@ -658,6 +1272,27 @@ impl LintConfig {
         "missing-semicolon",
         "indent",
         "include-path-exists",
+        "include-cycle",
+        "precompressed-assets-not-served",
+        "include-layering",
+        "lua-block-lint",
+        "rego-policy",
+        "auth-basic-user-file-missing",
+        "generated-region-modified",
+        "undefined-upstream",
+        "unused-upstream",
+        "upstream-keepalive-incomplete",
+        "proxy-pass-upstream-mismatch",
+        "missing-resolver",
+        "access-log-misconfigured",
+        "undefined-limit-zone",
+        "unused-limit-zone",
+        "proxy-cache-incomplete",
+        "undefined-variable",
+        "unused-variable",
+        "missing-server-name",
+        "https-redirect-misconfigured",
+        "duplicate-default-server",
     ];
 
     /// All rule names recognised by `nginx-lint config validate`.
@@ -678,6 +1313,27 @@ impl LintConfig {
         "missing-semicolon",
         "indent",
         "include-path-exists",
+        "include-cycle",
+        "precompressed-assets-not-served",
+        "include-layering",
+        "lua-block-lint",
+        "rego-policy",
+        "auth-basic-user-file-missing",
+        "generated-region-modified",
+        "undefined-upstream",
+        "unused-upstream",
+        "upstream-keepalive-incomplete",
+        "proxy-pass-upstream-mismatch",
+        "missing-resolver",
+        "access-log-misconfigured",
+        "undefined-limit-zone",
+        "unused-limit-zone",
+        "proxy-cache-incomplete",
+        "undefined-variable",
+        "unused-variable",
+        "missing-server-name",
+        "https-redirect-misconfigured",
+        "duplicate-default-server",
         // Builtin plugins — must match `BUILTIN_PLUGIN_NAMES` in
         // `src/plugin/mod.rs` (same order for easier review)
         "server-tokens-enabled",
@@ -708,14 +1364,48 @@ impl LintConfig {
         "client-max-body-size-not-set",
         "nginx-rift",
         "map-unnamed-capture",
+        "large-client-header-buffers-not-set",
+        "proxy-cache-key-missing-discriminators",
+        "add-header-in-if-in-location",
+        "ssl-certificate-missing",
+        "weak-ssl-key-exchange",
+        "proxy-pass-hardcoded-ip",
+        "missing-security-headers",
+        "redirect-to-http",
+        "allow-deny-order",
+        "proxy-timeout-not-set",
+        "unsafe-x-forwarded-for",
+        "permissive-cors",
+        "alias-traversal",
+        "suspicious-proxy-settings",
+        "charset-conflict",
+        "location-access-bypass",
+        "upstream-zone-too-small",
+        "http2-keepalive-not-tuned",
+        "auth-request-body-forwarded",
+        "plaintext-sensitive-upstream",
+        "limit-req-not-customized",
+        "worker-settings-mistuned",
+        "listen-conflict",
+        "suspicious-server-name",
+        "suspicious-rewrite",
+        "if-host-comparison",
+        "client-max-body-size-mismatch",
+        "ssl-performance-not-tuned",
+        "deprecated-directive",
+        "njs-module-reference",
     ];
 
     /// Check if a rule is enabled
+    ///
+    /// The `strict` preset runs every rule, so it also enables the rules
+    /// that are disabled by default, except those in
+    /// [`NEEDS_SETUP`](Self::NEEDS_SETUP).
     pub fn is_rule_enabled(&self, name: &str) -> bool {
-        self.rules
-            .get(name)
-            .map(|r| r.enabled)
-            .unwrap_or_else(|| !Self::DISABLED_BY_DEFAULT.contains(&name))
+        self.rules.get(name).map(|r| r.enabled).unwrap_or_else(|| {
+            !Self::DISABLED_BY_DEFAULT.contains(&name)
+                || (self.preset == Some(RulePreset::Strict) && !Self::NEEDS_SETUP.contains(&name))
+        })
     }
 
     /// Whether the user explicitly wrote a `[rules.<name>]` section for this
@@ -739,11 +1429,33 @@ impl LintConfig {
         self.target_nginx_version.as_deref()
     }
 
+    /// The `[environment]` settings
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
     /// Get the configuration for a specific rule
     pub fn get_rule_config(&self, name: &str) -> Option<&RuleConfig> {
         self.rules.get(name)
     }
 
+    /// Options of a rule that are not [`RuleConfig`] fields, e.g. the ones a
+    /// plugin declares in its spec. `None` when no such option is set.
+    pub fn rule_options(&self, name: &str) -> Option<&HashMap<String, serde_json::Value>> {
+        self.rules
+            .get(name)
+            .map(|rule| &rule.options)
+            .filter(|options| !options.is_empty())
+    }
+
+    /// Severity overrides configured with `severity = "..."`, by rule name
+    pub fn severity_overrides(&self) -> HashMap<String, Severity> {
+        self.rules
+            .iter()
+            .filter_map(|(name, rule)| Some((name.clone(), rule.severity?.into())))
+            .collect()
+    }
+
     /// Get the color mode setting
     pub fn color_mode(&self) -> ColorMode {
         self.color.ui
@@ -754,6 +1466,17 @@ impl LintConfig {
         &self.parser.block_directives
     }
 
+    /// Get additional raw block directives from config
+    ///
+    /// Set with `raw_block_directives` in the `[parser]` section, next to
+    /// `block_directives`. The blocks of these directives hold code of
+    /// another language: the parser keeps their content verbatim, and the
+    /// syntax rules that read the source skip it, as they do for the
+    /// built-in `*_by_lua_block`, `js_body` and `perl` blocks.
+    pub fn additional_raw_block_directives(&self) -> &[String] {
+        &self.parser.raw_block_directives
+    }
+
     /// Get include path mappings (applied in order to include patterns before resolving)
     pub fn include_path_mappings(&self) -> &[PathMapping] {
         &self.include.path_map
@@ -780,6 +1503,21 @@ impl LintConfig {
         self.cache_dir.as_deref()
     }
 
+    /// Get the trust policy set in the config file
+    pub fn trust_policy(&self) -> Option<TrustPolicy> {
+        self.trust_policy
+    }
+
+    /// Get the rule set the config starts from
+    pub fn preset(&self) -> Option<RulePreset> {
+        self.preset
+    }
+
+    /// Get the rules defined in the config
+    pub fn custom_rules(&self) -> &[CustomRuleConfig] {
+        &self.custom_rules
+    }
+
     /// Get additional contexts for invalid-directive-context rule
     pub fn additional_contexts(&self) -> Option<&HashMap<String, Vec<String>>> {
         self.rules
@@ -787,18 +1525,112 @@ impl LintConfig {
             .and_then(|r| r.additional_contexts.as_ref())
     }
 
-    /// Get excluded directives for directive-inheritance rule
-    pub fn directive_inheritance_excluded(&self) -> Option<&[String]> {
+    /// Get the docroot for precompressed-assets-not-served rule
+    pub fn precompressed_assets_docroot(&self) -> Option<&str> {
+        self.rules
+            .get("precompressed-assets-not-served")
+            .and_then(|r| r.docroot.as_deref())
+    }
+
+    /// Get the layers for include-layering rule
+    pub fn include_layering_layers(&self) -> &[LayerPolicy] {
+        self.rules
+            .get("include-layering")
+            .and_then(|r| r.layers.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Get the linter command for lua-block-lint rule
+    pub fn lua_block_lint_command(&self) -> Option<&[String]> {
+        self.rules
+            .get("lua-block-lint")
+            .and_then(|r| r.command.as_deref())
+    }
+
+    /// Get the policies evaluated by rego-policy rule
+    pub fn rego_policy_policies(&self) -> &[String] {
+        self.rules
+            .get("rego-policy")
+            .and_then(|r| r.policies.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Get the query for rego-policy rule
+    pub fn rego_policy_query(&self) -> Option<&str> {
+        self.rules
+            .get("rego-policy")
+            .and_then(|r| r.query.as_deref())
+    }
+
+    /// Get the command for rego-policy rule
+    pub fn rego_policy_command(&self) -> Option<&[String]> {
+        self.rules
+            .get("rego-policy")
+            .and_then(|r| r.command.as_deref())
+    }
+
+    /// Get the allowlist for proxy-pass-hardcoded-ip rule
+    pub fn proxy_pass_allowed_addresses(&self) -> Option<&[String]> {
+        self.rules
+            .get("proxy-pass-hardcoded-ip")
+            .and_then(|r| r.allowed_addresses.as_deref())
+    }
+
+    /// Get the maximum proxy_connect_timeout for proxy-timeout-not-set rule
+    pub fn proxy_max_connect_timeout(&self) -> Option<&str> {
+        self.rules
+            .get("proxy-timeout-not-set")
+            .and_then(|r| r.max_connect_timeout.as_deref())
+    }
+
+    /// Get the maximum proxy_read_timeout for proxy-timeout-not-set rule
+    pub fn proxy_max_read_timeout(&self) -> Option<&str> {
+        self.rules
+            .get("proxy-timeout-not-set")
+            .and_then(|r| r.max_read_timeout.as_deref())
+    }
+
+    /// Get the maximum proxy_read_timeout for suspicious-proxy-settings rule
+    pub fn suspicious_proxy_max_read_timeout(&self) -> Option<&str> {
+        self.rules
+            .get("suspicious-proxy-settings")
+            .and_then(|r| r.max_read_timeout.as_deref())
+    }
+
+    /// Get the deployment for unsafe-x-forwarded-for rule
+    pub fn x_forwarded_for_deployment(&self) -> Option<&str> {
+        self.rules
+            .get("unsafe-x-forwarded-for")
+            .and_then(|r| r.deployment.as_deref())
+    }
+
+    /// Get whether https-redirect-misconfigured requires a redirect server
+    /// for every HTTPS name
+    pub fn https_redirect_require_redirect_server(&self) -> Option<bool> {
+        self.rules
+            .get("https-redirect-misconfigured")
+            .and_then(|r| r.require_redirect_server)
+    }
+
+    /// Get the variables undefined-variable takes as defined
+    pub fn undefined_variable_known_variables(&self) -> Option<&[String]> {
+        self.rules
+            .get("undefined-variable")
+            .and_then(|r| r.known_variables.as_deref())
+    }
+
+    /// Get the variable prefixes undefined-variable takes as defined
+    pub fn undefined_variable_known_prefixes(&self) -> Option<&[String]> {
         self.rules
-            .get("directive-inheritance")
-            .and_then(|r| r.excluded_directives.as_deref())
+            .get("undefined-variable")
+            .and_then(|r| r.known_prefixes.as_deref())
     }
 
-    /// Get additional directives for directive-inheritance rule
-    pub fn directive_inheritance_additional(&self) -> Option<&[AdditionalDirective]> {
+    /// Get the required headers for missing-security-headers rule
+    pub fn required_security_headers(&self) -> Option<&[String]> {
         self.rules
-            .get("directive-inheritance")
-            .and_then(|r| r.additional_directives.as_deref())
+            .get("missing-security-headers")
+            .and_then(|r| r.required_headers.as_deref())
     }
 
     /// Validate a configuration file and return any errors
@@ -827,8 +1659,13 @@ impl LintConfig {
                 "color",
                 "parser",
                 "include",
+                "format",
                 "target_nginx_version",
+                "preset",
                 "cache_dir",
+                "trust_policy",
+                "environment",
+                "custom_rules",
             ]
             .into_iter()
             .collect();
@@ -863,7 +1700,9 @@ impl LintConfig {
 
             // Validate [parser] section
             if let Some(toml::Value::Table(parser)) = root.get("parser") {
-                let known_parser_keys: HashSet<&str> = ["block_directives"].into_iter().collect();
+                let known_parser_keys: HashSet<&str> = ["block_directives", "raw_block_directives"]
+                    .into_iter()
+                    .collect();
 
                 for key in parser.keys() {
                     if !known_parser_keys.contains(key.as_str()) {
@@ -894,9 +1733,92 @@ impl LintConfig {
                 }
             }
 
+            // Validate [format] section
+            if let Some(toml::Value::Table(format)) = root.get("format") {
+                let known_format_keys: HashSet<&str> = [
+                    "indent_width",
+                    "use_tabs",
+                    "max_line_length",
+                    "brace_style",
+                    "align_proxy_set_header",
+                ]
+                .into_iter()
+                .collect();
+
+                for key in format.keys() {
+                    if !known_format_keys.contains(key.as_str()) {
+                        let line = find_key_line(content, Some("format"), key);
+                        errors.push(ValidationError::UnknownField {
+                            path: format!("format.{}", key),
+                            line,
+                            suggestion: suggest_field(key, &known_format_keys),
+                        });
+                    }
+                }
+            }
+
+            // Validate [environment] section
+            if let Some(toml::Value::Table(environment)) = root.get("environment") {
+                let known_environment_keys: HashSet<&str> =
+                    ["os", "container", "behind_load_balancer"]
+                        .into_iter()
+                        .collect();
+
+                for key in environment.keys() {
+                    if !known_environment_keys.contains(key.as_str()) {
+                        let line = find_key_line(content, Some("environment"), key);
+                        errors.push(ValidationError::UnknownField {
+                            path: format!("environment.{}", key),
+                            line,
+                            suggestion: suggest_field(key, &known_environment_keys),
+                        });
+                    }
+                }
+            }
+
+            // Validate [[custom_rules]] tables
+            let mut custom_rule_names: Vec<String> = Vec::new();
+            if let Some(toml::Value::Array(custom_rules)) = root.get("custom_rules") {
+                let known_custom_rule_keys: HashSet<&str> = [
+                    "name",
+                    "directive",
+                    "args",
+                    "context",
+                    "require",
+                    "message",
+                    "severity",
+                    "fix",
+                ]
+                .into_iter()
+                .collect();
+
+                for rule in custom_rules {
+                    let toml::Value::Table(rule) = rule else {
+                        continue;
+                    };
+                    if let Some(toml::Value::String(name)) = rule.get("name") {
+                        custom_rule_names.push(name.clone());
+                    }
+                    for key in rule.keys() {
+                        if !known_custom_rule_keys.contains(key.as_str()) {
+                            let line = find_key_line(content, Some("custom_rules"), key);
+                            errors.push(ValidationError::UnknownField {
+                                path: format!("custom_rules.{}", key),
+                                line,
+                                suggestion: suggest_field(key, &known_custom_rule_keys),
+                            });
+                        }
+                    }
+                }
+            }
+
             // Validate [rules.*] sections
             if let Some(toml::Value::Table(rules)) = root.get("rules") {
-                let known_rules: HashSet<&str> = Self::KNOWN_RULE_NAMES.iter().copied().collect();
+                let known_rules: HashSet<&str> = Self::KNOWN_RULE_NAMES
+                    .iter()
+                    .copied()
+                    .chain(custom_rule_names.iter().map(String::as_str))
+                    .collect();
 
                 for (rule_name, rule_value) in rules {
                     if !known_rules.contains(rule_name.as_str()) {
@@ -955,9 +1877,10 @@ fn find_key_line(content: &str, section: Option<&str>, key: &str) -> Option<usiz
     for (i, line) in lines.iter().enumerate() {
         let trimmed = line.trim();
 
-        // Check for section header [section] or [section.subsection]
+        // Check for section header [section], [section.subsection] or
+        // [[array.of.tables]]
         if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            let section_name = &trimmed[1..trimmed.len() - 1];
+            let section_name = trimmed.trim_start_matches('[').trim_end_matches(']');
 
             // Check if this is a [rules.rule-name] style section
             let full_section = format!("{}.{}", target_section, key);
@@ -984,7 +1907,9 @@ fn find_key_line(content: &str, section: Option<&str>, key: &str) -> Option<usiz
 
 /// Get known options for a specific rule
 fn get_known_rule_options(rule_name: &str) -> HashSet<&'static str> {
-    let mut options: HashSet<&str> = ["enabled", "skip_version_check"].into_iter().collect();
+    let mut options: HashSet<&str> = ["enabled", "skip_version_check", "severity"]
+        .into_iter()
+        .collect();
 
     match rule_name {
         "indent" => {
@@ -1000,10 +1925,70 @@ fn get_known_rule_options(rule_name: &str) -> HashSet<&'static str> {
         "block-lines" => {
             options.insert("max_block_lines");
         }
+        "gzip-not-enabled" => {
+            options.insert("check_gzip_types");
+        }
         "directive-inheritance" => {
             options.insert("excluded_directives");
             options.insert("additional_directives");
         }
+        "precompressed-assets-not-served" => {
+            options.insert("docroot");
+        }
+        "include-layering" => {
+            options.insert("layers");
+        }
+        "lua-block-lint" => {
+            options.insert("command");
+        }
+        "rego-policy" => {
+            options.insert("policies");
+            options.insert("query");
+            options.insert("command");
+        }
+        "proxy-pass-hardcoded-ip" => {
+            options.insert("allowed_addresses");
+        }
+        "missing-security-headers" => {
+            options.insert("required_headers");
+        }
+        "proxy-timeout-not-set" => {
+            options.insert("max_connect_timeout");
+            options.insert("max_read_timeout");
+        }
+        "http2-keepalive-not-tuned" => {
+            options.insert("min_keepalive_requests");
+            options.insert("min_keepalive_time");
+        }
+        "unsafe-x-forwarded-for" => {
+            options.insert("deployment");
+        }
+        "https-redirect-misconfigured" => {
+            options.insert("require_redirect_server");
+        }
+        "undefined-variable" => {
+            options.insert("known_variables");
+            options.insert("known_prefixes");
+        }
+        "plaintext-sensitive-upstream" => {
+            options.insert("sensitive_paths");
+        }
+        "worker-settings-mistuned" => {
+            options.insert("max_worker_processes");
+        }
+        "suspicious-proxy-settings" => {
+            options.insert("max_read_timeout");
+        }
+        "client-max-body-size-mismatch" => {
+            options.insert("min_upload_size");
+            options.insert("upload_paths");
+            options.insert("check_overrides");
+        }
+        "ssl-performance-not-tuned" => {
+            options.insert("session_cache");
+            options.insert("session_timeout");
+            options.insert("require_stapling");
+        }
         _ => {}
     }
 
@@ -1220,6 +2205,12 @@ mod tests {
         // These rules should be disabled by default
         assert!(!config.is_rule_enabled("gzip-not-enabled"));
         assert!(!config.is_rule_enabled("missing-error-log"));
+        assert!(!config.is_rule_enabled("large-client-header-buffers-not-set"));
+        assert!(!config.is_rule_enabled("precompressed-assets-not-served"));
+        assert!(!config.is_rule_enabled("proxy-pass-hardcoded-ip"));
+        assert!(!config.is_rule_enabled("missing-security-headers"));
+        assert!(!config.is_rule_enabled("proxy-timeout-not-set"));
+        assert!(!config.is_rule_enabled("suspicious-proxy-settings"));
         // Other rules should still be enabled by default
         assert!(config.is_rule_enabled("server-tokens-enabled"));
     }
@@ -1464,6 +2455,55 @@ unknown_key = "value"
         }
     }
 
+    #[test]
+    fn test_format_config_default_style() {
+        let config = LintConfig::default();
+        assert_eq!(config.format.style(), FormatStyle::default());
+    }
+
+    #[test]
+    fn test_format_config_parsed() {
+        let toml_content = r#"
+[format]
+indent_width = 2
+use_tabs = true
+max_line_length = 100
+brace_style = "next-line"
+align_proxy_set_header = true
+"#;
+        let config = LintConfig::parse(toml_content).unwrap();
+        let style = config.format.style();
+        assert_eq!(style.indent_width, 2);
+        assert!(style.use_tabs);
+        assert_eq!(style.max_line_length, Some(100));
+        assert_eq!(style.brace_style, BraceStyle::NextLine);
+        assert!(style.align_proxy_set_header);
+    }
+
+    #[test]
+    fn test_format_config_rejects_unknown_brace_style() {
+        let result = LintConfig::parse("[format]\nbrace_style = \"k&r\"\n");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_format_validation_rejects_unknown_field() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "[format]\nuse_tab = true\n").unwrap();
+
+        let errors = LintConfig::validate_file(file.path()).unwrap();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            ValidationError::UnknownField {
+                path, suggestion, ..
+            } => {
+                assert_eq!(path, "format.use_tab");
+                assert_eq!(suggestion.as_deref(), Some("use_tabs"));
+            }
+            other => panic!("expected UnknownField, got: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_include_prefix_none_by_default() {
         let config = LintConfig::default();
@@ -1495,6 +2535,97 @@ unknown_key = "value"
         );
     }
 
+    #[test]
+    fn test_strict_preset_enables_disabled_rules() {
+        let config = LintConfig::parse(
+            "preset = \"strict\"\n\n[rules.proxy-timeout-not-set]\nenabled = false\n",
+        )
+        .unwrap();
+        assert!(config.is_rule_enabled("missing-security-headers"));
+        assert!(!config.is_rule_enabled("proxy-timeout-not-set"));
+        assert!(!config.is_rule_enabled("lua-block-lint"));
+
+        let config = LintConfig::parse(r#"preset = "recommended""#).unwrap();
+        assert!(!config.is_rule_enabled("missing-security-headers"));
+        for name in LintConfig::NEEDS_SETUP {
+            assert!(LintConfig::DISABLED_BY_DEFAULT.contains(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_preset_parsed() {
+        assert_eq!(LintConfig::parse("").unwrap().preset(), None);
+        let config = LintConfig::parse(r#"preset = "recommended""#).unwrap();
+        assert_eq!(config.preset(), Some(RulePreset::Recommended));
+        assert!(LintConfig::parse(r#"preset = "ingress-nginx""#).is_err());
+
+        assert!(RulePreset::Minimal.includes(Some(RulePreset::Minimal)));
+        assert!(!RulePreset::Minimal.includes(Some(RulePreset::Recommended)));
+        assert!(!RulePreset::Recommended.includes(None));
+        assert!(RulePreset::Strict.includes(None));
+        assert_eq!(
+            RulePreset::from_name("recommended"),
+            Some(RulePreset::Recommended)
+        );
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "preset = \"minimal\"").unwrap();
+        assert!(LintConfig::validate_file(file.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_custom_rules_parsed() {
+        assert!(LintConfig::parse("").unwrap().custom_rules().is_empty());
+        let config = LintConfig::parse(
+            r#"
+[[custom_rules]]
+name = "internal-location-allow"
+directive = "location"
+args = "^/internal"
+context = ["server"]
+require = { directive = "allow", args = "^10\\.0\\.0\\.0/8$" }
+message = "location {args} must allow 10.0.0.0/8"
+severity = "error"
+fix = "allow 10.0.0.0/8;"
+"#,
+        )
+        .unwrap();
+        let rule = &config.custom_rules()[0];
+        assert_eq!(rule.name, "internal-location-allow");
+        assert_eq!(rule.context, ["server"]);
+        assert_eq!(rule.require.as_ref().unwrap().directive, "allow");
+        assert_eq!(
+            rule.require.as_ref().unwrap().args.as_deref(),
+            Some("^10\\.0\\.0\\.0/8$")
+        );
+        assert_eq!(rule.severity, Some(RuleSeverity::Error));
+        assert!(LintConfig::parse("[[custom_rules]]\nname = \"x\"\n").is_err());
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            "[[custom_rules]]\nname = \"x\"\ndirective = \"d\"\nmesage = \"m\"\n\n[rules.x]\nseverity = \"error\"\n"
+        )
+        .unwrap();
+        let errors = LintConfig::validate_file(file.path()).unwrap();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(
+            matches!(&errors[0], ValidationError::UnknownField { path, line: Some(4), .. } if path == "custom_rules.mesage")
+        );
+    }
+
+    #[test]
+    fn test_trust_policy_parsed() {
+        assert_eq!(LintConfig::parse("").unwrap().trust_policy(), None);
+        let config = LintConfig::parse(r#"trust_policy = "untrusted""#).unwrap();
+        assert_eq!(config.trust_policy(), Some(TrustPolicy::Untrusted));
+        assert!(LintConfig::parse(r#"trust_policy = "sandboxed""#).is_err());
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "trust_policy = \"trusted\"").unwrap();
+        assert!(LintConfig::validate_file(file.path()).unwrap().is_empty());
+    }
+
     #[test]
     fn test_include_prefix_parsed() {
         let toml_content = r#"
@@ -1553,6 +2684,7 @@ prefix = "/etc/nginx"
         assert!(props.contains_key("color"), "missing 'color' property");
         assert!(props.contains_key("parser"), "missing 'parser' property");
         assert!(props.contains_key("include"), "missing 'include' property");
+        assert!(props.contains_key("format"), "missing 'format' property");
     }
 
     /// Regression test for https://github.com/walf443/nginx-lint/issues/172:
@@ -1674,14 +2806,23 @@ prefix = "/etc/nginx"
         let expected_fields = [
             "enabled",
             "skip_version_check",
+            "severity",
             "indent_size",
             "allowed_protocols",
             "weak_ciphers",
             "required_exclusions",
             "additional_contexts",
             "max_block_lines",
-            "excluded_directives",
-            "additional_directives",
+            "docroot",
+            "allowed_addresses",
+            "required_headers",
+            "max_connect_timeout",
+            "max_read_timeout",
+            "deployment",
+            "layers",
+            "require_redirect_server",
+            "known_variables",
+            "known_prefixes",
         ];
 
         for field in &expected_fields {
@@ -1707,6 +2848,48 @@ target_nginx_version = "1.31.0"
         assert!(config.target_nginx_version().is_none());
     }
 
+    #[test]
+    fn test_environment_parsed() {
+        let toml_content = r#"
+[environment]
+os = "linux"
+behind_load_balancer = true
+"#;
+        let config = LintConfig::parse(toml_content).unwrap();
+        let environment = config.environment();
+        assert_eq!(environment.os.as_deref(), Some("linux"));
+        assert_eq!(environment.container, None);
+        assert_eq!(environment.behind_load_balancer, Some(true));
+        assert!(!environment.is_empty());
+
+        assert!(LintConfig::default().environment().is_empty());
+        assert!(LintConfig::parse("[environment]\ncontainer = \"yes\"\n").is_err());
+    }
+
+    #[test]
+    fn test_validator_environment_keys() {
+        let toml_content = r#"
+[environment]
+os = "linux"
+container = true
+behind_loadbalancer = true
+"#;
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", toml_content).unwrap();
+
+        let errors = LintConfig::validate_file(file.path()).unwrap();
+        assert_eq!(errors.len(), 1, "got: {errors:?}");
+        match &errors[0] {
+            ValidationError::UnknownField {
+                path, suggestion, ..
+            } => {
+                assert_eq!(path, "environment.behind_loadbalancer");
+                assert_eq!(suggestion.as_deref(), Some("behind_load_balancer"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
     #[test]
     fn test_skip_version_check_per_rule() {
         let toml_content = r#"
@@ -1719,6 +2902,51 @@ skip_version_check = true
         assert!(!config.rule_skip_version_check("server-tokens-enabled"));
     }
 
+    #[test]
+    fn test_severity_overrides() {
+        let toml_content = r#"
+[rules.deprecated-ssl-protocol]
+severity = "error"
+
+[rules.indent]
+severity = "warning"
+
+[rules.server-tokens-enabled]
+enabled = true
+"#;
+        let config = LintConfig::parse(toml_content).unwrap();
+        let overrides = config.severity_overrides();
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(
+            overrides.get("deprecated-ssl-protocol"),
+            Some(&Severity::Error)
+        );
+        assert_eq!(overrides.get("indent"), Some(&Severity::Warning));
+
+        assert!(LintConfig::parse("[rules.indent]\nseverity = \"info\"\n").is_err());
+    }
+
+    #[test]
+    fn test_rule_options() {
+        let toml_content = r#"
+[rules.my-plugin-rule]
+max_depth = 3
+names = ["a", "b"]
+
+[rules.indent]
+indent_size = 2
+"#;
+        let config = LintConfig::parse(toml_content).unwrap();
+        let options = config.rule_options("my-plugin-rule").unwrap();
+        assert_eq!(options.len(), 2);
+        assert_eq!(options["max_depth"], serde_json::json!(3));
+        assert_eq!(options["names"], serde_json::json!(["a", "b"]));
+
+        // RuleConfig fields are not rule options
+        assert!(config.rule_options("indent").is_none());
+        assert!(config.rule_options("block-lines").is_none());
+    }
+
     #[test]
     fn test_rule_explicitly_configured() {
         let toml_content = r#"
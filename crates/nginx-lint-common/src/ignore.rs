@@ -18,10 +18,23 @@
 //!
 //! - `rule-name`: Required. The name of the rule to ignore
 //! - `reason`: Required. A reason explaining why the rule is ignored
+//!
+//! ## Generated regions
+//! ```nginx
+//! # nginx-lint:generated-by ansible checksum=crc32:3b50ca2d
+//! server_tokens off;
+//! # nginx-lint:generated-end
+//! ```
+//!
+//! Style rules are not reported inside a generated region. The region ends
+//! at `nginx-lint:generated-end`, or at the end of the file. The optional
+//! checksum is checked by the `generated-region-modified` rule, see
+//! [`GeneratedRegion`].
 
 use std::collections::{HashMap, HashSet};
 
 use crate::linter::{Fix, LintError, Severity, compute_line_starts};
+use crate::parser::context::IncludeContext;
 
 /// A warning generated from parsing ignore comments
 #[derive(Debug, Clone)]
@@ -65,6 +78,9 @@ pub struct IgnoreTracker {
     /// these rules are excluded from "unused" warnings so the caller can
     /// disable rules per-invocation without churning the user's config.
     dormant_rules: HashSet<String>,
+    /// Regions marked with `nginx-lint:generated-by`, where style rules
+    /// are not reported
+    generated_regions: Vec<GeneratedRegion>,
 }
 
 impl IgnoreTracker {
@@ -81,6 +97,13 @@ impl IgnoreTracker {
             .unwrap_or(false)
     }
 
+    /// Check if a line is inside a generated region
+    pub fn is_generated(&self, line: usize) -> bool {
+        self.generated_regions
+            .iter()
+            .any(|region| region.contains(line))
+    }
+
     /// Build an ignore tracker from content, returning any warnings
     pub fn from_content(content: &str) -> (Self, Vec<IgnoreWarning>) {
         Self::from_content_with_rules(content, None)
@@ -221,6 +244,10 @@ impl IgnoreTracker {
             }
         }
 
+        let (regions, region_warnings) = parse_generated_regions(content);
+        tracker.generated_regions = regions;
+        warnings.extend(region_warnings);
+
         (tracker, warnings)
     }
 
@@ -400,6 +427,14 @@ pub fn filter_errors(errors: Vec<LintError>, tracker: &mut IgnoreTracker) -> Fil
             ignored_count += 1;
             continue;
         }
+        // Generated regions are rewritten by their tool, so style is its concern
+        if let Some(line) = error.line
+            && error.category == "style"
+            && tracker.is_generated(line)
+        {
+            ignored_count += 1;
+            continue;
+        }
         remaining.push(error);
     }
 
@@ -440,17 +475,18 @@ const CONTEXT_PREFIX: &str = "nginx-lint:context";
 /// Parse context comment from file content
 ///
 /// Looks for `# nginx-lint:context http,server` in the first few lines of the file.
-/// Returns the context as a vector of block names, or None if no context comment found.
+/// Frames may carry block arguments (`location=~ ^/api`), see
+/// [`IncludeContext::parse`]. Returns None if no context comment found.
 ///
 /// # Example
 /// ```
 /// use nginx_lint_common::ignore::parse_context_comment;
 ///
 /// let content = "# nginx-lint:context http,server\nserver { listen 80; }";
-/// let context = parse_context_comment(content);
-/// assert_eq!(context, Some(vec!["http".to_string(), "server".to_string()]));
+/// let context = parse_context_comment(content).unwrap();
+/// assert_eq!(context.to_names(), ["http", "server"]);
 /// ```
-pub fn parse_context_comment(content: &str) -> Option<Vec<String>> {
+pub fn parse_context_comment(content: &str) -> Option<IncludeContext> {
     // Only check first 10 lines for context comment
     for line in content.lines().take(10) {
         let trimmed = line.trim();
@@ -475,11 +511,7 @@ pub fn parse_context_comment(content: &str) -> Option<Vec<String>> {
                 return None;
             }
 
-            let context: Vec<String> = context_str
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
+            let context = IncludeContext::parse(context_str);
 
             if context.is_empty() {
                 return None;
@@ -492,6 +524,208 @@ pub fn parse_context_comment(content: &str) -> Option<Vec<String>> {
     None
 }
 
+/// Prefix for the comment starting a generated region
+const GENERATED_BY_PREFIX: &str = "nginx-lint:generated-by";
+
+/// Prefix for the comment ending a generated region
+const GENERATED_END_PREFIX: &str = "nginx-lint:generated-end";
+
+/// A region of a file written by a tool, marked with comments:
+///
+/// ```nginx
+/// # nginx-lint:generated-by <tool> [checksum=crc32:<8 hex digits>]
+/// ...
+/// # nginx-lint:generated-end
+/// ```
+///
+/// The checksum is the CRC-32 (as computed by zlib) of the lines between the
+/// two markers, each ending in `\n`. Without `generated-end`, the region
+/// runs to the end of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedRegion {
+    /// Tool named in the `generated-by` comment
+    pub tool: String,
+    /// Line of the `generated-by` comment
+    pub start_line: usize,
+    /// Line of the `generated-end` comment, or the last line of the file
+    pub end_line: usize,
+    /// Checksum recorded in the `generated-by` comment
+    pub checksum: Option<u32>,
+    /// Checksum of the region's current content
+    pub actual_checksum: u32,
+}
+
+impl GeneratedRegion {
+    /// Whether `line` is inside the region (markers included)
+    pub fn contains(&self, line: usize) -> bool {
+        (self.start_line..=self.end_line).contains(&line)
+    }
+
+    /// Whether the region has a checksum that no longer matches its content
+    pub fn is_modified(&self) -> bool {
+        self.checksum
+            .is_some_and(|checksum| checksum != self.actual_checksum)
+    }
+}
+
+/// Format a checksum as written in `generated-by` comments
+pub fn format_checksum(checksum: u32) -> String {
+    format!("crc32:{:08x}", checksum)
+}
+
+/// CRC-32 (IEEE 802.3, as computed by zlib)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// A `generated-by` comment that has not been closed yet
+struct OpenRegion {
+    tool: String,
+    start_line: usize,
+    checksum: Option<u32>,
+}
+
+impl OpenRegion {
+    fn close(self, end_line: usize, body: &[&str]) -> GeneratedRegion {
+        let mut text = String::new();
+        for line in body {
+            text.push_str(line);
+            text.push('\n');
+        }
+        GeneratedRegion {
+            tool: self.tool,
+            start_line: self.start_line,
+            end_line,
+            checksum: self.checksum,
+            actual_checksum: crc32(text.as_bytes()),
+        }
+    }
+}
+
+/// Parse the `generated-by`/`generated-end` comments of a file
+///
+/// Malformed markers are returned as warnings and do not start or end a
+/// region.
+///
+/// # Example
+/// ```
+/// use nginx_lint_common::ignore::parse_generated_regions;
+///
+/// let content = "# nginx-lint:generated-by ansible\nserver_tokens off;\n# nginx-lint:generated-end\n";
+/// let (regions, warnings) = parse_generated_regions(content);
+/// assert!(warnings.is_empty());
+/// assert_eq!(regions[0].tool, "ansible");
+/// assert_eq!((regions[0].start_line, regions[0].end_line), (1, 3));
+/// ```
+pub fn parse_generated_regions(content: &str) -> (Vec<GeneratedRegion>, Vec<IgnoreWarning>) {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut regions = Vec::new();
+    let mut warnings = Vec::new();
+    let mut open: Option<OpenRegion> = None;
+
+    let mut warn = |line: usize, message: String| {
+        warnings.push(IgnoreWarning {
+            line,
+            message,
+            fixes: Vec::new(),
+        });
+    };
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        let line_number = line_idx + 1;
+        let Some(comment) = line.trim().strip_prefix('#') else {
+            continue;
+        };
+        let comment = comment.trim_start_matches('#').trim();
+
+        if let Some(rest) = comment.strip_prefix(GENERATED_BY_PREFIX) {
+            let mut parts = rest.split_whitespace();
+            let Some(tool) = parts.next() else {
+                warn(
+                    line_number,
+                    "nginx-lint:generated-by requires a tool name".to_string(),
+                );
+                continue;
+            };
+            let mut checksum = None;
+            let mut valid = true;
+            for option in parts {
+                match option.strip_prefix("checksum=") {
+                    Some(value) => match value
+                        .strip_prefix("crc32:")
+                        .filter(|hex| hex.len() == 8)
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    {
+                        Some(value) => checksum = Some(value),
+                        None => {
+                            warn(
+                                line_number,
+                                format!(
+                                    "invalid checksum '{}' in nginx-lint:generated-by comment (expected crc32:<8 hex digits>)",
+                                    value
+                                ),
+                            );
+                            valid = false;
+                        }
+                    },
+                    None => {
+                        warn(
+                            line_number,
+                            format!(
+                                "unknown option '{}' in nginx-lint:generated-by comment",
+                                option
+                            ),
+                        );
+                        valid = false;
+                    }
+                }
+            }
+            if !valid {
+                continue;
+            }
+            if let Some(region) = open.take() {
+                warn(
+                    region.start_line,
+                    "nginx-lint:generated-by region is not closed before the next one; add nginx-lint:generated-end".to_string(),
+                );
+                let body = &lines[region.start_line..line_idx];
+                regions.push(region.close(line_idx, body));
+            }
+            open = Some(OpenRegion {
+                tool: tool.to_string(),
+                start_line: line_number,
+                checksum,
+            });
+        } else if comment.starts_with(GENERATED_END_PREFIX) {
+            match open.take() {
+                Some(region) => {
+                    let body = &lines[region.start_line..line_idx];
+                    regions.push(region.close(line_number, body));
+                }
+                None => warn(
+                    line_number,
+                    "nginx-lint:generated-end without a preceding nginx-lint:generated-by"
+                        .to_string(),
+                ),
+            }
+        }
+    }
+
+    if let Some(region) = open {
+        let body = &lines[region.start_line..];
+        regions.push(region.close(lines.len(), body));
+    }
+
+    (regions, warnings)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1022,41 +1256,35 @@ server_tokens on;
     fn test_parse_context_comment_simple() {
         let content = "# nginx-lint:context http\nserver { listen 80; }";
         let context = parse_context_comment(content);
-        assert_eq!(context, Some(vec!["http".to_string()]));
+        assert_eq!(context, Some(vec!["http"].into()));
     }
 
     #[test]
     fn test_parse_context_comment_multiple() {
         let content = "# nginx-lint:context http,server\nlocation / { }";
         let context = parse_context_comment(content);
-        assert_eq!(
-            context,
-            Some(vec!["http".to_string(), "server".to_string()])
-        );
+        assert_eq!(context, Some(vec!["http", "server"].into()));
     }
 
     #[test]
     fn test_parse_context_comment_with_spaces() {
         let content = "# nginx-lint:context http, server\nlocation / { }";
         let context = parse_context_comment(content);
-        assert_eq!(
-            context,
-            Some(vec!["http".to_string(), "server".to_string()])
-        );
+        assert_eq!(context, Some(vec!["http", "server"].into()));
     }
 
     #[test]
     fn test_parse_context_comment_after_empty_lines() {
         let content = "\n\n# nginx-lint:context http\nserver { }";
         let context = parse_context_comment(content);
-        assert_eq!(context, Some(vec!["http".to_string()]));
+        assert_eq!(context, Some(vec!["http"].into()));
     }
 
     #[test]
     fn test_parse_context_comment_after_other_comments() {
         let content = "# Some description\n# nginx-lint:context http\nserver { }";
         let context = parse_context_comment(content);
-        assert_eq!(context, Some(vec!["http".to_string()]));
+        assert_eq!(context, Some(vec!["http"].into()));
     }
 
     #[test]
@@ -1074,10 +1302,131 @@ server_tokens on;
         assert_eq!(context, None);
     }
 
+    #[test]
+    fn test_parse_context_comment_with_block_args() {
+        let content = "# nginx-lint:context http,server,location=/static/\nalias /srv/static/;";
+        let context = parse_context_comment(content).unwrap();
+        assert_eq!(context.to_names(), ["http", "server", "location"]);
+        assert_eq!(context.last().unwrap().args, ["/static/"]);
+    }
+
     #[test]
     fn test_parse_context_comment_empty_value() {
         let content = "# nginx-lint:context\nserver { }";
         let context = parse_context_comment(content);
         assert_eq!(context, None);
     }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_parse_generated_regions() {
+        let content = "\
+events {}
+# nginx-lint:generated-by ansible checksum=crc32:3b50ca2d
+server_tokens off;
+# nginx-lint:generated-end
+# nginx-lint:generated-by terraform
+gzip on;
+";
+        let (regions, warnings) = parse_generated_regions(content);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].tool, "ansible");
+        assert_eq!((regions[0].start_line, regions[0].end_line), (2, 4));
+        assert_eq!(regions[0].checksum, Some(0x3b50_ca2d));
+        assert!(!regions[0].is_modified());
+        // Runs to the end of the file
+        assert_eq!(regions[1].tool, "terraform");
+        assert_eq!((regions[1].start_line, regions[1].end_line), (5, 6));
+        assert_eq!(regions[1].checksum, None);
+        assert!(!regions[1].is_modified());
+    }
+
+    #[test]
+    fn test_generated_region_modified() {
+        let content = "# nginx-lint:generated-by ansible checksum=crc32:3b50ca2d\nserver_tokens on;\n# nginx-lint:generated-end\n";
+        let (regions, _) = parse_generated_regions(content);
+        assert!(regions[0].is_modified());
+        assert_eq!(
+            format_checksum(regions[0].actual_checksum),
+            "crc32:6158e797"
+        );
+    }
+
+    #[test]
+    fn test_parse_generated_regions_malformed() {
+        let content = "\
+# nginx-lint:generated-by
+# nginx-lint:generated-by ansible checksum=sha256:abcd
+# nginx-lint:generated-by ansible owner=ops
+# nginx-lint:generated-end
+# nginx-lint:generated-by chef
+# nginx-lint:generated-by puppet
+";
+        let (regions, warnings) = parse_generated_regions(content);
+        let messages: Vec<(usize, &str)> = warnings
+            .iter()
+            .map(|w| (w.line, w.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (1, "nginx-lint:generated-by requires a tool name"),
+                (
+                    2,
+                    "invalid checksum 'sha256:abcd' in nginx-lint:generated-by comment (expected crc32:<8 hex digits>)"
+                ),
+                (
+                    3,
+                    "unknown option 'owner=ops' in nginx-lint:generated-by comment"
+                ),
+                (
+                    4,
+                    "nginx-lint:generated-end without a preceding nginx-lint:generated-by"
+                ),
+                (
+                    5,
+                    "nginx-lint:generated-by region is not closed before the next one; add nginx-lint:generated-end"
+                ),
+            ]
+        );
+        assert_eq!(regions.len(), 2);
+        assert_eq!((regions[0].start_line, regions[0].end_line), (5, 5));
+        assert_eq!((regions[1].start_line, regions[1].end_line), (6, 6));
+    }
+
+    #[test]
+    fn test_filter_style_errors_in_generated_region() {
+        let content = "# nginx-lint:generated-by ansible\n  server_tokens on;\n# nginx-lint:generated-end\n  gzip on;\n";
+        let (mut tracker, warnings) = IgnoreTracker::from_content(content);
+        assert!(warnings.is_empty());
+
+        let errors = vec![
+            LintError::new("indent", "style", "bad indent", Severity::Warning).with_location(2, 1),
+            LintError::new(
+                "server-tokens-enabled",
+                "security",
+                "server_tokens on",
+                Severity::Warning,
+            )
+            .with_location(2, 3),
+            LintError::new("indent", "style", "bad indent", Severity::Warning).with_location(4, 1),
+        ];
+        let result = filter_errors(errors, &mut tracker);
+        assert_eq!(result.ignored_count, 1);
+        let remaining: Vec<(&str, Option<usize>)> = result
+            .errors
+            .iter()
+            .map(|e| (e.rule.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            remaining,
+            [("server-tokens-enabled", Some(2)), ("indent", Some(4))]
+        );
+    }
 }
@@ -10,6 +10,10 @@
 //! - [`config`] — Configuration loaded from `.nginx-lint.toml` ([`LintConfig`], [`ValidationError`])
 //! - [`ignore`] — `# nginx-lint-ignore` comment parsing and error filtering
 //! - [`docs`] — Rule documentation extraction ([`RuleDoc`])
+//! - [`variables`] — Variable definition/use analysis ([`variables::VariableTable`])
+//! - [`listen`] — Normalizing the addresses of `listen` directives
+//! - [`njs`] — njs (`js_*`) imports, handlers and module paths
+//! - [`paths`] — Resolving the files directives refer to against the prefix
 //!
 //! # Quick reference
 //!
@@ -31,23 +35,32 @@ pub mod config;
 pub mod docs;
 pub mod ignore;
 pub mod linter;
+pub mod listen;
 pub mod nginx_version;
+pub mod njs;
+pub mod paths;
+pub mod variables;
 
 // Re-export parser crate
 pub use nginx_lint_parser as parser;
 
 // Re-export commonly used types
 pub use config::{
-    Color, ColorConfig, ColorMode, IncludeConfig, LintConfig, PathMapping, ValidationError,
+    Color, ColorConfig, ColorMode, Environment, FormatConfig, IncludeConfig, LintConfig,
+    PathMapping, RulePreset, TrustPolicy, ValidationError,
 };
 pub use docs::{RuleDoc, RuleDocOwned};
 pub use ignore::{
     FilterResult, IgnoreTracker, IgnoreWarning, filter_errors, parse_context_comment,
 };
 pub use linter::{
-    Fix, FixApplyResult, LintError, LintRule, Linter, RULE_CATEGORIES, Severity,
-    apply_fixes_to_content, apply_fixes_to_content_detailed, compute_line_starts,
-    normalize_line_fix,
+    Applicability, Fix, FixAlternative, FixApplyResult, FixConflict, LintError, LintRule, Linter,
+    PLUGIN_ERROR_RULE, RULE_CATEGORIES, Severity, apply_fixes, apply_fixes_to_content,
+    apply_fixes_to_content_detailed, compare_diagnostics, compute_line_starts, normalize_line_fix,
+    sort_diagnostics,
+};
+pub use nginx_lint_parser::{
+    parse_config, parse_config_with_raw_blocks, parse_string, parse_string_with_errors,
+    parse_string_with_errors_and_raw_blocks,
 };
-pub use nginx_lint_parser::{parse_config, parse_string, parse_string_with_errors};
 pub use nginx_version::{NginxVersion, NginxVersionParseError, format_range, is_in_range};
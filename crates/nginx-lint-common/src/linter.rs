@@ -10,7 +10,8 @@
 //! - [`Linter`] — collects rules and runs them against a parsed config
 
 use crate::parser::ast::Config;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
 /// Display-ordered list of rule categories for UI output.
@@ -21,16 +22,24 @@ pub const RULE_CATEGORIES: &[&str] = &[
     "syntax",
     "security",
     "best-practices",
+    "performance",
     "deprecation",
+    "interop",
 ];
 
+/// Rule id of the diagnostic reported when a rule or plugin fails to run
+/// (a WASM trap or timeout, or a panic in native rule code).
+///
+/// See [`LintError::plugin_error`].
+pub const PLUGIN_ERROR_RULE: &str = "plugin-error";
+
 /// Severity level of a lint diagnostic.
 ///
 /// # Variants
 ///
 /// - `Error` — the configuration is broken or has a critical security issue.
 /// - `Warning` — the configuration works but uses discouraged settings or could be improved.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Severity {
     /// The configuration will not work correctly, or there is a critical security issue.
     Error,
@@ -47,8 +56,31 @@ impl std::fmt::Display for Severity {
     }
 }
 
+/// How safe a [`Fix`] is to apply without review.
+///
+/// `--fix` applies only [`MachineApplicable`](Self::MachineApplicable)
+/// fixes; `--fix-suggestions` applies the others as well.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Applicability {
+    /// The fix is certainly what the user wants (e.g. `server_tokens off`).
+    #[default]
+    MachineApplicable,
+    /// The fix resolves the diagnostic but may change behavior the user
+    /// relies on (e.g. copying inherited directives into a block).
+    MaybeIncorrect,
+    /// The fix contains placeholders the user has to fill in.
+    HasPlaceholders,
+}
+
+impl Applicability {
+    /// Whether `--fix` applies the fix without `--fix-suggestions`
+    pub fn is_machine_applicable(&self) -> bool {
+        *self == Applicability::MachineApplicable
+    }
+}
+
 /// Represents a fix that can be applied to resolve a lint error
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fix {
     /// Line number where the fix should be applied (1-indexed)
     pub line: usize,
@@ -57,17 +89,20 @@ pub struct Fix {
     /// The new text to insert (empty string with old_text=None means delete)
     pub new_text: String,
     /// Whether to delete the entire line
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub delete_line: bool,
     /// Whether to insert new_text as a new line after the specified line
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub insert_after: bool,
     /// Start byte offset for range-based fix (0-indexed, inclusive)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub start_offset: Option<usize>,
     /// End byte offset for range-based fix (0-indexed, exclusive)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub end_offset: Option<usize>,
+    /// How safe the fix is to apply without review
+    #[serde(default, skip_serializing_if = "Applicability::is_machine_applicable")]
+    pub applicability: Applicability,
 }
 
 impl Fix {
@@ -82,6 +117,7 @@ impl Fix {
             insert_after: false,
             start_offset: None,
             end_offset: None,
+            applicability: Applicability::MachineApplicable,
         }
     }
 
@@ -96,6 +132,7 @@ impl Fix {
             insert_after: false,
             start_offset: None,
             end_offset: None,
+            applicability: Applicability::MachineApplicable,
         }
     }
 
@@ -110,6 +147,7 @@ impl Fix {
             insert_after: false,
             start_offset: None,
             end_offset: None,
+            applicability: Applicability::MachineApplicable,
         }
     }
 
@@ -124,6 +162,7 @@ impl Fix {
             insert_after: true,
             start_offset: None,
             end_offset: None,
+            applicability: Applicability::MachineApplicable,
         }
     }
 
@@ -139,6 +178,7 @@ impl Fix {
             insert_after: false,
             start_offset: Some(start_offset),
             end_offset: Some(end_offset),
+            applicability: Applicability::MachineApplicable,
         }
     }
 
@@ -146,13 +186,44 @@ impl Fix {
     pub fn is_range_based(&self) -> bool {
         self.start_offset.is_some() && self.end_offset.is_some()
     }
+
+    /// Set how safe the fix is to apply without review.
+    pub fn with_applicability(mut self, applicability: Applicability) -> Self {
+        self.applicability = applicability;
+        self
+    }
+}
+
+/// Another way to resolve a diagnostic than its default fixes.
+///
+/// A set of [`Fix`] edits that are applied together, with a label naming
+/// the strategy (e.g. `"keep per-location roots"`). `--fix` applies the
+/// default fixes of a diagnostic; `--fix-alternative <label>` picks the
+/// alternative with that label instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixAlternative {
+    /// Short description of the strategy, also used to select it
+    pub label: String,
+    /// The edits making up the strategy
+    pub fixes: Vec<Fix>,
+}
+
+impl FixAlternative {
+    /// Create an alternative applying `fixes` together.
+    pub fn new(label: &str, fixes: Vec<Fix>) -> Self {
+        Self {
+            label: label.to_string(),
+            fixes,
+        }
+    }
 }
 
 /// A single lint diagnostic produced by a rule.
 ///
 /// Every [`LintRule::check`] call returns a `Vec<LintError>`. Each error
 /// carries the rule name, category, a human-readable message, severity, an
-/// optional source location, and zero or more [`Fix`] proposals.
+/// optional source location, zero or more [`Fix`] proposals applied by
+/// `--fix`, and labeled [`FixAlternative`]s to choose from instead.
 ///
 /// # Building errors
 ///
@@ -163,7 +234,7 @@ impl Fix {
 ///     .with_location(10, 1)
 ///     .with_fix(Fix::replace(10, "value  ", "value"));
 /// ```
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LintError {
     /// Rule identifier (e.g. `"server-tokens-enabled"`).
     pub rule: String,
@@ -177,9 +248,17 @@ pub struct LintError {
     pub line: Option<usize>,
     /// 1-indexed column number where the problem was detected.
     pub column: Option<usize>,
+    /// 1-indexed column just past the end of the offending text, when the
+    /// problem is a part of `line` (such as one argument) rather than the
+    /// position `column` alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
     /// Auto-fix proposals that can resolve this diagnostic.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub fixes: Vec<Fix>,
+    /// Other ways to resolve this diagnostic, applied only when chosen.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<FixAlternative>,
 }
 
 impl LintError {
@@ -195,7 +274,9 @@ impl LintError {
             severity,
             line: None,
             column: None,
+            end_column: None,
             fixes: Vec::new(),
+            alternatives: Vec::new(),
         }
     }
 
@@ -206,6 +287,13 @@ impl LintError {
         self
     }
 
+    /// Mark the text from `column` up to `end_column` (exclusive) on `line`
+    /// as the problem, for reporters that underline it.
+    pub fn with_end_column(mut self, end_column: usize) -> Self {
+        self.end_column = Some(end_column);
+        self
+    }
+
     /// Append a single [`Fix`] proposal to this error.
     pub fn with_fix(mut self, fix: Fix) -> Self {
         self.fixes.push(fix);
@@ -217,6 +305,74 @@ impl LintError {
         self.fixes.extend(fixes);
         self
     }
+
+    /// Offer `fixes` as an alternative named `label`, applied together
+    /// instead of the default fixes when chosen.
+    pub fn with_alternative(mut self, label: &str, fixes: Vec<Fix>) -> Self {
+        self.alternatives.push(FixAlternative::new(label, fixes));
+        self
+    }
+
+    /// The fixes to apply for this error: those of the first alternative
+    /// whose label is in `chosen`, or the default fixes.
+    ///
+    /// ```
+    /// use nginx_lint_common::linter::{Fix, LintError, Severity};
+    ///
+    /// let error = LintError::new("my-rule", "style", "message", Severity::Warning)
+    ///     .with_fix(Fix::replace_range(0, 3, "off"))
+    ///     .with_alternative("remove it", vec![Fix::replace_range(0, 4, "")]);
+    /// assert_eq!(error.chosen_fixes(&[])[0].new_text, "off");
+    /// assert_eq!(error.chosen_fixes(&["remove it".to_string()])[0].new_text, "");
+    /// ```
+    pub fn chosen_fixes(&self, chosen: &[String]) -> &[Fix] {
+        self.alternatives
+            .iter()
+            .find(|alternative| chosen.contains(&alternative.label))
+            .map_or(&self.fixes, |alternative| &alternative.fixes)
+    }
+
+    /// Create a [`plugin-error`](PLUGIN_ERROR_RULE) diagnostic for a rule or
+    /// plugin that failed to run on a file.
+    ///
+    /// The diagnostic is an error so the run still fails, but it is reported
+    /// like any other diagnostic instead of aborting the whole invocation.
+    pub fn plugin_error(plugin: &str, category: &str, message: &str) -> Self {
+        Self::new(
+            PLUGIN_ERROR_RULE,
+            category,
+            &format!("plugin '{}' failed: {}", plugin, message),
+            Severity::Error,
+        )
+    }
+}
+
+/// Canonical order of diagnostics within one file.
+///
+/// Diagnostics are ordered by line, then column (diagnostics without a
+/// location sort after those with one), then rule id, then message. Rules run
+/// in parallel and plugins are loaded in directory order, so the order errors
+/// are produced in is not stable; sorting with this comparator makes lint
+/// output reproducible across runs.
+pub fn compare_diagnostics(a: &LintError, b: &LintError) -> std::cmp::Ordering {
+    fn located_first(a: Option<usize>, b: Option<usize>) -> std::cmp::Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    }
+
+    located_first(a.line, b.line)
+        .then_with(|| located_first(a.column, b.column))
+        .then_with(|| a.rule.cmp(&b.rule))
+        .then_with(|| a.message.cmp(&b.message))
+}
+
+/// Sort diagnostics into [canonical order](compare_diagnostics).
+pub fn sort_diagnostics(errors: &mut [LintError]) {
+    errors.sort_by(compare_diagnostics);
 }
 
 /// A lint rule that can be checked against a parsed nginx configuration.
@@ -282,6 +438,21 @@ pub trait LintRule: Send + Sync {
         self.check(config, path)
     }
 
+    /// Run the rule with the per-file [`AnalysisCache`] shared by every rule
+    /// checking the file.
+    ///
+    /// The linter calls this instead of [`check`](Self::check) for rules that
+    /// do not [want a shared config handle](Self::wants_shared_config).
+    /// Default implementation ignores the cache and calls `check()`.
+    fn check_with_cache(
+        &self,
+        config: &Config,
+        path: &Path,
+        _cache: &AnalysisCache,
+    ) -> Vec<LintError> {
+        self.check(config, path)
+    }
+
     /// Whether this rule wants the raw file content directly.
     ///
     /// Rules that need to re-derive diagnostics from the source text itself
@@ -348,6 +519,110 @@ pub trait LintRule: Send + Sync {
     fn max_nginx_version(&self) -> Option<&str> {
         None
     }
+
+    /// Smallest [rule set](crate::config::RulePreset) this rule belongs to
+    /// (`"minimal"` or `"recommended"`).
+    ///
+    /// `None` means the rule is only part of `strict`. Native rules declare
+    /// it in their `RuleDoc` instead.
+    fn preset(&self) -> Option<&str> {
+        None
+    }
+
+    /// Apply options from the rule's config section that are not one of the
+    /// builtin [`RuleConfig`](crate::config::RuleConfig) fields.
+    ///
+    /// Called once, before any check, when the user set such options (see
+    /// [`LintConfig::rule_options`](crate::config::LintConfig::rule_options)).
+    /// Returns the problems found with the values (wrong type, unknown key);
+    /// the linter reports them as warnings. The default implementation
+    /// accepts no options and ignores them.
+    fn configure(&mut self, _options: &HashMap<String, serde_json::Value>) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Tell the rule which nginx version the config targets (the
+    /// [`target_nginx_version`](crate::config::LintConfig::target_nginx_version)
+    /// setting, unparsed). Called once, before any check, when it is set.
+    /// Default implementation ignores it.
+    fn set_target_nginx_version(&mut self, _version: &str) {}
+
+    /// Tell the rule where the config is deployed (the
+    /// [`[environment]`](crate::config::LintConfig::environment) section).
+    /// Called once, before any check, when any of it is set.
+    /// Default implementation ignores it.
+    fn set_environment(&mut self, _environment: &crate::config::Environment) {}
+}
+
+/// Analyses of one file, computed once and shared by every rule checking it.
+///
+/// The linter creates a cache per linted file and hands it to
+/// [`LintRule::check_with_cache`]. Entries are keyed by type, so an analysis
+/// is usually a newtype around its result:
+///
+/// ```
+/// use nginx_lint_common::linter::AnalysisCache;
+///
+/// struct ServerCount(usize);
+///
+/// let cache = AnalysisCache::new();
+/// let first = cache.get_or_insert_with(|| ServerCount(3));
+/// let second = cache.get_or_insert_with(|| -> ServerCount { unreachable!() });
+/// assert_eq!(first.0, second.0);
+/// ```
+#[derive(Default)]
+pub struct AnalysisCache {
+    entries: std::sync::Mutex<
+        HashMap<std::any::TypeId, std::sync::Arc<dyn std::any::Any + Send + Sync>>,
+    >,
+    /// The config as a shared handle, for rules that
+    /// [want one](LintRule::wants_shared_config)
+    shared_config: std::sync::OnceLock<std::sync::Arc<Config>>,
+}
+
+impl AnalysisCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached `T`, computed with `compute` by the first caller.
+    ///
+    /// `compute` runs without the cache locked, so it may read other entries;
+    /// if two rules compute the same entry at once, the first one stored wins.
+    pub fn get_or_insert_with<T: std::any::Any + Send + Sync>(
+        &self,
+        compute: impl FnOnce() -> T,
+    ) -> std::sync::Arc<T> {
+        let key = std::any::TypeId::of::<T>();
+        let cached = self
+            .entries
+            .lock()
+            .expect("analysis cache poisoned")
+            .get(&key)
+            .cloned();
+        let entry = match cached {
+            Some(entry) => entry,
+            None => {
+                let computed: std::sync::Arc<dyn std::any::Any + Send + Sync> =
+                    std::sync::Arc::new(compute());
+                self.entries
+                    .lock()
+                    .expect("analysis cache poisoned")
+                    .entry(key)
+                    .or_insert(computed)
+                    .clone()
+            }
+        };
+        entry
+            .downcast::<T>()
+            .unwrap_or_else(|_| unreachable!("analysis cache entries are keyed by their type"))
+    }
+
+    /// The shared handle to `config`, cloned into an `Arc` on first use
+    fn shared_config(&self, config: &Config) -> &std::sync::Arc<Config> {
+        self.shared_config
+            .get_or_init(|| std::sync::Arc::new(config.clone()))
+    }
 }
 
 /// Container that holds [`LintRule`]s and runs them against a parsed config.
@@ -384,11 +659,11 @@ impl Linter {
 
     /// Run all lint rules and collect errors (sequential version)
     pub fn lint(&self, config: &Config, path: &Path) -> Vec<LintError> {
-        let shared_config = std::sync::OnceLock::new();
+        let cache = AnalysisCache::new();
 
         self.rules
             .iter()
-            .flat_map(|rule| run_rule(rule.as_ref(), config, path, &shared_config))
+            .flat_map(|rule| run_rule(rule.as_ref(), config, path, &cache))
             .collect()
     }
 }
@@ -396,24 +671,28 @@ impl Linter {
 /// Run a single rule, dispatching to [`LintRule::check_shared`] with one
 /// lazily-created `Arc<Config>` for rules that
 /// [want a shared handle](LintRule::wants_shared_config), and to
-/// [`LintRule::check`] otherwise.
+/// [`LintRule::check_with_cache`] otherwise.
+///
+/// `cache` is the file's [`AnalysisCache`]; the `Arc` is created at most once
+/// per cache (i.e. per linted file), so purely native rule sets never pay for
+/// the clone. Linter implementations should route every rule invocation
+/// through this function so the dispatch policy stays in one place.
 ///
-/// The `Arc` is created at most once per `shared_config` cell (i.e. per
-/// linted file), so purely native rule sets never pay for the clone. Linter
-/// implementations should route every rule invocation through this function
-/// so the dispatch policy stays in one place.
+/// A panicking rule does not take down the run: the panic is caught and
+/// reported as a [`plugin-error`](LintError::plugin_error) diagnostic.
 pub fn run_rule(
     rule: &dyn LintRule,
     config: &Config,
     path: &Path,
-    shared_config: &std::sync::OnceLock<std::sync::Arc<Config>>,
+    cache: &AnalysisCache,
 ) -> Vec<LintError> {
-    if rule.wants_shared_config() {
-        let shared = shared_config.get_or_init(|| std::sync::Arc::new(config.clone()));
-        rule.check_shared(shared, path)
-    } else {
-        rule.check(config, path)
-    }
+    isolate_panics(rule, || {
+        if rule.wants_shared_config() {
+            rule.check_shared(cache.shared_config(config), path)
+        } else {
+            rule.check_with_cache(config, path, cache)
+        }
+    })
 }
 
 /// Like [`run_rule`], but additionally dispatches to
@@ -426,15 +705,32 @@ pub fn run_rule_with_content(
     config: &Config,
     path: &Path,
     content: &str,
-    shared_config: &std::sync::OnceLock<std::sync::Arc<Config>>,
+    cache: &AnalysisCache,
 ) -> Vec<LintError> {
     if rule.wants_content() {
-        rule.check_with_content(config, path, content)
+        isolate_panics(rule, || rule.check_with_content(config, path, content))
     } else {
-        run_rule(rule, config, path, shared_config)
+        run_rule(rule, config, path, cache)
     }
 }
 
+/// Run `check`, converting a panic into a `plugin-error` diagnostic for `rule`.
+fn isolate_panics(rule: &dyn LintRule, check: impl FnOnce() -> Vec<LintError>) -> Vec<LintError> {
+    // Rules only read the config, so observing it after a panic is fine.
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(check)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panicked".to_string());
+        vec![LintError::plugin_error(
+            rule.name(),
+            rule.category(),
+            &format!("panicked: {}", message),
+        )]
+    })
+}
+
 impl Default for Linter {
     fn default() -> Self {
         Self::new()
@@ -583,25 +879,105 @@ fn has_non_whitespace(s: &str) -> bool {
 /// Fixes that cannot be applied (invalid offsets, or line-based fixes that fail
 /// normalization) are skipped and counted in [`FixApplyResult::skipped_invalid`].
 pub fn apply_fixes_to_content_detailed(content: &str, fixes: &[&Fix]) -> FixApplyResult {
+    let (content, outcomes) = apply_fixes_with_outcomes(content, fixes);
+    FixApplyResult {
+        content,
+        applied: outcomes
+            .iter()
+            .filter(|o| matches!(o, FixOutcome::Applied))
+            .count(),
+        skipped_invalid: outcomes
+            .iter()
+            .filter(|o| matches!(o, FixOutcome::Invalid))
+            .count(),
+    }
+}
+
+/// A fix passed to [`apply_fixes`] that could not be applied.
+///
+/// Indices refer to the slice of fixes given to [`apply_fixes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixConflict {
+    /// The fix edits a range that another, applied fix already changes
+    Overlap {
+        /// Index of the fix that was not applied
+        index: usize,
+        /// Index of the applied fix it conflicts with
+        other: usize,
+    },
+    /// The fix points outside the source: offsets out of range or not on a
+    /// UTF-8 character boundary, or a line-based fix whose line or
+    /// `old_text` does not exist
+    Invalid {
+        /// Index of the fix that was not applied
+        index: usize,
+    },
+}
+
+impl std::fmt::Display for FixConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FixConflict::Overlap { index, other } => {
+                write!(f, "fix #{} overlaps fix #{}", index, other)
+            }
+            FixConflict::Invalid { index } => {
+                write!(f, "fix #{} does not apply to the source", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FixConflict {}
+
+/// Apply fixes to a source string with the same semantics as `--fix`.
+///
+/// Fixes are applied in one pass, exactly as [`apply_fixes_to_content`]
+/// does, including the trailing newline it ensures. Where `--fix` silently
+/// skips a fix that overlaps another one (and picks it up when re-linting),
+/// this returns the first such fix as an error, so callers applying fixes
+/// from the JSON output can decide how to resolve it.
+pub fn apply_fixes(source: &str, fixes: &[Fix]) -> Result<String, FixConflict> {
+    let fix_refs: Vec<&Fix> = fixes.iter().collect();
+    let (content, outcomes) = apply_fixes_with_outcomes(source, &fix_refs);
+    for (index, outcome) in outcomes.into_iter().enumerate() {
+        match outcome {
+            FixOutcome::Applied => {}
+            FixOutcome::Overlap(other) => return Err(FixConflict::Overlap { index, other }),
+            FixOutcome::Invalid => return Err(FixConflict::Invalid { index }),
+        }
+    }
+    Ok(content)
+}
+
+/// What happened to one fix in [`apply_fixes_with_outcomes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FixOutcome {
+    Applied,
+    /// Skipped because it overlaps the applied fix at this index
+    Overlap(usize),
+    Invalid,
+}
+
+/// Shared implementation of the apply functions, returning the new content
+/// and the outcome of each fix, in the order of `fixes`.
+fn apply_fixes_with_outcomes(content: &str, fixes: &[&Fix]) -> (String, Vec<FixOutcome>) {
     let line_starts = compute_line_starts(content);
-    let mut skipped_invalid = 0;
+    let mut outcomes = vec![FixOutcome::Invalid; fixes.len()];
 
-    // Normalize all fixes to range-based
-    let mut range_fixes: Vec<Fix> = Vec::with_capacity(fixes.len());
-    for fix in fixes {
+    // Normalize all fixes to range-based, remembering their input index
+    let mut range_fixes: Vec<(usize, Fix)> = Vec::with_capacity(fixes.len());
+    for (index, fix) in fixes.iter().enumerate() {
         if fix.is_range_based() {
-            range_fixes.push((*fix).clone());
+            range_fixes.push((index, (*fix).clone()));
         } else if let Some(normalized) = normalize_line_fix(fix, content, &line_starts) {
-            range_fixes.push(normalized);
-        } else {
-            skipped_invalid += 1;
+            range_fixes.push((index, normalized));
         }
     }
 
     // Sort by start_offset descending to avoid index shifts.
     // For same-offset insertions (start == end), sort by indent ascending so that
     // the more-indented text is processed last and ends up first in the file.
-    range_fixes.sort_by(|a, b| {
+    range_fixes.sort_by(|(_, a), (_, b)| {
         let a_start = a.start_offset.unwrap();
         let b_start = b.start_offset.unwrap();
         match b_start.cmp(&a_start) {
@@ -628,25 +1004,27 @@ pub fn apply_fixes_to_content_detailed(content: &str, fixes: &[&Fix]) -> FixAppl
     // order fixes happen to be processed in — the ascending-indent sort tiebreak
     // means a structural insert with more leading whitespace than a competing
     // reformatting insert would otherwise be processed second and let both apply.
-    let structural_insert_offsets: std::collections::HashSet<usize> = range_fixes
+    let structural_inserts: std::collections::HashMap<usize, usize> = range_fixes
         .iter()
-        .filter(|f| {
+        .filter(|(_, f)| {
             f.start_offset.unwrap() == f.end_offset.unwrap() && has_non_whitespace(&f.new_text)
         })
-        .map(|f| f.start_offset.unwrap())
+        .map(|(index, f)| (f.start_offset.unwrap(), *index))
         .collect();
 
-    let mut fix_count = 0;
     let mut result = content.to_string();
-    let mut applied_ranges: Vec<(usize, usize)> = Vec::new();
+    let mut applied_ranges: Vec<(usize, usize, usize)> = Vec::new();
 
-    for fix in &range_fixes {
+    for (index, fix) in &range_fixes {
         let start = fix.start_offset.unwrap();
         let end = fix.end_offset.unwrap();
         let is_insert = start == end;
 
         // Check if this range overlaps with any already applied range
-        let overlaps = applied_ranges.iter().any(|(s, e)| start < *e && end > *s);
+        let overlapping = applied_ranges
+            .iter()
+            .find(|(s, e, _)| start < *e && end > *s)
+            .map(|(_, _, other)| *other);
 
         // Two zero-width inserts at the identical point don't trip the
         // check above (touching, not overlapping) — which is intentional
@@ -657,11 +1035,14 @@ pub fn apply_fixes_to_content_detailed(content: &str, fixes: &[&Fix]) -> FixAppl
         // `}`) produces nonsensical interleaved output — the whitespace fix
         // was computed against a structure this other fix is about to
         // change anyway, so drop it (regardless of which is processed first).
-        let conflicts_with_structural_insert = is_insert
-            && is_whitespace_only(&fix.new_text)
-            && structural_insert_offsets.contains(&start);
+        let structural_conflict = if is_insert && is_whitespace_only(&fix.new_text) {
+            structural_inserts.get(&start).copied()
+        } else {
+            None
+        };
 
-        if overlaps || conflicts_with_structural_insert {
+        if let Some(other) = overlapping.or(structural_conflict) {
+            outcomes[*index] = FixOutcome::Overlap(other);
             continue;
         }
 
@@ -673,10 +1054,8 @@ pub fn apply_fixes_to_content_detailed(content: &str, fixes: &[&Fix]) -> FixAppl
             && result.is_char_boundary(end)
         {
             result.replace_range(start..end, &fix.new_text);
-            applied_ranges.push((start, start + fix.new_text.len()));
-            fix_count += 1;
-        } else {
-            skipped_invalid += 1;
+            applied_ranges.push((start, start + fix.new_text.len(), *index));
+            outcomes[*index] = FixOutcome::Applied;
         }
     }
 
@@ -685,10 +1064,157 @@ pub fn apply_fixes_to_content_detailed(content: &str, fixes: &[&Fix]) -> FixAppl
         result.push('\n');
     }
 
-    FixApplyResult {
-        content: result,
-        applied: fix_count,
-        skipped_invalid,
+    (result, outcomes)
+}
+
+#[cfg(test)]
+mod isolation_tests {
+    use super::*;
+
+    struct PanickingRule;
+
+    impl LintRule for PanickingRule {
+        fn name(&self) -> &'static str {
+            "panicking-rule"
+        }
+        fn category(&self) -> &'static str {
+            "security"
+        }
+        fn description(&self) -> &'static str {
+            "Always panics"
+        }
+        fn check(&self, _config: &Config, _path: &Path) -> Vec<LintError> {
+            panic!("edge case at {}", 42);
+        }
+    }
+
+    struct OkRule;
+
+    impl LintRule for OkRule {
+        fn name(&self) -> &'static str {
+            "ok-rule"
+        }
+        fn category(&self) -> &'static str {
+            "style"
+        }
+        fn description(&self) -> &'static str {
+            "Reports one warning"
+        }
+        fn check(&self, _config: &Config, _path: &Path) -> Vec<LintError> {
+            vec![LintError::new("ok-rule", "style", "ok", Severity::Warning)]
+        }
+    }
+
+    #[test]
+    fn test_panicking_rule_reports_plugin_error() {
+        let mut linter = Linter::new();
+        linter.add_rule(Box::new(PanickingRule));
+        linter.add_rule(Box::new(OkRule));
+        let config = crate::parse_string("listen 80;").unwrap();
+
+        let errors = linter.lint(&config, Path::new("test.conf"));
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].rule, PLUGIN_ERROR_RULE);
+        assert_eq!(errors[0].category, "security");
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert_eq!(
+            errors[0].message,
+            "plugin 'panicking-rule' failed: panicked: edge case at 42"
+        );
+        assert_eq!(errors[1].rule, "ok-rule");
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COMPUTED: AtomicUsize = AtomicUsize::new(0);
+
+    struct DirectiveCount(usize);
+
+    struct CountingRule(&'static str);
+
+    impl LintRule for CountingRule {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+        fn category(&self) -> &'static str {
+            "style"
+        }
+        fn description(&self) -> &'static str {
+            "Reports the directive count"
+        }
+        fn check(&self, _config: &Config, _path: &Path) -> Vec<LintError> {
+            unreachable!("the linter calls check_with_cache")
+        }
+        fn check_with_cache(
+            &self,
+            config: &Config,
+            _path: &Path,
+            cache: &AnalysisCache,
+        ) -> Vec<LintError> {
+            let count = cache.get_or_insert_with(|| {
+                COMPUTED.fetch_add(1, Ordering::SeqCst);
+                DirectiveCount(config.all_directives().count())
+            });
+            let message = count.0.to_string();
+            vec![LintError::new(self.0, "style", &message, Severity::Warning)]
+        }
+    }
+
+    #[test]
+    fn test_analysis_shared_between_rules() {
+        let mut linter = Linter::new();
+        linter.add_rule(Box::new(CountingRule("first")));
+        linter.add_rule(Box::new(CountingRule("second")));
+        let config = crate::parse_string("http {\n    server {}\n}\n").unwrap();
+
+        let errors = linter.lint(&config, Path::new("test.conf"));
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.message == "2"));
+        assert_eq!(COMPUTED.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[cfg(test)]
+mod order_tests {
+    use super::*;
+
+    fn error(rule: &str, message: &str, location: Option<(usize, usize)>) -> LintError {
+        let e = LintError::new(rule, "test", message, Severity::Warning);
+        match location {
+            Some((line, column)) => e.with_location(line, column),
+            None => e,
+        }
+    }
+
+    fn keys(errors: &[LintError]) -> Vec<(&str, &str, Option<usize>, Option<usize>)> {
+        errors
+            .iter()
+            .map(|e| (e.rule.as_str(), e.message.as_str(), e.line, e.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_diagnostics_by_span_then_rule() {
+        let expected = vec![
+            error("b-rule", "x", Some((1, 5))),
+            error("a-rule", "x", Some((2, 1))),
+            error("b-rule", "x", Some((2, 1))),
+            error("b-rule", "y", Some((2, 1))),
+            error("a-rule", "x", Some((2, 3))),
+            error("a-rule", "x", None),
+        ];
+        // Every rotation of the input sorts to the same output
+        for shift in 0..expected.len() {
+            let mut errors = expected.clone();
+            errors.rotate_left(shift);
+            errors.reverse();
+            sort_diagnostics(&mut errors);
+            assert_eq!(keys(&errors), keys(&expected), "rotation {}", shift);
+        }
     }
 }
 
@@ -873,6 +1399,37 @@ mod fix_tests {
         assert_eq!(result.skipped_invalid, 0);
     }
 
+    #[test]
+    fn test_apply_fixes_returns_content() {
+        let fixes = vec![
+            Fix::replace_range(0, 3, "XYZ"),
+            Fix::replace_range(3, 6, "QQQ"),
+        ];
+        // Like --fix, the result always ends with a newline
+        assert_eq!(apply_fixes("abcdef", &fixes).unwrap(), "XYZQQQ\n");
+    }
+
+    #[test]
+    fn test_apply_fixes_reports_overlap() {
+        let fixes = vec![
+            Fix::replace_range(0, 3, "XYZ"),
+            Fix::replace_range(2, 5, "QQQ"),
+        ];
+        // Later offsets are applied first, so the first fix is the one skipped
+        assert_eq!(
+            apply_fixes("abcdef\n", &fixes),
+            Err(FixConflict::Overlap { index: 0, other: 1 })
+        );
+    }
+
+    #[test]
+    fn test_apply_fixes_reports_invalid_fix() {
+        let fixes = vec![Fix::replace_range(2, 3, "x"), Fix::replace_range(4, 99, "")];
+        let err = apply_fixes("abcdef\n", &fixes).unwrap_err();
+        assert_eq!(err, FixConflict::Invalid { index: 1 });
+        assert_eq!(err.to_string(), "fix #1 does not apply to the source");
+    }
+
     /// Two whitespace-only inserts at the exact same point (e.g. two
     /// `indent` errors reconciling to the same total indentation) must
     /// still stack in ascending-indent order — this is the legitimate use
@@ -0,0 +1,66 @@
+//! Addresses of `listen` directives.
+//!
+//! nginx opens one socket per address, however it is written: `listen 80`,
+//! `listen *:80` and `listen 0.0.0.0:80` share a socket, and with it the
+//! `default_server` and the socket options. Rules comparing `listen`
+//! directives normalize their addresses first.
+
+/// Normalize the address of a `listen` directive to `host:port`, so that
+/// addresses nginx binds to the same socket compare equal
+///
+/// The wildcard host is written `*` and the default port 80 is filled in.
+/// Unix sockets are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use nginx_lint_common::listen::normalize_listen_address;
+///
+/// assert_eq!(normalize_listen_address("80"), "*:80");
+/// assert_eq!(normalize_listen_address("0.0.0.0:80"), "*:80");
+/// assert_eq!(normalize_listen_address("127.0.0.1"), "127.0.0.1:80");
+/// assert_eq!(normalize_listen_address("[::]:443"), "[::]:443");
+/// ```
+pub fn normalize_listen_address(address: &str) -> String {
+    if address.starts_with("unix:") {
+        return address.to_string();
+    }
+    if address.bytes().all(|b| b.is_ascii_digit()) {
+        return format!("*:{}", address);
+    }
+    let (host, port) = match address.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((host, port)) => (
+                format!("[{}]", host),
+                port.strip_prefix(':').unwrap_or("80"),
+            ),
+            None => (address.to_string(), "80"),
+        },
+        None => match address.rsplit_once(':') {
+            Some((host, port)) => (host.to_string(), port),
+            None => (address.to_string(), "80"),
+        },
+    };
+    let host = if host == "0.0.0.0" { "*" } else { &host };
+    format!("{}:{}", host.to_ascii_lowercase(), port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_listen_address() {
+        assert_eq!(normalize_listen_address("80"), "*:80");
+        assert_eq!(normalize_listen_address("*:80"), "*:80");
+        assert_eq!(normalize_listen_address("0.0.0.0:80"), "*:80");
+        assert_eq!(normalize_listen_address("127.0.0.1"), "127.0.0.1:80");
+        assert_eq!(normalize_listen_address("Localhost:8080"), "localhost:8080");
+        assert_eq!(normalize_listen_address("[::]:443"), "[::]:443");
+        assert_eq!(normalize_listen_address("[::1]"), "[::1]:80");
+        assert_eq!(
+            normalize_listen_address("unix:/run/nginx.sock"),
+            "unix:/run/nginx.sock"
+        );
+    }
+}
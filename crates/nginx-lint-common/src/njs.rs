@@ -0,0 +1,382 @@
+//! njs (`js_*`) directives.
+//!
+//! [`JsImport`] reads `js_import`, [`FunctionRef`] reads the `module.function`
+//! handler of the directives in [`FUNCTION_DIRECTIVES`], and
+//! [`module_candidates`] lists where nginx looks for an imported file.
+//! [`exported_names`] finds what a module exports, for checking handlers
+//! against it when its source is readable.
+//!
+//! ```
+//! use nginx_lint_common::njs::{FunctionRef, JsImport, exported_names, module_candidates};
+//! use nginx_lint_common::parse_string;
+//!
+//! let config = parse_string(
+//!     "http {\n    js_path /etc/nginx/njs;\n    js_import main from http/hello.js;\n    \
+//!      server { location / { js_content main.hello; } }\n}\n",
+//! )
+//! .unwrap();
+//! let import = config.all_directives().find_map(JsImport::parse).unwrap();
+//! assert_eq!((import.name.as_str(), import.module.as_str()), ("main", "http/hello.js"));
+//! assert_eq!(
+//!     module_candidates(&import.module, &["/etc/nginx/njs"]),
+//!     ["/etc/nginx/njs/http/hello.js", "http/hello.js"]
+//! );
+//!
+//! let handler = config.all_directives().find_map(FunctionRef::parse).unwrap();
+//! assert_eq!((handler.module, handler.function), (Some("main"), "hello"));
+//!
+//! let exports = exported_names("function hello(r) { r.return(200); }\nexport default { hello };");
+//! assert_eq!(exports, Some(vec!["hello".to_string()]));
+//! ```
+
+use crate::parser::ast::Directive;
+
+/// Directives naming an njs function, with the index of the argument that
+/// holds it
+pub const FUNCTION_DIRECTIVES: &[(&str, usize)] = &[
+    ("js_content", 0),
+    ("js_set", 1),
+    ("js_body_filter", 0),
+    ("js_header_filter", 0),
+    ("js_periodic", 0),
+    // stream
+    ("js_access", 0),
+    ("js_preread", 0),
+    ("js_filter", 0),
+];
+
+/// A module imported with `js_import [name from] module.js;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsImport {
+    /// Name handlers refer to the module by: the one given with `from`, or
+    /// the file name without its extension
+    pub name: String,
+    /// Path of the module file as written
+    pub module: String,
+}
+
+impl JsImport {
+    /// The import declared by `directive`; `None` for other directives and
+    /// for a `js_import` whose arguments do not have either form
+    pub fn parse(directive: &Directive) -> Option<JsImport> {
+        if !directive.is("js_import") {
+            return None;
+        }
+        match directive
+            .args
+            .iter()
+            .map(|a| a.as_str())
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [module] => {
+                let file = module.rsplit('/').next().unwrap_or(module);
+                let name = file.split_once('.').map_or(file, |(stem, _)| stem);
+                Some(JsImport {
+                    name: name.to_string(),
+                    module: module.to_string(),
+                })
+            }
+            [name, "from", module] => Some(JsImport {
+                name: name.to_string(),
+                module: module.to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The handler named by one of the [`FUNCTION_DIRECTIVES`]
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionRef<'a> {
+    pub directive: &'a Directive,
+    /// Imported module, `None` for a bare function name
+    pub module: Option<&'a str>,
+    /// Function, the property of the module's default export
+    pub function: &'a str,
+}
+
+impl<'a> FunctionRef<'a> {
+    /// The handler named by `directive`; `None` for other directives and
+    /// when the argument is missing
+    pub fn parse(directive: &'a Directive) -> Option<FunctionRef<'a>> {
+        let index = FUNCTION_DIRECTIVES
+            .iter()
+            .find(|(name, _)| directive.is(name))
+            .map(|(_, index)| *index)?;
+        let value = directive.args.get(index)?.as_str();
+        let (module, function) = match value.split_once('.') {
+            Some((module, function)) => (Some(module), function),
+            None => (None, value),
+        };
+        Some(FunctionRef {
+            directive,
+            module,
+            function,
+        })
+    }
+}
+
+/// Paths nginx tries, in order, for a `js_import` module: an absolute path
+/// as is, otherwise below each `js_path` and then as written, relative to
+/// the configuration prefix
+pub fn module_candidates(module: &str, js_paths: &[&str]) -> Vec<String> {
+    if module.starts_with('/') {
+        return vec![module.to_string()];
+    }
+    js_paths
+        .iter()
+        .map(|dir| format!("{}/{}", dir.trim_end_matches('/'), module))
+        .chain(std::iter::once(module.to_string()))
+        .collect()
+}
+
+/// Names a module exports: the keys of an `export default { ... }` object
+/// and the `export function` / `export const` names.
+///
+/// Returns `None` when the exports cannot be told from the source, e.g.
+/// `export default handlers;` or a spread in the default object.
+pub fn exported_names(source: &str) -> Option<Vec<String>> {
+    let code = strip_comments(source);
+    let mut names = Vec::new();
+    let mut found = false;
+
+    let mut rest = code.as_str();
+    while let Some(at) = rest.find("export") {
+        let after = &rest[at + "export".len()..];
+        let is_keyword =
+            !rest[..at].ends_with(is_identifier_char) && after.starts_with(char::is_whitespace);
+        rest = after;
+        if !is_keyword {
+            continue;
+        }
+        let after = after.trim_start();
+        if let Some(value) = after.strip_prefix("default") {
+            let body = value.trim_start().strip_prefix('{')?;
+            names.extend(object_keys(body)?);
+            found = true;
+        } else {
+            let declaration = ["async function", "function", "const", "let", "var"]
+                .iter()
+                .find_map(|keyword| after.strip_prefix(keyword));
+            if let Some(name) = declaration.map(|d| identifier(d.trim_start_matches(['*', ' '])))
+                && !name.is_empty()
+            {
+                names.push(name.to_string());
+                found = true;
+            }
+        }
+    }
+    found.then_some(names)
+}
+
+/// Keys of an object literal, `body` starting right after its `{`
+fn object_keys(body: &str) -> Option<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut entry_start = 0;
+    for (i, c) in body.char_indices() {
+        if let Some(q) = quote {
+            if c == q && !body[..i].ends_with('\\') {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' | '`' => quote = Some(c),
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' if depth > 0 => depth -= 1,
+            ',' | '}' if depth == 0 => {
+                let entry = body[entry_start..i].trim();
+                if !entry.is_empty() {
+                    keys.push(object_key(entry)?);
+                }
+                if c == '}' {
+                    return Some(keys);
+                }
+                entry_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Key of one `key: value`, `key`, or method entry of an object literal
+fn object_key(entry: &str) -> Option<String> {
+    if entry.starts_with("...") {
+        return None;
+    }
+    let mut entry = entry.trim_start_matches('*').trim_start();
+    for prefix in ["async ", "get ", "set "] {
+        if let Some(rest) = entry.strip_prefix(prefix)
+            && rest
+                .trim_start()
+                .starts_with(|c: char| is_identifier_char(c) || c == '*')
+        {
+            entry = rest.trim_start().trim_start_matches('*').trim_start();
+        }
+    }
+    let key = match entry.chars().next()? {
+        q @ ('"' | '\'') => entry[1..].split(q).next()?,
+        _ => identifier(entry),
+    };
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+fn identifier(s: &str) -> &str {
+    let s = s.trim_start();
+    let end = s.find(|c| !is_identifier_char(c)).unwrap_or(s.len());
+    &s[..end]
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// `source` with `//` and `/* */` comments replaced by spaces, leaving
+/// strings alone
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                out.push(' ');
+            }
+            ('"' | '\'' | '`', _) => {
+                quote = Some(c);
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_string;
+
+    fn imports(source: &str) -> Vec<Option<JsImport>> {
+        let config = parse_string(source).unwrap();
+        config.all_directives().map(JsImport::parse).collect()
+    }
+
+    #[test]
+    fn test_js_import_forms() {
+        let found = imports(
+            "js_import http.js;\njs_import utils from lib/utils.mjs;\njs_import njs/auth.v2.js;\njs_import a b;\n",
+        );
+        let names: Vec<_> = found
+            .iter()
+            .map(|i| i.as_ref().map(|i| (i.name.as_str(), i.module.as_str())))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                Some(("http", "http.js")),
+                Some(("utils", "lib/utils.mjs")),
+                Some(("auth", "njs/auth.v2.js")),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_function_refs() {
+        let config = parse_string(
+            "js_set $token auth.token;\njs_content hello;\njs_body_filter main.filter buffer_type=string;\njs_content;\nproxy_pass http://x;\n",
+        )
+        .unwrap();
+        let refs: Vec<_> = config
+            .all_directives()
+            .filter_map(FunctionRef::parse)
+            .map(|r| (r.directive.name.as_str(), r.module, r.function))
+            .collect();
+        assert_eq!(
+            refs,
+            [
+                ("js_set", Some("auth"), "token"),
+                ("js_content", None, "hello"),
+                ("js_body_filter", Some("main"), "filter"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_candidates() {
+        assert_eq!(module_candidates("/srv/a.js", &["njs"]), ["/srv/a.js"]);
+        assert_eq!(
+            module_candidates("a.js", &["njs/", "/opt/js"]),
+            ["njs/a.js", "/opt/js/a.js", "a.js"]
+        );
+    }
+
+    #[test]
+    fn test_exported_names() {
+        let source = r#"
+// export default { commented };
+import qs from 'querystring';
+
+function hello(r) { r.return(200, "}"); }
+async function fetchToken(r) { return { token: 1 }; }
+
+export function named(r) {}
+export const version = "1";
+
+export default {
+    hello,
+    'quoted': hello,
+    token: fetchToken,
+    /* note */ async later(r) { if (r) { return; } },
+    get status() { return 1; },
+};
+"#;
+        assert_eq!(
+            exported_names(source).unwrap(),
+            [
+                "named", "version", "hello", "quoted", "token", "later", "status"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exports_that_cannot_be_told() {
+        assert_eq!(exported_names("function f() {}\n"), None);
+        assert_eq!(exported_names("export default handlers;\n"), None);
+        assert_eq!(exported_names("export default { ...base, extra };\n"), None);
+        // An identifier merely containing the word
+        assert_eq!(exported_names("var reexport = 1;\n"), None);
+    }
+}
@@ -0,0 +1,102 @@
+//! Files referenced by the configuration.
+//!
+//! nginx resolves the relative paths of `include`, `ssl_certificate`,
+//! `auth_basic_user_file` and the like against its prefix (`nginx -p`).
+//! [`base_dir`] gives that directory for a linted file, falling back to the
+//! file's own directory when no prefix is set, and [`resolve_file`] resolves
+//! the path a directive names. Rules checking referenced files use these so
+//! that `--prefix` applies to all of them alike.
+//!
+//! ```
+//! use nginx_lint_common::parse_string;
+//! use nginx_lint_common::paths::{base_dir, resolve_file};
+//! use std::path::Path;
+//!
+//! let config = parse_string("auth_basic_user_file conf.d/htpasswd;\n").unwrap();
+//! let directive = config.all_directives().next().unwrap();
+//! let linted = Path::new("/srv/nginx/sites/app.conf");
+//!
+//! assert_eq!(base_dir(None, linted), Path::new("/srv/nginx/sites"));
+//! let (value, resolved) = resolve_file(Some(Path::new("/etc/nginx")), linted, directive).unwrap();
+//! assert_eq!(value, "conf.d/htpasswd");
+//! assert_eq!(resolved, Path::new("/etc/nginx/conf.d/htpasswd"));
+//! ```
+
+use crate::parser::ast::Directive;
+use std::path::{Path, PathBuf};
+
+/// Directory relative paths in `config_path` are resolved against: `prefix`
+/// when given, otherwise the directory of the file
+pub fn base_dir<'a>(prefix: Option<&'a Path>, config_path: &'a Path) -> &'a Path {
+    prefix.unwrap_or_else(|| config_path.parent().unwrap_or(Path::new(".")))
+}
+
+/// The file `directive`'s first argument refers to, as written and resolved
+/// against [`base_dir`]; absolute paths are kept as they are
+///
+/// Returns `None` for values that are not static paths: variables are
+/// resolved per request (e.g. `$ssl_server_name`), and `data:`/`engine:`
+/// values hold the content or a key reference.
+pub fn resolve_file<'a>(
+    prefix: Option<&Path>,
+    config_path: &Path,
+    directive: &'a Directive,
+) -> Option<(&'a str, PathBuf)> {
+    if directive.args.iter().any(|a| a.is_variable()) {
+        return None;
+    }
+    let value = directive.first_arg()?;
+    if value.contains('$') || value.starts_with("data:") || value.starts_with("engine:") {
+        return None;
+    }
+    Some((value, base_dir(prefix, config_path).join(value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_string;
+
+    fn resolve(prefix: Option<&Path>, source: &str) -> Option<PathBuf> {
+        let config = parse_string(source).unwrap();
+        let directive = config.all_directives().next().unwrap();
+        resolve_file(prefix, Path::new("conf/nginx.conf"), directive).map(|(_, path)| path)
+    }
+
+    #[test]
+    fn test_resolve_file() {
+        assert_eq!(
+            resolve(None, "ssl_certificate ssl/cert.pem;\n"),
+            Some(PathBuf::from("conf/ssl/cert.pem"))
+        );
+        assert_eq!(
+            resolve(Some(Path::new("/etc/nginx")), "include mime.types;\n"),
+            Some(PathBuf::from("/etc/nginx/mime.types"))
+        );
+        assert_eq!(
+            resolve(
+                Some(Path::new("/etc/nginx")),
+                "ssl_certificate /ssl/cert.pem;\n"
+            ),
+            Some(PathBuf::from("/ssl/cert.pem"))
+        );
+    }
+
+    #[test]
+    fn test_values_that_are_not_paths() {
+        assert_eq!(
+            resolve(None, "ssl_certificate $ssl_server_name.crt;\n"),
+            None
+        );
+        assert_eq!(
+            resolve(None, "ssl_certificate \"certs/${host}.crt\";\n"),
+            None
+        );
+        assert_eq!(
+            resolve(None, "ssl_certificate_key engine:pkcs11:key;\n"),
+            None
+        );
+        assert_eq!(resolve(None, "ssl_certificate data:PEM;\n"), None);
+        assert_eq!(resolve(None, "auth_basic_user_file;\n"), None);
+    }
+}
@@ -0,0 +1,605 @@
+//! Variable definition/use analysis.
+//!
+//! [`VariableTable`] records where a config defines variables (`set`, `map`,
+//! `geo`, `split_clients`, `auth_request_set`, named regex captures, ...) and
+//! where it uses them. Rules build on it to find variables that are used but
+//! never defined, or defined but never used.
+//!
+//! ```
+//! use nginx_lint_common::parse_string;
+//! use nginx_lint_common::variables::{VariableSource, VariableTable};
+//!
+//! let config = parse_string(
+//!     "map $http_upgrade $connection_upgrade { default upgrade; '' close; }\n\
+//!      server { location / { proxy_set_header Connection $connection_upgrade; } }",
+//! )
+//! .unwrap();
+//! let table = VariableTable::from_config(&config);
+//!
+//! let def = table.definition("connection_upgrade").unwrap();
+//! assert_eq!(def.source, VariableSource::Map);
+//! assert!(table.is_used("connection_upgrade"));
+//! assert!(table.uses().iter().any(|u| u.name == "http_upgrade"));
+//! ```
+
+use crate::parser::ast::{Argument, Config, ConfigItem, Directive};
+
+/// Directive that created a variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableSource {
+    /// `set $var value;`
+    Set,
+    /// `map $source $var { ... }`
+    Map,
+    /// `geo [$address] $var { ... }`
+    Geo,
+    /// `split_clients "$key" $var { ... }`
+    SplitClients,
+    /// `auth_request_set $var value;`
+    AuthRequestSet,
+    /// Named capture in a regex, e.g. `location ~ ^/(?<slug>[^/]+)`
+    RegexCapture,
+    /// Module directives that set a variable from code
+    /// (`js_set`, `perl_set`, `set_by_lua*`)
+    Module,
+}
+
+/// A variable definition.
+#[derive(Debug, Clone)]
+pub struct VariableDefinition {
+    /// Variable name without the leading `$`
+    pub name: String,
+    pub source: VariableSource,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A variable use.
+#[derive(Debug, Clone)]
+pub struct VariableUse {
+    /// Variable name without the leading `$`
+    pub name: String,
+    /// Directive whose arguments contain the use
+    pub directive: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Variables defined and used in one config.
+#[derive(Debug, Clone, Default)]
+pub struct VariableTable {
+    definitions: Vec<VariableDefinition>,
+    uses: Vec<VariableUse>,
+    /// Content of raw blocks (e.g. `content_by_lua_block`), which can read
+    /// variables through `ngx.var.<name>` without a `$` reference
+    raw_blocks: Vec<String>,
+}
+
+/// Directives whose first argument creates a variable.
+const FIRST_ARG_DEFINES: &[(&str, VariableSource)] = &[
+    ("set", VariableSource::Set),
+    ("auth_request_set", VariableSource::AuthRequestSet),
+    ("js_set", VariableSource::Module),
+    ("perl_set", VariableSource::Module),
+    ("set_by_lua", VariableSource::Module),
+    ("set_by_lua_block", VariableSource::Module),
+    ("set_by_lua_file", VariableSource::Module),
+];
+
+/// Whether argument `index` of `directive` is a regular expression, where
+/// `$` is an anchor rather than a variable reference.
+fn is_regex_arg(directive: &Directive, index: usize) -> bool {
+    match directive.name.as_str() {
+        "location" => index == 1 && matches!(directive.first_arg(), Some("~" | "~*")),
+        "rewrite" => index == 0,
+        "server_name" => true,
+        // `if ($var ~ regex)`: the pattern follows the match operator
+        "if" => directive
+            .args
+            .iter()
+            .position(|a| matches!(a.as_str(), "~" | "~*" | "!~" | "!~*"))
+            .is_some_and(|op| index > op),
+        _ => false,
+    }
+}
+
+impl VariableTable {
+    /// Analyze a parsed config.
+    pub fn from_config(config: &Config) -> Self {
+        let mut table = Self::default();
+        table.collect_items(&config.items, None);
+        table
+    }
+
+    fn collect_items(&mut self, items: &[ConfigItem], parent: Option<&str>) {
+        for item in items {
+            if let ConfigItem::Directive(directive) = item {
+                self.collect_directive(directive, parent);
+            }
+        }
+    }
+
+    fn collect_directive(&mut self, directive: &Directive, parent: Option<&str>) {
+        match parent {
+            // Entries of these blocks are match patterns and values, not
+            // directives; only map values can reference variables.
+            Some("map") => {
+                if !matches!(directive.name.as_str(), "hostnames" | "volatile") {
+                    self.collect_captures(&directive.name, directive);
+                    for arg in &directive.args {
+                        self.collect_uses(arg, directive);
+                    }
+                }
+                return;
+            }
+            Some("geo" | "split_clients" | "types") => return,
+            _ => {}
+        }
+
+        let name = directive.name.as_str();
+        if let Some((_, source)) = FIRST_ARG_DEFINES.iter().find(|(d, _)| *d == name) {
+            let mut args = directive.args.iter();
+            if let Some(target) = args.next() {
+                self.define(target, *source);
+            }
+            for arg in args {
+                self.collect_uses(arg, directive);
+            }
+        } else if name == "map" || name == "geo" || name == "split_clients" {
+            let source = match name {
+                "map" => VariableSource::Map,
+                "geo" => VariableSource::Geo,
+                _ => VariableSource::SplitClients,
+            };
+            // The last argument is the variable being defined; anything
+            // before it (the map/geo source, the split_clients key) is a use.
+            if let Some((target, inputs)) = directive.args.split_last() {
+                for arg in inputs {
+                    self.collect_uses(arg, directive);
+                }
+                self.define(target, source);
+            }
+        } else {
+            for (i, arg) in directive.args.iter().enumerate() {
+                if is_regex_arg(directive, i) && !arg.is_variable() {
+                    self.collect_captures(arg.as_str(), directive);
+                } else {
+                    self.collect_uses(arg, directive);
+                }
+            }
+        }
+
+        if let Some(block) = &directive.block {
+            if let Some(raw) = &block.raw_content {
+                self.raw_blocks.push(raw.clone());
+            } else {
+                self.collect_items(&block.items, Some(name));
+            }
+        }
+    }
+
+    fn define(&mut self, arg: &Argument, source: VariableSource) {
+        let name = arg.as_str().trim_start_matches('$');
+        if name.is_empty() {
+            return;
+        }
+        self.definitions.push(VariableDefinition {
+            name: name.to_string(),
+            source,
+            line: arg.span.start.line,
+            column: arg.span.start.column,
+        });
+    }
+
+    /// Record named captures (`(?<name>...)`, `(?P<name>...)`,
+    /// `(?'name'...)`) found in a regex.
+    fn collect_captures(&mut self, pattern: &str, directive: &Directive) {
+        let mut rest = pattern;
+        while let Some(idx) = rest.find("(?") {
+            rest = &rest[idx + 2..];
+            let rest_after_p = rest.strip_prefix('P').unwrap_or(rest);
+            let (open, close) = match rest_after_p.chars().next() {
+                Some('<') => ('<', '>'),
+                Some('\'') => ('\'', '\''),
+                _ => continue,
+            };
+            let body = &rest_after_p[open.len_utf8()..];
+            // Skip lookbehind assertions `(?<=` / `(?<!`
+            if body.starts_with('=') || body.starts_with('!') {
+                continue;
+            }
+            if let Some(end) = body.find(close) {
+                let name = &body[..end];
+                if !name.is_empty() && name.chars().all(is_variable_char) {
+                    self.definitions.push(VariableDefinition {
+                        name: name.to_string(),
+                        source: VariableSource::RegexCapture,
+                        line: directive.span.start.line,
+                        column: directive.span.start.column,
+                    });
+                }
+            }
+        }
+    }
+
+    fn collect_uses(&mut self, arg: &Argument, directive: &Directive) {
+        if arg.is_variable() {
+            self.uses.push(VariableUse {
+                name: arg.as_str().to_string(),
+                directive: directive.name.clone(),
+                line: arg.span.start.line,
+                column: arg.span.start.column,
+            });
+            return;
+        }
+        for name in variable_references(arg.as_str()) {
+            self.uses.push(VariableUse {
+                name: name.to_string(),
+                directive: directive.name.clone(),
+                line: arg.span.start.line,
+                column: arg.span.start.column,
+            });
+        }
+    }
+
+    /// All definitions, in document order.
+    pub fn definitions(&self) -> &[VariableDefinition] {
+        &self.definitions
+    }
+
+    /// All uses, in document order.
+    pub fn uses(&self) -> &[VariableUse] {
+        &self.uses
+    }
+
+    /// The first definition of `name`, if any.
+    pub fn definition(&self, name: &str) -> Option<&VariableDefinition> {
+        self.definitions.iter().find(|d| d.name == name)
+    }
+
+    /// Whether `name` is defined in this config.
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.definition(name).is_some()
+    }
+
+    /// Whether `name` is referenced, either as `$name` or as `ngx.var.name`
+    /// inside a raw (Lua) block.
+    pub fn is_used(&self, name: &str) -> bool {
+        self.uses.iter().any(|u| u.name == name)
+            || self
+                .raw_blocks
+                .iter()
+                .any(|raw| raw.contains(&format!("ngx.var.{}", name)))
+    }
+
+    /// Merge another table (e.g. from an included file) into this one.
+    pub fn extend(&mut self, other: VariableTable) {
+        self.definitions.extend(other.definitions);
+        self.uses.extend(other.uses);
+        self.raw_blocks.extend(other.raw_blocks);
+    }
+}
+
+fn is_variable_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Extract the variable names referenced as `$name` or `${name}` in a string.
+pub fn variable_references(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(idx) = rest.find('$') {
+        rest = &rest[idx + 1..];
+        let (name, after) = if let Some(braced) = rest.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => break,
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !is_variable_char(c))
+                .unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+        if !name.is_empty() {
+            names.push(name);
+        }
+        rest = after;
+    }
+    names
+}
+
+/// nginx built-in variables (without the `$`).
+const BUILTIN_VARIABLES: &[&str] = &[
+    // ngx_http_core_module
+    "args",
+    "binary_remote_addr",
+    "body_bytes_sent",
+    "bytes_sent",
+    "connection",
+    "connection_requests",
+    "connection_time",
+    "content_length",
+    "content_type",
+    "document_root",
+    "document_uri",
+    "host",
+    "hostname",
+    "https",
+    "is_args",
+    "limit_rate",
+    "msec",
+    "nginx_version",
+    "pid",
+    "pipe",
+    "proxy_protocol_addr",
+    "proxy_protocol_port",
+    "proxy_protocol_server_addr",
+    "proxy_protocol_server_port",
+    "query_string",
+    "realpath_root",
+    "remote_addr",
+    "remote_port",
+    "remote_user",
+    "request",
+    "request_body",
+    "request_body_file",
+    "request_completion",
+    "request_filename",
+    "request_id",
+    "request_length",
+    "request_method",
+    "request_time",
+    "request_uri",
+    "scheme",
+    "server_addr",
+    "server_name",
+    "server_port",
+    "server_protocol",
+    "status",
+    "tcpinfo_rtt",
+    "tcpinfo_rttvar",
+    "tcpinfo_snd_cwnd",
+    "tcpinfo_rcv_space",
+    "time_iso8601",
+    "time_local",
+    "uri",
+    // Other http modules
+    "ancient_browser",
+    "connections_active",
+    "connections_reading",
+    "connections_waiting",
+    "connections_writing",
+    "date_gmt",
+    "date_local",
+    "fastcgi_path_info",
+    "fastcgi_script_name",
+    "geoip_area_code",
+    "geoip_city",
+    "geoip_city_continent_code",
+    "geoip_city_country_code",
+    "geoip_city_country_code3",
+    "geoip_city_country_name",
+    "geoip_country_code",
+    "geoip_country_code3",
+    "geoip_country_name",
+    "geoip_dma_code",
+    "geoip_latitude",
+    "geoip_longitude",
+    "geoip_org",
+    "geoip_postal_code",
+    "geoip_region",
+    "geoip_region_name",
+    "gzip_ratio",
+    "http2",
+    "http3",
+    "invalid_referer",
+    "limit_conn_status",
+    "limit_req_status",
+    "memcached_key",
+    "modern_browser",
+    "msie",
+    "proxy_add_x_forwarded_for",
+    "proxy_host",
+    "proxy_port",
+    "quic",
+    "realip_remote_addr",
+    "realip_remote_port",
+    "secure_link",
+    "secure_link_expires",
+    "slice_range",
+    "spdy",
+    "spdy_request_priority",
+    "uid_got",
+    "uid_reset",
+    "uid_set",
+    "upstream_addr",
+    "upstream_bytes_received",
+    "upstream_bytes_sent",
+    "upstream_cache_status",
+    "upstream_connect_time",
+    "upstream_first_byte_time",
+    "upstream_header_time",
+    "upstream_last_server_name",
+    "upstream_queue_time",
+    "upstream_response_length",
+    "upstream_response_time",
+    "upstream_session_time",
+    "upstream_status",
+    // ngx_http_ssl_module / ngx_stream_ssl_module
+    "ssl_alpn_protocol",
+    "ssl_cipher",
+    "ssl_ciphers",
+    "ssl_client_cert",
+    "ssl_client_escaped_cert",
+    "ssl_client_fingerprint",
+    "ssl_client_i_dn",
+    "ssl_client_i_dn_legacy",
+    "ssl_client_raw_cert",
+    "ssl_client_s_dn",
+    "ssl_client_s_dn_legacy",
+    "ssl_client_serial",
+    "ssl_client_v_end",
+    "ssl_client_v_remain",
+    "ssl_client_v_start",
+    "ssl_client_verify",
+    "ssl_curve",
+    "ssl_curves",
+    "ssl_early_data",
+    "ssl_preread_alpn_protocols",
+    "ssl_preread_protocol",
+    "ssl_preread_server_name",
+    "ssl_protocol",
+    "ssl_server_name",
+    "ssl_session_id",
+    "ssl_session_reused",
+    // ngx_stream_core_module
+    "bytes_received",
+    "session_time",
+    "protocol",
+];
+
+/// Prefixes of built-in variable families (`$http_user_agent`, `$arg_id`, ...).
+const BUILTIN_PREFIXES: &[&str] = &[
+    "arg_",
+    "cookie_",
+    "http_",
+    "jwt_claim_",
+    "jwt_header_",
+    "proxy_protocol_tlv_",
+    "sent_http_",
+    "sent_trailer_",
+    "upstream_cookie_",
+    "upstream_http_",
+    "upstream_trailer_",
+];
+
+/// Whether `name` (without `$`) is provided by nginx itself: a core or
+/// module variable, a member of a prefixed family such as `http_*`, or a
+/// numbered regex capture (`$1`).
+pub fn is_builtin_variable(name: &str) -> bool {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_digit()) {
+        return true;
+    }
+    let lower = name.to_ascii_lowercase();
+    BUILTIN_VARIABLES.contains(&lower.as_str())
+        || BUILTIN_PREFIXES
+            .iter()
+            .any(|p| lower.starts_with(p) && lower.len() > p.len())
+}
+
+/// Prefix of the variables that template placeholders are replaced with
+/// before linting (`nginx-lint --template-mode`), e.g. `${template_PORT}`
+/// for envsubst's `${PORT}`.
+pub const TEMPLATE_VARIABLE_PREFIX: &str = "template_";
+
+/// Whether `name` (without `$`) stands for a template placeholder, see
+/// [`TEMPLATE_VARIABLE_PREFIX`]. Its value is filled in when the template is
+/// rendered, so it is never defined in the configuration.
+pub fn is_template_variable(name: &str) -> bool {
+    name.len() > TEMPLATE_VARIABLE_PREFIX.len() && name.starts_with(TEMPLATE_VARIABLE_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_string;
+
+    fn table(source: &str) -> VariableTable {
+        VariableTable::from_config(&parse_string(source).unwrap())
+    }
+
+    #[test]
+    fn test_variable_references() {
+        assert_eq!(variable_references("$a-${b}c $"), vec!["a", "b"]);
+        assert_eq!(variable_references("no vars"), Vec::<&str>::new());
+        assert_eq!(
+            variable_references("http://$host$request_uri"),
+            vec!["host", "request_uri"]
+        );
+    }
+
+    #[test]
+    fn test_set_definition_and_use() {
+        let t = table("server {\n    set $backend app;\n    proxy_pass http://$backend;\n}\n");
+        let def = t.definition("backend").unwrap();
+        assert_eq!(def.source, VariableSource::Set);
+        assert_eq!(def.line, 2);
+        assert!(t.is_used("backend"));
+    }
+
+    #[test]
+    fn test_map_geo_split_clients() {
+        let t = table(
+            r#"
+map $uri $is_api { default 0; ~^/api/ 1; }
+geo $remote_addr $internal { default 0; 10.0.0.0/8 1; }
+geo $office { default 0; }
+split_clients "${remote_addr}AAA" $variant { 50% a; * b; }
+"#,
+        );
+        for (name, source) in [
+            ("is_api", VariableSource::Map),
+            ("internal", VariableSource::Geo),
+            ("office", VariableSource::Geo),
+            ("variant", VariableSource::SplitClients),
+        ] {
+            assert_eq!(t.definition(name).map(|d| d.source), Some(source), "{name}");
+        }
+        let used: Vec<_> = t.uses().iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(used, vec!["uri", "remote_addr", "remote_addr"]);
+    }
+
+    #[test]
+    fn test_map_values_are_uses() {
+        let t = table("map $host $target { default $scheme://$host; }\n");
+        let used: Vec<_> = t.uses().iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(used, vec!["host", "scheme", "host"]);
+    }
+
+    #[test]
+    fn test_regex_named_captures() {
+        let t = table(
+            "server {\n    server_name ~^(?<sub>.+)\\.example\\.com$;\n    location ~ ^/(?P<slug>[^/]+)$ {\n        return 200 \"$sub $slug\";\n    }\n}\n",
+        );
+        assert_eq!(
+            t.definition("sub").map(|d| d.source),
+            Some(VariableSource::RegexCapture)
+        );
+        assert!(t.is_defined("slug"));
+        // `$` regex anchors are not variable uses
+        let used: Vec<_> = t.uses().iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(used, vec!["sub", "slug"]);
+    }
+
+    #[test]
+    fn test_auth_request_set() {
+        let t = table(
+            "location / {\n    auth_request /auth;\n    auth_request_set $user $upstream_http_x_user;\n    proxy_set_header X-User $user;\n}\n",
+        );
+        assert_eq!(
+            t.definition("user").map(|d| d.source),
+            Some(VariableSource::AuthRequestSet)
+        );
+        assert!(t.is_used("upstream_http_x_user"));
+    }
+
+    #[test]
+    fn test_lua_block_counts_as_use() {
+        let t = table(
+            "location / {\n    set $token '';\n    content_by_lua_block {\n        ngx.say(ngx.var.token)\n    }\n}\n",
+        );
+        assert!(t.is_used("token"));
+    }
+
+    #[test]
+    fn test_builtin_variables() {
+        assert!(is_builtin_variable("host"));
+        assert!(is_builtin_variable("http_user_agent"));
+        assert!(is_builtin_variable("HTTP_X_Forwarded_For"));
+        assert!(is_builtin_variable("1"));
+        assert!(is_builtin_variable("proxy_protocol_tlv_0xEA"));
+        assert!(!is_builtin_variable("http_"));
+        assert!(!is_builtin_variable("my_var"));
+    }
+}
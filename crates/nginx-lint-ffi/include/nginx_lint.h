@@ -0,0 +1,34 @@
+/*
+ * C bindings for nginx-lint.
+ *
+ * Link against libnginx_lint_ffi (built with `cargo build -p nginx-lint-ffi --release`).
+ */
+#ifndef NGINX_LINT_H
+#define NGINX_LINT_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Lint the nginx configuration `source` and return the diagnostics as a
+ * JSON document with the shape of `nginx-lint --format json`, or
+ * {"error": "<message>"} if the arguments are invalid.
+ *
+ * `options` is a JSON object with the fields `config`, `rules`,
+ * `only_rules`, `context` and `nginx_version`, or NULL for the defaults.
+ *
+ * The returned string must be freed with nginx_lint_string_free().
+ */
+char *nginx_lint_check(const char *source, const char *options);
+
+/*
+ * Free a string returned by nginx_lint_check(). NULL is ignored.
+ */
+void nginx_lint_string_free(char *ptr);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* NGINX_LINT_H */
@@ -0,0 +1,244 @@
+//! C bindings for nginx-lint.
+//!
+//! For configuration-management tools written in other languages (Ansible
+//! modules in Python, Terraform providers in Go) that would rather load a
+//! library than run the `nginx-lint` binary. The library is built as a
+//! `cdylib` and a `staticlib`; `include/nginx_lint.h` declares its
+//! functions:
+//!
+//! - [`nginx_lint_check`] — lint a configuration given as a string and
+//!   return the diagnostics as JSON
+//! - [`nginx_lint_string_free`] — free a string returned by the library
+//!
+//! # Output
+//!
+//! The JSON document has the shape of the CLI's `--format json` output:
+//!
+//! ```json
+//! {
+//!   "file": "<string>",
+//!   "errors": [{ "rule": "server-tokens-enabled", "category": "security", ... }],
+//!   "summary": { "errors": 0, "warnings": 1, "ignored": 0 }
+//! }
+//! ```
+//!
+//! If the arguments are invalid, the document is `{"error": "<message>"}`
+//! instead.
+//!
+//! # Options
+//!
+//! The options are a JSON object, or `NULL` for the defaults. Every field
+//! is optional:
+//!
+//! | Field | Type | Meaning |
+//! |-------|------|---------|
+//! | `config` | string | Content of a `.nginx-lint.toml` |
+//! | `rules` | object | Rule name to `true`/`false`, enabling or disabling it |
+//! | `only_rules` | array of strings | Run only these rules (`--rule-only`) |
+//! | `context` | string | Enclosing blocks, e.g. `"http,server"` (`--context`) |
+//! | `nginx_version` | string | Target nginx version, e.g. `"1.26.0"` |
+
+use nginx_lint::{LintError, LintOptions, LintSettings, Severity, lint_str};
+use serde::Serialize;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// The document returned by [`nginx_lint_check`], as in `--format json`
+#[derive(Serialize)]
+struct Report {
+    file: String,
+    errors: Vec<LintError>,
+    summary: Summary,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    errors: usize,
+    warnings: usize,
+    ignored: usize,
+}
+
+/// Lint `source` with the JSON `options` and serialize the result
+fn check(source: &str, options: Option<&str>) -> Result<String, String> {
+    let settings: LintSettings = match options {
+        Some(json) => serde_json::from_str(json).map_err(|e| format!("invalid options: {}", e))?,
+        None => LintSettings::default(),
+    };
+    let report = lint_str(source, &LintOptions::try_from(settings)?);
+    let file = report
+        .files
+        .into_iter()
+        .next()
+        .expect("lint_str reports one file");
+    let count = |severity| {
+        file.errors
+            .iter()
+            .filter(|e| e.severity == severity)
+            .count()
+    };
+    let summary = Summary {
+        errors: count(Severity::Error),
+        warnings: count(Severity::Warning),
+        ignored: file.ignored_count,
+    };
+    let report = Report {
+        file: file.path.display().to_string(),
+        errors: file.errors,
+        summary,
+    };
+    serde_json::to_string(&report).map_err(|e| e.to_string())
+}
+
+/// The `str` behind a C string argument, `None` for `NULL`
+///
+/// # Safety
+///
+/// `ptr` must be `NULL` or point to a NUL-terminated string.
+unsafe fn arg<'a>(name: &str, ptr: *const c_char) -> Result<Option<&'a str>, String> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    // SAFETY: guaranteed by the caller
+    let arg = unsafe { CStr::from_ptr(ptr) };
+    arg.to_str()
+        .map(Some)
+        .map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+/// Lint the nginx configuration `source` and return the diagnostics as a
+/// JSON document (see the [crate documentation](crate)).
+///
+/// `options` is a JSON object with the options, or `NULL` for the
+/// defaults. The returned string must be freed with
+/// [`nginx_lint_string_free`].
+///
+/// # Safety
+///
+/// `source` and `options` must each be `NULL` or point to a NUL-terminated
+/// string that stays valid for the duration of the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nginx_lint_check(
+    source: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: guaranteed by the caller
+        let source = unsafe { arg("source", source) }?.ok_or("source is NULL")?;
+        // SAFETY: guaranteed by the caller
+        let options = unsafe { arg("options", options) }?;
+        check(source, options)
+    }))
+    .unwrap_or_else(|_| Err("internal error while linting".to_string()));
+
+    let json = result.unwrap_or_else(|message| serde_json::json!({ "error": message }).to_string());
+    // JSON escapes control characters, so the document has no NUL byte
+    CString::new(json)
+        .expect("JSON contains no NUL byte")
+        .into_raw()
+}
+
+/// Free a string returned by [`nginx_lint_check`]. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `ptr` must be `NULL` or a string returned by this library that has not
+/// been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nginx_lint_string_free(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        // SAFETY: the string was created by `CString::into_raw`
+        drop(unsafe { CString::from_raw(ptr) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn call(source: Option<&str>, options: Option<&str>) -> Value {
+        let source = source.map(|s| CString::new(s).unwrap());
+        let options = options.map(|s| CString::new(s).unwrap());
+        let as_ptr = |s: &Option<CString>| s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
+        unsafe {
+            let result = nginx_lint_check(as_ptr(&source), as_ptr(&options));
+            let json = CStr::from_ptr(result).to_str().unwrap().to_string();
+            nginx_lint_string_free(result);
+            serde_json::from_str(&json).unwrap()
+        }
+    }
+
+    fn rules(report: &Value) -> Vec<&str> {
+        report["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["rule"].as_str().unwrap())
+            .collect()
+    }
+
+    const SOURCE: &str = "http {\n    server_tokens on;\n}\n";
+
+    #[test]
+    fn test_check_defaults() {
+        let report = call(Some(SOURCE), None);
+        assert_eq!(report["file"], "<string>");
+        assert!(rules(&report).contains(&"server-tokens-enabled"));
+        assert_eq!(
+            report["summary"]["warnings"].as_u64().unwrap() as usize
+                + report["summary"]["errors"].as_u64().unwrap() as usize,
+            rules(&report).len()
+        );
+    }
+
+    #[test]
+    fn test_check_options() {
+        let report = call(
+            Some(SOURCE),
+            Some(r#"{"only_rules": ["server-tokens-enabled"]}"#),
+        );
+        assert_eq!(rules(&report), ["server-tokens-enabled"]);
+        assert_eq!(report["errors"][0]["line"], 2);
+
+        let report = call(
+            Some(SOURCE),
+            Some(
+                r#"{"only_rules": ["server-tokens-enabled"], "rules": {"server-tokens-enabled": false}}"#,
+            ),
+        );
+        assert!(rules(&report).is_empty());
+
+        let report = call(
+            Some(SOURCE),
+            Some(
+                r#"{"only_rules": ["server-tokens-enabled"], "config": "[rules.server-tokens-enabled]\nenabled = false\n"}"#,
+            ),
+        );
+        assert!(rules(&report).is_empty());
+
+        let report = call(
+            Some("location / {\n    proxy_pass http://backend;\n}\n"),
+            Some(r#"{"only_rules": ["invalid-directive-context"], "context": "http,server"}"#),
+        );
+        assert!(rules(&report).is_empty());
+    }
+
+    #[test]
+    fn test_check_invalid_arguments() {
+        assert_eq!(call(None, None)["error"], "source is NULL");
+        let report = call(Some(SOURCE), Some(r#"{"unknown": 1}"#));
+        assert!(
+            report["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("invalid options: unknown field `unknown`")
+        );
+        let report = call(Some(SOURCE), Some(r#"{"nginx_version": "latest"}"#));
+        assert!(
+            report["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("invalid nginx_version: ")
+        );
+    }
+}
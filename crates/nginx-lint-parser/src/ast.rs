@@ -31,6 +31,9 @@
 //! assert_eq!(dir.first_arg(), Some("auto"));
 //! ```
 
+use crate::context::IncludeContext;
+use crate::format::{FormatStyle, Formatter};
+#[cfg(feature = "serde-ast")]
 use serde::{Deserialize, Serialize};
 
 /// A position (line, column, byte offset) in the source text.
@@ -40,7 +43,8 @@ use serde::{Deserialize, Serialize};
 ///
 /// **Note:** `column` is byte-based (not character-based), so for non-ASCII text
 /// the column value may be larger than the visible character count.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub struct Position {
     /// 1-based line number.
     pub line: usize,
@@ -63,7 +67,8 @@ impl Position {
 /// A half-open source range defined by a start and end [`Position`].
 ///
 /// `start` is inclusive, `end` is exclusive (one past the last character).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub struct Span {
     /// Inclusive start position.
     pub start: Position,
@@ -82,21 +87,25 @@ impl Span {
 /// Use [`directives()`](Config::directives) for top-level directives only, or
 /// [`all_directives()`](Config::all_directives) to recurse into blocks.
 /// Call [`to_source()`](Config::to_source) to reconstruct the source text.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub struct Config {
     /// Top-level items (directives, comments, blank lines).
     pub items: Vec<ConfigItem>,
     /// Context from parent file when this config was included
-    /// Empty for root file, e.g., ["http", "server"] for a file included in server block
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub include_context: Vec<String>,
+    /// Empty for root file, e.g., `http`, `server` for a file included in server block
+    #[cfg_attr(
+        feature = "serde-ast",
+        serde(default, skip_serializing_if = "IncludeContext::is_empty")
+    )]
+    pub include_context: IncludeContext,
 }
 
 impl Config {
     pub fn new() -> Self {
         Self {
             items: Vec::new(),
-            include_context: Vec::new(),
+            include_context: IncludeContext::new(),
         }
     }
 
@@ -119,12 +128,15 @@ impl Config {
     /// the parent block stack and nesting depth. The `include_context` is used as the initial
     /// parent context.
     pub fn all_directives_with_context(&self) -> crate::context::AllDirectivesWithContextIter<'_> {
-        crate::context::AllDirectivesWithContextIter::new(&self.items, self.include_context.clone())
+        crate::context::AllDirectivesWithContextIter::new(
+            &self.items,
+            self.include_context.to_names(),
+        )
     }
 
     /// Check if this config is included from within a specific context.
     pub fn is_included_from(&self, context: &str) -> bool {
-        self.include_context.iter().any(|c| c == context)
+        self.include_context.contains(context)
     }
 
     /// Check if this config is included from within `http` context.
@@ -134,18 +146,18 @@ impl Config {
 
     /// Check if this config is included from within `http > server` context.
     pub fn is_included_from_http_server(&self) -> bool {
-        let ctx = &self.include_context;
-        ctx.iter().any(|c| c == "http")
-            && ctx.iter().any(|c| c == "server")
-            && ctx.iter().position(|c| c == "http") < ctx.iter().position(|c| c == "server")
+        let ctx = self.include_context.names();
+        ctx.clone().any(|c| c == "http")
+            && ctx.clone().any(|c| c == "server")
+            && ctx.clone().position(|c| c == "http") < ctx.clone().position(|c| c == "server")
     }
 
     /// Check if this config is included from within `http > ... > location` context.
     pub fn is_included_from_http_location(&self) -> bool {
-        let ctx = &self.include_context;
-        ctx.iter().any(|c| c == "http")
-            && ctx.iter().any(|c| c == "location")
-            && ctx.iter().position(|c| c == "http") < ctx.iter().position(|c| c == "location")
+        let ctx = self.include_context.names();
+        ctx.clone().any(|c| c == "http")
+            && ctx.clone().any(|c| c == "location")
+            && ctx.clone().position(|c| c == "http") < ctx.clone().position(|c| c == "location")
     }
 
     /// Check if this config is included from within `stream` context.
@@ -155,7 +167,7 @@ impl Config {
 
     /// Get the immediate parent context (last element in include_context).
     pub fn immediate_parent_context(&self) -> Option<&str> {
-        self.include_context.last().map(|s| s.as_str())
+        self.include_context.last().map(|f| f.name.as_str())
     }
 
     /// Reconstruct source code from AST (for autofix)
@@ -166,10 +178,28 @@ impl Config {
         }
         output
     }
+
+    /// Print the config in canonical form with the given style
+    ///
+    /// Unlike [`to_source`](Config::to_source), the original whitespace and
+    /// argument spelling are discarded: every argument is rendered by
+    /// [`Argument::to_source`]. Use [`format_config`](crate::format::format_config)
+    /// to reformat a file while keeping its arguments as written.
+    pub fn to_source_formatted(&self, style: &FormatStyle) -> String {
+        let formatter = Formatter {
+            style,
+            source: None,
+            canonical: true,
+        };
+        let mut output = String::new();
+        formatter.write_items(&mut output, &self.items, 0);
+        output
+    }
 }
 
 /// An item in the configuration (directive, comment, or blank line).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub enum ConfigItem {
     /// A directive, possibly with a block (e.g. `listen 80;` or `server { … }`).
     Directive(Box<Directive>),
@@ -198,24 +228,26 @@ impl ConfigItem {
 }
 
 /// A blank line (may contain only whitespace)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub struct BlankLine {
     pub span: Span,
     /// Content of the line (whitespace only, for trailing whitespace detection)
-    #[serde(default)]
+    #[cfg_attr(feature = "serde-ast", serde(default))]
     pub content: String,
 }
 
 /// A comment (# ...)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub struct Comment {
     pub text: String, // Includes the '#' character
     pub span: Span,
     /// Leading whitespace before the comment (for indentation checking)
-    #[serde(default)]
+    #[cfg_attr(feature = "serde-ast", serde(default))]
     pub leading_whitespace: String,
     /// Trailing whitespace after the comment text (for trailing-whitespace detection)
-    #[serde(default)]
+    #[cfg_attr(feature = "serde-ast", serde(default))]
     pub trailing_whitespace: String,
 }
 
@@ -224,7 +256,8 @@ pub struct Comment {
 ///
 /// The [`span`](Directive::span) covers the entire directive from the first
 /// character of the name to the terminating `;` or closing `}`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub struct Directive {
     /// Directive name (e.g. `"server"`, `"listen"`, `"more_set_headers"`).
     pub name: String,
@@ -239,13 +272,13 @@ pub struct Directive {
     /// Optional comment at the end of the directive line.
     pub trailing_comment: Option<Comment>,
     /// Leading whitespace before the directive name (for indentation checking)
-    #[serde(default)]
+    #[cfg_attr(feature = "serde-ast", serde(default))]
     pub leading_whitespace: String,
     /// Whitespace before the terminator (; or {)
-    #[serde(default)]
+    #[cfg_attr(feature = "serde-ast", serde(default))]
     pub space_before_terminator: String,
     /// Trailing whitespace after the terminator (; or {) to end of line
-    #[serde(default)]
+    #[cfg_attr(feature = "serde-ast", serde(default))]
     pub trailing_whitespace: String,
 }
 
@@ -265,6 +298,75 @@ impl Directive {
         self.first_arg() == Some(value)
     }
 
+    /// The arguments as nginx reads them, joining adjacent arguments
+    ///
+    /// The parser splits a token such as `https://$host$request_uri` into
+    /// one argument per literal and variable part; arguments with no space
+    /// between them are joined back into one word. Words are written as in
+    /// [`Argument::raw`], quotes included:
+    ///
+    /// ```
+    /// use nginx_lint_parser::parse_string;
+    ///
+    /// let config = parse_string("return 301 https://$host$request_uri;").unwrap();
+    /// let dir = config.directives().next().unwrap();
+    ///
+    /// assert_eq!(dir.words(), ["301", "https://$host$request_uri"]);
+    /// ```
+    pub fn words(&self) -> Vec<String> {
+        let mut words: Vec<String> = Vec::new();
+        let mut prev_end = None;
+        for arg in &self.args {
+            match words.last_mut() {
+                Some(word) if prev_end == Some(arg.span.start.offset) => word.push_str(&arg.raw),
+                _ => words.push(arg.raw.clone()),
+            }
+            prev_end = Some(arg.span.end.offset);
+        }
+        words
+    }
+
+    /// Indentation of the line this directive starts on
+    ///
+    /// This is [`leading_whitespace`](Directive::leading_whitespace) as
+    /// written, tabs included, when the directive starts its line. A directive
+    /// that follows other text on its line (`server { listen 80; }`) gets
+    /// spaces up to its column.
+    pub fn line_indent(&self) -> String {
+        let column = self.span.start.column.saturating_sub(1);
+        if self.leading_whitespace.len() == column {
+            self.leading_whitespace.clone()
+        } else {
+            " ".repeat(column)
+        }
+    }
+
+    /// Print this directive in canonical form, e.g. for fix text
+    ///
+    /// Arguments are rendered by [`Argument::to_source`] and a block is printed
+    /// with the default [`FormatStyle`]. The result has no indentation, trailing
+    /// comment or final newline:
+    ///
+    /// ```
+    /// use nginx_lint_parser::parse_string;
+    ///
+    /// let config = parse_string("add_header   X-Frame-Options  'DENY' always;").unwrap();
+    /// let dir = config.directives().next().unwrap();
+    ///
+    /// assert_eq!(dir.to_source(), "add_header X-Frame-Options 'DENY' always;");
+    /// ```
+    pub fn to_source(&self) -> String {
+        let style = FormatStyle::default();
+        let formatter = Formatter {
+            style: &style,
+            source: None,
+            canonical: true,
+        };
+        let mut output = String::new();
+        formatter.write_directive_body(&mut output, self, 0, None);
+        output
+    }
+
     fn write_source(&self, output: &mut String, indent: usize) {
         // Use stored leading whitespace if available, otherwise calculate
         let indent_str = if !self.leading_whitespace.is_empty() {
@@ -280,7 +382,15 @@ impl Directive {
             output.push_str(&arg.raw);
         }
 
-        if let Some(block) = &self.block {
+        if let Some(block) = &self.block
+            && let Some(raw_content) = &block.raw_content
+        {
+            output.push_str(&self.space_before_terminator);
+            output.push('{');
+            output.push_str(raw_content);
+            output.push('}');
+            output.push_str(&block.trailing_whitespace);
+        } else if let Some(block) = &self.block {
             output.push_str(&self.space_before_terminator);
             output.push('{');
             output.push_str(&self.trailing_whitespace);
@@ -316,22 +426,25 @@ impl Directive {
 
 /// A brace-delimited block (`{ … }`).
 ///
-/// For Lua blocks (e.g. `content_by_lua_block`), the content is stored verbatim
-/// in [`raw_content`](Block::raw_content) instead of being parsed as directives.
+/// For raw blocks (e.g. `content_by_lua_block`, see
+/// [`is_raw_block_directive`](crate::is_raw_block_directive)), the source text
+/// between the braces is stored verbatim in [`raw_content`](Block::raw_content)
+/// instead of being parsed as directives.
 /// Use [`is_raw()`](Block::is_raw) to check.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub struct Block {
     /// Parsed items inside the block (empty for raw blocks).
     pub items: Vec<ConfigItem>,
     /// Span from `{` to `}` (inclusive of both braces).
     pub span: Span,
-    /// Raw content for special blocks like *_by_lua_block (Lua code)
+    /// Source text between the braces of a raw block like `*_by_lua_block`
     pub raw_content: Option<String>,
     /// Leading whitespace before closing brace (for indentation checking)
-    #[serde(default)]
+    #[cfg_attr(feature = "serde-ast", serde(default))]
     pub closing_brace_leading_whitespace: String,
     /// Trailing whitespace after closing brace (for trailing-whitespace detection)
-    #[serde(default)]
+    #[cfg_attr(feature = "serde-ast", serde(default))]
     pub trailing_whitespace: String,
 }
 
@@ -354,7 +467,8 @@ impl Block {
 ///
 /// Use [`as_str()`](Argument::as_str) to get the logical value (without quotes),
 /// or inspect [`raw`](Argument::raw) for the original source text.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub struct Argument {
     /// Parsed argument value (see [`ArgumentValue`] for variants).
     pub value: ArgumentValue,
@@ -412,10 +526,65 @@ impl Argument {
     pub fn is_single_quoted(&self) -> bool {
         matches!(self.value, ArgumentValue::SingleQuotedString(_))
     }
+
+    /// Render the argument as nginx source text from its [`value`](Argument::value)
+    ///
+    /// Quoted strings are re-quoted and escaped so that parsing the result
+    /// yields the same value, and literals that would not survive unquoted
+    /// (empty, or containing whitespace, `;`, `{`, `}`, `#` or quotes) are
+    /// double-quoted. Variables are rendered as `$name`.
+    pub fn to_source(&self) -> String {
+        match &self.value {
+            ArgumentValue::Literal(s) if needs_quotes(s) => quote(s, '"'),
+            ArgumentValue::Literal(s) => s.clone(),
+            ArgumentValue::QuotedString(s) => quote(s, '"'),
+            ArgumentValue::SingleQuotedString(s) => quote(s, '\''),
+            ArgumentValue::Variable(s) => format!("${}", s),
+        }
+    }
+}
+
+fn needs_quotes(s: &str) -> bool {
+    s.is_empty()
+        || s.chars()
+            .any(|c| c.is_whitespace() || matches!(c, ';' | '{' | '}' | '#' | '"' | '\''))
+}
+
+/// Quote `s`, escaping whatever the lexer would otherwise unescape or end the
+/// string on
+fn quote(s: &str, quote_char: char) -> String {
+    // A backslash is doubled when the lexer would otherwise read it as the
+    // start of an escape (including the ones emitted below for control chars)
+    let unescaped: &[char] = if quote_char == '"' {
+        &['n', 't', 'r', '\\', '"', '$', '\n', '\t', '\r']
+    } else {
+        &['\\', '\'']
+    };
+    let mut output = String::with_capacity(s.len() + 2);
+    output.push(quote_char);
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek().is_none_or(|next| unescaped.contains(next)) => {
+                output.push_str("\\\\")
+            }
+            c if c == quote_char => {
+                output.push('\\');
+                output.push(c);
+            }
+            '\n' if quote_char == '"' => output.push_str("\\n"),
+            '\t' if quote_char == '"' => output.push_str("\\t"),
+            '\r' if quote_char == '"' => output.push_str("\\r"),
+            c => output.push(c),
+        }
+    }
+    output.push(quote_char);
+    output
 }
 
 /// The kind and value of a directive argument.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
 pub enum ArgumentValue {
     /// Unquoted literal (e.g. `on`, `off`, `80`, `/path/to/file`).
     Literal(String),
@@ -536,7 +705,7 @@ mod tests {
                     trailing_whitespace: String::new(),
                 })),
             ],
-            include_context: Vec::new(),
+            include_context: IncludeContext::new(),
         };
 
         let names: Vec<&str> = config.all_directives().map(|d| d.name.as_str()).collect();
@@ -568,4 +737,92 @@ mod tests {
         assert!(directive.args[0].is_on());
         assert!(!directive.args[0].is_off());
     }
+
+    fn first_directive(source: &str) -> Directive {
+        let config = crate::parse_string(source).unwrap();
+        config.directives().next().unwrap().clone()
+    }
+
+    #[test]
+    fn test_argument_to_source_round_trips() {
+        let directive = first_directive(
+            r#"log_format main "say \"hi\"\n" 'it\'s' "C:\\dir\\" "a\qb" "" $host;"#,
+        );
+        let rendered = directive.to_source();
+        // `\d` and `\q` are not escapes, so their backslash stays single
+        assert_eq!(
+            rendered,
+            r#"log_format main "say \"hi\"\n" 'it\'s' "C:\dir\\" "a\qb" "" $host;"#
+        );
+
+        let reparsed = first_directive(&rendered);
+        let values = |d: &Directive| {
+            d.args
+                .iter()
+                .map(|a| a.as_str().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(&reparsed), values(&directive));
+    }
+
+    #[test]
+    fn test_argument_to_source_quotes_unsafe_literals() {
+        let arg = |value: &str| Argument {
+            value: ArgumentValue::Literal(value.to_string()),
+            span: Span::default(),
+            raw: String::new(),
+        };
+        assert_eq!(arg("on").to_source(), "on");
+        assert_eq!(arg("").to_source(), r#""""#);
+        assert_eq!(arg("a b").to_source(), r#""a b""#);
+        assert_eq!(arg("a;b").to_source(), r#""a;b""#);
+        assert_eq!(arg("line\nbreak").to_source(), r#""line\nbreak""#);
+    }
+
+    #[test]
+    fn test_directive_to_source_joins_adjacent_arguments() {
+        let directive = first_directive(r#"proxy_set_header Authorization "Bearer "$token;"#);
+        assert_eq!(
+            directive.to_source(),
+            r#"proxy_set_header Authorization "Bearer "$token;"#
+        );
+
+        let directive = first_directive("set $path ${uri}_suffix;");
+        assert_eq!(directive.to_source(), "set $path ${uri}_suffix;");
+    }
+
+    #[test]
+    fn test_directive_to_source_block() {
+        let directive = first_directive("location /  {  # root\n  root   /var/www;\n\n\n}");
+        assert_eq!(
+            directive.to_source(),
+            "location / { # root\n    root /var/www;\n}"
+        );
+    }
+
+    #[test]
+    fn test_directive_line_indent() {
+        let config = crate::parse_string("http {\n\tserver { listen 80; }\n}\n").unwrap();
+        let server = config.all_directives().find(|d| d.is("server")).unwrap();
+        let listen = config.all_directives().find(|d| d.is("listen")).unwrap();
+        assert_eq!(server.line_indent(), "\t");
+        // Follows `server {` on the same line, so only the column is known
+        assert_eq!(listen.line_indent(), " ".repeat(10));
+    }
+
+    #[test]
+    fn test_config_to_source_formatted() {
+        let config = crate::parse_string(
+            "server {\nlisten 80;\ncontent_by_lua_block {\nlocal x = 1\n}\n}\n",
+        )
+        .unwrap();
+        let style = FormatStyle {
+            indent_width: 2,
+            ..FormatStyle::default()
+        };
+        assert_eq!(
+            config.to_source_formatted(&style),
+            "server {\n  listen 80;\n  content_by_lua_block {\n    local x = 1\n  }\n}\n"
+        );
+    }
 }
@@ -0,0 +1,271 @@
+//! Programmatic construction of nginx configuration files.
+//!
+//! [`ConfigBuilder`] assembles directives, blocks, comments and blank lines
+//! and prints them with a [`FormatStyle`], so tools that generate
+//! configuration (e.g. one vhost per application) do not need string
+//! templates. Argument values are quoted only when nginx requires it.
+//!
+//! ```
+//! use nginx_lint_parser::builder::ConfigBuilder;
+//!
+//! let source = ConfigBuilder::new()
+//!     .block("server", &[], |server| {
+//!         server
+//!             .directive("listen", &["80"])
+//!             .directive("server_name", &["example.com"])
+//!             .blank_line()
+//!             .comment("Static files")
+//!             .block("location", &["/static/"], |location| {
+//!                 location.directive("root", &["/var/www"])
+//!             })
+//!     })
+//!     .to_source();
+//!
+//! assert_eq!(
+//!     source,
+//!     "server {\n    listen 80;\n    server_name example.com;\n\n    # Static files\n    \
+//!      location /static/ {\n        root /var/www;\n    }\n}\n"
+//! );
+//! ```
+
+use crate::ast::{
+    Argument, ArgumentValue, BlankLine, Block, Comment, Config, ConfigItem, Directive, Position,
+    Span,
+};
+use crate::error::ParseResult;
+use crate::format::{FormatStyle, Formatter};
+
+/// Builder for a [`Config`] or the source text of one.
+///
+/// Blocks are filled by a closure that receives a nested builder. Call
+/// [`to_source`](ConfigBuilder::to_source) for the text, or
+/// [`build`](ConfigBuilder::build) for a parsed [`Config`] whose spans point
+/// into that text.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    items: Vec<ConfigItem>,
+    style: FormatStyle,
+}
+
+impl ConfigBuilder {
+    /// An empty config printed with the default [`FormatStyle`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Print with `style` instead of the default style
+    pub fn with_style(mut self, style: FormatStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Add a simple directive, e.g. `directive("listen", &["80"])`
+    ///
+    /// Arguments that contain whitespace, `;`, braces, `#` or quotes, and
+    /// empty arguments, are double-quoted. `$name` is written as is.
+    pub fn directive(mut self, name: &str, args: &[&str]) -> Self {
+        self.push_directive(name, args, None);
+        self
+    }
+
+    /// Add a block directive whose contents are added by `build`
+    pub fn block(
+        mut self,
+        name: &str,
+        args: &[&str],
+        build: impl FnOnce(ConfigBuilder) -> ConfigBuilder,
+    ) -> Self {
+        let inner = build(ConfigBuilder::new());
+        let block = new_block(inner.items, None);
+        self.push_directive(name, args, Some(block));
+        self
+    }
+
+    /// Add a block whose body is kept as text, e.g. `content_by_lua_block`
+    ///
+    /// Each non-empty line of `content` is printed on its own line, indented
+    /// one level deeper than the directive.
+    pub fn raw_block(mut self, name: &str, args: &[&str], content: &str) -> Self {
+        let block = new_block(Vec::new(), Some(content.to_string()));
+        self.push_directive(name, args, Some(block));
+        self
+    }
+
+    /// Add a comment, one `#` line per line of `text`
+    ///
+    /// A line that already starts with `#` is kept as is.
+    pub fn comment(mut self, text: &str) -> Self {
+        for line in text.lines() {
+            let text = if line.starts_with('#') {
+                line.to_string()
+            } else if line.is_empty() {
+                "#".to_string()
+            } else {
+                format!("# {}", line)
+            };
+            self.items.push(ConfigItem::Comment(Comment {
+                text,
+                // Not the line of the enclosing block's `{` (line 0), where
+                // the printer would keep a leading comment
+                span: Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
+                leading_whitespace: String::new(),
+                trailing_whitespace: String::new(),
+            }));
+        }
+        self
+    }
+
+    /// Add a blank line (runs of blank lines are printed as one)
+    pub fn blank_line(mut self) -> Self {
+        self.items.push(ConfigItem::BlankLine(BlankLine {
+            span: Span::default(),
+            content: String::new(),
+        }));
+        self
+    }
+
+    fn push_directive(&mut self, name: &str, args: &[&str], block: Option<Block>) {
+        self.items
+            .push(ConfigItem::Directive(Box::new(new_directive(
+                name, args, block,
+            ))));
+    }
+
+    /// Print the config
+    pub fn to_source(&self) -> String {
+        let formatter = Formatter {
+            style: &self.style,
+            source: None,
+            canonical: true,
+        };
+        let mut output = String::new();
+        formatter.write_items(&mut output, &self.items, 0);
+        output
+    }
+
+    /// Print the config and parse the result
+    ///
+    /// The returned config's spans refer to [`to_source`](ConfigBuilder::to_source),
+    /// so lint errors reported on it have line numbers in the generated text.
+    pub fn build(&self) -> ParseResult<Config> {
+        crate::parse_string(&self.to_source())
+    }
+}
+
+fn new_directive(name: &str, args: &[&str], block: Option<Block>) -> Directive {
+    Directive {
+        name: name.to_string(),
+        name_span: Span::default(),
+        args: args
+            .iter()
+            .map(|value| {
+                let value = ArgumentValue::Literal(value.to_string());
+                let mut arg = Argument {
+                    value,
+                    span: Span::default(),
+                    raw: String::new(),
+                };
+                arg.raw = arg.to_source();
+                arg
+            })
+            .collect(),
+        block,
+        span: Span::default(),
+        trailing_comment: None,
+        leading_whitespace: String::new(),
+        space_before_terminator: String::new(),
+        trailing_whitespace: String::new(),
+    }
+}
+
+fn new_block(items: Vec<ConfigItem>, raw_content: Option<String>) -> Block {
+    Block {
+        items,
+        span: Span::default(),
+        raw_content,
+        closing_brace_leading_whitespace: String::new(),
+        trailing_whitespace: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotes_arguments_when_needed() {
+        let source = ConfigBuilder::new()
+            .directive(
+                "add_header",
+                &["Content-Security-Policy", "default-src 'self'", "always"],
+            )
+            .directive("set", &["$suffix", ""])
+            .directive("return", &["200", "ok; done"])
+            .to_source();
+
+        assert_eq!(
+            source,
+            "add_header Content-Security-Policy \"default-src 'self'\" always;\n\
+             set $suffix \"\";\n\
+             return 200 \"ok; done\";\n"
+        );
+    }
+
+    #[test]
+    fn test_comments_and_empty_blocks() {
+        let source = ConfigBuilder::new()
+            .comment("Generated\n\n# keep")
+            .block("events", &[], |events| events)
+            .block("http", &[], |http| {
+                http.comment("first").directive("sendfile", &["on"])
+            })
+            .to_source();
+
+        assert_eq!(
+            source,
+            "# Generated\n#\n# keep\nevents {}\nhttp {\n    # first\n    sendfile on;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_raw_block_and_style() {
+        let style = FormatStyle {
+            use_tabs: true,
+            ..FormatStyle::default()
+        };
+        let source = ConfigBuilder::new()
+            .with_style(style)
+            .block("location", &["/lua"], |location| {
+                location.raw_block(
+                    "content_by_lua_block",
+                    &[],
+                    "ngx.say('hi')\n  ngx.exit(200)",
+                )
+            })
+            .to_source();
+
+        assert_eq!(
+            source,
+            "location /lua {\n\tcontent_by_lua_block {\n\t\tngx.say('hi')\n\t\tngx.exit(200)\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_build_parses_generated_source() {
+        let config = ConfigBuilder::new()
+            .block("server", &[], |server| {
+                server
+                    .directive("listen", &["443", "ssl"])
+                    .directive("proxy_set_header", &["Host", "$host"])
+            })
+            .build()
+            .unwrap();
+
+        let header = config
+            .all_directives()
+            .find(|d| d.is("proxy_set_header"))
+            .unwrap();
+        assert_eq!(header.span.start.line, 3);
+        assert!(header.args[1].is_variable());
+    }
+}
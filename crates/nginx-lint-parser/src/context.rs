@@ -5,8 +5,248 @@
 //! block hierarchy (e.g., `["http", "server"]`).
 //!
 //! Obtained via [`Config::all_directives_with_context()`](crate::ast::Config::all_directives_with_context).
+//!
+//! It also defines [`IncludeContext`], the block context a file is included
+//! from (stored in [`Config::include_context`](crate::ast::Config::include_context)).
 
 use crate::ast::{ConfigItem, Directive};
+#[cfg(feature = "serde-ast")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// One enclosing block of an included file, e.g. `location ~ ^/api`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
+pub struct ContextFrame {
+    /// Block directive name (e.g. `"location"`)
+    pub name: String,
+    /// Block arguments, including modifiers (e.g. `["~", "^/api"]`).
+    /// Empty when only the block name is known.
+    #[cfg_attr(
+        feature = "serde-ast",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub args: Vec<String>,
+}
+
+impl ContextFrame {
+    /// A frame with no arguments.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// A frame with arguments.
+    pub fn with_args(name: impl Into<String>, args: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            args,
+        }
+    }
+
+    /// Build a frame from a block directive, keeping its arguments.
+    pub fn from_directive(directive: &Directive) -> Self {
+        Self {
+            name: directive.name.clone(),
+            args: directive.args.iter().map(|a| a.raw.clone()).collect(),
+        }
+    }
+}
+
+impl fmt::Display for ContextFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if !self.args.is_empty() {
+            write!(f, "={}", self.args.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The block context a file is included from, outermost block first.
+///
+/// Empty for a root file. A file included inside `location /api/ { ... }` of
+/// a server has the frames `http`, `server`, `location /api/`.
+///
+/// The textual form (used by `--context` and `# nginx-lint:context`) is a
+/// comma-separated list of frames, where each frame is a block name optionally
+/// followed by `=` and its space-separated arguments. Arguments may be quoted
+/// as in the configuration, and a comma in them only starts a new frame when
+/// a block name follows it:
+///
+/// ```
+/// use nginx_lint_parser::context::IncludeContext;
+///
+/// let ctx = IncludeContext::parse("http, server, location=~ ^/api");
+/// assert_eq!(ctx.names().collect::<Vec<_>>(), ["http", "server", "location"]);
+/// assert_eq!(ctx.last().unwrap().args, ["~", "^/api"]);
+/// assert_eq!(ctx.to_string(), "http,server,location=~ ^/api");
+///
+/// let ctx = IncludeContext::parse("http,server,location=~ ^/v[0-9]{1,2}/");
+/// assert_eq!(ctx.last().unwrap().args, ["~", "^/v[0-9]{1,2}/"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-ast", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-ast", serde(transparent))]
+pub struct IncludeContext {
+    frames: Vec<ContextFrame>,
+}
+
+impl IncludeContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the textual form (see the type docs). Empty frames are skipped.
+    pub fn parse(spec: &str) -> Self {
+        let mut frames: Vec<String> = Vec::new();
+        for piece in split_unquoted(spec, |c| c == ',') {
+            match frames.last_mut() {
+                // A comma in the arguments of the previous frame, e.g. in a
+                // regex quantifier like `{1,2}`
+                Some(frame) if frame.contains('=') && !is_frame_start(piece) => {
+                    frame.push(',');
+                    frame.push_str(piece);
+                }
+                _ => frames.push(piece.to_string()),
+            }
+        }
+        frames
+            .iter()
+            .map(|frame| frame.trim())
+            .filter(|frame| !frame.is_empty())
+            .map(|frame| match frame.split_once('=') {
+                Some((name, args)) => ContextFrame::with_args(
+                    name.trim(),
+                    split_unquoted(args, char::is_whitespace)
+                        .into_iter()
+                        .filter(|arg| !arg.is_empty())
+                        .map(String::from)
+                        .collect(),
+                ),
+                None => ContextFrame::new(frame),
+            })
+            .collect()
+    }
+
+    /// All frames, outermost first.
+    pub fn frames(&self) -> &[ContextFrame] {
+        &self.frames
+    }
+
+    /// Block names, outermost first.
+    pub fn names(&self) -> impl DoubleEndedIterator<Item = &str> + Clone {
+        self.frames.iter().map(|f| f.name.as_str())
+    }
+
+    /// Block names as owned strings (the form used by parent stacks).
+    pub fn to_names(&self) -> Vec<String> {
+        self.frames.iter().map(|f| f.name.clone()).collect()
+    }
+
+    /// Whether any enclosing block has the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.names().any(|n| n == name)
+    }
+
+    /// The innermost enclosing block.
+    pub fn last(&self) -> Option<&ContextFrame> {
+        self.frames.last()
+    }
+
+    /// The innermost enclosing block with the given name.
+    pub fn innermost(&self, name: &str) -> Option<&ContextFrame> {
+        self.frames.iter().rev().find(|f| f.name == name)
+    }
+
+    pub fn push(&mut self, frame: ContextFrame) {
+        self.frames.push(frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// Whether `piece` of a context spec, following a comma, begins a frame: it
+/// is empty or starts with a block name
+fn is_frame_start(piece: &str) -> bool {
+    let name = piece.split('=').next().unwrap_or_default().trim();
+    name.is_empty() || name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split `s` at the characters matching `sep`, except inside single or
+/// double quotes and after a backslash
+fn split_unquoted(s: &str, sep: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+        } else if c == '"' || c == '\'' {
+            quote = Some(c);
+        } else if sep(c) {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+impl fmt::Display for IncludeContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, frame) in self.frames.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}", frame)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<ContextFrame> for IncludeContext {
+    fn from_iter<I: IntoIterator<Item = ContextFrame>>(iter: I) -> Self {
+        Self {
+            frames: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl From<Vec<ContextFrame>> for IncludeContext {
+    fn from(frames: Vec<ContextFrame>) -> Self {
+        Self { frames }
+    }
+}
+
+/// Block names only, without arguments.
+impl From<Vec<String>> for IncludeContext {
+    fn from(names: Vec<String>) -> Self {
+        names.into_iter().map(ContextFrame::new).collect()
+    }
+}
+
+/// Block names only, without arguments.
+impl From<Vec<&str>> for IncludeContext {
+    fn from(names: Vec<&str>) -> Self {
+        names.into_iter().map(ContextFrame::new).collect()
+    }
+}
 
 /// A directive paired with its parent block context.
 ///
@@ -125,10 +365,54 @@ mod tests {
         assert_eq!(contexts[2].depth, 2);
     }
 
+    #[test]
+    fn test_include_context_parse_and_display() {
+        use super::{ContextFrame, IncludeContext};
+
+        let ctx = IncludeContext::parse(" http , server,location=^~ /static/ ,");
+        assert_eq!(
+            ctx.frames(),
+            &[
+                ContextFrame::new("http"),
+                ContextFrame::new("server"),
+                ContextFrame::with_args("location", vec!["^~".into(), "/static/".into()]),
+            ]
+        );
+        assert_eq!(ctx.to_string(), "http,server,location=^~ /static/");
+        assert_eq!(IncludeContext::parse(&ctx.to_string()), ctx);
+        assert!(IncludeContext::parse("").is_empty());
+        assert_eq!(ctx.innermost("server"), Some(&ContextFrame::new("server")));
+    }
+
+    #[test]
+    fn test_include_context_parse_commas_and_quotes_in_args() {
+        use super::{ContextFrame, IncludeContext};
+
+        let ctx = IncludeContext::parse("http,server,location=~ ^/v[0-9]{1,2}/");
+        assert_eq!(
+            ctx.names().collect::<Vec<_>>(),
+            ["http", "server", "location"]
+        );
+        assert_eq!(ctx.last().unwrap().args, ["~", "^/v[0-9]{1,2}/"]);
+        assert_eq!(IncludeContext::parse(&ctx.to_string()), ctx);
+
+        let ctx = IncludeContext::parse(r#"http,server,location=~ "^/a b/(x,y)",if=($a = b)"#);
+        assert_eq!(
+            ctx.frames(),
+            &[
+                ContextFrame::new("http"),
+                ContextFrame::new("server"),
+                ContextFrame::with_args("location", vec!["~".into(), r#""^/a b/(x,y)""#.into()]),
+                ContextFrame::with_args("if", vec!["($a".into(), "=".into(), "b)".into()]),
+            ]
+        );
+        assert_eq!(IncludeContext::parse(&ctx.to_string()), ctx);
+    }
+
     #[test]
     fn test_all_directives_with_context_include_context() {
         let mut config = crate::parse_string("server {\n    listen 80;\n}").unwrap();
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let contexts: Vec<_> = config.all_directives_with_context().collect();
         assert_eq!(contexts.len(), 2);
@@ -151,18 +435,18 @@ mod tests {
         assert!(!config.is_included_from_http());
         assert!(!config.is_included_from_stream());
 
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
         assert!(config.is_included_from_http());
         assert!(config.is_included_from("http"));
         assert!(!config.is_included_from_stream());
         assert_eq!(config.immediate_parent_context(), Some("http"));
 
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
         assert!(config.is_included_from_http());
         assert!(config.is_included_from_http_server());
         assert!(!config.is_included_from_http_location());
 
-        config.include_context = vec!["http".to_string(), "location".to_string()];
+        config.include_context = vec!["http".to_string(), "location".to_string()].into();
         assert!(config.is_included_from_http_location());
     }
 }
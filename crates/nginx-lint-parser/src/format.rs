@@ -0,0 +1,657 @@
+//! Configurable pretty printer for nginx configuration files.
+//!
+//! Unlike [`Config::to_source`], which reproduces the original text, the
+//! formatter rewrites whitespace according to a [`FormatStyle`]: indentation
+//! (width, tabs or spaces), wrapping of long argument lists, placement of the
+//! opening brace of blocks, and alignment of consecutive `proxy_set_header`
+//! values. Comments are kept, runs of blank lines are collapsed to one, and
+//! raw blocks (e.g. `content_by_lua_block`) are copied from the source verbatim.
+//!
+//! ```
+//! use nginx_lint_parser::format::{format_string, FormatStyle};
+//!
+//! let style = FormatStyle {
+//!     indent_width: 2,
+//!     ..FormatStyle::default()
+//! };
+//! let output = format_string("http {\nserver {\nlisten 80;\n}\n}\n", &style).unwrap();
+//! assert_eq!(output, "http {\n  server {\n    listen 80;\n  }\n}\n");
+//! ```
+
+use crate::ast::{Argument, ArgumentValue, Block, Comment, Config, ConfigItem, Directive};
+use crate::error::ParseResult;
+use serde::{Deserialize, Serialize};
+
+/// Placement of the opening brace of a block directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BraceStyle {
+    /// `server {` — brace on the directive line (default)
+    #[default]
+    SameLine,
+    /// Brace on its own line, at the directive's indentation
+    NextLine,
+}
+
+/// Style options for [`format_string`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatStyle {
+    /// Number of columns per nesting level (default: 4)
+    pub indent_width: usize,
+    /// Indent with one tab per level instead of `indent_width` spaces
+    pub use_tabs: bool,
+    /// Wrap directive arguments onto continuation lines when a line would be
+    /// longer than this many columns (`None` disables wrapping)
+    pub max_line_length: Option<usize>,
+    /// Placement of the opening brace of blocks
+    pub brace_style: BraceStyle,
+    /// Pad the header names of consecutive `proxy_set_header` directives so
+    /// their values start in the same column
+    pub align_proxy_set_header: bool,
+}
+
+impl Default for FormatStyle {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            use_tabs: false,
+            max_line_length: None,
+            brace_style: BraceStyle::SameLine,
+            align_proxy_set_header: false,
+        }
+    }
+}
+
+impl FormatStyle {
+    /// Indentation string for the given nesting depth
+    pub fn indent(&self, depth: usize) -> String {
+        if self.use_tabs {
+            "\t".repeat(depth)
+        } else {
+            " ".repeat(self.indent_width * depth)
+        }
+    }
+
+    /// Display width of the indentation for the given nesting depth
+    /// (a tab counts as `indent_width` columns)
+    fn indent_columns(&self, depth: usize) -> usize {
+        self.indent_width * depth
+    }
+}
+
+/// The whitespace making up one level of indentation
+///
+/// Use [`detect`](IndentStyle::detect) to find the style of an existing file,
+/// e.g. to indent the text of a fix like its surroundings:
+///
+/// ```
+/// use nginx_lint_parser::format::IndentStyle;
+/// use nginx_lint_parser::parse_string;
+///
+/// let config = parse_string("http {\n\tserver {\n\t\tlisten 80;\n\t}\n}\n").unwrap();
+/// let style = IndentStyle::detect(&config.items).unwrap();
+///
+/// assert_eq!(style, IndentStyle::Tabs);
+/// assert_eq!(style.indent(2), "\t\t");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// A fixed number of spaces
+    Spaces(usize),
+    /// A single tab
+    Tabs,
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        IndentStyle::Spaces(4)
+    }
+}
+
+impl From<&FormatStyle> for IndentStyle {
+    fn from(style: &FormatStyle) -> Self {
+        if style.use_tabs {
+            IndentStyle::Tabs
+        } else {
+            IndentStyle::Spaces(style.indent_width)
+        }
+    }
+}
+
+impl IndentStyle {
+    /// Detect the style from the first indented item inside a top-level
+    /// block, or `None` if no block has indented contents
+    pub fn detect(items: &[ConfigItem]) -> Option<Self> {
+        for item in items {
+            let ConfigItem::Directive(d) = item else {
+                continue;
+            };
+            let Some(block) = &d.block else {
+                continue;
+            };
+            for inner in &block.items {
+                let ws = match inner {
+                    ConfigItem::Directive(d) => &d.leading_whitespace,
+                    ConfigItem::Comment(c) => &c.leading_whitespace,
+                    ConfigItem::BlankLine(_) => continue,
+                };
+                if ws.starts_with('\t') {
+                    return Some(IndentStyle::Tabs);
+                }
+                if !ws.is_empty() && !ws.contains('\t') {
+                    return Some(IndentStyle::Spaces(ws.len()));
+                }
+            }
+        }
+        None
+    }
+
+    /// Whitespace for one level
+    pub fn unit(&self) -> String {
+        self.indent(1)
+    }
+
+    /// Whitespace for the given nesting depth
+    pub fn indent(&self, depth: usize) -> String {
+        match self {
+            IndentStyle::Spaces(size) => " ".repeat(size * depth),
+            IndentStyle::Tabs => "\t".repeat(depth),
+        }
+    }
+}
+
+/// Parse `source` and print it according to `style`.
+///
+/// Returns an error if the source contains syntax errors.
+pub fn format_string(source: &str, style: &FormatStyle) -> ParseResult<String> {
+    let config = crate::parse_string(source)?;
+    Ok(format_config(&config, source, style))
+}
+
+/// Print a parsed config according to `style`.
+///
+/// `source` must be the text `config` was parsed from; it is used to copy raw
+/// blocks verbatim.
+pub fn format_config(config: &Config, source: &str, style: &FormatStyle) -> String {
+    let formatter = Formatter {
+        style,
+        source: Some(source),
+        canonical: false,
+    };
+    let mut output = String::new();
+    formatter.write_items(&mut output, &config.items, 0);
+    output
+}
+
+/// Printer shared by [`format_config`], [`Config::to_source_formatted`] and
+/// [`Directive::to_source`]
+pub(crate) struct Formatter<'a> {
+    pub(crate) style: &'a FormatStyle,
+    /// Original text, used to copy raw blocks byte for byte (if available)
+    pub(crate) source: Option<&'a str>,
+    /// Render arguments with [`Argument::to_source`](crate::ast::Argument::to_source)
+    /// instead of their original text
+    pub(crate) canonical: bool,
+}
+
+impl Formatter<'_> {
+    pub(crate) fn write_items(&self, output: &mut String, items: &[ConfigItem], depth: usize) {
+        let align_width = self.proxy_set_header_widths(items);
+        let mut pending_blank = false;
+        let mut wrote_any = false;
+        let mut skip_comment_at = None;
+
+        for (i, item) in items.iter().enumerate() {
+            match item {
+                ConfigItem::BlankLine(_) => {
+                    // Collapse runs and drop leading blank lines; trailing
+                    // ones are dropped because nothing follows to flush them
+                    pending_blank = wrote_any;
+                    continue;
+                }
+                ConfigItem::Comment(c) if skip_comment_at == Some(c.span.start.offset) => {
+                    // Already printed as the trailing comment of a block
+                    continue;
+                }
+                _ => {}
+            }
+
+            if pending_blank {
+                output.push('\n');
+                pending_blank = false;
+            }
+            wrote_any = true;
+
+            match item {
+                ConfigItem::Directive(d) => {
+                    self.write_directive(output, d, depth, align_width[i]);
+                    if d.block.is_some() {
+                        skip_comment_at = d.trailing_comment.as_ref().map(|c| c.span.start.offset);
+                    }
+                }
+                ConfigItem::Comment(c) => self.write_comment(output, c, depth),
+                ConfigItem::BlankLine(_) => unreachable!(),
+            }
+        }
+    }
+
+    fn write_comment(&self, output: &mut String, comment: &Comment, depth: usize) {
+        output.push_str(&self.style.indent(depth));
+        output.push_str(comment.text.trim_end());
+        output.push('\n');
+    }
+
+    fn write_directive(
+        &self,
+        output: &mut String,
+        directive: &Directive,
+        depth: usize,
+        align_width: Option<usize>,
+    ) {
+        self.write_directive_body(output, directive, depth, align_width);
+        if let Some(comment) = &directive.trailing_comment {
+            output.push(' ');
+            output.push_str(comment.text.trim_end());
+        }
+        output.push('\n');
+    }
+
+    /// Write a directive without its trailing comment and final newline
+    pub(crate) fn write_directive_body(
+        &self,
+        output: &mut String,
+        directive: &Directive,
+        depth: usize,
+        align_width: Option<usize>,
+    ) {
+        let indent = self.style.indent(depth);
+        let terminator = if directive.block.is_some() {
+            match self.style.brace_style {
+                BraceStyle::SameLine => " {",
+                BraceStyle::NextLine => "",
+            }
+        } else {
+            ";"
+        };
+
+        output.push_str(&indent);
+        output.push_str(&self.directive_line(directive, depth, align_width, terminator));
+
+        let Some(block) = &directive.block else {
+            return;
+        };
+
+        if self.style.brace_style == BraceStyle::NextLine {
+            output.push('\n');
+            output.push_str(&indent);
+            output.push('{');
+        }
+        self.write_block(output, block, depth);
+        output.push('}');
+    }
+
+    /// Write the block body after `{`, up to (but excluding) the closing `}`
+    fn write_block(&self, output: &mut String, block: &Block, depth: usize) {
+        if let Some(raw_content) = &block.raw_content {
+            // Copy `{ … }` from the source, minus the braces themselves
+            let start = block.span.start.offset + 1;
+            let end = block.span.end.offset.saturating_sub(1);
+            if let Some(raw) = self.source.and_then(|source| source.get(start..end)) {
+                output.push_str(raw);
+                return;
+            }
+            // Without the source (e.g. a block from `ConfigBuilder::raw_block`),
+            // re-indent the raw content one line per statement
+            let indent = self.style.indent(depth + 1);
+            for line in raw_content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                output.push('\n');
+                output.push_str(&indent);
+                output.push_str(line);
+            }
+            output.push('\n');
+            output.push_str(&self.style.indent(depth));
+            return;
+        }
+
+        let mut items = block.items.as_slice();
+        // A comment on the same line as `{` stays there
+        if let Some(ConfigItem::Comment(c)) = items.first()
+            && c.span.start.line == block.span.start.line
+        {
+            output.push(' ');
+            output.push_str(c.text.trim_end());
+            items = &items[1..];
+        }
+
+        if items
+            .iter()
+            .all(|item| matches!(item, ConfigItem::BlankLine(_)))
+        {
+            if output.ends_with('{') {
+                return;
+            }
+            output.push('\n');
+            output.push_str(&self.style.indent(depth));
+            return;
+        }
+
+        output.push('\n');
+        self.write_items(output, items, depth + 1);
+        output.push_str(&self.style.indent(depth));
+    }
+
+    /// Render the directive name, arguments and terminator, wrapping the
+    /// arguments onto continuation lines if the line would be too long
+    fn directive_line(
+        &self,
+        directive: &Directive,
+        depth: usize,
+        align_width: Option<usize>,
+        terminator: &str,
+    ) -> String {
+        let words = self.argument_words(directive);
+
+        let mut line = directive.name.clone();
+        for (i, word) in words.iter().enumerate() {
+            line.push(' ');
+            line.push_str(word);
+            if i == 0
+                && let Some(width) = align_width
+            {
+                let pad = width.saturating_sub(word.chars().count());
+                line.push_str(&" ".repeat(pad));
+            }
+        }
+        line.push_str(terminator);
+
+        let Some(max) = self.style.max_line_length else {
+            return line;
+        };
+        let width = self.style.indent_columns(depth) + line.chars().count();
+        if width <= max || words.len() < 2 {
+            return line;
+        }
+
+        // Greedy fill: the first word always stays on the directive line,
+        // further words go to lines indented one level deeper
+        let continuation = self.style.indent(depth + 1);
+        let continuation_columns = self.style.indent_columns(depth + 1);
+        let mut wrapped = format!("{} {}", directive.name, words[0]);
+        let mut column = self.style.indent_columns(depth) + wrapped.chars().count();
+        let last = words.len() - 1;
+        for (i, word) in words.iter().enumerate().skip(1) {
+            let word_len = word.chars().count();
+            let trailer = if i == last {
+                terminator.chars().count()
+            } else {
+                0
+            };
+            if column + 1 + word_len + trailer > max {
+                wrapped.push('\n');
+                wrapped.push_str(&continuation);
+                column = continuation_columns + word_len;
+            } else {
+                wrapped.push(' ');
+                column += 1 + word_len;
+            }
+            wrapped.push_str(word);
+        }
+        wrapped.push_str(terminator);
+        wrapped
+    }
+
+    /// For each item, the column width to pad the first argument of a
+    /// `proxy_set_header` to, when it is part of an aligned run
+    fn proxy_set_header_widths(&self, items: &[ConfigItem]) -> Vec<Option<usize>> {
+        let mut widths = vec![None; items.len()];
+        if !self.style.align_proxy_set_header {
+            return widths;
+        }
+
+        let is_aligned = |item: &ConfigItem| match item {
+            ConfigItem::Directive(d) => {
+                d.is("proxy_set_header") && self.argument_words(d).len() >= 2
+            }
+            _ => false,
+        };
+
+        let mut i = 0;
+        while i < items.len() {
+            if !is_aligned(&items[i]) {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < items.len() && is_aligned(&items[i]) {
+                i += 1;
+            }
+            if i - start < 2 {
+                continue;
+            }
+            let width = items[start..i]
+                .iter()
+                .filter_map(|item| match item {
+                    ConfigItem::Directive(d) => self
+                        .argument_words(d)
+                        .first()
+                        .map(|word| word.chars().count()),
+                    _ => None,
+                })
+                .max();
+            for w in &mut widths[start..i] {
+                *w = width;
+            }
+        }
+        widths
+    }
+
+    /// Group a directive's arguments into whitespace-separated words.
+    ///
+    /// Arguments the parser split out of one token (e.g. `"Bearer "$token`) are
+    /// adjacent in the source and must stay joined.
+    fn argument_words(&self, directive: &Directive) -> Vec<String> {
+        let mut words: Vec<String> = Vec::new();
+        let mut prev: Option<&Argument> = None;
+        for arg in &directive.args {
+            let text = if self.canonical || arg.raw.is_empty() {
+                arg.to_source()
+            } else {
+                arg.raw.clone()
+            };
+            let adjacent = prev.is_some_and(|p| {
+                p.span.end.offset == arg.span.start.offset && p.span.end.offset != 0
+            });
+            match (words.last_mut(), prev) {
+                (Some(word), Some(prev)) if adjacent => {
+                    // `$name` directly followed by a name character would
+                    // read as a longer variable name
+                    if self.canonical
+                        && let ArgumentValue::Variable(name) = &prev.value
+                        && text.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+                    {
+                        word.truncate(word.len() - name.len() - 1);
+                        word.push_str(&format!("${{{}}}", name));
+                    }
+                    word.push_str(&text);
+                }
+                _ => words.push(text),
+            }
+            prev = Some(arg);
+        }
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str, style: &FormatStyle) -> String {
+        format_string(source, style).unwrap()
+    }
+
+    #[test]
+    fn test_default_style_reindents() {
+        let source = "http {\n  server {\n\tlisten   80;\n        }\n}\n";
+        assert_eq!(
+            format(source, &FormatStyle::default()),
+            "http {\n    server {\n        listen 80;\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_indent_width_and_tabs() {
+        let source = "http {\nserver {\nlisten 80;\n}\n}\n";
+        let two = FormatStyle {
+            indent_width: 2,
+            ..FormatStyle::default()
+        };
+        assert_eq!(
+            format(source, &two),
+            "http {\n  server {\n    listen 80;\n  }\n}\n"
+        );
+
+        let tabs = FormatStyle {
+            use_tabs: true,
+            ..FormatStyle::default()
+        };
+        assert_eq!(
+            format(source, &tabs),
+            "http {\n\tserver {\n\t\tlisten 80;\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_next_line_braces() {
+        let style = FormatStyle {
+            brace_style: BraceStyle::NextLine,
+            ..FormatStyle::default()
+        };
+        assert_eq!(
+            format("http {\nserver {\nlisten 80;\n}\n}\n", &style),
+            "http\n{\n    server\n    {\n        listen 80;\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_block() {
+        assert_eq!(
+            format("events {\n\n}\n", &FormatStyle::default()),
+            "events {}\n"
+        );
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines() {
+        let source = "\n\n# top\nhttp { # open\n\n\n    gzip on;   # trailing\n\n\n\n  # inner\n    server_tokens off;\n\n} # close\n\n";
+        assert_eq!(
+            format(source, &FormatStyle::default()),
+            "# top\nhttp { # open\n    gzip on; # trailing\n\n    # inner\n    server_tokens off;\n} # close\n"
+        );
+    }
+
+    #[test]
+    fn test_adjacent_arguments_stay_joined() {
+        let source = "proxy_set_header Authorization \"Bearer \"$token;\n";
+        assert_eq!(format(source, &FormatStyle::default()), source);
+    }
+
+    #[test]
+    fn test_wraps_long_argument_lists() {
+        let style = FormatStyle {
+            max_line_length: Some(40),
+            ..FormatStyle::default()
+        };
+        let source = "http {\nlog_format main '$remote_addr - $remote_user' '[$time_local] \"$request\"' '$status $body_bytes_sent';\n}\n";
+        assert_eq!(
+            format(source, &style),
+            "http {\n    log_format main\n        '$remote_addr - $remote_user'\n        '[$time_local] \"$request\"'\n        '$status $body_bytes_sent';\n}\n"
+        );
+
+        let source = "return 200 'a very long response body that exceeds the limit';\n";
+        assert_eq!(
+            format(source, &style),
+            "return 200\n    'a very long response body that exceeds the limit';\n"
+        );
+        // A single argument is never wrapped
+        let source = "root /a/very/long/path/that/exceeds/the/line/limit;\n";
+        assert_eq!(format(source, &style), source);
+    }
+
+    #[test]
+    fn test_align_proxy_set_header() {
+        let style = FormatStyle {
+            align_proxy_set_header: true,
+            ..FormatStyle::default()
+        };
+        let source = "location / {\nproxy_set_header Host $host;\nproxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\nproxy_set_header X-Real-IP $remote_addr;\n\nproxy_set_header Connection \"\";\nproxy_pass http://backend;\n}\n";
+        assert_eq!(
+            format(source, &style),
+            "location / {\n    proxy_set_header Host            $host;\n    proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n    proxy_set_header X-Real-IP       $remote_addr;\n\n    proxy_set_header Connection \"\";\n    proxy_pass http://backend;\n}\n"
+        );
+
+        // Not aligned by default
+        assert!(format(source, &FormatStyle::default()).contains("proxy_set_header Host $host;"));
+    }
+
+    #[test]
+    fn test_raw_block_copied_verbatim() {
+        let source =
+            "location / {\n  content_by_lua_block {\n      local x = 1\n      ngx.say(x)\n  }\n}\n";
+        assert_eq!(
+            format(source, &FormatStyle::default()),
+            "location / {\n    content_by_lua_block {\n      local x = 1\n      ngx.say(x)\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_raw_block_round_trips_byte_for_byte() {
+        let lua = "{\r\n\tif ngx.var.arg_x then  \r\n\n\t\tngx.say(\"{ x }\") -- it's\n\tend\n  }";
+        let source = format!("server {{\n    content_by_lua_block {}\n}}\n", lua);
+        let config = crate::parse_string(&source).unwrap();
+        assert_eq!(config.to_source(), source);
+        assert_eq!(format(&source, &FormatStyle::default()), source);
+
+        let block = config
+            .all_directives()
+            .nth(1)
+            .unwrap()
+            .block
+            .as_ref()
+            .unwrap();
+        assert_eq!(block.raw_content.as_deref(), Some(&lua[1..lua.len() - 1]));
+    }
+
+    #[test]
+    fn test_detect_indent_style() {
+        let detect =
+            |source: &str| IndentStyle::detect(&crate::parse_string(source).unwrap().items);
+        assert_eq!(
+            detect("http {\n\n  # c\n  server {}\n}\n"),
+            Some(IndentStyle::Spaces(2))
+        );
+        assert_eq!(
+            detect("events {}\nhttp {\n\tgzip on;\n}\n"),
+            Some(IndentStyle::Tabs)
+        );
+        assert_eq!(detect("worker_processes 1;\nevents { }\n"), None);
+        assert_eq!(
+            IndentStyle::from(&FormatStyle::default()).indent(2),
+            "        "
+        );
+    }
+
+    #[test]
+    fn test_idempotent() {
+        let style = FormatStyle {
+            indent_width: 2,
+            max_line_length: Some(50),
+            brace_style: BraceStyle::NextLine,
+            align_proxy_set_header: true,
+            ..FormatStyle::default()
+        };
+        let source = "http {\n# c\nserver {\nlisten 80;\nproxy_set_header Host $host;\nproxy_set_header X-Real-IP $remote_addr;\nssl_ciphers ECDHE-ECDSA-AES128-GCM-SHA256 ECDHE-RSA-AES128-GCM-SHA256 ECDHE-ECDSA-AES256-GCM-SHA384;\n}\n}\n";
+        let once = format(source, &style);
+        assert_eq!(format(&once, &style), once);
+    }
+}
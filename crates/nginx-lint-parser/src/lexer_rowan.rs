@@ -11,23 +11,39 @@ use crate::syntax_kind::SyntaxKind;
 /// Every byte of the input is represented exactly once, so
 /// `tokens.iter().map(|(_, t)| *t).collect::<String>() == source` always holds.
 pub fn tokenize(source: &str) -> Vec<(SyntaxKind, &str)> {
-    let mut lexer = RowanLexer::new(source);
+    tokenize_with_raw_blocks(source, &[])
+}
+
+/// Tokenise `source`, also treating `raw_block_directives` as raw blocks.
+///
+/// The body of a raw block (see [`is_raw_block_directive`](crate::is_raw_block_directive))
+/// is embedded code, not nginx syntax: quotes and `#` only matter for finding
+/// the closing brace, so an apostrophe in a comment of the embedded language
+/// cannot swallow the rest of the file. Inside the body, everything but
+/// whitespace, newlines and braces is emitted as ARGUMENT tokens.
+pub fn tokenize_with_raw_blocks<'a>(
+    source: &'a str,
+    raw_block_directives: &[String],
+) -> Vec<(SyntaxKind, &'a str)> {
+    let mut lexer = RowanLexer::new(source, raw_block_directives);
     lexer.tokenize_all()
 }
 
 /// Internal lexer state.
-struct RowanLexer<'a> {
+struct RowanLexer<'a, 'r> {
     source: &'a str,
     pos: usize,
     tokens: Vec<(SyntaxKind, &'a str)>,
+    raw_block_directives: &'r [String],
 }
 
-impl<'a> RowanLexer<'a> {
-    fn new(source: &'a str) -> Self {
+impl<'a, 'r> RowanLexer<'a, 'r> {
+    fn new(source: &'a str, raw_block_directives: &'r [String]) -> Self {
         Self {
             source,
             pos: 0,
             tokens: Vec::new(),
+            raw_block_directives,
         }
     }
 
@@ -66,10 +82,14 @@ impl<'a> RowanLexer<'a> {
         // Track whether the previous non-whitespace token was whitespace-preceded
         // for comment detection (# is only a comment after whitespace or at line start).
         let mut at_line_start = true;
+        // First token of the current statement (the directive name), used to
+        // recognise raw blocks at their `{`
+        let mut statement_name: Option<&'a str> = None;
 
         while !self.at_end() {
             let start = self.pos;
             let ch = self.peek().unwrap();
+            let tokens_before = self.tokens.len();
 
             match ch {
                 '\n' => {
@@ -91,16 +111,27 @@ impl<'a> RowanLexer<'a> {
                     self.advance_char();
                     self.emit(SyntaxKind::SEMICOLON, start);
                     at_line_start = false;
+                    statement_name = None;
+                    continue;
                 }
                 '{' => {
                     self.advance_char();
                     self.emit(SyntaxKind::L_BRACE, start);
                     at_line_start = false;
+                    if statement_name.is_some_and(|name| {
+                        crate::is_raw_block_directive_with_extras(name, self.raw_block_directives)
+                    }) {
+                        self.eat_raw_block_body();
+                    }
+                    statement_name = None;
+                    continue;
                 }
                 '}' => {
                     self.advance_char();
                     self.emit(SyntaxKind::R_BRACE, start);
                     at_line_start = false;
+                    statement_name = None;
+                    continue;
                 }
                 '"' => {
                     self.eat_double_quoted_string();
@@ -145,6 +176,16 @@ impl<'a> RowanLexer<'a> {
                     at_line_start = false;
                 }
             }
+
+            if statement_name.is_none()
+                && let Some(&(kind, text)) = self.tokens.get(tokens_before)
+                && !matches!(
+                    kind,
+                    SyntaxKind::WHITESPACE | SyntaxKind::NEWLINE | SyntaxKind::COMMENT
+                )
+            {
+                statement_name = Some(text);
+            }
         }
 
         std::mem::take(&mut self.tokens)
@@ -181,6 +222,74 @@ impl<'a> RowanLexer<'a> {
         )
     }
 
+    // ── raw block ───────────────────────────────────────────────────
+
+    /// Consume a raw block body after its `{`, up to and including the
+    /// matching `}`.
+    ///
+    /// A quoted string closed on the same line may contain braces; an
+    /// unclosed quote is an ordinary character.
+    fn eat_raw_block_body(&mut self) {
+        let mut depth: u32 = 1;
+        while let Some(ch) = self.peek() {
+            let start = self.pos;
+            match ch {
+                '\n' => {
+                    self.advance_char();
+                    self.emit(SyntaxKind::NEWLINE, start);
+                }
+                ' ' | '\t' => {
+                    self.eat_whitespace();
+                    self.emit(SyntaxKind::WHITESPACE, start);
+                }
+                '{' => {
+                    self.advance_char();
+                    self.emit(SyntaxKind::L_BRACE, start);
+                    depth += 1;
+                }
+                '}' => {
+                    self.advance_char();
+                    self.emit(SyntaxKind::R_BRACE, start);
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {
+                    while let Some(ch) = self.peek() {
+                        match ch {
+                            ' ' | '\t' | '\n' | '{' | '}' => break,
+                            '"' | '\'' | '`' => {
+                                let len = self.same_line_string_len(ch).unwrap_or(1);
+                                self.pos += len;
+                            }
+                            _ => {
+                                self.advance_char();
+                            }
+                        }
+                    }
+                    self.emit(SyntaxKind::ARGUMENT, start);
+                }
+            }
+        }
+    }
+
+    /// Byte length of the `quote`-delimited string starting at the current
+    /// position, if it is closed before the end of the line.
+    fn same_line_string_len(&self, quote: char) -> Option<usize> {
+        let mut escaped = false;
+        for (i, ch) in self.remaining().char_indices().skip(1) {
+            match ch {
+                '\n' => return None,
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                c if c == quote => return Some(i + 1),
+                _ => {}
+            }
+        }
+        None
+    }
+
     // ── strings ─────────────────────────────────────────────────────
 
     fn eat_double_quoted_string(&mut self) {
@@ -831,6 +940,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn raw_block_body() {
+        let source = "content_by_lua_block {\n    -- it's {\"}\"}\n}\nlisten 80;";
+        assert_lossless(source);
+        assert_eq!(
+            tokenize(source),
+            vec![
+                (SyntaxKind::IDENT, "content_by_lua_block"),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::L_BRACE, "{"),
+                (SyntaxKind::NEWLINE, "\n"),
+                (SyntaxKind::WHITESPACE, "    "),
+                (SyntaxKind::ARGUMENT, "--"),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::ARGUMENT, "it's"),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::L_BRACE, "{"),
+                (SyntaxKind::ARGUMENT, "\"}\""),
+                (SyntaxKind::R_BRACE, "}"),
+                (SyntaxKind::NEWLINE, "\n"),
+                (SyntaxKind::R_BRACE, "}"),
+                (SyntaxKind::NEWLINE, "\n"),
+                (SyntaxKind::IDENT, "listen"),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::ARGUMENT, "80"),
+                (SyntaxKind::SEMICOLON, ";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn configured_raw_block() {
+        let source = "js_inline { s = '\n}";
+        let extras = ["js_inline".to_string()];
+        assert_eq!(
+            tokenize_with_raw_blocks(source, &extras)[4..],
+            [
+                (SyntaxKind::ARGUMENT, "s"),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::ARGUMENT, "="),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::ARGUMENT, "'"),
+                (SyntaxKind::NEWLINE, "\n"),
+                (SyntaxKind::R_BRACE, "}"),
+            ]
+        );
+        // Without the configuration the quote runs to the end of the file
+        assert_eq!(
+            tokenize(source).last(),
+            Some(&(SyntaxKind::SINGLE_QUOTED_STRING, "'\n}"))
+        );
+    }
+
     #[test]
     fn extension_directive() {
         let tokens = tokenize(r#"more_set_headers "Server: Custom";"#);
@@ -30,7 +30,10 @@
 //!
 //! - [`ast`] — AST types: [`ast::Config`], [`ast::Directive`], [`ast::Block`],
 //!   [`ast::Argument`], [`ast::Span`], [`ast::Position`]
+//! - [`builder`] — Programmatic construction: [`builder::ConfigBuilder`]
 //! - [`error`] — Error types: [`error::ParseError`]
+//! - [`format`] — Configurable pretty printer: [`format::FormatStyle`]
+//! - [`source`] — Reading config files from disk: [`source::SourceText`]
 //!
 //! # Common Patterns
 //!
@@ -80,14 +83,17 @@
 //! ```
 
 pub mod ast;
+pub mod builder;
 pub mod context;
 pub mod error;
+pub mod format;
 pub mod syntax_kind;
 
 pub mod lexer_rowan;
 pub mod line_index;
 pub mod parser;
 pub mod rowan_to_ast;
+pub mod source;
 
 #[cfg(feature = "wasm")]
 mod wasm;
@@ -106,20 +112,63 @@ use syntax_kind::SyntaxNode;
 /// assert_eq!(root.text().to_string(), "listen 80;");
 /// ```
 pub fn parse_string_rowan(source: &str) -> (SyntaxNode, Vec<parser::SyntaxError>) {
-    let tokens = lexer_rowan::tokenize(source);
-    let (green, errors) = parser::parse(tokens);
+    parse_string_rowan_with_raw_blocks(source, &[])
+}
+
+/// Parse a source string into a rowan CST, also treating
+/// `raw_block_directives` as raw blocks (see [`is_raw_block_directive`]).
+///
+/// ```
+/// use nginx_lint_parser::parse_string_rowan_with_raw_blocks;
+///
+/// let source = "js_inline { return r.uri.split('}')[0]; }";
+/// let (root, errors) =
+///     parse_string_rowan_with_raw_blocks(source, &["js_inline".to_string()]);
+/// assert!(errors.is_empty());
+/// assert_eq!(root.text().to_string(), source);
+/// ```
+pub fn parse_string_rowan_with_raw_blocks(
+    source: &str,
+    raw_block_directives: &[String],
+) -> (SyntaxNode, Vec<parser::SyntaxError>) {
+    let tokens = lexer_rowan::tokenize_with_raw_blocks(source, raw_block_directives);
+    let (green, errors) = parser::parse_with_raw_blocks(tokens, raw_block_directives);
     (SyntaxNode::new_root(green), errors)
 }
 
 use ast::Config;
 use error::{ParseError, ParseResult};
-use std::fs;
+use source::SourceText;
 use std::path::Path;
 
 /// Parse a nginx configuration file from disk
+///
+/// Large files are memory-mapped rather than copied into memory
+/// (see [`source::SourceText`]).
 pub fn parse_config(path: &Path) -> ParseResult<Config> {
-    let content = fs::read_to_string(path).map_err(|e| ParseError::IoError(e.to_string()))?;
-    parse_string(&content)
+    parse_config_with_raw_blocks(path, &[])
+}
+
+/// Parse a nginx configuration file from disk, also treating
+/// `raw_block_directives` as raw blocks (see [`is_raw_block_directive`])
+pub fn parse_config_with_raw_blocks(
+    path: &Path,
+    raw_block_directives: &[String],
+) -> ParseResult<Config> {
+    let content = SourceText::read(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+    let (root, errors) = parse_string_rowan_with_raw_blocks(&content, raw_block_directives);
+    if let Some(err) = errors.first() {
+        return Err(ParseError::UnexpectedToken {
+            expected: "valid syntax".to_string(),
+            found: err.message.clone(),
+            position: line_index::LineIndex::new(&content).position(err.offset),
+        });
+    }
+    Ok(rowan_to_ast::convert_with_raw_blocks(
+        &root,
+        &content,
+        raw_block_directives,
+    ))
 }
 
 /// Parse nginx configuration from a string
@@ -144,15 +193,43 @@ pub fn parse_string(source: &str) -> ParseResult<Config> {
 /// leveraging rowan's error-recovery. Syntax errors are returned alongside the
 /// AST so callers can report them without aborting the lint pipeline.
 pub fn parse_string_with_errors(source: &str) -> (Config, Vec<parser::SyntaxError>) {
-    let (root, errors) = parse_string_rowan(source);
-    let config = rowan_to_ast::convert(&root, source);
+    parse_string_with_errors_and_raw_blocks(source, &[])
+}
+
+/// Like [`parse_string_with_errors`], also treating `raw_block_directives`
+/// as raw blocks (see [`is_raw_block_directive`]).
+///
+/// ```
+/// use nginx_lint_parser::parse_string_with_errors_and_raw_blocks;
+///
+/// let source = "js_inline {\n    // don't parse me\n}\n";
+/// let (config, errors) =
+///     parse_string_with_errors_and_raw_blocks(source, &["js_inline".to_string()]);
+/// assert!(errors.is_empty());
+/// let block = config.directives().next().unwrap().block.as_ref().unwrap();
+/// assert_eq!(block.raw_content.as_deref(), Some("\n    // don't parse me\n"));
+/// ```
+pub fn parse_string_with_errors_and_raw_blocks(
+    source: &str,
+    raw_block_directives: &[String],
+) -> (Config, Vec<parser::SyntaxError>) {
+    let (root, errors) = parse_string_rowan_with_raw_blocks(source, raw_block_directives);
+    let config = rowan_to_ast::convert_with_raw_blocks(&root, source, raw_block_directives);
     (config, errors)
 }
 
+/// Directives whose block holds code of another language
+const RAW_BLOCK_DIRECTIVES: &[&str] = &[
+    // njs
+    "js_body", // ngx_http_perl_module
+    "perl",
+];
+
 /// Check if a directive name indicates a raw block (Lua code, etc.)
 ///
 /// Raw block directives contain code (like Lua) that should not be parsed
-/// as nginx configuration. The content inside the block is preserved as-is.
+/// as nginx configuration. The source text between the braces is preserved
+/// verbatim in [`Block::raw_content`](ast::Block::raw_content).
 ///
 /// # Examples
 /// ```
@@ -160,12 +237,27 @@ pub fn parse_string_with_errors(source: &str) -> (Config, Vec<parser::SyntaxErro
 ///
 /// assert!(is_raw_block_directive("content_by_lua_block"));
 /// assert!(is_raw_block_directive("init_by_lua_block"));
+/// assert!(is_raw_block_directive("js_body"));
 /// assert!(!is_raw_block_directive("server"));
 /// ```
 pub fn is_raw_block_directive(name: &str) -> bool {
     // OpenResty / lua-nginx-module directives
     // Using ends_with covers all *_by_lua_block patterns
-    name.ends_with("_by_lua_block")
+    name.ends_with("_by_lua_block") || RAW_BLOCK_DIRECTIVES.contains(&name)
+}
+
+/// Check if a directive is a raw block directive, including custom additions
+///
+/// # Examples
+/// ```
+/// use nginx_lint_parser::is_raw_block_directive_with_extras;
+///
+/// assert!(is_raw_block_directive_with_extras("content_by_lua_block", &[]));
+/// assert!(is_raw_block_directive_with_extras("js_inline", &["js_inline".to_string()]));
+/// assert!(!is_raw_block_directive_with_extras("js_inline", &[]));
+/// ```
+pub fn is_raw_block_directive_with_extras(name: &str, additional: &[String]) -> bool {
+    is_raw_block_directive(name) || additional.iter().any(|s| s == name)
 }
 
 /// Known nginx block directive names that require `{` instead of `;`
@@ -236,6 +328,12 @@ pub fn is_block_directive_with_extras(name: &str, additional: &[String]) -> bool
 /// assert!(is_raw_block_cst_node(&block));
 /// ```
 pub fn is_raw_block_cst_node(block: &SyntaxNode) -> bool {
+    is_raw_block_cst_node_with_extras(block, &[])
+}
+
+/// Check if a CST BLOCK node belongs to a raw block directive, including
+/// custom additions (see [`is_raw_block_directive_with_extras`]).
+pub fn is_raw_block_cst_node_with_extras(block: &SyntaxNode, additional: &[String]) -> bool {
     use syntax_kind::SyntaxKind;
 
     let directive = match block.parent() {
@@ -246,7 +344,7 @@ pub fn is_raw_block_cst_node(block: &SyntaxNode) -> bool {
         if let Some(t) = child.as_token()
             && t.kind() == SyntaxKind::IDENT
         {
-            return is_raw_block_directive(t.text());
+            return is_raw_block_directive_with_extras(t.text(), additional);
         }
     }
     false
@@ -496,6 +594,38 @@ http {
         assert!(content.contains("require \"resty.core\""));
     }
 
+    #[test]
+    fn test_raw_block_content_verbatim() {
+        let source = "location / {\n    js_body {\n        if (r.uri == '/') { r.return(200); } // it's\n    }\n    perl {\n        $r->print(\"}\");\n    }\n}\n";
+        let (config, errors) = parse_string_with_errors(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let names: Vec<_> = config.all_directives().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["location", "js_body", "perl"]);
+
+        let js_body = config.all_directives().nth(1).unwrap();
+        assert_eq!(
+            js_body.block.as_ref().unwrap().raw_content.as_deref(),
+            Some("\n        if (r.uri == '/') { r.return(200); } // it's\n    ")
+        );
+        assert_eq!(config.to_source(), source);
+    }
+
+    #[test]
+    fn test_configured_raw_block_directive() {
+        let source = "http {\n    njs_script {\n        var s = '{';\n    }\n    gzip on;\n}\n";
+        let extras = ["njs_script".to_string()];
+
+        let (config, errors) = parse_string_with_errors_and_raw_blocks(source, &extras);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let names: Vec<_> = config.all_directives().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["http", "njs_script", "gzip"]);
+        assert_eq!(config.to_source(), source);
+
+        // Parsed as nginx syntax without the configuration
+        let (config, _) = parse_string_with_errors(source);
+        assert!(config.all_directives().any(|d| d.name == "var"));
+    }
+
     #[test]
     fn test_whitespace_capture() {
         let config = parse_string("http {\n    listen 80;\n}").unwrap();
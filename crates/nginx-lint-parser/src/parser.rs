@@ -18,15 +18,28 @@ pub struct SyntaxError {
 ///
 /// Returns the root green node and any errors encountered during parsing.
 pub fn parse(tokens: Vec<(SyntaxKind, &str)>) -> (GreenNode, Vec<SyntaxError>) {
-    let mut parser = Parser::new(tokens);
+    parse_with_raw_blocks(tokens, &[])
+}
+
+/// Parse a flat token list, also treating `raw_block_directives` as raw blocks.
+///
+/// `tokens` should come from
+/// [`tokenize_with_raw_blocks`](crate::lexer_rowan::tokenize_with_raw_blocks)
+/// with the same list.
+pub fn parse_with_raw_blocks(
+    tokens: Vec<(SyntaxKind, &str)>,
+    raw_block_directives: &[String],
+) -> (GreenNode, Vec<SyntaxError>) {
+    let mut parser = Parser::new(tokens, raw_block_directives);
     parser.parse_root();
     (parser.builder.finish(), parser.errors)
 }
 
 // ── Parser ──────────────────────────────────────────────────────────────
 
-struct Parser<'a> {
+struct Parser<'a, 'r> {
     tokens: Vec<(SyntaxKind, &'a str)>,
+    raw_block_directives: &'r [String],
     pos: usize,
     builder: GreenNodeBuilder<'static>,
     errors: Vec<SyntaxError>,
@@ -34,10 +47,11 @@ struct Parser<'a> {
     offset: usize,
 }
 
-impl<'a> Parser<'a> {
-    fn new(tokens: Vec<(SyntaxKind, &'a str)>) -> Self {
+impl<'a, 'r> Parser<'a, 'r> {
+    fn new(tokens: Vec<(SyntaxKind, &'a str)>, raw_block_directives: &'r [String]) -> Self {
         Self {
             tokens,
+            raw_block_directives,
             pos: 0,
             builder: GreenNodeBuilder::new(),
             errors: Vec::new(),
@@ -206,8 +220,8 @@ impl<'a> Parser<'a> {
         // Arguments (consume whitespace + argument tokens)
         self.parse_arguments();
 
-        // Check for lua block
-        let is_lua_block = name.ends_with("_by_lua_block");
+        let is_raw_block =
+            crate::is_raw_block_directive_with_extras(&name, self.raw_block_directives);
 
         // Terminator: semicolon or block
         match self.peek_non_trivia() {
@@ -219,7 +233,7 @@ impl<'a> Parser<'a> {
             }
             Some(SyntaxKind::L_BRACE) => {
                 self.eat_trivia();
-                if is_lua_block {
+                if is_raw_block {
                     self.parse_raw_block();
                 } else {
                     self.parse_block();
@@ -297,7 +311,7 @@ impl<'a> Parser<'a> {
         self.builder.finish_node();
     }
 
-    /// Parse a raw block for `*_by_lua_block` directives.
+    /// Parse a raw block (`*_by_lua_block` and the like).
     /// All tokens between L_BRACE and matching R_BRACE are consumed as-is,
     /// tracking brace depth.
     fn parse_raw_block(&mut self) {
@@ -327,7 +341,7 @@ impl<'a> Parser<'a> {
         }
 
         if depth > 0 {
-            self.error("expected '}' for lua block");
+            self.error("expected '}' for raw block");
         }
 
         self.builder.finish_node();
@@ -6,26 +6,37 @@
 use crate::ast::{
     Argument, ArgumentValue, BlankLine, Block, Comment, Config, ConfigItem, Directive, Span,
 };
-use crate::is_raw_block_directive;
+use crate::is_raw_block_directive_with_extras;
 use crate::line_index::LineIndex;
 use crate::syntax_kind::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
 
 /// Convert a rowan CST root node into the existing AST [`Config`].
 pub fn convert(root: &SyntaxNode, source: &str) -> Config {
+    convert_with_raw_blocks(root, source, &[])
+}
+
+/// Convert a rowan CST root node parsed with additional raw block directives.
+pub fn convert_with_raw_blocks(
+    root: &SyntaxNode,
+    source: &str,
+    raw_block_directives: &[String],
+) -> Config {
     let line_index = LineIndex::new(source);
     let ctx = ConvertCtx {
         line_index: &line_index,
+        raw_block_directives,
     };
     let items = ctx.convert_items(root);
     Config {
         items,
-        include_context: Vec::new(),
+        include_context: crate::context::IncludeContext::new(),
     }
 }
 
 /// Shared context for the conversion.
 struct ConvertCtx<'a> {
     line_index: &'a LineIndex,
+    raw_block_directives: &'a [String],
 }
 
 impl<'a> ConvertCtx<'a> {
@@ -210,7 +221,7 @@ impl<'a> ConvertCtx<'a> {
             }
             Terminator::Block { idx } => {
                 let block_node = children[idx].as_node().unwrap();
-                let is_raw = is_raw_block_directive(&name);
+                let is_raw = is_raw_block_directive_with_extras(&name, self.raw_block_directives);
 
                 // space_before_terminator is whitespace before the BLOCK node
                 space_before_terminator = self.whitespace_before(&children, idx);
@@ -513,79 +524,11 @@ impl<'a> ConvertCtx<'a> {
         }
     }
 
-    /// Extract raw content from a raw block, matching the original parser's
-    /// token-by-token reconstruction.
-    ///
-    /// The original parser's `read_raw_block` joins token `raw` values with
-    /// spaces (dropping indentation whitespace) and converts newlines to `\n`.
-    /// We replicate this behaviour by walking the rowan tokens.
+    /// Extract the source text between the braces of a raw block, verbatim.
     fn extract_raw_content(&self, block_node: &SyntaxNode) -> String {
-        let children: Vec<SyntaxElement> = block_node.children_with_tokens().collect();
-        let mut content = String::new();
-        let mut depth: u32 = 0;
-        let mut i = 0;
-
-        while i < children.len() {
-            let kind = children[i].kind();
-            match kind {
-                SyntaxKind::L_BRACE => {
-                    if depth > 0 {
-                        content.push('{');
-                    }
-                    depth += 1;
-                    i += 1;
-                }
-                SyntaxKind::R_BRACE => {
-                    depth = depth.saturating_sub(1);
-                    if depth > 0 {
-                        content.push('}');
-                    }
-                    i += 1;
-                }
-                SyntaxKind::NEWLINE => {
-                    // The original parser's read_raw_block pushes both the
-                    // raw text ("\n") and an explicit '\n' for each Newline
-                    // token, resulting in \n\n per newline. Replicate this.
-                    content.push('\n');
-                    content.push('\n');
-                    i += 1;
-                }
-                SyntaxKind::WHITESPACE => {
-                    // Skip whitespace (indentation) — the original parser
-                    // reconstructs with single spaces between tokens instead.
-                    i += 1;
-                }
-                _ => {
-                    // Append token text
-                    if let Some(tok) = children[i].as_token() {
-                        content.push_str(tok.text());
-                    }
-                    i += 1;
-                    // Look ahead: if the next meaningful token is not a
-                    // newline/eof/closebrace/semicolon, add a space.
-                    // Skip whitespace to find the next meaningful token.
-                    let mut next_i = i;
-                    while next_i < children.len()
-                        && children[next_i].kind() == SyntaxKind::WHITESPACE
-                    {
-                        next_i += 1;
-                    }
-                    if next_i < children.len() {
-                        let next_kind = children[next_i].kind();
-                        if !matches!(
-                            next_kind,
-                            SyntaxKind::NEWLINE | SyntaxKind::R_BRACE | SyntaxKind::SEMICOLON
-                        ) {
-                            content.push(' ');
-                        }
-                    }
-                    // Skip the whitespace we peeked past
-                    i = next_i;
-                }
-            }
-        }
-
-        content.trim().to_string()
+        let text = block_node.text().to_string();
+        let body = text.strip_prefix('{').unwrap_or(&text);
+        body.strip_suffix('}').unwrap_or(body).to_string()
     }
 
     /// Get the leading whitespace before the closing brace `}`.
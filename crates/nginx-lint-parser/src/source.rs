@@ -0,0 +1,182 @@
+//! Reading configuration sources from disk.
+//!
+//! [`SourceText`] holds the content of a config file as UTF-8 text. Files of
+//! at least [`MMAP_THRESHOLD`] bytes (e.g. large `nginx -T` dumps) are
+//! memory-mapped instead of being copied into a `String`, so the lexer
+//! borrows directly from the mapping. When mapping is unavailable (WASM
+//! targets, special files, filesystems without mmap support) the file is read
+//! into memory as before.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::path::Path;
+
+/// Files at least this large are memory-mapped (1 MiB).
+///
+/// Below this size a plain read is cheaper than setting up a mapping.
+pub const MMAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// The UTF-8 content of a config file, either owned or memory-mapped.
+///
+/// Dereferences to `str`:
+///
+/// ```no_run
+/// use std::path::Path;
+/// use nginx_lint_parser::source::SourceText;
+///
+/// let source = SourceText::read(Path::new("/etc/nginx/nginx.conf")).unwrap();
+/// let config = nginx_lint_parser::parse_string(&source).unwrap();
+/// ```
+pub struct SourceText {
+    inner: Inner,
+}
+
+enum Inner {
+    Owned(String),
+    #[cfg(not(target_arch = "wasm32"))]
+    Mapped(memmap2::Mmap),
+}
+
+impl SourceText {
+    /// Read a file, memory-mapping it if it is large enough.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the file is not
+    /// valid UTF-8, matching [`std::fs::read_to_string`].
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if len >= MMAP_THRESHOLD
+            && let Some(mapped) = Self::map(&file)?
+        {
+            return Ok(mapped);
+        }
+
+        let mut content = String::with_capacity(len as usize);
+        file.read_to_string(&mut content)?;
+        Ok(Self::from(content))
+    }
+
+    /// Map `file`, returning `None` if the platform or filesystem refuses
+    /// the mapping so the caller can fall back to reading.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn map(file: &File) -> io::Result<Option<Self>> {
+        // SAFETY: `Mmap::map` creates a read-only *shared* mapping: we never
+        // write through it, but writes to the file by other processes show
+        // through, and truncating it makes accesses past the new end fault.
+        // We assume config files are not modified while being linted; a
+        // file changed anyway breaks this assumption (see `as_str`).
+        let Ok(mmap) = (unsafe { memmap2::Mmap::map(file) }) else {
+            return Ok(None);
+        };
+        if let Err(e) = std::str::from_utf8(&mmap) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        Ok(Some(Self {
+            inner: Inner::Mapped(mmap),
+        }))
+    }
+
+    /// Whether the content is backed by a memory mapping.
+    pub fn is_mapped(&self) -> bool {
+        match self.inner {
+            Inner::Owned(_) => false,
+            #[cfg(not(target_arch = "wasm32"))]
+            Inner::Mapped(_) => true,
+        }
+    }
+
+    /// Get the content as a string slice.
+    pub fn as_str(&self) -> &str {
+        match &self.inner {
+            Inner::Owned(s) => s,
+            // SAFETY: `map()` only creates a `Mapped` source after checking
+            // the whole mapping with `std::str::from_utf8`. The mapping is
+            // shared, so that check only holds while no other process
+            // modifies the file; like `map()`, this assumes the file is not
+            // changed while it is linted, as a write could leave invalid
+            // UTF-8 behind the `&str`.
+            #[cfg(not(target_arch = "wasm32"))]
+            Inner::Mapped(m) => unsafe { std::str::from_utf8_unchecked(m) },
+        }
+    }
+}
+
+impl From<String> for SourceText {
+    fn from(content: String) -> Self {
+        Self {
+            inner: Inner::Owned(content),
+        }
+    }
+}
+
+impl Deref for SourceText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Debug for SourceText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SourceText")
+            .field("len", &self.len())
+            .field("mapped", &self.is_mapped())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_file(name: &str, content: &[u8]) -> (std::path::PathBuf, File) {
+        let path = std::env::temp_dir().join(format!(
+            "nginx-lint-source-{}-{}.conf",
+            std::process::id(),
+            name
+        ));
+        let mut file = File::create(&path).unwrap();
+        file.write_all(content).unwrap();
+        (path, file)
+    }
+
+    #[test]
+    fn test_small_file_is_read() {
+        let (path, _file) = temp_file("small", b"listen 80;\n");
+        let source = SourceText::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!source.is_mapped());
+        assert_eq!(&*source, "listen 80;\n");
+    }
+
+    #[test]
+    fn test_large_file_is_mapped() {
+        let line = "    server_name example.com;\n";
+        let mut content = String::from("server {\n");
+        while (content.len() as u64) < MMAP_THRESHOLD {
+            content.push_str(line);
+        }
+        content.push_str("}\n");
+        let (path, _file) = temp_file("large", content.as_bytes());
+        let source = SourceText::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(source.is_mapped());
+        assert_eq!(source.as_str(), content);
+        assert!(crate::parse_string(&source).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_rejected() {
+        let mut content = vec![b'#'; MMAP_THRESHOLD as usize];
+        content.push(0xff);
+        let (path, _file) = temp_file("invalid", &content);
+        let err = SourceText::read(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -15,7 +15,16 @@ impl Guest for ParserComponent {
         include_context: Vec<String>,
     ) -> Result<nginx_lint::plugin::parser_types::ParseOutput, String> {
         let mut config = crate::parse_string(&source).map_err(|e| e.to_string())?;
-        config.include_context = include_context;
+        // Each entry is a context frame in `--context` syntax
+        // (`location=~ ^/api`); plain block names are the common case.
+        config.include_context = include_context
+            .iter()
+            .flat_map(|frame| {
+                crate::context::IncludeContext::parse(frame)
+                    .frames()
+                    .to_vec()
+            })
+            .collect();
         Ok(build_parse_output(&config))
     }
 }
@@ -162,7 +171,7 @@ fn build_parse_output(config: &Config) -> pt::ParseOutput {
 
     pt::ParseOutput {
         directives_with_context,
-        include_context: config.include_context.clone(),
+        include_context: config.include_context.to_names(),
         all_items,
         top_level_indices,
     }
@@ -178,7 +187,7 @@ fn build_directive_contexts(
     collect_directive_contexts(
         all_items,
         top_level_indices,
-        &config.include_context,
+        &config.include_context.to_names(),
         &mut results,
     );
     results
@@ -0,0 +1,124 @@
+//! Per-check information for [`Plugin::check_with_context`](crate::Plugin::check_with_context).
+
+use crate::options::RuleOptions;
+use nginx_lint_common::parser::ast::Config;
+use nginx_lint_common::parser::context::IncludeContext;
+use nginx_lint_common::variables::VariableTable;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+pub use nginx_lint_common::config::Environment;
+pub use nginx_lint_common::linter::AnalysisCache;
+
+/// What the host knows about the file being checked, besides its config.
+///
+/// ```
+/// use nginx_lint_plugin::prelude::*;
+///
+/// let config = nginx_lint_plugin::parse_string("http { set $a 1; }").unwrap();
+/// let ctx = CheckContext::new(&config, "nginx.conf").with_target_nginx_version(Some("1.24.0"));
+///
+/// assert_eq!(ctx.path(), "nginx.conf");
+/// assert!(ctx.include_context().is_empty());
+/// assert_eq!(ctx.target_nginx_version(), Some("1.24.0"));
+/// assert_eq!(ctx.environment().behind_load_balancer, None);
+/// assert!(ctx.variable_table().definition("a").is_some());
+/// ```
+pub struct CheckContext<'a> {
+    config: &'a Config,
+    path: &'a str,
+    target_nginx_version: Option<&'a str>,
+    environment: Cow<'a, Environment>,
+    options: Cow<'a, RuleOptions>,
+    cache: CacheRef<'a>,
+}
+
+enum CacheRef<'a> {
+    Owned(AnalysisCache),
+    Shared(&'a AnalysisCache),
+}
+
+impl<'a> CheckContext<'a> {
+    /// Context for checking `config`, read from `path`, with no target
+    /// version, no environment settings, no options and a cache of its own
+    pub fn new(config: &'a Config, path: &'a str) -> Self {
+        Self {
+            config,
+            path,
+            target_nginx_version: None,
+            environment: Cow::Owned(Environment::default()),
+            options: Cow::Owned(RuleOptions::default()),
+            cache: CacheRef::Owned(AnalysisCache::new()),
+        }
+    }
+
+    /// Set the nginx version the config targets
+    pub fn with_target_nginx_version(mut self, version: Option<&'a str>) -> Self {
+        self.target_nginx_version = version;
+        self
+    }
+
+    /// Set where the config is deployed
+    pub fn with_environment(mut self, environment: &'a Environment) -> Self {
+        self.environment = Cow::Borrowed(environment);
+        self
+    }
+
+    /// Set the plugin's resolved options
+    pub fn with_options(mut self, options: &'a RuleOptions) -> Self {
+        self.options = Cow::Borrowed(options);
+        self
+    }
+
+    /// Use a cache shared with other rules checking the same file
+    pub fn with_cache(mut self, cache: &'a AnalysisCache) -> Self {
+        self.cache = CacheRef::Shared(cache);
+        self
+    }
+
+    /// Path of the file being checked
+    pub fn path(&self) -> &str {
+        self.path
+    }
+
+    /// Blocks the file is included from, outermost first (empty for a file
+    /// linted on its own), as resolved by the host from the include tree
+    pub fn include_context(&self) -> &IncludeContext {
+        &self.config.include_context
+    }
+
+    /// The `target_nginx_version` setting (e.g. `"1.24.0"`), if set
+    pub fn target_nginx_version(&self) -> Option<&str> {
+        self.target_nginx_version
+    }
+
+    /// The `[environment]` settings: target OS, container, load balancer in
+    /// front. Fields the user did not set are `None`.
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    /// The plugin's options, resolved against its
+    /// [declarations](crate::PluginSpec::with_option); the same values
+    /// [`Plugin::configure`](crate::Plugin::configure) received
+    pub fn options(&self) -> &RuleOptions {
+        &self.options
+    }
+
+    /// Analyses of this file shared by the rules checking it.
+    ///
+    /// Builtin rules run natively share one cache per file; a WASM plugin
+    /// gets a cache of its own for each check.
+    pub fn cache(&self) -> &AnalysisCache {
+        match &self.cache {
+            CacheRef::Owned(cache) => cache,
+            CacheRef::Shared(cache) => cache,
+        }
+    }
+
+    /// The file's variable table, computed once per [cache](Self::cache)
+    pub fn variable_table(&self) -> Arc<VariableTable> {
+        self.cache()
+            .get_or_insert_with(|| VariableTable::from_config(self.config))
+    }
+}
@@ -0,0 +1,331 @@
+//! Declarative behaviour checks against a running nginx.
+//!
+//! A [`BehaviorTest`] starts nginx with a config, sends each
+//! [`RequestCheck`] to it and asserts on the responses. It ties a rule to the
+//! behaviour it warns about: run it once with the rule's bad example and once
+//! with the good one, and optionally assert that the plugin reports the bad
+//! config, so the rule's premise is verified together with the rule itself.
+//!
+//! ```rust,no_run
+//! use nginx_lint_plugin::container_testing::{BehaviorTest, RequestCheck};
+//!
+//! #[tokio::test]
+//! #[ignore]
+//! async fn server_tokens_on_exposes_version() {
+//!     BehaviorTest::new("http { server_tokens on; server { listen 80; } }")
+//!         .request(RequestCheck::get("/").expect_header_contains("server", "/"))
+//!         .run()
+//!         .await;
+//! }
+//! ```
+
+use std::path::Path;
+
+use reqwest::Method;
+use reqwest::header::HeaderMap;
+
+use super::nginx::NginxContainer;
+use crate::types::{Config, ConfigItem, Plugin};
+
+/// A config to run in nginx and the requests to check against it.
+pub struct BehaviorTest {
+    config: String,
+    health_path: String,
+    requests: Vec<RequestCheck>,
+}
+
+impl BehaviorTest {
+    /// Test `config`
+    ///
+    /// An `events {}` block is added when the config has none, so rule
+    /// examples that only contain an `http` block can be used as they are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config does not parse.
+    pub fn new(config: &str) -> Self {
+        let parsed = crate::parse_string(config)
+            .unwrap_or_else(|e| panic!("Failed to parse behaviour test config: {}", e));
+        let config = if has_events_block(&parsed) {
+            config.to_string()
+        } else {
+            format!("events {{}}\n{}", config)
+        };
+        Self {
+            config,
+            health_path: "/".to_string(),
+            requests: Vec::new(),
+        }
+    }
+
+    /// Test the config in `path`, e.g. a plugin's `examples/bad.conf`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file cannot be read or does not parse.
+    pub fn from_file(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+        Self::new(&config)
+    }
+
+    /// Path that must return 200 once nginx is up (default: `"/"`)
+    pub fn health_path(mut self, path: &str) -> Self {
+        self.health_path = path.to_string();
+        self
+    }
+
+    /// Add a request and its expectations
+    pub fn request(mut self, check: RequestCheck) -> Self {
+        self.requests.push(check);
+        self
+    }
+
+    /// Assert that `plugin` reports `expected` errors for the config
+    ///
+    /// Runs immediately, without a container, so a rule that stops
+    /// matching its own example fails before nginx is started.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of errors differs.
+    pub fn linted_by<P: Plugin>(self, plugin: &P, expected: usize) -> Self {
+        let config = crate::parse_string(&self.config).expect("config parsed in new()");
+        let errors = crate::testing::check_with_defaults(plugin, &config, "test.conf");
+        assert_eq!(
+            errors.len(),
+            expected,
+            "Expected {} error(s) from {}, got: {:?}",
+            expected,
+            plugin.spec().name,
+            errors
+        );
+        self
+    }
+
+    /// The config passed to nginx
+    pub fn config(&self) -> &str {
+        &self.config
+    }
+
+    /// Start nginx, send every request and check the responses
+    ///
+    /// All requests are sent before failing, and the panic message lists
+    /// every unmet expectation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if nginx does not start, a request fails or an expectation is
+    /// not met.
+    pub async fn run(self) {
+        let nginx = NginxContainer::builder()
+            .health_path(&self.health_path)
+            .start(self.config.as_bytes().to_vec())
+            .await;
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Failed to build HTTP client");
+
+        let mut failures = Vec::new();
+        for check in &self.requests {
+            let mut request = client.request(check.method.clone(), nginx.url(&check.path));
+            for (name, value) in &check.headers {
+                request = request.header(name, value);
+            }
+            let response = request
+                .send()
+                .await
+                .unwrap_or_else(|e| panic!("{} {} failed: {}", check.method, check.path, e));
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            failures.extend(
+                check
+                    .failures(status, &headers, &body)
+                    .into_iter()
+                    .map(|f| format!("{} {}: {}", check.method, check.path, f)),
+            );
+        }
+
+        assert!(
+            failures.is_empty(),
+            "Unmet expectations:\n  {}\nConfig:\n{}",
+            failures.join("\n  "),
+            self.config
+        );
+    }
+}
+
+fn has_events_block(config: &Config) -> bool {
+    config
+        .items
+        .iter()
+        .any(|item| matches!(item, ConfigItem::Directive(d) if d.name == "events"))
+}
+
+/// One request to send and what its response must look like.
+pub struct RequestCheck {
+    method: Method,
+    path: String,
+    headers: Vec<(String, String)>,
+    expectations: Vec<Expectation>,
+}
+
+enum Expectation {
+    Status(u16),
+    HeaderPresent(String),
+    HeaderAbsent(String),
+    HeaderEquals(String, String),
+    HeaderContains(String, String),
+    BodyContains(String),
+}
+
+impl RequestCheck {
+    /// A request with the given method, e.g. `Method::OPTIONS`
+    pub fn new(method: Method, path: &str) -> Self {
+        Self {
+            method,
+            path: path.to_string(),
+            headers: Vec::new(),
+            expectations: Vec::new(),
+        }
+    }
+
+    /// A `GET` request
+    pub fn get(path: &str) -> Self {
+        Self::new(Method::GET, path)
+    }
+
+    /// Send a request header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Expect the response status
+    pub fn expect_status(mut self, status: u16) -> Self {
+        self.expectations.push(Expectation::Status(status));
+        self
+    }
+
+    /// Expect a response header to be present
+    pub fn expect_header_present(mut self, name: &str) -> Self {
+        self.expectations
+            .push(Expectation::HeaderPresent(name.to_string()));
+        self
+    }
+
+    /// Expect a response header to be absent
+    pub fn expect_header_absent(mut self, name: &str) -> Self {
+        self.expectations
+            .push(Expectation::HeaderAbsent(name.to_string()));
+        self
+    }
+
+    /// Expect a response header to have exactly `value`
+    pub fn expect_header(mut self, name: &str, value: &str) -> Self {
+        self.expectations.push(Expectation::HeaderEquals(
+            name.to_string(),
+            value.to_string(),
+        ));
+        self
+    }
+
+    /// Expect a response header to contain `part`
+    pub fn expect_header_contains(mut self, name: &str, part: &str) -> Self {
+        self.expectations.push(Expectation::HeaderContains(
+            name.to_string(),
+            part.to_string(),
+        ));
+        self
+    }
+
+    /// Expect the response body to contain `text`
+    pub fn expect_body_contains(mut self, text: &str) -> Self {
+        self.expectations
+            .push(Expectation::BodyContains(text.to_string()));
+        self
+    }
+
+    /// Unmet expectations for a response
+    fn failures(&self, status: u16, headers: &HeaderMap, body: &str) -> Vec<String> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .map(|v| String::from_utf8_lossy(v.as_bytes()).into_owned())
+        };
+        self.expectations
+            .iter()
+            .filter_map(|expectation| match expectation {
+                Expectation::Status(expected) => (status != *expected)
+                    .then(|| format!("expected status {}, got {}", expected, status)),
+                Expectation::HeaderPresent(name) => header(name)
+                    .is_none()
+                    .then(|| format!("expected header {} to be present", name)),
+                Expectation::HeaderAbsent(name) => header(name)
+                    .map(|v| format!("expected header {} to be absent, got {:?}", name, v)),
+                Expectation::HeaderEquals(name, expected) => match header(name) {
+                    Some(v) if v == *expected => None,
+                    v => Some(format!(
+                        "expected header {}: {:?}, got {:?}",
+                        name, expected, v
+                    )),
+                },
+                Expectation::HeaderContains(name, part) => match header(name) {
+                    Some(v) if v.contains(part.as_str()) => None,
+                    v => Some(format!(
+                        "expected header {} to contain {:?}, got {:?}",
+                        name, part, v
+                    )),
+                },
+                Expectation::BodyContains(text) => (!body.contains(text.as_str()))
+                    .then(|| format!("expected body to contain {:?}", text)),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_adds_events_block_when_missing() {
+        let test = BehaviorTest::new("http {\n    server { listen 80; }\n}\n");
+        assert!(test.config().starts_with("events {}\nhttp {"));
+
+        let config = "events { worker_connections 16; }\nhttp {}\n";
+        assert_eq!(BehaviorTest::new(config).config(), config);
+    }
+
+    #[test]
+    fn test_request_check_failures() {
+        let mut headers = HeaderMap::new();
+        headers.insert("server", HeaderValue::from_static("nginx/1.27.4"));
+        headers.insert("x-frame-options", HeaderValue::from_static("DENY"));
+
+        let passing = RequestCheck::get("/")
+            .expect_status(200)
+            .expect_header_contains("server", "/")
+            .expect_header("x-frame-options", "DENY")
+            .expect_header_absent("access-control-allow-origin")
+            .expect_body_contains("OK");
+        assert!(passing.failures(200, &headers, "OK").is_empty());
+
+        let failing = RequestCheck::get("/")
+            .expect_status(404)
+            .expect_header_absent("server")
+            .expect_header_present("content-security-policy");
+        assert_eq!(
+            failing.failures(200, &headers, ""),
+            vec![
+                "expected status 404, got 200",
+                "expected header server to be absent, got \"nginx/1.27.4\"",
+                "expected header content-security-policy to be present",
+            ]
+        );
+    }
+}
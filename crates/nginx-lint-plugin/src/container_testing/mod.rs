@@ -27,10 +27,16 @@
 //!     assert_eq!(resp.status(), 200);
 //! }
 //! ```
+//!
+//! For the common "start nginx, send requests, check the responses" case,
+//! [`BehaviorTest`] describes the requests and expectations declaratively
+//! and can also assert that the plugin flags the same config.
 
+pub mod behavior;
 pub mod coredns;
 pub mod nginx;
 
+pub use behavior::{BehaviorTest, RequestCheck};
 pub use reqwest;
 pub use testcontainers;
 
@@ -0,0 +1,480 @@
+//! Fixes described as edits of AST nodes
+//!
+//! [`FixBuilder`] turns "insert this directive into that block", "replace
+//! this argument" and "remove this directive" into range-based [`Fix`]es,
+//! working out byte offsets, line boundaries and indentation from the
+//! parsed [`Config`] so plugins don't have to.
+//!
+//! ```
+//! use nginx_lint_plugin::prelude::*;
+//!
+//! let source = "http {\n\tserver {\n\t\tlisten 80;\n\t}\n}\n";
+//! let config = nginx_lint_plugin::parse_string(source).unwrap();
+//! let fixes = FixBuilder::new(&config);
+//! let http = config.all_directives().find(|d| d.is("http")).unwrap();
+//!
+//! let fix = fixes
+//!     .insert_directive_into_block(http, "gzip on;", BlockPosition::Start)
+//!     .unwrap();
+//! // Inserted at the start of the `server` line, with its indentation
+//! assert_eq!(fix.new_text, "\tgzip on;\n");
+//! assert_eq!(fix.start_offset, Some(source.find("\tserver").unwrap()));
+//! ```
+
+use crate::types::{Config, ConfigItem, Directive, Fix, IndentStyle};
+
+/// Where [`FixBuilder::insert_directive_into_block`] puts the new directive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockPosition {
+    /// Before the first item of the block
+    Start,
+    /// After the last item of the block
+    End,
+}
+
+/// Builds [`Fix`]es from edits of AST nodes
+///
+/// Indentation follows the block being edited; blocks without indented
+/// contents use the indent style detected for the whole file (four spaces
+/// when nothing is indented).
+#[derive(Debug, Clone)]
+pub struct FixBuilder<'a> {
+    config: &'a Config,
+    style: IndentStyle,
+}
+
+/// Line and byte offsets of a block item
+struct ItemSpan {
+    line: usize,
+    /// Offset of the start of its line
+    line_start: usize,
+    /// Offset after the item, including a comment on the same line
+    end: usize,
+    indent: String,
+}
+
+impl ItemSpan {
+    fn of(item: &ConfigItem) -> Option<Self> {
+        let (span, leading_whitespace, end) = match item {
+            ConfigItem::Directive(d) => (
+                d.span,
+                &d.leading_whitespace,
+                d.trailing_comment
+                    .as_ref()
+                    .map_or(d.span.end.offset, |c| c.span.end.offset),
+            ),
+            ConfigItem::Comment(c) => (c.span, &c.leading_whitespace, c.span.end.offset),
+            ConfigItem::BlankLine(_) => return None,
+        };
+        let column = span.start.column - 1;
+        Some(Self {
+            line: span.start.line,
+            line_start: span.start.offset - column,
+            end,
+            indent: if leading_whitespace.len() == column {
+                leading_whitespace.clone()
+            } else {
+                " ".repeat(column)
+            },
+        })
+    }
+}
+
+impl<'a> FixBuilder<'a> {
+    /// Create a builder for fixes to `config`, using its indent style
+    pub fn new(config: &'a Config) -> Self {
+        Self {
+            config,
+            style: IndentStyle::detect(&config.items).unwrap_or_default(),
+        }
+    }
+
+    /// Insert `new_text` as a directive of the block of `directive`
+    ///
+    /// Returns `None` if `directive` has no block or a raw block such as
+    /// `content_by_lua_block`.
+    pub fn insert_directive_into_block(
+        &self,
+        directive: &Directive,
+        new_text: &str,
+        position: BlockPosition,
+    ) -> Option<Fix> {
+        self.insert_directives_into_block(directive, &[new_text], position)
+    }
+
+    /// Insert several directives into the block of `directive`, in order
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// let source = "server {\n  listen 80;\n}\n";
+    /// let config = nginx_lint_plugin::parse_string(source).unwrap();
+    /// let server = config.all_directives().find(|d| d.is("server")).unwrap();
+    ///
+    /// let fix = FixBuilder::new(&config)
+    ///     .insert_directives_into_block(
+    ///         server,
+    ///         &["server_tokens off;", "charset utf-8;"],
+    ///         BlockPosition::End,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(fix.new_text, "\n  server_tokens off;\n  charset utf-8;");
+    /// ```
+    pub fn insert_directives_into_block(
+        &self,
+        directive: &Directive,
+        lines: &[&str],
+        position: BlockPosition,
+    ) -> Option<Fix> {
+        let block = directive.block.as_ref()?;
+        if block.is_raw() {
+            return None;
+        }
+        let open = block.span.start;
+        let items: Vec<ItemSpan> = block.items.iter().filter_map(ItemSpan::of).collect();
+
+        let (Some(first), Some(last)) = (items.first(), items.last()) else {
+            // Empty block: replace everything between the braces
+            let indent = format!("{}{}", directive.line_indent(), self.style.unit());
+            let mut text: String = lines
+                .iter()
+                .map(|line| format!("\n{}{}", indent, line))
+                .collect();
+            text.push('\n');
+            text.push_str(&directive.line_indent());
+            return Some(Fix::replace_range(
+                open.offset + 1,
+                block.span.end.offset - 1,
+                &text,
+            ));
+        };
+
+        if block.span.end.line == open.line {
+            // One-line block: keep it on one line
+            let text: String = lines.iter().map(|line| format!(" {}", line)).collect();
+            let offset = match position {
+                BlockPosition::Start => open.offset + 1,
+                BlockPosition::End => last.end,
+            };
+            return Some(Fix::replace_range(offset, offset, &text));
+        }
+
+        let indent = match items.iter().find(|item| item.line != open.line) {
+            Some(item) => item.indent.clone(),
+            None => format!("{}{}", directive.line_indent(), self.style.unit()),
+        };
+        let after = |offset: usize| {
+            let text: String = lines
+                .iter()
+                .map(|line| format!("\n{}{}", indent, line))
+                .collect();
+            Fix::replace_range(offset, offset, &text)
+        };
+        Some(match position {
+            BlockPosition::Start => {
+                // Items sharing the line of `{` (such as a comment) stay first
+                match items
+                    .iter()
+                    .take_while(|item| item.line == open.line)
+                    .last()
+                {
+                    Some(item) => after(item.end),
+                    None => {
+                        let text: String = lines
+                            .iter()
+                            .map(|line| format!("{}{}\n", indent, line))
+                            .collect();
+                        Fix::replace_range(first.line_start, first.line_start, &text)
+                    }
+                }
+            }
+            BlockPosition::End => after(last.end),
+        })
+    }
+
+    /// Replace the argument at `index` of `directive` with `new_text`
+    ///
+    /// `new_text` is inserted as written, so it must carry its own quotes.
+    /// Returns `None` if the directive has fewer arguments.
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// let source = "zone backend 64k;\n";
+    /// let config = nginx_lint_plugin::parse_string(source).unwrap();
+    /// let zone = config.all_directives().next().unwrap();
+    ///
+    /// let fix = FixBuilder::new(&config).replace_argument(zone, 1, "1m").unwrap();
+    /// assert_eq!(fix.new_text, "1m");
+    /// assert_eq!(&source[fix.start_offset.unwrap()..fix.end_offset.unwrap()], "64k");
+    /// ```
+    pub fn replace_argument(
+        &self,
+        directive: &Directive,
+        index: usize,
+        new_text: &str,
+    ) -> Option<Fix> {
+        let arg = directive.args.get(index)?;
+        Some(Fix::replace_range(
+            arg.span.start.offset,
+            arg.span.end.offset,
+            new_text,
+        ))
+    }
+
+    /// Remove the argument at `index` of `directive`, with the whitespace
+    /// separating it from the name or argument before it
+    ///
+    /// Returns `None` if the directive has fewer arguments.
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// let source = "listen 443 ssl spdy;\n";
+    /// let config = nginx_lint_plugin::parse_string(source).unwrap();
+    /// let listen = config.all_directives().next().unwrap();
+    ///
+    /// let fix = FixBuilder::new(&config).remove_argument(listen, 2).unwrap();
+    /// assert_eq!(fix.new_text, "");
+    /// assert_eq!(&source[fix.start_offset.unwrap()..fix.end_offset.unwrap()], " spdy");
+    /// ```
+    pub fn remove_argument(&self, directive: &Directive, index: usize) -> Option<Fix> {
+        let arg = directive.args.get(index)?;
+        let start = match index.checked_sub(1) {
+            Some(previous) => directive.args[previous].span.end.offset,
+            None => directive.name_span.end.offset,
+        };
+        Some(Fix::replace_range(start, arg.span.end.offset, ""))
+    }
+
+    /// Remove `directive`, with its block and trailing comment
+    ///
+    /// The whole line goes when the directive starts it; otherwise only the
+    /// directive and the whitespace separating it from the text before it
+    /// are removed.
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// let source = "server {\n    listen 443 ssl; ssl on;\n}\n";
+    /// let config = nginx_lint_plugin::parse_string(source).unwrap();
+    /// let ssl = config.all_directives().find(|d| d.is("ssl")).unwrap();
+    ///
+    /// let fix = FixBuilder::new(&config).remove_directive(ssl);
+    /// assert_eq!(&source[fix.start_offset.unwrap()..fix.end_offset.unwrap()], " ssl on;");
+    /// ```
+    pub fn remove_directive(&self, directive: &Directive) -> Fix {
+        let start = directive.span.start.offset - directive.leading_whitespace.len();
+        let end = match &directive.trailing_comment {
+            Some(comment) => comment.span.end.offset + comment.trailing_whitespace.len(),
+            None => {
+                let trailing = match &directive.block {
+                    Some(block) => &block.trailing_whitespace,
+                    None => &directive.trailing_whitespace,
+                };
+                directive.span.end.offset + trailing.len()
+            }
+        };
+        // The end of a directive preceding this one on its line
+        let preceding = self
+            .config
+            .all_directives()
+            .filter(|d| {
+                d.span.end.line == directive.span.start.line
+                    && d.span.end.offset <= directive.span.start.offset
+            })
+            .map(|d| d.span.end.offset)
+            .max();
+        if let Some(preceding) = preceding {
+            Fix::replace_range(preceding, end, "")
+        } else if directive.leading_whitespace.len() != directive.span.start.column - 1 {
+            // After the `{` of a one-line block
+            Fix::replace_range(start, end, "")
+        } else if start > 0 {
+            Fix::replace_range(start - 1, end, "")
+        } else {
+            Fix::replace_range(start, end + 1, "")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(source: &str, fix: &Fix) -> String {
+        let (start, end) = (fix.start_offset.unwrap(), fix.end_offset.unwrap());
+        format!("{}{}{}", &source[..start], fix.new_text, &source[end..])
+    }
+
+    fn insert(source: &str, name: &str, lines: &[&str], position: BlockPosition) -> String {
+        let config = crate::parse_string(source).unwrap();
+        let directive = config.all_directives().find(|d| d.is(name)).unwrap();
+        let fix = FixBuilder::new(&config)
+            .insert_directives_into_block(directive, lines, position)
+            .unwrap();
+        apply(source, &fix)
+    }
+
+    fn remove(source: &str, name: &str) -> String {
+        let config = crate::parse_string(source).unwrap();
+        let directive = config.all_directives().find(|d| d.is(name)).unwrap();
+        apply(
+            source,
+            &FixBuilder::new(&config).remove_directive(directive),
+        )
+    }
+
+    #[test]
+    fn test_insert_at_start_and_end() {
+        let source = "http {\n    # servers\n    server {\n        listen 80;\n    }\n}\n";
+        assert_eq!(
+            insert(
+                source,
+                "http",
+                &["gzip on;", "gzip_vary on;"],
+                BlockPosition::Start
+            ),
+            "http {\n    gzip on;\n    gzip_vary on;\n    # servers\n    server {\n        \
+             listen 80;\n    }\n}\n"
+        );
+        assert_eq!(
+            insert(
+                source,
+                "server",
+                &["server_tokens off;"],
+                BlockPosition::End
+            ),
+            "http {\n    # servers\n    server {\n        listen 80;\n        \
+             server_tokens off;\n    }\n}\n"
+        );
+        // A comment after a trailing directive stays with it
+        assert_eq!(
+            insert(
+                "server {\n  listen 80; # http\n}\n",
+                "server",
+                &["charset utf-8;"],
+                BlockPosition::End
+            ),
+            "server {\n  listen 80; # http\n  charset utf-8;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_after_comment_on_brace_line() {
+        assert_eq!(
+            insert(
+                "server { # main\n\tlisten 80;\n}\n",
+                "server",
+                &["charset utf-8;"],
+                BlockPosition::Start
+            ),
+            "server { # main\n\tcharset utf-8;\n\tlisten 80;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_into_empty_block() {
+        // The file's indent style applies when the block has no contents
+        assert_eq!(
+            insert(
+                "events {\n\tworker_connections 64;\n}\nhttp {\n}\n",
+                "http",
+                &["gzip on;"],
+                BlockPosition::End
+            ),
+            "events {\n\tworker_connections 64;\n}\nhttp {\n\tgzip on;\n}\n"
+        );
+        assert_eq!(
+            insert(
+                "http {\n    server {}\n}\n",
+                "server",
+                &["listen 80;"],
+                BlockPosition::Start
+            ),
+            "http {\n    server {\n        listen 80;\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_into_one_line_block() {
+        let source = "location / { return 404; }\n";
+        assert_eq!(
+            insert(
+                source,
+                "location",
+                &["access_log off;"],
+                BlockPosition::Start
+            ),
+            "location / { access_log off; return 404; }\n"
+        );
+        assert_eq!(
+            insert(source, "location", &["access_log off;"], BlockPosition::End),
+            "location / { return 404; access_log off; }\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_without_block() {
+        let config =
+            crate::parse_string("listen 80;\ncontent_by_lua_block { ngx.say(1) }\n").unwrap();
+        let fixes = FixBuilder::new(&config);
+        for directive in config.all_directives() {
+            assert!(
+                fixes
+                    .insert_directive_into_block(directive, "x;", BlockPosition::Start)
+                    .is_none()
+            );
+        }
+    }
+
+    #[test]
+    fn test_replace_argument() {
+        let source = "add_header X-Frame-Options \"DENY\";\n";
+        let config = crate::parse_string(source).unwrap();
+        let directive = config.all_directives().next().unwrap();
+        let fixes = FixBuilder::new(&config);
+        let fix = fixes.replace_argument(directive, 1, "SAMEORIGIN").unwrap();
+        assert_eq!(
+            apply(source, &fix),
+            "add_header X-Frame-Options SAMEORIGIN;\n"
+        );
+        assert!(fixes.replace_argument(directive, 2, "always").is_none());
+    }
+
+    #[test]
+    fn test_remove_argument() {
+        let source = "listen spdy 443 ssl  http2;\n";
+        let config = crate::parse_string(source).unwrap();
+        let directive = config.all_directives().next().unwrap();
+        let fixes = FixBuilder::new(&config);
+        let first = fixes.remove_argument(directive, 0).unwrap();
+        assert_eq!(apply(source, &first), "listen 443 ssl  http2;\n");
+        let last = fixes.remove_argument(directive, 3).unwrap();
+        assert_eq!(apply(source, &last), "listen spdy 443 ssl;\n");
+        assert!(fixes.remove_argument(directive, 4).is_none());
+    }
+
+    #[test]
+    fn test_remove_directive() {
+        assert_eq!(
+            remove(
+                "server {\n    ssl on; # legacy\n    listen 443 ssl;\n}\n",
+                "ssl"
+            ),
+            "server {\n    listen 443 ssl;\n}\n"
+        );
+        assert_eq!(
+            remove("server {\n    listen 443 ssl; ssl on;\n}\n", "ssl"),
+            "server {\n    listen 443 ssl;\n}\n"
+        );
+        assert_eq!(remove("ssl on;\nhttp {}\n", "ssl"), "http {}\n");
+        assert_eq!(
+            remove(
+                "http {\n    server {\n        listen 80;\n    }\n    gzip on;\n}\n",
+                "server"
+            ),
+            "http {\n    gzip on;\n}\n"
+        );
+    }
+}
@@ -142,6 +142,8 @@ pub fn extract_domain(host: &str) -> &str {
     host.split(':').next().unwrap_or(host)
 }
 
+pub use nginx_lint_common::listen::normalize_listen_address;
+
 use crate::regex_scan::{Group, scan};
 
 /// Find byte offsets of `(` characters that open an unnamed PCRE capture group.
@@ -197,6 +199,82 @@ pub fn regex_has_named_capture(regex: &str) -> bool {
     scan(regex).iter().any(|(_, group)| *group == Group::Named)
 }
 
+/// Parse an nginx time value (e.g. `500ms`, `30s`, `1m30s`, `60`) into
+/// milliseconds
+///
+/// A number without a unit is seconds. Returns `None` for values that are
+/// not valid nginx time syntax (including variables).
+///
+/// # Examples
+///
+/// ```
+/// use nginx_lint_plugin::helpers::parse_time_ms;
+///
+/// assert_eq!(parse_time_ms("60"), Some(60_000));
+/// assert_eq!(parse_time_ms("1m30s"), Some(90_000));
+/// assert_eq!(parse_time_ms("$timeout"), None);
+/// ```
+pub fn parse_time_ms(value: &str) -> Option<u64> {
+    if value.is_empty() {
+        return None;
+    }
+    let mut total = 0u64;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let number: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let (unit, len) = if rest.starts_with("ms") {
+            (1, 2)
+        } else {
+            match rest.chars().next() {
+                None | Some('s') => (1_000, usize::from(!rest.is_empty())),
+                Some('m') => (60_000, 1),
+                Some('h') => (3_600_000, 1),
+                Some('d') => (86_400_000, 1),
+                Some('w') => (604_800_000, 1),
+                _ => return None,
+            }
+        };
+        total = total.checked_add(number.checked_mul(unit)?)?;
+        rest = &rest[len..];
+    }
+    Some(total)
+}
+
+/// Parse an nginx size value (e.g. `64k`, `1m`, `262144`) into bytes
+///
+/// The units `k`/`K`, `m`/`M` and `g`/`G` are powers of 1024. Returns
+/// `None` for values that are not valid nginx size syntax (including
+/// variables).
+///
+/// # Examples
+///
+/// ```
+/// use nginx_lint_plugin::helpers::parse_size;
+///
+/// assert_eq!(parse_size("64k"), Some(64 * 1024));
+/// assert_eq!(parse_size("262144"), Some(262144));
+/// assert_eq!(parse_size("$size"), None);
+/// ```
+pub fn parse_size(value: &str) -> Option<u64> {
+    let (digits, unit) = match value.char_indices().last()? {
+        (i, 'k' | 'K') => (&value[..i], 1024),
+        (i, 'm' | 'M') => (&value[..i], 1024 * 1024),
+        (i, 'g' | 'G') => (&value[..i], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse::<u64>().ok()?.checked_mul(unit)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,4 +352,26 @@ mod tests {
         assert_eq!(extract_domain("localhost:3000"), "localhost");
         assert_eq!(extract_domain("127.0.0.1:80"), "127.0.0.1");
     }
+
+    #[test]
+    fn test_parse_time_ms() {
+        assert_eq!(parse_time_ms("60"), Some(60_000));
+        assert_eq!(parse_time_ms("500ms"), Some(500));
+        assert_eq!(parse_time_ms("1m30s"), Some(90_000));
+        assert_eq!(parse_time_ms("2h"), Some(7_200_000));
+        assert_eq!(parse_time_ms(""), None);
+        assert_eq!(parse_time_ms("fast"), None);
+        assert_eq!(parse_time_ms("10x"), None);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("64k"), Some(64 * 1024));
+        assert_eq!(parse_size("1M"), Some(1024 * 1024));
+        assert_eq!(parse_size("2g"), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("262144"), Some(262144));
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("k"), None);
+        assert_eq!(parse_size("64kb"), None);
+    }
 }
@@ -0,0 +1,314 @@
+//! Settings inherited from enclosing blocks.
+//!
+//! Most nginx directives apply to the block they are in and to every block
+//! nested in it, unless a nested block sets its own value. While walking
+//! down the blocks, [`Inherited`] follows one such setting and
+//! [`InheritedSettings`] several at once. [`InheritedList`] follows an array
+//! setting (`allow`/`deny`, `error_page`, `ssl_certificate`, ...), whose
+//! whole list is replaced by a block that has any of its directives.
+//!
+//! A file included from a block inherits from blocks of another file, which
+//! are not known: the `root` constructors start such a walk as `Unknown`
+//! rather than with nginx's defaults.
+//!
+//! ```
+//! use nginx_lint_plugin::inherit::{Inherited, InheritedSettings};
+//! use nginx_lint_plugin::prelude::*;
+//!
+//! let config = nginx_lint_plugin::parse_string(r#"
+//! http {
+//!     charset utf-8;
+//!     server {
+//!         default_type application/json;
+//!     }
+//! }
+//! "#).unwrap();
+//! let http = config.directives().find(|d| d.is("http")).unwrap().block.as_ref().unwrap();
+//! let server = http.directives().find(|d| d.is("server")).unwrap().block.as_ref().unwrap();
+//!
+//! let root = InheritedSettings::root(["charset", "default_type"], &config);
+//! let http_scope = root.enter(&http.items);
+//! let server_scope = http_scope.enter(&server.items);
+//! assert_eq!(server_scope.get("charset").value("off"), Some("utf-8"));
+//! assert_eq!(http_scope.get("default_type").value("text/plain"), Some("text/plain"));
+//! assert!(matches!(server_scope.get("default_type"), Inherited::Set(d) if d.line() == 5));
+//! ```
+
+use nginx_lint_common::parser::ast::{Config, ConfigItem, Directive};
+
+/// The directives directly in `items`
+fn directives(items: &[ConfigItem]) -> impl Iterator<Item = &Directive> {
+    items.iter().filter_map(|item| match item {
+        ConfigItem::Directive(d) => Some(d.as_ref()),
+        _ => None,
+    })
+}
+
+/// Where the value of a setting in effect comes from
+#[derive(Debug, Clone, Copy)]
+pub enum Inherited<'a> {
+    /// Not set anywhere; nginx's default applies
+    Default,
+    /// Set by a directive in this file
+    Set(&'a Directive),
+    /// Not set in this file, which is included from a block that may set it
+    Unknown,
+}
+
+impl<'a> Inherited<'a> {
+    /// The setting at the top level of `config`: nginx's default, or
+    /// [`Inherited::Unknown`] when the file is included from a block
+    pub fn root(config: &Config) -> Self {
+        if config.include_context.is_empty() {
+            Inherited::Default
+        } else {
+            Inherited::Unknown
+        }
+    }
+
+    /// The setting in effect inside `items`: the last `name` directive
+    /// directly in them, or `self`
+    pub fn enter(self, items: &'a [ConfigItem], name: &str) -> Self {
+        directives(items)
+            .filter(|d| d.is(name))
+            .last()
+            .map_or(self, Inherited::Set)
+    }
+
+    /// The directive setting the value, if it is in this file
+    pub fn directive(self) -> Option<&'a Directive> {
+        match self {
+            Inherited::Set(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// The value in effect (the first argument), `default` when not set, or
+    /// `None` when not known
+    pub fn value(self, default: &'a str) -> Option<&'a str> {
+        match self {
+            Inherited::Default => Some(default),
+            Inherited::Set(d) => d.args.first().map(|a| a.as_str()),
+            Inherited::Unknown => None,
+        }
+    }
+
+    /// Whether the setting is known not to be set
+    pub fn is_default(self) -> bool {
+        matches!(self, Inherited::Default)
+    }
+
+    /// The directive setting `self` if the block entered from `outer` set
+    /// it, rather than inheriting it
+    pub fn set_here(self, outer: Self) -> Option<&'a Directive> {
+        match (self, outer) {
+            (Inherited::Set(d), Inherited::Set(o)) if std::ptr::eq(d, o) => None,
+            (Inherited::Set(d), _) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// Where the value comes from, for messages
+    pub fn origin(&self) -> String {
+        match self {
+            Inherited::Default => "nginx's default".to_string(),
+            Inherited::Set(d) => format!("line {}", d.span.start.line),
+            Inherited::Unknown => "the including file".to_string(),
+        }
+    }
+}
+
+/// Several settings in effect in a block, looked up by directive name
+#[derive(Debug, Clone, Copy)]
+pub struct InheritedSettings<'a, const N: usize> {
+    names: [&'a str; N],
+    values: [Inherited<'a>; N],
+}
+
+impl<'a, const N: usize> InheritedSettings<'a, N> {
+    /// The settings of the directives `names`, all starting as `initial`
+    pub fn new(names: [&'a str; N], initial: Inherited<'a>) -> Self {
+        Self {
+            names,
+            values: [initial; N],
+        }
+    }
+
+    /// The settings at the top level of `config` (see [`Inherited::root`])
+    pub fn root(names: [&'a str; N], config: &Config) -> Self {
+        Self::new(names, Inherited::root(config))
+    }
+
+    /// The settings in effect inside `items`, inheriting from `self`
+    pub fn enter(self, items: &'a [ConfigItem]) -> Self {
+        let mut effective = self;
+        for d in directives(items) {
+            if let Some(i) = self.names.iter().position(|name| d.is(name)) {
+                effective.values[i] = Inherited::Set(d);
+            }
+        }
+        effective
+    }
+
+    /// The setting of the directive `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not one of the names the settings were created
+    /// with.
+    pub fn get(&self, name: &str) -> Inherited<'a> {
+        match self.names.iter().position(|n| *n == name) {
+            Some(i) => self.values[i],
+            None => panic!("'{}' is not an inherited setting tracked here", name),
+        }
+    }
+}
+
+/// Where the list of an array setting in effect comes from
+#[derive(Debug, Clone, Copy)]
+pub enum InheritedList<'a> {
+    /// No level sets any of the directives; nginx's default applies
+    Default,
+    /// Set by the `names` directives in `items`, the innermost block that
+    /// has any of them
+    Set {
+        items: &'a [ConfigItem],
+        names: &'a [&'a str],
+    },
+    /// Not set in this file, which is included from a block that may set it
+    Unknown,
+}
+
+impl<'a> InheritedList<'a> {
+    /// The list at the top level of `config` (see [`Inherited::root`])
+    pub fn root(config: &Config) -> Self {
+        match Inherited::root(config) {
+            Inherited::Unknown => InheritedList::Unknown,
+            _ => InheritedList::Default,
+        }
+    }
+
+    /// The list in effect inside `items`: the `names` directives directly in
+    /// them if there are any, or `self`
+    pub fn enter(self, items: &'a [ConfigItem], names: &'a [&'a str]) -> Self {
+        if directives(items).any(|d| names.iter().any(|name| d.is(name))) {
+            InheritedList::Set { items, names }
+        } else {
+            self
+        }
+    }
+
+    /// The directives of the list in effect, in order; none unless it is
+    /// set in this file
+    pub fn directives(self) -> impl Iterator<Item = &'a Directive> {
+        let (items, names): (&'a [ConfigItem], &'a [&'a str]) = match self {
+            InheritedList::Set { items, names } => (items, names),
+            _ => (&[], &[]),
+        };
+        directives(items).filter(move |d| names.iter().any(|name| d.is(name)))
+    }
+
+    /// Whether the list is known not to be set
+    pub fn is_default(self) -> bool {
+        matches!(self, InheritedList::Default)
+    }
+
+    /// Whether the list is not known
+    pub fn is_unknown(self) -> bool {
+        matches!(self, InheritedList::Unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DirectiveExt, parse_string};
+
+    /// The items of the first block named `name`, searched depth-first
+    fn block_items<'a>(items: &'a [ConfigItem], name: &str) -> &'a [ConfigItem] {
+        directives(items)
+            .find_map(|d| {
+                let block = d.block.as_ref()?;
+                if d.is(name) {
+                    Some(&block.items[..])
+                } else {
+                    let found = block_items(&block.items, name);
+                    (!found.is_empty()).then_some(found)
+                }
+            })
+            .unwrap_or(&[])
+    }
+
+    #[test]
+    fn test_inherited_setting() {
+        let config = parse_string(
+            "http {\n    keepalive_timeout 10s;\n    keepalive_timeout 20s;\n    server {\n        location / {\n            keepalive_timeout 5s;\n        }\n    }\n}\n",
+        )
+        .unwrap();
+        let http = block_items(&config.items, "http");
+        let server = block_items(http, "server");
+        let location = block_items(server, "location");
+
+        let root = Inherited::root(&config);
+        assert!(root.is_default());
+        let in_http = root.enter(http, "keepalive_timeout");
+        let in_server = in_http.enter(server, "keepalive_timeout");
+        let in_location = in_server.enter(location, "keepalive_timeout");
+        assert_eq!(in_server.value("75s"), Some("20s"));
+        assert_eq!(in_server.origin(), "line 3");
+        assert_eq!(in_location.value("75s"), Some("5s"));
+        assert!(in_server.set_here(in_http).is_none());
+        assert_eq!(in_http.set_here(root).map(|d| d.line()), Some(3));
+        assert_eq!(in_location.set_here(in_server).map(|d| d.line()), Some(6));
+    }
+
+    #[test]
+    fn test_included_file_is_unknown() {
+        let mut config = parse_string("server {\n}\n").unwrap();
+        config.include_context = vec!["http".to_string()].into();
+        let setting = Inherited::root(&config).enter(&config.items, "charset");
+        assert!(matches!(setting, Inherited::Unknown));
+        assert_eq!(setting.value("off"), None);
+        assert!(InheritedList::root(&config).is_unknown());
+    }
+
+    #[test]
+    fn test_settings_use_the_last_directive() {
+        let config =
+            parse_string("charset utf-8;\nsource_charset koi8-r;\ncharset latin1;\n").unwrap();
+        let settings = InheritedSettings::new(["charset", "override_charset"], Inherited::Default)
+            .enter(&config.items);
+        assert_eq!(settings.get("charset").value("off"), Some("latin1"));
+        assert!(settings.get("override_charset").is_default());
+    }
+
+    #[test]
+    #[should_panic(expected = "'source_charset' is not an inherited setting")]
+    fn test_settings_get_untracked_name() {
+        InheritedSettings::new(["charset"], Inherited::Default).get("source_charset");
+    }
+
+    #[test]
+    fn test_list_is_replaced_by_a_level_with_any_directive() {
+        let config = parse_string(
+            "server {\n    deny 10.0.0.1;\n    allow all;\n    location /a {\n        allow 10.0.0.2;\n    }\n    location /b {\n        return 204;\n    }\n}\n",
+        )
+        .unwrap();
+        let server = block_items(&config.items, "server");
+        let names: &[&str] = &["allow", "deny"];
+        let list = InheritedList::root(&config).enter(server, names);
+        let lines = |list: InheritedList| list.directives().map(|d| d.line()).collect::<Vec<_>>();
+        assert_eq!(lines(list), [2, 3]);
+        assert_eq!(
+            lines(list.enter(block_items(server, "location"), names)),
+            [5]
+        );
+        let b = directives(server)
+            .filter(|d| d.is("location"))
+            .nth(1)
+            .and_then(|d| d.block.as_ref())
+            .unwrap();
+        assert_eq!(lines(list.enter(&b.items, names)), [2, 3]);
+        assert!(InheritedList::Default.directives().next().is_none());
+    }
+}
@@ -14,16 +14,26 @@
 //!
 //! - [`types`] - Core types: [`Plugin`], [`PluginSpec`], [`LintError`], [`Fix`],
 //!   [`ConfigExt`], [`DirectiveExt`]
+//! - [`CheckContext`] - File metadata for [`Plugin::check_with_context`]
+//! - [`RuleOption`], [`RuleOptions`] - Typed options users set in
+//!   `.nginx-lint.toml`, declared with [`PluginSpec::with_option`]
 //! - [`helpers`] - Utility functions for common checks (domain names, URLs, etc.)
+//! - [`location_match`] - Which `location` nginx selects for a URI
+//! - [`inherit`] - Settings inherited from enclosing blocks, and whether
+//!   they are known in an included file
 //! - [`testing`] - Test runner and builder: [`testing::PluginTestRunner`], [`testing::TestCase`]
 //! - [`native`] - [`native::NativePluginRule`] adapter for running plugins without WASM
+//! - [`variables`] - Variable definition/use table ([`VariableTable`]), also
+//!   available per config through [`ConfigExt::variable_table`]
+//! - [`njs`] - njs (`js_*`) imports, handlers and module paths
+//! - [`paths`] - The files directives refer to, resolved against the prefix
 //! - [`prelude`] - Convenient re-exports for `use nginx_lint_plugin::prelude::*`
 //!
 //! # API Versioning
 //!
 //! Plugins declare the API version they use via [`PluginSpec::api_version`].
-//! This allows the host to support multiple output formats for backward compatibility.
 //! [`PluginSpec::new()`] automatically sets the current API version ([`API_VERSION`]).
+//! The host refuses to load a plugin whose major version differs from its own.
 //!
 //! # Example
 //!
@@ -66,8 +76,13 @@
 //! assert_eq!(errors.len(), 1);
 //! ```
 
+mod check_context;
+mod fix_builder;
 pub mod helpers;
+pub mod inherit;
+pub mod location_match;
 pub mod native;
+mod options;
 pub mod testing;
 mod types;
 
@@ -79,11 +94,18 @@ pub mod wasm_config;
 #[cfg(feature = "wit-export")]
 pub mod wit_guest;
 
+pub use check_context::{AnalysisCache, CheckContext, Environment};
+pub use fix_builder::{BlockPosition, FixBuilder};
+pub use options::{OptionValue, RuleOption, RuleOptions};
 pub use types::*;
 
 // Re-export common types from nginx-lint-common
+pub use nginx_lint_common::NginxVersion;
+pub use nginx_lint_common::njs;
 pub use nginx_lint_common::parse_string;
 pub use nginx_lint_common::parser;
+pub use nginx_lint_common::paths;
+pub use nginx_lint_common::variables;
 
 /// Prelude module for convenient imports.
 ///
@@ -100,11 +122,15 @@ pub use nginx_lint_common::parser;
 /// This re-exports all core types ([`Plugin`], [`PluginSpec`], [`LintError`], [`Fix`],
 /// [`Config`], [`Directive`], etc.), extension traits ([`ConfigExt`], [`DirectiveExt`]),
 /// the [`helpers`] module, and the [`export_component_plugin!`] macro.
+pub mod regex_match;
 pub mod regex_scan;
 
 pub mod prelude {
+    pub use super::check_context::{AnalysisCache, CheckContext, Environment};
     pub use super::export_component_plugin;
+    pub use super::fix_builder::{BlockPosition, FixBuilder};
     pub use super::helpers;
+    pub use super::options::{OptionValue, RuleOption, RuleOptions};
     pub use super::types::API_VERSION;
     pub use super::types::*;
 }
@@ -141,6 +167,19 @@ macro_rules! export_component_plugin {
                 PLUGIN.get_or_init(|| <$plugin_type>::default())
             }
 
+            static CONFIGURED: std::sync::OnceLock<($plugin_type, $crate::RuleOptions)> =
+                std::sync::OnceLock::new();
+
+            /// The plugin with the user's rule options applied, and the
+            /// resolved options, for check()
+            fn get_configured_plugin() -> &'static ($plugin_type, $crate::RuleOptions) {
+                CONFIGURED.get_or_init(|| {
+                    let mut plugin = <$plugin_type>::default();
+                    let options = $crate::wit_guest::configure_plugin(&mut plugin);
+                    (plugin, options)
+                })
+            }
+
             struct ComponentExport;
 
             impl Guest for ComponentExport {
@@ -154,7 +193,7 @@ macro_rules! export_component_plugin {
                     config: &$crate::wit_guest::nginx_lint::plugin::config_api::Config,
                     path: String,
                 ) -> Vec<$crate::wit_guest::nginx_lint::plugin::types::LintError> {
-                    let plugin = get_plugin();
+                    let (plugin, options) = get_configured_plugin();
                     // Reconstruct parser Config from host resource handle,
                     // pruned to relevant_directives() if the plugin declared it
                     let config = match $crate::Plugin::relevant_directives(plugin) {
@@ -163,7 +202,13 @@ macro_rules! export_component_plugin {
                         }
                         None => $crate::wit_guest::reconstruct_config(config),
                     };
-                    let errors = $crate::Plugin::check(plugin, &config, &path);
+                    let target_nginx_version = $crate::wit_guest::target_nginx_version();
+                    let environment = $crate::wit_guest::environment();
+                    let ctx = $crate::CheckContext::new(&config, &path)
+                        .with_target_nginx_version(target_nginx_version.as_deref())
+                        .with_environment(&environment)
+                        .with_options(options);
+                    let errors = $crate::Plugin::check_with_context(plugin, &config, &ctx);
                     errors
                         .into_iter()
                         .map($crate::wit_guest::convert_lint_error)
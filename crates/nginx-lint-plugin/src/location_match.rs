@@ -0,0 +1,339 @@
+//! Which `location` nginx selects for a URI.
+//!
+//! [`LocationTree`] holds the locations of a `server` block (and their
+//! nested locations) and [`LocationTree::select`] follows nginx's own
+//! search:
+//!
+//! 1. An exact (`=`) location equal to the URI wins outright.
+//! 2. Otherwise the longest matching prefix location is remembered, and its
+//!    nested locations are searched the same way; a match found there wins.
+//! 3. Unless that prefix location is `^~`, the regex (`~`, `~*`) locations
+//!    are tried in config order and the first match wins, replacing the
+//!    prefix location.
+//! 4. Otherwise the remembered prefix location is used.
+//!
+//! Regexes are matched with [`crate::regex_match`]; when a regex that has
+//! to be tried uses syntax it does not support, `select` returns `None`
+//! rather than guess.
+//!
+//! ```
+//! use nginx_lint_plugin::location_match::LocationTree;
+//!
+//! let config = nginx_lint_plugin::parse_string(r#"
+//! server {
+//!     location /admin/ { deny all; }
+//!     location ~ \.php$ { fastcgi_pass php; }
+//! }
+//! "#).unwrap();
+//! let server = config.all_directives().find(|d| d.is("server")).unwrap();
+//! let tree = LocationTree::from_items(&server.block.as_ref().unwrap().items);
+//!
+//! let chain = tree.select("/admin/index.php").unwrap();
+//! assert_eq!(chain.last().unwrap().pattern(), r"\.php$");
+//! let chain = tree.select("/admin/users").unwrap();
+//! assert_eq!(chain.last().unwrap().pattern(), "/admin/");
+//! ```
+
+use crate::regex_match::Regex;
+use nginx_lint_common::parser::ast::{ConfigItem, Directive};
+
+/// How a location matches URIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocationModifier {
+    /// `location = /uri`
+    Exact,
+    /// `location /uri`
+    Prefix,
+    /// `location ^~ /uri`
+    PrefixNoRegex,
+    /// `location ~ regex`
+    Regex,
+    /// `location ~* regex`
+    RegexCaseInsensitive,
+    /// `location @name`
+    Named,
+}
+
+/// A `location` block and the locations nested in it
+#[derive(Debug, Clone)]
+pub struct Location<'a> {
+    directive: &'a Directive,
+    modifier: LocationModifier,
+    pattern: &'a str,
+    /// Parsed regex of a regex location; `None` when unsupported
+    regex: Option<Regex>,
+    children: LocationTree<'a>,
+}
+
+impl<'a> Location<'a> {
+    fn from_directive(directive: &'a Directive) -> Option<Self> {
+        if !directive.is("location") {
+            return None;
+        }
+        let (modifier, pattern) = match directive.args.as_slice() {
+            [m, p, ..] if m.as_str() == "=" => (LocationModifier::Exact, p),
+            [m, p, ..] if m.as_str() == "^~" => (LocationModifier::PrefixNoRegex, p),
+            [m, p, ..] if m.as_str() == "~" => (LocationModifier::Regex, p),
+            [m, p, ..] if m.as_str() == "~*" => (LocationModifier::RegexCaseInsensitive, p),
+            [p, ..] if p.as_str().starts_with('@') => (LocationModifier::Named, p),
+            [p, ..] => (LocationModifier::Prefix, p),
+            [] => return None,
+        };
+        let pattern = pattern.as_str();
+        let regex = match modifier {
+            LocationModifier::Regex => Regex::new(pattern, false),
+            LocationModifier::RegexCaseInsensitive => Regex::new(pattern, true),
+            _ => None,
+        };
+        let children = directive
+            .block
+            .as_ref()
+            .map(|block| LocationTree::from_items(&block.items))
+            .unwrap_or_default();
+        Some(Self {
+            directive,
+            modifier,
+            pattern,
+            regex,
+            children,
+        })
+    }
+
+    /// The `location` directive
+    pub fn directive(&self) -> &'a Directive {
+        self.directive
+    }
+
+    pub fn modifier(&self) -> LocationModifier {
+        self.modifier
+    }
+
+    /// The URI or regex the location matches
+    pub fn pattern(&self) -> &'a str {
+        self.pattern
+    }
+
+    /// The location's arguments as written, e.g. `~ \.php$`
+    pub fn display(&self) -> String {
+        self.directive
+            .args
+            .iter()
+            .map(|arg| arg.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Locations nested in this one
+    pub fn children(&self) -> &LocationTree<'a> {
+        &self.children
+    }
+
+    pub fn is_regex(&self) -> bool {
+        matches!(
+            self.modifier,
+            LocationModifier::Regex | LocationModifier::RegexCaseInsensitive
+        )
+    }
+
+    /// Whether the location matches `uri` on its own, ignoring the other
+    /// locations; `None` for a regex that cannot be evaluated
+    pub fn matches(&self, uri: &str) -> Option<bool> {
+        match self.modifier {
+            LocationModifier::Exact => Some(uri == self.pattern),
+            LocationModifier::Prefix | LocationModifier::PrefixNoRegex => {
+                Some(uri.starts_with(self.pattern))
+            }
+            LocationModifier::Regex | LocationModifier::RegexCaseInsensitive => {
+                Some(self.regex.as_ref()?.is_match(uri))
+            }
+            LocationModifier::Named => Some(false),
+        }
+    }
+
+    /// A string the location's regex matches (see [`Regex::example`]);
+    /// `None` for other locations and unsupported regexes
+    pub fn regex_example(&self) -> Option<String> {
+        self.regex.as_ref().map(Regex::example)
+    }
+}
+
+/// The locations of one block, in config order
+#[derive(Debug, Clone, Default)]
+pub struct LocationTree<'a> {
+    locations: Vec<Location<'a>>,
+}
+
+/// Outcome of searching one level of the tree
+enum Search {
+    /// A final location was selected (exact or regex match)
+    Done,
+    /// Only a prefix location matched (or none, if the chain did not grow)
+    Prefix,
+}
+
+impl<'a> LocationTree<'a> {
+    /// Collect the `location` blocks directly in `items` (e.g. a `server`
+    /// block's items), with the locations nested in them
+    pub fn from_items(items: &'a [ConfigItem]) -> Self {
+        let locations = items
+            .iter()
+            .filter_map(|item| match item {
+                ConfigItem::Directive(directive) => Location::from_directive(directive),
+                _ => None,
+            })
+            .collect();
+        Self { locations }
+    }
+
+    /// The locations of this level
+    pub fn locations(&self) -> &[Location<'a>] {
+        &self.locations
+    }
+
+    /// Every location with the locations enclosing it, outermost first
+    pub fn chains(&self) -> Vec<Vec<&Location<'a>>> {
+        let mut chains = Vec::new();
+        self.collect_chains(&mut Vec::new(), &mut chains);
+        chains
+    }
+
+    fn collect_chains<'t>(
+        &'t self,
+        parents: &mut Vec<&'t Location<'a>>,
+        chains: &mut Vec<Vec<&'t Location<'a>>>,
+    ) {
+        for location in &self.locations {
+            parents.push(location);
+            chains.push(parents.clone());
+            location.children.collect_chains(parents, chains);
+            parents.pop();
+        }
+    }
+
+    /// The location nginx selects for `uri`, with the locations enclosing
+    /// it, outermost first; empty when no location matches and the request
+    /// is handled by the enclosing block itself
+    ///
+    /// `None` when the answer depends on a regex that cannot be evaluated.
+    pub fn select(&self, uri: &str) -> Option<Vec<&Location<'a>>> {
+        let mut chain = Vec::new();
+        self.search(uri, &mut chain)?;
+        Some(chain)
+    }
+
+    fn search<'t>(&'t self, uri: &str, chain: &mut Vec<&'t Location<'a>>) -> Option<Search> {
+        let level = chain.len();
+
+        let exact = self
+            .locations
+            .iter()
+            .find(|l| l.modifier == LocationModifier::Exact && l.pattern == uri);
+        if let Some(location) = exact {
+            chain.push(location);
+            return Some(Search::Done);
+        }
+
+        let prefix = self
+            .locations
+            .iter()
+            .filter(|l| {
+                matches!(
+                    l.modifier,
+                    LocationModifier::Prefix | LocationModifier::PrefixNoRegex
+                ) && uri.starts_with(l.pattern)
+            })
+            .max_by_key(|l| l.pattern.len());
+        let mut noregex = false;
+        if let Some(location) = prefix {
+            chain.push(location);
+            noregex = location.modifier == LocationModifier::PrefixNoRegex;
+            if let Search::Done = location.children.search(uri, chain)? {
+                return Some(Search::Done);
+            }
+        }
+
+        if !noregex {
+            for location in self.locations.iter().filter(|l| l.is_regex()) {
+                if location.matches(uri)? {
+                    chain.truncate(level);
+                    chain.push(location);
+                    location.children.search(uri, chain)?;
+                    return Some(Search::Done);
+                }
+            }
+        }
+        Some(Search::Prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_string;
+
+    fn select(config: &str, uri: &str) -> Option<Vec<String>> {
+        let config = parse_string(config).unwrap();
+        let tree = LocationTree::from_items(&config.items);
+        tree.select(uri)
+            .map(|chain| chain.iter().map(|l| l.display()).collect())
+    }
+
+    const LOCATIONS: &str = r#"
+location = / { }
+location / { }
+location /images/ {
+    location ~ \.svg$ { }
+}
+location ^~ /static/ { }
+location ~* \.(png|jpg)$ { }
+location ~ ^/api/ { }
+location @fallback { }
+"#;
+
+    #[test]
+    fn test_selection_order() {
+        assert_eq!(select(LOCATIONS, "/").unwrap(), ["= /"]);
+        assert_eq!(select(LOCATIONS, "/about").unwrap(), ["/"]);
+        assert_eq!(select(LOCATIONS, "/images/a.gif").unwrap(), ["/images/"]);
+        // Nested regex before outer regex
+        assert_eq!(
+            select(LOCATIONS, "/images/a.svg").unwrap(),
+            ["/images/", r"~ \.svg$"]
+        );
+        // Regex replaces the prefix location
+        assert_eq!(
+            select(LOCATIONS, "/images/a.PNG").unwrap(),
+            [r"~* \.(png|jpg)$"]
+        );
+        // ^~ stops the regex search
+        assert_eq!(select(LOCATIONS, "/static/a.png").unwrap(), ["^~ /static/"]);
+        assert_eq!(select(LOCATIONS, "/api/users").unwrap(), ["~ ^/api/"]);
+    }
+
+    #[test]
+    fn test_no_location_matches() {
+        let chain = select("location /a/ { }", "/b").unwrap();
+        assert!(chain.is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_regex_is_unknown() {
+        let config = "location /a/ { }\nlocation ~ ^/(?!b) { }\n";
+        assert_eq!(select(config, "/a/x"), None);
+        // Not consulted behind ^~
+        assert!(select("location ^~ /a/ { }\nlocation ~ (?!b) { }\n", "/a/x").is_some());
+    }
+
+    #[test]
+    fn test_chains() {
+        let config = parse_string(LOCATIONS).unwrap();
+        let tree = LocationTree::from_items(&config.items);
+        let chains: Vec<Vec<String>> = tree
+            .chains()
+            .iter()
+            .map(|chain| chain.iter().map(|l| l.display()).collect())
+            .collect();
+        assert_eq!(chains.len(), 8);
+        assert_eq!(chains[3], ["/images/", r"~ \.svg$"]);
+    }
+}
@@ -28,13 +28,17 @@
 //! // `rule` now implements LintRule and can be registered in the linter
 //! ```
 
+use crate::check_context::{CheckContext, Environment};
+use crate::options::RuleOptions;
 use crate::types::{
     Fix as PluginFix, LintError as PluginLintError, Plugin, Severity as PluginSeverity,
 };
 use nginx_lint_common::linter::{
-    Fix as CommonFix, LintError as CommonLintError, LintRule, Severity as CommonSeverity,
+    AnalysisCache, Fix as CommonFix, LintError as CommonLintError, LintRule,
+    Severity as CommonSeverity,
 };
 use nginx_lint_common::parser::ast::Config;
+use std::collections::HashMap;
 use std::path::Path;
 
 /// Convert a plugin Fix to a common Fix
@@ -47,6 +51,7 @@ fn convert_fix(fix: PluginFix) -> CommonFix {
         insert_after: fix.insert_after,
         start_offset: fix.start_offset,
         end_offset: fix.end_offset,
+        applicability: fix.applicability,
     }
 }
 
@@ -61,6 +66,9 @@ fn convert_lint_error(err: PluginLintError) -> CommonLintError {
 
     if let (Some(line), Some(column)) = (err.line, err.column) {
         common = common.with_location(line, column);
+        if let Some(end_column) = err.end_column.filter(|&end| end > column) {
+            common = common.with_end_column(end_column);
+        }
     } else if let Some(line) = err.line {
         common = common.with_location(line, 1);
     }
@@ -69,6 +77,11 @@ fn convert_lint_error(err: PluginLintError) -> CommonLintError {
         common = common.with_fix(convert_fix(fix));
     }
 
+    for alternative in err.alternatives {
+        let fixes = alternative.fixes.into_iter().map(convert_fix).collect();
+        common = common.with_alternative(&alternative.label, fixes);
+    }
+
     common
 }
 
@@ -88,6 +101,13 @@ pub struct NativePluginRule<P: Plugin> {
     references: Option<Vec<String>>,
     min_nginx_version: Option<&'static str>,
     max_nginx_version: Option<&'static str>,
+    preset: Option<&'static str>,
+    /// Resolved options (the declared defaults until configured)
+    options: RuleOptions,
+    /// The configured `target_nginx_version`
+    target_nginx_version: Option<String>,
+    /// The configured `[environment]` settings
+    environment: Environment,
 }
 
 impl<P: Plugin> Default for NativePluginRule<P> {
@@ -104,6 +124,7 @@ impl<P: Plugin> NativePluginRule<P> {
     /// Create a NativePluginRule with a pre-configured plugin instance
     pub fn with_plugin(plugin: P) -> Self {
         let spec = plugin.spec();
+        let options = RuleOptions::defaults(&spec.options);
 
         // Leak strings for 'static lifetime (same approach as ComponentLintRule)
         let name: &'static str = Box::leak(spec.name.into_boxed_str());
@@ -122,6 +143,7 @@ impl<P: Plugin> NativePluginRule<P> {
         let max_nginx_version: Option<&'static str> = spec
             .max_nginx_version
             .map(|s| &*Box::leak(s.into_boxed_str()));
+        let preset: Option<&'static str> = spec.preset.map(|s| &*Box::leak(s.into_boxed_str()));
 
         Self {
             plugin,
@@ -135,8 +157,24 @@ impl<P: Plugin> NativePluginRule<P> {
             references,
             min_nginx_version,
             max_nginx_version,
+            preset,
+            options,
+            target_nginx_version: None,
+            environment: Environment::default(),
         }
     }
+
+    /// Run the plugin with a context built from the rule's settings
+    fn run(&self, config: &Config, path: &Path, cache: &AnalysisCache) -> Vec<CommonLintError> {
+        let path_str = path.to_string_lossy();
+        let ctx = CheckContext::new(config, &path_str)
+            .with_target_nginx_version(self.target_nginx_version.as_deref())
+            .with_environment(&self.environment)
+            .with_options(&self.options)
+            .with_cache(cache);
+        let errors = self.plugin.check_with_context(config, &ctx);
+        errors.into_iter().map(convert_lint_error).collect()
+    }
 }
 
 impl<P: Plugin + Send + Sync> LintRule for NativePluginRule<P> {
@@ -153,9 +191,16 @@ impl<P: Plugin + Send + Sync> LintRule for NativePluginRule<P> {
     }
 
     fn check(&self, config: &Config, path: &Path) -> Vec<CommonLintError> {
-        let path_str = path.to_string_lossy();
-        let errors = self.plugin.check(config, &path_str);
-        errors.into_iter().map(convert_lint_error).collect()
+        self.run(config, path, &AnalysisCache::new())
+    }
+
+    fn check_with_cache(
+        &self,
+        config: &Config,
+        path: &Path,
+        cache: &AnalysisCache,
+    ) -> Vec<CommonLintError> {
+        self.run(config, path, cache)
     }
 
     fn severity(&self) -> Option<&str> {
@@ -185,4 +230,77 @@ impl<P: Plugin + Send + Sync> LintRule for NativePluginRule<P> {
     fn max_nginx_version(&self) -> Option<&str> {
         self.max_nginx_version
     }
+
+    fn preset(&self) -> Option<&str> {
+        self.preset
+    }
+
+    fn configure(&mut self, options: &HashMap<String, serde_json::Value>) -> Vec<String> {
+        self.options = RuleOptions::resolve(&self.plugin.spec().options, options);
+        self.plugin.configure(&self.options);
+        self.options.problems().to_vec()
+    }
+
+    fn set_target_nginx_version(&mut self, version: &str) {
+        self.target_nginx_version = Some(version.to_string());
+    }
+
+    fn set_environment(&mut self, environment: &Environment) {
+        self.environment = environment.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PluginSpec;
+
+    /// Reports what it found in its context
+    #[derive(Default)]
+    struct ContextEcho;
+
+    impl Plugin for ContextEcho {
+        fn spec(&self) -> PluginSpec {
+            PluginSpec::new("context-echo", "test", "Reports its check context")
+                .with_option(crate::RuleOption::integer("level", 1, "Level"))
+        }
+
+        fn check(&self, _config: &Config, _path: &str) -> Vec<PluginLintError> {
+            Vec::new()
+        }
+
+        fn check_with_context(&self, _config: &Config, ctx: &CheckContext) -> Vec<PluginLintError> {
+            let message = format!(
+                "{} {:?} {:?} {}",
+                ctx.path(),
+                ctx.target_nginx_version(),
+                ctx.environment().behind_load_balancer,
+                ctx.options().integer("level")
+            );
+            vec![self.spec().error_builder().warning(&message, 1, 1)]
+        }
+    }
+
+    fn message(rule: &NativePluginRule<ContextEcho>) -> String {
+        let config = crate::parse_string("").unwrap();
+        let errors = rule.check(&config, Path::new("nginx.conf"));
+        errors[0].message.clone()
+    }
+
+    #[test]
+    fn test_check_with_context_receives_settings() {
+        let mut rule = NativePluginRule::<ContextEcho>::new();
+        assert_eq!(message(&rule), "nginx.conf None None 1");
+
+        rule.set_target_nginx_version("1.24.0");
+        rule.set_environment(&Environment {
+            behind_load_balancer: Some(true),
+            ..Default::default()
+        });
+        rule.configure(&HashMap::from([(
+            "level".to_string(),
+            serde_json::json!(3),
+        )]));
+        assert_eq!(message(&rule), "nginx.conf Some(\"1.24.0\") Some(true) 3");
+    }
 }
@@ -0,0 +1,351 @@
+//! Typed rule options.
+//!
+//! A plugin declares the options it understands with
+//! [`PluginSpec::with_option`](crate::PluginSpec::with_option), and users set
+//! them in the rule's section of `.nginx-lint.toml`:
+//!
+//! ```toml
+//! [rules.my-rule]
+//! max_depth = 3
+//! ```
+//!
+//! The host resolves the configured values against the declarations and
+//! passes them to [`Plugin::configure`](crate::Plugin::configure) before the
+//! rule checks anything. Options that are not set resolve to their declared
+//! default, so a plugin reads every option it declared without handling
+//! missing values.
+//!
+//! ```
+//! use nginx_lint_plugin::prelude::*;
+//! use std::collections::HashMap;
+//!
+//! let declared = [RuleOption::integer("max_depth", 5, "Deepest allowed nesting")];
+//! let configured = HashMap::from([("max_depth".to_string(), serde_json::json!(3))]);
+//!
+//! let options = RuleOptions::resolve(&declared, &configured);
+//! assert_eq!(options.integer("max_depth"), 3);
+//! assert!(options.problems().is_empty());
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Value of a rule option
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OptionValue {
+    Bool(bool),
+    Integer(i64),
+    String(String),
+    StringList(Vec<String>),
+    /// Tables such as `[[rules.my-rule.extra]]` sections, read by the plugin
+    TableList(Vec<serde_json::Map<String, serde_json::Value>>),
+}
+
+impl OptionValue {
+    /// Name of the value's type, as used in messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            OptionValue::Bool(_) => "a boolean",
+            OptionValue::Integer(_) => "an integer",
+            OptionValue::String(_) => "a string",
+            OptionValue::StringList(_) => "a list of strings",
+            OptionValue::TableList(_) => "a list of tables",
+        }
+    }
+
+    /// Convert a configured value to the type of `self`
+    fn convert(&self, value: &serde_json::Value) -> Option<OptionValue> {
+        match self {
+            OptionValue::Bool(_) => value.as_bool().map(OptionValue::Bool),
+            OptionValue::Integer(_) => value.as_i64().map(OptionValue::Integer),
+            OptionValue::String(_) => value.as_str().map(|s| OptionValue::String(s.to_string())),
+            OptionValue::StringList(_) => value
+                .as_array()?
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .map(OptionValue::StringList),
+            OptionValue::TableList(_) => value
+                .as_array()?
+                .iter()
+                .map(|item| item.as_object().cloned())
+                .collect::<Option<Vec<_>>>()
+                .map(OptionValue::TableList),
+        }
+    }
+}
+
+/// An option declared by a plugin.
+///
+/// The type of the option is the type of its default value.
+///
+/// ```
+/// use nginx_lint_plugin::prelude::*;
+///
+/// let spec = PluginSpec::new("my-rule", "style", "Example rule")
+///     .with_option(RuleOption::integer("max_depth", 5, "Deepest allowed nesting"))
+///     .with_option(RuleOption::string_list("ignored", &["events"], "Blocks to skip"));
+/// assert_eq!(spec.options.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleOption {
+    /// Key in the rule's config section (e.g. `"max_depth"`)
+    pub name: String,
+    /// One-line description for documentation
+    pub description: String,
+    /// Value used when the option is not set
+    pub default: OptionValue,
+}
+
+impl RuleOption {
+    fn new(name: &str, default: OptionValue, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            default,
+        }
+    }
+
+    /// An integer option
+    pub fn integer(name: &str, default: i64, description: &str) -> Self {
+        Self::new(name, OptionValue::Integer(default), description)
+    }
+
+    /// A boolean option
+    pub fn bool(name: &str, default: bool, description: &str) -> Self {
+        Self::new(name, OptionValue::Bool(default), description)
+    }
+
+    /// A string option
+    pub fn string(name: &str, default: &str, description: &str) -> Self {
+        Self::new(name, OptionValue::String(default.to_string()), description)
+    }
+
+    /// A list-of-strings option
+    pub fn string_list(name: &str, default: &[&str], description: &str) -> Self {
+        Self::new(
+            name,
+            OptionValue::StringList(default.iter().map(|s| s.to_string()).collect()),
+            description,
+        )
+    }
+
+    /// A list-of-tables option, empty by default
+    pub fn table_list(name: &str, description: &str) -> Self {
+        Self::new(name, OptionValue::TableList(Vec::new()), description)
+    }
+}
+
+/// Resolved values of a plugin's declared options.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleOptions {
+    values: HashMap<String, OptionValue>,
+    problems: Vec<String>,
+}
+
+impl RuleOptions {
+    /// Resolve `configured` values (keyed by option name) against the
+    /// `declared` options
+    ///
+    /// A value of the wrong type falls back to the option's default, and a
+    /// key that no option declares is ignored; both are recorded in
+    /// [`problems`](Self::problems).
+    pub fn resolve(
+        declared: &[RuleOption],
+        configured: &HashMap<String, serde_json::Value>,
+    ) -> Self {
+        let mut problems = Vec::new();
+        let values = declared
+            .iter()
+            .map(|option| {
+                let value = match configured.get(&option.name) {
+                    None => option.default.clone(),
+                    Some(value) => option.default.convert(value).unwrap_or_else(|| {
+                        problems.push(format!(
+                            "option '{}' must be {}, got {}; using the default",
+                            option.name,
+                            option.default.type_name(),
+                            value
+                        ));
+                        option.default.clone()
+                    }),
+                };
+                (option.name.clone(), value)
+            })
+            .collect();
+
+        let mut unknown: Vec<&String> = configured
+            .keys()
+            .filter(|key| !declared.iter().any(|option| &option.name == *key))
+            .collect();
+        unknown.sort();
+        problems.extend(
+            unknown
+                .into_iter()
+                .map(|key| format!("unknown option '{}'", key)),
+        );
+
+        Self { values, problems }
+    }
+
+    /// Every declared option at its default
+    pub fn defaults(declared: &[RuleOption]) -> Self {
+        Self::resolve(declared, &HashMap::new())
+    }
+
+    /// Configured values that could not be used
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    /// The value of option `name`, if it was declared
+    pub fn get(&self, name: &str) -> Option<&OptionValue> {
+        self.values.get(name)
+    }
+
+    /// The value of integer option `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin did not declare `name` as an integer option.
+    pub fn integer(&self, name: &str) -> i64 {
+        match self.get(name) {
+            Some(OptionValue::Integer(value)) => *value,
+            other => undeclared(name, "integer", other),
+        }
+    }
+
+    /// The value of boolean option `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin did not declare `name` as a boolean option.
+    pub fn bool(&self, name: &str) -> bool {
+        match self.get(name) {
+            Some(OptionValue::Bool(value)) => *value,
+            other => undeclared(name, "bool", other),
+        }
+    }
+
+    /// The value of string option `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin did not declare `name` as a string option.
+    pub fn string(&self, name: &str) -> &str {
+        match self.get(name) {
+            Some(OptionValue::String(value)) => value,
+            other => undeclared(name, "string", other),
+        }
+    }
+
+    /// The value of list-of-strings option `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin did not declare `name` as a list-of-strings
+    /// option.
+    pub fn string_list(&self, name: &str) -> &[String] {
+        match self.get(name) {
+            Some(OptionValue::StringList(value)) => value,
+            other => undeclared(name, "string_list", other),
+        }
+    }
+
+    /// The value of list-of-tables option `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin did not declare `name` as a list-of-tables
+    /// option.
+    pub fn table_list(&self, name: &str) -> &[serde_json::Map<String, serde_json::Value>] {
+        match self.get(name) {
+            Some(OptionValue::TableList(value)) => value,
+            other => undeclared(name, "table_list", other),
+        }
+    }
+}
+
+fn undeclared(name: &str, kind: &str, found: Option<&OptionValue>) -> ! {
+    match found {
+        Some(value) => panic!(
+            "option '{}' is declared as {}, not read with {}()",
+            name,
+            value.type_name(),
+            kind
+        ),
+        None => panic!("option '{}' is not declared in the plugin spec", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn declared() -> Vec<RuleOption> {
+        vec![
+            RuleOption::integer("max", 10, "Maximum"),
+            RuleOption::bool("strict", false, "Strict mode"),
+            RuleOption::string("mode", "auto", "Mode"),
+            RuleOption::string_list("names", &["a"], "Names"),
+            RuleOption::table_list("extra", "Extra entries"),
+        ]
+    }
+
+    #[test]
+    fn test_resolve_defaults_and_values() {
+        let options = RuleOptions::defaults(&declared());
+        assert_eq!(options.integer("max"), 10);
+        assert!(!options.bool("strict"));
+        assert_eq!(options.string("mode"), "auto");
+        assert_eq!(options.string_list("names"), ["a"]);
+        assert!(options.table_list("extra").is_empty());
+
+        let configured = HashMap::from([
+            ("max".to_string(), json!(3)),
+            ("strict".to_string(), json!(true)),
+            ("names".to_string(), json!(["x", "y"])),
+            ("extra".to_string(), json!([{ "name": "x" }])),
+        ]);
+        let options = RuleOptions::resolve(&declared(), &configured);
+        assert_eq!(options.integer("max"), 3);
+        assert!(options.bool("strict"));
+        assert_eq!(options.string("mode"), "auto");
+        assert_eq!(options.string_list("names"), ["x", "y"]);
+        assert_eq!(options.table_list("extra")[0]["name"], "x");
+        assert!(options.problems().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_reports_problems() {
+        let configured = HashMap::from([
+            ("max".to_string(), json!("ten")),
+            ("names".to_string(), json!(["x", 1])),
+            ("maxx".to_string(), json!(3)),
+            ("extra".to_string(), json!(["x"])),
+        ]);
+        let options = RuleOptions::resolve(&declared(), &configured);
+
+        assert_eq!(options.integer("max"), 10);
+        assert_eq!(options.string_list("names"), ["a"]);
+        let mut problems = options.problems().to_vec();
+        problems.sort();
+        assert_eq!(
+            problems,
+            vec![
+                "option 'extra' must be a list of tables, got [\"x\"]; using the default",
+                "option 'max' must be an integer, got \"ten\"; using the default",
+                "option 'names' must be a list of strings, got [\"x\",1]; using the default",
+                "unknown option 'maxx'",
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "option 'max' is declared as an integer, not read with bool()")]
+    fn test_reading_wrong_type_panics() {
+        RuleOptions::defaults(&declared()).bool("max");
+    }
+}
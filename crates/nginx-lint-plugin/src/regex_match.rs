@@ -0,0 +1,488 @@
+//! Matching for the subset of PCRE that `location` regexes are written in.
+//!
+//! Rules that ask which location serves a URI need to run the location's
+//! regex against it. Plugins are built for WASM without a regex engine, so
+//! this is a small backtracking matcher covering what nginx configs use in
+//! practice: literals and escapes, `.`, character classes, groups (plain,
+//! non-capturing and named), alternation, greedy and lazy quantifiers, and
+//! the `^` / `$` anchors.
+//!
+//! Anything else — lookarounds, back references, inline flags, `\Q...\E`,
+//! POSIX classes, word boundaries — makes [`Regex::new`] return `None`, so a
+//! rule can tell "does not match" apart from "cannot tell" and stay quiet on
+//! the latter.
+//!
+//! ```
+//! use nginx_lint_plugin::regex_match::Regex;
+//!
+//! let php = Regex::new(r"\.php$", false).unwrap();
+//! assert!(php.is_match("/admin/index.php"));
+//! assert!(!php.is_match("/admin/index.php.bak"));
+//! assert_eq!(php.example(), ".php");
+//!
+//! assert!(Regex::new(r"^/(?=admin)", false).is_none());
+//! ```
+
+/// A parsed regex
+#[derive(Debug, Clone)]
+pub struct Regex {
+    node: Node,
+    case_insensitive: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+impl Regex {
+    /// Parse `pattern`, matching case-insensitively (as `~*` does) when
+    /// `case_insensitive` is set; `None` if it uses unsupported syntax
+    pub fn new(pattern: &str, case_insensitive: bool) -> Option<Self> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let node = parser.alternation()?;
+        if parser.pos != parser.chars.len() {
+            return None;
+        }
+        Some(Self {
+            node,
+            case_insensitive,
+        })
+    }
+
+    /// Whether the regex matches anywhere in `text`
+    pub fn is_match(&self, text: &str) -> bool {
+        let matcher = Matcher {
+            input: text.chars().collect(),
+            case_insensitive: self.case_insensitive,
+        };
+        (0..=matcher.input.len()).any(|start| matcher.node(&self.node, start, &mut |_| true))
+    }
+
+    /// A short string the regex matches: the first alternative of each
+    /// group, the fewest repetitions, and a representative character for
+    /// `.` and classes
+    pub fn example(&self) -> String {
+        let mut out = String::new();
+        example(&self.node, &mut out);
+        out
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let len = s.chars().count();
+        if self.chars.len() >= self.pos + len
+            && self.chars[self.pos..self.pos + len]
+                .iter()
+                .copied()
+                .eq(s.chars())
+        {
+            self.pos += len;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Option<Node> {
+        let mut alternatives = vec![self.concatenation()?];
+        while self.eat('|') {
+            alternatives.push(self.concatenation()?);
+        }
+        Some(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Node::Alt(alternatives)
+        })
+    }
+
+    fn concatenation(&mut self) -> Option<Node> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Some(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn atom(&mut self) -> Option<Node> {
+        let c = self.peek()?;
+        self.pos += 1;
+        match c {
+            '(' => {
+                // Only non-capturing and named groups are plain groups
+                if self.eat('?') {
+                    let supported = if self.eat(':') {
+                        true
+                    } else if self.eat_str("P<") || self.eat('<') && !self.at_lookbehind() {
+                        self.skip_name('>')
+                    } else if self.eat('\'') {
+                        self.skip_name('\'')
+                    } else {
+                        false
+                    };
+                    if !supported {
+                        return None;
+                    }
+                }
+                let inner = self.alternation()?;
+                self.eat(')').then_some(inner)
+            }
+            '[' => self.class(),
+            '.' => Some(Node::Any),
+            '^' => Some(Node::Start),
+            '$' => Some(Node::End),
+            '\\' => self.escape(false),
+            '*' | '+' | '?' | ')' => None,
+            '{' if self.repetition_bounds().is_some() => None,
+            _ => Some(Node::Char(c)),
+        }
+    }
+
+    /// Whether a `(?<` just read opens a lookbehind (`(?<=`, `(?<!`)
+    fn at_lookbehind(&self) -> bool {
+        matches!(self.peek(), Some('=' | '!'))
+    }
+
+    /// Skip a group name up to and including `end`
+    fn skip_name(&mut self, end: char) -> bool {
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == end {
+                return true;
+            }
+            if !(c.is_ascii_alphanumeric() || c == '_') {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// Parse an escape after its `\`; `in_class` rejects escapes that cannot
+    /// be expressed as class ranges
+    fn escape(&mut self, in_class: bool) -> Option<Node> {
+        let c = self.peek()?;
+        self.pos += 1;
+        let class = |ranges: &[(char, char)], negated: bool| Node::Class {
+            ranges: ranges.to_vec(),
+            negated,
+        };
+        const DIGIT: &[(char, char)] = &[('0', '9')];
+        const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+        const SPACE: &[(char, char)] = &[(' ', ' '), ('\t', '\r')];
+        Some(match c {
+            'd' => class(DIGIT, false),
+            'w' => class(WORD, false),
+            's' => class(SPACE, false),
+            'D' | 'W' | 'S' if in_class => return None,
+            'D' => class(DIGIT, true),
+            'W' => class(WORD, true),
+            'S' => class(SPACE, true),
+            't' => Node::Char('\t'),
+            'n' => Node::Char('\n'),
+            'r' => Node::Char('\r'),
+            _ if c.is_ascii_alphanumeric() => return None,
+            _ => Node::Char(c),
+        })
+    }
+
+    fn class(&mut self) -> Option<Node> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.peek()?;
+            if c == ']' && !first {
+                self.pos += 1;
+                break;
+            }
+            first = false;
+            if c == '[' && self.chars.get(self.pos + 1) == Some(&':') {
+                return None;
+            }
+            self.pos += 1;
+            let start = if c == '\\' {
+                match self.escape(true)? {
+                    Node::Char(c) => c,
+                    Node::Class { ranges: set, .. } => {
+                        ranges.extend(set);
+                        continue;
+                    }
+                    _ => return None,
+                }
+            } else {
+                c
+            };
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']') {
+                self.pos += 1;
+                let mut end = self.peek()?;
+                self.pos += 1;
+                if end == '\\' {
+                    match self.escape(true)? {
+                        Node::Char(c) => end = c,
+                        _ => return None,
+                    }
+                }
+                ranges.push((start, end));
+            } else {
+                ranges.push((start, start));
+            }
+        }
+        Some(Node::Class { ranges, negated })
+    }
+
+    /// `{n}`, `{n,}` or `{n,m}` at the current position, without consuming it
+    fn repetition_bounds(&self) -> Option<(u32, Option<u32>, usize)> {
+        let rest: String = self.chars[self.pos..].iter().collect();
+        let end = rest.find('}')?;
+        let body = &rest[..end];
+        let bounds = match body.split_once(',') {
+            None => {
+                let n = body.parse().ok()?;
+                (n, Some(n))
+            }
+            Some((min, "")) => (min.parse().ok()?, None),
+            Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+        };
+        Some((bounds.0, bounds.1, body.chars().count() + 1))
+    }
+
+    fn quantified(&mut self, atom: Node) -> Option<Node> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.pos += 1;
+                match self.repetition_bounds() {
+                    Some((min, max, len)) => {
+                        self.pos += len - 1;
+                        (min, max)
+                    }
+                    None => {
+                        // A `{` that is not a quantifier is a literal
+                        self.pos -= 1;
+                        return Some(atom);
+                    }
+                }
+            }
+            _ => return Some(atom),
+        };
+        self.pos += 1;
+        if matches!(atom, Node::Start | Node::End | Node::Empty) {
+            return None;
+        }
+        // Lazy and possessive forms select the same strings here
+        if !self.eat('?') {
+            self.eat('+');
+        }
+        Some(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+        })
+    }
+}
+
+struct Matcher {
+    input: Vec<char>,
+    case_insensitive: bool,
+}
+
+impl Matcher {
+    fn char_eq(&self, a: char, b: char) -> bool {
+        a == b || self.case_insensitive && a.eq_ignore_ascii_case(&b)
+    }
+
+    fn in_class(&self, c: char, ranges: &[(char, char)], negated: bool) -> bool {
+        let hit = |c: char| ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+        let found = if self.case_insensitive {
+            hit(c) || hit(c.to_ascii_lowercase()) || hit(c.to_ascii_uppercase())
+        } else {
+            hit(c)
+        };
+        found != negated
+    }
+
+    /// Match `node` at `pos`, calling `next` with each end position until it
+    /// accepts one
+    fn node(&self, node: &Node, pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+        let current = self.input.get(pos).copied();
+        match node {
+            Node::Empty => next(pos),
+            Node::Char(c) => current.is_some_and(|x| self.char_eq(x, *c)) && next(pos + 1),
+            Node::Any => current.is_some_and(|x| x != '\n') && next(pos + 1),
+            Node::Class { ranges, negated } => {
+                current.is_some_and(|x| self.in_class(x, ranges, *negated)) && next(pos + 1)
+            }
+            Node::Start => pos == 0 && next(pos),
+            Node::End => pos == self.input.len() && next(pos),
+            Node::Concat(nodes) => self.sequence(nodes, pos, next),
+            Node::Alt(alternatives) => alternatives.iter().any(|alt| self.node(alt, pos, next)),
+            Node::Repeat { node, min, max } => self.repeat(node, *min, *max, 0, pos, next),
+        }
+    }
+
+    fn sequence(&self, nodes: &[Node], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+        match nodes.split_first() {
+            None => next(pos),
+            Some((first, rest)) => self.node(first, pos, &mut |p| self.sequence(rest, p, next)),
+        }
+    }
+
+    fn repeat(
+        &self,
+        node: &Node,
+        min: u32,
+        max: Option<u32>,
+        count: u32,
+        pos: usize,
+        next: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        if max.is_none_or(|max| count < max)
+            && self.node(node, pos, &mut |p| {
+                // An empty iteration beyond the minimum cannot lead anywhere new
+                (p != pos || count < min) && self.repeat(node, min, max, count + 1, p, next)
+            })
+        {
+            return true;
+        }
+        count >= min && next(pos)
+    }
+}
+
+fn example(node: &Node, out: &mut String) {
+    match node {
+        Node::Empty | Node::Start | Node::End => {}
+        Node::Char(c) => out.push(*c),
+        Node::Any => out.push('a'),
+        Node::Class { ranges, negated } => {
+            let hit = |c: char| ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+            let pick = if *negated {
+                ['a', 'x', '0', '_', '-'].into_iter().find(|&c| !hit(c))
+            } else {
+                ranges.first().map(|&(lo, _)| lo)
+            };
+            out.extend(pick);
+        }
+        Node::Concat(nodes) => nodes.iter().for_each(|n| example(n, out)),
+        Node::Alt(alternatives) => example(&alternatives[0], out),
+        Node::Repeat { node, min, .. } => {
+            for _ in 0..*min {
+                example(node, out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern, false).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn test_common_location_patterns() {
+        assert!(matches(r"\.php$", "/index.php"));
+        assert!(!matches(r"\.php$", "/index.phps"));
+        assert!(matches(r"^/api/v[0-9]+/", "/api/v12/users"));
+        assert!(!matches(r"^/api/v[0-9]+/", "/web/api/v1/"));
+        assert!(matches(r"\.(?:css|js|png)$", "/static/app.js"));
+        assert!(matches(r"^/(?<lang>en|ja)/", "/ja/docs"));
+        assert!(matches(r"^/files/[^/]+\.txt$", "/files/a.txt"));
+        assert!(!matches(r"^/files/[^/]+\.txt$", "/files/a/b.txt"));
+        assert!(matches(r"^/a{2,3}$", "/aaa"));
+        assert!(!matches(r"^/a{2,3}$", "/aaaa"));
+        assert!(matches(r"^/x{$", "/x{"));
+        assert!(matches(r"/\w+\d*$", "/abc12"));
+        assert!(matches(r"^/(a|ab)c$", "/abc"));
+        assert!(matches(r"^(/.*)*$", "/a/b"));
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let regex = Regex::new(r"^/admin/[a-z]+$", true).unwrap();
+        assert!(regex.is_match("/ADMIN/Panel"));
+        assert!(!Regex::new(r"^/admin", false).unwrap().is_match("/Admin"));
+    }
+
+    #[test]
+    fn test_unsupported_syntax() {
+        for pattern in [
+            r"^/(?!api)",
+            r"(a)\1",
+            r"(?i)^/a",
+            r"\Qa.b\E",
+            "[[:alpha:]]",
+            r"\bx",
+        ] {
+            assert!(Regex::new(pattern, false).is_none(), "{}", pattern);
+        }
+        assert!(Regex::new("(a", false).is_none());
+        assert!(Regex::new("a)", false).is_none());
+    }
+
+    #[test]
+    fn test_example_matches() {
+        for pattern in [
+            r"\.php$",
+            r"^/api/(v1|v2)/.+\.json$",
+            r"^/[^/]+/download/\d{3}$",
+            r"\.(?:css|js)$",
+        ] {
+            let regex = Regex::new(pattern, false).unwrap();
+            assert!(
+                regex.is_match(&regex.example()),
+                "{} -> {}",
+                pattern,
+                regex.example()
+            );
+        }
+    }
+}
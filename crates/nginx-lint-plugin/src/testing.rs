@@ -48,6 +48,8 @@
 //!     └── expected/nginx.conf   # Config after applying fixes (no errors expected)
 //! ```
 
+use super::check_context::CheckContext;
+use super::options::RuleOptions;
 use super::types::{Config, Fix, LintError, Plugin, PluginSpec};
 use std::path::{Path, PathBuf};
 
@@ -97,6 +99,14 @@ macro_rules! fixtures_dir {
 /// runner.assert_errors("http {\n    bad on;\n    bad on;\n}", 2);
 /// runner.assert_error_on_line("http {\n    bad on;\n}", 2);
 /// ```
+/// Run `plugin` through [`Plugin::check_with_context`], as the host does,
+/// with its options at their declared defaults
+pub fn check_with_defaults<P: Plugin>(plugin: &P, config: &Config, path: &str) -> Vec<LintError> {
+    let options = RuleOptions::defaults(&plugin.spec().options);
+    let ctx = CheckContext::new(config, path).with_options(&options);
+    plugin.check_with_context(config, &ctx)
+}
+
 pub struct PluginTestRunner<P: Plugin> {
     plugin: P,
 }
@@ -116,7 +126,7 @@ impl<P: Plugin> PluginTestRunner<P> {
     pub fn check_string(&self, content: &str) -> Result<Vec<LintError>, String> {
         let config: Config = nginx_lint_common::parse_string(content)
             .map_err(|e| format!("Failed to parse config: {}", e))?;
-        Ok(self.plugin.check(&config, "test.conf"))
+        Ok(check_with_defaults(&self.plugin, &config, "test.conf"))
     }
 
     /// Run the plugin check on a file
@@ -125,7 +135,11 @@ impl<P: Plugin> PluginTestRunner<P> {
             std::fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
         let config: Config = nginx_lint_common::parse_string(&content)
             .map_err(|e| format!("Failed to parse config: {}", e))?;
-        Ok(self.plugin.check(&config, path.to_string_lossy().as_ref()))
+        Ok(check_with_defaults(
+            &self.plugin,
+            &config,
+            path.to_string_lossy().as_ref(),
+        ))
     }
 
     /// Test all fixtures in a directory
@@ -522,7 +536,7 @@ impl TestCase {
         let config: Config = nginx_lint_common::parse_string(&self.content)
             .unwrap_or_else(|e| panic!("Failed to parse test config: {}", e));
 
-        let errors = plugin.check(&config, "test.conf");
+        let errors = check_with_defaults(plugin, &config, "test.conf");
         let plugin_spec = plugin.spec();
         let rule_errors: Vec<_> = errors
             .iter()
@@ -664,6 +678,7 @@ fn apply_fixes(content: &str, fixes: &[&Fix]) -> String {
             insert_after: f.insert_after,
             start_offset: f.start_offset,
             end_offset: f.end_offset,
+            applicability: f.applicability,
         })
         .collect();
     let common_refs: Vec<&nginx_lint_common::Fix> = common_fixes.iter().collect();
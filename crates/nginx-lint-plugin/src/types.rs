@@ -13,10 +13,14 @@
 //!
 //! These types mirror the nginx-lint AST and error types for use in WASM plugins.
 
+use crate::check_context::CheckContext;
+use crate::options::{RuleOption, RuleOptions};
 use serde::{Deserialize, Serialize};
 
+pub use nginx_lint_common::config::RulePreset;
+
 /// Current API version for the plugin SDK
-pub const API_VERSION: &str = "1.2";
+pub const API_VERSION: &str = "2.0";
 
 /// Plugin metadata describing a lint rule.
 ///
@@ -73,6 +77,15 @@ pub struct PluginSpec {
     /// `None` means unbounded on the upper end.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_nginx_version: Option<String>,
+    /// Smallest rule preset the rule belongs to (`"minimal"` or
+    /// `"recommended"`). `None` means only `strict`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// Options users can set in the rule's config section (see
+    /// [`Plugin::configure`]). Resolved inside the plugin, so they are not
+    /// part of the spec sent to the host.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<RuleOption>,
 }
 
 impl PluginSpec {
@@ -94,6 +107,8 @@ impl PluginSpec {
             references: None,
             min_nginx_version: None,
             max_nginx_version: None,
+            preset: None,
+            options: Vec::new(),
         }
     }
 
@@ -139,6 +154,28 @@ impl PluginSpec {
         self
     }
 
+    /// Declare the smallest rule preset this rule belongs to; larger
+    /// presets include it too. Rules without one only run with `strict`
+    /// (or without a preset).
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// let spec = PluginSpec::new("my-rule", "security", "Check something")
+    ///     .with_preset(RulePreset::Recommended);
+    /// assert_eq!(spec.preset.as_deref(), Some("recommended"));
+    /// ```
+    pub fn with_preset(mut self, preset: RulePreset) -> Self {
+        self.preset = Some(preset.as_str().to_string());
+        self
+    }
+
+    /// Declare an option users can set for this rule (see [`RuleOption`])
+    pub fn with_option(mut self, option: RuleOption) -> Self {
+        self.options.push(option);
+        self
+    }
+
     /// Create an error builder that uses this plugin's name and category
     ///
     /// This reduces boilerplate when creating errors in the check method.
@@ -194,6 +231,45 @@ impl ErrorBuilder {
     pub fn warning_at(&self, message: &str, directive: &(impl DirectiveExt + ?Sized)) -> LintError {
         self.warning(message, directive.line(), directive.column())
     }
+
+    /// Create an error pointing at the argument at `index` of `directive`
+    ///
+    /// Falls back to the directive's location if it has fewer arguments.
+    pub fn error_at_arg(&self, message: &str, directive: &Directive, index: usize) -> LintError {
+        at_arg(self.error_at(message, directive), directive, index)
+    }
+
+    /// Create a warning pointing at the argument at `index` of `directive`
+    ///
+    /// The text reporter underlines the argument, so the message can leave
+    /// out which one it means.
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// let config = nginx_lint_plugin::parse_string("proxy_pass http://backend/path;").unwrap();
+    /// let proxy_pass = config.all_directives().next().unwrap();
+    ///
+    /// let err = PluginSpec::new("my-rule", "best-practices", "Check something").error_builder();
+    /// let warning = err.warning_at_arg("the URI replaces the location prefix", proxy_pass, 0);
+    /// assert_eq!(warning.column, Some(12));
+    /// assert_eq!(warning.end_column, Some(31));
+    /// ```
+    pub fn warning_at_arg(&self, message: &str, directive: &Directive, index: usize) -> LintError {
+        at_arg(self.warning_at(message, directive), directive, index)
+    }
+}
+
+/// Move `error` to the argument at `index` of `directive`, if it exists
+fn at_arg(mut error: LintError, directive: &Directive, index: usize) -> LintError {
+    if let Some(arg) = directive.args.get(index) {
+        let (start, end) = (arg.span.start, arg.span.end);
+        error.line = Some(start.line);
+        error.column = Some(start.column);
+        // A quoted argument spanning lines is not underlined
+        error.end_column = (end.line == start.line).then_some(end.column);
+    }
+    error
 }
 
 /// Severity level for lint errors
@@ -235,6 +311,22 @@ pub enum Severity {
 /// let fix = directive.insert_before("# Security headers");
 /// assert!(fix.is_range_based());
 /// ```
+///
+/// Fixes are applied by `--fix` unless marked as less than
+/// [`Applicability::MachineApplicable`]:
+///
+/// ```
+/// use nginx_lint_plugin::prelude::*;
+///
+/// let config = nginx_lint_plugin::parse_string("proxy_set_header Host $host;").unwrap();
+/// let directive = config.all_directives().next().unwrap();
+///
+/// // Only applied with --fix-suggestions
+/// let fix = directive
+///     .insert_before("proxy_set_header X-Real-IP $remote_addr;")
+///     .with_applicability(Applicability::MaybeIncorrect);
+/// assert!(!fix.applicability.is_machine_applicable());
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fix {
     /// Line number where the fix should be applied (1-indexed)
@@ -256,8 +348,14 @@ pub struct Fix {
     /// End byte offset for range-based fix (0-indexed, exclusive)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub end_offset: Option<usize>,
+    /// How safe the fix is to apply without review
+    #[serde(default, skip_serializing_if = "Applicability::is_machine_applicable")]
+    pub applicability: Applicability,
 }
 
+/// How safe a [`Fix`] is to apply without review (shared with the host)
+pub use nginx_lint_common::linter::Applicability;
+
 impl Fix {
     /// Create a fix that deletes an entire line
     #[deprecated(note = "Use Fix::replace_range() for offset-based fixes instead")]
@@ -270,6 +368,7 @@ impl Fix {
             insert_after: false,
             start_offset: None,
             end_offset: None,
+            applicability: Applicability::MachineApplicable,
         }
     }
 
@@ -284,6 +383,7 @@ impl Fix {
             insert_after: true,
             start_offset: None,
             end_offset: None,
+            applicability: Applicability::MachineApplicable,
         }
     }
 
@@ -299,6 +399,7 @@ impl Fix {
             insert_after: false,
             start_offset: Some(start_offset),
             end_offset: Some(end_offset),
+            applicability: Applicability::MachineApplicable,
         }
     }
 
@@ -306,6 +407,24 @@ impl Fix {
     pub fn is_range_based(&self) -> bool {
         self.start_offset.is_some() && self.end_offset.is_some()
     }
+
+    /// Set how safe the fix is to apply without review
+    pub fn with_applicability(mut self, applicability: Applicability) -> Self {
+        self.applicability = applicability;
+        self
+    }
+}
+
+/// Another way to resolve a lint error than its default fixes.
+///
+/// The fixes are applied together when the user picks the alternative by
+/// its label. Attach one with [`LintError::with_alternative()`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixAlternative {
+    /// Short description of the strategy, used to select it
+    pub label: String,
+    /// The edits making up the strategy
+    pub fixes: Vec<Fix>,
 }
 
 /// A lint error reported by a plugin.
@@ -340,8 +459,16 @@ pub struct LintError {
     pub line: Option<usize>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub column: Option<usize>,
+    /// Column just past the end of the reported text on `line`, set by
+    /// [`ErrorBuilder::warning_at_arg`] and [`ErrorBuilder::error_at_arg`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub fixes: Vec<Fix>,
+    /// Other ways to resolve the error, applied only when the user picks
+    /// one by label (`--fix-alternative`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<FixAlternative>,
 }
 
 impl LintError {
@@ -354,7 +481,9 @@ impl LintError {
             severity: Severity::Error,
             line: if line > 0 { Some(line) } else { None },
             column: if column > 0 { Some(column) } else { None },
+            end_column: None,
             fixes: Vec::new(),
+            alternatives: Vec::new(),
         }
     }
 
@@ -367,7 +496,9 @@ impl LintError {
             severity: Severity::Warning,
             line: if line > 0 { Some(line) } else { None },
             column: if column > 0 { Some(column) } else { None },
+            end_column: None,
             fixes: Vec::new(),
+            alternatives: Vec::new(),
         }
     }
 
@@ -382,6 +513,16 @@ impl LintError {
         self.fixes.extend(fixes);
         self
     }
+
+    /// Offer `fixes` as an alternative to the default fixes, applied
+    /// together when the user picks `label`
+    pub fn with_alternative(mut self, label: &str, fixes: Vec<Fix>) -> Self {
+        self.alternatives.push(FixAlternative {
+            label: label.to_string(),
+            fixes,
+        });
+        self
+    }
 }
 
 /// Trait that all plugins must implement.
@@ -447,6 +588,49 @@ pub trait Plugin: Default {
     /// when you need to know the parent block context.
     fn check(&self, config: &Config, path: &str) -> Vec<LintError>;
 
+    /// Check the configuration with what the host knows about the file: its
+    /// include context, the target nginx version, the resolved options and
+    /// an analysis cache shared with other rules (see [`CheckContext`]).
+    ///
+    /// This is the method the host calls. The default implementation calls
+    /// [`check()`](Plugin::check), so plugins that only need the path keep
+    /// implementing `check`. Plugins that override this should make `check`
+    /// delegate to it with [`CheckContext::new`].
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// #[derive(Default)]
+    /// struct UnusedSet;
+    ///
+    /// impl Plugin for UnusedSet {
+    ///     fn spec(&self) -> PluginSpec {
+    ///         PluginSpec::new("unused-set", "best-practices", "Reports unused set variables")
+    ///     }
+    ///
+    ///     fn check(&self, config: &Config, path: &str) -> Vec<LintError> {
+    ///         self.check_with_context(config, &CheckContext::new(config, path))
+    ///     }
+    ///
+    ///     fn check_with_context(&self, _config: &Config, ctx: &CheckContext) -> Vec<LintError> {
+    ///         let err = self.spec().error_builder();
+    ///         let variables = ctx.variable_table();
+    ///         variables
+    ///             .definitions()
+    ///             .iter()
+    ///             .filter(|def| !variables.is_used(&def.name))
+    ///             .map(|def| err.warning(&format!("${} is never used", def.name), def.line, def.column))
+    ///             .collect()
+    ///     }
+    /// }
+    ///
+    /// let config = nginx_lint_plugin::parse_string("set $a 1;").unwrap();
+    /// assert_eq!(UnusedSet.check(&config, "test.conf").len(), 1);
+    /// ```
+    fn check_with_context(&self, config: &Config, ctx: &CheckContext) -> Vec<LintError> {
+        self.check(config, ctx.path())
+    }
+
     /// Declare the directive names this plugin's [`check()`](Plugin::check)
     /// reads, if it only reads a fixed, known set.
     ///
@@ -466,13 +650,54 @@ pub trait Plugin: Default {
     fn relevant_directives(&self) -> Option<&'static [&'static str]> {
         None
     }
+
+    /// Apply the user's values for the options declared with
+    /// [`PluginSpec::with_option`].
+    ///
+    /// Called before [`check()`](Plugin::check), and only when the user set
+    /// at least one option for this rule, so [`Default`] must behave like
+    /// the declared defaults. Unset options resolve to their defaults.
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// struct MaxDepth(i64);
+    ///
+    /// impl Default for MaxDepth {
+    ///     fn default() -> Self {
+    ///         Self(5)
+    ///     }
+    /// }
+    ///
+    /// impl Plugin for MaxDepth {
+    ///     fn spec(&self) -> PluginSpec {
+    ///         PluginSpec::new("max-depth", "style", "Limits block nesting")
+    ///             .with_option(RuleOption::integer("max_depth", 5, "Deepest allowed nesting"))
+    ///     }
+    ///
+    ///     fn configure(&mut self, options: &RuleOptions) {
+    ///         self.0 = options.integer("max_depth");
+    ///     }
+    ///
+    ///     fn check(&self, _config: &Config, _path: &str) -> Vec<LintError> {
+    ///         Vec::new()
+    ///     }
+    /// }
+    /// ```
+    fn configure(&mut self, _options: &RuleOptions) {}
 }
 
 // Re-export AST types from nginx-lint-common
 pub use nginx_lint_common::parser::ast::{
     Argument, ArgumentValue, Block, Comment, Config, ConfigItem, Directive, Position, Span,
 };
-pub use nginx_lint_common::parser::context::{AllDirectivesWithContextIter, DirectiveWithContext};
+pub use nginx_lint_common::parser::context::{
+    AllDirectivesWithContextIter, ContextFrame, DirectiveWithContext, IncludeContext,
+};
+pub use nginx_lint_common::parser::format::IndentStyle;
+pub use nginx_lint_common::variables::{
+    VariableDefinition, VariableSource, VariableTable, VariableUse, is_builtin_variable,
+};
 
 /// Extension trait for [`Config`] providing iteration and include-context helpers.
 ///
@@ -489,11 +714,12 @@ pub use nginx_lint_common::parser::context::{AllDirectivesWithContextIter, Direc
 /// # Include Context
 ///
 /// When nginx-lint processes `include` directives, the included file's [`Config`] receives
-/// an `include_context` field recording the parent block names. For example, a file included
-/// from `http { server { include conf.d/*.conf; } }` would have
-/// `include_context = ["http", "server"]`.
+/// an `include_context` field recording the enclosing blocks. For example, a file included
+/// from `http { server { location /api/ { include api.conf; } } }` would have the frames
+/// `http`, `server` and `location /api/`; each [`ContextFrame`] keeps the block's arguments,
+/// so [`IncludeContext::innermost`] can recover the enclosing `location`'s match.
 ///
-/// The `is_included_from_*` methods check this context:
+/// The `is_included_from_*` methods check the block names:
 ///
 /// ```
 /// use nginx_lint_plugin::prelude::*;
@@ -502,8 +728,12 @@ pub use nginx_lint_common::parser::context::{AllDirectivesWithContextIter, Direc
 /// assert!(!config.is_included_from_http());
 ///
 /// // Simulate being included from http context
-/// config.include_context = vec!["http".to_string()];
+/// config.include_context = vec!["http".to_string()].into();
 /// assert!(config.is_included_from_http());
+///
+/// // Frames can carry block arguments, as with `--context "http,server,location=/api/"`
+/// config.include_context = IncludeContext::parse("http,server,location=/api/");
+/// assert_eq!(config.include_context.innermost("location").unwrap().args, ["/api/"]);
 /// ```
 pub trait ConfigExt {
     /// Iterate over all directives recursively.
@@ -535,6 +765,25 @@ pub trait ConfigExt {
 
     /// Get the immediate parent context (last element in include_context).
     fn immediate_parent_context(&self) -> Option<&str>;
+
+    /// Collect the variables this config defines (`set`, `map`, `geo`,
+    /// `split_clients`, `auth_request_set`, named regex captures) and uses.
+    ///
+    /// The table only covers this file. A plugin that declares
+    /// [`relevant_directives`](Plugin::relevant_directives) receives a pruned
+    /// config, so it must list every defining/using directive it cares about.
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// let config = nginx_lint_plugin::parse_string(
+    ///     "set $backend app;\nproxy_pass http://$backend;",
+    /// ).unwrap();
+    /// let vars = config.variable_table();
+    /// assert_eq!(vars.definition("backend").unwrap().source, VariableSource::Set);
+    /// assert!(vars.is_used("backend"));
+    /// ```
+    fn variable_table(&self) -> VariableTable;
 }
 
 impl ConfigExt for Config {
@@ -571,6 +820,10 @@ impl ConfigExt for Config {
     fn immediate_parent_context(&self) -> Option<&str> {
         Config::immediate_parent_context(self)
     }
+
+    fn variable_table(&self) -> VariableTable {
+        VariableTable::from_config(self)
+    }
 }
 
 /// Extension trait for [`Directive`] providing inspection and fix-generation helpers.
@@ -625,6 +878,17 @@ pub trait DirectiveExt {
     /// Create a [`Fix`] that deletes this directive's line.
     fn delete_line(&self) -> Fix;
     /// Create a [`Fix`] that inserts a new line after this directive, matching indentation.
+    ///
+    /// The indentation is copied from the directive's line, so tab-indented
+    /// files get tabs (see [`Directive::line_indent`]).
+    ///
+    /// ```
+    /// use nginx_lint_plugin::prelude::*;
+    ///
+    /// let config = nginx_lint_plugin::parse_string("http {\n\tgzip on;\n}\n").unwrap();
+    /// let gzip = config.all_directives().find(|d| d.is("gzip")).unwrap();
+    /// assert_eq!(gzip.insert_after("gzip_vary on;").new_text, "\n\tgzip_vary on;");
+    /// ```
     fn insert_after(&self, new_text: &str) -> Fix;
     /// Create a [`Fix`] that inserts multiple new lines after this directive.
     fn insert_after_many(&self, lines: &[&str]) -> Fix;
@@ -706,7 +970,7 @@ impl DirectiveExt for Directive {
     }
 
     fn insert_after_many(&self, lines: &[&str]) -> Fix {
-        let indent = " ".repeat(self.span.start.column.saturating_sub(1));
+        let indent = self.line_indent();
         let fix_text: String = lines
             .iter()
             .map(|line| format!("\n{}{}", indent, line))
@@ -720,7 +984,7 @@ impl DirectiveExt for Directive {
     }
 
     fn insert_before_many(&self, lines: &[&str]) -> Fix {
-        let indent = " ".repeat(self.span.start.column.saturating_sub(1));
+        let indent = self.line_indent();
         let fix_text: String = lines
             .iter()
             .map(|line| format!("{}{}\n", indent, line))
@@ -833,6 +1097,8 @@ impl DirectiveExt for Box<Directive> {
 }
 
 /// Extension trait for Argument to add source reconstruction
+///
+/// Kept for compatibility; [`Argument::to_source`] is now an inherent method.
 pub trait ArgumentExt {
     /// Reconstruct the source text for this argument
     fn to_source(&self) -> String;
@@ -840,11 +1106,6 @@ pub trait ArgumentExt {
 
 impl ArgumentExt for Argument {
     fn to_source(&self) -> String {
-        match &self.value {
-            ArgumentValue::Literal(s) => s.clone(),
-            ArgumentValue::QuotedString(s) => format!("\"{}\"", s),
-            ArgumentValue::SingleQuotedString(s) => format!("'{}'", s),
-            ArgumentValue::Variable(s) => format!("${}", s),
-        }
+        Argument::to_source(self)
     }
 }
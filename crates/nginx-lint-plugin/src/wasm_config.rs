@@ -15,7 +15,8 @@
 //! variants (`first_arg_owned()`, `arg_at_owned()`, `last_arg_owned()`) or
 //! the standard `reconstruct_config` path instead.
 
-use crate::types::Fix;
+use crate::parser::context::{ContextFrame, IncludeContext};
+use crate::types::{Applicability, Fix};
 use crate::wit_guest::nginx_lint::plugin::config_api;
 use crate::wit_guest::nginx_lint::plugin::types as wit_types;
 
@@ -71,6 +72,15 @@ impl Config {
         self.handle.include_context()
     }
 
+    /// Get the include context with each enclosing block's arguments.
+    pub fn include_context_frames(&self) -> IncludeContext {
+        self.handle
+            .include_context_frames()
+            .into_iter()
+            .map(|frame| ContextFrame::with_args(frame.name, frame.args))
+            .collect()
+    }
+
     /// Check if this config is included from within a specific context.
     pub fn is_included_from(&self, context: &str) -> bool {
         self.handle.is_included_from(context)
@@ -415,5 +425,10 @@ fn convert_wit_fix(fix: wit_types::Fix) -> Fix {
         insert_after: fix.insert_after,
         start_offset: fix.start_offset.map(|v| v as usize),
         end_offset: fix.end_offset.map(|v| v as usize),
+        applicability: match fix.applicability {
+            wit_types::Applicability::MachineApplicable => Applicability::MachineApplicable,
+            wit_types::Applicability::MaybeIncorrect => Applicability::MaybeIncorrect,
+            wit_types::Applicability::HasPlaceholders => Applicability::HasPlaceholders,
+        },
     }
 }
@@ -24,6 +24,7 @@ pub fn convert_spec(sdk_spec: super::PluginSpec) -> nginx_lint::plugin::types::P
         references: sdk_spec.references,
         min_nginx_version: sdk_spec.min_nginx_version,
         max_nginx_version: sdk_spec.max_nginx_version,
+        preset: sdk_spec.preset,
     }
 }
 
@@ -45,6 +46,17 @@ pub fn convert_fix(fix: super::Fix) -> nginx_lint::plugin::types::Fix {
         insert_after: fix.insert_after,
         start_offset: fix.start_offset.map(|v| v as u32),
         end_offset: fix.end_offset.map(|v| v as u32),
+        applicability: match fix.applicability {
+            super::Applicability::MachineApplicable => {
+                nginx_lint::plugin::types::Applicability::MachineApplicable
+            }
+            super::Applicability::MaybeIncorrect => {
+                nginx_lint::plugin::types::Applicability::MaybeIncorrect
+            }
+            super::Applicability::HasPlaceholders => {
+                nginx_lint::plugin::types::Applicability::HasPlaceholders
+            }
+        },
     }
 }
 
@@ -57,7 +69,55 @@ pub fn convert_lint_error(error: super::LintError) -> nginx_lint::plugin::types:
         severity: convert_severity(error.severity),
         line: error.line.map(|v| v as u32),
         column: error.column.map(|v| v as u32),
+        end_column: error.end_column.map(|v| v as u32),
         fixes: error.fixes.into_iter().map(convert_fix).collect(),
+        alternatives: error
+            .alternatives
+            .into_iter()
+            .map(|alternative| nginx_lint::plugin::types::FixAlternative {
+                label: alternative.label,
+                fixes: alternative.fixes.into_iter().map(convert_fix).collect(),
+            })
+            .collect(),
+    }
+}
+
+/// Pass the options the user set for this rule to `plugin`, and return them
+/// resolved (every option at its default when none is set).
+///
+/// Does not call [`crate::Plugin::configure`] when no option is set.
+/// Values the plugin cannot use are reported to the host, which warns about
+/// them.
+pub fn configure_plugin<P: crate::Plugin>(plugin: &mut P) -> crate::RuleOptions {
+    let configured: std::collections::HashMap<String, serde_json::Value> =
+        nginx_lint::plugin::config_api::rule_options()
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, serde_json::from_str(&value).ok()?)))
+            .collect();
+    let declared = plugin.spec().options;
+    if configured.is_empty() {
+        return crate::RuleOptions::defaults(&declared);
+    }
+    let options = crate::RuleOptions::resolve(&declared, &configured);
+    for problem in options.problems() {
+        nginx_lint::plugin::config_api::report_option_problem(problem);
+    }
+    plugin.configure(&options);
+    options
+}
+
+/// The `target_nginx_version` setting, if set
+pub fn target_nginx_version() -> Option<String> {
+    nginx_lint::plugin::config_api::target_nginx_version()
+}
+
+/// The `[environment]` settings
+pub fn environment() -> crate::Environment {
+    let environment = nginx_lint::plugin::config_api::environment();
+    crate::Environment {
+        os: environment.os,
+        container: environment.container,
+        behind_load_balancer: environment.behind_load_balancer,
     }
 }
 
@@ -66,11 +126,13 @@ pub fn convert_lint_error(error: super::LintError) -> nginx_lint::plugin::types:
 /// Fetches the entire config in a single `snapshot()` host call (a flat
 /// DFS-ordered array with index-based child references) and rebuilds the
 /// tree guest-side. One WIT boundary crossing regardless of config size,
-/// instead of two calls (`data` + `block-items`) per directive.
+/// instead of two calls (`data` + `block-items`) per directive (plus one
+/// `include-context-frames` call for included files, to recover the
+/// arguments of the enclosing blocks).
 ///
 /// Known-lossy fields (unchanged since the original reconstruction path;
-/// they cannot be added without a breaking WIT record change that would
-/// fail instantiation of already-built plugins):
+/// they cannot be added without a breaking WIT record change, which needs a
+/// major plugin API version bump):
 /// - a directive's `trailing_comment` carries only its text; its span and
 ///   whitespace are zeroed
 /// - a blank line's span end is recomputed from its content, which excludes
@@ -78,7 +140,7 @@ pub fn convert_lint_error(error: super::LintError) -> nginx_lint::plugin::types:
 pub fn reconstruct_config(
     config: &nginx_lint::plugin::config_api::Config,
 ) -> crate::parser::ast::Config {
-    config_from_snapshot(config.snapshot())
+    config_from_snapshot(config, config.snapshot())
 }
 
 /// Like [`reconstruct_config`], but built from a snapshot pruned to
@@ -92,16 +154,18 @@ pub fn reconstruct_config_filtered(
     names: &[&str],
 ) -> crate::parser::ast::Config {
     let names: Vec<String> = names.iter().map(|s| s.to_string()).collect();
-    config_from_snapshot(config.snapshot_filtered(&names))
+    config_from_snapshot(config, config.snapshot_filtered(&names))
 }
 
 /// Shared rebuild step for [`reconstruct_config`] and
 /// [`reconstruct_config_filtered`]: turn a (possibly pruned) flat snapshot
 /// into a native `ast::Config` tree.
 fn config_from_snapshot(
+    config: &nginx_lint::plugin::config_api::Config,
     snapshot: nginx_lint::plugin::config_api::ConfigSnapshot,
 ) -> crate::parser::ast::Config {
     use crate::parser::ast;
+    use crate::parser::context::{ContextFrame, IncludeContext};
 
     // Slots let each flat item be moved out exactly once while children are
     // resolved by index, avoiding a clone of every string in the config
@@ -113,9 +177,21 @@ fn config_from_snapshot(
         .map(|&index| build_item(&mut slots, index))
         .collect();
 
+    // The snapshot only carries block names; fetch the frames (with block
+    // arguments) only when there is a context to describe
+    let include_context = if snapshot.include_context.is_empty() {
+        IncludeContext::new()
+    } else {
+        config
+            .include_context_frames()
+            .into_iter()
+            .map(|frame| ContextFrame::with_args(frame.name, frame.args))
+            .collect()
+    };
+
     ast::Config {
         items,
-        include_context: snapshot.include_context,
+        include_context,
     }
 }
 
@@ -0,0 +1,301 @@
+//! nginx-lint diagnostics paired with the source they were reported on.
+//!
+//! A [`LintError`] carries a rule, a message and a line/column, but not the
+//! text of the file. Tools embedding the linter that want to show the
+//! offending line need both, so [`LintDiagnostic`] keeps an error together
+//! with the named source it refers to.
+//!
+//! With the `miette` feature (enabled by default) `LintDiagnostic`
+//! implements [`miette::Diagnostic`], so any miette report handler renders
+//! it with the rule as the code, the reported range labelled in the source
+//! and the fix alternatives as help.
+//!
+//! # Example
+//!
+//! ```
+//! use nginx_lint_common::{LintError, Severity};
+//! use nginx_lint_report::LintDiagnostic;
+//!
+//! let source = "server {\n    server_tokens on;\n}\n";
+//! let error = LintError::new(
+//!     "server-tokens-enabled",
+//!     "security",
+//!     "server_tokens should be off",
+//!     Severity::Warning,
+//! )
+//! .with_location(2, 5);
+//!
+//! let diagnostic = LintDiagnostic::new(error, "nginx.conf", source);
+//! // The rest of the reported line: `server_tokens on;`
+//! assert_eq!(diagnostic.span(), Some(13..30));
+//!
+//! # #[cfg(feature = "miette")]
+//! println!("{:?}", miette::Report::new(diagnostic));
+//! ```
+
+use nginx_lint_common::LintError;
+#[cfg(feature = "miette")]
+use nginx_lint_common::Severity;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A file name and its content, shared by the diagnostics reported on it
+#[derive(Debug)]
+struct Source {
+    name: String,
+    text: String,
+}
+
+/// A lint error together with the source it was reported on
+#[derive(Debug, Clone)]
+pub struct LintDiagnostic {
+    error: LintError,
+    source: Arc<Source>,
+}
+
+impl LintDiagnostic {
+    /// Pair `error` with the content `text` of the file `name`.
+    pub fn new(error: LintError, name: &str, text: &str) -> Self {
+        Self {
+            error,
+            source: Arc::new(Source {
+                name: name.to_string(),
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    /// Pair all `errors` reported on the file `name` with its content
+    /// `text`, which the diagnostics share.
+    pub fn from_errors(errors: Vec<LintError>, name: &str, text: &str) -> Vec<Self> {
+        let source = Arc::new(Source {
+            name: name.to_string(),
+            text: text.to_string(),
+        });
+        errors
+            .into_iter()
+            .map(|error| Self {
+                error,
+                source: Arc::clone(&source),
+            })
+            .collect()
+    }
+
+    /// The lint error
+    pub fn error(&self) -> &LintError {
+        &self.error
+    }
+
+    /// Take back the lint error
+    pub fn into_error(self) -> LintError {
+        self.error
+    }
+
+    /// Name of the file the error was reported on
+    pub fn source_name(&self) -> &str {
+        &self.source.name
+    }
+
+    /// Content of the file the error was reported on
+    pub fn source_text(&self) -> &str {
+        &self.source.text
+    }
+
+    /// Byte range of the source the error points at.
+    ///
+    /// This is the range up to [`LintError::end_column`] when the error has
+    /// one, and otherwise the rest of the reported line from its column (or
+    /// from the first non-blank character without a column). `None` if the
+    /// error has no line or the position is outside the source.
+    pub fn span(&self) -> Option<Range<usize>> {
+        let (line_start, line) = line_at(&self.source.text, self.error.line?)?;
+        let start = match self.error.column {
+            Some(column) => column.checked_sub(1)?,
+            None => line.len() - line.trim_start().len(),
+        };
+        let end = match self.error.end_column {
+            Some(end_column) => end_column.checked_sub(1)?.min(line.len()),
+            None => line.len(),
+        };
+        line.get(start..end.max(start))?;
+        Some(line_start + start..line_start + end.max(start))
+    }
+}
+
+/// Offset and text (without the line break) of the 1-indexed `line`
+fn line_at(text: &str, line: usize) -> Option<(usize, &str)> {
+    let mut offset = 0;
+    for (i, content) in text.split_inclusive('\n').enumerate() {
+        if i + 1 == line {
+            return Some((offset, content.trim_end_matches(['\n', '\r'])));
+        }
+        offset += content.len();
+    }
+    None
+}
+
+impl fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.error.message)
+    }
+}
+
+impl std::error::Error for LintDiagnostic {}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for LintDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(format!(
+            "{}/{}",
+            self.error.category, self.error.rule
+        )))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.error.severity {
+            Severity::Error => miette::Severity::Error,
+            Severity::Warning => miette::Severity::Warning,
+        })
+    }
+
+    /// The choices for `--fix-alternative`, one per line
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        if self.error.alternatives.is_empty() {
+            return None;
+        }
+        let help: Vec<String> = self
+            .error
+            .alternatives
+            .iter()
+            .map(|alternative| format!("alternative fix: {}", alternative.label))
+            .collect();
+        Some(Box::new(help.join("\n")))
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&*self.source)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.span()?;
+        Some(Box::new(std::iter::once(miette::LabeledSpan::underline(
+            span,
+        ))))
+    }
+}
+
+/// Reads like the text, with the file name attached to the contents
+#[cfg(feature = "miette")]
+impl miette::SourceCode for Source {
+    fn read_span<'a>(
+        &'a self,
+        span: &miette::SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn miette::SpanContents<'a> + 'a>, miette::MietteError> {
+        let contents = self
+            .text
+            .read_span(span, context_lines_before, context_lines_after)?;
+        Ok(Box::new(miette::MietteSpanContents::new_named(
+            self.name.clone(),
+            contents.data(),
+            *contents.span(),
+            contents.line(),
+            contents.column(),
+            contents.line_count(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_common::Severity;
+
+    const SOURCE: &str = "http {\n    server {\n        ssl_ciphers RC4:HIGH;  \n    }\n}\n";
+
+    fn warning() -> LintError {
+        LintError::new(
+            "weak-ssl-ciphers",
+            "security",
+            "Weak cipher suite 'RC4' should not be used",
+            Severity::Warning,
+        )
+    }
+
+    #[test]
+    fn test_span_with_end_column() {
+        let error = warning().with_location(3, 21).with_end_column(29);
+        let diagnostic = LintDiagnostic::new(error, "nginx.conf", SOURCE);
+        let span = diagnostic.span().unwrap();
+        assert_eq!(&SOURCE[span], "RC4:HIGH");
+    }
+
+    #[test]
+    fn test_span_to_end_of_line() {
+        let error = warning().with_location(3, 9);
+        let diagnostic = LintDiagnostic::new(error, "nginx.conf", SOURCE);
+        let span = diagnostic.span().unwrap();
+        assert_eq!(&SOURCE[span], "ssl_ciphers RC4:HIGH;  ");
+
+        let mut error = warning();
+        error.line = Some(2);
+        let diagnostic = LintDiagnostic::new(error, "nginx.conf", SOURCE);
+        let span = diagnostic.span().unwrap();
+        assert_eq!(&SOURCE[span], "server {");
+    }
+
+    #[test]
+    fn test_span_outside_source() {
+        for error in [
+            warning(),
+            warning().with_location(9, 1),
+            warning().with_location(1, 40),
+        ] {
+            let diagnostic = LintDiagnostic::new(error, "nginx.conf", SOURCE);
+            assert_eq!(diagnostic.span(), None);
+        }
+    }
+
+    #[test]
+    fn test_from_errors_shares_source() {
+        let diagnostics = LintDiagnostic::from_errors(
+            vec![warning().with_location(1, 1), warning().with_location(2, 5)],
+            "nginx.conf",
+            SOURCE,
+        );
+        assert_eq!(diagnostics.len(), 2);
+        assert!(Arc::ptr_eq(&diagnostics[0].source, &diagnostics[1].source));
+        assert_eq!(diagnostics[1].source_name(), "nginx.conf");
+        assert_eq!(diagnostics[1].to_string(), warning().message);
+    }
+
+    #[cfg(feature = "miette")]
+    #[test]
+    fn test_miette_report() {
+        use miette::{GraphicalReportHandler, GraphicalTheme};
+
+        let error = warning()
+            .with_location(3, 21)
+            .with_end_column(29)
+            .with_alternative("use HIGH:!aNULL:!MD5", vec![]);
+        let diagnostic = LintDiagnostic::new(error, "nginx.conf", SOURCE);
+
+        let mut output = String::new();
+        GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+            .with_width(120)
+            .render_report(&mut output, &diagnostic)
+            .unwrap();
+
+        assert!(output.contains("security/weak-ssl-ciphers"), "{output}");
+        assert!(output.contains("Weak cipher suite 'RC4'"), "{output}");
+        assert!(output.contains("[nginx.conf:3:21]"), "{output}");
+        assert!(output.contains("ssl_ciphers RC4:HIGH;"), "{output}");
+        assert!(output.contains("────────"), "{output}");
+        assert!(
+            output.contains("alternative fix: use HIGH:!aNULL:!MD5"),
+            "{output}"
+        );
+    }
+}
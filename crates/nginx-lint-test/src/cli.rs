@@ -0,0 +1,121 @@
+//! Running the `nginx-lint` binary.
+
+use crate::fixture::Fixture;
+use crate::report::{FixRun, LintRun};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Environment variable naming the binary used by [`Cli::from_env`]
+pub const BINARY_ENV: &str = "NGINX_LINT";
+
+/// An `nginx-lint` binary and the extra arguments to pass on every run.
+#[derive(Debug, Clone)]
+pub struct Cli {
+    program: PathBuf,
+    args: Vec<OsString>,
+}
+
+impl Cli {
+    /// Use the binary at `program`
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Use the binary named by `NGINX_LINT`, or `nginx-lint` from `PATH`
+    pub fn from_env() -> Self {
+        Self::new(std::env::var_os(BINARY_ENV).unwrap_or_else(|| "nginx-lint".into()))
+    }
+
+    /// Pass an extra argument, e.g. `--rule-only` or `--context`
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Pass extra arguments
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Lint the fixture's entrypoint (and the files it includes)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the binary cannot be run or its output is not the JSON
+    /// report.
+    pub fn lint(&self, fixture: &Fixture) -> LintRun {
+        self.run(fixture, false)
+    }
+
+    /// Run with `--fix`, returning the report after fixing and the files
+    /// that changed
+    ///
+    /// # Panics
+    ///
+    /// Panics like [`lint`](Cli::lint).
+    pub fn fix(&self, fixture: &Fixture) -> FixRun {
+        let before = fixture.snapshot();
+        let lint = self.run(fixture, true);
+        FixRun::new(lint, before, fixture.snapshot())
+    }
+
+    /// Run `--fix` and check that the result is stable: no remaining
+    /// diagnostic offers a fix, and a second `--fix` changes nothing
+    ///
+    /// Returns the first run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either check fails, or like [`lint`](Cli::lint).
+    pub fn fix_round_trip(&self, fixture: &Fixture) -> FixRun {
+        let first = self.fix(fixture);
+        let fixable: Vec<_> = first
+            .lint
+            .diagnostics()
+            .filter(|(_, e)| !e.fixes.is_empty())
+            .map(|(file, e)| format!("{}:{}: [{}]", file, e.line.unwrap_or(0), e.rule))
+            .collect();
+        assert!(
+            fixable.is_empty(),
+            "fixable diagnostics remain after --fix: {:?}",
+            fixable
+        );
+
+        let second = self.fix(fixture);
+        assert!(
+            second.changed.is_empty(),
+            "a second --fix changed {:?}",
+            second.changed_files()
+        );
+        first
+    }
+
+    fn run(&self, fixture: &Fixture, fix: bool) -> LintRun {
+        let mut command = Command::new(&self.program);
+        command
+            .current_dir(fixture.root())
+            .args(["--no-color", "--format", "json"])
+            .args(&self.args);
+        if fix {
+            command.arg("--fix");
+        }
+        command.arg(fixture.entrypoint());
+
+        let output = command
+            .output()
+            .unwrap_or_else(|e| panic!("failed to run {}: {}", self.program.display(), e));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        LintRun::parse(&stdout, output.status.code(), stderr)
+            .unwrap_or_else(|e| panic!("unexpected output from {}: {}", self.program.display(), e))
+    }
+}
@@ -0,0 +1,170 @@
+//! Temporary config trees.
+
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Default entrypoint of a [`Fixture`]
+pub const DEFAULT_ENTRYPOINT: &str = "nginx.conf";
+
+/// A config tree in a temporary directory, removed when dropped.
+///
+/// Paths given to the builder methods are relative to the fixture root;
+/// parent directories are created as needed. The CLI is run from the root
+/// with the [entrypoint](Fixture::entrypoint) as its argument, so include
+/// patterns and reported file names are relative to the root as well.
+#[derive(Debug)]
+pub struct Fixture {
+    dir: TempDir,
+    entrypoint: PathBuf,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fixture {
+    /// An empty tree whose entrypoint is `nginx.conf`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the temporary directory cannot be created.
+    pub fn new() -> Self {
+        Self {
+            dir: tempfile::tempdir().expect("failed to create fixture directory"),
+            entrypoint: PathBuf::from(DEFAULT_ENTRYPOINT),
+        }
+    }
+
+    /// Copy an existing directory (e.g. `tests/fixtures/site`) into a new
+    /// fixture, so `--fix` never modifies the checked-in files
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` cannot be read.
+    pub fn from_dir(source: impl AsRef<Path>) -> Self {
+        let fixture = Self::new();
+        copy_dir(source.as_ref(), fixture.root());
+        fixture
+    }
+
+    /// Write a file into the tree
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file cannot be written.
+    pub fn file(self, path: impl AsRef<Path>, content: &str) -> Self {
+        let path = self.root().join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("failed to create fixture directory");
+        }
+        std::fs::write(&path, content)
+            .unwrap_or_else(|e| panic!("failed to write {}: {}", path.display(), e));
+        self
+    }
+
+    /// Write `.nginx-lint.toml` at the root, which the CLI picks up
+    pub fn lint_config(self, toml: &str) -> Self {
+        self.file(".nginx-lint.toml", toml)
+    }
+
+    /// Lint `path` instead of `nginx.conf`
+    pub fn with_entrypoint(mut self, path: impl AsRef<Path>) -> Self {
+        self.entrypoint = path.as_ref().to_path_buf();
+        self
+    }
+
+    /// Root directory of the tree
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// File passed to the CLI, relative to the root
+    pub fn entrypoint(&self) -> &Path {
+        &self.entrypoint
+    }
+
+    /// Read a file of the tree
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file does not exist.
+    pub fn read(&self, path: impl AsRef<Path>) -> String {
+        let path = self.root().join(path);
+        std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e))
+    }
+
+    /// Contents of every file of the tree, keyed by path relative to the root
+    pub fn snapshot(&self) -> Vec<(PathBuf, String)> {
+        let mut files = Vec::new();
+        collect_files(self.root(), self.root(), &mut files);
+        files.sort();
+        files
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    let entries = std::fs::read_dir(from)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", from.display(), e));
+    for entry in entries.flatten() {
+        let target = to.join(entry.file_name());
+        if entry.path().is_dir() {
+            std::fs::create_dir_all(&target).expect("failed to create fixture directory");
+            copy_dir(&entry.path(), &target);
+        } else {
+            std::fs::copy(entry.path(), &target)
+                .unwrap_or_else(|e| panic!("failed to copy {}: {}", entry.path().display(), e));
+        }
+    }
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(PathBuf, String)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, files);
+        } else if let Ok(content) = std::fs::read_to_string(&path) {
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            files.push((relative, content));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_and_snapshot() {
+        let fixture = Fixture::new()
+            .file("nginx.conf", "http {}\n")
+            .file("conf.d/app.conf", "server {}\n")
+            .lint_config("[rules.indent]\nenabled = false\n");
+
+        assert_eq!(fixture.read("conf.d/app.conf"), "server {}\n");
+        let paths: Vec<_> = fixture.snapshot().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from(".nginx-lint.toml"),
+                PathBuf::from("conf.d/app.conf"),
+                PathBuf::from("nginx.conf"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_dir_copies_tree() {
+        let source = Fixture::new().file("sites/a.conf", "server {}\n");
+        let copy = Fixture::from_dir(source.root()).with_entrypoint("sites/a.conf");
+
+        assert_eq!(copy.read("sites/a.conf"), "server {}\n");
+        assert_eq!(copy.entrypoint(), Path::new("sites/a.conf"));
+        assert_ne!(copy.root(), source.root());
+    }
+}
@@ -0,0 +1,41 @@
+//! Helpers for end-to-end tests of the nginx-lint CLI.
+//!
+//! Rule-level tests (`nginx_lint_plugin::testing::PluginTestRunner`) check a
+//! single plugin against a string. This crate runs the `nginx-lint` binary
+//! itself against a config tree on disk, the way users and packagers do, and
+//! parses its JSON output so tests assert on diagnostics instead of text.
+//!
+//! # Modules
+//!
+//! - [`fixture`] — Temporary config trees ([`Fixture`])
+//! - [`cli`] — Running the binary ([`Cli`])
+//! - [`report`] — Parsed results ([`LintRun`], [`FixRun`], [`FileReport`])
+//!
+//! # Example
+//!
+//! ```no_run
+//! use nginx_lint_test::{Cli, Fixture};
+//!
+//! let fixture = Fixture::new()
+//!     .file("nginx.conf", "http {\n    include conf.d/*.conf;\n}\n")
+//!     .file("conf.d/app.conf", "server {\n    server_tokens on;\n}\n");
+//!
+//! let cli = Cli::from_env();
+//! let run = cli.lint(&fixture);
+//! run.assert_rule_at("server-tokens-enabled", "conf.d/app.conf", 2);
+//!
+//! let fixed = cli.fix(&fixture);
+//! fixed.assert_file("conf.d/app.conf", "server {\n    server_tokens off;\n}\n");
+//! fixed.lint.assert_no_rule("server-tokens-enabled");
+//! ```
+
+pub mod cli;
+pub mod fixture;
+pub mod report;
+
+pub use cli::Cli;
+pub use fixture::Fixture;
+pub use report::{FileReport, FixRun, LintRun, Summary};
+
+// Re-export the diagnostic types found in reports
+pub use nginx_lint_common::{Fix, LintError, Severity};
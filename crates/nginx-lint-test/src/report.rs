@@ -0,0 +1,288 @@
+//! Parsed results of a CLI run.
+
+use nginx_lint_common::{LintError, Severity};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// The JSON report of one file (`nginx-lint --format json`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileReport {
+    /// File path as printed by the CLI, relative to the fixture root
+    pub file: String,
+    /// Diagnostics in line and column order
+    pub errors: Vec<LintError>,
+    pub summary: Summary,
+}
+
+/// Diagnostic counts of a [`FileReport`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct Summary {
+    pub errors: usize,
+    pub warnings: usize,
+    /// Diagnostics suppressed by ignore comments
+    pub ignored: usize,
+}
+
+/// Result of linting a fixture.
+#[derive(Debug, Clone)]
+pub struct LintRun {
+    /// One report per linted file, in path order
+    pub reports: Vec<FileReport>,
+    /// Exit code of the process (`None` if killed by a signal)
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+}
+
+impl LintRun {
+    /// Parse the CLI's JSON output: one report object per file
+    pub fn parse(
+        stdout: &str,
+        exit_code: Option<i32>,
+        stderr: String,
+    ) -> Result<Self, serde_json::Error> {
+        let reports = serde_json::Deserializer::from_str(stdout)
+            .into_iter::<FileReport>()
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            reports,
+            exit_code,
+            stderr,
+        })
+    }
+
+    /// The report of `file`, if it was linted
+    pub fn report(&self, file: impl AsRef<Path>) -> Option<&FileReport> {
+        self.reports
+            .iter()
+            .find(|r| same_path(&r.file, file.as_ref()))
+    }
+
+    /// Every diagnostic with the file it belongs to
+    pub fn diagnostics(&self) -> impl Iterator<Item = (&str, &LintError)> {
+        self.reports
+            .iter()
+            .flat_map(|r| r.errors.iter().map(move |e| (r.file.as_str(), e)))
+    }
+
+    /// Diagnostics reported by `rule`
+    pub fn rule(&self, rule: &str) -> Vec<(&str, &LintError)> {
+        self.diagnostics().filter(|(_, e)| e.rule == rule).collect()
+    }
+
+    /// Whether any diagnostic has error severity
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics()
+            .any(|(_, e)| e.severity == Severity::Error)
+    }
+
+    /// Assert that nothing was reported and the run succeeded
+    pub fn assert_clean(&self) {
+        let diagnostics: Vec<_> = self.diagnostics().collect();
+        assert!(
+            diagnostics.is_empty(),
+            "expected no diagnostics, got:\n{}",
+            describe(&diagnostics)
+        );
+        self.assert_exit_code(0);
+    }
+
+    /// Assert that `rule` reported something on `line` of `file`
+    pub fn assert_rule_at(&self, rule: &str, file: impl AsRef<Path>, line: usize) {
+        let file = file.as_ref();
+        assert!(
+            self.rule(rule)
+                .iter()
+                .any(|(f, e)| same_path(f, file) && e.line == Some(line)),
+            "expected {} at {}:{}, got:\n{}",
+            rule,
+            file.display(),
+            line,
+            describe(&self.diagnostics().collect::<Vec<_>>())
+        );
+    }
+
+    /// Assert that `rule` reported nothing
+    pub fn assert_no_rule(&self, rule: &str) {
+        let found = self.rule(rule);
+        assert!(
+            found.is_empty(),
+            "expected no {} diagnostics, got:\n{}",
+            rule,
+            describe(&found)
+        );
+    }
+
+    /// Assert the process exit code (0: clean, 1: problems found, 2: failure)
+    pub fn assert_exit_code(&self, code: i32) {
+        assert_eq!(
+            self.exit_code,
+            Some(code),
+            "unexpected exit code; stderr:\n{}",
+            self.stderr
+        );
+    }
+}
+
+/// Result of running the CLI with `--fix`.
+#[derive(Debug, Clone)]
+pub struct FixRun {
+    /// The report printed after fixing (remaining diagnostics)
+    pub lint: LintRun,
+    /// Files whose content changed: (path, before, after)
+    pub changed: Vec<(PathBuf, String, String)>,
+}
+
+impl FixRun {
+    pub(crate) fn new(
+        lint: LintRun,
+        before: Vec<(PathBuf, String)>,
+        after: Vec<(PathBuf, String)>,
+    ) -> Self {
+        let changed = after
+            .into_iter()
+            .filter_map(|(path, new)| {
+                let old = before
+                    .iter()
+                    .find(|(p, _)| *p == path)
+                    .map(|(_, c)| c.clone())
+                    .unwrap_or_default();
+                (old != new).then_some((path, old, new))
+            })
+            .collect();
+        Self { lint, changed }
+    }
+
+    /// Paths of the files that changed
+    pub fn changed_files(&self) -> Vec<&Path> {
+        self.changed.iter().map(|(p, _, _)| p.as_path()).collect()
+    }
+
+    /// Assert that `file` was rewritten to `expected`
+    pub fn assert_file(&self, file: impl AsRef<Path>, expected: &str) {
+        let file = file.as_ref();
+        let Some((_, _, after)) = self.changed.iter().find(|(p, _, _)| p == file) else {
+            panic!(
+                "expected {} to be fixed; changed files: {:?}",
+                file.display(),
+                self.changed_files()
+            );
+        };
+        assert_eq!(after, expected, "fixed content of {}", file.display());
+    }
+
+    /// Assert that no file changed
+    pub fn assert_unchanged(&self) {
+        assert!(
+            self.changed.is_empty(),
+            "expected no changes; changed files: {:?}",
+            self.changed_files()
+        );
+    }
+}
+
+/// Compare a path printed by the CLI with one given by a test, ignoring a
+/// leading `./`
+fn same_path(printed: &str, expected: &Path) -> bool {
+    Path::new(printed.strip_prefix("./").unwrap_or(printed)) == expected
+}
+
+fn describe(diagnostics: &[(&str, &LintError)]) -> String {
+    diagnostics
+        .iter()
+        .map(|(file, e)| {
+            format!(
+                "  {}:{}: [{}] {}\n",
+                file,
+                e.line.unwrap_or(0),
+                e.rule,
+                e.message
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = r#"{
+  "file": "conf.d/app.conf",
+  "errors": [
+    {
+      "rule": "server-tokens-enabled",
+      "category": "security",
+      "message": "server_tokens should be 'off' to hide nginx version",
+      "severity": "Warning",
+      "line": 2,
+      "column": 5,
+      "fixes": [
+        {
+          "line": 0,
+          "old_text": null,
+          "new_text": "    server_tokens off;",
+          "start_offset": 9,
+          "end_offset": 31
+        }
+      ]
+    }
+  ],
+  "summary": {
+    "errors": 0,
+    "warnings": 1,
+    "ignored": 0
+  }
+}
+{
+  "file": "nginx.conf",
+  "errors": [],
+  "summary": {
+    "errors": 0,
+    "warnings": 0,
+    "ignored": 1
+  }
+}
+"#;
+
+    #[test]
+    fn test_parse_reports() {
+        let run = LintRun::parse(OUTPUT, Some(1), String::new()).unwrap();
+
+        assert_eq!(run.reports.len(), 2);
+        assert_eq!(run.report("nginx.conf").unwrap().summary.ignored, 1);
+        let (file, error) = run.diagnostics().next().unwrap();
+        assert_eq!(file, "conf.d/app.conf");
+        assert_eq!(error.severity, Severity::Warning);
+        assert_eq!(error.fixes[0].start_offset, Some(9));
+        assert!(!run.has_errors());
+
+        run.assert_rule_at("server-tokens-enabled", "conf.d/app.conf", 2);
+        run.assert_no_rule("indent");
+        run.assert_exit_code(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected no diagnostics")]
+    fn test_assert_clean_lists_diagnostics() {
+        LintRun::parse(OUTPUT, Some(1), String::new())
+            .unwrap()
+            .assert_clean();
+    }
+
+    #[test]
+    fn test_fix_run_changed_files() {
+        let run = FixRun::new(
+            LintRun::parse("", Some(0), String::new()).unwrap(),
+            vec![
+                (PathBuf::from("a.conf"), "a  \n".to_string()),
+                (PathBuf::from("b.conf"), "b\n".to_string()),
+            ],
+            vec![
+                (PathBuf::from("a.conf"), "a\n".to_string()),
+                (PathBuf::from("b.conf"), "b\n".to_string()),
+            ],
+        );
+
+        assert_eq!(run.changed_files(), vec![Path::new("a.conf")]);
+        run.assert_file("a.conf", "a\n");
+    }
+}
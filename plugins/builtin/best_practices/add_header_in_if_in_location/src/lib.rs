@@ -0,0 +1,510 @@
+//! add-header-in-if-in-location plugin
+//!
+//! This plugin warns about `add_header` inside an `if` block inside `location`.
+//! The `if` block is a separate configuration level, so for requests where the
+//! condition matches only the headers in the `if` are sent, and for the others
+//! only the headers outside it. The warning suggests an equivalent `map`.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+
+/// Check for `add_header` inside `if` inside `location`
+#[derive(Default)]
+pub struct AddHeaderInIfInLocationPlugin;
+
+/// Comparison operators of the `if` directive
+const OPERATORS: &[&str] = &["=", "!=", "~", "~*", "!~", "!~*"];
+
+impl AddHeaderInIfInLocationPlugin {
+    /// Walk `items`, tracking whether we are inside a location and which
+    /// `add_header` names are inherited at this level
+    fn check_items(
+        &self,
+        items: &[ConfigItem],
+        in_location: bool,
+        inherited: &[String],
+        errors: &mut Vec<LintError>,
+    ) {
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            let Some(block) = &directive.block else {
+                continue;
+            };
+
+            if directive.is("if") {
+                if in_location {
+                    self.check_if_block(directive, block, inherited, errors);
+                }
+                continue;
+            }
+
+            // add_header is inherited only when a level defines none of its own
+            let own = Self::header_names(block);
+            let inherited = if own.is_empty() {
+                inherited.to_vec()
+            } else {
+                own
+            };
+            let in_location = in_location || directive.is("location");
+            self.check_items(&block.items, in_location, &inherited, errors);
+        }
+    }
+
+    /// Warn about each `add_header` directly inside an `if` block
+    fn check_if_block(
+        &self,
+        if_directive: &Directive,
+        block: &Block,
+        inherited: &[String],
+        errors: &mut Vec<LintError>,
+    ) {
+        let err =
+            PluginSpec::new("add-header-in-if-in-location", "best-practices", "").error_builder();
+
+        for directive in block.directives().filter(|d| d.is("add_header")) {
+            let Some(name) = directive.first_arg() else {
+                continue;
+            };
+
+            let dropped = if inherited.is_empty() {
+                "add_header directives from outer levels are not inherited".to_string()
+            } else {
+                format!(
+                    "the headers added outside the 'if' ({}) are dropped",
+                    inherited.join(", ")
+                )
+            };
+            let suggestion = match Self::map_rewrite(if_directive, directive) {
+                Some(rewrite) => format!("use a map in http context instead: {}", rewrite),
+                None => "set the header value with a map in http context instead".to_string(),
+            };
+
+            errors.push(err.warning_at(
+                &format!(
+                    "'add_header {}' inside 'if' in location: for requests matching the \
+                     condition, {}, and the header is missing for all other requests; {}",
+                    name, dropped, suggestion
+                ),
+                directive,
+            ));
+        }
+    }
+
+    /// Names of the `add_header` directives directly inside `block`
+    fn header_names(block: &Block) -> Vec<String> {
+        block
+            .directives()
+            .filter(|d| d.is("add_header"))
+            .filter_map(|d| d.first_arg().map(str::to_string))
+            .collect()
+    }
+
+    /// Build the `map` + `add_header` pair equivalent to `add_header` inside
+    /// `if`, or `None` when the condition is not a variable test
+    ///
+    /// An empty value makes nginx skip the header, which is what the `map`
+    /// returns for requests the `if` would not match.
+    fn map_rewrite(if_directive: &Directive, add_header: &Directive) -> Option<String> {
+        let words = Self::condition_words(if_directive);
+        let variable = words.first().filter(|w| w.starts_with('$'))?;
+
+        let [_, value, ..] = add_header.args.as_slice() else {
+            return None;
+        };
+        let value = value.to_source();
+        let name = add_header.first_arg()?;
+        let map_variable = format!(
+            "${}_header",
+            name.to_ascii_lowercase()
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+        );
+
+        let entries = match words.as_slice() {
+            [_] => format!("\"\" \"\"; \"0\" \"\"; default {};", value),
+            [_, op, operand] if OPERATORS.contains(&op.as_str()) => {
+                let key = match op.trim_start_matches('!') {
+                    "=" => operand.clone(),
+                    regex_op => match operand.chars().next() {
+                        Some(q @ ('"' | '\'')) => format!("{}{}{}", q, regex_op, &operand[1..]),
+                        _ => format!("{}{}", regex_op, operand),
+                    },
+                };
+                if op.starts_with('!') {
+                    format!("{} \"\"; default {};", key, value)
+                } else {
+                    format!("{} {}; default \"\";", key, value)
+                }
+            }
+            _ => return None,
+        };
+
+        let mut add_header_args = vec![name.to_string(), map_variable.clone()];
+        add_header_args.extend(add_header.args[2..].iter().map(|a| a.to_source()));
+
+        Some(format!(
+            "`map {} {} {{ {} }}` with `add_header {};` in the location",
+            variable,
+            map_variable,
+            entries,
+            add_header_args.join(" ")
+        ))
+    }
+
+    /// Split the `if` condition into words, without the surrounding parentheses
+    fn condition_words(if_directive: &Directive) -> Vec<String> {
+        let mut words = if_directive.words();
+        if let Some(first) = words.first_mut() {
+            *first = first.trim_start_matches('(').to_string();
+        }
+        if let Some(last) = words.last_mut() {
+            *last = last.trim_end_matches(')').to_string();
+        }
+        words.retain(|w| !w.is_empty());
+        words
+    }
+}
+
+impl Plugin for AddHeaderInIfInLocationPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "add-header-in-if-in-location",
+            "best-practices",
+            "Warns about add_header inside 'if' in location context",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "An 'if' block inside a location is its own configuration level, and add_header \
+             directives are inherited from the previous level only if the current level \
+             defines none. An add_header inside 'if' therefore sets a double trap:\n\n\
+             - for requests matching the condition, every add_header of the location (or of \
+             server/http, when the location has none) is silently dropped, including security \
+             headers such as Strict-Transport-Security;\n\
+             - for all other requests, the header from the 'if' is not sent.\n\n\
+             Unlike the generic if-is-evil-in-location warning, this is a certain bug rather \
+             than a risk. Compute the value with a map in http context and add the header \
+             unconditionally; nginx does not send a header whose value is empty:\n\n\
+             map $arg_debug $x_debug_header {\n    \
+                 \"1\" \"enabled\";\n    \
+                 default \"\";\n\
+             }\n\n\
+             location / {\n    \
+                 add_header X-Frame-Options DENY;\n    \
+                 add_header X-Debug $x_debug_header;\n\
+             }",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_headers_module.html#add_header".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_map_module.html#map".to_string(),
+            "https://github.com/nginxinc/nginx-wiki/blob/master/source/start/topics/depth/ifisevil.rst".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/best_practices/add_header_in_if_in_location/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["http", "server", "location", "if", "add_header"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let in_location = config.is_included_from_http_location();
+        self.check_items(&config.items, in_location, &[], &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(AddHeaderInIfInLocationPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(source: &str) -> Vec<LintError> {
+        let config = parse_string(source).unwrap();
+        AddHeaderInIfInLocationPlugin.check(&config, "test.conf")
+    }
+
+    #[test]
+    fn test_add_header_in_if_drops_location_headers() {
+        let errors = check(
+            r#"
+http {
+    server {
+        location / {
+            add_header X-Frame-Options DENY;
+            if ($arg_flag = "1") {
+                add_header X-Inside "conditional" always;
+            }
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "Expected 1 error, got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(7));
+        assert!(errors[0].message.contains("'add_header X-Inside'"));
+        assert!(
+            errors[0]
+                .message
+                .contains("the headers added outside the 'if' (X-Frame-Options) are dropped")
+        );
+        assert!(errors[0].message.contains(
+            r#"`map $arg_flag $x_inside_header { "1" "conditional"; default ""; }` with `add_header X-Inside $x_inside_header always;`"#
+        ));
+    }
+
+    #[test]
+    fn test_inherited_headers_from_server() {
+        let errors = check(
+            r#"
+http {
+    add_header X-Http 1;
+    server {
+        add_header Strict-Transport-Security "max-age=31536000";
+        location / {
+            if ($http_x_debug) {
+                add_header X-Debug on;
+            }
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .contains("(Strict-Transport-Security) are dropped")
+        );
+        assert!(
+            errors[0]
+                .message
+                .contains(r#"{ "" ""; "0" ""; default on; }"#)
+        );
+    }
+
+    #[test]
+    fn test_no_outer_headers() {
+        let errors = check(
+            r#"
+http {
+    server {
+        location / {
+            if ($request_uri ~* "\.json$") {
+                add_header Content-Disposition inline;
+            }
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .contains("add_header directives from outer levels are not inherited")
+        );
+        assert!(
+            errors[0]
+                .message
+                .contains(r#"{ "~*\.json$" inline; default ""; }"#)
+        );
+    }
+
+    #[test]
+    fn test_negated_condition() {
+        let errors = check(
+            r#"
+http {
+    server {
+        location / {
+            if ($scheme != https) {
+                add_header X-Insecure yes;
+            }
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .contains(r#"`map $scheme $x_insecure_header { https ""; default yes; }`"#)
+        );
+    }
+
+    #[test]
+    fn test_file_test_has_no_map_suggestion() {
+        let errors = check(
+            r#"
+http {
+    server {
+        location / {
+            if (-f $request_filename) {
+                add_header X-Static 1;
+            }
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .contains("set the header value with a map in http context instead")
+        );
+    }
+
+    #[test]
+    fn test_each_add_header_reported() {
+        let errors = check(
+            r#"
+http {
+    server {
+        location / {
+            if ($arg_debug) {
+                add_header X-Debug 1;
+                add_header X-Debug-Host $hostname;
+                return 200;
+            }
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, Some(6));
+        assert_eq!(errors[1].line, Some(7));
+    }
+
+    #[test]
+    fn test_if_in_server_is_ok() {
+        let runner = PluginTestRunner::new(AddHeaderInIfInLocationPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        if ($host = old.example.com) {
+            add_header X-Moved 1;
+            return 301 https://example.com$request_uri;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_if_without_add_header_is_ok() {
+        let runner = PluginTestRunner::new(AddHeaderInIfInLocationPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        location / {
+            add_header X-Frame-Options DENY;
+            if ($request_method = POST) {
+                return 405;
+            }
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_nested_location() {
+        let errors = check(
+            r#"
+http {
+    server {
+        location / {
+            add_header X-Outer 1;
+            location /api/ {
+                if ($arg_v = 2) {
+                    add_header X-Version 2;
+                }
+            }
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("(X-Outer) are dropped"));
+    }
+
+    #[test]
+    fn test_include_context_from_location() {
+        let mut config = parse_string(
+            r#"
+if ($arg_flag) {
+    add_header X-Flag 1;
+}
+"#,
+        )
+        .unwrap();
+        config.include_context = vec![
+            "http".to_string(),
+            "server".to_string(),
+            "location".to_string(),
+        ]
+        .into();
+
+        let errors = AddHeaderInIfInLocationPlugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "Expected 1 error, got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_include_context_from_server_no_error() {
+        let mut config = parse_string(
+            r#"
+if ($arg_flag) {
+    add_header X-Flag 1;
+}
+"#,
+        )
+        .unwrap();
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
+
+        let errors = AddHeaderInIfInLocationPlugin.check(&config, "test.conf");
+        assert!(errors.is_empty(), "Expected no errors, got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(AddHeaderInIfInLocationPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(AddHeaderInIfInLocationPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
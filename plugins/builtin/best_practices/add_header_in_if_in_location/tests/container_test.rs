@@ -0,0 +1,104 @@
+//! Container-based integration tests for the add-header-in-if-in-location rule.
+//!
+//! Demonstrates both sides of the trap: when the `if` matches, the headers of
+//! the enclosing location are dropped, and when it does not match, the header
+//! from the `if` is missing. The `map` rewrite suggested by the rule sends both.
+//!
+//! Run with:
+//!   cargo test -p add-header-in-if-in-location-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p add-header-in-if-in-location-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, reqwest};
+
+fn header(resp: &reqwest::Response, name: &str) -> Option<String> {
+    resp.headers()
+        .get(name)
+        .map(|v| v.to_str().unwrap().to_string())
+}
+
+/// `add_header` inside `if`: each request gets only one of the two headers.
+#[tokio::test]
+#[ignore]
+async fn add_header_in_if_sends_only_one_level() {
+    let nginx = NginxContainer::start(
+        br#"
+events {
+    worker_connections 1024;
+}
+http {
+    server {
+        listen 80;
+
+        location / {
+            add_header X-Frame-Options DENY always;
+
+            if ($arg_debug = "1") {
+                add_header X-Debug "enabled" always;
+            }
+            return 200 "ok";
+        }
+    }
+}
+"#,
+    )
+    .await;
+
+    // Condition does not match: the header from the if is missing
+    let resp = reqwest::get(nginx.url("/")).await.unwrap();
+    assert_eq!(header(&resp, "X-Frame-Options").as_deref(), Some("DENY"));
+    assert_eq!(header(&resp, "X-Debug"), None);
+
+    // Condition matches: the location's X-Frame-Options is dropped
+    let resp = reqwest::get(nginx.url("/?debug=1")).await.unwrap();
+    assert_eq!(header(&resp, "X-Debug").as_deref(), Some("enabled"));
+    assert_eq!(
+        header(&resp, "X-Frame-Options"),
+        None,
+        "X-Frame-Options should be dropped when the if matches"
+    );
+}
+
+/// The suggested `map` rewrite keeps the location's headers and sends the
+/// conditional one only when the condition matches.
+#[tokio::test]
+#[ignore]
+async fn map_rewrite_sends_both_headers() {
+    let nginx = NginxContainer::start(
+        br#"
+events {
+    worker_connections 1024;
+}
+http {
+    map $arg_debug $x_debug_header {
+        "1" "enabled";
+        default "";
+    }
+
+    server {
+        listen 80;
+
+        location / {
+            add_header X-Frame-Options DENY always;
+            add_header X-Debug $x_debug_header always;
+            return 200 "ok";
+        }
+    }
+}
+"#,
+    )
+    .await;
+
+    let resp = reqwest::get(nginx.url("/")).await.unwrap();
+    assert_eq!(header(&resp, "X-Frame-Options").as_deref(), Some("DENY"));
+    assert_eq!(
+        header(&resp, "X-Debug"),
+        None,
+        "an empty map value should not send the header"
+    );
+
+    let resp = reqwest::get(nginx.url("/?debug=1")).await.unwrap();
+    assert_eq!(header(&resp, "X-Frame-Options").as_deref(), Some("DENY"));
+    assert_eq!(header(&resp, "X-Debug").as_deref(), Some("enabled"));
+}
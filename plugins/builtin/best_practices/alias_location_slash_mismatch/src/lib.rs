@@ -33,48 +33,19 @@ impl AliasLocationSlashMismatchPlugin {
         }
     }
 
-    /// Get the location path from a location directive
-    fn get_location_path(directive: &Directive) -> Option<String> {
-        if directive.name != "location" {
-            return None;
-        }
-
-        // location can have modifiers: location [=|~|~*|^~] uri { ... }
-        // We need to get the actual URI path
-        for arg in &directive.args {
-            let value = match &arg.value {
-                ArgumentValue::Literal(s) => s.clone(),
-                ArgumentValue::QuotedString(s) => s.clone(),
-                ArgumentValue::SingleQuotedString(s) => s.clone(),
-                ArgumentValue::Variable(s) => format!("${}", s),
-            };
-
-            // Skip modifiers
-            if value == "=" || value == "~" || value == "~*" || value == "^~" {
-                continue;
+    /// Inspect a location's arguments (`[=|~|~*|^~] uri`) and return whether
+    /// its URI ends with a trailing slash and whether it is a regex location
+    fn location_flags<'a>(args: impl IntoIterator<Item = &'a str>) -> (bool, bool) {
+        let mut is_regex = false;
+        for arg in args {
+            match arg {
+                "~" | "~*" => is_regex = true,
+                // Other modifiers
+                "=" | "^~" => {}
+                path => return (path.ends_with('/'), is_regex),
             }
-
-            return Some(value);
         }
-
-        None
-    }
-
-    /// Check if location is a regex location
-    fn is_regex_location(directive: &Directive) -> bool {
-        if directive.name != "location" {
-            return false;
-        }
-
-        for arg in &directive.args {
-            if let ArgumentValue::Literal(s) = &arg.value
-                && (s == "~" || s == "~*")
-            {
-                return true;
-            }
-        }
-
-        false
+        (false, is_regex)
     }
 
     /// Recursively check for alias directives without trailing slash
@@ -140,9 +111,8 @@ impl AliasLocationSlashMismatchPlugin {
                 // Recurse into blocks
                 if let Some(block) = &directive.block {
                     if directive.name == "location" {
-                        let loc_path = Self::get_location_path(directive);
-                        let ends_with_slash = loc_path.as_ref().is_some_and(|p| p.ends_with('/'));
-                        let is_regex = Self::is_regex_location(directive);
+                        let (ends_with_slash, is_regex) =
+                            Self::location_flags(directive.args.iter().map(|arg| arg.as_str()));
                         self.check_items(&block.items, ends_with_slash, is_regex, errors);
                     } else {
                         self.check_items(
@@ -166,6 +136,7 @@ impl Plugin for AliasLocationSlashMismatchPlugin {
             "Warns when alias path doesn't end with '/' when location ends with '/'",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "The `alias` directive replaces the matched location prefix with the specified path. \
              When the location ends with a trailing slash, the alias should also end with a \
@@ -176,7 +147,9 @@ impl Plugin for AliasLocationSlashMismatchPlugin {
              `/images/photo.jpg` would try to access `/data/imagesphoto.jpg` instead of \
              `/data/images/photo.jpg`.\n\n\
              For regex locations using capture groups (e.g., `alias /data/$1`), this rule \
-             does not warn since the variable handles the path correctly.",
+             does not warn since the variable handles the path correctly.\n\n\
+             For a file included from inside a location, the location is taken from the \
+             include context, e.g. `--context \"http,server,location=/images/\"`.",
         )
         .with_bad_example(include_str!("../examples/bad.conf").trim())
         .with_good_example(include_str!("../examples/good.conf").trim())
@@ -199,8 +172,13 @@ impl Plugin for AliasLocationSlashMismatchPlugin {
 
     fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
         let mut errors = Vec::new();
-        // Start with location_ends_with_slash=false and is_regex_location=false
-        self.check_items(&config.items, false, false, &mut errors);
+        // Top-level aliases belong to the location the file is included from
+        let (ends_with_slash, is_regex) = config
+            .include_context
+            .innermost("location")
+            .map(|frame| Self::location_flags(frame.args.iter().map(String::as_str)))
+            .unwrap_or_default();
+        self.check_items(&config.items, ends_with_slash, is_regex, &mut errors);
         errors
     }
 }
@@ -407,6 +385,27 @@ http {
         );
     }
 
+    #[test]
+    fn test_include_context_location_args() {
+        let plugin = AliasLocationSlashMismatchPlugin;
+
+        let mut config = parse_string("alias /data/images;\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server,location=/images/");
+        let errors = plugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "Expected 1 error, got: {:?}", errors);
+        assert!(!errors[0].fixes.is_empty());
+
+        // Regex location from the context: warn, but no autofix
+        config.include_context = IncludeContext::parse("http,server,location=~ ^/images/");
+        let errors = plugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "Expected 1 error, got: {:?}", errors);
+        assert!(errors[0].fixes.is_empty());
+
+        // Only block names: the location match is unknown
+        config.include_context = IncludeContext::parse("http,server,location");
+        assert!(plugin.check(&config, "test.conf").is_empty());
+    }
+
     #[test]
     fn test_examples() {
         let runner = PluginTestRunner::new(AliasLocationSlashMismatchPlugin);
@@ -0,0 +1,497 @@
+//! auth-request-body-forwarded plugin
+//!
+//! This plugin warns when a location used as an `auth_request` target
+//! proxies the subrequest without `proxy_pass_request_body off` and
+//! `proxy_set_header Content-Length ""`.
+//!
+//! The authorization subrequest inherits the client's body and
+//! Content-Length header. Without the two directives nginx forwards the
+//! whole body to the auth server for every request (and an auth server that
+//! is only sent the header waits for a body that never comes).
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::location_match::{Location, LocationTree};
+use nginx_lint_plugin::prelude::*;
+
+const BODY_OFF: &str = "proxy_pass_request_body off;";
+const EMPTY_CONTENT_LENGTH: &str = "proxy_set_header Content-Length \"\";";
+
+/// Check auth_request targets for a forwarded request body
+#[derive(Default)]
+pub struct AuthRequestBodyForwardedPlugin;
+
+/// The URI of an `auth_request` directive, if it names one without variables
+fn auth_uri(directive: &Directive) -> Option<&str> {
+    // A URI with variables is parsed as several arguments
+    let [arg] = &directive.args[..] else {
+        return None;
+    };
+    let uri = arg.as_str();
+    if uri == "off" || arg.raw.contains('$') {
+        return None;
+    }
+    Some(uri.split('?').next().unwrap_or(uri))
+}
+
+/// The `auth_request` directives in `items` and the blocks nested in them
+fn collect_auth_requests<'a>(items: &'a [ConfigItem], found: &mut Vec<&'a Directive>) {
+    for item in items {
+        if let ConfigItem::Directive(directive) = item {
+            if directive.is("auth_request") {
+                found.push(directive);
+            }
+            if let Some(block) = &directive.block {
+                collect_auth_requests(&block.items, found);
+            }
+        }
+    }
+}
+
+/// The directives named `name` directly in `items`
+fn directives_named<'a>(
+    items: &'a [ConfigItem],
+    name: &'a str,
+) -> impl DoubleEndedIterator<Item = &'a Directive> {
+    items.iter().filter_map(move |item| match item {
+        ConfigItem::Directive(d) if d.is(name) => Some(d.as_ref()),
+        _ => None,
+    })
+}
+
+impl AuthRequestBodyForwardedPlugin {
+    /// Check the servers directly in `items` (an `http` block, or the top
+    /// level of a file included in `http`)
+    fn check_http(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        // auth_request set at the http level applies to every server
+        let inherited: Vec<&Directive> = directives_named(items, "auth_request").collect();
+
+        for server in directives_named(items, "server") {
+            let Some(block) = &server.block else {
+                continue;
+            };
+            let mut auth_requests = inherited.clone();
+            collect_auth_requests(&block.items, &mut auth_requests);
+
+            let tree = LocationTree::from_items(&block.items);
+            let mut reported: Vec<&Directive> = Vec::new();
+            for auth_request in auth_requests {
+                let Some(uri) = auth_uri(auth_request) else {
+                    continue;
+                };
+                let Some(chain) = tree.select(uri) else {
+                    continue;
+                };
+                let Some(target) = chain.last() else {
+                    continue;
+                };
+                if reported
+                    .iter()
+                    .any(|d| std::ptr::eq(*d, target.directive()))
+                {
+                    continue;
+                }
+                let levels: Vec<&[ConfigItem]> = [items, &block.items[..]]
+                    .into_iter()
+                    .chain(chain.iter().filter_map(|l| location_items(l)))
+                    .collect();
+                if let Some(error) = self.check_target(target, &levels, auth_request) {
+                    reported.push(target.directive());
+                    errors.push(error);
+                }
+            }
+        }
+    }
+
+    /// Check the location an `auth_request` selects; `levels` are the items
+    /// of the enclosing blocks, outermost first, ending with the location's
+    fn check_target(
+        &self,
+        target: &Location<'_>,
+        levels: &[&[ConfigItem]],
+        auth_request: &Directive,
+    ) -> Option<LintError> {
+        let items = *levels.last()?;
+        let proxy_pass = directives_named(items, "proxy_pass").next()?;
+
+        // proxy_pass_request_body is inherited from the closest level setting it
+        let body = levels
+            .iter()
+            .rev()
+            .find_map(|items| directives_named(items, "proxy_pass_request_body").next_back());
+        let body_forwarded = !body.is_some_and(|d| d.first_arg_is("off"));
+
+        // proxy_set_header is inherited only by levels that set none
+        let headers = levels
+            .iter()
+            .rev()
+            .map(|items| directives_named(items, "proxy_set_header").collect::<Vec<_>>())
+            .find(|headers| !headers.is_empty())
+            .unwrap_or_default();
+        let length_cleared = headers.iter().any(|d| {
+            d.first_arg()
+                .is_some_and(|name| name.eq_ignore_ascii_case("content-length"))
+                && d.arg_at(1) == Some("")
+        });
+
+        if !body_forwarded && length_cleared {
+            return None;
+        }
+
+        let mut missing = Vec::new();
+        let mut fixes = Vec::new();
+        let mut inserted = Vec::new();
+        if body_forwarded {
+            missing.push("proxy_pass_request_body off");
+            match body {
+                // Turned on in this location: turn it off there
+                Some(directive)
+                    if directives_named(items, "proxy_pass_request_body")
+                        .any(|d| std::ptr::eq(d, directive)) =>
+                {
+                    fixes.push(directive.replace_with(BODY_OFF));
+                }
+                _ => inserted.push(BODY_OFF),
+            }
+        }
+        if !length_cleared {
+            missing.push("proxy_set_header Content-Length \"\"");
+            inserted.push(EMPTY_CONTENT_LENGTH);
+        }
+        if !inserted.is_empty() {
+            fixes.push(proxy_pass.insert_after_many(&inserted));
+        }
+
+        let message = format!(
+            "location {} is the auth_request target of line {} but forwards the client's \
+             request body to the auth server; add {}",
+            target.display(),
+            auth_request.line(),
+            missing.join(" and ")
+        );
+        Some(
+            self.spec()
+                .error_builder()
+                .warning_at(&message, target.directive())
+                .with_fixes(fixes),
+        )
+    }
+}
+
+/// The items of a location block
+fn location_items<'a>(location: &Location<'a>) -> Option<&'a [ConfigItem]> {
+    location
+        .directive()
+        .block
+        .as_ref()
+        .map(|block| &block.items[..])
+}
+
+impl Plugin for AuthRequestBodyForwardedPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "auth-request-body-forwarded",
+            "best-practices",
+            "Warns when an auth_request target proxies the client's request body",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "The subrequest made by `auth_request` carries the client's request body and \
+             headers. A location proxying it to an authorization server therefore sends the \
+             whole body, such as a large upload, to the auth server on every request, \
+             buffering it first.\n\n\
+             Disabling the body with `proxy_pass_request_body off` alone is not enough: the \
+             client's `Content-Length` header is still sent, so the auth server waits for a \
+             body that never arrives until the request times out. Clear it with \
+             `proxy_set_header Content-Length \"\"`.\n\n\
+             Note that `proxy_set_header` directives are inherited only by blocks that set \
+             none of their own, so the header has to be cleared in the location itself if it \
+             sets any other header.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_auth_request_module.html".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_pass_request_body"
+                .to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["auth_request"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+
+        if config.include_context.contains("server") {
+            // The auth_request directives and locations of the server may
+            // be split across files; nothing can be resolved here
+        } else if config.is_included_from_http() {
+            self.check_http(&config.items, &mut errors);
+        } else {
+            for http in config.directives().filter(|d| d.is("http")) {
+                if let Some(block) = &http.block {
+                    self.check_http(&block.items, &mut errors);
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(AuthRequestBodyForwardedPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn runner() -> PluginTestRunner<AuthRequestBodyForwardedPlugin> {
+        PluginTestRunner::new(AuthRequestBodyForwardedPlugin)
+    }
+
+    #[test]
+    fn test_missing_both_directives() {
+        let errors = runner()
+            .check_string(
+                r#"
+http {
+    server {
+        location /private/ {
+            auth_request /auth;
+        }
+
+        location = /auth {
+            internal;
+            proxy_pass http://auth-server;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(8));
+        assert_eq!(
+            errors[0].message,
+            "location = /auth is the auth_request target of line 5 but forwards the client's \
+             request body to the auth server; add proxy_pass_request_body off and \
+             proxy_set_header Content-Length \"\""
+        );
+    }
+
+    #[test]
+    fn test_configured_target() {
+        runner().assert_no_errors(
+            r#"
+http {
+    server {
+        auth_request /auth;
+
+        location = /auth {
+            internal;
+            proxy_pass http://auth-server;
+            proxy_pass_request_body off;
+            proxy_set_header Content-Length "";
+            proxy_set_header X-Original-URI $request_uri;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_inherited_settings() {
+        // Both settings are inherited from the server
+        runner().assert_no_errors(
+            r#"
+http {
+    server {
+        proxy_pass_request_body off;
+        proxy_set_header Content-Length "";
+
+        location / {
+            auth_request /auth;
+        }
+
+        location /auth {
+            proxy_pass http://auth-server;
+        }
+    }
+}
+"#,
+        );
+
+        // The location's own proxy_set_header replaces the inherited ones
+        let errors = runner()
+            .check_string(
+                r#"
+http {
+    proxy_pass_request_body off;
+    proxy_set_header Content-Length "";
+
+    server {
+        location / {
+            auth_request /auth;
+        }
+
+        location /auth {
+            proxy_pass http://auth-server;
+            proxy_set_header X-Original-URI $request_uri;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .ends_with("add proxy_set_header Content-Length \"\"")
+        );
+    }
+
+    #[test]
+    fn test_targets_without_proxy_pass_are_ignored() {
+        runner().assert_no_errors(
+            r#"
+http {
+    server {
+        location / {
+            auth_request /auth;
+        }
+
+        location = /auth {
+            fastcgi_pass unix:/run/php.sock;
+        }
+    }
+}
+"#,
+        );
+        // The target is computed at runtime
+        runner().assert_no_errors(
+            r#"
+http {
+    server {
+        location / {
+            auth_request /auth$uri;
+        }
+
+        location /auth {
+            proxy_pass http://auth-server;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_http_level_auth_request() {
+        runner().assert_error_on_line(
+            r#"
+http {
+    auth_request /auth;
+
+    server {
+        location /auth {
+            proxy_pass http://auth-server;
+        }
+    }
+}
+"#,
+            6,
+        );
+    }
+
+    #[test]
+    fn test_target_reported_once() {
+        let errors = runner()
+            .check_string(
+                r#"
+http {
+    server {
+        location /a/ {
+            auth_request /auth;
+        }
+
+        location /b/ {
+            auth_request /auth?scope=b;
+        }
+
+        location = /auth {
+            proxy_pass http://auth-server;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_fix_turns_body_off() {
+        runner().assert_fix_produces(
+            r#"http {
+    server {
+        auth_request /auth;
+
+        location = /auth {
+            proxy_pass http://auth-server;
+            proxy_pass_request_body on;
+        }
+    }
+}
+"#,
+            r#"http {
+    server {
+        auth_request /auth;
+
+        location = /auth {
+            proxy_pass http://auth-server;
+            proxy_set_header Content-Length "";
+            proxy_pass_request_body off;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_included_file() {
+        let mut config = nginx_lint_plugin::parse_string(
+            "server {\n    auth_request /auth;\n    location /auth {\n        proxy_pass http://auth;\n    }\n}\n",
+        )
+        .unwrap();
+        config.include_context = IncludeContext::parse("http");
+        let errors = AuthRequestBodyForwardedPlugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 1);
+
+        config.include_context = IncludeContext::parse("http,server");
+        let errors = AuthRequestBodyForwardedPlugin.check(&config, "test.conf");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_examples() {
+        runner().test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        runner().test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,428 @@
+//! charset-conflict plugin
+//!
+//! This plugin detects `charset`, `override_charset`, `default_type` and
+//! `charset_types` settings that contradict each other once inheritance is
+//! taken into account. Each of these directives is inherited independently,
+//! so a value set in a less specific block can silently decide what a more
+//! specific block's setting does:
+//!
+//! - `charset` is only added to responses whose type is in the
+//!   `charset_types` in effect, so a `default_type` outside that list is
+//!   sent without a charset
+//! - a `default_type` that names its own charset gets a different charset
+//!   than responses of other types, which use `charset`
+//! - `override_charset on` does nothing while `charset` is off
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedSettings};
+use nginx_lint_plugin::prelude::*;
+
+/// Check for charset settings that conflict along an inheritance chain
+#[derive(Default)]
+pub struct CharsetConflictPlugin;
+
+/// nginx's default `charset_types` (`text/html` is always included)
+const DEFAULT_CHARSET_TYPES: &[&str] = &[
+    "text/html",
+    "text/xml",
+    "text/plain",
+    "text/vnd.wap.wml",
+    "application/javascript",
+    "application/rss+xml",
+];
+
+/// The settings checked, as in effect in a block
+type Scope<'a> = InheritedSettings<'a, 4>;
+
+/// The directives followed through the blocks
+const SETTINGS: [&str; 4] = [
+    "charset",
+    "override_charset",
+    "default_type",
+    "charset_types",
+];
+
+/// The charset added to responses, `None` when `charset` is off, or
+/// `Err(())` when it is not known
+fn charset<'a>(scope: &Scope<'a>) -> Result<Option<&'a str>, ()> {
+    match scope.get("charset") {
+        Inherited::Default => Ok(None),
+        Inherited::Set(d) => Ok(d.first_arg().filter(|c| !c.eq_ignore_ascii_case("off"))),
+        Inherited::Unknown => Err(()),
+    }
+}
+
+/// Whether the `charset_types` in effect include `mime`, if known
+fn charset_applies_to(scope: &Scope, mime: &str) -> Option<bool> {
+    if mime.eq_ignore_ascii_case("text/html") {
+        return Some(true);
+    }
+    match scope.get("charset_types") {
+        Inherited::Default => Some(lists(DEFAULT_CHARSET_TYPES.iter().copied(), mime)),
+        Inherited::Set(d) => Some(lists(d.args.iter().map(|a| a.as_str()), mime)),
+        Inherited::Unknown => None,
+    }
+}
+
+/// Whether a `charset_types` list matches `mime`
+fn lists<'a>(mut types: impl Iterator<Item = &'a str>, mime: &str) -> bool {
+    types.any(|t| t == "*" || t.eq_ignore_ascii_case(mime))
+}
+
+/// Split a `default_type` value into its MIME type and the charset it names
+fn split_type(value: &str) -> (&str, Option<&str>) {
+    let mut parts = value.split(';');
+    let mime = parts.next().unwrap_or_default().trim();
+    let charset = parts.find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    });
+    (mime, charset)
+}
+
+/// Whether responses of type `mime` are text that a charset applies to
+fn is_textual(mime: &str) -> bool {
+    let mime = mime.to_ascii_lowercase();
+    mime.starts_with("text/") || mime.ends_with("xml") || mime.ends_with("javascript")
+}
+
+impl CharsetConflictPlugin {
+    /// Report conflicts in each block that sets one of the settings
+    fn check_items<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: Scope<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let scope = inherited.enter(items);
+        self.check_scope(&scope, &inherited, errors);
+
+        for item in items {
+            if let ConfigItem::Directive(directive) = item
+                && let Some(block) = &directive.block
+            {
+                self.check_items(&block.items, scope, errors);
+            }
+        }
+    }
+
+    fn check_scope(&self, scope: &Scope, inherited: &Scope, errors: &mut Vec<LintError>) {
+        let err = self.spec().error_builder();
+        let set_here = |name| scope.get(name).set_here(inherited.get(name));
+        let charset_here = set_here("charset");
+        let default_type_here = set_here("default_type");
+        let charset_types_here = set_here("charset_types");
+        let override_here = set_here("override_charset");
+
+        let Ok(charset) = charset(scope) else {
+            return;
+        };
+
+        if let Some(charset) = charset
+            && let Some(value) = scope.get("default_type").value("text/plain")
+        {
+            let (mime, named) = split_type(value);
+            if let Some(named) = named {
+                // The charset named by default_type wins for default-typed
+                // responses only
+                if !named.eq_ignore_ascii_case(charset)
+                    && let Some(at) = default_type_here.or(charset_here)
+                {
+                    errors.push(err.warning_at(
+                        &format!(
+                            "default_type '{}' ({}) names charset '{}', but charset '{}' ({}) \
+                             applies to responses of other types, so the charset sent depends \
+                             on the response type",
+                            value,
+                            scope.get("default_type").origin(),
+                            named,
+                            charset,
+                            scope.get("charset").origin()
+                        ),
+                        at,
+                    ));
+                }
+            } else if is_textual(mime)
+                && charset_applies_to(scope, mime) == Some(false)
+                && let Some(at) = default_type_here.or(charset_types_here).or(charset_here)
+            {
+                errors.push(err.warning_at(
+                    &format!(
+                        "charset '{}' ({}) is not added to responses of default_type '{}' ({}) \
+                         because charset_types ({}) does not include it",
+                        charset,
+                        scope.get("charset").origin(),
+                        mime,
+                        scope.get("default_type").origin(),
+                        scope.get("charset_types").origin()
+                    ),
+                    at,
+                ));
+            }
+        }
+
+        if charset.is_none()
+            && let Inherited::Set(directive) = scope.get("override_charset")
+            && directive.first_arg_is("on")
+            && let Some(at) = override_here.or(charset_here)
+        {
+            let mut error = err.warning_at(
+                &format!(
+                    "override_charset on ({}) has no effect because charset is off ({})",
+                    scope.get("override_charset").origin(),
+                    scope.get("charset").origin()
+                ),
+                at,
+            );
+            if override_here.is_some() {
+                error = error.with_fix(directive.delete_line());
+            }
+            errors.push(error);
+        }
+    }
+}
+
+impl Plugin for CharsetConflictPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "charset-conflict",
+            "best-practices",
+            "Detects charset and default_type settings that conflict through inheritance",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "`charset`, `override_charset`, `default_type` and `charset_types` are each \
+             inherited on their own, so a value set in a less specific block can silently \
+             change what a more specific block's setting does.\n\n\
+             `charset` is only added to responses whose type is listed in the \
+             `charset_types` in effect. A location that sets `default_type application/xml` \
+             under `charset utf-8` still sends its responses without a charset, because \
+             the default `charset_types` (inherited from `http`, or nginx's default) does not \
+             list `application/xml`. A `default_type` that names its own charset \
+             (`text/html; charset=iso-8859-1`) is not touched by `charset`, so responses of \
+             the default type and of other types get different charsets. And \
+             `override_charset on` does nothing while `charset` is off.\n\n\
+             Add the type to `charset_types` where `charset` is set, keep the charset out \
+             of `default_type`, and remove `override_charset` where no charset is set.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_charset_module.html".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#default_type".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/best_practices/charset_conflict/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        // Only blocks that set one of these can change the settings
+        Some(&[
+            "charset",
+            "override_charset",
+            "default_type",
+            "charset_types",
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        // An included file inherits settings from blocks in another file
+        let root = Scope::root(SETTINGS, config);
+        self.check_items(&config.items, root, &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(CharsetConflictPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn messages(config: &str) -> Vec<(usize, String)> {
+        PluginTestRunner::new(CharsetConflictPlugin)
+            .check_string(config)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.line.unwrap(), e.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_type_outside_charset_types() {
+        assert_eq!(
+            messages(
+                r#"
+http {
+    charset utf-8;
+    server {
+        location /feed {
+            default_type application/xml;
+        }
+    }
+}
+"#
+            ),
+            vec![(
+                6,
+                "charset 'utf-8' (line 3) is not added to responses of default_type \
+                 'application/xml' (line 6) because charset_types (nginx's default) does not \
+                 include it"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_charset_types_inherited_from_http() {
+        assert_eq!(
+            messages(
+                r#"
+http {
+    charset_types text/html;
+    server {
+        charset utf-8;
+    }
+}
+"#
+            ),
+            vec![(
+                5,
+                "charset 'utf-8' (line 5) is not added to responses of default_type \
+                 'text/plain' (nginx's default) because charset_types (line 3) does not \
+                 include it"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_default_type_naming_another_charset() {
+        assert_eq!(
+            messages(
+                r#"
+http {
+    charset utf-8;
+    location / {
+        default_type "text/html; charset=iso-8859-1";
+    }
+}
+"#
+            ),
+            vec![(
+                5,
+                "default_type 'text/html; charset=iso-8859-1' (line 5) names charset \
+                 'iso-8859-1', but charset 'utf-8' (line 3) applies to responses of other \
+                 types, so the charset sent depends on the response type"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_override_charset_without_charset() {
+        let runner = PluginTestRunner::new(CharsetConflictPlugin);
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    override_charset on;
+    server {
+        charset koi8-r;
+        location /raw/ {
+            charset off;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        // Reported where charset is off: nginx's default in http, and the
+        // location that turns it off again
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(3));
+        assert_eq!(
+            errors[0].message,
+            "override_charset on (line 3) has no effect because charset is off (nginx's default)"
+        );
+        assert_eq!(errors[0].fixes.len(), 1);
+        assert_eq!(errors[1].line, Some(7));
+        assert!(errors[1].fixes.is_empty());
+    }
+
+    #[test]
+    fn test_consistent_settings() {
+        let runner = PluginTestRunner::new(CharsetConflictPlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    charset utf-8;
+    charset_types *;
+    default_type application/octet-stream;
+    server {
+        location /a { default_type application/xml; }
+        location /b { default_type "text/html; charset=utf-8"; }
+        location /c { default_type application/json; }
+        location /d { charset off; default_type "text/html; charset=iso-8859-1"; }
+        location /e { charset koi8-r; override_charset on; }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_reported_once_where_settings_change() {
+        let runner = PluginTestRunner::new(CharsetConflictPlugin);
+        runner.assert_errors(
+            r#"
+http {
+    charset utf-8;
+    default_type text/css;
+    charset_types text/html;
+    server {
+        location / {
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            1,
+        );
+    }
+
+    #[test]
+    fn test_included_file_with_unknown_settings() {
+        let mut config =
+            parse_string("location /feed { default_type application/xml; }\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server");
+
+        // The including file may set charset_types
+        assert!(CharsetConflictPlugin.check(&config, "test.conf").is_empty());
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(CharsetConflictPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(CharsetConflictPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,36 @@
+//! Container-based integration tests for the charset-conflict rule.
+//!
+//! Verifies that `charset` is only added to responses whose type is in the
+//! `charset_types` in effect: a location whose `default_type` is missing
+//! from the default list sends its responses without a charset.
+//!
+//! Run with:
+//!   cargo test -p charset-conflict-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p charset-conflict-plugin --test container_test -- --ignored
+
+use charset_conflict_plugin::CharsetConflictPlugin;
+use nginx_lint_plugin::container_testing::{BehaviorTest, RequestCheck};
+
+#[tokio::test]
+#[ignore]
+async fn bad_example_sends_no_charset() {
+    BehaviorTest::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/bad.conf"))
+        .linted_by(&CharsetConflictPlugin, 1)
+        .request(RequestCheck::get("/").expect_header("content-type", "application/xml"))
+        .run()
+        .await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn good_example_sends_charset() {
+    BehaviorTest::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/good.conf"))
+        .linted_by(&CharsetConflictPlugin, 0)
+        .request(
+            RequestCheck::get("/").expect_header("content-type", "application/xml; charset=utf-8"),
+        )
+        .run()
+        .await;
+}
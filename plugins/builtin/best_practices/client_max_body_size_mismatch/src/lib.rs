@@ -0,0 +1,503 @@
+//! client-max-body-size-mismatch plugin
+//!
+//! This advisory plugin reports locations that proxy uploads with a
+//! `client_max_body_size` too small for them.
+//!
+//! A location proxies uploads when it passes requests to a backend
+//! (`proxy_pass`, `fastcgi_pass`, ...) and either allows an upload method in
+//! `limit_except` or has a path containing one of the `upload_paths`. The
+//! limit in effect is looked up through the enclosing location, server and
+//! http blocks; nginx's default of 1m rejects most file uploads with 413.
+//!
+//! An upload location that lowers the limit set by an enclosing block is
+//! reported as well: requests the enclosing block accepts are rejected
+//! there, which is rarely what the larger value was set for.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::Inherited;
+use nginx_lint_plugin::prelude::*;
+
+/// nginx's default `client_max_body_size`
+const DEFAULT_CLIENT_MAX_BODY_SIZE: &str = "1m";
+
+/// Path fragments that make a proxying location an upload location by
+/// default
+const DEFAULT_UPLOAD_PATHS: &[&str] = &["upload"];
+
+/// Methods carrying a request body worth uploading
+const UPLOAD_METHODS: &[&str] = &["POST", "PUT", "PATCH"];
+
+/// Directives passing requests, and their bodies, to a backend
+const PASS_DIRECTIVES: &[&str] = &[
+    "proxy_pass",
+    "fastcgi_pass",
+    "grpc_pass",
+    "uwsgi_pass",
+    "scgi_pass",
+];
+
+/// Check upload locations for a too small or lowered `client_max_body_size`
+pub struct ClientMaxBodySizeMismatchPlugin {
+    /// Smallest acceptable limit on upload locations (option
+    /// `min_upload_size`); also the value the fix sets
+    min_upload_size: String,
+    /// Path fragments that make a location an upload location (option
+    /// `upload_paths`), matched case-insensitively
+    upload_paths: Vec<String>,
+    /// Whether to report upload locations lowering an enclosing limit
+    /// (option `check_overrides`)
+    check_overrides: bool,
+}
+
+impl Default for ClientMaxBodySizeMismatchPlugin {
+    fn default() -> Self {
+        Self {
+            min_upload_size: "10m".to_string(),
+            upload_paths: DEFAULT_UPLOAD_PATHS
+                .iter()
+                .map(|path| path.to_string())
+                .collect(),
+            check_overrides: true,
+        }
+    }
+}
+
+/// The limit in bytes (`u64::MAX` for `0`, which disables the check), if
+/// known
+fn limit_bytes(limit: Inherited) -> Option<u64> {
+    match helpers::parse_size(limit.value(DEFAULT_CLIENT_MAX_BODY_SIZE)?)? {
+        0 => Some(u64::MAX),
+        bytes => Some(bytes),
+    }
+}
+
+/// The value of a limit and where it comes from, for messages
+fn describe(limit: Inherited) -> String {
+    match limit {
+        Inherited::Set(d) => format!("{} (line {})", d.first_arg().unwrap_or_default(), d.line()),
+        _ => format!("{} (nginx's default)", DEFAULT_CLIENT_MAX_BODY_SIZE),
+    }
+}
+
+/// Whether `items`, or the `if` and `limit_except` blocks in them, pass
+/// requests to a backend
+fn has_pass(items: &[ConfigItem]) -> bool {
+    items.iter().any(|item| match item {
+        ConfigItem::Directive(d) if PASS_DIRECTIVES.iter().any(|name| d.is(name)) => true,
+        ConfigItem::Directive(d) if d.is("if") || d.is("limit_except") => {
+            d.block.as_ref().is_some_and(|b| has_pass(&b.items))
+        }
+        _ => false,
+    })
+}
+
+impl ClientMaxBodySizeMismatchPlugin {
+    /// Why `location` is considered to proxy uploads, for messages
+    fn upload_reason(&self, location: &Directive, block: &Block) -> Option<String> {
+        if !has_pass(&block.items) {
+            return None;
+        }
+        let limit_except = block.directives().find(|d| {
+            d.is("limit_except")
+                && d.args.iter().any(|arg| {
+                    UPLOAD_METHODS
+                        .iter()
+                        .any(|method| arg.as_str().eq_ignore_ascii_case(method))
+                })
+        });
+        if let Some(limit_except) = limit_except {
+            return Some(format!(
+                "limit_except allows uploads at line {}",
+                limit_except.line()
+            ));
+        }
+        let path = location.last_arg()?.to_ascii_lowercase();
+        self.upload_paths
+            .iter()
+            .find(|fragment| path.contains(&fragment.to_ascii_lowercase()))
+            .map(|fragment| format!("path contains '{}'", fragment))
+    }
+
+    /// Check the servers directly in `items` (an `http` block, or the top
+    /// level of a file included in `http`)
+    fn check_servers<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        limit: Inherited<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let limit = limit.enter(items, "client_max_body_size");
+        for item in items {
+            let ConfigItem::Directive(server) = item else {
+                continue;
+            };
+            if let (true, Some(block)) = (server.is("server"), &server.block) {
+                self.check_locations(
+                    &block.items,
+                    limit.enter(&block.items, "client_max_body_size"),
+                    errors,
+                );
+            }
+        }
+    }
+
+    /// Check the locations directly in `items` and the ones nested in them;
+    /// `limit` is the one in effect in `items`
+    fn check_locations<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        limit: Inherited<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        for item in items {
+            let ConfigItem::Directive(location) = item else {
+                continue;
+            };
+            let (true, Some(block)) = (location.is("location"), &location.block) else {
+                continue;
+            };
+            if let Some(reason) = self.upload_reason(location, block) {
+                self.check_location(location, block, limit, &reason, errors);
+            }
+            self.check_locations(
+                &block.items,
+                limit.enter(&block.items, "client_max_body_size"),
+                errors,
+            );
+        }
+    }
+
+    fn check_location(
+        &self,
+        location: &Directive,
+        block: &Block,
+        enclosing: Inherited<'_>,
+        reason: &str,
+        errors: &mut Vec<LintError>,
+    ) {
+        let Some(min_bytes) = helpers::parse_size(&self.min_upload_size) else {
+            return;
+        };
+        let limit = enclosing.enter(&block.items, "client_max_body_size");
+        let own = limit.set_here(enclosing);
+        let Some(bytes) = limit_bytes(limit) else {
+            return;
+        };
+        let err = self.spec().error_builder();
+        let pattern = location.last_arg().unwrap_or_default();
+
+        if bytes < min_bytes {
+            let message = format!(
+                "location {} proxies uploads ({}) but client_max_body_size is {}; larger \
+                 request bodies are rejected with 413; consider client_max_body_size {}",
+                pattern,
+                reason,
+                describe(limit),
+                self.min_upload_size
+            );
+            let setting = format!("client_max_body_size {};", self.min_upload_size);
+            let error = match own {
+                Some(directive) => err
+                    .warning_at(&message, directive)
+                    .with_fix(directive.replace_with(&setting)),
+                None => {
+                    let error = err.warning_at(&message, location);
+                    match block.directives().next() {
+                        Some(first) => error.with_fix(first.insert_before(&setting)),
+                        None => error,
+                    }
+                }
+            };
+            errors.push(error);
+            return;
+        }
+
+        if !self.check_overrides {
+            return;
+        }
+        let (Some(directive), Inherited::Set(_)) = (own, enclosing) else {
+            return;
+        };
+        if limit_bytes(enclosing).is_some_and(|enclosing| bytes < enclosing) {
+            let message = format!(
+                "client_max_body_size {} in upload location {} is smaller than {} set by an \
+                 enclosing block; request bodies accepted there are rejected with 413 here",
+                directive.first_arg().unwrap_or_default(),
+                pattern,
+                describe(enclosing)
+            );
+            errors.push(err.warning_at(&message, directive));
+        }
+    }
+}
+
+impl Plugin for ClientMaxBodySizeMismatchPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "client-max-body-size-mismatch",
+            "best-practices",
+            "Warns when locations proxying uploads keep a too small or lowered client_max_body_size",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "nginx rejects a request whose body exceeds `client_max_body_size` with 413 \
+             (Request Entity Too Large) before the backend sees it. The limit is inherited \
+             from the enclosing location, server and http blocks, and defaults to 1m, which \
+             is too small for most file uploads: the backend accepts them when tested directly, \
+             and they fail once nginx is in front of it.\n\n\
+             A location proxying uploads is also reported when it sets a smaller limit than \
+             an enclosing block. The larger value was usually raised for those uploads, and \
+             the nested value silently takes precedence.\n\n\
+             A location proxies uploads when it passes requests to a backend and allows \
+             POST, PUT or PATCH in `limit_except`, or its path contains one of the \
+             `upload_paths`. The smallest acceptable limit is set with the `min_upload_size` \
+             option, and the check of lowered limits is disabled with `check_overrides = \
+             false`.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#client_max_body_size"
+                .to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#limit_except".to_string(),
+        ])
+        .with_option(RuleOption::string(
+            "min_upload_size",
+            "10m",
+            "Smallest acceptable client_max_body_size on upload locations (nginx size syntax; also the value the fix sets)",
+        ))
+        .with_option(RuleOption::string_list(
+            "upload_paths",
+            DEFAULT_UPLOAD_PATHS,
+            "Path fragments that make a proxying location an upload location (case-insensitive)",
+        ))
+        .with_option(RuleOption::bool(
+            "check_overrides",
+            true,
+            "Report upload locations setting a smaller client_max_body_size than an enclosing block",
+        ))
+    }
+
+    fn configure(&mut self, options: &RuleOptions) {
+        self.min_upload_size = options.string("min_upload_size").to_string();
+        self.upload_paths = options.string_list("upload_paths").to_vec();
+        self.check_overrides = options.bool("check_overrides");
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["location"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let context = &config.include_context;
+
+        if context.contains("location") {
+            // The location, and what it proxies, are in the including file
+        } else if context.contains("server") {
+            self.check_locations(
+                &config.items,
+                Inherited::Unknown.enter(&config.items, "client_max_body_size"),
+                &mut errors,
+            );
+        } else if context.contains("http") {
+            self.check_servers(&config.items, Inherited::Unknown, &mut errors);
+        } else {
+            for http in config.directives().filter(|d| d.is("http")) {
+                if let Some(block) = &http.block {
+                    self.check_servers(&block.items, Inherited::Default, &mut errors);
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(ClientMaxBodySizeMismatchPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn messages(plugin: ClientMaxBodySizeMismatchPlugin, config: &str) -> Vec<(usize, String)> {
+        PluginTestRunner::new(plugin)
+            .check_string(config)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.line.unwrap(), e.message))
+            .collect()
+    }
+
+    fn server(body: &str) -> String {
+        format!(
+            "http {{\n    server {{\n        listen 80;\n{}\n    }}\n}}\n",
+            body
+        )
+    }
+
+    #[test]
+    fn test_default_limit_on_upload_location() {
+        let errors = messages(
+            ClientMaxBodySizeMismatchPlugin::default(),
+            &server(
+                "        location /upload {\n            proxy_pass http://storage;\n        }",
+            ),
+        );
+        assert_eq!(
+            errors,
+            [(
+                4,
+                "location /upload proxies uploads (path contains 'upload') but \
+                 client_max_body_size is 1m (nginx's default); larger request bodies are \
+                 rejected with 413; consider client_max_body_size 10m"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_upload_detection() {
+        let runner = PluginTestRunner::new(ClientMaxBodySizeMismatchPlugin::default());
+        runner.assert_has_errors(&server(
+            "        location /files {\n            limit_except PUT {\n                deny all;\n            }\n            fastcgi_pass php;\n        }",
+        ));
+        runner.assert_has_errors(&server(
+            "        location ~* ^/Uploads/ {\n            if ($arg_v) {\n                proxy_pass http://app;\n            }\n        }",
+        ));
+        // Read-only location
+        runner.assert_no_errors(&server(
+            "        location /files {\n            limit_except GET {\n                deny all;\n            }\n            proxy_pass http://app;\n        }",
+        ));
+        // Served by nginx itself
+        runner.assert_no_errors(&server(
+            "        location /uploads {\n            root /srv;\n        }",
+        ));
+    }
+
+    #[test]
+    fn test_inherited_limits() {
+        let runner = PluginTestRunner::new(ClientMaxBodySizeMismatchPlugin::default());
+        runner.assert_no_errors(
+            "http {\n    client_max_body_size 100m;\n    server {\n        location /upload {\n            proxy_pass http://app;\n        }\n    }\n}\n",
+        );
+        runner.assert_no_errors(
+            "http {\n    server {\n        client_max_body_size 0;\n        location /upload {\n            proxy_pass http://app;\n        }\n    }\n}\n",
+        );
+
+        let errors = messages(
+            ClientMaxBodySizeMismatchPlugin::default(),
+            "http {\n    client_max_body_size 2m;\n    server {\n        location /upload {\n            proxy_pass http://app;\n        }\n    }\n}\n",
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 4);
+        assert!(errors[0].1.contains("client_max_body_size is 2m (line 2)"));
+    }
+
+    #[test]
+    fn test_lowered_limit() {
+        let config = "http {\n    client_max_body_size 100m;\n    server {\n        location /upload {\n            client_max_body_size 20m;\n            proxy_pass http://app;\n        }\n    }\n}\n";
+        let errors = messages(ClientMaxBodySizeMismatchPlugin::default(), config);
+        assert_eq!(
+            errors,
+            [(
+                5,
+                "client_max_body_size 20m in upload location /upload is smaller than 100m \
+                 (line 2) set by an enclosing block; request bodies accepted there are rejected \
+                 with 413 here"
+                    .to_string()
+            )]
+        );
+
+        let mut plugin = ClientMaxBodySizeMismatchPlugin::default();
+        let configured =
+            std::collections::HashMap::from([("check_overrides".to_string(), false.into())]);
+        plugin.configure(&RuleOptions::resolve(&plugin.spec().options, &configured));
+        assert!(messages(plugin, config).is_empty());
+    }
+
+    #[test]
+    fn test_configured_options() {
+        use std::collections::HashMap;
+
+        let mut plugin = ClientMaxBodySizeMismatchPlugin::default();
+        let configured = HashMap::from([
+            ("min_upload_size".to_string(), "1g".into()),
+            (
+                "upload_paths".to_string(),
+                vec!["/media".to_string()].into(),
+            ),
+        ]);
+        plugin.configure(&RuleOptions::resolve(&plugin.spec().options, &configured));
+        let errors = messages(
+            plugin,
+            &server(
+                "        client_max_body_size 100m;\n        location /media {\n            proxy_pass http://app;\n        }\n        location /upload {\n            proxy_pass http://app;\n        }",
+            ),
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 5);
+        assert!(errors[0].1.ends_with("consider client_max_body_size 1g"));
+    }
+
+    #[test]
+    fn test_included_files() {
+        let check = |context: &str, source: &str| {
+            let mut config = nginx_lint_plugin::parse_string(source).unwrap();
+            config.include_context = IncludeContext::parse(context);
+            ClientMaxBodySizeMismatchPlugin::default().check(&config, "test.conf")
+        };
+        let location = "location /upload {\n    proxy_pass http://app;\n}\n";
+
+        // The limit may be set by the including blocks
+        assert!(check("http,server", location).is_empty());
+        assert!(check("http", &format!("server {{\n{}}}\n", location)).is_empty());
+
+        let errors = check(
+            "http,server",
+            "location /upload {\n    client_max_body_size 512k;\n    proxy_pass http://app;\n}\n",
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_fix() {
+        let runner = PluginTestRunner::new(ClientMaxBodySizeMismatchPlugin::default());
+        runner.assert_fix_produces(
+            &server("        location /upload {\n            proxy_pass http://storage;\n        }"),
+            &server(
+                "        location /upload {\n            client_max_body_size 10m;\n            proxy_pass http://storage;\n        }",
+            ),
+        );
+        runner.assert_fix_produces(
+            &server(
+                "        location /upload {\n            client_max_body_size 1m;\n            proxy_pass http://storage;\n        }",
+            ),
+            &server(
+                "        location /upload {\n            client_max_body_size 10m;\n            proxy_pass http://storage;\n        }",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(ClientMaxBodySizeMismatchPlugin::default());
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(ClientMaxBodySizeMismatchPlugin::default());
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -25,6 +25,7 @@ impl Plugin for ClientMaxBodySizeNotSetPlugin {
             "Warns when client_max_body_size is not explicitly set",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "The default client_max_body_size is 1m, which may cause unexpected 413 \
              (Request Entity Too Large) errors for file uploads or large POST requests. \
@@ -269,7 +270,7 @@ server {
         .unwrap();
 
         // Simulate being included from http context
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let plugin = ClientMaxBodySizeNotSetPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -298,7 +299,7 @@ server {
         .unwrap();
 
         // Simulate being included from http context
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let plugin = ClientMaxBodySizeNotSetPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -321,7 +322,7 @@ server {
         .unwrap();
 
         // Simulate being included from stream context
-        config.include_context = vec!["stream".to_string()];
+        config.include_context = vec!["stream".to_string()].into();
 
         let plugin = ClientMaxBodySizeNotSetPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -10,6 +10,9 @@
 //! - proxy_set_header, add_header, proxy_hide_header, grpc_set_header (case-insensitive keys)
 //! - fastcgi_param, uwsgi_param, scgi_param (case-sensitive keys)
 //! - limit_conn, limit_req (case-sensitive zone keys)
+//!
+//! The `excluded_directives` and `additional_directives` options change the
+//! checked set.
 
 use nginx_lint_plugin::prelude::*;
 use std::collections::HashMap;
@@ -149,11 +152,7 @@ impl DirectiveInheritancePlugin {
 
     /// Reconstruct directive text from a Directive AST node
     fn directive_to_text(directive: &Directive) -> Rc<str> {
-        let mut parts = vec![directive.name.clone()];
-        for arg in &directive.args {
-            parts.push(arg.to_source());
-        }
-        Rc::from(format!("{};", parts.join(" ")))
+        Rc::from(directive.to_source())
     }
 
     /// Normalize a key based on the directive spec
@@ -338,7 +337,13 @@ impl DirectiveInheritancePlugin {
                     ),
                     first,
                 )
-                .with_fix(first.insert_before_many(&missing_texts));
+                // Repeating the parent's directives restores what the block
+                // lost, but the block may have dropped them on purpose
+                .with_fix(
+                    first
+                        .insert_before_many(&missing_texts)
+                        .with_applicability(Applicability::MaybeIncorrect),
+                );
 
             errors.push(error);
         }
@@ -359,6 +364,7 @@ impl Plugin for DirectiveInheritancePlugin {
             "Warns when directives in child blocks don't include parent block values",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             format!(
                 "In nginx, certain directives in a child block (like location) completely \
@@ -377,14 +383,46 @@ impl Plugin for DirectiveInheritancePlugin {
             "https://nginx.org/en/docs/http/ngx_http_headers_module.html#add_header".to_string(),
             "https://nginx.org/en/docs/http/ngx_http_fastcgi_module.html#fastcgi_param".to_string(),
         ])
+        .with_option(RuleOption::string_list(
+            "excluded_directives",
+            &[],
+            "Checked directives to skip",
+        ))
+        .with_option(RuleOption::table_list(
+            "additional_directives",
+            "Other directives to check: `name`, and `case_insensitive` and `multi_key` (default false)",
+        ))
+    }
+
+    fn configure(&mut self, options: &RuleOptions) {
+        let excluded = options.string_list("excluded_directives").to_vec();
+        // Entries without a name have nothing to check
+        let additional = options
+            .table_list("additional_directives")
+            .iter()
+            .filter_map(|entry| {
+                let flag = |key: &str| entry.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+                Some(DirectiveSpecOwned {
+                    name: entry.get("name")?.as_str()?.to_string(),
+                    case_insensitive: flag("case_insensitive"),
+                    multi_key: flag("multi_key"),
+                })
+            })
+            .collect();
+        *self = Self::with_config(excluded, additional);
     }
 
     fn relevant_directives(&self) -> Option<&'static [&'static str]> {
-        // The WASM guest is always constructed via Default::default() (see
-        // export_component_plugin!), which always uses the base
-        // CHECKED_DIRECTIVES — the with_config() excluded/additional variant
-        // is only reachable through the native (non-WASM) plugin path in
-        // src/linter.rs, so this static list is safe to hardcode here.
+        // Directives added with `additional_directives` are not in the
+        // static list, so the whole config is needed
+        let only_builtin = self
+            .effective_specs
+            .iter()
+            .all(|s| CHECKED_DIRECTIVES.iter().any(|c| c.name == s.name));
+        if !only_builtin {
+            return None;
+        }
+
         //
         // check_block's context-boundary names ("http", "server", "location",
         // "if", "limit_except") are NOT needed: a block of one of these types
@@ -491,6 +529,7 @@ http {
             "Fix should contain X-Real-IP header: {}",
             fix.new_text
         );
+        assert_eq!(fix.applicability, Applicability::MaybeIncorrect);
     }
 
     #[test]
@@ -1434,4 +1473,43 @@ http {
         );
         assert!(errors2[0].message.contains("proxy_set_cookie"));
     }
+
+    #[test]
+    fn test_configured_options() {
+        use serde_json::json;
+        use std::collections::HashMap;
+
+        let mut plugin = DirectiveInheritancePlugin::default();
+        let configured = HashMap::from([
+            ("excluded_directives".to_string(), json!(["add_header"])),
+            (
+                "additional_directives".to_string(),
+                json!([{ "name": "proxy_set_cookie", "case_insensitive": true }, { "multi_key": true }]),
+            ),
+        ]);
+        let options = RuleOptions::resolve(&plugin.spec().options, &configured);
+        assert!(options.problems().is_empty(), "{:?}", options.problems());
+        plugin.configure(&options);
+        assert_eq!(plugin.relevant_directives(), None);
+
+        let errors = PluginTestRunner::new(plugin)
+            .check_string(
+                r#"
+http {
+    server {
+        add_header X-Frame-Options DENY;
+        proxy_set_cookie Session "a";
+
+        location / {
+            add_header X-Custom "value";
+            proxy_set_cookie Other "b";
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].message.contains("proxy_set_cookie"));
+    }
 }
@@ -4,6 +4,13 @@
 //! Gzip compression significantly reduces response sizes and improves
 //! page load times.
 //!
+//! It reports:
+//!
+//! - an `http` block without `gzip on` or `brotli on`; the fix inserts
+//!   `gzip on` and a recommended `gzip_types`
+//! - `gzip on` without `gzip_types`, which compresses `text/html` only
+//! - `gzip_types` that omits common text types; the fix appends them
+//!
 //! gzip is only valid in http, server, and location contexts.
 //!
 //! Build with:
@@ -13,9 +20,62 @@
 
 use nginx_lint_plugin::prelude::*;
 
+/// Text types worth compressing, each with its accepted spellings; the
+/// first spelling is the one inserted by fixes
+const TEXT_TYPES: &[&[&str]] = &[
+    &["text/plain"],
+    &["text/css"],
+    &["application/json"],
+    &[
+        "application/javascript",
+        "text/javascript",
+        "application/x-javascript",
+    ],
+];
+
 /// Check if gzip compression is enabled
-#[derive(Default)]
-pub struct GzipNotEnabledPlugin;
+pub struct GzipNotEnabledPlugin {
+    /// Report a missing or incomplete `gzip_types` (option `check_gzip_types`)
+    check_gzip_types: bool,
+}
+
+impl Default for GzipNotEnabledPlugin {
+    fn default() -> Self {
+        Self {
+            check_gzip_types: true,
+        }
+    }
+}
+
+/// The recommended `gzip_types` directive
+fn recommended_gzip_types() -> String {
+    let types: Vec<&str> = TEXT_TYPES.iter().map(|names| names[0]).collect();
+    format!("gzip_types {};", types.join(" "))
+}
+
+/// Text types missing from a `gzip_types` directive (none for `*`)
+fn missing_types(gzip_types: &Directive) -> Vec<&'static str> {
+    if gzip_types.has_arg("*") {
+        return Vec::new();
+    }
+    TEXT_TYPES
+        .iter()
+        .filter(|names| !names.iter().any(|name| gzip_types.has_arg(name)))
+        .map(|names| names[0])
+        .collect()
+}
+
+impl GzipNotEnabledPlugin {
+    /// Fix that inserts `gzip on` and `gzip_types` at the start of the
+    /// http block
+    fn enable_fix(config: &Config, http: &Directive) -> Option<Fix> {
+        FixBuilder::new(config).insert_directives_into_block(
+            http,
+            &["gzip on;", &recommended_gzip_types()],
+            BlockPosition::Start,
+        )
+    }
+}
 
 impl Plugin for GzipNotEnabledPlugin {
     fn spec(&self) -> PluginSpec {
@@ -25,31 +85,48 @@ impl Plugin for GzipNotEnabledPlugin {
             "Suggests enabling gzip compression for better performance",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "Gzip compression can significantly reduce the size of HTTP responses, often by \
              60-80% for text-based content like HTML, CSS, and JavaScript. This improves page \
-             load times and reduces bandwidth usage.",
+             load times and reduces bandwidth usage.\n\n\
+             `gzip on` alone only compresses `text/html`: other types must be listed in \
+             `gzip_types`, so CSS, JavaScript and JSON responses are sent uncompressed unless \
+             they are added. `brotli on` (ngx_brotli) is accepted in place of `gzip on`.",
         )
         .with_bad_example(include_str!("../examples/bad.conf").trim())
         .with_good_example(include_str!("../examples/good.conf").trim())
         .with_references(vec![
             "https://nginx.org/en/docs/http/ngx_http_gzip_module.html".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_gzip_module.html#gzip_types".to_string(),
+            "https://github.com/google/ngx_brotli".to_string(),
             "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/best_practices/gzip_not_enabled/tests/container_test.rs".to_string(),
         ])
+        .with_option(RuleOption::bool(
+            "check_gzip_types",
+            true,
+            "Also report gzip_types that is missing or omits common text types",
+        ))
+    }
+
+    fn configure(&mut self, options: &RuleOptions) {
+        self.check_gzip_types = options.bool("check_gzip_types");
     }
 
     fn relevant_directives(&self) -> Option<&'static [&'static str]> {
-        Some(&["http", "gzip"])
+        Some(&["http", "gzip", "gzip_types", "brotli"])
     }
 
     fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
-        let mut gzip_on = false;
-        let mut has_http_block = false;
+        let mut http_block = None;
+        let mut compressed = false;
+        let mut gzip_on = None;
+        let mut gzip_types = Vec::new();
 
         for ctx in config.all_directives_with_context() {
             // Track if we have an http block in THIS file
-            if ctx.directive.is("http") {
-                has_http_block = true;
+            if ctx.directive.is("http") && http_block.is_none() {
+                http_block = Some(ctx.directive);
             }
 
             // Only check gzip in http context (http, server, location)
@@ -59,23 +136,82 @@ impl Plugin for GzipNotEnabledPlugin {
             }
 
             if ctx.directive.is("gzip") && ctx.directive.first_arg_is("on") {
-                gzip_on = true;
-                break;
+                compressed = true;
+                gzip_on.get_or_insert(ctx.directive);
+            } else if ctx.directive.is("brotli") && ctx.directive.first_arg_is("on") {
+                compressed = true;
+            } else if ctx.directive.is("gzip_types") {
+                gzip_types.push(ctx.directive);
             }
         }
 
-        // Only warn if THIS file has an http block but no gzip on
+        let err = self.spec().error_builder();
+        let mut errors = Vec::new();
+
+        // Only warn if THIS file has an http block but no compression
         // Don't warn for included files - gzip should be set in the main config
-        if has_http_block && !gzip_on {
-            let err = self.spec().error_builder();
-            vec![err.warning(
+        if let Some(http) = http_block
+            && !compressed
+        {
+            let mut error = err.warning_at(
                 "Consider enabling gzip compression for better performance",
-                0,
-                0,
-            )]
-        } else {
-            vec![]
+                http,
+            );
+            if let Some(fix) = Self::enable_fix(config, http) {
+                error = error.with_fix(fix);
+            }
+            errors.push(error);
+            return errors;
+        }
+
+        if !self.check_gzip_types {
+            return errors;
+        }
+
+        // In an included file, gzip_types may be set by the including file
+        if let Some(gzip) = gzip_on
+            && gzip_types.is_empty()
+            && http_block.is_some()
+        {
+            errors.push(
+                err.warning_at(
+                    "gzip is enabled without gzip_types, so only text/html responses are \
+                     compressed; add gzip_types for other text types",
+                    gzip,
+                )
+                .with_fix(gzip.insert_after(&recommended_gzip_types())),
+            );
         }
+
+        if gzip_on.is_some() || !config.include_context.is_empty() {
+            for directive in gzip_types {
+                let missing = missing_types(directive);
+                let Some(last) = directive.args.last() else {
+                    continue;
+                };
+                if missing.is_empty() {
+                    continue;
+                }
+                let end = last.span.end.offset;
+                errors.push(
+                    err.warning_at(
+                        &format!(
+                            "gzip_types does not include {}, so those responses are sent \
+                             uncompressed",
+                            missing.join(", ")
+                        ),
+                        directive,
+                    )
+                    .with_fix(Fix::replace_range(
+                        end,
+                        end,
+                        &format!(" {}", missing.join(" ")),
+                    )),
+                );
+            }
+        }
+
+        errors
     }
 }
 
@@ -88,7 +224,7 @@ mod tests {
 
     #[test]
     fn test_no_gzip_directive() {
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
 
         runner.assert_has_errors(
             r#"
@@ -103,12 +239,13 @@ http {
 
     #[test]
     fn test_gzip_on() {
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
 
         runner.assert_no_errors(
             r#"
 http {
     gzip on;
+    gzip_types text/plain text/css application/json application/javascript;
     server {
         listen 80;
     }
@@ -119,7 +256,7 @@ http {
 
     #[test]
     fn test_gzip_off() {
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
 
         runner.assert_has_errors(
             r#"
@@ -135,7 +272,7 @@ http {
 
     #[test]
     fn test_gzip_in_server_block() {
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
 
         runner.assert_no_errors(
             r#"
@@ -143,25 +280,99 @@ http {
     server {
         listen 80;
         gzip on;
+        gzip_types text/plain text/css application/json application/javascript;
+    }
+}
+"#,
+        );
     }
+
+    #[test]
+    fn test_brotli_counts_as_compression() {
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
+
+        runner.assert_no_errors(
+            r#"
+http {
+    brotli on;
+    brotli_types text/css application/javascript;
 }
 "#,
         );
     }
 
     #[test]
-    fn test_examples() {
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
-        runner.test_examples(
+    fn test_gzip_without_gzip_types() {
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
+
+        runner.test_examples_with_fix(
+            "http {\n    gzip on;\n}\n",
+            "http {\n    gzip on;\n    gzip_types text/plain text/css application/json application/javascript;\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_gzip_types_missing_text_types() {
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    gzip on;
+    gzip_types text/plain text/javascript;
+    server {
+        gzip_types *;
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(4));
+        assert_eq!(
+            errors[0].message,
+            "gzip_types does not include text/css, application/json, so those responses are \
+             sent uncompressed"
+        );
+        assert_eq!(errors[0].fixes[0].new_text, " text/css application/json");
+    }
+
+    #[test]
+    fn test_examples_with_fix() {
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
+        runner.test_examples_with_fix(
             include_str!("../examples/bad.conf"),
             include_str!("../examples/good.conf"),
         );
     }
 
+    #[test]
+    fn test_enable_fix_keeps_tab_indent() {
+        let config =
+            nginx_lint_plugin::parse_string("http {\n\tserver {\n\t\tlisten 80;\n\t}\n}\n")
+                .unwrap();
+        let errors = GzipNotEnabledPlugin::default().check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(
+            errors[0].fixes[0]
+                .new_text
+                .starts_with("\tgzip on;\n\tgzip_types "),
+            "got: {:?}",
+            errors[0].fixes[0].new_text
+        );
+
+        let config =
+            nginx_lint_plugin::parse_string("events {\n\tworker_connections 64;\n}\nhttp {\n}\n")
+                .unwrap();
+        let errors = GzipNotEnabledPlugin::default().check(&config, "test.conf");
+        assert!(errors[0].fixes[0].new_text.starts_with("\n\tgzip on;"));
+    }
+
     #[test]
     fn test_no_http_context_no_warning() {
         // Config without http block should not warn about gzip
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
 
         runner.assert_no_errors(
             r#"
@@ -175,7 +386,7 @@ events {
     #[test]
     fn test_stream_context_no_warning() {
         // stream context doesn't support gzip, so no warning
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
 
         runner.assert_no_errors(
             r#"
@@ -191,12 +402,13 @@ stream {
     #[test]
     fn test_http_and_stream_mixed() {
         // Only http context should be checked
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
 
         runner.assert_no_errors(
             r#"
 http {
     gzip on;
+    gzip_types text/plain text/css application/json application/javascript;
     server {
         listen 80;
     }
@@ -213,7 +425,7 @@ stream {
     #[test]
     fn test_http_and_stream_mixed_warns_for_http() {
         // Should warn only about http context, not stream
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
 
         runner.assert_has_errors(
             r#"
@@ -247,9 +459,9 @@ server {
         .unwrap();
 
         // Simulate being included from http context
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
-        let plugin = GzipNotEnabledPlugin;
+        let plugin = GzipNotEnabledPlugin::default();
         let errors = plugin.check(&config, "test.conf");
 
         // Should NOT warn - parent config should set gzip
@@ -276,9 +488,9 @@ server {
         .unwrap();
 
         // Simulate being included from http context
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
-        let plugin = GzipNotEnabledPlugin;
+        let plugin = GzipNotEnabledPlugin::default();
         let errors = plugin.check(&config, "test.conf");
 
         assert!(errors.is_empty(), "Expected no errors, got: {:?}", errors);
@@ -299,9 +511,9 @@ server {
         .unwrap();
 
         // Simulate being included from stream context
-        config.include_context = vec!["stream".to_string()];
+        config.include_context = vec!["stream".to_string()].into();
 
-        let plugin = GzipNotEnabledPlugin;
+        let plugin = GzipNotEnabledPlugin::default();
         let errors = plugin.check(&config, "test.conf");
 
         assert!(
@@ -311,9 +523,27 @@ server {
         );
     }
 
+    #[test]
+    fn test_check_gzip_types_option() {
+        use std::collections::HashMap;
+
+        let config = "http {\n    gzip on;\n    gzip_types text/css;\n}\n";
+        let mut plugin = GzipNotEnabledPlugin::default();
+        let configured = HashMap::from([("check_gzip_types".to_string(), false.into())]);
+        plugin.configure(&RuleOptions::resolve(&plugin.spec().options, &configured));
+
+        PluginTestRunner::new(plugin).assert_no_errors(config);
+        PluginTestRunner::new(GzipNotEnabledPlugin::default()).assert_errors(config, 1);
+
+        // Compression itself is still required
+        let mut plugin = GzipNotEnabledPlugin::default();
+        plugin.configure(&RuleOptions::resolve(&plugin.spec().options, &configured));
+        PluginTestRunner::new(plugin).assert_errors("http {\n    server {}\n}\n", 1);
+    }
+
     #[test]
     fn test_fixtures() {
-        let runner = PluginTestRunner::new(GzipNotEnabledPlugin);
+        let runner = PluginTestRunner::new(GzipNotEnabledPlugin::default());
         runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
     }
 }
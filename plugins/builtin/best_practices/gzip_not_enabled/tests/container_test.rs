@@ -178,3 +178,44 @@ http {
         content_encoding
     );
 }
+
+#[tokio::test]
+#[ignore]
+async fn gzip_on_without_gzip_types_does_not_compress_css() {
+    let nginx = NginxContainer::start(br#"
+events {
+    worker_connections 1024;
+}
+http {
+    gzip on;
+    gzip_min_length 20;
+
+    server {
+        listen 80;
+        default_type text/css;
+
+        location / {
+            return 200 'AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA';
+        }
+    }
+}
+"#).await;
+
+    let resp = http_client()
+        .get(nginx.url("/"))
+        .header("Accept-Encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+
+    let content_encoding = resp
+        .headers()
+        .get("content-encoding")
+        .map(|v| v.to_str().unwrap().to_string());
+
+    assert!(
+        content_encoding.is_none(),
+        "Expected text/css to stay uncompressed without gzip_types (default is text/html only), got: {:?}",
+        content_encoding
+    );
+}
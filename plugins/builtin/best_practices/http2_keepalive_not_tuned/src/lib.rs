@@ -0,0 +1,461 @@
+//! http2-keepalive-not-tuned plugin
+//!
+//! This advisory plugin reports HTTP/2 and gRPC servers that keep nginx's
+//! connection limits for HTTP/1.1 clients.
+//!
+//! Since nginx 1.19.7 `keepalive_requests` also limits the requests on an
+//! HTTP/2 connection, and `keepalive_time` its lifetime. When a limit is
+//! reached nginx sends GOAWAY and the client has to reconnect; with the
+//! default of 1000 requests a busy gRPC client multiplexing all its calls
+//! over one connection reconnects every few seconds, and many clients doing
+//! so at once cause GOAWAY storms.
+//!
+//! The limits are checked where HTTP/2 connections use them: the `http`
+//! and `server` levels (location-level values only affect HTTP/1.1).
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedSettings};
+use nginx_lint_plugin::prelude::*;
+
+/// nginx's default `keepalive_requests`
+const DEFAULT_KEEPALIVE_REQUESTS: i64 = 1000;
+
+/// nginx's default `keepalive_time`
+const DEFAULT_KEEPALIVE_TIME: &str = "1h";
+
+/// Check HTTP/2 and gRPC servers for untuned keepalive limits
+pub struct Http2KeepaliveNotTunedPlugin {
+    /// Smallest acceptable `keepalive_requests` (option
+    /// `min_keepalive_requests`); also the value the fix sets
+    min_requests: i64,
+    /// Shortest acceptable `keepalive_time` (option `min_keepalive_time`)
+    min_time: String,
+}
+
+impl Default for Http2KeepaliveNotTunedPlugin {
+    fn default() -> Self {
+        Self {
+            min_requests: 10000,
+            min_time: DEFAULT_KEEPALIVE_TIME.to_string(),
+        }
+    }
+}
+
+/// The limits and `http2` in effect in a block
+type Settings<'a> = InheritedSettings<'a, 3>;
+
+/// The directives followed through the blocks
+const SETTINGS: [&str; 3] = ["keepalive_requests", "keepalive_time", "http2"];
+
+/// Why a server is considered to serve HTTP/2 clients, for messages;
+/// `settings` are the ones in effect in it
+fn http2_reason(block: &Block, settings: &Settings) -> Option<String> {
+    if let Some(listen) = block
+        .directives()
+        .find(|d| d.is("listen") && d.has_arg("http2"))
+    {
+        return Some(format!("listen ... http2 at line {}", listen.line()));
+    }
+    if let Inherited::Set(http2) = settings.get("http2")
+        && http2.first_arg_is("on")
+    {
+        return Some(format!("http2 on at line {}", http2.line()));
+    }
+    find_grpc_pass(&block.items).map(|grpc| format!("grpc_pass at line {}", grpc.line()))
+}
+
+/// The first `grpc_pass` in `items` or the blocks nested in them
+fn find_grpc_pass(items: &[ConfigItem]) -> Option<&Directive> {
+    items.iter().find_map(|item| match item {
+        ConfigItem::Directive(d) if d.is("grpc_pass") => Some(d.as_ref()),
+        ConfigItem::Directive(d) => d.block.as_ref().and_then(|b| find_grpc_pass(&b.items)),
+        _ => None,
+    })
+}
+
+impl Http2KeepaliveNotTunedPlugin {
+    /// Check the servers directly in `items` (an `http` block, or the top
+    /// level of a file included in `http`)
+    fn check_servers<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: Settings<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let http = inherited.enter(items);
+        for item in items {
+            let ConfigItem::Directive(server) = item else {
+                continue;
+            };
+            let (true, Some(block)) = (server.is("server"), &server.block) else {
+                continue;
+            };
+            let settings = http.enter(&block.items);
+            let Some(reason) = http2_reason(block, &settings) else {
+                continue;
+            };
+            self.check_requests(server, settings.get("keepalive_requests"), &reason, errors);
+            self.check_time(server, settings.get("keepalive_time"), &reason, errors);
+        }
+    }
+
+    fn check_requests(
+        &self,
+        server: &Directive,
+        requests: Inherited<'_>,
+        reason: &str,
+        errors: &mut Vec<LintError>,
+    ) {
+        let count = match requests {
+            Inherited::Default => DEFAULT_KEEPALIVE_REQUESTS,
+            Inherited::Set(d) => match d.first_arg().and_then(|v| v.parse().ok()) {
+                Some(count) => count,
+                None => return,
+            },
+            Inherited::Unknown => return,
+        };
+        if count >= self.min_requests {
+            return;
+        }
+
+        let err = self.spec().error_builder();
+        let message = format!(
+            "keepalive_requests is {} ({}) on a server handling HTTP/2 ({}); nginx closes each \
+             connection with GOAWAY after that many requests, so busy gRPC clients reconnect \
+             constantly; consider keepalive_requests {}",
+            count,
+            requests.origin(),
+            reason,
+            self.min_requests
+        );
+        let setting = format!("keepalive_requests {};", self.min_requests);
+        let error = match requests {
+            // Set in this server: report and fix it there
+            Inherited::Set(directive) if is_in(server, directive) => err
+                .warning_at(&message, directive)
+                .with_fix(directive.replace_with(&setting)),
+            _ => {
+                let error = err.warning_at(&message, server);
+                match insert_fix(server, &setting) {
+                    Some(fix) => error.with_fix(fix),
+                    None => error,
+                }
+            }
+        };
+        errors.push(error);
+    }
+
+    fn check_time(
+        &self,
+        server: &Directive,
+        time: Inherited<'_>,
+        reason: &str,
+        errors: &mut Vec<LintError>,
+    ) {
+        let Some(min_ms) = helpers::parse_time_ms(&self.min_time) else {
+            return;
+        };
+        let Some(value) = time.value(DEFAULT_KEEPALIVE_TIME) else {
+            return;
+        };
+        if helpers::parse_time_ms(value).is_none_or(|ms| ms >= min_ms) {
+            return;
+        }
+
+        let err = self.spec().error_builder();
+        let message = format!(
+            "keepalive_time is {} ({}) on a server handling HTTP/2 ({}); nginx closes \
+             long-lived connections with GOAWAY after that time; consider keepalive_time {}",
+            value,
+            time.origin(),
+            reason,
+            self.min_time
+        );
+        let at = match time {
+            Inherited::Set(directive) if is_in(server, directive) => directive,
+            _ => server,
+        };
+        errors.push(err.warning_at(&message, at));
+    }
+}
+
+/// Whether `directive` is directly in `server`'s block
+fn is_in(server: &Directive, directive: &Directive) -> bool {
+    server
+        .block
+        .as_ref()
+        .is_some_and(|block| block.directives().any(|d| std::ptr::eq(d, directive)))
+}
+
+/// Fix adding `setting` to a server block, after its last `listen`
+fn insert_fix(server: &Directive, setting: &str) -> Option<Fix> {
+    let block = server.block.as_ref()?;
+    let anchor = block
+        .directives()
+        .filter(|d| d.is("listen"))
+        .last()
+        .or_else(|| block.directives().next())?;
+    Some(anchor.insert_after(setting))
+}
+
+impl Plugin for Http2KeepaliveNotTunedPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "http2-keepalive-not-tuned",
+            "best-practices",
+            "Suggests raising keepalive_requests and keepalive_time on HTTP/2 and gRPC servers",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Strict)
+        .with_why(
+            "Since nginx 1.19.7, `keepalive_requests` also limits how many requests an HTTP/2 \
+             connection may carry, and `keepalive_time` (1.19.10) how long it may stay open. \
+             When a limit is reached nginx sends GOAWAY and the client must open a new \
+             connection.\n\n\
+             The defaults (1000 requests, 1 hour) suit HTTP/1.1 browsers. A gRPC client sends \
+             all its calls over one connection, so at a few hundred calls per second it hits \
+             1000 requests every few seconds. The reconnects add latency, can fail in-flight \
+             calls in clients that handle GOAWAY poorly, and many clients reconnecting at once \
+             cause GOAWAY storms.\n\n\
+             Raise `keepalive_requests` (and keep `keepalive_time` long) in the http or server \
+             block of HTTP/2 and gRPC servers. The thresholds are configurable with the \
+             `min_keepalive_requests` and `min_keepalive_time` options.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#keepalive_requests"
+                .to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#keepalive_time".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_v2_module.html".to_string(),
+        ])
+        .with_option(RuleOption::integer(
+            "min_keepalive_requests",
+            10000,
+            "Smallest acceptable keepalive_requests on HTTP/2 servers (also the value the fix sets)",
+        ))
+        .with_option(RuleOption::string(
+            "min_keepalive_time",
+            DEFAULT_KEEPALIVE_TIME,
+            "Shortest acceptable keepalive_time on HTTP/2 servers (nginx time syntax)",
+        ))
+    }
+
+    fn configure(&mut self, options: &RuleOptions) {
+        self.min_requests = options.integer("min_keepalive_requests");
+        self.min_time = options.string("min_keepalive_time").to_string();
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["server"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let context = &config.include_context;
+
+        if context.contains("server") {
+            // The server block, and the limits, are in the including file
+        } else if context.contains("http") {
+            let root = Settings::new(SETTINGS, Inherited::Unknown);
+            self.check_servers(&config.items, root, &mut errors);
+        } else {
+            for http in config.directives().filter(|d| d.is("http")) {
+                if let Some(block) = &http.block {
+                    let root = Settings::new(SETTINGS, Inherited::Default);
+                    self.check_servers(&block.items, root, &mut errors);
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(Http2KeepaliveNotTunedPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn messages(plugin: Http2KeepaliveNotTunedPlugin, config: &str) -> Vec<(usize, String)> {
+        PluginTestRunner::new(plugin)
+            .check_string(config)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.line.unwrap(), e.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_keepalive_requests_on_grpc_server() {
+        let errors = messages(
+            Http2KeepaliveNotTunedPlugin::default(),
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        http2 on;
+
+        location / {
+            grpc_pass grpc://backend;
+        }
+    }
+}
+"#,
+        );
+        assert_eq!(
+            errors,
+            [(
+                3,
+                "keepalive_requests is 1000 (nginx's default) on a server handling HTTP/2 \
+                 (http2 on at line 5); nginx closes each connection with GOAWAY after that many \
+                 requests, so busy gRPC clients reconnect constantly; consider \
+                 keepalive_requests 10000"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_http2_detection() {
+        let runner = PluginTestRunner::new(Http2KeepaliveNotTunedPlugin::default());
+        runner.assert_has_errors("http {\n    server {\n        listen 443 ssl http2;\n    }\n}\n");
+        runner.assert_has_errors(
+            "http {\n    server {\n        listen 80;\n        location / {\n            grpc_pass grpc://b;\n        }\n    }\n}\n",
+        );
+        // http2 on inherited from http
+        runner.assert_has_errors(
+            "http {\n    http2 on;\n    server {\n        listen 443 ssl;\n    }\n}\n",
+        );
+        // Plain HTTP/1.1 server
+        runner.assert_no_errors("http {\n    server {\n        listen 80;\n    }\n}\n");
+        runner.assert_no_errors(
+            "http {\n    http2 on;\n    server {\n        listen 443 ssl;\n        http2 off;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_tuned_values() {
+        let runner = PluginTestRunner::new(Http2KeepaliveNotTunedPlugin::default());
+        runner.assert_no_errors(
+            r#"
+http {
+    keepalive_requests 100000;
+
+    server {
+        listen 443 ssl http2;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_location_level_value_is_ignored() {
+        // HTTP/2 connections use the server-level value
+        let runner = PluginTestRunner::new(Http2KeepaliveNotTunedPlugin::default());
+        runner.assert_error_on_line(
+            r#"
+http {
+    server {
+        listen 443 ssl http2;
+
+        location / {
+            keepalive_requests 100000;
+        }
+    }
+}
+"#,
+            3,
+        );
+    }
+
+    #[test]
+    fn test_low_keepalive_time() {
+        let errors = messages(
+            Http2KeepaliveNotTunedPlugin::default(),
+            r#"
+http {
+    keepalive_requests 100000;
+
+    server {
+        listen 443 ssl http2;
+        keepalive_time 10m;
+    }
+}
+"#,
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 7);
+        assert!(errors[0].1.starts_with("keepalive_time is 10m (line 7)"));
+    }
+
+    #[test]
+    fn test_configured_thresholds() {
+        use std::collections::HashMap;
+
+        let mut plugin = Http2KeepaliveNotTunedPlugin::default();
+        let configured = HashMap::from([
+            ("min_keepalive_requests".to_string(), 500.into()),
+            ("min_keepalive_time".to_string(), "2h".into()),
+        ]);
+        plugin.configure(&RuleOptions::resolve(&plugin.spec().options, &configured));
+        let errors = messages(
+            plugin,
+            "http {\n    server {\n        listen 443 ssl http2;\n    }\n}\n",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .1
+                .starts_with("keepalive_time is 1h (nginx's default)")
+        );
+    }
+
+    #[test]
+    fn test_included_file_with_unknown_limits() {
+        let mut config =
+            nginx_lint_plugin::parse_string("server {\n    listen 443 ssl http2;\n}\n").unwrap();
+        config.include_context = IncludeContext::parse("http");
+        let errors = Http2KeepaliveNotTunedPlugin::default().check(&config, "test.conf");
+        assert!(errors.is_empty());
+
+        let mut config = nginx_lint_plugin::parse_string(
+            "server {\n    listen 443 ssl http2;\n    keepalive_requests 100;\n}\n",
+        )
+        .unwrap();
+        config.include_context = IncludeContext::parse("http");
+        let errors = Http2KeepaliveNotTunedPlugin::default().check(&config, "test.conf");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_fix_replaces_value_set_in_server() {
+        let runner = PluginTestRunner::new(Http2KeepaliveNotTunedPlugin::default());
+        runner.assert_fix_produces(
+            "http {\n    server {\n        listen 443 ssl http2;\n        keepalive_requests 1000;\n    }\n}\n",
+            "http {\n    server {\n        listen 443 ssl http2;\n        keepalive_requests 10000;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(Http2KeepaliveNotTunedPlugin::default());
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(Http2KeepaliveNotTunedPlugin::default());
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,307 @@
+//! if-host-comparison plugin
+//!
+//! This plugin reports `if` blocks that compare `$host` or `$http_host`
+//! with `=` or `!=`, the most common use of `if`. nginx selects the server
+//! block by host name already, so the comparison belongs in `server_name`;
+//! values that differ per host belong in a `map`.
+//!
+//! The message suggests the refactoring that fits the block's body: a
+//! server block for redirects, a `map` for `set`.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+
+/// Variables holding the requested host name
+const HOST_VARIABLES: &[&str] = &["$host", "$http_host"];
+
+/// Check `if` blocks comparing the host name
+#[derive(Default)]
+pub struct IfHostComparisonPlugin;
+
+/// The words of the `if` condition, without the surrounding parentheses
+fn condition_words(if_directive: &Directive) -> Vec<String> {
+    let mut words = if_directive.words();
+    if let Some(first) = words.first_mut() {
+        *first = first.trim_start_matches('(').to_string();
+    }
+    if let Some(last) = words.last_mut() {
+        *last = last.trim_end_matches(')').to_string();
+    }
+    words.retain(|w| !w.is_empty());
+    words
+}
+
+/// Whether `directive` answers with a redirect or a fixed response
+fn is_redirect(directive: &Directive) -> bool {
+    directive.is("return")
+        || directive.is("rewrite") && matches!(directive.last_arg(), Some("permanent" | "redirect"))
+}
+
+impl IfHostComparisonPlugin {
+    fn check_if(&self, if_directive: &Directive, block: &Block, errors: &mut Vec<LintError>) {
+        let condition = condition_words(if_directive);
+        let [variable, operator, value] = condition.as_slice() else {
+            return;
+        };
+        if !HOST_VARIABLES.contains(&variable.as_str()) || !matches!(operator.as_str(), "=" | "!=")
+        {
+            return;
+        }
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        let condition = format!("if ({} {} {})", variable, operator, value);
+
+        let body: Vec<&Directive> = block.directives().collect();
+        let suggestion = if !body.is_empty() && body.iter().all(|d| is_redirect(d)) {
+            if operator == "=" {
+                format!(
+                    "move the redirect to its own block: server {{ server_name {}; {} }}",
+                    value,
+                    body.iter()
+                        .map(|d| format!("{} {};", d.name, d.words().join(" ")))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                )
+            } else {
+                format!(
+                    "serve {} from this server only and redirect other hosts from a \
+                     default_server block",
+                    value
+                )
+            }
+        } else if !body.is_empty() && body.iter().all(|d| d.is("set")) {
+            let maps: Vec<String> = body
+                .iter()
+                .filter_map(|d| {
+                    let set = d.words();
+                    let target = set.first()?;
+                    let set_value = set.get(1).map_or("\"\"", String::as_str);
+                    let entry = if operator == "=" {
+                        format!("{} {};", value, set_value)
+                    } else {
+                        format!("{} \"\"; default {};", value, set_value)
+                    };
+                    Some(format!("map {} {} {{ {} }}", variable, target, entry))
+                })
+                .collect();
+            format!("use {} in http instead", maps.join(" and "))
+        } else {
+            format!(
+                "use a server block with server_name {}, or a map on {}",
+                value, variable
+            )
+        };
+
+        let message = format!(
+            "{} duplicates nginx's server selection; {}",
+            condition, suggestion
+        );
+        errors.push(
+            self.spec()
+                .error_builder()
+                .warning_at(&message, if_directive),
+        );
+    }
+
+    fn check_items(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            let Some(block) = &directive.block else {
+                continue;
+            };
+            if directive.is("if") {
+                self.check_if(directive, block, errors);
+            }
+            self.check_items(&block.items, errors);
+        }
+    }
+}
+
+impl Plugin for IfHostComparisonPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "if-host-comparison",
+            "best-practices",
+            "Suggests server blocks or map instead of if comparing $host or $http_host",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "`if ($host = www.example.com)` re-does the work nginx already did when it chose \
+             the server block by `server_name`, and it does so on every request. It also \
+             brings the problems of `if` (see `if-is-evil-in-location`) into configurations \
+             that need none of them.\n\n\
+             A redirect for one host name belongs in a `server` block of its own with that \
+             `server_name`. Values that differ per host belong in a `map` on `$host`, which \
+             is evaluated only when the variable is used and scales to any number of \
+             hosts.\n\n\
+             The message spells out the replacement for the `if` block at hand.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/converting_rewrite_rules.html".to_string(),
+            "https://nginx.org/en/docs/http/server_names.html".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_map_module.html".to_string(),
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        self.check_items(&config.items, &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(IfHostComparisonPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn messages(config: &str) -> Vec<String> {
+        PluginTestRunner::new(IfHostComparisonPlugin)
+            .check_string(config)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_redirect() {
+        let errors = messages(
+            r#"
+http {
+    server {
+        server_name example.com www.example.com;
+
+        if ($host = www.example.com) {
+            return 301 https://example.com$request_uri;
+        }
+    }
+}
+"#,
+        );
+        assert_eq!(
+            errors,
+            [
+                "if ($host = www.example.com) duplicates nginx's server selection; move the \
+              redirect to its own block: server { server_name www.example.com; return 301 \
+              https://example.com$request_uri; }"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_negated_redirect() {
+        let errors = messages(
+            r#"
+http {
+    server {
+        if ($http_host != "example.com") {
+            rewrite ^ https://example.com$request_uri? permanent;
+        }
+    }
+}
+"#,
+        );
+        assert_eq!(
+            errors,
+            [
+                "if ($http_host != example.com) duplicates nginx's server selection; serve \
+              example.com from this server only and redirect other hosts from a default_server \
+              block"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set() {
+        let errors = messages(
+            r#"
+http {
+    server {
+        if ($host = 'api.example.com') {
+            set $backend api;
+        }
+    }
+}
+"#,
+        );
+        assert_eq!(
+            errors,
+            [
+                "if ($host = api.example.com) duplicates nginx's server selection; use map $host \
+              $backend { api.example.com api; } in http instead"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_body() {
+        let errors = messages(
+            r#"
+http {
+    server {
+        location / {
+            if ($host = static.example.com) {
+                root /srv/static;
+            }
+        }
+    }
+}
+"#,
+        );
+        assert_eq!(
+            errors,
+            [
+                "if ($host = static.example.com) duplicates nginx's server selection; use a server \
+              block with server_name static.example.com, or a map on $host"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_conditions() {
+        let runner = PluginTestRunner::new(IfHostComparisonPlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        if ($host ~* ^www\.) {
+            return 301 https://example.com$request_uri;
+        }
+        if ($request_method = POST) {
+            return 405;
+        }
+        if ($http_x_forwarded_host = example.com) {
+            set $forwarded 1;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(IfHostComparisonPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(IfHostComparisonPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -112,6 +112,7 @@ impl Plugin for IfIsEvilInLocationPlugin {
             "Warns when 'if' blocks in location context contain unsafe directives",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "The 'if' directive in nginx has unexpected behavior when used with most directives \
              inside location blocks. This is a well-known issue documented in the nginx wiki \
@@ -930,7 +931,8 @@ if ($slow) {
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = IfIsEvilInLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -957,7 +959,7 @@ if ($slow) {
         .unwrap();
 
         // Simulate being included from http > server context (not location)
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = IfIsEvilInLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -986,7 +988,8 @@ if ($mobile) {
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = IfIsEvilInLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
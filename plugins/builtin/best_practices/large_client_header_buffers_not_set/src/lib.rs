@@ -0,0 +1,352 @@
+//! large-client-header-buffers-not-set plugin
+//!
+//! This plugin warns when an API-style server (one that forwards the client's
+//! `Authorization` header to a backend) relies on the default
+//! `large_client_header_buffers 4 8k`.
+//!
+//! A single request header line (or the request line) longer than one buffer is
+//! rejected by nginx with 400 (Request Header Or Cookie Too Large) or 414
+//! (Request-URI Too Large). Bearer tokens such as JWTs with many claims, and
+//! the cookies that often travel with them, can exceed 8k.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+
+/// Directives that pass request headers on to a backend
+const FORWARDING_DIRECTIVES: &[&str] = &[
+    "proxy_set_header",
+    "grpc_set_header",
+    "fastcgi_param",
+    "uwsgi_param",
+    "scgi_param",
+];
+
+/// Check for Authorization-forwarding servers using default header buffers
+#[derive(Default)]
+pub struct LargeClientHeaderBuffersNotSetPlugin;
+
+impl LargeClientHeaderBuffersNotSetPlugin {
+    /// Check if a directive forwards the client's Authorization header
+    ///
+    /// Matches `proxy_set_header Authorization ...` (and the `*_param
+    /// HTTP_AUTHORIZATION` form) unless it clears the header with `""`, and
+    /// any forwarding directive whose value uses `$http_authorization`.
+    fn forwards_authorization(directive: &Directive) -> bool {
+        if !FORWARDING_DIRECTIVES.contains(&directive.name.as_str()) {
+            return false;
+        }
+
+        let names_header = directive.first_arg().is_some_and(|name| {
+            name.eq_ignore_ascii_case("authorization")
+                || name.eq_ignore_ascii_case("http_authorization")
+        });
+        let clears_header = directive.arg_at(1).is_some_and(str::is_empty);
+        let uses_variable = directive
+            .args
+            .iter()
+            .skip(1)
+            .any(|arg| arg.raw.to_ascii_lowercase().contains("$http_authorization"));
+
+        (names_header && !clears_header) || uses_variable
+    }
+
+    /// Check if any directive in a block (recursively) forwards Authorization
+    fn block_forwards_authorization(block: &Block) -> bool {
+        block.directives().any(|directive| {
+            Self::forwards_authorization(directive)
+                || directive
+                    .block
+                    .as_ref()
+                    .is_some_and(Self::block_forwards_authorization)
+        })
+    }
+
+    /// Check if a block sets large_client_header_buffers directly
+    fn has_buffers(block: &Block) -> bool {
+        block
+            .directives()
+            .any(|directive| directive.is("large_client_header_buffers"))
+    }
+}
+
+impl Plugin for LargeClientHeaderBuffersNotSetPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "large-client-header-buffers-not-set",
+            "best-practices",
+            "Warns when a server forwarding Authorization headers uses the default large_client_header_buffers",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Strict)
+        .with_why(
+            "By default nginx reads long request headers into `large_client_header_buffers 4 8k`: \
+             any single header line longer than 8k is rejected with 400 (Request Header Or Cookie \
+             Too Large), and a request line longer than 8k with 414 (Request-URI Too Large).\n\n\
+             API servers that forward the client's `Authorization` header to a backend commonly \
+             receive bearer tokens such as JWTs. Tokens carrying many claims or roles, together \
+             with large session cookies, can exceed 8k, and the failure only shows up for the \
+             affected users. Setting the buffers explicitly (for example \
+             `large_client_header_buffers 4 32k;`) makes the limit a deliberate choice.\n\n\
+             The directive is honoured in the `http` block and in the default `server` of a \
+             listen socket, so setting it in `http` is the most reliable. Servers in files \
+             included from a parent config are not checked, because the parent's `http` block \
+             may already set it. This rule is advisory and disabled by default.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#large_client_header_buffers"
+                .to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/best_practices/large_client_header_buffers_not_set/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&[
+            "http",
+            "server",
+            "large_client_header_buffers",
+            "proxy_set_header",
+            "grpc_set_header",
+            "fastcgi_param",
+            "uwsgi_param",
+            "scgi_param",
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let err = self.spec().error_builder();
+        let mut errors = Vec::new();
+
+        // Only http blocks in THIS file: for included files the parent's
+        // http block may set the buffers
+        for http in config.all_directives().filter(|d| d.is("http")) {
+            let Some(http_block) = &http.block else {
+                continue;
+            };
+            if Self::has_buffers(http_block) {
+                continue;
+            }
+            let http_forwards = http_block.directives().any(Self::forwards_authorization);
+
+            for server in http_block.directives().filter(|d| d.is("server")) {
+                let Some(server_block) = &server.block else {
+                    continue;
+                };
+                if Self::has_buffers(server_block) {
+                    continue;
+                }
+                if http_forwards || Self::block_forwards_authorization(server_block) {
+                    errors.push(err.warning_at(
+                        "server forwards the Authorization header but large_client_header_buffers \
+                         is not set; the default (4 8k) rejects JWTs or cookies larger than 8k \
+                         with 400/414. Consider e.g. 'large_client_header_buffers 4 32k;'",
+                        server,
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(LargeClientHeaderBuffersNotSetPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_authorization_forwarding_without_buffers() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+
+        runner.assert_errors(
+            r#"
+http {
+    server {
+        listen 80;
+        location /api/ {
+            proxy_set_header Authorization $http_authorization;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            1,
+        );
+    }
+
+    #[test]
+    fn test_buffers_set_in_http() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    large_client_header_buffers 4 32k;
+    server {
+        listen 80;
+        location /api/ {
+            proxy_set_header Authorization $http_authorization;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_buffers_set_in_server() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 80;
+        large_client_header_buffers 4 16k;
+        proxy_set_header Authorization $http_authorization;
+        location / {
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_server_without_authorization_forwarding() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 80;
+        location / {
+            proxy_set_header Host $host;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_cleared_authorization_header() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 80;
+        location / {
+            proxy_set_header Authorization "";
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_http_authorization_variable_forms() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+
+        // FastCGI parameter, custom header name, and case-insensitive header name
+        runner.assert_errors(
+            r#"
+http {
+    server {
+        listen 80;
+        location ~ \.php$ {
+            fastcgi_param HTTP_AUTHORIZATION $http_authorization;
+            fastcgi_pass unix:/run/php.sock;
+        }
+    }
+    server {
+        listen 81;
+        location / {
+            proxy_set_header X-Upstream-Auth "Bearer $http_authorization";
+            proxy_pass http://backend;
+        }
+    }
+    server {
+        listen 82;
+        location / {
+            grpc_set_header authorization $http_x_token;
+            grpc_pass grpc://backend;
+        }
+    }
+}
+"#,
+            3,
+        );
+    }
+
+    #[test]
+    fn test_http_level_forwarding_applies_to_each_server() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+
+        runner.assert_errors(
+            r#"
+http {
+    proxy_set_header Authorization $http_authorization;
+    server {
+        listen 80;
+    }
+    server {
+        listen 81;
+        large_client_header_buffers 4 32k;
+    }
+}
+"#,
+            1,
+        );
+    }
+
+    #[test]
+    fn test_included_file_not_checked() {
+        use nginx_lint_plugin::parse_string;
+
+        let mut config = parse_string(
+            r#"
+server {
+    listen 80;
+    proxy_set_header Authorization $http_authorization;
+}
+"#,
+        )
+        .unwrap();
+        config.include_context = vec!["http".to_string()].into();
+
+        let errors = LargeClientHeaderBuffersNotSetPlugin.check(&config, "test.conf");
+        assert!(errors.is_empty(), "Expected no errors, got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(LargeClientHeaderBuffersNotSetPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,88 @@
+//! Container-based integration tests for the large-client-header-buffers-not-set rule.
+//!
+//! Verifies that with the default `large_client_header_buffers 4 8k`, nginx
+//! rejects a request whose Authorization header exceeds 8k, and that raising
+//! the buffer size lets it through.
+//!
+//! Run with:
+//!   cargo test -p large-client-header-buffers-not-set-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p large-client-header-buffers-not-set-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, reqwest};
+
+/// A bearer token header value of roughly the given size in bytes.
+fn make_bearer_token(size: usize) -> String {
+    format!("Bearer {}", "a".repeat(size))
+}
+
+/// With the default buffers, an Authorization header over 8k is rejected with 400.
+#[tokio::test]
+#[ignore]
+async fn default_buffers_reject_large_authorization_header() {
+    let nginx = NginxContainer::start(
+        br#"
+events {
+    worker_connections 1024;
+}
+http {
+    server {
+        listen 80;
+
+        location / {
+            return 200 'OK';
+        }
+    }
+}
+"#,
+    )
+    .await;
+
+    let resp = reqwest::Client::new()
+        .get(nginx.url("/"))
+        .header("Authorization", make_bearer_token(10 * 1024))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        resp.status(),
+        400,
+        "Expected 400 Request Header Or Cookie Too Large for an Authorization header over 8k"
+    );
+}
+
+/// With `large_client_header_buffers 4 32k`, the same request succeeds.
+#[tokio::test]
+#[ignore]
+async fn larger_buffers_accept_large_authorization_header() {
+    let nginx = NginxContainer::start(
+        br#"
+events {
+    worker_connections 1024;
+}
+http {
+    large_client_header_buffers 4 32k;
+
+    server {
+        listen 80;
+
+        location / {
+            return 200 'OK';
+        }
+    }
+}
+"#,
+    )
+    .await;
+
+    let resp = reqwest::Client::new()
+        .get(nginx.url("/"))
+        .header("Authorization", make_bearer_token(10 * 1024))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+}
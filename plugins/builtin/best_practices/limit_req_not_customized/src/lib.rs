@@ -0,0 +1,340 @@
+//! limit-req-not-customized plugin
+//!
+//! This advisory plugin reports `limit_req` used with nginx's defaults for
+//! rejected requests:
+//!
+//! - Rejections are answered with a bare 503 unless `limit_req_status`
+//!   sets another code or an `error_page` handles 503. Clients and
+//!   monitoring then cannot tell rate limiting from an outage.
+//! - Each rejection is logged at `error` level unless
+//!   `limit_req_log_level` is set, so a client hitting the limit floods
+//!   the error log.
+//!
+//! All three directives are inherited from `http` to `server` to
+//! `location`; `error_page` only when the inner level sets none.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedList, InheritedSettings};
+use nginx_lint_plugin::prelude::*;
+
+/// The status the fix sets
+const FIX_STATUS: &str = "limit_req_status 429;";
+
+/// Check `limit_req` for the default rejection status and log level
+#[derive(Default)]
+pub struct LimitReqNotCustomizedPlugin;
+
+/// `limit_req_status` and `limit_req_log_level` in effect in a block
+type Settings<'a> = InheritedSettings<'a, 2>;
+
+/// The directives followed through the blocks
+const SETTINGS: [&str; 2] = ["limit_req_status", "limit_req_log_level"];
+
+impl LimitReqNotCustomizedPlugin {
+    /// Walk `items` with the settings and `error_page` list inherited from
+    /// the enclosing blocks
+    fn check_items<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: Settings<'a>,
+        error_pages: InheritedList<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let settings = inherited.enter(items);
+        // error_page is inherited only by levels that set none
+        let error_pages = error_pages.enter(items, &["error_page"]);
+        // One report per block, however many zones it limits
+        if let Some(limit_req) = directives(items).find(|d| d.is("limit_req")) {
+            self.check_limit_req(limit_req, &settings, error_pages, errors);
+        }
+        for directive in directives(items) {
+            if let Some(block) = &directive.block {
+                self.check_items(&block.items, settings, error_pages, errors);
+            }
+        }
+    }
+
+    fn check_limit_req(
+        &self,
+        limit_req: &Directive,
+        settings: &Settings,
+        error_pages: InheritedList,
+        errors: &mut Vec<LintError>,
+    ) {
+        let err = self.spec().error_builder();
+
+        let status = match settings.get("limit_req_status") {
+            Inherited::Set(d) => d.first_arg().unwrap_or("503"),
+            Inherited::Default => "503",
+            Inherited::Unknown => "",
+        };
+        if status == "503" && !handles_503(error_pages) {
+            let message = "limit_req rejects excess requests with a bare 503, which clients and \
+                           monitoring cannot tell apart from an outage; set limit_req_status 429 \
+                           or handle 503 with error_page";
+            errors.push(
+                err.warning_at(message, limit_req)
+                    .with_fix(limit_req.insert_after(FIX_STATUS)),
+            );
+        }
+
+        if settings.get("limit_req_log_level").is_default() {
+            let message = "limit_req_log_level is not set, so every rejected request is logged \
+                           at error level; consider limit_req_log_level warn";
+            errors.push(err.warning_at(message, limit_req));
+        }
+    }
+}
+
+/// The directives directly in `items`
+fn directives(items: &[ConfigItem]) -> impl Iterator<Item = &Directive> {
+    items.iter().filter_map(|item| match item {
+        ConfigItem::Directive(d) => Some(d.as_ref()),
+        _ => None,
+    })
+}
+
+/// Whether the `error_page` directives in effect handle 503, or may do so
+/// in the including file
+fn handles_503(error_pages: InheritedList) -> bool {
+    error_pages.is_unknown()
+        || error_pages.directives().any(|page| {
+            let codes = &page.args[..page.args.len().saturating_sub(1)];
+            codes.iter().any(|code| code.as_str() == "503")
+        })
+}
+
+impl Plugin for LimitReqNotCustomizedPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "limit-req-not-customized",
+            "best-practices",
+            "Suggests setting limit_req_status and limit_req_log_level where limit_req is used",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Strict)
+        .with_why(
+            "By default, a request rejected by `limit_req` gets a 503 Service Unavailable \
+             response with nginx's stock error page. Clients retry it as if the server were \
+             down, and dashboards and alerts count it as an outage. `limit_req_status 429` \
+             (Too Many Requests) tells clients to back off and keeps rate limiting out of the \
+             5xx numbers; alternatively, handle 503 with an `error_page` that explains the \
+             limit.\n\n\
+             nginx also logs every rejection at `error` level. A single client hitting the \
+             limit can then fill the error log and bury real errors; \
+             `limit_req_log_level warn` (or lower) keeps the entries distinguishable.\n\n\
+             The fix adds `limit_req_status 429;` after `limit_req`. The rule is disabled by \
+             default.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_limit_req_module.html#limit_req_status"
+                .to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_limit_req_module.html#limit_req_log_level"
+                .to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&[
+            "limit_req",
+            "limit_req_status",
+            "limit_req_log_level",
+            "error_page",
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        self.check_items(
+            &config.items,
+            Settings::root(SETTINGS, config),
+            InheritedList::root(config),
+            &mut errors,
+        );
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(LimitReqNotCustomizedPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn messages(config: &str) -> Vec<(usize, String)> {
+        PluginTestRunner::new(LimitReqNotCustomizedPlugin)
+            .check_string(config)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.line.unwrap(), e.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let errors = messages(
+            r#"
+http {
+    limit_req_zone $binary_remote_addr zone=api:10m rate=10r/s;
+
+    server {
+        location /api/ {
+            limit_req zone=api burst=20;
+            limit_req zone=api_burst;
+        }
+    }
+}
+"#,
+        );
+        assert_eq!(
+            errors,
+            [
+                (
+                    7,
+                    "limit_req rejects excess requests with a bare 503, which clients and \
+                     monitoring cannot tell apart from an outage; set limit_req_status 429 or \
+                     handle 503 with error_page"
+                        .to_string()
+                ),
+                (
+                    7,
+                    "limit_req_log_level is not set, so every rejected request is logged at \
+                     error level; consider limit_req_log_level warn"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inherited_settings() {
+        let runner = PluginTestRunner::new(LimitReqNotCustomizedPlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    limit_req_status 429;
+    limit_req_log_level warn;
+
+    server {
+        limit_req zone=global;
+
+        location /api/ {
+            limit_req zone=api;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_status_503_set_explicitly() {
+        let runner = PluginTestRunner::new(LimitReqNotCustomizedPlugin);
+        runner.assert_errors(
+            "http {\n    limit_req_log_level notice;\n    limit_req_status 503;\n    limit_req zone=one;\n}\n",
+            1,
+        );
+    }
+
+    #[test]
+    fn test_error_page_503() {
+        let runner = PluginTestRunner::new(LimitReqNotCustomizedPlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    limit_req_log_level warn;
+
+    server {
+        error_page 502 503 504 /busy.html;
+
+        location / {
+            limit_req zone=one;
+        }
+    }
+}
+"#,
+        );
+        // A location setting its own error_page does not inherit the server's
+        runner.assert_error_on_line(
+            r#"
+http {
+    limit_req_log_level warn;
+
+    server {
+        error_page 503 /busy.html;
+
+        location / {
+            error_page 404 /missing.html;
+            limit_req zone=one;
+        }
+    }
+}
+"#,
+            10,
+        );
+    }
+
+    #[test]
+    fn test_included_file() {
+        let mut config = nginx_lint_plugin::parse_string("limit_req zone=one;\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server,location=/api/");
+        assert!(
+            LimitReqNotCustomizedPlugin
+                .check(&config, "test.conf")
+                .is_empty()
+        );
+
+        let mut config =
+            nginx_lint_plugin::parse_string("limit_req zone=one;\nlimit_req_status 503;\n")
+                .unwrap();
+        config.include_context = IncludeContext::parse("http,server,location=/api/");
+        assert!(
+            LimitReqNotCustomizedPlugin
+                .check(&config, "test.conf")
+                .is_empty()
+        );
+
+        let mut config = nginx_lint_plugin::parse_string(
+            "limit_req zone=one;\nlimit_req_status 503;\nerror_page 404 /404.html;\n",
+        )
+        .unwrap();
+        config.include_context = IncludeContext::parse("http,server,location=/api/");
+        assert_eq!(
+            LimitReqNotCustomizedPlugin
+                .check(&config, "test.conf")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_fix_inserts_status() {
+        let runner = PluginTestRunner::new(LimitReqNotCustomizedPlugin);
+        runner.assert_fix_produces(
+            "http {\n    limit_req_log_level warn;\n    server {\n        limit_req zone=one burst=5;\n    }\n}\n",
+            "http {\n    limit_req_log_level warn;\n    server {\n        limit_req zone=one burst=5;\n        limit_req_status 429;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(LimitReqNotCustomizedPlugin);
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(LimitReqNotCustomizedPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
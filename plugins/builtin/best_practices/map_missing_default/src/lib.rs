@@ -23,6 +23,7 @@ impl Plugin for MapMissingDefaultPlugin {
             "Warns when a map block does not have a default entry",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "Without a `default` entry in a `map` block, any value that doesn't match a listed \
              pattern will silently resolve to an empty string. This can lead to subtle bugs that \
@@ -23,6 +23,7 @@ impl Plugin for MissingErrorLogPlugin {
             "Suggests configuring error_log for debugging",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "Configuring error_log allows you to record errors and issues in log files for \
              troubleshooting purposes. Setting an appropriate log level helps capture necessary \
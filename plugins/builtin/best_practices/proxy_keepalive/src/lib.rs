@@ -153,6 +153,7 @@ impl Plugin for ProxyKeepalivePlugin {
             "Warns when proxy_http_version 1.1+ is set without proxy_set_header Connection",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "When using HTTP/1.1 or higher with upstream servers, the Connection header \
              should be cleared to enable keepalive connections. Without this, the default \
@@ -510,7 +511,8 @@ proxy_pass http://backend;
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = ProxyKeepalivePlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -536,7 +538,7 @@ proxy_http_version 1.1;
         .unwrap();
 
         // Simulate being included from http > server context
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = ProxyKeepalivePlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -566,7 +568,8 @@ proxy_pass http://backend;
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = ProxyKeepalivePlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -594,7 +597,8 @@ proxy_pass http://backend;
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = ProxyKeepalivePlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -144,6 +144,7 @@ impl Plugin for ProxyMissingHostHeaderPlugin {
             "Warns when proxy_pass is used without proxy_set_header Host",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "When using proxy_pass, the default Host header sent to the backend is \
              $proxy_host (the host and port from the proxy_pass URL). This breaks \
@@ -469,7 +470,8 @@ proxy_pass http://backend;
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = ProxyMissingHostHeaderPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -500,7 +502,8 @@ proxy_set_header Host $host;
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = ProxyMissingHostHeaderPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -25,6 +25,7 @@ impl Plugin for ProxyPassDomainPlugin {
             "Warns when proxy_pass uses a domain name directly without proper DNS handling",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "When proxy_pass specifies a domain name directly, nginx resolves \
              the DNS at startup and caches the IP address. If the IP address changes, nginx \
@@ -0,0 +1,313 @@
+//! proxy-pass-hardcoded-ip plugin
+//!
+//! This plugin flags `proxy_pass` directives that point at a literal IP
+//! address (e.g. `proxy_pass http://10.0.12.34:8080;`) instead of an
+//! `upstream` block or a DNS name. It is disabled by default: it enforces a
+//! service-discovery convention rather than catching a misconfiguration.
+//!
+//! Loopback addresses are allowed by default; the allowlist can be replaced
+//! with `allowed_addresses` (addresses or CIDR ranges) in the rule config.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+use std::net::IpAddr;
+
+/// Addresses allowed when no allowlist is configured
+pub const DEFAULT_ALLOWED_ADDRESSES: &[&str] = &["127.0.0.0/8", "::1"];
+
+/// Check for hardcoded backend IP addresses in proxy_pass
+pub struct ProxyPassHardcodedIpPlugin {
+    allowed: Vec<AddressRange>,
+}
+
+impl Default for ProxyPassHardcodedIpPlugin {
+    fn default() -> Self {
+        Self::with_allowed_addresses(DEFAULT_ALLOWED_ADDRESSES)
+    }
+}
+
+impl ProxyPassHardcodedIpPlugin {
+    /// Create a new plugin that allows the given addresses or CIDR ranges
+    /// (e.g. `"127.0.0.1"`, `"10.0.0.0/8"`, `"::1"`). Entries that do not
+    /// parse are ignored.
+    pub fn with_allowed_addresses<S: AsRef<str>>(allowed: &[S]) -> Self {
+        Self {
+            allowed: allowed
+                .iter()
+                .filter_map(|a| AddressRange::parse(a.as_ref()))
+                .collect(),
+        }
+    }
+
+    fn is_allowed(&self, addr: IpAddr) -> bool {
+        self.allowed.iter().any(|range| range.contains(addr))
+    }
+}
+
+/// An address or CIDR range from the allowlist
+#[derive(Debug, Clone, Copy)]
+struct AddressRange {
+    network: IpAddr,
+    prefix_len: u32,
+}
+
+impl AddressRange {
+    fn parse(s: &str) -> Option<Self> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, len)) => (addr, Some(len.parse::<u32>().ok()?)),
+            None => (s, None),
+        };
+        let network: IpAddr = addr.parse().ok()?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = prefix_len.unwrap_or(max);
+        (prefix_len <= max).then_some(Self {
+            network,
+            prefix_len,
+        })
+    }
+
+    fn contains(&self, addr: IpAddr) -> bool {
+        match (self.network, addr) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix_len).unwrap_or(0);
+                u32::from(network) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix_len).unwrap_or(0);
+                u128::from(network) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The IP literal a `proxy_pass` value points at, if any
+///
+/// Handles `http://10.0.0.1:8080/path`, `http://[fd00::1]:8080` and the
+/// scheme-less `10.0.0.1:5432` form used in the stream module.
+fn ip_literal(target: &str) -> Option<IpAddr> {
+    let host = helpers::extract_host_from_url(target).unwrap_or(target);
+    let host = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next()?,
+        None => helpers::extract_domain(host),
+    };
+    host.parse().ok()
+}
+
+impl Plugin for ProxyPassHardcodedIpPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "proxy-pass-hardcoded-ip",
+            "best-practices",
+            "Flags proxy_pass targets that are hardcoded IP addresses",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Strict)
+        .with_why(
+            "A backend address written directly into `proxy_pass` has to be edited in every \
+             place it appears whenever the backend moves, and gives no name to search for. \
+             Defining an `upstream` block (whose servers can be DNS names) or proxying to a \
+             DNS name keeps the address in one place and lets service discovery do its job.\n\n\
+             This rule is disabled by default since hardcoded addresses are a convention \
+             choice rather than an error. Loopback addresses (`127.0.0.0/8`, `::1`) are \
+             allowed; set `allowed_addresses` to replace that allowlist with your own \
+             addresses or CIDR ranges.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_pass".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_upstream_module.html#upstream".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["proxy_pass"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let err = self.spec().error_builder();
+
+        for directive in config.all_directives() {
+            if directive.is("proxy_pass")
+                && let Some(target) = directive.first_arg()
+                && let Some(addr) = ip_literal(target)
+                && !self.is_allowed(addr)
+            {
+                errors.push(err.warning_at(
+                    &format!(
+                        "proxy_pass points at hardcoded IP address '{}'; define an upstream \
+                         block or use a DNS name for the backend",
+                        addr
+                    ),
+                    directive,
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(ProxyPassHardcodedIpPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_detects_private_ipv4() {
+        let runner = PluginTestRunner::new(ProxyPassHardcodedIpPlugin::default());
+
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        location / {
+            proxy_pass http://10.0.12.34:8080/app/;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert!(
+            errors[0]
+                .message
+                .contains("hardcoded IP address '10.0.12.34'")
+        );
+    }
+
+    #[test]
+    fn test_detects_ipv6_and_stream_targets() {
+        let runner = PluginTestRunner::new(ProxyPassHardcodedIpPlugin::default());
+
+        runner.assert_errors(
+            r#"
+http {
+    server {
+        location / {
+            proxy_pass https://[fd00::12]:9000;
+        }
+    }
+}
+stream {
+    server {
+        listen 5432;
+        proxy_pass 192.168.1.20:5432;
+    }
+}
+"#,
+            2,
+        );
+    }
+
+    #[test]
+    fn test_allows_names_upstreams_and_variables() {
+        let runner = PluginTestRunner::new(ProxyPassHardcodedIpPlugin::default());
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        location /a { proxy_pass http://backend; }
+        location /b { proxy_pass http://api.example.com:8080; }
+        location /c { proxy_pass http://$upstream_host; }
+        location /d { proxy_pass http://unix:/run/app.sock; }
+    }
+}
+stream {
+    server {
+        proxy_pass db_backend;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_loopback_allowed_by_default() {
+        let runner = PluginTestRunner::new(ProxyPassHardcodedIpPlugin::default());
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        location /a { proxy_pass http://127.0.0.1:3000; }
+        location /b { proxy_pass http://127.0.1.1; }
+        location /c { proxy_pass http://[::1]:3000; }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_configured_allowlist() {
+        let runner = PluginTestRunner::new(ProxyPassHardcodedIpPlugin::with_allowed_addresses(&[
+            "10.0.0.0/8",
+            "fd00::12",
+            "not-an-address",
+        ]));
+
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        location /a { proxy_pass http://10.1.2.3:8080; }
+        location /b { proxy_pass http://[fd00::12]:9000; }
+        location /c { proxy_pass http://127.0.0.1:3000; }
+        location /d { proxy_pass http://192.168.0.10; }
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        // The configured list replaces the loopback default
+        let lines: Vec<_> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![Some(6), Some(7)], "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_address_range() {
+        let range = AddressRange::parse("192.168.0.0/16").unwrap();
+        assert!(range.contains("192.168.44.1".parse().unwrap()));
+        assert!(!range.contains("192.169.0.1".parse().unwrap()));
+        assert!(!range.contains("::1".parse().unwrap()));
+
+        assert!(
+            AddressRange::parse("0.0.0.0/0")
+                .unwrap()
+                .contains("8.8.8.8".parse().unwrap())
+        );
+        assert!(AddressRange::parse("10.0.0.0/33").is_none());
+        assert!(AddressRange::parse("backend").is_none());
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(ProxyPassHardcodedIpPlugin::default());
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(ProxyPassHardcodedIpPlugin::default());
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -120,6 +120,7 @@ impl Plugin for ProxyPassWithUriPlugin {
             "Warns when proxy_pass has a URI path that causes URI rewriting",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "When `proxy_pass` has a URI component (including just `/`), nginx performs URI \
              rewriting - it replaces the matched location prefix with the proxy_pass URI. \
@@ -0,0 +1,402 @@
+//! proxy-timeout-not-set plugin
+//!
+//! This plugin flags `proxy_pass` directives that target an external-looking
+//! host (a DNS name or a public IP address) without an explicit
+//! `proxy_connect_timeout` and `proxy_read_timeout` in effect. nginx then
+//! waits up to the 60s defaults for a slow or unreachable third party,
+//! tying up connections and making clients wait a full minute for an error.
+//!
+//! It is disabled by default: suitable timeouts depend on the backend. Set
+//! `max_connect_timeout` / `max_read_timeout` in the rule config to also
+//! flag explicit values above a project limit; missing timeouts are then
+//! fixed by inserting those limits.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedSettings};
+use nginx_lint_plugin::prelude::*;
+use std::net::IpAddr;
+
+/// Check for external proxy targets relying on the default timeouts
+#[derive(Default)]
+pub struct ProxyTimeoutNotSetPlugin {
+    max_connect: Option<Limit>,
+    max_read: Option<Limit>,
+}
+
+/// A configured maximum, kept as written for messages and fixes
+#[derive(Debug, Clone)]
+struct Limit {
+    text: String,
+    ms: u64,
+}
+
+impl Limit {
+    fn parse(text: &str) -> Option<Self> {
+        Some(Self {
+            ms: helpers::parse_time_ms(text)?,
+            text: text.to_string(),
+        })
+    }
+}
+
+impl ProxyTimeoutNotSetPlugin {
+    /// Create a new plugin with maximum timeouts in nginx time syntax (e.g.
+    /// `"5s"`, `"1m"`). Values that do not parse are ignored.
+    pub fn with_max_timeouts(connect: Option<&str>, read: Option<&str>) -> Self {
+        Self {
+            max_connect: connect.and_then(Limit::parse),
+            max_read: read.and_then(Limit::parse),
+        }
+    }
+
+    /// Walk http-level blocks, tracking the timeouts in effect
+    ///
+    /// In included files the including file may set the timeouts; only
+    /// explicit values are checked there.
+    fn check_items<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: Timeouts<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let timeouts = inherited.enter(items);
+
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if let Some(block) = &directive.block {
+                self.check_items(&block.items, timeouts, errors);
+            } else if directive.is("proxy_pass") {
+                self.check_proxy_pass(directive, &timeouts, errors);
+            }
+        }
+    }
+
+    fn check_proxy_pass(
+        &self,
+        directive: &Directive,
+        timeouts: &Timeouts,
+        errors: &mut Vec<LintError>,
+    ) {
+        let Some(host) = directive.first_arg().and_then(external_host) else {
+            return;
+        };
+        let err = self.spec().error_builder();
+
+        let checks = [
+            ("proxy_connect_timeout", &self.max_connect),
+            ("proxy_read_timeout", &self.max_read),
+        ];
+
+        let mut missing = Vec::new();
+        for (name, limit) in checks {
+            match (timeouts.get(name), limit) {
+                (Inherited::Default, _) => missing.push((name, limit)),
+                (Inherited::Set(set), Some(limit)) => {
+                    let Some(value) = set.first_arg() else {
+                        continue;
+                    };
+                    if helpers::parse_time_ms(value).is_some_and(|ms| ms > limit.ms) {
+                        errors.push(err.warning_at(
+                            &format!(
+                                "{} {} for external host '{}' exceeds the configured maximum \
+                                 of {}",
+                                name, value, host, limit.text
+                            ),
+                            set,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if missing.is_empty() {
+            return;
+        }
+
+        let mut error = err.warning_at(
+            &format!(
+                "proxy_pass to external host '{}' relies on the default 60s {}; set {} \
+                 explicitly",
+                host,
+                missing
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(" and "),
+                if missing.len() == 1 { "it" } else { "them" }
+            ),
+            directive,
+        );
+        let lines: Vec<String> = missing
+            .iter()
+            .filter_map(|(name, limit)| {
+                limit
+                    .as_ref()
+                    .map(|limit| format!("{} {};", name, limit.text))
+            })
+            .collect();
+        if lines.len() == missing.len() {
+            let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+            error = error.with_fix(directive.insert_before_many(&lines));
+        }
+        errors.push(error);
+    }
+}
+
+/// `proxy_connect_timeout` / `proxy_read_timeout` in effect for a block
+type Timeouts<'a> = InheritedSettings<'a, 2>;
+
+/// The directives followed through the blocks
+const TIMEOUTS: [&str; 2] = ["proxy_connect_timeout", "proxy_read_timeout"];
+
+/// Host of a proxy_pass target if it looks like a third-party service
+///
+/// DNS names and public IP addresses count as external; upstream names,
+/// `localhost`, private and loopback addresses, UNIX sockets and targets
+/// built from variables do not.
+fn external_host(target: &str) -> Option<&str> {
+    let host = helpers::extract_host_from_url(target)?;
+    if host.starts_with("unix:") || host.contains('$') {
+        return None;
+    }
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next()?,
+        None => helpers::extract_domain(host),
+    };
+
+    let external = match name.parse::<IpAddr>() {
+        Ok(IpAddr::V4(addr)) => !(addr.is_private() || addr.is_loopback() || addr.is_link_local()),
+        Ok(IpAddr::V6(addr)) => !(addr.is_loopback() || (addr.segments()[0] & 0xfe00) == 0xfc00),
+        Err(_) => helpers::is_domain_name(name) && name.contains('.'),
+    };
+    external.then_some(name)
+}
+
+impl Plugin for ProxyTimeoutNotSetPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "proxy-timeout-not-set",
+            "best-practices",
+            "Flags proxying to external hosts without explicit proxy timeouts",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Strict)
+        .with_why(
+            "`proxy_connect_timeout` and `proxy_read_timeout` default to 60 seconds. For a \
+             backend on another network, such as a third-party API, that means a slow or \
+             unreachable service holds each request (and its worker connection) for up to a \
+             minute before the client sees an error. Setting the timeouts explicitly, usually \
+             to a few seconds for connecting, bounds the damage of an outage you do not \
+             control.\n\n\
+             Only targets that look external are checked: DNS names and public IP addresses. \
+             Upstream names, private and loopback addresses are not. Timeouts set in the \
+             enclosing server or http block count.\n\n\
+             This rule is disabled by default. Set `max_connect_timeout` and \
+             `max_read_timeout` (e.g. `\"5s\"`) to also flag larger explicit values; \
+             missing timeouts are then fixed by inserting those limits.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_connect_timeout"
+                .to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_read_timeout"
+                .to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["proxy_pass"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+
+        if config.include_context.is_empty() {
+            for http in config.directives().filter(|d| d.is("http")) {
+                if let Some(block) = &http.block {
+                    let root = Timeouts::new(TIMEOUTS, Inherited::Default);
+                    self.check_items(&block.items, root, &mut errors);
+                }
+            }
+        } else if config.is_included_from_http() {
+            let root = Timeouts::new(TIMEOUTS, Inherited::Unknown);
+            self.check_items(&config.items, root, &mut errors);
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(ProxyTimeoutNotSetPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(plugin: &ProxyTimeoutNotSetPlugin, source: &str) -> Vec<LintError> {
+        plugin.check(&parse_string(source).unwrap(), "test.conf")
+    }
+
+    #[test]
+    fn test_detects_missing_timeouts() {
+        let errors = check(
+            &ProxyTimeoutNotSetPlugin::default(),
+            r#"
+http {
+    server {
+        location /api/ {
+            proxy_pass https://api.example.com:8443/v1/;
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert_eq!(
+            errors[0].message,
+            "proxy_pass to external host 'api.example.com' relies on the default 60s \
+             proxy_connect_timeout and proxy_read_timeout; set them explicitly"
+        );
+        // Without configured limits there is no value to insert
+        assert!(errors[0].fixes.is_empty());
+    }
+
+    #[test]
+    fn test_timeouts_inherited_from_server() {
+        let errors = check(
+            &ProxyTimeoutNotSetPlugin::default(),
+            r#"
+http {
+    proxy_read_timeout 20s;
+    server {
+        proxy_connect_timeout 3s;
+        location /a { proxy_pass http://a.example.com; }
+        location /b {
+            proxy_read_timeout 2m;
+            proxy_pass http://203.0.113.10;
+        }
+    }
+    server {
+        location /c { proxy_pass http://c.example.com; }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(13));
+        assert!(
+            errors[0]
+                .message
+                .contains("default 60s proxy_connect_timeout; set it explicitly")
+        );
+    }
+
+    #[test]
+    fn test_internal_targets_are_ignored() {
+        let runner = PluginTestRunner::new(ProxyTimeoutNotSetPlugin::default());
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        location /a { proxy_pass http://backend; }
+        location /b { proxy_pass http://127.0.0.1:8080; }
+        location /c { proxy_pass http://10.0.0.5:8080; }
+        location /d { proxy_pass http://localhost:3000; }
+        location /e { proxy_pass http://unix:/run/app.sock; }
+        location /f { proxy_pass http://$backend_host; }
+        location /g { proxy_pass http://[fd00::1]:8080; }
+    }
+}
+stream {
+    server {
+        proxy_pass db.example.com:5432;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_configured_limits() {
+        let plugin = ProxyTimeoutNotSetPlugin::with_max_timeouts(Some("5s"), Some("1m"));
+        let errors = check(
+            &plugin,
+            r#"
+http {
+    server {
+        location /a {
+            proxy_connect_timeout 10s;
+            proxy_read_timeout 60;
+            proxy_pass https://a.example.com;
+        }
+        location /b {
+            proxy_connect_timeout 2s;
+            proxy_pass https://b.example.com;
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "proxy_connect_timeout 10s for external host 'a.example.com' exceeds the \
+             configured maximum of 5s"
+        );
+        assert_eq!(errors[1].line, Some(11));
+        assert_eq!(
+            errors[1].fixes[0].new_text,
+            "            proxy_read_timeout 1m;\n"
+        );
+    }
+
+    #[test]
+    fn test_included_file_only_checks_explicit_values() {
+        let plugin = ProxyTimeoutNotSetPlugin::with_max_timeouts(None, Some("30s"));
+        let mut config = parse_string(
+            r#"
+location /a { proxy_pass https://a.example.com; }
+location /b {
+    proxy_read_timeout 300s;
+    proxy_pass https://b.example.com;
+}
+"#,
+        )
+        .unwrap();
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
+
+        let errors = plugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(4));
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(ProxyTimeoutNotSetPlugin::default());
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(ProxyTimeoutNotSetPlugin::default());
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -5,6 +5,11 @@
 //! The recommended practice is to define `root` at the server level and use
 //! `alias` inside location blocks when a different path is needed.
 //!
+//! When every location of a server sets the same `root`, a single warning is
+//! reported for the server, with a fix that moves the `root` to the server
+//! block and removes the copies from the locations. The alternative fix
+//! `keep per-location roots` ignores the warning instead.
+//!
 //! Build with:
 //! ```sh
 //! cargo build --target wasm32-unknown-unknown --release
@@ -16,19 +21,93 @@ use nginx_lint_plugin::prelude::*;
 #[derive(Default)]
 pub struct RootInLocationPlugin;
 
+/// The directives directly in `items`
+fn directives(items: &[ConfigItem]) -> impl Iterator<Item = &Directive> {
+    items.iter().filter_map(|item| match item {
+        ConfigItem::Directive(d) => Some(d.as_ref()),
+        _ => None,
+    })
+}
+
+/// The arguments of a `root` directive as written
+fn root_value(root: &Directive) -> String {
+    root.args
+        .iter()
+        .map(|a| a.raw.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 impl RootInLocationPlugin {
+    /// Check a server block whose locations all repeat the same root
+    ///
+    /// Returns the repeated root directives, which are reported together
+    /// with a fix hoisting the root to the server.
+    fn check_server<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        err: &ErrorBuilder,
+        errors: &mut Vec<LintError>,
+    ) -> Vec<&'a Directive> {
+        if directives(items).any(|d| d.is("root")) {
+            return Vec::new();
+        }
+        let locations: Vec<&Directive> = directives(items).filter(|d| d.is("location")).collect();
+        if locations.len() < 2 {
+            return Vec::new();
+        }
+        let mut roots = Vec::new();
+        for location in &locations {
+            let Some(block) = &location.block else {
+                return Vec::new();
+            };
+            let mut location_roots = directives(&block.items).filter(|d| d.is("root"));
+            match (location_roots.next(), location_roots.next()) {
+                (Some(root), None) => roots.push(root),
+                _ => return Vec::new(),
+            }
+        }
+        let value = root_value(roots[0]);
+        if roots.iter().any(|root| root_value(root) != value) {
+            return Vec::new();
+        }
+
+        let message = format!(
+            "every location in this server sets root {}; define it once at server level",
+            value
+        );
+        let mut fixes = vec![locations[0].insert_before(&format!("root {};", value))];
+        fixes.extend(roots.iter().map(|root| root.delete_line()));
+        // Leaving the roots where they are is a valid choice too
+        let keep = roots[0].insert_before(
+            "# nginx-lint:ignore root-in-location every location keeps its own root",
+        );
+        errors.push(
+            err.warning_at(&message, roots[0])
+                .with_fixes(fixes)
+                .with_alternative("keep per-location roots", vec![keep]),
+        );
+        roots
+    }
+
     /// Recursively check for root directives inside location blocks
+    ///
+    /// The `skip` directives were reported by `check_server` already.
     fn check_items(
         &self,
         items: &[ConfigItem],
         in_location: bool,
+        skip: &[&Directive],
         err: &ErrorBuilder,
         errors: &mut Vec<LintError>,
     ) {
         for item in items {
             if let ConfigItem::Directive(directive) = item {
                 // Check if we're in a location block and found a root directive
-                if in_location && directive.name == "root" {
+                if in_location
+                    && directive.name == "root"
+                    && !skip.iter().any(|d| std::ptr::eq(*d, directive.as_ref()))
+                {
                     errors.push(err.warning_at(
                         "root directive inside location block; consider defining root at server level and using alias in location blocks",
                         directive,
@@ -38,8 +117,14 @@ impl RootInLocationPlugin {
                 // Recurse into blocks
                 if let Some(block) = &directive.block {
                     let is_location = directive.name == "location";
+                    let hoisted = if directive.name == "server" && !in_location {
+                        self.check_server(&block.items, err, errors)
+                    } else {
+                        Vec::new()
+                    };
+                    let skip: Vec<&Directive> = skip.iter().copied().chain(hoisted).collect();
                     // Once we're in a location, stay in_location for nested blocks
-                    self.check_items(&block.items, in_location || is_location, err, errors);
+                    self.check_items(&block.items, in_location || is_location, &skip, err, errors);
                 }
             }
         }
@@ -54,13 +139,16 @@ impl Plugin for RootInLocationPlugin {
             "Warns when root directive is used inside location blocks",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "Defining `root` inside location blocks can lead to confusion and maintenance issues. \
              The recommended practice is to define `root` at the server level, which applies to \
              all locations by default. When a location needs a different document root, use the \
              `alias` directive instead, which is more explicit about its purpose.\n\n\
              Using `root` at server level also helps avoid the common pitfall of forgetting to \
-             define `root` in some location blocks.",
+             define `root` in some location blocks.\n\n\
+             When every location of a server repeats the same `root`, the fix moves it to the \
+             server block and removes the copies.",
         )
         .with_bad_example(include_str!("../examples/bad.conf").trim())
         .with_good_example(include_str!("../examples/good.conf").trim())
@@ -72,11 +160,9 @@ impl Plugin for RootInLocationPlugin {
     }
 
     fn relevant_directives(&self) -> Option<&'static [&'static str]> {
-        // "location" is not needed: any location containing a kept "root"
-        // descendant is retained as a full-fidelity ancestor regardless, so
-        // check_items still sees directive.name == "location" correctly
-        // when propagating the in_location flag downward.
-        Some(&["root"])
+        // Locations without a root are kept too, so check_server sees every
+        // location of a server.
+        Some(&["root", "location"])
     }
 
     fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
@@ -84,7 +170,14 @@ impl Plugin for RootInLocationPlugin {
         let err = self.spec().error_builder();
         // Check if this file is included from within a location context
         let in_location = config.is_included_from_http_location();
-        self.check_items(&config.items, in_location, &err, &mut errors);
+        let hoisted = if config.immediate_parent_context() == Some("server")
+            && config.is_included_from_http_server()
+        {
+            self.check_server(&config.items, &err, &mut errors)
+        } else {
+            Vec::new()
+        };
+        self.check_items(&config.items, in_location, &hoisted, &err, &mut errors);
         errors
     }
 }
@@ -238,6 +331,115 @@ http {
         assert_eq!(errors.len(), 2, "Expected 2 errors, got: {:?}", errors);
     }
 
+    #[test]
+    fn test_same_root_in_every_location() {
+        let runner = PluginTestRunner::new(RootInLocationPlugin);
+        let config = r#"
+http {
+    server {
+        listen 80;
+
+        location / {
+            root /var/www/html;
+            try_files $uri $uri/ =404;
+        }
+
+        location /static/ {
+            root /var/www/html;
+            expires 30d;
+        }
+    }
+}
+"#;
+        let errors = runner.check_string(config).unwrap();
+        assert_eq!(errors.len(), 1, "Expected 1 error, got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(7));
+        assert_eq!(
+            errors[0].message,
+            "every location in this server sets root /var/www/html; define it once at server level"
+        );
+        runner.assert_fix_produces(
+            config,
+            r#"
+http {
+    server {
+        listen 80;
+
+        root /var/www/html;
+        location / {
+            try_files $uri $uri/ =404;
+        }
+
+        location /static/ {
+            expires 30d;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_same_root_not_in_every_location() {
+        let runner = PluginTestRunner::new(RootInLocationPlugin);
+        // A location without root
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        location / {
+            root /var/www/html;
+        }
+        location /api {
+            proxy_pass http://backend;
+        }
+        location /static/ {
+            root /var/www/html;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 2, "Expected 2 errors, got: {:?}", errors);
+        assert!(errors.iter().all(|e| e.fixes.is_empty()));
+
+        // The server sets a root of its own
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        root /srv;
+        location / {
+            root /var/www/html;
+        }
+        location /static/ {
+            root /var/www/html;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 2, "Expected 2 errors, got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_same_root_in_included_server() {
+        let mut config = parse_string(
+            "location / {\n    root /var/www;\n}\nlocation /a/ {\n    root /var/www;\n}\n",
+        )
+        .unwrap();
+        config.include_context = IncludeContext::parse("http,server");
+
+        let errors = RootInLocationPlugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "Expected 1 error, got: {:?}", errors);
+        assert_eq!(errors[0].fixes.len(), 3);
+        assert_eq!(errors[0].alternatives[0].label, "keep per-location roots");
+    }
+
     #[test]
     fn test_examples() {
         let runner = PluginTestRunner::new(RootInLocationPlugin);
@@ -262,7 +464,8 @@ root /var/www/html;
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = RootInLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -287,7 +490,7 @@ root /var/www/html;
         .unwrap();
 
         // Simulate being included from http > server context (not location)
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = RootInLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -0,0 +1,315 @@
+//! suspicious-proxy-settings plugin
+//!
+//! This plugin flags proxy buffering and timeout settings that are valid but
+//! rarely what was intended:
+//!
+//! - `proxy_buffering off` where buffer sizes for large responses
+//!   (`proxy_buffers`, `proxy_busy_buffers_size`, `proxy_max_temp_file_size`,
+//!   `proxy_temp_file_write_size`) are in effect; nginx ignores them
+//! - `proxy_read_timeout` above a limit (default 300s, configurable with
+//!   `max_read_timeout`)
+//! - `proxy_next_upstream` retries without `proxy_next_upstream_timeout`
+//!
+//! It is disabled by default: suitable values depend on the application.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::InheritedSettings;
+use nginx_lint_plugin::prelude::*;
+
+/// Limit used when `max_read_timeout` is not configured
+const DEFAULT_MAX_READ_TIMEOUT: &str = "300s";
+
+/// The directives followed through the blocks: the buffer sizes, then
+/// `proxy_next_upstream_timeout`
+const SETTINGS: [&str; 5] = [
+    "proxy_buffers",
+    "proxy_busy_buffers_size",
+    "proxy_max_temp_file_size",
+    "proxy_temp_file_write_size",
+    "proxy_next_upstream_timeout",
+];
+
+/// Buffer settings that only apply while proxy_buffering is on
+const BUFFER_SIZES: &[&str] = SETTINGS.split_at(4).0;
+
+/// Settings in effect for a block
+type Scope<'a> = InheritedSettings<'a, 5>;
+
+/// Check for suspicious proxy buffering and timeout settings
+pub struct SuspiciousProxySettingsPlugin {
+    max_read_timeout: String,
+    max_read_timeout_ms: u64,
+}
+
+impl Default for SuspiciousProxySettingsPlugin {
+    fn default() -> Self {
+        Self::with_max_read_timeout(DEFAULT_MAX_READ_TIMEOUT)
+    }
+}
+
+impl SuspiciousProxySettingsPlugin {
+    /// Create a new plugin with the largest acceptable `proxy_read_timeout`
+    /// in nginx time syntax (e.g. `"2m"`). A value that does not parse
+    /// falls back to the default of 300s.
+    pub fn with_max_read_timeout(max_read_timeout: &str) -> Self {
+        let (text, ms) = match helpers::parse_time_ms(max_read_timeout) {
+            Some(ms) => (max_read_timeout, ms),
+            None => (DEFAULT_MAX_READ_TIMEOUT, 300_000),
+        };
+        Self {
+            max_read_timeout: text.to_string(),
+            max_read_timeout_ms: ms,
+        }
+    }
+
+    /// Walk the config, tracking the settings inherited by each block
+    ///
+    /// In included files the including file may set
+    /// `proxy_next_upstream_timeout`; the retry check is skipped there.
+    fn check_items<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: Scope<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let scope = inherited.enter(items);
+        let err = self.spec().error_builder();
+
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if let Some(block) = &directive.block {
+                self.check_items(&block.items, scope, errors);
+                continue;
+            }
+
+            match directive.name.as_str() {
+                "proxy_buffering" if directive.first_arg_is("off") => {
+                    if let Some(sizes) = BUFFER_SIZES
+                        .iter()
+                        .find_map(|name| scope.get(name).directive())
+                    {
+                        errors.push(err.warning_at(
+                            &format!(
+                                "proxy_buffering off ignores {} (line {}); responses are passed \
+                                 to the client as they arrive, holding the backend connection \
+                                 until a slow client has read them",
+                                sizes.name, sizes.span.start.line
+                            ),
+                            directive,
+                        ));
+                    }
+                }
+                "proxy_read_timeout" => {
+                    let Some(value) = directive.first_arg() else {
+                        continue;
+                    };
+                    if helpers::parse_time_ms(value).is_some_and(|ms| ms > self.max_read_timeout_ms)
+                    {
+                        errors.push(err.warning_at(
+                            &format!(
+                                "proxy_read_timeout {} exceeds {}; a stalled backend holds the \
+                                 request and its connection that long",
+                                value, self.max_read_timeout
+                            ),
+                            directive,
+                        ));
+                    }
+                }
+                "proxy_next_upstream"
+                    if !directive.first_arg_is("off")
+                        && scope.get("proxy_next_upstream_timeout").is_default() =>
+                {
+                    errors.push(err.warning_at(
+                        "proxy_next_upstream retries without proxy_next_upstream_timeout, so a \
+                         request can wait for the full timeout on every upstream server; set \
+                         proxy_next_upstream_timeout",
+                        directive,
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Plugin for SuspiciousProxySettingsPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "suspicious-proxy-settings",
+            "best-practices",
+            "Flags proxy buffering and timeout settings that are likely mistakes",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "Some proxy settings are valid but rarely what was intended:\n\n\
+             - With `proxy_buffering off`, nginx passes the response to the client as it \
+             arrives and ignores `proxy_buffers`, `proxy_busy_buffers_size` and the temporary \
+             file settings. Configuring those for large responses and then turning buffering \
+             off keeps the backend busy until slow clients have downloaded everything.\n\
+             - A very long `proxy_read_timeout` lets a stalled backend hold requests and \
+             connections for that long; long-polling and WebSocket locations are better given \
+             their own, explicit value.\n\
+             - `proxy_next_upstream` retries a failed request on the next server. Without \
+             `proxy_next_upstream_timeout`, each attempt may take the full connect and read \
+             timeouts, multiplying the time a client waits.\n\n\
+             This rule is disabled by default. Set `max_read_timeout` (default `\"300s\"`) to \
+             change the largest accepted `proxy_read_timeout`.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_buffering".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_read_timeout"
+                .to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_next_upstream_timeout"
+                .to_string(),
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        self.check_items(&config.items, Scope::root(SETTINGS, config), &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(SuspiciousProxySettingsPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(plugin: &SuspiciousProxySettingsPlugin, source: &str) -> Vec<LintError> {
+        plugin.check(&parse_string(source).unwrap(), "test.conf")
+    }
+
+    #[test]
+    fn test_buffering_off_with_buffer_sizes() {
+        let errors = check(
+            &SuspiciousProxySettingsPlugin::default(),
+            r#"
+http {
+    proxy_buffers 16 64k;
+    server {
+        location /download/ {
+            proxy_buffering off;
+            proxy_pass http://backend;
+        }
+        location /stream/ {
+            proxy_buffering off;
+            proxy_buffer_size 8k;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(6));
+        assert!(
+            errors[0]
+                .message
+                .starts_with("proxy_buffering off ignores proxy_buffers (line 3)")
+        );
+    }
+
+    #[test]
+    fn test_read_timeout_limit() {
+        let source = r#"
+location / {
+    proxy_read_timeout 10m;
+    proxy_pass http://backend;
+}
+location /ws/ {
+    proxy_read_timeout 1h;
+    proxy_pass http://backend;
+}
+"#;
+        let errors = check(&SuspiciousProxySettingsPlugin::default(), source);
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "proxy_read_timeout 10m exceeds 300s; a stalled backend holds the request and its \
+             connection that long"
+        );
+
+        let errors = check(
+            &SuspiciousProxySettingsPlugin::with_max_read_timeout("30m"),
+            source,
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(7));
+    }
+
+    #[test]
+    fn test_next_upstream_without_timeout() {
+        let runner = PluginTestRunner::new(SuspiciousProxySettingsPlugin::default());
+
+        runner.assert_errors(
+            r#"
+http {
+    server {
+        location / {
+            proxy_next_upstream error timeout http_502;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            1,
+        );
+        runner.assert_no_errors(
+            r#"
+http {
+    proxy_next_upstream_timeout 10s;
+    server {
+        location / {
+            proxy_next_upstream error timeout http_502;
+            proxy_pass http://backend;
+        }
+        location /once/ {
+            proxy_next_upstream off;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_included_file_skips_next_upstream_check() {
+        let mut config =
+            parse_string("proxy_next_upstream error timeout;\nproxy_pass http://backend;\n")
+                .unwrap();
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
+
+        let errors = SuspiciousProxySettingsPlugin::default().check(&config, "test.conf");
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(SuspiciousProxySettingsPlugin::default());
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(SuspiciousProxySettingsPlugin::default());
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,461 @@
+//! suspicious-rewrite plugin
+//!
+//! This plugin reports `rewrite` directives that are better written another
+//! way or do not do what they appear to:
+//!
+//! - a rewrite of every URI that always redirects (`rewrite ^
+//!   https://example.com$request_uri permanent;`), which `return` does
+//!   without running a regex; the fix converts it
+//! - a rewrite in a location that maps the URI back into the same location,
+//!   where it is rewritten again until nginx gives up with a 500 error
+//! - `$1`..`$9` in the replacement beyond the pattern's capture groups,
+//!   which expand to an empty string
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::location_match::LocationTree;
+use nginx_lint_plugin::prelude::*;
+use nginx_lint_plugin::regex_match::Regex;
+use nginx_lint_plugin::regex_scan::scan;
+
+/// Patterns that match every URI without capturing part of it
+const MATCH_ALL_PATTERNS: &[&str] = &["^", "^.*", "^.*$", ".*", "^/", "^/.*", "^/.*$"];
+
+/// Variables that already carry the request arguments
+const ARGS_VARIABLES: &[&str] = &["$request_uri", "$args", "$query_string", "$is_args"];
+
+/// Check rewrite directives for return candidates, loops and missing captures
+#[derive(Default)]
+pub struct SuspiciousRewritePlugin;
+
+/// The `$N` / `${N}` capture references in `replacement`
+fn capture_references(replacement: &str) -> Vec<usize> {
+    let bytes = replacement.as_bytes();
+    let mut references = Vec::new();
+    for (i, _) in replacement.match_indices('$') {
+        let digit = match bytes.get(i + 1) {
+            Some(b'{') if bytes.get(i + 3) == Some(&b'}') => bytes.get(i + 2),
+            other => other,
+        };
+        if let Some(digit) = digit.filter(|d| d.is_ascii_digit()) {
+            references.push(usize::from(digit - b'0'));
+        }
+    }
+    references
+}
+
+/// The pattern, replacement and flag of `rewrite`, unquoted
+fn rewrite_words(rewrite: &Directive) -> Vec<String> {
+    let mut words = rewrite.words();
+    for word in &mut words {
+        let quoted = word.len() >= 2
+            && (word.starts_with('"') && word.ends_with('"')
+                || word.starts_with('\'') && word.ends_with('\''));
+        if quoted {
+            *word = word[1..word.len() - 1].to_string();
+        }
+    }
+    words
+}
+
+/// The status of the redirect a rewrite to `replacement` with `flag`
+/// always answers with, if it does
+fn redirect_status(replacement: &str, flag: Option<&str>) -> Option<u16> {
+    match flag {
+        Some("permanent") => Some(301),
+        Some("redirect") => Some(302),
+        None if ["http://", "https://", "$scheme"]
+            .iter()
+            .any(|scheme| replacement.starts_with(scheme)) =>
+        {
+            Some(302)
+        }
+        _ => None,
+    }
+}
+
+/// The `return` target equivalent to redirecting to `replacement`, which
+/// nginx follows with the request arguments unless it ends with `?`;
+/// `None` when it adds arguments of its own, which nginx merges with them
+fn return_target(replacement: &str) -> Option<String> {
+    if let Some(target) = replacement.strip_suffix('?') {
+        return Some(target.to_string());
+    }
+    if ARGS_VARIABLES
+        .iter()
+        .any(|variable| replacement.contains(variable))
+    {
+        return Some(replacement.to_string());
+    }
+    if replacement.contains('?') {
+        return None;
+    }
+    Some(format!("{}$is_args$args", replacement))
+}
+
+/// `replacement` as a URI, with variables and captures given a placeholder
+/// value and the arguments dropped
+fn sample_uri(replacement: &str) -> String {
+    let mut uri = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '?' => break,
+            '$' => {
+                if chars.peek() == Some(&'{') {
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                    }
+                } else {
+                    while chars
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+                    {
+                        chars.next();
+                    }
+                }
+                uri.push('x');
+            }
+            c => uri.push(c),
+        }
+    }
+    uri
+}
+
+/// The rewrites directly in `items`
+fn rewrites(items: &[ConfigItem]) -> impl Iterator<Item = &Directive> {
+    items.iter().filter_map(|item| match item {
+        ConfigItem::Directive(d) if d.is("rewrite") => Some(d.as_ref()),
+        _ => None,
+    })
+}
+
+impl SuspiciousRewritePlugin {
+    /// Check one rewrite on its own
+    fn check_rewrite(&self, rewrite: &Directive, errors: &mut Vec<LintError>) {
+        let err = self.spec().error_builder();
+        let words = rewrite_words(rewrite);
+        let [pattern, replacement, rest @ ..] = words.as_slice() else {
+            return;
+        };
+        let (pattern, replacement) = (pattern.as_str(), replacement.as_str());
+
+        let captures = scan(pattern).len();
+        if let Some(reference) = capture_references(replacement)
+            .into_iter()
+            .find(|reference| *reference > captures)
+        {
+            let message = format!(
+                "rewrite replacement uses ${} but the pattern has {} capture group{}, so it \
+                 expands to an empty string",
+                reference,
+                captures,
+                if captures == 1 { "" } else { "s" }
+            );
+            errors.push(err.warning_at(&message, rewrite));
+        }
+
+        if !MATCH_ALL_PATTERNS.contains(&pattern) || !capture_references(replacement).is_empty() {
+            return;
+        }
+        let Some(status) = redirect_status(replacement, rest.first().map(String::as_str)) else {
+            return;
+        };
+        let message = format!(
+            "rewrite {} always redirects; use return {} instead, which skips the regex",
+            pattern, status
+        );
+        let mut error = err.warning_at(&message, rewrite);
+        if let Some(target) = return_target(replacement) {
+            let target = if target.contains(char::is_whitespace) {
+                format!("\"{}\"", target)
+            } else {
+                target
+            };
+            error = error.with_fix(rewrite.replace_with(&format!("return {} {};", status, target)));
+        }
+        errors.push(error);
+    }
+
+    /// Report rewrites in the locations of a server (whose directives are
+    /// `items`) that send the URI back into the same location
+    fn check_loops(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        let tree = LocationTree::from_items(items);
+        for chain in tree.chains() {
+            let Some(location) = chain.last() else {
+                continue;
+            };
+            let Some(block) = &location.directive().block else {
+                continue;
+            };
+            for rewrite in rewrites(&block.items) {
+                let words = rewrite_words(rewrite);
+                let [pattern, replacement, rest @ ..] = words.as_slice() else {
+                    continue;
+                };
+                if !matches!(rest.first().map(String::as_str), None | Some("last")) {
+                    continue;
+                }
+                if !replacement.starts_with('/') {
+                    continue;
+                }
+                let uri = sample_uri(replacement);
+                let rewritten_again =
+                    Regex::new(pattern, false).is_some_and(|regex| regex.is_match(&uri));
+                let same_location = tree.select(&uri).is_some_and(|selected| {
+                    selected
+                        .last()
+                        .is_some_and(|l| std::ptr::eq(l.directive(), location.directive()))
+                });
+                if rewritten_again && same_location {
+                    let message = format!(
+                        "rewrite sends {} back into location {}, where it is rewritten again \
+                         until nginx stops after 10 cycles with a 500 error; use the break flag \
+                         or a pattern the new URI does not match",
+                        uri,
+                        location.display()
+                    );
+                    errors.push(self.spec().error_builder().warning_at(&message, rewrite));
+                }
+            }
+        }
+    }
+
+    /// Walk `items`, checking every rewrite and every server's locations
+    fn check_items(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if directive.is("rewrite") {
+                self.check_rewrite(directive, errors);
+            }
+            if let Some(block) = &directive.block {
+                if directive.is("server") {
+                    self.check_loops(&block.items, errors);
+                }
+                self.check_items(&block.items, errors);
+            }
+        }
+    }
+}
+
+impl Plugin for SuspiciousRewritePlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "suspicious-rewrite",
+            "best-practices",
+            "Detects rewrites that should be return, loop within a location, or use missing captures",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "A `rewrite` that matches every URI and redirects (`rewrite ^ \
+             https://example.com$request_uri permanent;`) runs a regex for nothing; `return 301 \
+             https://example.com$request_uri;` says the same directly. It is also easy to get \
+             wrong: `rewrite` appends the request arguments to the redirect unless the \
+             replacement ends with `?`, so with `$request_uri` they are sent twice.\n\n\
+             Inside a location, a rewrite without a flag (or with `last`) starts a new location \
+             search with the rewritten URI. When that URI selects the same location and \
+             matches the pattern again, it is rewritten again, until nginx stops after 10 \
+             cycles and answers 500. Use `break` to stay in the location.\n\n\
+             `$1`..`$9` refer to the pattern's capture groups; one beyond them expands to an \
+             empty string.\n\n\
+             The fix converts redirecting rewrites of every URI to `return`, keeping the \
+             arguments the rewrite would have passed on.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_rewrite_module.html#rewrite".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_rewrite_module.html#return".to_string(),
+            "https://www.nginx.com/blog/creating-nginx-rewrite-rules/".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        // Locations are kept as ancestors; the loop check also needs those
+        // without rewrites to select the location of a rewritten URI
+        Some(&["rewrite", "location"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        if config
+            .include_context
+            .last()
+            .is_some_and(|frame| frame.name == "server")
+        {
+            self.check_loops(&config.items, &mut errors);
+        }
+        self.check_items(&config.items, &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(SuspiciousRewritePlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_capture_references() {
+        assert_eq!(capture_references("/a/$1/${2}x/$host/$"), vec![1, 2]);
+        assert!(capture_references("/$request_uri").is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_to_return() {
+        let runner = PluginTestRunner::new(SuspiciousRewritePlugin);
+        runner.assert_fix_produces(
+            "http {\n    server {\n        rewrite ^ https://example.com$request_uri permanent;\n    }\n}\n",
+            "http {\n    server {\n        return 301 https://example.com$request_uri;\n    }\n}\n",
+        );
+        runner.assert_fix_produces(
+            "http {\n    server {\n        rewrite ^/.*$ https://example.com/? redirect;\n    }\n}\n",
+            "http {\n    server {\n        return 302 https://example.com/;\n    }\n}\n",
+        );
+        runner.assert_fix_produces(
+            "http {\n    server {\n        rewrite ^ https://example.com/new;\n    }\n}\n",
+            "http {\n    server {\n        return 302 https://example.com/new$is_args$args;\n    }\n}\n",
+        );
+
+        // Reported, but arguments of its own are merged with the request's
+        let errors = runner
+            .check_string("http {\n    server {\n        rewrite ^ /new?a=1 permanent;\n    }\n}\n")
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "rewrite ^ always redirects; use return 301 instead, which skips the regex"
+        );
+        assert!(errors[0].fixes.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_not_replaceable() {
+        let runner = PluginTestRunner::new(SuspiciousRewritePlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        rewrite ^/old/(.*)$ /new/$1 permanent;
+        rewrite ^(.*)$ https://example.com$1 permanent;
+        rewrite ^ /index.php last;
+        rewrite ^ /index.php;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_missing_capture() {
+        let runner = PluginTestRunner::new(SuspiciousRewritePlugin);
+        let errors = runner
+            .check_string(
+                "http {\n    server {\n        rewrite ^/user/(\\d+)$ /profile?id=$1&tab=$2 last;\n    }\n}\n",
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "rewrite replacement uses $2 but the pattern has 1 capture group, so it expands to \
+             an empty string"
+        );
+        runner.assert_no_errors(
+            "http {\n    server {\n        rewrite ^/(?<section>\\w+)/(.*)$ /$section/index.php?path=$2 last;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_rewrite_loop() {
+        let runner = PluginTestRunner::new(SuspiciousRewritePlugin);
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        location /app/ {
+            rewrite ^/app/(.*)$ /app/index.php?q=$1;
+        }
+
+        location /api/ {
+            rewrite ^/api/(.*)$ /api/v2/$1 last;
+        }
+
+        location /old/ {
+            rewrite ^/old/(.*)$ /new/$1 last;
+        }
+
+        location /static/ {
+            rewrite ^/static/(.*)$ /static/v2/$1 break;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert_eq!(
+            errors[0].message,
+            "rewrite sends /app/index.php back into location /app/, where it is rewritten again \
+             until nginx stops after 10 cycles with a 500 error; use the break flag or a pattern \
+             the new URI does not match"
+        );
+        assert_eq!(errors[1].line, Some(9));
+    }
+
+    #[test]
+    fn test_rewrite_into_other_location() {
+        let runner = PluginTestRunner::new(SuspiciousRewritePlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        location /app/ {
+            rewrite ^/app/(.*)$ /app/index.php?q=$1;
+        }
+
+        location ~ \.php$ {
+            fastcgi_pass php;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_included_file() {
+        let mut config = nginx_lint_plugin::parse_string(
+            "location /app/ {\n    rewrite ^/app/(.*)$ /app/x/$1 last;\n}\n",
+        )
+        .unwrap();
+        config.include_context = IncludeContext::parse("http,server");
+        assert_eq!(SuspiciousRewritePlugin.check(&config, "test.conf").len(), 1);
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(SuspiciousRewritePlugin);
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(SuspiciousRewritePlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,431 @@
+//! suspicious-server-name plugin
+//!
+//! This plugin checks the names given to `server_name`:
+//!
+//! - wildcards nginx rejects (`*.*.example.com`, `www.*.example.com`); a
+//!   `*` is only allowed as the whole first or last label
+//! - regular expressions without the `~` prefix, which nginx compares as
+//!   literal names that no request matches
+//! - names listed twice in one server
+//!
+//! It also reports addresses that several servers of an `http` block listen
+//! on when none of them is marked `default_server`. Servers are compared
+//! within one file.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::helpers::normalize_listen_address;
+use nginx_lint_plugin::prelude::*;
+use std::collections::HashMap;
+
+/// Characters that only make sense in a regular expression
+const REGEX_CHARS: &[char] = &['^', '$', '\\', '(', ')', '[', ']', '|', '+', '?', '{', '}'];
+
+/// Check server_name arguments and the default server of shared addresses
+#[derive(Default)]
+pub struct SuspiciousServerNamePlugin;
+
+/// Whether `name` uses `*` the way nginx accepts
+fn is_valid_wildcard(name: &str) -> bool {
+    match (name.strip_prefix("*."), name.strip_suffix(".*")) {
+        (Some(rest), _) | (None, Some(rest)) => !rest.is_empty() && !rest.contains('*'),
+        (None, None) => !name.contains('*'),
+    }
+}
+
+/// Whether `name` looks like a regular expression
+fn looks_like_regex(name: &str) -> bool {
+    name != "$hostname" && name.contains(REGEX_CHARS)
+}
+
+/// The server blocks directly in `items`
+fn server_blocks(items: &[ConfigItem]) -> impl Iterator<Item = (&Directive, &[ConfigItem])> {
+    items.iter().filter_map(|item| match item {
+        ConfigItem::Directive(d) if d.is("server") => d
+            .block
+            .as_ref()
+            .map(|block| (d.as_ref(), block.items.as_slice())),
+        _ => None,
+    })
+}
+
+/// The normalized addresses the server with `items` listens on
+fn listen_addresses(items: &[ConfigItem]) -> Vec<(String, bool)> {
+    let listens: Vec<&Directive> = items
+        .iter()
+        .filter_map(|item| match item {
+            ConfigItem::Directive(d) if d.is("listen") => Some(d.as_ref()),
+            _ => None,
+        })
+        .collect();
+    if listens.is_empty() {
+        return vec![("*:80".to_string(), false)];
+    }
+    listens
+        .into_iter()
+        .filter_map(|listen| {
+            let address = listen.first_arg().filter(|a| !a.contains('$'))?;
+            let default_server = listen
+                .args
+                .iter()
+                .any(|a| matches!(a.as_str(), "default_server" | "default"));
+            Some((normalize_listen_address(address), default_server))
+        })
+        .collect()
+}
+
+impl SuspiciousServerNamePlugin {
+    /// Check the `server_name` directives of one server, whose directives
+    /// are `items`
+    fn check_names(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        let err = self.spec().error_builder();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if !directive.is("server_name") {
+                continue;
+            }
+            for arg in &directive.args {
+                let name = arg.as_str();
+                if name.starts_with('~') {
+                    // Regular expressions are case-sensitive
+                } else if looks_like_regex(name) {
+                    let message = format!(
+                        "server name {} looks like a regular expression but has no ~ prefix, \
+                         so it is compared as a literal name",
+                        name
+                    );
+                    let quoted = arg.raw.starts_with(['"', '\'']);
+                    let offset = arg.span.start.offset + usize::from(quoted);
+                    errors.push(
+                        err.warning_at(&message, directive)
+                            .with_fix(Fix::replace_range(offset, offset, "~")),
+                    );
+                    continue;
+                } else if !is_valid_wildcard(name) {
+                    let message = format!(
+                        "invalid wildcard server name {}; * must be the whole first or last \
+                         label, as in *.example.com or www.example.*",
+                        name
+                    );
+                    errors.push(err.error_at(&message, directive));
+                    continue;
+                }
+
+                let key = if name.starts_with('~') {
+                    name.to_string()
+                } else {
+                    name.to_ascii_lowercase()
+                };
+                if let Some(line) = seen.get(&key) {
+                    let message = format!(
+                        "server name {} is already listed at line {} of this server",
+                        name, line
+                    );
+                    errors.push(err.warning_at(&message, directive));
+                } else {
+                    seen.insert(key, directive.line());
+                }
+            }
+        }
+    }
+
+    /// Report addresses several of the servers in `items` listen on without
+    /// a `default_server`
+    fn check_defaults(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        // For each address: the first server, the number of servers, and
+        // whether one is the default
+        let mut addresses: Vec<(String, &Directive, usize, bool)> = Vec::new();
+        for (server, server_items) in server_blocks(items) {
+            let mut listened: Vec<String> = Vec::new();
+            for (address, default_server) in listen_addresses(server_items) {
+                let new_for_server = !listened.contains(&address);
+                match addresses.iter_mut().find(|(a, ..)| *a == address) {
+                    Some((_, _, count, has_default)) => {
+                        if new_for_server {
+                            *count += 1;
+                        }
+                        *has_default |= default_server;
+                    }
+                    None => addresses.push((address.clone(), server, 1, default_server)),
+                }
+                listened.push(address);
+            }
+        }
+
+        for (address, first, count, has_default) in addresses {
+            if count < 2 || has_default {
+                continue;
+            }
+            let message = format!(
+                "{} servers listen on {} and none is marked default_server, so this first one \
+                 receives requests for unknown names; mark the intended catch-all with \
+                 listen ... default_server",
+                count, address
+            );
+            errors.push(self.spec().error_builder().warning_at(&message, first));
+        }
+    }
+
+    /// Walk `items`, checking the names of every server block
+    fn check_items(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            let Some(block) = &directive.block else {
+                continue;
+            };
+            if directive.is("server") {
+                self.check_names(&block.items, errors);
+            } else {
+                if directive.is("http") {
+                    self.check_defaults(&block.items, errors);
+                }
+                self.check_items(&block.items, errors);
+            }
+        }
+    }
+}
+
+impl Plugin for SuspiciousServerNamePlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "suspicious-server-name",
+            "best-practices",
+            "Detects invalid, misspelled or duplicated server names and shared addresses without a default server",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "nginx accepts a `*` in `server_name` only as the whole first or last label \
+             (`*.example.com`, `www.example.*`) and refuses to start with other wildcards such \
+             as `*.*.example.com`. A regular expression needs the `~` prefix; without it, \
+             `^www\\.example\\.com$` is compared as a literal host name and never matches. A \
+             name listed twice in one server is redundant and usually a copy-and-paste \
+             leftover.\n\n\
+             When several servers listen on the same address and none is marked \
+             `default_server`, requests whose `Host` matches no name go to whichever server \
+             comes first, which depends on the order of the blocks and of the included files. \
+             Mark the intended catch-all explicitly.\n\n\
+             The fix adds the `~` prefix to names that look like regular expressions.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/server_names.html".to_string(),
+            "https://nginx.org/en/docs/http/request_processing.html".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["server_name", "listen", "server"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let context: Vec<&str> = config.include_context.names().collect();
+        match context.last() {
+            Some(&"server") => self.check_names(&config.items, &mut errors),
+            Some(&"http") => {
+                self.check_defaults(&config.items, &mut errors);
+                self.check_items(&config.items, &mut errors);
+            }
+            _ => self.check_items(&config.items, &mut errors),
+        }
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(SuspiciousServerNamePlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn messages(config: &str) -> Vec<(usize, String)> {
+        PluginTestRunner::new(SuspiciousServerNamePlugin)
+            .check_string(config)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.line.unwrap(), e.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(is_valid_wildcard("*.example.com"));
+        assert!(is_valid_wildcard("www.example.*"));
+        assert!(is_valid_wildcard(".example.com"));
+        assert!(is_valid_wildcard("example.com"));
+        assert!(!is_valid_wildcard("*.*.example.com"));
+        assert!(!is_valid_wildcard("www.*.example.com"));
+        assert!(!is_valid_wildcard("*example.com"));
+        assert!(!is_valid_wildcard("*"));
+
+        let errors = PluginTestRunner::new(SuspiciousServerNamePlugin)
+            .check_string("http {\n    server {\n        server_name *.*.example.com;\n    }\n}\n")
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert_eq!(
+            errors[0].message,
+            "invalid wildcard server name *.*.example.com; * must be the whole first or last \
+             label, as in *.example.com or www.example.*"
+        );
+    }
+
+    #[test]
+    fn test_regex_without_tilde() {
+        let runner = PluginTestRunner::new(SuspiciousServerNamePlugin);
+        runner.assert_fix_produces(
+            "http {\n    server {\n        server_name example.com ^www\\d+\\.example\\.com$;\n    }\n}\n",
+            "http {\n    server {\n        server_name example.com ~^www\\d+\\.example\\.com$;\n    }\n}\n",
+        );
+        runner.assert_fix_produces(
+            "http {\n    server {\n        server_name \"^(?<sub>.+)\\.example\\.com$\";\n    }\n}\n",
+            "http {\n    server {\n        server_name \"~^(?<sub>.+)\\.example\\.com$\";\n    }\n}\n",
+        );
+        runner.assert_no_errors(
+            "http {\n    server {\n        server_name ~^www\\d+\\.example\\.com$ $hostname \"\" _;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let errors = messages(
+            r#"
+http {
+    server {
+        server_name example.com www.example.com;
+        server_name Example.com ~^api\.;
+        server_name ~^api\.;
+    }
+
+    server {
+        server_name example.com;
+    }
+}
+"#,
+        );
+        assert_eq!(
+            errors,
+            [
+                (
+                    3,
+                    "2 servers listen on *:80 and none is marked default_server, so this first \
+                     one receives requests for unknown names; mark the intended catch-all with \
+                     listen ... default_server"
+                        .to_string()
+                ),
+                (
+                    5,
+                    "server name Example.com is already listed at line 4 of this server"
+                        .to_string()
+                ),
+                (
+                    6,
+                    "server name ~^api\\. is already listed at line 5 of this server".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_server() {
+        let runner = PluginTestRunner::new(SuspiciousServerNamePlugin);
+        runner.assert_error_on_line(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        listen [::]:443 ssl;
+        server_name a.example.com;
+    }
+
+    server {
+        listen 0.0.0.0:443 ssl;
+        listen [::]:443 ssl default_server;
+        server_name b.example.com;
+    }
+}
+"#,
+            3,
+        );
+        runner.assert_errors(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        listen [::]:443 ssl;
+        server_name a.example.com;
+    }
+
+    server {
+        listen 0.0.0.0:443 ssl;
+        listen [::]:443 ssl default_server;
+        server_name b.example.com;
+    }
+}
+"#,
+            1,
+        );
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 80 default_server;
+        server_name _;
+    }
+
+    server {
+        listen 80;
+        server_name example.com;
+    }
+
+    server {
+        listen 8080;
+        server_name example.com;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_included_file() {
+        let plugin = SuspiciousServerNamePlugin;
+        let mut config =
+            nginx_lint_plugin::parse_string("server_name example.com example.com;\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server");
+        assert_eq!(plugin.check(&config, "test.conf").len(), 1);
+
+        let mut config = nginx_lint_plugin::parse_string(
+            "server {\n    server_name a.example.com;\n}\nserver {\n    server_name b.example.com;\n}\n",
+        )
+        .unwrap();
+        config.include_context = IncludeContext::parse("http");
+        assert_eq!(plugin.check(&config, "test.conf").len(), 1);
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(SuspiciousServerNamePlugin);
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(SuspiciousServerNamePlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -99,6 +99,7 @@ impl Plugin for TryFilesWithProxyPlugin {
             "Warns when try_files and proxy_pass are used in the same location block",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "When both try_files and proxy_pass are in the same location block, \
              proxy_pass becomes the content handler and try_files only performs URI \
@@ -331,7 +332,8 @@ proxy_pass http://backend;
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = TryFilesWithProxyPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -359,7 +361,7 @@ proxy_pass http://backend;
         .unwrap();
 
         // Simulate being included from http > server context (not location)
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = TryFilesWithProxyPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -387,7 +389,8 @@ try_files $uri $uri/ @backend;
             "http".to_string(),
             "server".to_string(),
             "location".to_string(),
-        ];
+        ]
+        .into();
 
         let plugin = TryFilesWithProxyPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -47,6 +47,16 @@ impl LocationInfo {
             .iter()
             .map(|a| a.as_str().to_string())
             .collect();
+        Self::from_args(
+            &args,
+            directive.span.start.line,
+            directive.span.start.column,
+        )
+    }
+
+    /// Build from location arguments (`[modifier] pattern`), e.g. from an
+    /// include context frame
+    fn from_args(args: &[String], line: usize, column: usize) -> Option<Self> {
         if args.is_empty() {
             return None;
         }
@@ -69,8 +79,8 @@ impl LocationInfo {
         Some(LocationInfo {
             modifier,
             pattern,
-            line: directive.span.start.line,
-            column: directive.span.start.column,
+            line,
+            column,
             display,
         })
     }
@@ -82,6 +92,11 @@ impl LocationInfo {
     fn is_prefix_no_regex(&self) -> bool {
         self.modifier == "^~"
     }
+
+    /// Plain or `^~` prefix location
+    fn is_prefix(&self) -> bool {
+        self.modifier.is_empty() || self.is_prefix_no_regex()
+    }
 }
 
 impl UnreachableLocationPlugin {
@@ -104,6 +119,39 @@ impl UnreachableLocationPlugin {
         self.check_prefix_no_regex_shadowing(&locations, errors);
     }
 
+    /// Check that locations nested in a prefix location stay under its prefix.
+    ///
+    /// nginx rejects (`location "..." is outside location "..."`) a nested
+    /// prefix or exact location that does not start with the enclosing prefix.
+    fn check_nested_locations(
+        &self,
+        parent: &LocationInfo,
+        items: &[ConfigItem],
+        errors: &mut Vec<LintError>,
+    ) {
+        if !parent.is_prefix() {
+            return;
+        }
+        let err = PluginSpec::new("unreachable-location", "best-practices", "").error_builder();
+
+        for item in items {
+            if let ConfigItem::Directive(directive) = item
+                && let Some(loc) = LocationInfo::from_directive(directive)
+                && !loc.is_regex()
+                && !loc.pattern.starts_with(&parent.pattern)
+            {
+                errors.push(err.warning(
+                    &format!(
+                        "Location '{}' is outside the enclosing location '{}'",
+                        loc.display, parent.display
+                    ),
+                    loc.line,
+                    loc.column,
+                ));
+            }
+        }
+    }
+
     /// Check for duplicate location paths (same modifier and pattern)
     fn check_duplicate_locations(&self, locations: &[LocationInfo], errors: &mut Vec<LintError>) {
         let mut seen: HashMap<String, &LocationInfo> = HashMap::new();
@@ -410,6 +458,7 @@ impl Plugin for UnreachableLocationPlugin {
             "Detects location blocks that may never be evaluated",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "nginx's location matching follows specific rules:\n\
              1. Exact match (`=`) has highest priority\n\
@@ -446,6 +495,17 @@ impl Plugin for UnreachableLocationPlugin {
             self.check_server_locations(&config.items, &mut errors);
         }
 
+        // If included from inside a location whose match is known (e.g.
+        // `--context "http,server,location=/api/"`), top-level locations
+        // are nested in it
+        if let Some(parent) = config
+            .include_context
+            .innermost("location")
+            .and_then(|frame| LocationInfo::from_args(&frame.args, 0, 0))
+        {
+            self.check_nested_locations(&parent, &config.items, &mut errors);
+        }
+
         self.check_items(&config.items, &mut errors);
         errors
     }
@@ -827,7 +887,7 @@ location /api {
         .unwrap();
 
         // Simulate being included from http > server context
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = UnreachableLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -859,7 +919,7 @@ location ~ /api/v1 {
         .unwrap();
 
         // Simulate being included from http > server context
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = UnreachableLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -873,6 +933,43 @@ location ~ /api/v1 {
         assert!(errors[0].message.contains("may never match"));
     }
 
+    #[test]
+    fn test_include_context_from_location_outside_prefix() {
+        use nginx_lint_plugin::parse_string;
+
+        let mut config = parse_string(
+            r#"
+location /api/v1/ {
+    proxy_pass http://v1;
+}
+location /static/ {
+    root /srv;
+}
+location ~ \.json$ {
+    default_type application/json;
+}
+"#,
+        )
+        .unwrap();
+
+        config.include_context = IncludeContext::parse("http,server,location=/api/");
+
+        let plugin = UnreachableLocationPlugin;
+        let errors = plugin.check(&config, "test.conf");
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert!(
+            errors[0]
+                .message
+                .contains("'/static/' is outside the enclosing location '/api/'")
+        );
+
+        // Without the location's arguments the nesting cannot be checked
+        config.include_context = IncludeContext::parse("http,server,location");
+        assert!(plugin.check(&config, "test.conf").is_empty());
+    }
+
     #[test]
     fn test_include_context_from_http_no_error() {
         // Test that locations at http level (not server) don't trigger
@@ -891,7 +988,7 @@ location /api {
         .unwrap();
 
         // Simulate being included from http context only (not server)
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let plugin = UnreachableLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -922,7 +1019,7 @@ location /api {
         .unwrap();
 
         // Simulate being included from http > server context
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = UnreachableLocationPlugin;
         let errors = plugin.check(&config, "test.conf");
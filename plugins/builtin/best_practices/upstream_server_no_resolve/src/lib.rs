@@ -63,6 +63,7 @@ impl Plugin for UpstreamServerNoResolvePlugin {
             "Warns when upstream server uses a domain without 'resolve' or when 'resolve' is used without 'zone'",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "When upstream server specifies a domain name directly without 'resolve', nginx resolves \
              the DNS at startup and caches the IP address. If the IP address changes, nginx \
@@ -0,0 +1,365 @@
+//! upstream-zone-too-small plugin
+//!
+//! This plugin warns when the shared memory `zone` of an upstream group is
+//! too small for the servers it has to hold, when some of them use
+//! `resolve`.
+//!
+//! The zone stores the group's servers. A server with `resolve` is
+//! re-resolved at runtime and stores every address its name resolves to,
+//! so a zone that fits the config at startup can run out of memory later,
+//! and the new addresses are then dropped. The size needed is estimated
+//! from the per-server figures NGINX documents for a 256k zone.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+use std::collections::BTreeMap;
+
+/// Check for upstream zones too small for their servers
+#[derive(Default)]
+pub struct UpstreamZoneTooSmallPlugin;
+
+/// Size of the zone the documented server counts are given for
+const DOCUMENTED_ZONE_SIZE: u64 = 256 * 1024;
+
+/// Servers defined as an IP address and port that fit in the documented zone
+const IP_SERVERS_PER_ZONE: u64 = 128;
+
+/// Servers defined as a hostname resolving to a single address that fit in
+/// the documented zone
+const HOSTNAME_SERVERS_PER_ZONE: u64 = 88;
+
+/// Servers defined as a hostname resolving to several addresses that fit in
+/// the documented zone; used for `resolve` servers, whose names may resolve
+/// to several addresses at runtime
+const RESOLVE_SERVERS_PER_ZONE: u64 = 12;
+
+/// Smallest zone nginx accepts (8 pages of 4k)
+const MIN_ZONE_SIZE: u64 = 32 * 1024;
+
+/// The servers stored in one zone, which several upstreams may share
+#[derive(Default)]
+struct ZoneUsage<'a> {
+    /// `zone` directives that set the size
+    sized: Vec<(&'a Directive, u64)>,
+    ip_servers: u64,
+    hostname_servers: u64,
+    resolve_servers: u64,
+}
+
+impl ZoneUsage<'_> {
+    fn servers(&self) -> u64 {
+        self.ip_servers + self.hostname_servers + self.resolve_servers
+    }
+
+    /// Estimated bytes needed for the servers
+    fn estimate(&self) -> u64 {
+        [
+            (self.ip_servers, IP_SERVERS_PER_ZONE),
+            (self.hostname_servers, HOSTNAME_SERVERS_PER_ZONE),
+            (self.resolve_servers, RESOLVE_SERVERS_PER_ZONE),
+        ]
+        .iter()
+        .map(|(count, per_zone)| (count * DOCUMENTED_ZONE_SIZE).div_ceil(*per_zone))
+        .sum()
+    }
+}
+
+/// Format a size for messages and fixes, in the largest exact unit
+fn format_size(bytes: u64) -> String {
+    if bytes.is_multiple_of(1024 * 1024) {
+        format!("{}m", bytes / (1024 * 1024))
+    } else if bytes.is_multiple_of(1024) {
+        format!("{}k", bytes / 1024)
+    } else {
+        bytes.to_string()
+    }
+}
+
+/// Round an estimate up to a zone size to suggest: the next power of two
+/// kilobytes, and at least the smallest zone nginx accepts
+fn suggested_size(estimate: u64) -> u64 {
+    estimate
+        .div_ceil(1024)
+        .next_power_of_two()
+        .max(MIN_ZONE_SIZE / 1024)
+        * 1024
+}
+
+impl UpstreamZoneTooSmallPlugin {
+    /// Add the servers of an upstream block to its zone's usage
+    fn collect_upstream<'a>(upstream: &'a Directive, zones: &mut BTreeMap<&'a str, ZoneUsage<'a>>) {
+        let Some(block) = &upstream.block else {
+            return;
+        };
+        let directives = || {
+            block.items.iter().filter_map(|item| match item {
+                ConfigItem::Directive(d) => Some(d.as_ref()),
+                _ => None,
+            })
+        };
+
+        let Some(zone) = directives().find(|d| d.is("zone")) else {
+            return;
+        };
+        let Some(name) = zone.first_arg() else {
+            return;
+        };
+        let usage = zones.entry(name).or_default();
+        if let Some(size) = zone
+            .args
+            .get(1)
+            .and_then(|arg| helpers::parse_size(arg.as_str()))
+        {
+            usage.sized.push((zone, size));
+        }
+
+        for server in directives().filter(|d| d.is("server")) {
+            let Some(address) = server.first_arg() else {
+                continue;
+            };
+            if server.args.iter().any(|arg| arg.as_str() == "resolve") {
+                usage.resolve_servers += 1;
+            } else if helpers::is_domain_name(address) {
+                usage.hostname_servers += 1;
+            } else {
+                usage.ip_servers += 1;
+            }
+        }
+    }
+}
+
+impl Plugin for UpstreamZoneTooSmallPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "upstream-zone-too-small",
+            "best-practices",
+            "Warns when an upstream zone is too small for its servers that use 'resolve'",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "An upstream group's 'zone' holds its servers in shared memory. A server with \
+             'resolve' is re-resolved while nginx runs and keeps every address its name \
+             resolves to, so the memory it needs grows at runtime. When the zone is full, \
+             nginx logs that it could not allocate memory and the new addresses are not \
+             used, even though the config loaded fine.\n\n\
+             The minimum size is estimated from NGINX's documented figures for a 256k zone: \
+             about 128 servers defined as IP address and port, 88 defined by a hostname \
+             resolving to one address, or 12 defined by a hostname resolving to several \
+             addresses. 'resolve' servers are counted as the latter. The figures are for a \
+             group with session persistence and a health check, so they leave some headroom \
+             for simpler groups.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_upstream_module.html#zone".to_string(),
+            "https://docs.nginx.com/nginx/admin-guide/load-balancer/http-load-balancer/#sharing-data-with-multiple-worker-processes".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["upstream"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let err = self.spec().error_builder();
+        let fixes = FixBuilder::new(config);
+
+        // Upstreams naming the same zone share its memory
+        let mut zones: BTreeMap<&str, ZoneUsage> = BTreeMap::new();
+        for directive in config.all_directives().filter(|d| d.is("upstream")) {
+            Self::collect_upstream(directive, &mut zones);
+        }
+
+        for (name, usage) in &zones {
+            if usage.resolve_servers == 0 {
+                continue;
+            }
+            let estimate = usage.estimate();
+            let suggestion = format_size(suggested_size(estimate));
+            for (zone, size) in &usage.sized {
+                if *size >= estimate {
+                    continue;
+                }
+                let message = format!(
+                    "upstream zone '{}' is {}, but its {} server(s) ({} with 'resolve') need about {}; \
+                     increase it to {} so runtime DNS updates do not exhaust shared memory",
+                    name,
+                    format_size(*size),
+                    usage.servers(),
+                    usage.resolve_servers,
+                    format_size(estimate.div_ceil(1024) * 1024),
+                    suggestion
+                );
+                let mut error = err.warning_at(&message, *zone);
+                if let Some(fix) = fixes.replace_argument(zone, 1, &suggestion) {
+                    error = error.with_fix(fix);
+                }
+                errors.push(error);
+            }
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(UpstreamZoneTooSmallPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_suggested_size() {
+        assert_eq!(suggested_size(10), MIN_ZONE_SIZE);
+        assert_eq!(suggested_size(65 * 1024), 128 * 1024);
+        assert_eq!(suggested_size(1024 * 1024), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_zone_too_small_for_resolve_servers() {
+        let runner = PluginTestRunner::new(UpstreamZoneTooSmallPlugin);
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    upstream backend {
+        zone backend 64k;
+        server a.example.com resolve;
+        server b.example.com resolve;
+        server c.example.com resolve;
+        server d.example.com resolve;
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(4));
+        assert_eq!(
+            errors[0].message,
+            "upstream zone 'backend' is 64k, but its 4 server(s) (4 with 'resolve') need about 86k; \
+             increase it to 128k so runtime DNS updates do not exhaust shared memory"
+        );
+    }
+
+    #[test]
+    fn test_zone_large_enough() {
+        let runner = PluginTestRunner::new(UpstreamZoneTooSmallPlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    upstream backend {
+        zone backend 64k;
+        server a.example.com resolve;
+        server b.example.com resolve;
+        server 10.0.0.1:8080;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_no_resolve_servers() {
+        // Without 'resolve' the zone is filled at startup, where nginx
+        // reports a zone that is too small itself
+        let runner = PluginTestRunner::new(UpstreamZoneTooSmallPlugin);
+        let mut config =
+            String::from("http {\n    upstream backend {\n        zone backend 32k;\n");
+        for i in 0..40 {
+            config.push_str(&format!("        server 10.0.0.{}:8080;\n", i));
+        }
+        config.push_str("    }\n}\n");
+        runner.assert_no_errors(&config);
+    }
+
+    #[test]
+    fn test_shared_zone_counts_every_upstream() {
+        let runner = PluginTestRunner::new(UpstreamZoneTooSmallPlugin);
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    upstream api {
+        zone shared 64k;
+        server a.example.com resolve;
+        server b.example.com resolve;
+    }
+    upstream web {
+        zone shared;
+        server c.example.com resolve;
+        server d.example.com resolve;
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(4));
+        assert!(errors[0].message.contains("its 4 server(s)"));
+    }
+
+    #[test]
+    fn test_stream_upstream() {
+        let runner = PluginTestRunner::new(UpstreamZoneTooSmallPlugin);
+        runner.assert_has_errors(
+            r#"
+stream {
+    upstream dns {
+        zone dns 32k;
+        server ns1.example.com:53 resolve;
+        server ns2.example.com:53 resolve;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_fix_updates_size() {
+        let runner = PluginTestRunner::new(UpstreamZoneTooSmallPlugin);
+        runner.assert_fix_produces(
+            r#"
+http {
+    upstream backend {
+        zone backend 32k;
+        server a.example.com resolve;
+        server b.example.com resolve;
+    }
+}
+"#,
+            r#"
+http {
+    upstream backend {
+        zone backend 64k;
+        server a.example.com resolve;
+        server b.example.com resolve;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(UpstreamZoneTooSmallPlugin);
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(UpstreamZoneTooSmallPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,486 @@
+//! deprecated-directive plugin
+//!
+//! This plugin detects directives and `listen` parameters that nginx made
+//! obsolete or removed, and fixes them to their modern equivalents:
+//!
+//! - the SPDY module (`listen ... spdy`, `spdy_*`), replaced by HTTP/2 in
+//!   nginx 1.9.5
+//! - `limit_zone`, replaced by `limit_conn_zone` and removed in 1.7.6
+//! - HTTP/2 directives made obsolete by the common keepalive and header
+//!   limits in 1.19.7 and 1.21.1
+//! - `http2_push` and `http2_push_preload`, obsolete since 1.25.1
+//!
+//! With `target_nginx_version` set, directives still current in that
+//! version are not reported, and `listen ... spdy` is fixed to
+//! `listen ... http2` before 1.25.1 and to `http2 on;` from then on.
+//! `ssl on;` and `listen ... http2` are reported by ssl-on-deprecated and
+//! listen-http2-deprecated.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::NginxVersion;
+use nginx_lint_plugin::prelude::*;
+
+/// What replaces a deprecated directive
+enum Replacement {
+    /// A directive taking the same arguments
+    Rename(&'static str),
+    /// Nothing; the reason is given in the message
+    Delete(&'static str),
+    /// `limit_zone NAME $var SIZE` becomes `limit_conn_zone $var zone=NAME:SIZE`
+    LimitConnZone,
+    /// A directive taking other arguments, which the fix cannot write
+    Manual(&'static str),
+}
+
+/// A directive nginx warns about or rejects
+struct Deprecation {
+    directive: &'static str,
+    /// Version from which nginx warns about or rejects it
+    since: NginxVersion,
+    /// Whether nginx refuses to start with it from `since`, rather than
+    /// ignoring it with a warning
+    removed: bool,
+    replacement: Replacement,
+}
+
+const SPDY_REMOVED: NginxVersion = NginxVersion::new(1, 9, 5);
+const HTTP2_DIRECTIVE: NginxVersion = NginxVersion::new(1, 25, 1);
+
+const DEPRECATIONS: &[Deprecation] = &[
+    Deprecation {
+        directive: "limit_zone",
+        since: NginxVersion::new(1, 7, 6),
+        removed: true,
+        replacement: Replacement::LimitConnZone,
+    },
+    Deprecation {
+        directive: "spdy_chunk_size",
+        since: SPDY_REMOVED,
+        removed: true,
+        replacement: Replacement::Rename("http2_chunk_size"),
+    },
+    Deprecation {
+        directive: "spdy_headers_comp",
+        since: SPDY_REMOVED,
+        removed: true,
+        replacement: Replacement::Delete("HTTP/2 always compresses headers"),
+    },
+    Deprecation {
+        directive: "spdy_keepalive_timeout",
+        since: SPDY_REMOVED,
+        removed: true,
+        replacement: Replacement::Rename("keepalive_timeout"),
+    },
+    Deprecation {
+        directive: "spdy_max_concurrent_streams",
+        since: SPDY_REMOVED,
+        removed: true,
+        replacement: Replacement::Rename("http2_max_concurrent_streams"),
+    },
+    Deprecation {
+        directive: "spdy_recv_buffer_size",
+        since: SPDY_REMOVED,
+        removed: true,
+        replacement: Replacement::Rename("http2_recv_buffer_size"),
+    },
+    Deprecation {
+        directive: "spdy_recv_timeout",
+        since: SPDY_REMOVED,
+        removed: true,
+        replacement: Replacement::Rename("client_header_timeout"),
+    },
+    Deprecation {
+        directive: "spdy_pool_size",
+        since: SPDY_REMOVED,
+        removed: true,
+        replacement: Replacement::Delete("nginx sizes the pool itself"),
+    },
+    Deprecation {
+        directive: "spdy_streams_index_size",
+        since: SPDY_REMOVED,
+        removed: true,
+        replacement: Replacement::Delete("nginx sizes the index itself"),
+    },
+    Deprecation {
+        directive: "http2_idle_timeout",
+        since: NginxVersion::new(1, 19, 7),
+        removed: false,
+        replacement: Replacement::Rename("keepalive_timeout"),
+    },
+    Deprecation {
+        directive: "http2_recv_timeout",
+        since: NginxVersion::new(1, 19, 7),
+        removed: false,
+        replacement: Replacement::Rename("client_header_timeout"),
+    },
+    Deprecation {
+        directive: "http2_max_requests",
+        since: NginxVersion::new(1, 19, 7),
+        removed: false,
+        replacement: Replacement::Rename("keepalive_requests"),
+    },
+    Deprecation {
+        directive: "http2_max_field_size",
+        since: NginxVersion::new(1, 19, 7),
+        removed: false,
+        replacement: Replacement::Manual("large_client_header_buffers"),
+    },
+    Deprecation {
+        directive: "http2_max_header_size",
+        since: NginxVersion::new(1, 19, 7),
+        removed: false,
+        replacement: Replacement::Manual("large_client_header_buffers"),
+    },
+    Deprecation {
+        directive: "http2_pool_size",
+        since: NginxVersion::new(1, 21, 1),
+        removed: false,
+        replacement: Replacement::Delete("nginx sizes the pool itself"),
+    },
+    Deprecation {
+        directive: "http2_streams_index_size",
+        since: NginxVersion::new(1, 21, 1),
+        removed: false,
+        replacement: Replacement::Delete("nginx sizes the index itself"),
+    },
+    Deprecation {
+        directive: "http2_push",
+        since: HTTP2_DIRECTIVE,
+        removed: false,
+        replacement: Replacement::Delete(
+            "browsers dropped HTTP/2 server push; send 103 Early Hints or preload links instead",
+        ),
+    },
+    Deprecation {
+        directive: "http2_push_preload",
+        since: HTTP2_DIRECTIVE,
+        removed: false,
+        replacement: Replacement::Delete(
+            "browsers dropped HTTP/2 server push; send 103 Early Hints or preload links instead",
+        ),
+    },
+];
+
+/// Check for obsolete and removed directives
+#[derive(Default)]
+pub struct DeprecatedDirectivePlugin;
+
+impl DeprecatedDirectivePlugin {
+    /// Check `config` for nginx `target`, or for the latest nginx when unset
+    fn check_version(&self, config: &Config, target: Option<NginxVersion>) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let fixes = FixBuilder::new(config);
+        self.check_items(&fixes, &config.items, target, &mut errors);
+        errors
+    }
+
+    fn check_items(
+        &self,
+        fixes: &FixBuilder,
+        items: &[ConfigItem],
+        target: Option<NginxVersion>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let directives: Vec<&Directive> = items
+            .iter()
+            .filter_map(|item| match item {
+                ConfigItem::Directive(d) => Some(d.as_ref()),
+                _ => None,
+            })
+            .collect();
+
+        for directive in &directives {
+            if let Some(deprecation) = DEPRECATIONS.iter().find(|d| directive.is(d.directive))
+                && target.is_none_or(|target| target >= deprecation.since)
+            {
+                errors.push(self.report(directive, deprecation));
+            }
+            if let Some(block) = &directive.block {
+                self.check_items(fixes, &block.items, target, errors);
+            }
+        }
+
+        self.check_listen_spdy(fixes, &directives, target, errors);
+    }
+
+    fn report(&self, directive: &Directive, deprecation: &Deprecation) -> LintError {
+        let advice = match deprecation.replacement {
+            Replacement::Rename(name) | Replacement::Manual(name) => {
+                format!("use '{}' instead", name)
+            }
+            Replacement::Delete(reason) => format!("remove it, {}", reason),
+            Replacement::LimitConnZone => {
+                "use 'limit_conn_zone $variable zone=name:size' instead".to_string()
+            }
+        };
+        let err = self.spec().error_builder();
+        let error = if deprecation.removed {
+            let message = format!(
+                "'{}' was removed in nginx {}, which refuses to start with it; {}",
+                deprecation.directive, deprecation.since, advice
+            );
+            err.error_at(&message, directive)
+        } else {
+            let message = format!(
+                "'{}' is obsolete since nginx {}, which ignores it; {}",
+                deprecation.directive, deprecation.since, advice
+            );
+            err.warning_at(&message, directive)
+        };
+
+        let fix = match deprecation.replacement {
+            Replacement::Rename(name) => {
+                let start = directive.span.start.offset;
+                Some(Fix::replace_range(
+                    start,
+                    start + directive.name.len(),
+                    name,
+                ))
+            }
+            Replacement::Delete(_) => Some(directive.delete_line()),
+            Replacement::LimitConnZone => match directive.args.as_slice() {
+                [name, variable, size] => Some(directive.replace_with(&format!(
+                    "limit_conn_zone {} zone={}:{};",
+                    variable.raw, name.raw, size.raw
+                ))),
+                _ => None,
+            },
+            Replacement::Manual(_) => None,
+        };
+        match fix {
+            Some(fix) => error.with_fix(fix),
+            None => error,
+        }
+    }
+
+    /// `listen ... spdy` in a server block, fixed to HTTP/2
+    fn check_listen_spdy(
+        &self,
+        fixes: &FixBuilder,
+        directives: &[&Directive],
+        target: Option<NginxVersion>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let listens: Vec<_> = directives
+            .iter()
+            .filter(|d| d.is("listen") && d.has_arg("spdy"))
+            .collect();
+        let Some(last) = listens.last() else {
+            return;
+        };
+        let listen_parameter = target.is_some_and(|target| target < HTTP2_DIRECTIVE);
+        let has_http2_on = directives
+            .iter()
+            .any(|d| d.is("http2") && d.first_arg_is("on"));
+
+        let err = self.spec().error_builder();
+        for listen in &listens {
+            let Some(spdy) = listen.args.iter().position(|a| a.as_str() == "spdy") else {
+                continue;
+            };
+            let message = format!(
+                "'spdy' parameter in 'listen' was removed in nginx {}, which refuses to start \
+                 with it; {}",
+                SPDY_REMOVED,
+                if listen_parameter {
+                    "use 'listen ... http2' instead"
+                } else {
+                    "use 'http2 on;' instead"
+                }
+            );
+            let has_http2 = listen.has_arg("http2");
+            let fix = if listen_parameter && !has_http2 {
+                fixes.replace_argument(listen, spdy, "http2")
+            } else {
+                fixes.remove_argument(listen, spdy)
+            };
+            let mut error = err.error_at(&message, listen);
+            if let Some(fix) = fix {
+                error = error.with_fix(fix);
+            }
+            if !listen_parameter && !has_http2 && !has_http2_on && std::ptr::eq(listen, last) {
+                error = error.with_fix(listen.insert_after("http2 on;"));
+            }
+            errors.push(error);
+        }
+    }
+}
+
+impl Plugin for DeprecatedDirectivePlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "deprecated-directive",
+            "deprecation",
+            "Detects directives and listen parameters that nginx made obsolete or removed",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "nginx refuses to start with a removed directive (\"unknown directive\"), and \
+             ignores an obsolete one with a warning in the error log, so its setting silently \
+             stops applying. These directives usually survive in configurations copied from \
+             old guides.\n\n\
+             The SPDY module and its `spdy_*` directives were replaced by HTTP/2 in nginx \
+             1.9.5, and `limit_zone` by `limit_conn_zone` in 1.7.6. The HTTP/2 timeouts and \
+             limits became obsolete in 1.19.7, when HTTP/2 started to use `keepalive_timeout`, \
+             `keepalive_requests`, `client_header_timeout` and \
+             `large_client_header_buffers` like HTTP/1.1. Server push was removed in 1.25.1, \
+             as browsers no longer support it.\n\n\
+             When `target_nginx_version` is set, directives still current in that version are \
+             not reported, and fixes use what that version supports (`listen ... http2` \
+             before 1.25.1, `http2 on;` from then on). `ssl on;` and `listen ... http2` are \
+             reported by ssl-on-deprecated and listen-http2-deprecated.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_v2_module.html".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_limit_conn_module.html#limit_conn_zone"
+                .to_string(),
+            "https://nginx.org/en/CHANGES".to_string(),
+        ])
+        .with_min_version("1.7.6")
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&[
+            "limit_zone",
+            "listen",
+            "http2",
+            "spdy_chunk_size",
+            "spdy_headers_comp",
+            "spdy_keepalive_timeout",
+            "spdy_max_concurrent_streams",
+            "spdy_recv_buffer_size",
+            "spdy_recv_timeout",
+            "spdy_pool_size",
+            "spdy_streams_index_size",
+            "http2_idle_timeout",
+            "http2_recv_timeout",
+            "http2_max_requests",
+            "http2_max_field_size",
+            "http2_max_header_size",
+            "http2_pool_size",
+            "http2_streams_index_size",
+            "http2_push",
+            "http2_push_preload",
+        ])
+    }
+
+    fn check_with_context(&self, config: &Config, ctx: &CheckContext) -> Vec<LintError> {
+        let target = ctx
+            .target_nginx_version()
+            .and_then(|version| NginxVersion::parse(version).ok());
+        self.check_version(config, target)
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        self.check_version(config, None)
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(DeprecatedDirectivePlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check_for(version: &str, source: &str) -> Vec<LintError> {
+        let config = parse_string(source).unwrap();
+        let ctx = CheckContext::new(&config, "test.conf").with_target_nginx_version(Some(version));
+        DeprecatedDirectivePlugin.check_with_context(&config, &ctx)
+    }
+
+    #[test]
+    fn test_messages() {
+        let errors = PluginTestRunner::new(DeprecatedDirectivePlugin)
+            .check_string("http {\n    http2_push_preload on;\n    spdy_chunk_size 8k;\n}\n")
+            .unwrap();
+        let messages: Vec<_> = errors
+            .iter()
+            .map(|e| (e.severity, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (
+                    Severity::Warning,
+                    "'http2_push_preload' is obsolete since nginx 1.25.1, which ignores it; \
+                     remove it, browsers dropped HTTP/2 server push; send 103 Early Hints or \
+                     preload links instead"
+                ),
+                (
+                    Severity::Error,
+                    "'spdy_chunk_size' was removed in nginx 1.9.5, which refuses to start with \
+                     it; use 'http2_chunk_size' instead"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fixes() {
+        let runner = PluginTestRunner::new(DeprecatedDirectivePlugin);
+        runner.assert_fix_produces(
+            "http {\n    http2_idle_timeout 3m;\n    http2_push_preload on;\n    limit_zone one $binary_remote_addr 10m;\n}\n",
+            "http {\n    keepalive_timeout 3m;\n    limit_conn_zone $binary_remote_addr zone=one:10m;\n}\n",
+        );
+        runner.assert_fix_produces(
+            "server {\n    listen 443 ssl spdy;\n    listen [::]:443 ssl spdy;\n}\n",
+            "server {\n    listen 443 ssl;\n    listen [::]:443 ssl;\n    http2 on;\n}\n",
+        );
+        runner.assert_fix_produces(
+            "server {\n    listen 443 ssl spdy;\n    http2 on;\n}\n",
+            "server {\n    listen 443 ssl;\n    http2 on;\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_target_version() {
+        let source = "http {\n    http2_push_preload on;\n    http2_max_requests 1000;\n}\n";
+        // Still current before 1.25.1 and 1.19.7
+        assert_eq!(check_for("1.24.0", source).len(), 1);
+        assert!(check_for("1.18.0", source).is_empty());
+        assert_eq!(check_for("1.25.1", source).len(), 2);
+
+        // The http2 listen parameter before 1.25.1
+        let errors = check_for("1.24.0", "server {\n    listen 443 ssl spdy;\n}\n");
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .ends_with("use 'listen ... http2' instead")
+        );
+        assert_eq!(errors[0].fixes.len(), 1);
+        let errors = check_for("1.27.0", "server {\n    listen 443 ssl spdy;\n}\n");
+        assert!(errors[0].message.ends_with("use 'http2 on;' instead"));
+        assert_eq!(errors[0].fixes.len(), 2);
+    }
+
+    #[test]
+    fn test_current_directives() {
+        PluginTestRunner::new(DeprecatedDirectivePlugin).assert_no_errors(
+            "http {\n    limit_conn_zone $binary_remote_addr zone=one:10m;\n    http2_chunk_size 8k;\n    server {\n        listen 443 ssl http2;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        PluginTestRunner::new(DeprecatedDirectivePlugin).test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        PluginTestRunner::new(DeprecatedDirectivePlugin)
+            .test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -23,6 +23,7 @@ impl Plugin for ListenHttp2DeprecatedPlugin {
             "Detects the deprecated 'http2' parameter in 'listen' directive (use 'http2 on;' instead)",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "The 'http2' parameter on the 'listen' directive was deprecated in nginx 1.25.1. \
              Use the standalone 'http2 on;' directive instead. \
@@ -9,8 +9,12 @@
 //! cargo build --target wasm32-unknown-unknown --release
 //! ```
 
+use nginx_lint_plugin::NginxVersion;
 use nginx_lint_plugin::prelude::*;
 
+/// Version that stopped accepting `ssl on;`
+const SSL_REMOVED: NginxVersion = NginxVersion::new(1, 25, 1);
+
 /// Check for deprecated `ssl on;` directive
 #[derive(Default)]
 pub struct SslOnDeprecatedPlugin;
@@ -23,6 +27,7 @@ impl Plugin for SslOnDeprecatedPlugin {
             "Detects the deprecated 'ssl on;' directive (use 'listen ... ssl;' instead)",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "The 'ssl on;' directive was deprecated in nginx 1.15.0 and \
              removed in nginx 1.25.1. Use the 'ssl' parameter on the \
@@ -42,18 +47,39 @@ impl Plugin for SslOnDeprecatedPlugin {
         Some(&["listen", "ssl"])
     }
 
+    fn check_with_context(&self, config: &Config, ctx: &CheckContext) -> Vec<LintError> {
+        let removed = ctx
+            .target_nginx_version()
+            .and_then(|version| NginxVersion::parse(version).ok())
+            .is_some_and(|version| version >= SSL_REMOVED);
+        let mut errors = Vec::new();
+        let err = self.spec().error_builder();
+
+        check_items(&config.items, &err, removed, &mut errors);
+
+        errors
+    }
+
     fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
         let mut errors = Vec::new();
         let err = self.spec().error_builder();
 
-        check_items(&config.items, &err, &mut errors);
+        check_items(&config.items, &err, false, &mut errors);
 
         errors
     }
 }
 
 /// Check a list of config items for `ssl on;` and generate fixes
-fn check_items(items: &[ConfigItem], err: &ErrorBuilder, errors: &mut Vec<LintError>) {
+///
+/// `removed` reports `ssl on;` as an error, for target versions that
+/// refuse to start with it.
+fn check_items(
+    items: &[ConfigItem],
+    err: &ErrorBuilder,
+    removed: bool,
+    errors: &mut Vec<LintError>,
+) {
     let mut listen_without_ssl: Vec<&Directive> = Vec::new();
     let mut ssl_on_directives: Vec<&Directive> = Vec::new();
 
@@ -67,16 +93,26 @@ fn check_items(items: &[ConfigItem], err: &ErrorBuilder, errors: &mut Vec<LintEr
             }
             // Recurse into child blocks
             if let Some(block) = &directive.block {
-                check_items(&block.items, err, errors);
+                check_items(&block.items, err, removed, errors);
             }
         }
     }
 
     for ssl_dir in &ssl_on_directives {
-        let mut error = err.warning_at(
-            "'ssl on;' is deprecated, use 'listen ... ssl;' instead",
-            ssl_dir,
-        );
+        let mut error = if removed {
+            err.error_at(
+                &format!(
+                    "'ssl on;' was removed in nginx {}, which refuses to start with it; use 'listen ... ssl;' instead",
+                    SSL_REMOVED
+                ),
+                ssl_dir,
+            )
+        } else {
+            err.warning_at(
+                "'ssl on;' is deprecated, use 'listen ... ssl;' instead",
+                ssl_dir,
+            )
+        };
 
         // Delete `ssl on;`
         error = error.with_fix(ssl_dir.delete_line());
@@ -283,4 +319,22 @@ server {
         let spec = SslOnDeprecatedPlugin.spec();
         assert_eq!(spec.min_nginx_version.as_deref(), Some("1.15.0"));
     }
+
+    #[test]
+    fn test_removed_in_target_version() {
+        let config = nginx_lint_plugin::parse_string("server {\n    ssl on;\n}\n").unwrap();
+        let check = |version| {
+            let ctx =
+                CheckContext::new(&config, "test.conf").with_target_nginx_version(Some(version));
+            SslOnDeprecatedPlugin.check_with_context(&config, &ctx)
+        };
+        let errors = check("1.25.1");
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert!(
+            errors[0]
+                .message
+                .starts_with("'ssl on;' was removed in nginx 1.25.1")
+        );
+        assert_eq!(check("1.24.0")[0].severity, Severity::Warning);
+    }
 }
@@ -0,0 +1,303 @@
+//! njs-module-reference plugin
+//!
+//! This plugin checks the njs (`js_*`) directives: `js_import` must have
+//! the form `js_import [name from] module.js;` with names unique within a
+//! block, and handlers (`js_content`, `js_set`, ...) must be written as
+//! `module.function` with a module some `js_import` declares.
+//!
+//! Checking that the module files exist and export the handlers needs
+//! filesystem access, which plugins do not have; the CLI does it with
+//! `--check-paths`.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::njs::{FUNCTION_DIRECTIVES, FunctionRef, JsImport};
+use nginx_lint_plugin::prelude::*;
+use std::collections::HashSet;
+
+/// Check js_import syntax and the modules njs handlers refer to
+#[derive(Default)]
+pub struct NjsModuleReferencePlugin;
+
+impl NjsModuleReferencePlugin {
+    /// Report malformed and duplicate imports of every block
+    fn check_imports(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        let err = self.spec().error_builder();
+        let mut names = HashSet::new();
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if directive.is("js_import") {
+                match JsImport::parse(directive) {
+                    None => errors.push(err.error_at(
+                        "js_import expects 'module.js' or 'name from module.js'",
+                        directive,
+                    )),
+                    Some(import) if !names.insert(import.name.clone()) => {
+                        errors.push(err.error_at(
+                            &format!(
+                                "js_import name '{}' is already imported in this block; nginx refuses to start",
+                                import.name
+                            ),
+                            directive,
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+            if let Some(block) = &directive.block {
+                self.check_imports(&block.items, errors);
+            }
+        }
+    }
+}
+
+impl Plugin for NjsModuleReferencePlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "njs-module-reference",
+            "interop",
+            "Detects malformed js_import directives and njs handlers naming no imported module",
+        )
+        .with_severity("error")
+        .with_preset(RulePreset::Minimal)
+        .with_why(
+            "njs handlers are written as `module.function`, where `module` is the name a \
+             `js_import` gives the module: the name before `from`, or the file name without \
+             its extension. nginx refuses to start when a handler names a module that is not \
+             imported, and bare function names stopped working when `js_include` was removed \
+             in njs 0.7.1.\n\n\
+             Handlers are only checked against the imports of files that neither include \
+             other files nor are included themselves, since an import may otherwise be \
+             declared elsewhere.\n\n\
+             Run `nginx-lint --check-paths` to also check that imported module files exist \
+             (below a `js_path` or the configuration directory) and export the handlers \
+             named.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_js_module.html#js_import".to_string(),
+            "https://nginx.org/en/docs/njs/".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&[
+            "include",
+            "js_import",
+            "js_include",
+            "js_content",
+            "js_set",
+            "js_body_filter",
+            "js_header_filter",
+            "js_periodic",
+            "js_access",
+            "js_preread",
+            "js_filter",
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let err = self.spec().error_builder();
+        let mut errors = Vec::new();
+        self.check_imports(&config.items, &mut errors);
+
+        let legacy_include = config.all_directives().any(|d| d.is("js_include"));
+        // Every import is in this file only when it is a whole configuration
+        let imports: Option<HashSet<String>> = (config.include_context.is_empty()
+            && !config.all_directives().any(|d| d.is("include")))
+        .then(|| {
+            config
+                .all_directives()
+                .filter_map(JsImport::parse)
+                .map(|import| import.name)
+                .collect()
+        });
+
+        for handler in config.all_directives().filter_map(FunctionRef::parse) {
+            let directive = handler.directive;
+            let index = FUNCTION_DIRECTIVES
+                .iter()
+                .find(|(name, _)| directive.is(name))
+                .map_or(0, |(_, index)| *index);
+            match handler.module {
+                None if !legacy_include => errors.push(err.error_at_arg(
+                    &format!(
+                        "{} '{}' must name a function as 'module.function' of a js_import; bare names only worked with js_include, removed in njs 0.7.1",
+                        directive.name, handler.function
+                    ),
+                    directive,
+                    index,
+                )),
+                Some(module)
+                    if imports
+                        .as_ref()
+                        .is_some_and(|imports| !imports.contains(module)) =>
+                {
+                    errors.push(err.error_at_arg(
+                        &format!(
+                            "{} refers to module '{}', which no js_import declares",
+                            directive.name, module
+                        ),
+                        directive,
+                        index,
+                    ))
+                }
+                _ => {}
+            }
+        }
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(NjsModuleReferencePlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(source: &str) -> Vec<LintError> {
+        let config = parse_string(source).unwrap();
+        NjsModuleReferencePlugin.check(&config, "test.conf")
+    }
+
+    fn messages(errors: &[LintError]) -> Vec<(usize, &str)> {
+        errors
+            .iter()
+            .map(|e| (e.line.unwrap(), e.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unknown_module_and_bare_name() {
+        let errors = check(
+            r#"
+http {
+    js_import main from http.js;
+    js_import utils.js;
+    js_set $token utils.token;
+    server {
+        location / {
+            js_content http.hello;
+        }
+        location /legacy {
+            js_content hello;
+        }
+    }
+}
+"#,
+        );
+        assert_eq!(
+            messages(&errors),
+            [
+                (
+                    8,
+                    "js_content refers to module 'http', which no js_import declares"
+                ),
+                (
+                    11,
+                    "js_content 'hello' must name a function as 'module.function' of a js_import; bare names only worked with js_include, removed in njs 0.7.1"
+                ),
+            ]
+        );
+        assert_eq!(errors[0].column, Some(24));
+    }
+
+    #[test]
+    fn test_malformed_and_duplicate_imports() {
+        let errors = check(
+            r#"
+http {
+    js_import main http.js;
+    js_import main from a.js;
+    js_import main from b.js;
+    server {
+        js_import main from c.js;
+    }
+}
+"#,
+        );
+        assert_eq!(
+            messages(&errors),
+            [
+                (3, "js_import expects 'module.js' or 'name from module.js'"),
+                (
+                    5,
+                    "js_import name 'main' is already imported in this block; nginx refuses to start"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imports_elsewhere_are_not_assumed_missing() {
+        let runner = PluginTestRunner::new(NjsModuleReferencePlugin);
+        // The import may be in the included files
+        runner.assert_no_errors(
+            r#"
+http {
+    include conf.d/*.conf;
+    server {
+        location / {
+            js_content main.hello;
+        }
+    }
+}
+"#,
+        );
+
+        // Or in the file including this one
+        let mut config = parse_string("location / {\n    js_content main.hello;\n}\n").unwrap();
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
+        assert!(
+            NjsModuleReferencePlugin
+                .check(&config, "test.conf")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_stream_handlers_and_legacy_include() {
+        let errors = check(
+            r#"
+stream {
+    js_import stream.js;
+    server {
+        listen 12345;
+        js_access stream.access;
+        js_preread auth.preread;
+        js_filter filter;
+    }
+}
+"#,
+        );
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(7));
+        assert_eq!(errors[1].line, Some(8));
+
+        let runner = PluginTestRunner::new(NjsModuleReferencePlugin);
+        runner.assert_no_errors("http {\n    js_include http.js;\n    js_content hello;\n}\n");
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(NjsModuleReferencePlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(NjsModuleReferencePlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,437 @@
+//! ssl-performance-not-tuned plugin
+//!
+//! This advisory plugin checks TLS servers for the settings that make
+//! handshakes cheap:
+//!
+//! - `ssl_session_cache` and `ssl_session_timeout`, without which sessions
+//!   are not shared between workers and expire after 5 minutes
+//! - `ssl_stapling on` without a `resolver`, which nginx needs to reach the
+//!   OCSP responder
+//! - optionally (`require_stapling`), servers without `ssl_stapling`
+//!
+//! The settings are looked up through the `server` and `http` blocks. Files
+//! included in `http` are only checked for what their servers set, and files
+//! included in a server are not checked.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedSettings};
+use nginx_lint_plugin::prelude::*;
+
+/// Check TLS servers for session caching and OCSP stapling settings
+pub struct SslPerformanceNotTunedPlugin {
+    /// Value the fix sets for `ssl_session_cache` (option `session_cache`)
+    session_cache: String,
+    /// Value the fix sets for `ssl_session_timeout` (option
+    /// `session_timeout`)
+    session_timeout: String,
+    /// Whether TLS servers without `ssl_stapling` are reported (option
+    /// `require_stapling`)
+    require_stapling: bool,
+}
+
+impl Default for SslPerformanceNotTunedPlugin {
+    fn default() -> Self {
+        Self {
+            session_cache: "shared:SSL:10m".to_string(),
+            session_timeout: "1d".to_string(),
+            require_stapling: false,
+        }
+    }
+}
+
+/// The settings checked, as in effect in a block
+type Settings<'a> = InheritedSettings<'a, 6>;
+
+/// The directives followed through the blocks
+const SETTINGS: [&str; 6] = [
+    "ssl",
+    "ssl_session_cache",
+    "ssl_session_timeout",
+    "ssl_stapling",
+    "ssl_stapling_file",
+    "resolver",
+];
+
+/// Whether `server` accepts TLS connections
+fn is_tls(block: &Block, settings: &Settings<'_>) -> bool {
+    block
+        .directives()
+        .any(|d| d.is("listen") && d.has_arg("ssl"))
+        || settings
+            .get("ssl")
+            .directive()
+            .is_some_and(|ssl| ssl.first_arg_is("on"))
+}
+
+/// Fix adding `lines` to a server block, after its last `listen`
+fn insert_fix(block: &Block, lines: &[&str]) -> Option<Fix> {
+    let anchor = block
+        .directives()
+        .filter(|d| d.is("listen"))
+        .last()
+        .or_else(|| block.directives().next())?;
+    Some(anchor.insert_after_many(lines))
+}
+
+impl SslPerformanceNotTunedPlugin {
+    /// Check the servers directly in `items` (an `http` block, or the top
+    /// level of a file included in `http`)
+    fn check_servers<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: Settings<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let http = inherited.enter(items);
+        // Missing session settings are added to the http block, before its
+        // first server, when it is in this file
+        let http_anchor = match inherited.get("ssl_session_cache") {
+            Inherited::Unknown => None,
+            _ => items.iter().find_map(|item| match item {
+                ConfigItem::Directive(d) if d.is("server") => Some(d.as_ref()),
+                _ => None,
+            }),
+        };
+        let mut added_to_http = Vec::new();
+
+        for item in items {
+            let ConfigItem::Directive(server) = item else {
+                continue;
+            };
+            if !server.is("server") {
+                continue;
+            }
+            let Some(block) = &server.block else {
+                continue;
+            };
+            let settings = http.enter(&block.items);
+            if !is_tls(block, &settings) {
+                continue;
+            }
+            self.check_session(
+                server,
+                block,
+                &settings,
+                http_anchor,
+                &mut added_to_http,
+                errors,
+            );
+            self.check_stapling(server, block, &settings, errors);
+        }
+    }
+
+    /// Report missing session settings; `added_to_http` holds the lines an
+    /// earlier error's fix adds to http
+    fn check_session(
+        &self,
+        server: &Directive,
+        block: &Block,
+        settings: &Settings<'_>,
+        http_anchor: Option<&Directive>,
+        added_to_http: &mut Vec<String>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let cache = format!("ssl_session_cache {};", self.session_cache);
+        let timeout = format!("ssl_session_timeout {};", self.session_timeout);
+        let mut missing = Vec::new();
+        let mut lines = Vec::new();
+        if settings.get("ssl_session_cache").is_default() {
+            missing.push("ssl_session_cache (sessions are not shared between workers)");
+            lines.push(cache.as_str());
+        }
+        if settings.get("ssl_session_timeout").is_default() {
+            missing.push("ssl_session_timeout (sessions expire after nginx's default of 5m)");
+            lines.push(timeout.as_str());
+        }
+        if missing.is_empty() {
+            return;
+        }
+
+        let message = format!(
+            "TLS server without {}; reconnecting clients pay for a full handshake; consider {}",
+            missing.join(" or "),
+            lines.join(" ")
+        );
+        let error = self.spec().error_builder().warning_at(&message, server);
+        // The missing lines are set neither in http nor in this server, so
+        // adding them to http applies them here. Only the first error adds
+        // them there, as identical inserts would all be applied; later
+        // servers only add what it does not.
+        let fix = match http_anchor {
+            Some(first) if added_to_http.is_empty() => {
+                added_to_http.extend(lines.iter().map(|line| line.to_string()));
+                Some(first.insert_before_many(&lines))
+            }
+            _ => {
+                lines.retain(|line| !added_to_http.iter().any(|added| added == line));
+                if lines.is_empty() {
+                    None
+                } else {
+                    insert_fix(block, &lines)
+                }
+            }
+        };
+        errors.push(match fix {
+            Some(fix) => error.with_fix(fix),
+            None => error,
+        });
+    }
+
+    fn check_stapling(
+        &self,
+        server: &Directive,
+        block: &Block,
+        settings: &Settings<'_>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let err = self.spec().error_builder();
+        match settings.get("ssl_stapling") {
+            Inherited::Set(stapling)
+                if stapling.first_arg_is("on")
+                    && settings.get("resolver").is_default()
+                    && settings.get("ssl_stapling_file").is_default() =>
+            {
+                let message = format!(
+                    "ssl_stapling on (line {}) without a resolver; nginx cannot look up the \
+                     OCSP responder, so no OCSP response is stapled",
+                    stapling.line()
+                );
+                errors.push(err.warning_at(&message, server));
+            }
+            Inherited::Default if self.require_stapling => {
+                let message = "TLS server without ssl_stapling; clients have to query the \
+                               CA's OCSP responder themselves; consider ssl_stapling on \
+                               with ssl_stapling_verify on and a resolver";
+                let error = err.warning_at(message, server);
+                errors.push(
+                    match insert_fix(block, &["ssl_stapling on;", "ssl_stapling_verify on;"]) {
+                        Some(fix) => error.with_fix(fix),
+                        None => error,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Plugin for SslPerformanceNotTunedPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "ssl-performance-not-tuned",
+            "performance",
+            "Suggests ssl_session_cache, ssl_session_timeout and a resolver for OCSP stapling on TLS servers",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Strict)
+        .with_why(
+            "A full TLS handshake costs the client extra round trips and the server an \
+             asymmetric key operation. A returning client can skip it by resuming its session, \
+             but without `ssl_session_cache` sessions are only kept by the worker that created \
+             them, and `ssl_session_timeout` defaults to 5 minutes. A shared cache of 10 MB \
+             holds about 40,000 sessions; keeping them for a day lets most returning visitors \
+             resume.\n\n\
+             `ssl_stapling on` sends the certificate's OCSP status with the handshake, so the \
+             client does not have to ask the CA. nginx fetches the status from the responder \
+             named in the certificate, which needs a `resolver`; without one stapling silently \
+             does nothing.\n\n\
+             The fix adds the session settings to the `http` block when it is in the same file, \
+             and to the server otherwise. With the `require_stapling` option, TLS servers \
+             without `ssl_stapling` are also reported; many CAs no longer run OCSP responders, \
+             so it is off by default. The rule is disabled by default.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/configuring_https_servers.html#optimization"
+                .to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_ssl_module.html#ssl_session_cache"
+                .to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_ssl_module.html#ssl_stapling".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#resolver".to_string(),
+        ])
+        .with_option(RuleOption::string(
+            "session_cache",
+            "shared:SSL:10m",
+            "Value the fix sets for ssl_session_cache",
+        ))
+        .with_option(RuleOption::string(
+            "session_timeout",
+            "1d",
+            "Value the fix sets for ssl_session_timeout",
+        ))
+        .with_option(RuleOption::bool(
+            "require_stapling",
+            false,
+            "Also report TLS servers without ssl_stapling",
+        ))
+    }
+
+    fn configure(&mut self, options: &RuleOptions) {
+        self.session_cache = options.string("session_cache").to_string();
+        self.session_timeout = options.string("session_timeout").to_string();
+        self.require_stapling = options.bool("require_stapling");
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["server"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let context = &config.include_context;
+
+        if context.contains("server") {
+            // The server block, and its settings, are in the including file
+        } else if context.contains("http") {
+            self.check_servers(
+                &config.items,
+                Settings::new(SETTINGS, Inherited::Unknown),
+                &mut errors,
+            );
+        } else {
+            for http in config.directives().filter(|d| d.is("http")) {
+                if let Some(block) = &http.block {
+                    self.check_servers(
+                        &block.items,
+                        Settings::new(SETTINGS, Inherited::Default),
+                        &mut errors,
+                    );
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(SslPerformanceNotTunedPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+    use std::collections::HashMap;
+
+    fn runner() -> PluginTestRunner<SslPerformanceNotTunedPlugin> {
+        PluginTestRunner::new(SslPerformanceNotTunedPlugin::default())
+    }
+
+    #[test]
+    fn test_missing_session_settings() {
+        let errors = runner()
+            .check_string("http {\n    server {\n        listen 443 ssl;\n    }\n}\n")
+            .unwrap();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(2));
+        assert_eq!(
+            errors[0].message,
+            "TLS server without ssl_session_cache (sessions are not shared between workers) or \
+             ssl_session_timeout (sessions expire after nginx's default of 5m); reconnecting \
+             clients pay for a full handshake; consider ssl_session_cache shared:SSL:10m; \
+             ssl_session_timeout 1d;"
+        );
+
+        let runner = runner();
+        // Only what is missing is reported
+        runner.assert_errors(
+            "http {\n    ssl_session_cache shared:SSL:10m;\n    server {\n        listen 443 ssl;\n    }\n}\n",
+            1,
+        );
+        runner.assert_no_errors(
+            "http {\n    ssl_session_cache shared:SSL:10m;\n    server {\n        listen 443 ssl;\n        ssl_session_timeout 4h;\n    }\n}\n",
+        );
+        // Plain HTTP and ssl on
+        runner.assert_no_errors("http {\n    server {\n        listen 80;\n    }\n}\n");
+        runner.assert_errors(
+            "http {\n    ssl on;\n    server {\n        listen 443;\n    }\n}\n",
+            1,
+        );
+    }
+
+    #[test]
+    fn test_fix_adds_to_http_once() {
+        // The second server only needs what the first one's fix does not add
+        runner().assert_fix_produces(
+            "http {\n    server {\n        listen 443 ssl;\n        ssl_session_timeout 4h;\n    }\n    server {\n        listen 8443 ssl;\n    }\n}\n",
+            "http {\n    ssl_session_cache shared:SSL:10m;\n    server {\n        listen 443 ssl;\n        ssl_session_timeout 4h;\n    }\n    server {\n        listen 8443 ssl;\n        ssl_session_timeout 1d;\n    }\n}\n",
+        );
+        runner().assert_fix_produces(
+            "http {\n    server {\n        listen 443 ssl;\n    }\n    server {\n        listen 8443 ssl;\n    }\n}\n",
+            "http {\n    ssl_session_cache shared:SSL:10m;\n    ssl_session_timeout 1d;\n    server {\n        listen 443 ssl;\n    }\n    server {\n        listen 8443 ssl;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_included_file() {
+        let plugin = SslPerformanceNotTunedPlugin::default();
+        let mut config = nginx_lint_plugin::parse_string(
+            "server {\n    listen 443 ssl;\n    ssl_stapling on;\n}\n",
+        )
+        .unwrap();
+        // The including http block may set the session settings and resolver
+        config.include_context = IncludeContext::parse("http");
+        assert!(plugin.check(&config, "test.conf").is_empty());
+
+        let mut config = nginx_lint_plugin::parse_string("listen 443 ssl;\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server");
+        assert!(plugin.check(&config, "test.conf").is_empty());
+    }
+
+    #[test]
+    fn test_stapling_without_resolver() {
+        let runner = runner();
+        let config = |extra: &str| {
+            format!(
+                "http {{\n    ssl_session_cache shared:SSL:10m;\n    ssl_session_timeout 1d;\n    ssl_stapling on;\n{}    server {{\n        listen 443 ssl;\n    }}\n}}\n",
+                extra
+            )
+        };
+        let errors = runner.check_string(&config("")).unwrap();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "ssl_stapling on (line 4) without a resolver; nginx cannot look up the OCSP \
+             responder, so no OCSP response is stapled"
+        );
+        assert!(errors[0].fixes.is_empty());
+        runner.assert_no_errors(&config("    resolver 127.0.0.53;\n"));
+        runner.assert_no_errors(&config("    ssl_stapling_file /etc/nginx/ocsp.der;\n"));
+        // Stapling is not required by default
+        runner.assert_no_errors(
+            "http {\n    ssl_session_cache shared:SSL:10m;\n    ssl_session_timeout 1d;\n    server {\n        listen 443 ssl;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_require_stapling() {
+        let mut plugin = SslPerformanceNotTunedPlugin::default();
+        let configured = HashMap::from([("require_stapling".to_string(), true.into())]);
+        plugin.configure(&RuleOptions::resolve(&plugin.spec().options, &configured));
+        let runner = PluginTestRunner::new(plugin);
+        runner.assert_fix_produces(
+            "http {\n    ssl_session_cache shared:SSL:10m;\n    ssl_session_timeout 1d;\n    server {\n        listen 443 ssl;\n    }\n}\n",
+            "http {\n    ssl_session_cache shared:SSL:10m;\n    ssl_session_timeout 1d;\n    server {\n        listen 443 ssl;\n        ssl_stapling on;\n        ssl_stapling_verify on;\n    }\n}\n",
+        );
+        runner.assert_no_errors(
+            "http {\n    ssl_session_cache shared:SSL:10m;\n    ssl_session_timeout 1d;\n    server {\n        listen 443 ssl;\n        ssl_stapling off;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        runner().test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        runner().test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
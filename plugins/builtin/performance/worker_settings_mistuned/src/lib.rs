@@ -0,0 +1,286 @@
+//! worker-settings-mistuned plugin
+//!
+//! This advisory plugin checks the worker settings of a main nginx
+//! configuration:
+//!
+//! - `worker_processes` hard-coded above `max_worker_processes`, where
+//!   `auto` would follow the number of CPU cores
+//! - `worker_rlimit_nofile` below twice `worker_connections`; a proxied
+//!   request holds one descriptor for the client and one for the backend
+//! - no `events` block, without which nginx refuses to start
+//!
+//! All of these are main-context settings, so files included from another
+//! file are not checked. The `events` check is skipped when the file has a
+//! top-level `include`, which may supply the block.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+
+/// nginx's default `worker_connections`
+const DEFAULT_WORKER_CONNECTIONS: i64 = 512;
+
+/// Blocks that only appear in a main configuration, which needs `events`
+const MAIN_BLOCKS: &[&str] = &["http", "stream", "mail"];
+
+/// Check worker_processes, worker_connections and worker_rlimit_nofile
+pub struct WorkerSettingsMistunedPlugin {
+    /// Largest worker_processes accepted as a hard-coded number (option
+    /// `max_worker_processes`)
+    max_worker_processes: i64,
+}
+
+impl Default for WorkerSettingsMistunedPlugin {
+    fn default() -> Self {
+        Self {
+            max_worker_processes: 16,
+        }
+    }
+}
+
+/// The directives directly in `items`
+fn directives(items: &[ConfigItem]) -> impl Iterator<Item = &Directive> {
+    items.iter().filter_map(|item| match item {
+        ConfigItem::Directive(d) => Some(d.as_ref()),
+        _ => None,
+    })
+}
+
+/// The last `name` directive in `items`; nginx uses the last of duplicates
+fn last<'a>(items: &'a [ConfigItem], name: &str) -> Option<&'a Directive> {
+    directives(items).filter(|d| d.is(name)).last()
+}
+
+/// The numeric first argument of `directive`
+fn number(directive: &Directive) -> Option<i64> {
+    directive.first_arg()?.parse().ok()
+}
+
+impl WorkerSettingsMistunedPlugin {
+    fn check_worker_processes(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        let Some(directive) = last(items, "worker_processes") else {
+            return;
+        };
+        let Some(count) = number(directive) else {
+            return;
+        };
+        if count > self.max_worker_processes {
+            let message = format!(
+                "worker_processes {} is hard-coded above {}; use worker_processes auto to start \
+                 one worker per CPU core",
+                count, self.max_worker_processes
+            );
+            errors.push(self.spec().error_builder().warning_at(&message, directive));
+        }
+    }
+
+    fn check_rlimit_nofile(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        let Some(rlimit) = last(items, "worker_rlimit_nofile") else {
+            return;
+        };
+        let Some(limit) = number(rlimit) else {
+            return;
+        };
+        let connections = match last(items, "events")
+            .and_then(|events| events.block.as_ref())
+            .and_then(|block| last(&block.items, "worker_connections"))
+        {
+            Some(directive) => match number(directive) {
+                Some(connections) => connections,
+                None => return,
+            },
+            None => DEFAULT_WORKER_CONNECTIONS,
+        };
+        if limit < connections * 2 {
+            let message = format!(
+                "worker_rlimit_nofile {} is below twice worker_connections {}; a proxied \
+                 connection uses two file descriptors, so set it to at least {}",
+                limit,
+                connections,
+                connections * 2
+            );
+            errors.push(self.spec().error_builder().warning_at(&message, rlimit));
+        }
+    }
+
+    fn check_events(&self, items: &[ConfigItem], errors: &mut Vec<LintError>) {
+        if directives(items).any(|d| d.is("events") || d.is("include")) {
+            return;
+        }
+        if let Some(block) = directives(items).find(|d| MAIN_BLOCKS.iter().any(|name| d.is(name))) {
+            let message = "no events block in the main configuration; nginx refuses to start \
+                           without one";
+            errors.push(self.spec().error_builder().error_at(message, block));
+        }
+    }
+}
+
+impl Plugin for WorkerSettingsMistunedPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "worker-settings-mistuned",
+            "performance",
+            "Checks worker_processes, worker_connections and worker_rlimit_nofile for common mistakes",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Strict)
+        .with_why(
+            "A hard-coded `worker_processes` is usually copied from another machine. More \
+             workers than CPU cores only add context switches, and fewer leave cores idle; \
+             `worker_processes auto` starts one worker per core wherever the configuration \
+             runs.\n\n\
+             Each worker can open at most `worker_rlimit_nofile` files. A proxied request holds \
+             a descriptor for the client connection and another for the backend, so a limit \
+             below twice `worker_connections` makes workers fail with \"Too many open files\" \
+             before they reach their connection limit.\n\n\
+             nginx also refuses to start when the main configuration has no `events` block, \
+             even an empty one.\n\n\
+             Hard-coded counts up to `max_worker_processes` are accepted. Included files are \
+             not checked. The rule is disabled by default.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/ngx_core_module.html#worker_processes".to_string(),
+            "https://nginx.org/en/docs/ngx_core_module.html#worker_rlimit_nofile".to_string(),
+            "https://nginx.org/en/docs/ngx_core_module.html#worker_connections".to_string(),
+            "https://www.f5.com/company/blog/nginx/tuning-nginx".to_string(),
+        ])
+        .with_option(RuleOption::integer(
+            "max_worker_processes",
+            16,
+            "Largest worker_processes accepted as a hard-coded number",
+        ))
+    }
+
+    fn configure(&mut self, options: &RuleOptions) {
+        self.max_worker_processes = options.integer("max_worker_processes");
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&[
+            "worker_processes",
+            "worker_rlimit_nofile",
+            "worker_connections",
+            "events",
+            "http",
+            "stream",
+            "mail",
+            "include",
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        if !config.include_context.is_empty() {
+            return errors;
+        }
+        self.check_worker_processes(&config.items, &mut errors);
+        self.check_rlimit_nofile(&config.items, &mut errors);
+        self.check_events(&config.items, &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(WorkerSettingsMistunedPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn runner() -> PluginTestRunner<WorkerSettingsMistunedPlugin> {
+        PluginTestRunner::new(WorkerSettingsMistunedPlugin::default())
+    }
+
+    #[test]
+    fn test_worker_processes() {
+        let runner = runner();
+        let errors = runner
+            .check_string("worker_processes 64;\nevents {}\n")
+            .unwrap();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "worker_processes 64 is hard-coded above 16; use worker_processes auto to start one \
+             worker per CPU core"
+        );
+        runner.assert_no_errors("worker_processes auto;\nevents {}\n");
+        runner.assert_no_errors("worker_processes 4;\nevents {}\n");
+    }
+
+    #[test]
+    fn test_rlimit_nofile() {
+        let runner = runner();
+        runner.assert_error_on_line(
+            "worker_rlimit_nofile 4096;\n\nevents {\n    worker_connections 4096;\n}\n",
+            1,
+        );
+        runner.assert_no_errors(
+            "worker_rlimit_nofile 8192;\n\nevents {\n    worker_connections 4096;\n}\n",
+        );
+        // worker_connections defaults to 512
+        runner.assert_errors("worker_rlimit_nofile 1000;\nevents {}\n", 1);
+        runner.assert_no_errors("worker_rlimit_nofile 1024;\nevents {}\n");
+        // Without worker_rlimit_nofile the limit comes from the OS
+        runner.assert_no_errors("events {\n    worker_connections 65535;\n}\n");
+    }
+
+    #[test]
+    fn test_missing_events() {
+        let runner = runner();
+        let errors = runner
+            .check_string("worker_processes auto;\n\nhttp {\n    server {}\n}\n")
+            .unwrap();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(3));
+        assert_eq!(errors[0].severity, Severity::Error);
+
+        runner.assert_errors("stream {\n    server {}\n}\n", 1);
+        // A fragment without main-context blocks
+        runner.assert_no_errors("server {\n    listen 80;\n}\n");
+        // The events block may come from the included file
+        runner.assert_no_errors("include /etc/nginx/events.conf;\nhttp {}\n");
+    }
+
+    #[test]
+    fn test_configured_max() {
+        use std::collections::HashMap;
+
+        let mut plugin = WorkerSettingsMistunedPlugin::default();
+        let configured = HashMap::from([("max_worker_processes".to_string(), 64.into())]);
+        plugin.configure(&RuleOptions::resolve(&plugin.spec().options, &configured));
+        let runner = PluginTestRunner::new(plugin);
+
+        runner.assert_no_errors("worker_processes 64;\nevents {}\n");
+        runner.assert_errors("worker_processes 96;\nevents {}\n", 1);
+    }
+
+    #[test]
+    fn test_included_file() {
+        let mut config =
+            nginx_lint_plugin::parse_string("worker_processes 64;\nhttp {}\n").unwrap();
+        config.include_context = IncludeContext::parse("http");
+        assert!(
+            WorkerSettingsMistunedPlugin::default()
+                .check(&config, "test.conf")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        runner().test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        runner().test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
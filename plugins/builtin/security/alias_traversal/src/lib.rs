@@ -0,0 +1,244 @@
+//! alias-traversal plugin
+//!
+//! This plugin detects the "off-by-slash" alias misconfiguration: a prefix
+//! `location` whose URI does not end with `/` serving an `alias` that does,
+//! e.g. `location /static { alias /var/www/static/; }`. nginx replaces the
+//! matched prefix with the alias path, so `/static../secret` resolves to
+//! `/var/www/static/../secret`, one directory above the alias.
+//!
+//! The fix adds the trailing slash to the location. The opposite mismatch
+//! (location with a slash, alias without) is reported by
+//! alias-location-slash-mismatch.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+
+/// Check for prefix locations without a trailing slash serving an alias with one
+#[derive(Default)]
+pub struct AliasTraversalPlugin;
+
+/// The URI of a prefix location (`location [^~] uri`), or `None` for exact,
+/// regex and named locations, where the prefix is not replaced by the alias
+fn prefix_uri<'a, T>(args: &'a [T], as_str: impl Fn(&'a T) -> &'a str) -> Option<&'a T> {
+    let (first, rest) = args.split_first()?;
+    let uri = match as_str(first) {
+        "^~" => rest.first()?,
+        "=" | "~" | "~*" => return None,
+        _ => first,
+    };
+    let text = as_str(uri);
+    (!text.starts_with('@')).then_some(uri)
+}
+
+impl AliasTraversalPlugin {
+    /// Report `alias` directives below `items`
+    ///
+    /// `location` is the URI of the enclosing prefix location and, when it
+    /// is in this file, its argument for the fix.
+    fn check_items(
+        &self,
+        items: &[ConfigItem],
+        location: Option<(&str, Option<&Argument>)>,
+        errors: &mut Vec<LintError>,
+    ) {
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+
+            if directive.is("alias")
+                && let Some((uri, uri_arg)) = location
+                && let Some(path) = directive.first_arg()
+                && !uri.ends_with('/')
+                && path.ends_with('/')
+                // The lexer splits `/srv/$host/` into several arguments
+                && !directive
+                    .args
+                    .iter()
+                    .any(|arg| arg.is_variable() || arg.as_str().contains('$'))
+            {
+                let err = self.spec().error_builder();
+                let mut error = err.warning_at(
+                    &format!(
+                        "location '{}' does not end with '/' but alias '{}' does, so '{}../' \
+                         reaches the parent of the alias directory; add a trailing slash to the \
+                         location",
+                        uri, path, uri
+                    ),
+                    directive,
+                );
+                if let Some(arg) = uri_arg {
+                    // Insert before the closing quote of a quoted URI
+                    let end = match arg.value {
+                        ArgumentValue::QuotedString(_) | ArgumentValue::SingleQuotedString(_) => {
+                            arg.span.end.offset - 1
+                        }
+                        _ => arg.span.end.offset,
+                    };
+                    error = error.with_fix(Fix::replace_range(end, end, "/"));
+                }
+                errors.push(error);
+            }
+
+            if let Some(block) = &directive.block {
+                let inner = if directive.is("location") {
+                    prefix_uri(&directive.args, Argument::as_str)
+                        .map(|arg| (arg.as_str(), Some(arg)))
+                } else {
+                    location
+                };
+                self.check_items(&block.items, inner, errors);
+            }
+        }
+    }
+}
+
+impl Plugin for AliasTraversalPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "alias-traversal",
+            "security",
+            "Detects alias path traversal through a location without a trailing slash",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Minimal)
+        .with_why(
+            "`alias` replaces the part of the URI matched by the location with the alias \
+             path. With `location /static { alias /var/www/app/static/; }` the location also \
+             matches `/static../config.py`, which nginx maps to \
+             `/var/www/app/static/../config.py`: a client can read any file in the parent of \
+             the alias directory, often the application's source or configuration.\n\n\
+             Add the trailing slash to the location (`location /static/`), so only URIs below \
+             the directory match. Requests for `/static` itself are then redirected to \
+             `/static/` by nginx. Exact, regex and named locations are not affected.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#alias".to_string(),
+            "https://github.com/yandex/gixy/blob/master/docs/en/plugins/aliastraversal.md"
+                .to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/alias_traversal/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        // Locations enclosing a kept alias are retained as ancestors
+        Some(&["alias"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        // Top-level aliases belong to the location the file is included
+        // from; that location is in another file, so there is no fix
+        let location = config
+            .include_context
+            .innermost("location")
+            .and_then(|frame| prefix_uri(&frame.args, String::as_str))
+            .map(|uri| (uri.as_str(), None));
+        self.check_items(&config.items, location, &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(AliasTraversalPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_detects_off_by_slash() {
+        let runner = PluginTestRunner::new(AliasTraversalPlugin);
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        location /static {
+            alias /var/www/app/static/;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert_eq!(
+            errors[0].message,
+            "location '/static' does not end with '/' but alias '/var/www/app/static/' does, \
+             so '/static../' reaches the parent of the alias directory; add a trailing slash \
+             to the location"
+        );
+        assert_eq!(errors[0].fixes[0].new_text, "/");
+    }
+
+    #[test]
+    fn test_alias_in_nested_if() {
+        let runner = PluginTestRunner::new(AliasTraversalPlugin);
+        runner.assert_errors(
+            r#"
+location ^~ /media {
+    if ($request_method = POST) {
+        return 405;
+    }
+    alias /srv/media/;
+}
+"#,
+            1,
+        );
+    }
+
+    #[test]
+    fn test_safe_locations() {
+        let runner = PluginTestRunner::new(AliasTraversalPlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        location /a/ { alias /srv/a/; }
+        location /b { alias /srv/b; }
+        location = /c { alias /srv/c/index.html/; }
+        location ~ ^/d/(.*)$ { alias /srv/d/$1; }
+        location /e { alias /srv/$host/; }
+        location @f { alias /srv/f/; }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_location_from_include_context() {
+        let mut config = parse_string("alias /srv/docs/;\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server,location=/docs");
+
+        let errors = AliasTraversalPlugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        // The location is in the including file, so there is nothing to fix here
+        assert!(errors[0].fixes.is_empty());
+    }
+
+    #[test]
+    fn test_examples_with_fix() {
+        let runner = PluginTestRunner::new(AliasTraversalPlugin);
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(AliasTraversalPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
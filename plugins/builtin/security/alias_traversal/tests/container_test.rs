@@ -0,0 +1,62 @@
+//! Container-based integration tests for the alias-traversal rule.
+//!
+//! With `location /conf { alias {conf_dir}/conf.d/; }`, a request to
+//! `/conf../mime.types` resolves to `{conf_dir}/conf.d/../mime.types` and
+//! serves a file outside the aliased directory. With the trailing slash on
+//! the location, the same request no longer matches.
+//!
+//! Run with:
+//!   cargo test -p alias-traversal-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p alias-traversal-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, nginx_conf_dir, reqwest};
+
+async fn start(location: &str) -> NginxContainer {
+    let conf_dir = nginx_conf_dir();
+    let config = format!(
+        r#"
+events {{
+    worker_connections 1024;
+}}
+http {{
+    server {{
+        listen 80;
+
+        location /healthz {{
+            return 200 'OK';
+        }}
+
+        location {location} {{
+            alias {conf_dir}/conf.d/;
+        }}
+    }}
+}}
+"#
+    );
+    NginxContainer::builder()
+        .health_path("/healthz")
+        .start(config)
+        .await
+}
+
+#[tokio::test]
+#[ignore]
+async fn missing_slash_allows_traversal() {
+    let nginx = start("/conf").await;
+    let resp = reqwest::get(nginx.url("/conf../mime.types")).await.unwrap();
+    assert_eq!(
+        resp.status(),
+        200,
+        "Expected mime.types from the parent of the alias directory"
+    );
+}
+
+#[tokio::test]
+#[ignore]
+async fn trailing_slash_blocks_traversal() {
+    let nginx = start("/conf/").await;
+    let resp = reqwest::get(nginx.url("/conf../mime.types")).await.unwrap();
+    assert_eq!(resp.status(), 404);
+}
@@ -0,0 +1,314 @@
+//! allow-deny-order plugin
+//!
+//! ngx_http_access_module checks the `allow`/`deny` rules of a block in
+//! order and stops at the first match. This plugin detects:
+//!
+//! - `deny all;` (or `allow all;`) followed by more rules in the same block,
+//!   which can never match
+//! - `allow all;` where `auth_basic` is in effect: with `satisfy any` it lets
+//!   every client through without credentials, and with the default
+//!   `satisfy all` it does nothing
+//!
+//! `auth_basic` and `satisfy` are inherited from enclosing blocks.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::InheritedSettings;
+use nginx_lint_plugin::prelude::*;
+
+/// Check the order of allow/deny rules
+#[derive(Default)]
+pub struct AllowDenyOrderPlugin;
+
+/// `auth_basic` and `satisfy` in effect for a block
+type AuthState<'a> = InheritedSettings<'a, 2>;
+
+/// The directives followed through the blocks
+const AUTH_SETTINGS: [&str; 2] = ["auth_basic", "satisfy"];
+
+fn is_access_rule(directive: &Directive) -> bool {
+    directive.is("allow") || directive.is("deny")
+}
+
+impl AllowDenyOrderPlugin {
+    fn check_items<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: AuthState<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let state = inherited.enter(items);
+        let err = self.spec().error_builder();
+
+        let rules: Vec<&Directive> = items
+            .iter()
+            .filter_map(|item| match item {
+                ConfigItem::Directive(d) if is_access_rule(d) => Some(d.as_ref()),
+                _ => None,
+            })
+            .collect();
+
+        self.check_unreachable(&rules, &err, errors);
+
+        let auth_basic = state.get("auth_basic").directive();
+        if auth_basic.is_some_and(|d| !d.first_arg_is("off")) {
+            for rule in rules
+                .iter()
+                .filter(|d| d.is("allow") && d.first_arg_is("all"))
+            {
+                let satisfy = state.get("satisfy").directive();
+                let message = if satisfy.is_some_and(|d| d.first_arg_is("any")) {
+                    "'allow all' with 'satisfy any' lets every client through without \
+                     auth_basic credentials; remove it or list the trusted addresses"
+                } else {
+                    "'allow all' has no effect next to auth_basic and disables \
+                     authentication as soon as 'satisfy any' is set; remove it"
+                };
+                errors.push(err.warning_at(message, *rule).with_fix(rule.delete_line()));
+            }
+        }
+
+        for item in items {
+            if let ConfigItem::Directive(d) = item
+                && let Some(block) = &d.block
+            {
+                self.check_items(&block.items, state, errors);
+            }
+        }
+    }
+
+    /// Report an `allow all`/`deny all` that shadows the rules after it
+    fn check_unreachable(
+        &self,
+        rules: &[&Directive],
+        err: &ErrorBuilder,
+        errors: &mut Vec<LintError>,
+    ) {
+        let Some(index) = rules.iter().position(|d| d.first_arg_is("all")) else {
+            return;
+        };
+        let catch_all = rules[index];
+        let shadowed = &rules[index + 1..];
+        let Some(last) = shadowed.last() else {
+            return;
+        };
+
+        let mut error = err.warning_at(
+            &format!(
+                "'{} all' matches every client, so the {} rule(s) after it in this block \
+                 are never reached; move it to the end",
+                catch_all.name,
+                shadowed.len()
+            ),
+            catch_all,
+        );
+        // Moving the catch-all only keeps the intent if it is the only one
+        if !shadowed.iter().any(|d| d.first_arg_is("all")) {
+            error = error
+                .with_fix(catch_all.delete_line())
+                .with_fix(last.insert_after(&format!("{} all;", catch_all.name)));
+        }
+        errors.push(error);
+    }
+}
+
+impl Plugin for AllowDenyOrderPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "allow-deny-order",
+            "security",
+            "Detects allow/deny rules that are never reached or bypass auth_basic",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "The access module checks `allow` and `deny` rules in the order they appear in a \
+             block and stops at the first match. Rules after `deny all;` or `allow all;` can \
+             never match, so a `deny all;` written first locks out the addresses that were \
+             meant to be allowed. Put the catch-all rule last.\n\n\
+             `allow all;` next to `auth_basic` is either useless (with the default `satisfy \
+             all`, credentials are still required) or an authentication bypass (with \
+             `satisfy any`, passing the access check is enough). `auth_basic` and `satisfy` \
+             are inherited, so the problem is often set up across several blocks.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_access_module.html".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#satisfy".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/allow_deny_order/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["allow", "deny"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        self.check_items(
+            &config.items,
+            AuthState::root(AUTH_SETTINGS, config),
+            &mut errors,
+        );
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(AllowDenyOrderPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(source: &str) -> Vec<LintError> {
+        let config = nginx_lint_plugin::parse_string(source).unwrap();
+        AllowDenyOrderPlugin.check(&config, "test.conf")
+    }
+
+    #[test]
+    fn test_deny_all_before_allow() {
+        let errors = check(
+            r#"
+http {
+    server {
+        location /admin/ {
+            deny all;
+            allow 10.0.0.0/8;
+            allow 192.168.0.0/16;
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert_eq!(
+            errors[0].message,
+            "'deny all' matches every client, so the 2 rule(s) after it in this block are \
+             never reached; move it to the end"
+        );
+        assert_eq!(errors[0].fixes.len(), 2);
+        assert_eq!(errors[0].fixes[1].new_text, "\n            deny all;");
+    }
+
+    #[test]
+    fn test_allow_all_before_deny() {
+        let errors = check("allow all;\ndeny 10.0.0.1;\n");
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(
+            errors[0]
+                .message
+                .starts_with("'allow all' matches every client")
+        );
+    }
+
+    #[test]
+    fn test_two_catch_alls_are_not_fixed() {
+        let errors = check("deny all;\nallow 10.0.0.1;\nallow all;\n");
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].fixes.is_empty());
+    }
+
+    #[test]
+    fn test_correct_order() {
+        let runner = PluginTestRunner::new(AllowDenyOrderPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    allow 127.0.0.1;
+    deny all;
+
+    server {
+        location / {
+            deny 192.168.1.1;
+            allow all;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_allow_all_with_satisfy_any() {
+        let errors = check(
+            r#"
+http {
+    satisfy any;
+    server {
+        auth_basic "Restricted";
+        auth_basic_user_file /etc/nginx/.htpasswd;
+
+        location /private/ {
+            allow all;
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(9));
+        assert!(errors[0].message.contains("lets every client through"));
+        assert_eq!(errors[0].fixes.len(), 1);
+    }
+
+    #[test]
+    fn test_allow_all_with_satisfy_all() {
+        let errors = check(
+            r#"
+location /private/ {
+    auth_basic "Restricted";
+    allow all;
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].message.starts_with("'allow all' has no effect"));
+    }
+
+    #[test]
+    fn test_allow_all_without_auth() {
+        let runner = PluginTestRunner::new(AllowDenyOrderPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    auth_basic "Restricted";
+    satisfy any;
+    server {
+        location /public/ {
+            auth_basic off;
+            allow all;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_examples_with_fix() {
+        let runner = PluginTestRunner::new(AllowDenyOrderPlugin);
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(AllowDenyOrderPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
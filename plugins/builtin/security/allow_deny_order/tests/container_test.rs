@@ -0,0 +1,89 @@
+//! Container-based integration tests for the allow-deny-order rule.
+//!
+//! Shows that access rules are checked in order with the first match
+//! winning, and that `allow all` under `satisfy any` skips auth_basic.
+//!
+//! Run with:
+//!   cargo test -p allow-deny-order-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p allow-deny-order-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, reqwest};
+
+/// A leading `deny all` rejects clients that a later rule allows.
+#[tokio::test]
+#[ignore]
+async fn deny_all_first_shadows_allow() {
+    let nginx = NginxContainer::start(
+        br#"
+events {
+    worker_connections 1024;
+}
+http {
+    server {
+        listen 80;
+
+        location /deny-first {
+            deny all;
+            allow all;
+            return 200 "ok";
+        }
+        location /allow-first {
+            allow all;
+            deny all;
+            return 200 "ok";
+        }
+        location / {
+            return 200 "ok";
+        }
+    }
+}
+"#,
+    )
+    .await;
+
+    let resp = reqwest::get(nginx.url("/deny-first")).await.unwrap();
+    assert_eq!(resp.status(), 403);
+    let resp = reqwest::get(nginx.url("/allow-first")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
+/// With `satisfy any`, `allow all` makes auth_basic credentials optional.
+#[tokio::test]
+#[ignore]
+async fn allow_all_with_satisfy_any_bypasses_auth_basic() {
+    let nginx = NginxContainer::start(
+        br#"
+events {
+    worker_connections 1024;
+}
+http {
+    server {
+        listen 80;
+        satisfy any;
+        auth_basic "Restricted";
+        auth_basic_user_file /etc/nginx/htpasswd-does-not-matter;
+
+        location /bypass {
+            allow all;
+            return 200 "ok";
+        }
+        location /protected {
+            return 200 "ok";
+        }
+        location / {
+            auth_basic off;
+            return 200 "ok";
+        }
+    }
+}
+"#,
+    )
+    .await;
+
+    let resp = reqwest::get(nginx.url("/bypass")).await.unwrap();
+    assert_eq!(resp.status(), 200, "allow all should skip auth_basic");
+    let resp = reqwest::get(nginx.url("/protected")).await.unwrap();
+    assert_eq!(resp.status(), 401);
+}
@@ -24,6 +24,7 @@ impl Plugin for AutoindexEnabledPlugin {
             "Detects when autoindex is enabled (can expose directory contents)",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "When autoindex is enabled, nginx will generate a directory listing when a request \
              is made to a directory without an index file. This can expose sensitive files, \
@@ -165,6 +166,35 @@ http {
         );
     }
 
+    #[test]
+    fn test_detects_autoindex_in_http_and_server() {
+        let runner = PluginTestRunner::new(AutoindexEnabledPlugin);
+
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    autoindex on;
+    server {
+        autoindex on;
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        let errors: Vec<_> = errors
+            .iter()
+            .filter(|e| e.rule == "autoindex-enabled")
+            .collect();
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(3));
+        assert_eq!(errors[1].line, Some(5));
+        for error in errors {
+            assert_eq!(error.fixes[0].new_text.trim(), "autoindex off;");
+        }
+    }
+
     #[test]
     fn test_examples_with_fix() {
         let runner = PluginTestRunner::new(AutoindexEnabledPlugin);
@@ -218,7 +248,7 @@ autoindex on;
         .unwrap();
 
         // Simulate being included from http context
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let plugin = AutoindexEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -242,7 +272,7 @@ location / {
         .unwrap();
 
         // Simulate being included from http > server context
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = AutoindexEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -263,7 +293,7 @@ autoindex on;
         .unwrap();
 
         // Simulate being included from stream context
-        config.include_context = vec!["stream".to_string()];
+        config.include_context = vec!["stream".to_string()].into();
 
         let plugin = AutoindexEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
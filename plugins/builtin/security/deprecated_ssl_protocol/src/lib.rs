@@ -25,6 +25,7 @@ impl Plugin for DeprecatedSslProtocolPlugin {
             "Detects usage of deprecated SSL/TLS protocols (SSLv3, TLSv1, TLSv1.1)",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "SSLv2, SSLv3, TLSv1.0, and TLSv1.1 have known vulnerabilities and are deprecated. \
              Using these protocols makes your server vulnerable to attacks like POODLE, BEAST, \
@@ -35,6 +36,7 @@ impl Plugin for DeprecatedSslProtocolPlugin {
         .with_references(vec![
             "https://nginx.org/en/docs/http/ngx_http_ssl_module.html#ssl_protocols".to_string(),
             "https://wiki.mozilla.org/Security/Server_Side_TLS".to_string(),
+            "https://ssl-config.mozilla.org/".to_string(),
             "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/deprecated_ssl_protocol/tests/container_test.rs".to_string(),
         ])
     }
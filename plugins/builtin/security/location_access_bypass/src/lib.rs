@@ -0,0 +1,495 @@
+//! location-access-bypass plugin
+//!
+//! This plugin detects prefix locations protected by `allow`/`deny`,
+//! `auth_basic` or `auth_request` whose files can also be requested through
+//! another location that lacks the protection:
+//!
+//! - a regex location (`location ~ \.php$`) takes precedence over a prefix
+//!   location without `^~`, so `/admin/index.php` is handled by the regex
+//!   location, not by `location /admin/`
+//! - prefix matching is case-sensitive, so where `~*` locations suggest a
+//!   case-insensitive file system, `/ADMIN/` maps to the same files without
+//!   matching `location /admin/`
+//!
+//! Candidate URIs are run through [`location_match`], which selects
+//! locations the way nginx does.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedList, InheritedSettings};
+use nginx_lint_plugin::location_match::{Location, LocationModifier, LocationTree};
+use nginx_lint_plugin::prelude::*;
+
+/// Check for protected prefix locations reachable through another location
+#[derive(Default)]
+pub struct LocationAccessBypassPlugin;
+
+/// Access restrictions in effect in a block; each is inherited unless the
+/// block sets its own
+#[derive(Debug, Clone, Copy)]
+struct Protection<'a> {
+    /// `allow`/`deny` rules, inherited as a list
+    access: InheritedList<'a>,
+    /// `auth_basic` and `auth_request`
+    auth: InheritedSettings<'a, 2>,
+}
+
+impl<'a> Protection<'a> {
+    fn new() -> Self {
+        Self {
+            access: InheritedList::Default,
+            auth: InheritedSettings::new(["auth_basic", "auth_request"], Inherited::Default),
+        }
+    }
+
+    /// Restrictions in effect inside `items`, inheriting from `self`
+    fn enter(&self, items: &'a [ConfigItem]) -> Self {
+        Self {
+            access: self.access.enter(items, &["allow", "deny"]),
+            auth: self.auth.enter(items),
+        }
+    }
+
+    /// The first `deny` rule in effect
+    fn deny(&self) -> Option<&'a Directive> {
+        self.access.directives().find(|d| d.is("deny"))
+    }
+
+    /// The `name` authentication in effect, unless it is `off`
+    fn auth(&self, name: &str) -> Option<&'a Directive> {
+        self.auth
+            .get(name)
+            .directive()
+            .filter(|d| !d.first_arg_is("off"))
+    }
+
+    /// Restrictions in effect in the last location of `chain`
+    fn along(&self, chain: &[&Location<'a>]) -> Self {
+        chain.iter().fold(*self, |protection, location| {
+            match &location.directive().block {
+                Some(block) => protection.enter(&block.items),
+                None => protection,
+            }
+        })
+    }
+
+    /// Names of the restrictions `self` has and `other` lacks
+    fn missing_in(&self, other: &Protection) -> Vec<&'static str> {
+        [
+            (
+                self.deny().is_some() && other.deny().is_none(),
+                "allow/deny",
+            ),
+            (
+                self.auth("auth_basic").is_some() && other.auth("auth_basic").is_none(),
+                "auth_basic",
+            ),
+            (
+                self.auth("auth_request").is_some() && other.auth("auth_request").is_none(),
+                "auth_request",
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(missing, name)| missing.then_some(name))
+        .collect()
+    }
+}
+
+/// Whether `location` sets one of the restrictions itself
+fn sets_protection(location: &Location) -> bool {
+    location.directive().block.as_ref().is_some_and(|block| {
+        block.items.iter().any(|item| {
+            matches!(item, ConfigItem::Directive(d)
+                if ["allow", "deny", "auth_basic", "auth_request"].contains(&d.name.as_str()))
+        })
+    })
+}
+
+/// Whether a location selected for a URI serves content for it, rather
+/// than answering itself or being unreachable from outside
+fn serves_content(chain: &[&Location]) -> bool {
+    chain.last().is_none_or(|location| {
+        !location.directive().block.as_ref().is_some_and(|block| {
+            block.items.iter().any(|item| {
+                matches!(item, ConfigItem::Directive(d) if d.is("return") || d.is("internal"))
+            })
+        })
+    })
+}
+
+/// How a candidate URI reaches the protected files
+enum Route {
+    /// Through a regex location that takes precedence
+    Regex,
+    /// Through a different-case spelling of the prefix
+    Case,
+}
+
+impl LocationAccessBypassPlugin {
+    fn check_items<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: Protection<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let protection = inherited.enter(items);
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            let Some(block) = &directive.block else {
+                continue;
+            };
+            if directive.is("server") {
+                self.check_server(&block.items, protection.enter(&block.items), errors);
+            } else if !directive.is("location") {
+                self.check_items(&block.items, protection, errors);
+            }
+        }
+    }
+
+    /// Check the locations of a server whose own restrictions are `server`
+    fn check_server(&self, items: &[ConfigItem], server: Protection, errors: &mut Vec<LintError>) {
+        let tree = LocationTree::from_items(items);
+        let chains = tree.chains();
+        let regexes: Vec<&Location> = chains
+            .iter()
+            .filter_map(|chain| chain.last().copied())
+            .filter(|location| location.is_regex())
+            .collect();
+        let has_case_insensitive = regexes
+            .iter()
+            .any(|l| l.modifier() == LocationModifier::RegexCaseInsensitive);
+
+        for chain in &chains {
+            let protected = chain[chain.len() - 1];
+            if !matches!(
+                protected.modifier(),
+                LocationModifier::Prefix | LocationModifier::PrefixNoRegex
+            ) || !sets_protection(protected)
+                || !serves_content(chain)
+            {
+                continue;
+            }
+            let restrictions = server.along(chain);
+            let prefix = protected.pattern();
+            let base = if prefix.ends_with('/') {
+                format!("{}x", prefix)
+            } else {
+                format!("{}/x", prefix)
+            };
+
+            let mut candidates = Vec::new();
+            if protected.modifier() == LocationModifier::Prefix {
+                for regex in &regexes {
+                    let Some(example) = regex.regex_example() else {
+                        continue;
+                    };
+                    let uri = if regex.pattern().starts_with('^') {
+                        example
+                    } else {
+                        format!("{}{}", base, example)
+                    };
+                    if uri.starts_with(prefix) {
+                        candidates.push((uri, Route::Regex));
+                    }
+                }
+            }
+            let upper = format!("{}{}", prefix.to_ascii_uppercase(), &base[prefix.len()..]);
+            if has_case_insensitive && !upper.starts_with(prefix) {
+                candidates.push((upper, Route::Case));
+            }
+
+            let mut reported: Vec<*const Directive> = Vec::new();
+            for (uri, route) in candidates {
+                let Some(selected) = tree.select(&uri) else {
+                    continue;
+                };
+                if selected.iter().any(|l| std::ptr::eq(*l, protected))
+                    || !serves_content(&selected)
+                {
+                    continue;
+                }
+                let missing = restrictions.missing_in(&server.along(&selected));
+                let handler = selected.last().map(|l| l.directive());
+                let key = handler.map_or(std::ptr::null(), |d| d as *const Directive);
+                if missing.is_empty() || reported.contains(&key) {
+                    continue;
+                }
+                reported.push(key);
+                errors.push(self.error(protected, &missing, &uri, selected.last(), route));
+            }
+        }
+    }
+
+    fn error(
+        &self,
+        protected: &Location,
+        missing: &[&str],
+        uri: &str,
+        handler: Option<&&Location>,
+        route: Route,
+    ) -> LintError {
+        let handler = match handler {
+            Some(location) => format!(
+                "location '{}' (line {})",
+                location.display(),
+                location.directive().span.start.line
+            ),
+            None => "the server block".to_string(),
+        };
+        let reason = match route {
+            Route::Regex => {
+                "regex locations take precedence over prefix locations without '^~'".to_string()
+            }
+            Route::Case => format!(
+                "this server uses case-insensitive '~*' locations, so on a case-insensitive \
+                 file system '{}' names the same files",
+                uri
+            ),
+        };
+        self.spec().error_builder().warning_at(
+            &format!(
+                "location '{}' is protected by {}, but '{}' is handled by {} without it; {}",
+                protected.display(),
+                missing.join(" and "),
+                uri,
+                handler,
+                reason
+            ),
+            protected.directive(),
+        )
+    }
+}
+
+impl Plugin for LocationAccessBypassPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "location-access-bypass",
+            "security",
+            "Detects protected prefix locations whose files are reachable through another location",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "Access restrictions in a prefix location only apply to requests nginx handles in \
+             that location. Regex locations are checked after the longest prefix match and \
+             win over it unless the prefix location uses `^~`, so with \
+             `location /admin/ { deny all; }` and `location ~ \\.php$ { ... }`, a request for \
+             `/admin/index.php` is handled by the regex location and the `deny` never \
+             applies.\n\n\
+             Prefix matching is also case-sensitive. Where the files live on a \
+             case-insensitive file system (which `~*` locations often indicate), `/ADMIN/` \
+             reaches the files of `/admin/` through another location.\n\n\
+             Nest the regex location inside the protected location, add `^~` to it, or move \
+             the restrictions to a level both locations inherit from.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#location".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/location_access_bypass/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        // Locations of a server split across files cannot be compared here
+        if config.include_context.contains("server") {
+            return errors;
+        }
+        self.check_items(&config.items, Protection::new(), &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(LocationAccessBypassPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_regex_location_bypasses_deny() {
+        let runner = PluginTestRunner::new(LocationAccessBypassPlugin);
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        location /admin/ {
+            deny all;
+        }
+        location ~ \.php$ {
+            fastcgi_pass php;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(4));
+        assert_eq!(
+            errors[0].message,
+            "location '/admin/' is protected by allow/deny, but '/admin/x.php' is handled by \
+             location '~ \\.php$' (line 7) without it; regex locations take precedence over \
+             prefix locations without '^~'"
+        );
+    }
+
+    #[test]
+    fn test_anchored_regex_into_protected_prefix() {
+        let runner = PluginTestRunner::new(LocationAccessBypassPlugin);
+        runner.assert_errors(
+            r#"
+server {
+    location /internal {
+        auth_request /auth;
+    }
+    location ~ ^/internal/reports/.+\.csv$ {
+        root /srv/reports;
+    }
+    location ~ ^/public/ {
+    }
+}
+"#,
+            1,
+        );
+    }
+
+    #[test]
+    fn test_different_case_with_case_insensitive_locations() {
+        let runner = PluginTestRunner::new(LocationAccessBypassPlugin);
+        let errors = runner
+            .check_string(
+                r#"
+server {
+    location ^~ /private/ {
+        auth_basic "Private";
+    }
+    location ~* \.(png|jpg)$ {
+        expires 30d;
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(
+            errors[0]
+                .message
+                .contains("'/PRIVATE/x' is handled by the server block without it"),
+            "got: {}",
+            errors[0].message
+        );
+    }
+
+    #[test]
+    fn test_alternate_route_keeps_protection() {
+        let runner = PluginTestRunner::new(LocationAccessBypassPlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        location /admin/ {
+            deny all;
+            location ~ \.php$ { fastcgi_pass php; }
+        }
+        location ^~ /ops/ {
+            auth_basic "Ops";
+        }
+        location /staff/ {
+            allow 10.0.0.0/8;
+            deny all;
+        }
+        location ~ ^/staff/.+\.pdf$ {
+            allow 10.0.0.0/8;
+            deny all;
+        }
+        location ~ ^/assets/ { }
+    }
+    server {
+        auth_basic "Site";
+        location /members/ {
+            auth_basic "Members";
+        }
+        location ~* \.php$ { fastcgi_pass php; }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_alternates_that_serve_nothing() {
+        let runner = PluginTestRunner::new(LocationAccessBypassPlugin);
+        runner.assert_no_errors(
+            r#"
+server {
+    location /admin/ {
+        deny all;
+    }
+    location ~ \.php$ {
+        return 404;
+    }
+    location ~ \.log$ {
+        internal;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_unsupported_regex_is_skipped() {
+        let runner = PluginTestRunner::new(LocationAccessBypassPlugin);
+        runner.assert_no_errors(
+            r#"
+server {
+    location /admin/ {
+        deny all;
+    }
+    location ~ ^/(?!health)\w+\.php$ {
+        fastcgi_pass php;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_included_server_fragment_is_skipped() {
+        let mut config =
+            parse_string("location /admin/ { deny all; }\nlocation ~ \\.php$ { }\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server");
+
+        assert!(
+            LocationAccessBypassPlugin
+                .check(&config, "test.conf")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(LocationAccessBypassPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(LocationAccessBypassPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
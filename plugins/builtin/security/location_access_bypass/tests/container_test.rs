@@ -0,0 +1,69 @@
+//! Container-based integration tests for the location-access-bypass rule.
+//!
+//! With the parent of the nginx config directory as `root`, `location
+//! /nginx/ { deny all; }` and a regex location for `.types` files,
+//! `/nginx/mime.types` is served by the regex location and the `deny` never
+//! applies. Nesting the regex location inside the protected one
+//! keeps the restriction.
+//!
+//! Run with:
+//!   cargo test -p location-access-bypass-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p location-access-bypass-plugin --test container_test -- --ignored
+
+use location_access_bypass_plugin::LocationAccessBypassPlugin;
+use nginx_lint_plugin::container_testing::{BehaviorTest, RequestCheck, nginx_conf_dir};
+
+/// Config protecting the config directory, and the URI of its mime.types
+fn config(nested: bool) -> (String, String) {
+    let (root, dir) = nginx_conf_dir().rsplit_once('/').unwrap();
+    let regex = r#"location ~ \.types$ { default_type text/plain; }"#;
+    let (inner, outer) = if nested { (regex, "") } else { ("", regex) };
+    let config = format!(
+        r#"
+http {{
+    server {{
+        listen 80;
+        root {root};
+
+        location = /healthz {{
+            return 200 'OK';
+        }}
+
+        location /{dir}/ {{
+            deny all;
+            {inner}
+        }}
+
+        {outer}
+    }}
+}}
+"#
+    );
+    (config, format!("/{}/mime.types", dir))
+}
+
+#[tokio::test]
+#[ignore]
+async fn regex_location_bypasses_deny() {
+    let (config, uri) = config(false);
+    BehaviorTest::new(&config)
+        .health_path("/healthz")
+        .linted_by(&LocationAccessBypassPlugin, 1)
+        .request(RequestCheck::get(&uri).expect_status(200))
+        .run()
+        .await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn nested_regex_location_keeps_deny() {
+    let (config, uri) = config(true);
+    BehaviorTest::new(&config)
+        .health_path("/healthz")
+        .linted_by(&LocationAccessBypassPlugin, 0)
+        .request(RequestCheck::get(&uri).expect_status(403))
+        .run()
+        .await;
+}
@@ -68,6 +68,7 @@ impl Plugin for MapUnnamedCapturePlugin {
             "Warns when a map regex entry uses an unnamed capture group (CVE-2026-42533)",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Minimal)
         .with_why(
             "A capture group in a `map` regex makes nginx reallocate its shared capture array. On \
              builds before nginx 1.30.4 / 1.31.3 (CVE-2026-42533), when that regex runs inside a \
@@ -0,0 +1,497 @@
+//! missing-security-headers plugin
+//!
+//! This plugin checks that every `server` block serving HTTP responses sends
+//! a set of security headers (by default `Strict-Transport-Security`,
+//! `X-Content-Type-Options`, `X-Frame-Options` and `Content-Security-Policy`).
+//! It is disabled by default: which headers an application needs is a policy
+//! decision, and many setups add them in the backend instead.
+//!
+//! `add_header` directives are inherited from the `http` block only when the
+//! server has none of its own, so the headers in effect for a server are
+//! either its own or the `http` ones, never both. Fixes add the missing
+//! headers to whichever of the two levels currently provides them.
+//!
+//! The header list can be replaced with `required_headers` in the rule config.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+
+/// Headers required when no list is configured
+pub const DEFAULT_REQUIRED_HEADERS: &[&str] = &[
+    "Strict-Transport-Security",
+    "X-Content-Type-Options",
+    "X-Frame-Options",
+    "Content-Security-Policy",
+];
+
+/// Values used by fixes; headers without an entry are reported without a fix
+const DEFAULT_HEADER_VALUES: &[(&str, &str)] = &[
+    (
+        "Strict-Transport-Security",
+        "\"max-age=31536000; includeSubDomains\"",
+    ),
+    ("X-Content-Type-Options", "\"nosniff\""),
+    ("X-Frame-Options", "\"SAMEORIGIN\""),
+    ("Content-Security-Policy", "\"frame-ancestors 'self'\""),
+    ("Referrer-Policy", "\"strict-origin-when-cross-origin\""),
+];
+
+/// Only meaningful over HTTPS, so not required for plain HTTP servers
+const HSTS: &str = "Strict-Transport-Security";
+
+/// Check for missing security headers in server blocks
+pub struct MissingSecurityHeadersPlugin {
+    required: Vec<String>,
+}
+
+impl Default for MissingSecurityHeadersPlugin {
+    fn default() -> Self {
+        Self::with_required_headers(DEFAULT_REQUIRED_HEADERS)
+    }
+}
+
+impl MissingSecurityHeadersPlugin {
+    /// Create a new plugin that requires the given headers (e.g.
+    /// `"X-Frame-Options"`). Header names are matched case-insensitively.
+    pub fn with_required_headers<S: AsRef<str>>(headers: &[S]) -> Self {
+        Self {
+            required: headers.iter().map(|h| h.as_ref().to_string()).collect(),
+        }
+    }
+
+    /// Check the servers in `items`
+    ///
+    /// `http_headers` holds the `add_header` directives of the enclosing
+    /// `http` block, or `None` when they are unknown (in an included file).
+    fn check_servers(
+        &self,
+        items: &[ConfigItem],
+        http_headers: Option<&[&Directive]>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let err = self.spec().error_builder();
+        // Headers added to the http block are fixed once for all servers
+        let mut http_fixed = false;
+
+        for item in items {
+            let ConfigItem::Directive(server) = item else {
+                continue;
+            };
+            let Some(block) = server.block.as_ref().filter(|_| server.is("server")) else {
+                continue;
+            };
+            let directives: Vec<&Directive> = block
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    ConfigItem::Directive(d) => Some(d.as_ref()),
+                    _ => None,
+                })
+                .collect();
+
+            // A server-level return answers every request itself (usually a
+            // redirect to HTTPS), so there are no pages to protect
+            if directives.iter().any(|d| d.is("return")) {
+                continue;
+            }
+
+            let own_headers: Vec<&Directive> = add_headers(&directives);
+            let inherited = own_headers.is_empty();
+            let effective = if inherited {
+                match http_headers {
+                    Some(headers) => headers,
+                    None => continue,
+                }
+            } else {
+                &own_headers[..]
+            };
+
+            let tls = directives.iter().any(|d| {
+                (d.is("listen") && (d.has_arg("ssl") || d.has_arg("quic")))
+                    || (d.is("ssl") && d.first_arg_is("on"))
+            });
+            let missing: Vec<&str> = self
+                .required
+                .iter()
+                .map(String::as_str)
+                .filter(|name| tls || !name.eq_ignore_ascii_case(HSTS))
+                .filter(|name| {
+                    !effective
+                        .iter()
+                        .any(|d| d.first_arg().is_some_and(|h| h.eq_ignore_ascii_case(name)))
+                })
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+
+            let mut error = err.warning_at(
+                &format!(
+                    "server does not send security header(s) {}",
+                    missing
+                        .iter()
+                        .map(|h| format!("'{}'", h))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                server,
+            );
+
+            let lines: Vec<String> = missing
+                .iter()
+                .filter_map(|name| {
+                    DEFAULT_HEADER_VALUES
+                        .iter()
+                        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+                        .map(|(known, value)| format!("add_header {} {} always;", known, value))
+                })
+                .collect();
+            let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+
+            if !lines.is_empty() {
+                // Adding headers to a server that inherits them from http
+                // would stop the inheritance, so extend the http list instead
+                let fix = match (effective.last(), inherited) {
+                    (Some(last), false) => Some(last.insert_after_many(&lines)),
+                    (Some(last), true) if !http_fixed => {
+                        http_fixed = true;
+                        Some(last.insert_after_many(&lines))
+                    }
+                    (Some(_), true) => None,
+                    (None, _) => directives
+                        .iter()
+                        .rev()
+                        .find(|d| d.is("listen") || d.is("server_name"))
+                        .map(|anchor| anchor.insert_after_many(&lines))
+                        .or_else(|| directives.first().map(|d| d.insert_before_many(&lines))),
+                };
+                // The default header values may not suit the site
+                if let Some(fix) = fix {
+                    error = error.with_fix(fix.with_applicability(Applicability::MaybeIncorrect));
+                }
+            }
+
+            errors.push(error);
+        }
+    }
+}
+
+/// `add_header` directives among `directives`
+fn add_headers<'a>(directives: &[&'a Directive]) -> Vec<&'a Directive> {
+    directives
+        .iter()
+        .copied()
+        .filter(|d| d.is("add_header"))
+        .collect()
+}
+
+impl Plugin for MissingSecurityHeadersPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "missing-security-headers",
+            "security",
+            "Detects server blocks that do not send common security headers",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Strict)
+        .with_why(
+            "Headers such as `Strict-Transport-Security`, `X-Content-Type-Options`, \
+             `X-Frame-Options` and `Content-Security-Policy` tell browsers to enforce HTTPS, \
+             stop MIME sniffing and refuse to frame the site. Without them, responses rely \
+             on browser defaults that allow downgrade, content sniffing and clickjacking \
+             attacks. `Strict-Transport-Security` is only required for servers listening \
+             with `ssl`.\n\n\
+             `add_header` directives are inherited from the `http` block only if the server \
+             does not define any of its own, so a single `add_header` in a server silently \
+             drops all headers set in `http`. The suggested fix adds the missing headers \
+             next to the ones already in effect, with `always` so they are also sent on \
+             error responses.\n\n\
+             This rule is disabled by default. Set `required_headers` to replace the list of \
+             headers to require.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_headers_module.html#add_header".to_string(),
+            "https://owasp.org/www-project-secure-headers/".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/missing_security_headers/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["server"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+
+        if config.include_context.is_empty() {
+            for http in config.directives().filter(|d| d.is("http")) {
+                let Some(block) = &http.block else {
+                    continue;
+                };
+                let directives: Vec<&Directive> = block
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        ConfigItem::Directive(d) => Some(d.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                let http_headers = add_headers(&directives);
+                self.check_servers(&block.items, Some(&http_headers), &mut errors);
+            }
+        } else if config.is_included_from_http() && !config.is_included_from("server") {
+            // The headers of the including http block are unknown, so only
+            // servers with their own add_header can be checked
+            self.check_servers(&config.items, None, &mut errors);
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(MissingSecurityHeadersPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(source: &str) -> Vec<LintError> {
+        let config = parse_string(source).unwrap();
+        MissingSecurityHeadersPlugin::default().check(&config, "test.conf")
+    }
+
+    #[test]
+    fn test_detects_missing_headers() {
+        let errors = check(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        server_name example.com;
+        add_header X-Frame-Options DENY;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(3));
+        assert_eq!(
+            errors[0].message,
+            "server does not send security header(s) 'Strict-Transport-Security', \
+             'X-Content-Type-Options', 'Content-Security-Policy'"
+        );
+        // The server already has add_header, so the fix extends its list
+        let fix = &errors[0].fixes[0];
+        assert_eq!(
+            fix.new_text,
+            "\n        add_header Strict-Transport-Security \"max-age=31536000; includeSubDomains\" always;\
+             \n        add_header X-Content-Type-Options \"nosniff\" always;\
+             \n        add_header Content-Security-Policy \"frame-ancestors 'self'\" always;"
+        );
+    }
+
+    #[test]
+    fn test_hsts_not_required_without_ssl() {
+        let runner = PluginTestRunner::new(MissingSecurityHeadersPlugin::default());
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 80;
+        add_header x-content-type-options nosniff;
+        add_header X-Frame-Options SAMEORIGIN;
+        add_header Content-Security-Policy "default-src 'self'";
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_headers_inherited_from_http() {
+        let config = r#"
+http {
+    add_header Strict-Transport-Security "max-age=31536000" always;
+    add_header X-Content-Type-Options nosniff always;
+    add_header X-Frame-Options DENY always;
+    add_header Content-Security-Policy "default-src 'self'" always;
+
+    server {
+        listen 443 ssl;
+    }
+    server {
+        listen 8443 ssl;
+        add_header X-Robots-Tag noindex;
+    }
+}
+"#;
+        let errors = check(config);
+
+        // The second server's own add_header stops the inheritance
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(11));
+        assert!(errors[0].message.contains("'X-Frame-Options'"));
+        assert_eq!(errors[0].fixes.len(), 1);
+    }
+
+    #[test]
+    fn test_fix_extends_http_headers_once() {
+        let errors = check(
+            r#"
+http {
+    add_header X-Content-Type-Options nosniff always;
+
+    server {
+        listen 80;
+    }
+    server {
+        listen 8080;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        // Inserting into a server would drop the inherited header, so the
+        // http block is extended, and only by the first error
+        assert_eq!(errors[0].fixes.len(), 1);
+        assert!(errors[1].fixes.is_empty());
+        assert!(
+            errors[0].fixes[0]
+                .new_text
+                .starts_with("\n    add_header X-Frame-Options \"SAMEORIGIN\" always;")
+        );
+        assert!(
+            !errors[0].fixes[0]
+                .new_text
+                .contains("Strict-Transport-Security")
+        );
+    }
+
+    #[test]
+    fn test_fix_without_any_headers() {
+        let errors = check(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        server_name example.com;
+        root /var/www;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        let fix = &errors[0].fixes[0];
+        // Inserted after server_name, with the server's indentation
+        assert!(
+            fix.new_text
+                .starts_with("\n        add_header Strict-Transport-Security")
+        );
+        assert_eq!(fix.new_text.matches("add_header").count(), 4);
+    }
+
+    #[test]
+    fn test_skips_redirect_servers() {
+        let runner = PluginTestRunner::new(MissingSecurityHeadersPlugin::default());
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 80;
+        server_name example.com;
+        return 301 https://example.com$request_uri;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_configured_headers() {
+        let runner = PluginTestRunner::new(MissingSecurityHeadersPlugin::with_required_headers(&[
+            "Referrer-Policy",
+            "Permissions-Policy",
+        ]));
+
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        listen 443 ssl;
+        add_header Referrer-Policy no-referrer;
+    }
+    server {
+        listen 8443 ssl;
+        add_header Permissions-Policy "camera=()";
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert!(errors[0].message.ends_with("'Permissions-Policy'"));
+        // No default value is known for Permissions-Policy
+        assert!(errors[0].fixes.is_empty());
+        assert!(errors[1].message.ends_with("'Referrer-Policy'"));
+        assert!(
+            errors[1].fixes[0]
+                .new_text
+                .contains("add_header Referrer-Policy \"strict-origin-when-cross-origin\" always;")
+        );
+    }
+
+    #[test]
+    fn test_included_files() {
+        let source = r#"
+server {
+    listen 443 ssl;
+}
+server {
+    listen 8443 ssl;
+    add_header X-Frame-Options DENY;
+}
+"#;
+
+        // From http: only the server with its own headers can be checked
+        let mut config = parse_string(source).unwrap();
+        config.include_context = vec!["http".to_string()].into();
+        let errors = MissingSecurityHeadersPlugin::default().check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+
+        let mut config = parse_string("add_header X-Frame-Options DENY;\n").unwrap();
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
+        let errors = MissingSecurityHeadersPlugin::default().check(&config, "test.conf");
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(MissingSecurityHeadersPlugin::default());
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(MissingSecurityHeadersPlugin::default());
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,109 @@
+//! Container-based integration tests for the missing-security-headers rule.
+//!
+//! Shows the inheritance pitfall the rule's fixes avoid: a single `add_header`
+//! in a server drops every header set in the `http` block, while extending
+//! the `http` list keeps all of them.
+//!
+//! Run with:
+//!   cargo test -p missing-security-headers-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p missing-security-headers-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, reqwest};
+
+fn header(resp: &reqwest::Response, name: &str) -> Option<String> {
+    resp.headers()
+        .get(name)
+        .map(|v| v.to_str().unwrap().to_string())
+}
+
+/// A server-level `add_header` replaces the headers inherited from `http`.
+#[tokio::test]
+#[ignore]
+async fn server_add_header_drops_http_headers() {
+    let nginx = NginxContainer::start(
+        br#"
+events {
+    worker_connections 1024;
+}
+http {
+    add_header X-Content-Type-Options "nosniff" always;
+
+    server {
+        listen 80;
+        add_header X-Frame-Options "SAMEORIGIN" always;
+
+        location / {
+            return 200 "ok";
+        }
+    }
+}
+"#,
+    )
+    .await;
+
+    let resp = reqwest::get(nginx.url("/")).await.unwrap();
+    assert_eq!(
+        header(&resp, "X-Frame-Options").as_deref(),
+        Some("SAMEORIGIN")
+    );
+    assert_eq!(
+        header(&resp, "X-Content-Type-Options"),
+        None,
+        "X-Content-Type-Options from http should be dropped"
+    );
+}
+
+/// Headers added next to the `http` ones, as the fix does, are all sent,
+/// including on error responses thanks to `always`.
+#[tokio::test]
+#[ignore]
+async fn http_headers_are_sent_with_always() {
+    let nginx = NginxContainer::start(
+        br#"
+events {
+    worker_connections 1024;
+}
+http {
+    add_header X-Content-Type-Options "nosniff" always;
+    add_header X-Frame-Options "SAMEORIGIN" always;
+    add_header Content-Security-Policy "frame-ancestors 'self'" always;
+
+    server {
+        listen 80;
+
+        location / {
+            return 200 "ok";
+        }
+        location /missing {
+            return 404;
+        }
+    }
+}
+"#,
+    )
+    .await;
+
+    for path in ["/", "/missing"] {
+        let resp = reqwest::get(nginx.url(path)).await.unwrap();
+        assert_eq!(
+            header(&resp, "X-Content-Type-Options").as_deref(),
+            Some("nosniff"),
+            "path: {}",
+            path
+        );
+        assert_eq!(
+            header(&resp, "X-Frame-Options").as_deref(),
+            Some("SAMEORIGIN"),
+            "path: {}",
+            path
+        );
+        assert_eq!(
+            header(&resp, "Content-Security-Policy").as_deref(),
+            Some("frame-ancestors 'self'"),
+            "path: {}",
+            path
+        );
+    }
+}
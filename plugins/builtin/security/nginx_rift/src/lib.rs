@@ -43,6 +43,7 @@ impl Plugin for NginxRiftPlugin {
              unnamed capture",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Minimal)
         .with_why(
             "CVE-2026-42945 (\"NGINX Rift\") and its redirect-path sibling \
              CVE-2026-9256 are heap buffer overflows in \
@@ -0,0 +1,354 @@
+//! permissive-cors plugin
+//!
+//! This plugin checks the CORS headers set with `add_header` and detects:
+//!
+//! - `Access-Control-Allow-Origin *` together with
+//!   `Access-Control-Allow-Credentials true`
+//! - `Access-Control-Allow-Origin $http_origin`, which allows every origin,
+//!   unless it is set inside an `if` that tests `$http_origin`
+//! - `Access-Control-Allow-Origin` set from a `map $http_origin` variable whose
+//!   `default` (or a match-everything regex) returns `$http_origin`
+//!
+//! `add_header` directives of one block replace the inherited ones, so each
+//! block's own list is checked on its own.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+use std::collections::HashSet;
+
+const ALLOW_ORIGIN: &str = "access-control-allow-origin";
+const ALLOW_CREDENTIALS: &str = "access-control-allow-credentials";
+
+/// Check for CORS headers that allow every origin
+#[derive(Default)]
+pub struct PermissiveCorsPlugin;
+
+/// Text of an argument as written, without quotes
+///
+/// A bare variable's value is its name without the `$`, so the raw text is
+/// used for it instead.
+fn arg_text(arg: &Argument) -> &str {
+    if arg.is_variable() {
+        arg.raw.as_str()
+    } else {
+        arg.as_str()
+    }
+}
+
+/// Whether a map key matches every value of the source variable
+fn matches_everything(key: &str) -> bool {
+    if key == "default" {
+        return true;
+    }
+    let Some(regex) = key.strip_prefix("~*").or_else(|| key.strip_prefix('~')) else {
+        return false;
+    };
+    let regex = regex.trim_start_matches('^').trim_end_matches('$');
+    matches!(regex, "" | ".*" | ".+" | ".")
+}
+
+/// Variables of `map $http_origin $var` blocks that return the origin for
+/// any request
+fn reflecting_map_variables(config: &Config) -> HashSet<String> {
+    config
+        .all_directives()
+        .filter(|d| d.is("map") && d.args.first().map(arg_text) == Some("$http_origin"))
+        .filter_map(|map| {
+            let variable = map.args.get(1)?;
+            let block = map.block.as_ref()?;
+            block
+                .directives()
+                .any(|entry| {
+                    matches_everything(&entry.name)
+                        && entry.args.first().map(arg_text) == Some("$http_origin")
+                })
+                .then(|| arg_text(variable).to_string())
+        })
+        .collect()
+}
+
+impl PermissiveCorsPlugin {
+    /// Check the `add_header` list of every block below `items`
+    ///
+    /// `origin_checked` is true inside an `if` whose condition tests
+    /// `$http_origin`, where reflecting the origin is a whitelist.
+    fn check_items(
+        &self,
+        items: &[ConfigItem],
+        origin_checked: bool,
+        reflecting: &HashSet<String>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let headers: Vec<&Directive> = items
+            .iter()
+            .filter_map(|item| match item {
+                ConfigItem::Directive(d) if d.is("add_header") => Some(d.as_ref()),
+                _ => None,
+            })
+            .collect();
+        self.check_headers(&headers, origin_checked, reflecting, errors);
+
+        for item in items {
+            if let ConfigItem::Directive(d) = item
+                && let Some(block) = &d.block
+            {
+                let checked = origin_checked
+                    || (d.is("if") && d.args.iter().any(|a| arg_text(a).contains("$http_origin")));
+                self.check_items(&block.items, checked, reflecting, errors);
+            }
+        }
+    }
+
+    fn check_headers(
+        &self,
+        headers: &[&Directive],
+        origin_checked: bool,
+        reflecting: &HashSet<String>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let value_of = |name: &str| {
+            headers.iter().find_map(|d| {
+                let header = d.args.first()?;
+                if !header.as_str().eq_ignore_ascii_case(name) {
+                    return None;
+                }
+                Some((*d, arg_text(d.args.get(1)?)))
+            })
+        };
+        let Some((origin, value)) = value_of(ALLOW_ORIGIN) else {
+            return;
+        };
+        let credentials = value_of(ALLOW_CREDENTIALS)
+            .is_some_and(|(_, value)| value.eq_ignore_ascii_case("true"));
+        let err = self.spec().error_builder();
+        let exposed = if credentials {
+            "any site can make credentialed requests and read the responses"
+        } else {
+            "any site can read the responses"
+        };
+
+        if value == "*" {
+            if credentials {
+                errors.push(err.warning_at(
+                    "Access-Control-Allow-Origin '*' with Access-Control-Allow-Credentials true \
+                     is rejected by browsers, and reflecting the Origin instead allows every \
+                     site; allow specific origins with a map of $http_origin",
+                    origin,
+                ));
+            }
+        } else if value == "$http_origin" {
+            if !origin_checked {
+                errors.push(err.warning_at(
+                    &format!(
+                        "Access-Control-Allow-Origin reflects $http_origin without a whitelist, \
+                         so {}; map $http_origin to the allowed origins",
+                        exposed
+                    ),
+                    origin,
+                ));
+            }
+        } else if reflecting.contains(value) {
+            errors.push(err.warning_at(
+                &format!(
+                    "Access-Control-Allow-Origin uses {}, whose map returns $http_origin for any \
+                     origin, so {}; make the map default empty",
+                    value, exposed
+                ),
+                origin,
+            ));
+        }
+    }
+}
+
+impl Plugin for PermissiveCorsPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "permissive-cors",
+            "security",
+            "Detects CORS headers that allow every origin",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "`Access-Control-Allow-Origin` tells browsers which other sites may read a \
+             response. Browsers refuse `*` for requests with cookies, so configurations that \
+             need `Access-Control-Allow-Credentials: true` often echo the request's `Origin` \
+             header back instead. Without a check of that origin, this allows every site on the \
+             internet, and with credentials any page the user visits can read their data from \
+             the API.\n\n\
+             Allow specific origins with a `map $http_origin` whose default is empty (no CORS \
+             headers for unknown origins), or reflect the origin only inside an `if` that tests \
+             `$http_origin`. Maps whose `default` returns `$http_origin` are reported as well, \
+             when defined in the same file.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://developer.mozilla.org/en-US/docs/Web/HTTP/Guides/CORS".to_string(),
+            "https://portswigger.net/web-security/cors".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_map_module.html".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/permissive_cors/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["add_header"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let reflecting = reflecting_map_variables(config);
+        self.check_items(&config.items, false, &reflecting, &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(PermissiveCorsPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(source: &str) -> Vec<LintError> {
+        let config = nginx_lint_plugin::parse_string(source).unwrap();
+        PermissiveCorsPlugin.check(&config, "test.conf")
+    }
+
+    #[test]
+    fn test_wildcard_with_credentials() {
+        let errors = check(
+            r#"
+http {
+    server {
+        location /api/ {
+            add_header Access-Control-Allow-Origin "*" always;
+            add_header access-control-allow-credentials true always;
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert!(
+            errors[0].message.starts_with(
+                "Access-Control-Allow-Origin '*' with Access-Control-Allow-Credentials"
+            )
+        );
+    }
+
+    #[test]
+    fn test_reflected_origin() {
+        let errors = check(
+            r#"
+http {
+    server {
+        add_header Access-Control-Allow-Origin $http_origin;
+        add_header Access-Control-Allow-Credentials true;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "Access-Control-Allow-Origin reflects $http_origin without a whitelist, so any \
+             site can make credentialed requests and read the responses; map $http_origin to \
+             the allowed origins"
+        );
+    }
+
+    #[test]
+    fn test_reflecting_map_default() {
+        let errors = check(
+            r#"
+http {
+    map $http_origin $cors {
+        ~^.*$ $http_origin;
+    }
+    server {
+        add_header Access-Control-Allow-Origin $cors;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].message.contains("uses $cors, whose map returns"));
+        assert!(
+            errors[0]
+                .message
+                .contains("so any site can read the responses")
+        );
+    }
+
+    #[test]
+    fn test_whitelisted_origins() {
+        let runner = PluginTestRunner::new(PermissiveCorsPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    map $http_origin $cors {
+        default "";
+        ~^https://[a-z]+\.example\.com$ $http_origin;
+    }
+    server {
+        location /a/ {
+            add_header Access-Control-Allow-Origin $cors;
+            add_header Access-Control-Allow-Credentials true;
+        }
+        location /b/ {
+            if ($http_origin ~ ^https://app\.example\.com$) {
+                add_header Access-Control-Allow-Origin $http_origin;
+            }
+        }
+        location /c/ {
+            add_header Access-Control-Allow-Origin *;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_headers_checked_per_block() {
+        // The location's own add_header replaces the server's list, so the
+        // wildcard and the credentials never appear together
+        let runner = PluginTestRunner::new(PermissiveCorsPlugin);
+
+        runner.assert_no_errors(
+            r#"
+server {
+    add_header Access-Control-Allow-Credentials true;
+    location / {
+        add_header Access-Control-Allow-Origin *;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(PermissiveCorsPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(PermissiveCorsPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
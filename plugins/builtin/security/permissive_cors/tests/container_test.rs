@@ -0,0 +1,79 @@
+//! Container-based integration tests for the permissive-cors rule.
+//!
+//! Shows that reflecting `$http_origin` grants CORS access to any origin,
+//! while a `map $http_origin` with an empty default only answers the listed
+//! origins.
+//!
+//! Run with:
+//!   cargo test -p permissive-cors-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p permissive-cors-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, reqwest};
+
+const CONFIG: &str = r#"
+events { worker_connections 1024; }
+http {
+    map $http_origin $cors_origin {
+        default "";
+        "https://app.example.com" $http_origin;
+    }
+
+    server {
+        listen 80;
+        location = / { return 200 "ok"; }
+        location /reflect {
+            add_header Access-Control-Allow-Origin $http_origin always;
+            add_header Access-Control-Allow-Credentials true always;
+            return 200 "secret";
+        }
+        location /mapped {
+            add_header Access-Control-Allow-Origin $cors_origin always;
+            add_header Access-Control-Allow-Credentials true always;
+            return 200 "secret";
+        }
+    }
+}
+"#;
+
+/// Access-Control-Allow-Origin returned for a request from `origin`
+async fn allowed_origin(nginx: &NginxContainer, path: &str, origin: &str) -> Option<String> {
+    let resp = reqwest::Client::new()
+        .get(nginx.url(path))
+        .header("Origin", origin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    resp.headers()
+        .get("access-control-allow-origin")
+        .map(|v| v.to_str().unwrap().to_string())
+}
+
+/// Reflecting the Origin allows an arbitrary attacker site.
+#[tokio::test]
+#[ignore]
+async fn reflected_origin_allows_any_site() {
+    let nginx = NginxContainer::start(CONFIG).await;
+    assert_eq!(
+        allowed_origin(&nginx, "/reflect", "https://evil.example.net").await,
+        Some("https://evil.example.net".to_string())
+    );
+}
+
+/// The map only answers listed origins; nginx omits the header when the
+/// value is empty.
+#[tokio::test]
+#[ignore]
+async fn map_whitelist_rejects_unknown_site() {
+    let nginx = NginxContainer::start(CONFIG).await;
+    assert_eq!(
+        allowed_origin(&nginx, "/mapped", "https://evil.example.net").await,
+        None
+    );
+    assert_eq!(
+        allowed_origin(&nginx, "/mapped", "https://app.example.com").await,
+        Some("https://app.example.com".to_string())
+    );
+}
@@ -0,0 +1,406 @@
+//! plaintext-sensitive-upstream plugin
+//!
+//! This opt-in plugin reports `proxy_pass http://` in locations whose path
+//! suggests sensitive data (`/login`, `/payment`, `/admin`, ...) when the
+//! backend is not on the same host. Credentials, session cookies and card
+//! data then cross the network between nginx and the backend unencrypted.
+//!
+//! Backends on a unix socket or a loopback address (`127.0.0.0/8`, `::1`,
+//! `localhost`) are not reported, nor are upstream blocks whose servers are
+//! all such addresses. Upstream blocks are looked up in the same file.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::helpers::extract_host_from_url;
+use nginx_lint_plugin::prelude::*;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Path fragments that make a location sensitive by default
+const DEFAULT_SENSITIVE_PATHS: &[&str] = &[
+    "/login",
+    "/signin",
+    "/auth",
+    "/account",
+    "/password",
+    "/payment",
+    "/checkout",
+    "/admin",
+];
+
+/// Check sensitive locations for plaintext proxying to remote backends
+pub struct PlaintextSensitiveUpstreamPlugin {
+    /// Path fragments that make a location sensitive (option
+    /// `sensitive_paths`), matched case-insensitively
+    sensitive_paths: Vec<String>,
+}
+
+impl Default for PlaintextSensitiveUpstreamPlugin {
+    fn default() -> Self {
+        Self {
+            sensitive_paths: DEFAULT_SENSITIVE_PATHS
+                .iter()
+                .map(|path| path.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Whether `host` (with an optional port) is a unix socket or a loopback
+/// address
+fn is_local(host: &str) -> bool {
+    if host.starts_with("unix:") {
+        return true;
+    }
+    if let Some(rest) = host.strip_prefix('[') {
+        let address = rest.split(']').next().unwrap_or(rest);
+        return address.parse::<Ipv6Addr>().is_ok_and(|ip| ip.is_loopback());
+    }
+    let name = host.split(':').next().unwrap_or(host);
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<Ipv4Addr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Record, for each upstream block in `items` and the blocks nested in
+/// them, whether all of its servers are local
+fn collect_upstreams<'a>(items: &'a [ConfigItem], upstreams: &mut HashMap<&'a str, bool>) {
+    for item in items {
+        let ConfigItem::Directive(directive) = item else {
+            continue;
+        };
+        let Some(block) = &directive.block else {
+            continue;
+        };
+        if directive.is("upstream") {
+            if let Some(name) = directive.first_arg() {
+                let local = block
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        ConfigItem::Directive(d) if d.is("server") => d.first_arg(),
+                        _ => None,
+                    })
+                    .all(is_local);
+                upstreams.insert(name, local);
+            }
+        } else {
+            collect_upstreams(&block.items, upstreams);
+        }
+    }
+}
+
+impl PlaintextSensitiveUpstreamPlugin {
+    /// Whether a location matching `pattern` is sensitive
+    fn is_sensitive(&self, pattern: &str) -> bool {
+        let pattern = pattern.to_ascii_lowercase();
+        self.sensitive_paths
+            .iter()
+            .any(|path| pattern.contains(&path.to_ascii_lowercase()))
+    }
+
+    /// Walk `items`; `location` is the pattern of the closest enclosing
+    /// sensitive location
+    fn check_items(
+        &self,
+        items: &[ConfigItem],
+        location: Option<&str>,
+        upstreams: &HashMap<&str, bool>,
+        errors: &mut Vec<LintError>,
+    ) {
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if let Some(block) = &directive.block {
+                if directive.is("upstream") {
+                    continue;
+                }
+                let pattern = directive
+                    .args
+                    .last()
+                    .map(|arg| arg.as_str())
+                    .filter(|pattern| directive.is("location") && self.is_sensitive(pattern));
+                self.check_items(&block.items, pattern.or(location), upstreams, errors);
+            } else if directive.is("proxy_pass")
+                && let Some(location) = location
+            {
+                self.check_proxy_pass(directive, location, upstreams, errors);
+            }
+        }
+    }
+
+    fn check_proxy_pass(
+        &self,
+        directive: &Directive,
+        location: &str,
+        upstreams: &HashMap<&str, bool>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let Some(arg) = directive.args.first() else {
+            return;
+        };
+        let url = arg.as_str();
+        // The backend is only known at runtime (`http://$backend` lexes as
+        // two arguments)
+        if directive.args.iter().any(|arg| arg.raw.contains('$')) {
+            return;
+        }
+        if !url
+            .get(..7)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("http://"))
+        {
+            return;
+        }
+        let Some(host) = extract_host_from_url(url) else {
+            return;
+        };
+        let local = match upstreams.get(host) {
+            Some(local) => *local,
+            None => is_local(host),
+        };
+        if local {
+            return;
+        }
+
+        let message = format!(
+            "location {} looks sensitive but proxies to {} over plaintext HTTP; use https:// \
+             to the backend",
+            location, host
+        );
+        errors.push(self.spec().error_builder().warning_at(&message, directive));
+    }
+}
+
+impl Plugin for PlaintextSensitiveUpstreamPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "plaintext-sensitive-upstream",
+            "security",
+            "Detects plaintext proxy_pass to remote backends from sensitive locations",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "TLS between the client and nginx does not protect the hop from nginx to the \
+             backend. With `proxy_pass http://`, passwords, session cookies and payment data \
+             sent to login, payment or admin locations cross the network unencrypted, where \
+             anyone on the path between the two hosts can read or alter them.\n\n\
+             Proxy such locations over `https://` (with `proxy_ssl_verify on` and \
+             `proxy_ssl_trusted_certificate` to authenticate the backend), or run the backend \
+             on the same host. Backends on a unix socket or a loopback address, and upstream \
+             blocks in the same file whose servers all are, are not reported. When the network \
+             in between is trusted, suppress the warning with an ignore comment stating so.\n\n\
+             A location is sensitive when its path contains one of the `sensitive_paths` \
+             (case-insensitive). The rule is disabled by default.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_pass".to_string(),
+            "https://docs.nginx.com/nginx/admin-guide/security-controls/securing-http-traffic-upstream/"
+                .to_string(),
+        ])
+        .with_option(RuleOption::string_list(
+            "sensitive_paths",
+            DEFAULT_SENSITIVE_PATHS,
+            "Path fragments that make a location sensitive (case-insensitive)",
+        ))
+    }
+
+    fn configure(&mut self, options: &RuleOptions) {
+        self.sensitive_paths = options.string_list("sensitive_paths").to_vec();
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        // Locations enclosing a kept proxy_pass are retained as ancestors
+        Some(&["proxy_pass", "upstream", "server"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let mut upstreams = HashMap::new();
+        collect_upstreams(&config.items, &mut upstreams);
+
+        // Top-level directives belong to the location the file is included from
+        let location = config
+            .include_context
+            .innermost("location")
+            .and_then(|frame| frame.args.last())
+            .map(String::as_str)
+            .filter(|pattern| self.is_sensitive(pattern));
+        self.check_items(&config.items, location, &upstreams, &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(PlaintextSensitiveUpstreamPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn runner() -> PluginTestRunner<PlaintextSensitiveUpstreamPlugin> {
+        PluginTestRunner::new(PlaintextSensitiveUpstreamPlugin::default())
+    }
+
+    #[test]
+    fn test_plaintext_remote_backend() {
+        let errors = runner()
+            .check_string(
+                r#"
+http {
+    server {
+        location / {
+            proxy_pass http://10.0.0.5:8080;
+        }
+
+        location /login {
+            proxy_pass http://10.0.0.5:8080;
+        }
+
+        location ~* ^/API/Payment/ {
+            proxy_pass http://payments.internal;
+        }
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(9));
+        assert_eq!(
+            errors[0].message,
+            "location /login looks sensitive but proxies to 10.0.0.5:8080 over plaintext \
+             HTTP; use https:// to the backend"
+        );
+        assert_eq!(errors[1].line, Some(13));
+    }
+
+    #[test]
+    fn test_local_and_tls_backends() {
+        runner().assert_no_errors(
+            r#"
+http {
+    upstream app {
+        server 127.0.0.1:8080;
+        server unix:/run/app.sock;
+    }
+
+    server {
+        location /admin/ {
+            proxy_pass https://10.0.0.5;
+        }
+
+        location /login {
+            proxy_pass http://127.0.0.1:3000;
+        }
+
+        location /account {
+            proxy_pass http://[::1]:3000/account;
+        }
+
+        location /checkout {
+            proxy_pass http://localhost:3000;
+        }
+
+        location /payment {
+            proxy_pass http://unix:/run/pay.sock:/;
+        }
+
+        location /auth {
+            proxy_pass http://app;
+        }
+
+        location /password {
+            proxy_pass http://$backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_remote_upstream_block() {
+        runner().assert_error_on_line(
+            r#"
+http {
+    upstream app {
+        server 127.0.0.1:8080;
+        server 10.0.0.6:8080 backup;
+    }
+
+    server {
+        location /admin {
+            location /admin/static/ {
+                root /srv;
+            }
+            proxy_pass http://app;
+        }
+    }
+}
+"#,
+            13,
+        );
+    }
+
+    #[test]
+    fn test_nested_location() {
+        runner().assert_error_on_line(
+            r#"
+http {
+    server {
+        location /admin/ {
+            location ~ \.php$ {
+                proxy_pass http://php.internal;
+            }
+        }
+    }
+}
+"#,
+            6,
+        );
+    }
+
+    #[test]
+    fn test_configured_paths() {
+        use std::collections::HashMap;
+
+        let mut plugin = PlaintextSensitiveUpstreamPlugin::default();
+        let configured = HashMap::from([("sensitive_paths".to_string(), vec!["/billing"].into())]);
+        plugin.configure(&RuleOptions::resolve(&plugin.spec().options, &configured));
+        let runner = PluginTestRunner::new(plugin);
+
+        let config = "http {\n    server {\n        location /login {\n            proxy_pass http://10.0.0.5;\n        }\n        location /billing {\n            proxy_pass http://10.0.0.5;\n        }\n    }\n}\n";
+        runner.assert_error_on_line(config, 7);
+        runner.assert_errors(config, 1);
+    }
+
+    #[test]
+    fn test_included_file() {
+        let mut config = nginx_lint_plugin::parse_string("proxy_pass http://10.0.0.5;\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server,location=/admin/");
+        let plugin = PlaintextSensitiveUpstreamPlugin::default();
+        assert_eq!(plugin.check(&config, "test.conf").len(), 1);
+
+        config.include_context = IncludeContext::parse("http,server,location=/static/");
+        assert!(plugin.check(&config, "test.conf").is_empty());
+    }
+
+    #[test]
+    fn test_examples() {
+        runner().test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        runner().test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
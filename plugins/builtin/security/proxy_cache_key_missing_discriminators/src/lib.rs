@@ -0,0 +1,754 @@
+//! proxy-cache-key-missing-discriminators plugin
+//!
+//! This plugin warns when a `proxy_cache_key` cannot tell apart requests that
+//! must not share a cache entry:
+//!
+//! - the key omits `$host` while the cache zone is used by several server names
+//! - the key omits `$scheme` while the cache zone serves both http and https
+//! - the key uses `$uri` without the query string
+//! - `proxy_ignore_headers Vary` is set but the key has no request header variable
+//!
+//! Each of these lets a response generated for one request be served for
+//! another (cache poisoning or cross-vhost leaks).
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedSettings};
+use nginx_lint_plugin::prelude::*;
+use nginx_lint_plugin::variables::variable_references;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// nginx's key when `proxy_cache_key` is not set
+const DEFAULT_CACHE_KEY: &str = "$scheme$proxy_host$request_uri";
+
+/// Variables that identify the requested virtual host
+const HOST_VARIABLES: &[&str] = &["host", "http_host"];
+
+/// Variables that carry the query string
+const ARGS_VARIABLES: &[&str] = &["args", "query_string", "request_uri"];
+
+/// Variables holding the path without the query string
+const URI_VARIABLES: &[&str] = &["uri", "document_uri"];
+
+/// Check proxy_cache_key for missing request discriminators
+#[derive(Default)]
+pub struct ProxyCacheKeyMissingDiscriminatorsPlugin;
+
+/// Proxy cache settings in effect at a block (inherited from enclosing blocks)
+#[derive(Clone, Copy)]
+struct CacheScope<'a>(InheritedSettings<'a, 3>);
+
+impl<'a> CacheScope<'a> {
+    fn new() -> Self {
+        Self(InheritedSettings::new(
+            ["proxy_cache", "proxy_cache_key", "proxy_ignore_headers"],
+            Inherited::Default,
+        ))
+    }
+
+    /// Scope of a nested block: its own settings override the inherited ones
+    fn enter(&self, items: &'a [ConfigItem]) -> Self {
+        Self(self.0.enter(items))
+    }
+
+    /// The `proxy_cache_key` in effect, if set
+    fn key(&self) -> Option<&'a Directive> {
+        self.0.get("proxy_cache_key").directive()
+    }
+
+    /// The `proxy_ignore_headers` in effect, if set
+    fn ignore_headers(&self) -> Option<&'a Directive> {
+        self.0.get("proxy_ignore_headers").directive()
+    }
+
+    /// Cache zone in use, if caching is enabled
+    fn zone(&self) -> Option<&'a str> {
+        self.0
+            .get("proxy_cache")
+            .directive()
+            .and_then(|d| d.first_arg())
+            .filter(|zone| *zone != "off")
+    }
+
+    /// Variables referenced by the effective cache key
+    fn key_variables(&self) -> Vec<&'a str> {
+        match self.key() {
+            Some(key) => key
+                .args
+                .iter()
+                .flat_map(|arg| variable_references(&arg.raw))
+                .collect(),
+            None => variable_references(DEFAULT_CACHE_KEY),
+        }
+    }
+
+    /// Whether proxy_ignore_headers makes nginx ignore the backend's Vary
+    fn ignores_vary(&self) -> bool {
+        self.ignore_headers().is_some_and(|d| {
+            d.args
+                .iter()
+                .any(|arg| arg.as_str().eq_ignore_ascii_case("vary"))
+        })
+    }
+}
+
+/// A block that proxies requests while a cache zone is enabled
+struct CacheUse<'a> {
+    zone: &'a str,
+    server: usize,
+    scope: CacheScope<'a>,
+}
+
+/// Virtual hosts and protocols of a server block
+struct ServerInfo<'a> {
+    names: Vec<&'a str>,
+    has_tls: bool,
+    has_plain: bool,
+}
+
+impl<'a> ServerInfo<'a> {
+    fn from_block(block: &'a Block) -> Self {
+        let names = block
+            .directives()
+            .filter(|d| d.is("server_name"))
+            .flat_map(|d| d.args.iter().map(|arg| arg.as_str()))
+            .filter(|name| !name.is_empty() && *name != "_")
+            .collect();
+
+        let mut has_tls = false;
+        let mut has_plain = false;
+        let mut has_listen = false;
+        for listen in block.directives().filter(|d| d.is("listen")) {
+            has_listen = true;
+            if listen
+                .args
+                .iter()
+                .skip(1)
+                .any(|arg| arg.as_str() == "ssl" || arg.as_str() == "quic")
+            {
+                has_tls = true;
+            } else {
+                has_plain = true;
+            }
+        }
+
+        Self {
+            names,
+            has_tls,
+            // Without listen, a server listens on *:80
+            has_plain: has_plain || !has_listen,
+        }
+    }
+}
+
+/// Virtual hosts and protocols of all servers sharing a cache zone
+#[derive(Default)]
+struct ZoneInfo<'a> {
+    names: BTreeSet<&'a str>,
+    has_tls: bool,
+    has_plain: bool,
+}
+
+impl ZoneInfo<'_> {
+    /// Whether requests for different hosts can reach this zone
+    fn is_multi_host(&self) -> bool {
+        // A wildcard or regex server_name matches many hosts by itself
+        self.names.len() > 1
+            || self
+                .names
+                .iter()
+                .any(|name| name.starts_with(['*', '.', '~']) || name.ends_with('*'))
+    }
+
+    fn names_for_message(&self) -> String {
+        const MAX: usize = 3;
+        let mut names: Vec<_> = self.names.iter().take(MAX).copied().collect();
+        if self.names.len() > MAX {
+            names.push("...");
+        }
+        names.join(", ")
+    }
+}
+
+impl ProxyCacheKeyMissingDiscriminatorsPlugin {
+    /// Record cache uses in a block and its nested blocks
+    fn collect_uses<'a>(
+        block: &'a Block,
+        scope: CacheScope<'a>,
+        server: usize,
+        uses: &mut Vec<CacheUse<'a>>,
+    ) {
+        if let Some(zone) = scope.zone()
+            && block.directives().any(|d| d.is("proxy_pass"))
+        {
+            uses.push(CacheUse {
+                zone,
+                server,
+                scope,
+            });
+        }
+
+        for child in block.directives() {
+            if let Some(child_block) = &child.block
+                && (child.is("location") || child.is("if"))
+            {
+                Self::collect_uses(child_block, scope.enter(&child_block.items), server, uses);
+            }
+        }
+    }
+
+    /// Check the servers of one http context
+    fn check_servers<'a>(
+        &self,
+        servers: impl Iterator<Item = &'a Directive>,
+        http_scope: CacheScope<'a>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let mut infos = Vec::new();
+        let mut uses = Vec::new();
+        for server in servers {
+            let Some(block) = &server.block else {
+                continue;
+            };
+            Self::collect_uses(
+                block,
+                http_scope.enter(&block.items),
+                infos.len(),
+                &mut uses,
+            );
+            infos.push(ServerInfo::from_block(block));
+        }
+
+        let mut zones: HashMap<&str, ZoneInfo> = HashMap::new();
+        for cache_use in &uses {
+            let info = &infos[cache_use.server];
+            let zone = zones.entry(cache_use.zone).or_default();
+            zone.names.extend(info.names.iter().copied());
+            zone.has_tls |= info.has_tls;
+            zone.has_plain |= info.has_plain;
+        }
+
+        let err = self.spec().error_builder();
+        // Directives are reported once even if they apply to many locations
+        let mut reported: HashSet<(usize, &str)> = HashSet::new();
+        for cache_use in &uses {
+            let zone = &zones[cache_use.zone];
+            let scope = &cache_use.scope;
+            let variables = scope.key_variables();
+            let uses_any = |names: &[&str]| variables.iter().any(|v| names.contains(v));
+
+            if let Some(key) = scope.key() {
+                let offset = key.span.start.offset;
+
+                if zone.is_multi_host()
+                    && !uses_any(HOST_VARIABLES)
+                    && reported.insert((offset, "host"))
+                {
+                    errors.push(err.warning_at(
+                        &format!(
+                            "proxy_cache_key does not include $host, but cache zone '{}' is \
+                             shared by multiple server names ({}); a response cached for one \
+                             host can be served for another",
+                            cache_use.zone,
+                            zone.names_for_message()
+                        ),
+                        key,
+                    ));
+                }
+
+                if zone.has_tls
+                    && zone.has_plain
+                    && !uses_any(&["scheme"])
+                    && reported.insert((offset, "scheme"))
+                {
+                    errors.push(err.warning_at(
+                        &format!(
+                            "proxy_cache_key does not include $scheme, but cache zone '{}' \
+                             serves both http and https; responses (e.g. redirects) cached \
+                             for one protocol can be served for the other",
+                            cache_use.zone
+                        ),
+                        key,
+                    ));
+                }
+
+                let has_args =
+                    uses_any(ARGS_VARIABLES) || variables.iter().any(|v| v.starts_with("arg_"));
+                if uses_any(URI_VARIABLES) && !has_args && reported.insert((offset, "args")) {
+                    errors.push(err.warning_at(
+                        "proxy_cache_key uses $uri without the query string; requests that \
+                         differ only in their arguments share one cache entry. Use \
+                         $request_uri or append $is_args$args",
+                        key,
+                    ));
+                }
+            }
+
+            if let Some(ignore) = scope.ignore_headers()
+                && scope.ignores_vary()
+                && !variables.iter().any(|v| {
+                    (v.starts_with("http_") && *v != "http_host") || v.starts_with("cookie_")
+                })
+                && reported.insert((ignore.span.start.offset, "vary"))
+            {
+                errors.push(err.warning_at(
+                    "proxy_ignore_headers Vary stores a single variant per cache key, but \
+                     proxy_cache_key has no request header variable to tell variants apart \
+                     (e.g. $http_accept_encoding); clients can receive a compressed or \
+                     localized response meant for others",
+                    ignore,
+                ));
+            }
+        }
+    }
+}
+
+impl Plugin for ProxyCacheKeyMissingDiscriminatorsPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "proxy-cache-key-missing-discriminators",
+            "security",
+            "Detects proxy_cache_key values that let unrelated requests share a cache entry",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "nginx stores and looks up cached responses by `proxy_cache_key` (default \
+             `$scheme$proxy_host$request_uri`). Everything that changes the response must be \
+             part of the key, otherwise a response generated for one request is served for \
+             another.\n\n\
+             - When several server names use the same cache zone, a key without `$host` mixes \
+             up virtual hosts: a page (or a poisoned response) for one site is served on the \
+             other.\n\
+             - When the zone serves both http and https, a key without `$scheme` serves \
+             responses such as `http://` redirects on https, causing redirect loops or mixed \
+             content.\n\
+             - A key built from `$uri` drops the query string, so `/search?q=a` and \
+             `/search?q=b` share an entry.\n\
+             - `proxy_ignore_headers Vary` tells nginx to ignore the backend's `Vary` header, so \
+             only one variant (gzip or identity, one language) is cached per key unless the \
+             key includes the request header it varies on.\n\n\
+             Only keys and `proxy_ignore_headers` in effect where a cached location has \
+             `proxy_pass` are checked, and zone sharing is determined from the servers in the \
+             same file.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_cache_key".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_ignore_headers"
+                .to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&[
+            "http",
+            "server",
+            "location",
+            "if",
+            "server_name",
+            "listen",
+            "proxy_pass",
+            "proxy_cache",
+            "proxy_cache_key",
+            "proxy_ignore_headers",
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+
+        for http in config.all_directives().filter(|d| d.is("http")) {
+            let Some(block) = &http.block else {
+                continue;
+            };
+            let scope = CacheScope::new().enter(&block.items);
+            self.check_servers(
+                block.directives().filter(|d| d.is("server")),
+                scope,
+                &mut errors,
+            );
+        }
+
+        // A file included from http holds server blocks at the top level;
+        // settings from the parent http block are unknown
+        if config.immediate_parent_context() == Some("http") {
+            let scope = CacheScope::new().enter(&config.items);
+            self.check_servers(
+                config.directives().filter(|d| d.is("server")),
+                scope,
+                &mut errors,
+            );
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_key_without_host_shared_by_servers() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_error_message_contains(
+            r#"
+http {
+    proxy_cache_key $scheme$request_uri;
+    server {
+        server_name a.example.com;
+        location / {
+            proxy_cache shared;
+            proxy_pass http://backend;
+        }
+    }
+    server {
+        server_name b.example.com;
+        location / {
+            proxy_cache shared;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            "shared by multiple server names (a.example.com, b.example.com)",
+        );
+    }
+
+    #[test]
+    fn test_key_without_host_single_server_multiple_names() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_errors(
+            r#"
+http {
+    server {
+        server_name example.com www.example.com;
+        location / {
+            proxy_cache zone;
+            proxy_cache_key $scheme$request_uri;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            1,
+        );
+    }
+
+    #[test]
+    fn test_key_with_host_is_fine() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    proxy_cache_key $scheme$host$request_uri;
+    server {
+        listen 80;
+        listen 443 ssl;
+        server_name a.example.com b.example.com;
+        location / {
+            proxy_cache shared;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_single_host_without_host_variable() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        server_name example.com;
+        location / {
+            proxy_cache zone;
+            proxy_cache_key $scheme$request_uri;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_separate_zones_are_not_shared() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    proxy_cache_key $scheme$request_uri;
+    server {
+        server_name a.example.com;
+        proxy_cache zone_a;
+        location / {
+            proxy_pass http://backend;
+        }
+    }
+    server {
+        server_name b.example.com;
+        proxy_cache zone_b;
+        location / {
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_wildcard_server_name() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_error_message_contains(
+            r#"
+http {
+    server {
+        server_name *.example.com;
+        location / {
+            proxy_cache zone;
+            proxy_cache_key $scheme$request_uri;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            "does not include $host",
+        );
+    }
+
+    #[test]
+    fn test_key_without_scheme_for_http_and_https() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_error_message_contains(
+            r#"
+http {
+    server {
+        listen 80;
+        listen 443 ssl;
+        server_name example.com;
+        location / {
+            proxy_cache zone;
+            proxy_cache_key $host$request_uri;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            "does not include $scheme",
+        );
+    }
+
+    #[test]
+    fn test_key_without_scheme_https_only() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        server_name example.com;
+        location / {
+            proxy_cache zone;
+            proxy_cache_key $host$request_uri;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_uri_without_args() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_error_message_contains(
+            r#"
+http {
+    server {
+        server_name example.com;
+        location /search {
+            proxy_cache zone;
+            proxy_cache_key $scheme$host$uri;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            "uses $uri without the query string",
+        );
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        server_name example.com;
+        location /search {
+            proxy_cache zone;
+            proxy_cache_key $scheme$host$uri$is_args$args;
+            proxy_pass http://backend;
+        }
+        location /page {
+            proxy_cache zone;
+            proxy_cache_key "$scheme$host$uri?p=$arg_p";
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_ignore_vary_without_header_variable() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        // Applies to the default key as well
+        runner.assert_error_message_contains(
+            r#"
+http {
+    server {
+        server_name example.com;
+        location / {
+            proxy_cache zone;
+            proxy_ignore_headers Cache-Control Vary;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+            "proxy_ignore_headers Vary",
+        );
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        server_name example.com;
+        location / {
+            proxy_cache zone;
+            proxy_cache_key $scheme$host$request_uri$http_accept_encoding;
+            proxy_ignore_headers Vary;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_cache_off_and_no_proxy_pass() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    proxy_cache zone;
+    proxy_cache_key $uri;
+    server {
+        server_name a.example.com b.example.com;
+        location / {
+            proxy_cache off;
+            proxy_pass http://backend;
+        }
+        location /static/ {
+            root /var/www;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_key_reported_once() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+
+        runner.assert_errors(
+            r#"
+http {
+    proxy_cache zone;
+    proxy_cache_key $request_uri;
+    server {
+        server_name a.example.com;
+        location /one { proxy_pass http://backend; }
+        location /two { proxy_pass http://backend; }
+    }
+    server {
+        server_name b.example.com;
+        location / { proxy_pass http://backend; }
+    }
+}
+"#,
+            1,
+        );
+    }
+
+    #[test]
+    fn test_included_servers() {
+        use nginx_lint_plugin::parse_string;
+
+        let mut config = parse_string(
+            r#"
+server {
+    server_name a.example.com;
+    location / {
+        proxy_cache zone;
+        proxy_cache_key $scheme$request_uri;
+        proxy_pass http://backend;
+    }
+}
+server {
+    server_name b.example.com;
+    location / {
+        proxy_cache zone;
+        proxy_cache_key $scheme$request_uri;
+        proxy_pass http://backend;
+    }
+}
+"#,
+        )
+        .unwrap();
+        config.include_context = vec!["http".to_string()].into();
+
+        let errors = ProxyCacheKeyMissingDiscriminatorsPlugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 2, "Expected 2 errors, got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(ProxyCacheKeyMissingDiscriminatorsPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,97 @@
+//! Container-based integration tests for the proxy-cache-key-missing-discriminators rule.
+//!
+//! Verifies that when two virtual hosts share a cache zone and the cache key
+//! omits `$host`, a response cached for one host is served for the other.
+//!
+//! Each test uses a backend server on port 8080 that echoes the Host header it
+//! received, and two frontend servers on port 80 (`a.example.com` and
+//! `b.example.com`) that proxy to it through the same cache zone.
+//!
+//! Run with:
+//!   cargo test -p proxy-cache-key-missing-discriminators-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p proxy-cache-key-missing-discriminators-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, reqwest};
+
+/// Build a config with two vhosts sharing one cache zone and the given key.
+fn shared_cache_config(cache_key: &str) -> Vec<u8> {
+    format!(
+        r#"
+events {{
+    worker_connections 1024;
+}}
+http {{
+    proxy_cache_path /tmp/nginx-cache keys_zone=shared:1m;
+    proxy_cache_key {cache_key};
+    proxy_cache_valid 200 1m;
+
+    server {{
+        listen 8080;
+        location / {{
+            return 200 $http_host;
+        }}
+    }}
+
+    server {{
+        listen 80;
+        server_name a.example.com;
+        location /page {{
+            proxy_cache shared;
+            proxy_set_header Host $host;
+            proxy_pass http://127.0.0.1:8080;
+        }}
+    }}
+
+    server {{
+        listen 80;
+        server_name b.example.com;
+        location /page {{
+            proxy_cache shared;
+            proxy_set_header Host $host;
+            proxy_pass http://127.0.0.1:8080;
+        }}
+    }}
+}}
+"#
+    )
+    .into_bytes()
+}
+
+/// Fetch /page with the given Host header and return the body.
+async fn get_page(nginx: &NginxContainer, host: &str) -> String {
+    reqwest::Client::new()
+        .get(nginx.url("/page"))
+        .header("Host", host)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap()
+}
+
+/// Without $host in the key, b.example.com receives the response cached for a.example.com.
+#[tokio::test]
+#[ignore]
+async fn key_without_host_serves_other_vhost_response() {
+    let nginx = NginxContainer::start(shared_cache_config("$scheme$request_uri")).await;
+
+    assert_eq!(get_page(&nginx, "a.example.com").await, "a.example.com");
+    let body = get_page(&nginx, "b.example.com").await;
+    assert_eq!(
+        body, "a.example.com",
+        "Expected the response cached for a.example.com to be served for b.example.com, got: {body}"
+    );
+}
+
+/// With $host in the key, each vhost gets its own cache entry.
+#[tokio::test]
+#[ignore]
+async fn key_with_host_separates_vhosts() {
+    let nginx = NginxContainer::start(shared_cache_config("$scheme$host$request_uri")).await;
+
+    assert_eq!(get_page(&nginx, "a.example.com").await, "a.example.com");
+    assert_eq!(get_page(&nginx, "b.example.com").await, "b.example.com");
+}
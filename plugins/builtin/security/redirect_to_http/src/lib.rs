@@ -0,0 +1,284 @@
+//! redirect-to-http plugin
+//!
+//! This plugin flags redirects whose target is a literal `http://` URL:
+//! `return 301 http://...;`, `return http://...;` and `rewrite ... http://...;`.
+//! Such redirects send clients back to plaintext HTTP, usually because the
+//! target was written before the site moved to HTTPS. The fix switches the
+//! scheme to `https://`.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+
+/// Status codes for which `return` takes a redirect URL
+const REDIRECT_CODES: &[&str] = &["301", "302", "303", "307", "308"];
+
+/// Check for redirects to plaintext http:// URLs
+#[derive(Default)]
+pub struct RedirectToHttpPlugin;
+
+/// The redirect target of `directive`, if it has one: the argument the
+/// target starts with, and the whole target as written (the parser splits
+/// `http://$host$request_uri` into several arguments)
+fn redirect_target(directive: &Directive) -> Option<(&Argument, String)> {
+    let mut words = directive.words();
+    let index = match directive.name.as_str() {
+        // `return URL;` is a 302; with a code only 3xx codes take a URL
+        "return" => {
+            let first = words.first()?;
+            if REDIRECT_CODES.contains(&first.as_str()) {
+                1
+            } else if first.parse::<u16>().is_err() {
+                0
+            } else {
+                return None;
+            }
+        }
+        // A replacement starting with http:// always ends in a redirect
+        "rewrite" => 1,
+        _ => return None,
+    };
+    if index >= words.len() {
+        return None;
+    }
+    let target = words.swap_remove(index);
+
+    // The arguments each word starts with
+    let mut prev_end = None;
+    let start = directive
+        .args
+        .iter()
+        .filter(|arg| {
+            let starts_word = prev_end != Some(arg.span.start.offset);
+            prev_end = Some(arg.span.end.offset);
+            starts_word
+        })
+        .nth(index)?;
+    Some((start, target))
+}
+
+/// Whether `url` points at the local machine, where TLS is rarely set up
+fn is_loopback(url: &str) -> bool {
+    let Some(host) = helpers::extract_host_from_url(url) else {
+        return false;
+    };
+    // `http://localhost$request_uri`
+    let host = host.split('$').next().unwrap_or(host);
+    let host = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => helpers::extract_domain(host),
+    };
+    host.eq_ignore_ascii_case("localhost") || host.starts_with("127.") || host == "::1"
+}
+
+impl Plugin for RedirectToHttpPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "redirect-to-http",
+            "security",
+            "Detects redirects to plaintext http:// URLs",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "A `return 301 http://...` or `rewrite ... http://...` sends the client to an \
+             unencrypted URL. Even when the target later redirects to HTTPS itself, the \
+             first request (with its cookies and path) travels in plaintext and can be \
+             intercepted or rewritten. Such targets are often leftovers from before the \
+             site moved to HTTPS.\n\n\
+             Use an `https://` target. Redirects to the same host can also use a \
+             relative path, which keeps the scheme of the current request. Targets \
+             built from `$scheme` and redirects to loopback addresses are not reported.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_rewrite_module.html#return".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_rewrite_module.html#rewrite".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/redirect_to_http/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["return", "rewrite"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let err = self.spec().error_builder();
+
+        for ctx in config.all_directives_with_context() {
+            // return and rewrite in stream/mail have nothing to do with HTTP
+            if !ctx.is_inside("http") && !ctx.parent_stack.is_empty() {
+                continue;
+            }
+            let directive = ctx.directive;
+            let Some((start, target)) = redirect_target(directive) else {
+                continue;
+            };
+            let url = target.trim_matches(|c| c == '"' || c == '\'');
+            if !url.starts_with("http://") || is_loopback(url) {
+                continue;
+            }
+
+            // The scheme sits after the opening quote of a quoted target
+            let scheme_start = start.span.start.offset + start.raw.find("http://").unwrap_or(0);
+            errors.push(
+                err.warning_at(
+                    &format!(
+                        "{} redirects to plaintext URL '{}'; use https:// instead",
+                        directive.name, url
+                    ),
+                    directive,
+                )
+                .with_fix(Fix::replace_range(
+                    scheme_start,
+                    scheme_start + "http://".len(),
+                    "https://",
+                )),
+            );
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(RedirectToHttpPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_detects_return_redirects() {
+        let runner = PluginTestRunner::new(RedirectToHttpPlugin);
+
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        location /a { return 301 http://example.com$request_uri; }
+        location /b { return http://example.com/b; }
+        location /c { return 308 "http://example.com/c"; }
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(errors.len(), 3, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "return redirects to plaintext URL 'http://example.com$request_uri'; use https:// instead"
+        );
+        assert!(errors[2].message.contains("'http://example.com/c'"));
+    }
+
+    #[test]
+    fn test_detects_rewrite() {
+        let runner = PluginTestRunner::new(RedirectToHttpPlugin);
+
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        rewrite ^/old/(.*)$ http://new.example.com/$1 permanent;
+    }
+}
+"#,
+            )
+            .unwrap();
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(4));
+        assert!(errors[0].message.starts_with("rewrite redirects"));
+    }
+
+    #[test]
+    fn test_target_with_variables() {
+        let source = "http {\n    rewrite ^/$arg_p/(.*)$ http://$host/$1 redirect;\n}\n";
+        let runner = PluginTestRunner::new(RedirectToHttpPlugin);
+        let errors = runner.check_string(source).unwrap();
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].message.contains("'http://$host/$1'"));
+        let fix = &errors[0].fixes[0];
+        let (start, end) = (fix.start_offset.unwrap(), fix.end_offset.unwrap());
+        assert_eq!(&source[start..end], "http://");
+    }
+
+    #[test]
+    fn test_fix_keeps_quotes() {
+        let source = "http {\n    return 302 \"http://example.com/\";\n}\n";
+        let runner = PluginTestRunner::new(RedirectToHttpPlugin);
+        let errors = runner.check_string(source).unwrap();
+
+        let fix = &errors[0].fixes[0];
+        let (start, end) = (fix.start_offset.unwrap(), fix.end_offset.unwrap());
+        let fixed = format!("{}{}{}", &source[..start], fix.new_text, &source[end..]);
+        assert_eq!(
+            fixed,
+            "http {\n    return 302 \"https://example.com/\";\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_no_error_for_safe_targets() {
+        let runner = PluginTestRunner::new(RedirectToHttpPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        location /a { return 301 https://example.com$request_uri; }
+        location /b { return 301 $scheme://example.com$request_uri; }
+        location /c { return 301 /new-path; }
+        location /d { return 200 "see http://example.com/"; }
+        location /e { return 302 http://localhost:8080/; }
+        location /e2 { return 302 http://localhost$request_uri; }
+        location /f { return 302 http://127.0.0.1/; }
+        location /g { return 302 http://[::1]:3000/; }
+        location /h { rewrite ^/h/(.*)$ /new/$1 last; }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_ignores_stream_context() {
+        let runner = PluginTestRunner::new(RedirectToHttpPlugin);
+
+        runner.assert_no_errors(
+            r#"
+stream {
+    server {
+        listen 12345;
+        return http://example.com/;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_examples_with_fix() {
+        let runner = PluginTestRunner::new(RedirectToHttpPlugin);
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(RedirectToHttpPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
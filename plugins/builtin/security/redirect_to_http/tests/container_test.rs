@@ -0,0 +1,61 @@
+//! Container-based integration tests for the redirect-to-http rule.
+//!
+//! Shows that a literal `http://` target is sent to the client as-is, even
+//! from a server reached over HTTPS, and that the fixed `https://` target
+//! keeps the client on TLS.
+//!
+//! Run with:
+//!   cargo test -p redirect-to-http-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p redirect-to-http-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, reqwest};
+
+const CONFIG: &str = r#"
+events { worker_connections 1024; }
+http {
+    server {
+        listen 443 ssl;
+        ssl_certificate /tmp/cert.pem;
+        ssl_certificate_key /tmp/key.pem;
+
+        location /plain { return 301 http://www.example.com$request_uri; }
+        location /fixed { return 301 https://www.example.com$request_uri; }
+    }
+}
+"#;
+
+/// Location header of the redirect for `path`, without following it
+async fn location(nginx: &NginxContainer, path: &str) -> String {
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let resp = client.get(nginx.url(path)).send().await.unwrap();
+    assert_eq!(resp.status(), 301);
+    resp.headers()["location"].to_str().unwrap().to_string()
+}
+
+/// A request made over HTTPS is redirected to plaintext HTTP.
+#[tokio::test]
+#[ignore]
+async fn http_target_downgrades_https_request() {
+    let nginx = NginxContainer::start_ssl(CONFIG).await;
+    assert_eq!(
+        location(&nginx, "/plain/page").await,
+        "http://www.example.com/plain/page"
+    );
+}
+
+/// The fixed target keeps the client on https.
+#[tokio::test]
+#[ignore]
+async fn https_target_keeps_tls() {
+    let nginx = NginxContainer::start_ssl(CONFIG).await;
+    assert_eq!(
+        location(&nginx, "/fixed/page").await,
+        "https://www.example.com/fixed/page"
+    );
+}
@@ -25,6 +25,7 @@ impl Plugin for ServerTokensEnabledPlugin {
             "Detects when server_tokens is enabled (exposes nginx version)",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "When server_tokens is 'on' (the default), nginx includes its version number in \
              the Server response header and on default error pages. This information can help \
@@ -325,7 +326,7 @@ server {
         .unwrap();
 
         // Simulate being included from http context
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let plugin = ServerTokensEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -354,7 +355,7 @@ server {
         .unwrap();
 
         // Simulate being included from http context
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let plugin = ServerTokensEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -380,7 +381,7 @@ server {
         .unwrap();
 
         // Simulate being included from http context
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let plugin = ServerTokensEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -404,7 +405,7 @@ location / {
         .unwrap();
 
         // Simulate being included from http > server context
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = ServerTokensEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -430,7 +431,7 @@ server_tokens on;
         .unwrap();
 
         // Simulate being included from http > server context
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = ServerTokensEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -455,7 +456,7 @@ server {
         .unwrap();
 
         // Simulate being included from stream context
-        config.include_context = vec!["stream".to_string()];
+        config.include_context = vec!["stream".to_string()].into();
 
         let plugin = ServerTokensEnabledPlugin;
         let errors = plugin.check(&config, "test.conf");
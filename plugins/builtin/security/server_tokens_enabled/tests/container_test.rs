@@ -9,7 +9,10 @@
 //! Specify nginx version via environment variable (default: "1.27"):
 //!   NGINX_VERSION=1.26 cargo test -p server-tokens-enabled-plugin --test container_test -- --ignored
 
-use nginx_lint_plugin::container_testing::{NginxContainer, nginx_server_name, reqwest};
+use nginx_lint_plugin::container_testing::{
+    BehaviorTest, NginxContainer, RequestCheck, nginx_server_name, reqwest,
+};
+use server_tokens_enabled_plugin::ServerTokensEnabledPlugin;
 
 // ============================================================================
 // Server header version exposure
@@ -181,3 +184,27 @@ http {
         server_header
     );
 }
+
+// ============================================================================
+// Rule examples
+// ============================================================================
+
+#[tokio::test]
+#[ignore]
+async fn bad_example_exposes_version() {
+    BehaviorTest::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/bad.conf"))
+        .linted_by(&ServerTokensEnabledPlugin, 1)
+        .request(RequestCheck::get("/").expect_header_contains("server", "/"))
+        .run()
+        .await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn good_example_hides_version() {
+    BehaviorTest::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/good.conf"))
+        .linted_by(&ServerTokensEnabledPlugin, 0)
+        .request(RequestCheck::get("/").expect_header("server", nginx_server_name()))
+        .run()
+        .await;
+}
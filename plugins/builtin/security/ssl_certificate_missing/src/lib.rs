@@ -0,0 +1,454 @@
+//! ssl-certificate-missing plugin
+//!
+//! This plugin warns when a server listens with `ssl` (or `quic`) but no
+//! `ssl_certificate` or `ssl_certificate_key` is in effect for it, taking
+//! inheritance from the `http`/`stream` block into account, and when the
+//! certificates and keys are not paired one to one.
+//!
+//! Checking that the referenced files exist needs filesystem access, which
+//! plugins do not have; the CLI does it with `--check-paths`.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedList};
+use nginx_lint_plugin::prelude::*;
+
+/// Check that TLS servers have a certificate and a matching key
+#[derive(Default)]
+pub struct SslCertificateMissingPlugin;
+
+/// `ssl_certificate` / `ssl_certificate_key` directives and `ssl` in effect.
+///
+/// Both are array directives: a level that defines any of them replaces the
+/// whole list inherited from the previous level.
+#[derive(Debug, Clone, Copy)]
+struct Certificates<'a> {
+    certificates: InheritedList<'a>,
+    keys: InheritedList<'a>,
+    ssl: Inherited<'a>,
+}
+
+impl<'a> Certificates<'a> {
+    /// Nothing set
+    fn new() -> Self {
+        Self {
+            certificates: InheritedList::Default,
+            keys: InheritedList::Default,
+            ssl: Inherited::Default,
+        }
+    }
+
+    /// Settings in effect inside `block`, inheriting from `self`
+    fn enter(&self, block: &'a Block) -> Self {
+        Self {
+            certificates: self.certificates.enter(&block.items, &["ssl_certificate"]),
+            keys: self.keys.enter(&block.items, &["ssl_certificate_key"]),
+            ssl: self.ssl.enter(&block.items, "ssl"),
+        }
+    }
+
+    fn certificates(&self) -> usize {
+        self.certificates.directives().count()
+    }
+
+    fn keys(&self) -> usize {
+        self.keys.directives().count()
+    }
+
+    fn ssl_on(&self) -> bool {
+        self.ssl.directive().is_some_and(|d| d.first_arg_is("on"))
+    }
+}
+
+impl SslCertificateMissingPlugin {
+    /// Check the servers of an `http` or `stream` block (or of a file included
+    /// into one, in which case `inherited` is `None`)
+    fn check_servers(
+        &self,
+        items: &[ConfigItem],
+        inherited: Option<Certificates<'_>>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let err = PluginSpec::new("ssl-certificate-missing", "security", "").error_builder();
+
+        for server in items.iter().filter_map(|item| match item {
+            ConfigItem::Directive(d) if d.is("server") => Some(d),
+            _ => None,
+        }) {
+            let Some(block) = &server.block else {
+                continue;
+            };
+
+            let ssl_listen = block.directives().find(|d| {
+                d.is("listen")
+                    && d.args
+                        .iter()
+                        .any(|a| a.as_str() == "ssl" || a.as_str() == "quic")
+            });
+
+            let own = Certificates::new().enter(block);
+            let Some(inherited) = inherited else {
+                // The including http/stream block is not visible, so only a
+                // server that configures both lists itself can be checked
+                if (ssl_listen.is_some() || own.ssl_on())
+                    && own.certificates() > 0
+                    && own.keys() > 0
+                    && own.certificates() != own.keys()
+                {
+                    errors.push(err.warning_at(&Self::mismatch_message(own), server));
+                }
+                continue;
+            };
+            let effective = inherited.enter(block);
+
+            let Some(ssl_at) = ssl_listen.or(effective.ssl_on().then_some(&**server)) else {
+                continue;
+            };
+
+            let missing: Vec<&str> = [
+                (effective.certificates(), "ssl_certificate"),
+                (effective.keys(), "ssl_certificate_key"),
+            ]
+            .into_iter()
+            .filter(|(count, _)| *count == 0)
+            .map(|(_, name)| name)
+            .collect();
+
+            if !missing.is_empty() {
+                errors.push(err.warning_at(
+                    &format!(
+                        "server listens with ssl but no {} is defined for it (nginx will \
+                         refuse to start)",
+                        missing.join(" or ")
+                    ),
+                    ssl_at,
+                ));
+            } else if effective.certificates() != effective.keys() {
+                errors.push(err.warning_at(&Self::mismatch_message(effective), server));
+            }
+        }
+    }
+
+    fn mismatch_message(certificates: Certificates) -> String {
+        format!(
+            "ssl_certificate and ssl_certificate_key must be paired: found {} certificate(s) \
+             and {} key(s)",
+            certificates.certificates(),
+            certificates.keys()
+        )
+    }
+}
+
+impl Plugin for SslCertificateMissingPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "ssl-certificate-missing",
+            "security",
+            "Detects TLS servers without a certificate/key pair",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Minimal)
+        .with_why(
+            "A server that listens with `ssl` (or `quic`) needs an `ssl_certificate` and an \
+             `ssl_certificate_key`, either in the server itself or inherited from the `http` \
+             (or `stream`) block. Without them nginx refuses to start (`no \"ssl_certificate\" \
+             is defined for the listen directive`), which typically happens when a vhost is \
+             copied from one that relied on certificates set in the main config.\n\n\
+             When several certificates are configured (e.g. RSA and ECDSA), every \
+             `ssl_certificate` needs its own `ssl_certificate_key`, in the same order. Note \
+             that defining either directive in a server replaces all of the ones inherited \
+             from `http`.\n\n\
+             Run `nginx-lint --check-paths` to also check that the certificate and key files \
+             exist on disk.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_ssl_module.html#ssl_certificate".to_string(),
+            "https://nginx.org/en/docs/http/configuring_https_servers.html".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/ssl_certificate_missing/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&[
+            "http",
+            "stream",
+            "server",
+            "listen",
+            "ssl",
+            "ssl_certificate",
+            "ssl_certificate_key",
+        ])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+
+        for directive in config.all_directives() {
+            if (directive.is("http") || directive.is("stream"))
+                && let Some(block) = &directive.block
+            {
+                let inherited = Certificates::new().enter(block);
+                self.check_servers(&block.items, Some(inherited), &mut errors);
+            }
+        }
+
+        // Servers in a file included from http/stream
+        if matches!(config.immediate_parent_context(), Some("http" | "stream")) {
+            self.check_servers(&config.items, None, &mut errors);
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(SslCertificateMissingPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(source: &str) -> Vec<LintError> {
+        let config = parse_string(source).unwrap();
+        SslCertificateMissingPlugin.check(&config, "test.conf")
+    }
+
+    #[test]
+    fn test_missing_certificate_and_key() {
+        let errors = check(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        server_name example.com;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "Expected 1 error, got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(4));
+        assert!(
+            errors[0]
+                .message
+                .contains("no ssl_certificate or ssl_certificate_key is defined")
+        );
+    }
+
+    #[test]
+    fn test_missing_key_only() {
+        let errors = check(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        ssl_certificate /etc/nginx/cert.pem;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .contains("no ssl_certificate_key is defined")
+        );
+    }
+
+    #[test]
+    fn test_inherited_from_http() {
+        let runner = PluginTestRunner::new(SslCertificateMissingPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    ssl_certificate /etc/nginx/cert.pem;
+    ssl_certificate_key /etc/nginx/key.pem;
+
+    server {
+        listen 443 ssl;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_key_from_http_certificate_in_server() {
+        let runner = PluginTestRunner::new(SslCertificateMissingPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    ssl_certificate_key /etc/nginx/key.pem;
+
+    server {
+        listen 443 ssl;
+        ssl_certificate /etc/nginx/cert.pem;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_unpaired_certificates() {
+        let errors = check(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        ssl_certificate /etc/nginx/rsa.pem;
+        ssl_certificate /etc/nginx/ecdsa.pem;
+        ssl_certificate_key /etc/nginx/rsa.key;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(3));
+        assert!(
+            errors[0]
+                .message
+                .contains("found 2 certificate(s) and 1 key(s)")
+        );
+    }
+
+    #[test]
+    fn test_server_certificate_replaces_http_list() {
+        // The server's single certificate replaces both inherited ones, but
+        // the two inherited keys still apply
+        let errors = check(
+            r#"
+http {
+    ssl_certificate_key /etc/nginx/rsa.key;
+    ssl_certificate_key /etc/nginx/ecdsa.key;
+
+    server {
+        listen 443 ssl;
+        ssl_certificate /etc/nginx/rsa.pem;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .contains("found 1 certificate(s) and 2 key(s)")
+        );
+    }
+
+    #[test]
+    fn test_quic_listen_and_ssl_on() {
+        let errors = check(
+            r#"
+http {
+    server {
+        listen 443 quic;
+    }
+    server {
+        listen 443;
+        ssl on;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(4));
+        assert_eq!(errors[1].line, Some(6));
+    }
+
+    #[test]
+    fn test_plain_server_is_ok() {
+        let runner = PluginTestRunner::new(SslCertificateMissingPlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 80;
+        ssl_certificate /etc/nginx/cert.pem;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_stream_server() {
+        let errors = check(
+            r#"
+stream {
+    server {
+        listen 5432 ssl;
+        proxy_pass 127.0.0.1:5433;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_included_server_without_certificates_is_not_reported() {
+        let mut config = parse_string(
+            r#"
+server {
+    listen 443 ssl;
+    server_name example.com;
+}
+"#,
+        )
+        .unwrap();
+        config.include_context = vec!["http".to_string()].into();
+
+        let errors = SslCertificateMissingPlugin.check(&config, "test.conf");
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_included_server_with_unpaired_certificates() {
+        let mut config = parse_string(
+            r#"
+server {
+    listen 443 ssl;
+    ssl_certificate /etc/nginx/rsa.pem;
+    ssl_certificate /etc/nginx/ecdsa.pem;
+    ssl_certificate_key /etc/nginx/rsa.key;
+}
+"#,
+        )
+        .unwrap();
+        config.include_context = vec!["http".to_string()].into();
+
+        let errors = SslCertificateMissingPlugin.check(&config, "test.conf");
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(SslCertificateMissingPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(SslCertificateMissingPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,73 @@
+//! Container-based integration tests for the ssl-certificate-missing rule.
+//!
+//! Verifies that nginx refuses to start when a TLS server has no certificate,
+//! when certificates and keys are not paired, and (the `--check-paths` case)
+//! when a referenced certificate file does not exist.
+//!
+//! Run with:
+//!   cargo test -p ssl-certificate-missing-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p ssl-certificate-missing-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::nginx_config_test;
+
+/// `listen ... ssl` without any certificate fails the config test.
+#[test]
+#[ignore]
+fn ssl_listen_without_certificate_fails() {
+    let result = nginx_config_test(
+        r#"
+events { worker_connections 1024; }
+http {
+    server {
+        listen 443 ssl;
+        location / { return 200 "ok"; }
+    }
+}
+"#,
+    );
+    result.assert_fails_with(r#"no "ssl_certificate" is defined"#);
+}
+
+/// A second certificate without its own key fails before any file is read.
+#[test]
+#[ignore]
+fn unpaired_certificate_fails() {
+    let result = nginx_config_test(
+        r#"
+events { worker_connections 1024; }
+http {
+    server {
+        listen 443 ssl;
+        ssl_certificate /etc/nginx/rsa.pem;
+        ssl_certificate /etc/nginx/ecdsa.pem;
+        ssl_certificate_key /etc/nginx/rsa.key;
+        location / { return 200 "ok"; }
+    }
+}
+"#,
+    );
+    result.assert_fails_with(r#"no "ssl_certificate_key" is defined for certificate"#);
+}
+
+/// Certificate files are loaded at startup, so a missing file also fails.
+#[test]
+#[ignore]
+fn missing_certificate_file_fails() {
+    let result = nginx_config_test(
+        r#"
+events { worker_connections 1024; }
+http {
+    ssl_certificate /etc/nginx/missing.pem;
+    ssl_certificate_key /etc/nginx/missing.key;
+
+    server {
+        listen 443 ssl;
+        location / { return 200 "ok"; }
+    }
+}
+"#,
+    );
+    result.assert_fails_with("cannot load certificate");
+}
@@ -0,0 +1,402 @@
+//! unsafe-x-forwarded-for plugin
+//!
+//! This plugin checks how `proxy_set_header X-Forwarded-For` builds the
+//! header sent to the backend:
+//!
+//! - `$remote_addr` replaces the chain added by earlier proxies with the
+//!   address of the last hop; the fix uses `$proxy_add_x_forwarded_for`
+//! - when the rule config sets `deployment` to `"edge"` or `"internal"`, the
+//!   client-supplied chain (`$proxy_add_x_forwarded_for` or
+//!   `$http_x_forwarded_for`) must not be forwarded unless `set_real_ip_from`
+//!   is in effect
+//!
+//! At the edge, overwriting the header with `$remote_addr` is the safe
+//! setup, so the first check is skipped for `deployment = "edge"`.
+//!
+//! Without `deployment`, `behind_load_balancer` in the `[environment]`
+//! config section selects `"internal"` (true) or `"edge"` (false).
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::prelude::*;
+
+/// Where this nginx sits in front of the backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Deployment {
+    /// Not configured: only the discarded chain is reported
+    #[default]
+    Unspecified,
+    /// Receives requests directly from clients
+    Edge,
+    /// Sits behind another proxy or load balancer
+    Internal,
+}
+
+/// Check for unsafe X-Forwarded-For forwarding
+#[derive(Default)]
+pub struct UnsafeXForwardedForPlugin {
+    deployment: Deployment,
+}
+
+impl UnsafeXForwardedForPlugin {
+    /// Create a new plugin for a deployment, `"edge"` or `"internal"`.
+    /// Other values are ignored.
+    pub fn with_deployment(deployment: &str) -> Self {
+        let deployment = match deployment {
+            "edge" => Deployment::Edge,
+            "internal" => Deployment::Internal,
+            _ => Deployment::Unspecified,
+        };
+        Self { deployment }
+    }
+
+    /// Walk the config, tracking whether `set_real_ip_from` is in effect
+    ///
+    /// `known` is false in included files, where the including file may
+    /// configure real_ip; the real_ip check is skipped there.
+    fn check_items(
+        &self,
+        items: &[ConfigItem],
+        inherited_real_ip: bool,
+        known: bool,
+        errors: &mut Vec<LintError>,
+    ) {
+        let real_ip = inherited_real_ip
+            || items
+                .iter()
+                .any(|item| matches!(item, ConfigItem::Directive(d) if d.is("set_real_ip_from")));
+
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if let Some(block) = &directive.block {
+                self.check_items(&block.items, real_ip, known, errors);
+            } else if directive.is("proxy_set_header")
+                && directive
+                    .first_arg()
+                    .is_some_and(|name| name.eq_ignore_ascii_case("x-forwarded-for"))
+                && let Some(value) = directive.args.get(1)
+            {
+                self.check_header(directive, value, real_ip || !known, errors);
+            }
+        }
+    }
+
+    fn check_header(
+        &self,
+        directive: &Directive,
+        value: &Argument,
+        real_ip: bool,
+        errors: &mut Vec<LintError>,
+    ) {
+        let err = self.spec().error_builder();
+        // A bare variable's value is its name without the `$`
+        let text = if value.is_variable() {
+            value.raw.as_str()
+        } else {
+            value.as_str()
+        };
+
+        if text == "$remote_addr" {
+            if self.deployment != Deployment::Edge {
+                errors.push(
+                    err.warning_at(
+                        "X-Forwarded-For set to $remote_addr discards the addresses added by \
+                         earlier proxies; use $proxy_add_x_forwarded_for",
+                        directive,
+                    )
+                    .with_fix(replace_variable(
+                        value,
+                        "$remote_addr",
+                        "$proxy_add_x_forwarded_for",
+                    )),
+                );
+            }
+            return;
+        }
+
+        let forwarded = ["$proxy_add_x_forwarded_for", "$http_x_forwarded_for"]
+            .into_iter()
+            .find(|var| text.contains(var));
+        let Some(forwarded) = forwarded else {
+            return;
+        };
+        if real_ip {
+            return;
+        }
+
+        match self.deployment {
+            Deployment::Unspecified => {}
+            Deployment::Edge => {
+                let mut error = err.warning_at(
+                    "X-Forwarded-For from clients is forwarded without real_ip configuration, \
+                     so backends may trust spoofed addresses; use $remote_addr or add \
+                     set_real_ip_from for the trusted proxies in front",
+                    directive,
+                );
+                // The whole value is the client chain, so it can be replaced
+                if text == forwarded {
+                    error = error.with_fix(replace_variable(value, forwarded, "$remote_addr"));
+                }
+                errors.push(error);
+            }
+            Deployment::Internal => {
+                errors.push(err.warning_at(
+                    "X-Forwarded-For is forwarded without real_ip configuration, so \
+                     $remote_addr is the address of the proxy in front; add set_real_ip_from \
+                     for it and 'real_ip_header X-Forwarded-For'",
+                    directive,
+                ));
+            }
+        }
+    }
+}
+
+/// Replace `variable` inside `value`, keeping any quotes around it
+fn replace_variable(value: &Argument, variable: &str, replacement: &str) -> Fix {
+    let start = value.span.start.offset + value.raw.find(variable).unwrap_or(0);
+    Fix::replace_range(start, start + variable.len(), replacement)
+}
+
+impl Plugin for UnsafeXForwardedForPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "unsafe-x-forwarded-for",
+            "security",
+            "Detects X-Forwarded-For headers that discard or blindly forward the client chain",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "`proxy_set_header X-Forwarded-For $remote_addr;` replaces the header with the \
+             address of the immediate peer. Behind a load balancer or CDN that is the \
+             balancer itself, so the chain recorded by the proxies in front, including the \
+             client address, is lost. `$proxy_add_x_forwarded_for` appends `$remote_addr` to \
+             the received header instead.\n\n\
+             The received header, however, is supplied by whoever sent the request. Set \
+             `deployment` in the rule config to describe where this nginx sits:\n\n\
+             - `\"edge\"`: nginx talks to clients directly. Forwarding their X-Forwarded-For \
+             lets them put any address in front of the real one, so it is reported unless \
+             `set_real_ip_from` names trusted proxies. Overwriting with `$remote_addr` is \
+             not reported.\n\
+             - `\"internal\"`: nginx sits behind another proxy. Forwarding the chain without \
+             `set_real_ip_from` and `real_ip_header` leaves `$remote_addr` (in logs, \
+             `allow`/`deny` and rate limits) set to the proxy in front.\n\n\
+             Without `deployment`, `behind_load_balancer` in the `[environment]` section \
+             selects `\"internal\"` or `\"edge\"`.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#var_proxy_add_x_forwarded_for"
+                .to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_realip_module.html".to_string(),
+            "https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Headers/X-Forwarded-For"
+                .to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/unsafe_x_forwarded_for/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["proxy_set_header"])
+    }
+
+    fn check_with_context(&self, config: &Config, ctx: &CheckContext) -> Vec<LintError> {
+        let deployment = match (self.deployment, ctx.environment().behind_load_balancer) {
+            (Deployment::Unspecified, Some(true)) => Deployment::Internal,
+            (Deployment::Unspecified, Some(false)) => Deployment::Edge,
+            (deployment, _) => deployment,
+        };
+        Self { deployment }.check(config, ctx.path())
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let known = config.include_context.is_empty();
+        self.check_items(&config.items, false, known, &mut errors);
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(UnsafeXForwardedForPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(plugin: &UnsafeXForwardedForPlugin, source: &str) -> Vec<LintError> {
+        plugin.check(&parse_string(source).unwrap(), "test.conf")
+    }
+
+    const FORWARDED_CHAIN: &str = r#"
+http {
+    server {
+        location /a/ {
+            proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+            proxy_pass http://backend;
+        }
+        location /b/ {
+            proxy_set_header X-Forwarded-For "$http_x_forwarded_for, $remote_addr";
+            proxy_pass http://backend;
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn test_remote_addr_discards_chain() {
+        let errors = check(
+            &UnsafeXForwardedForPlugin::default(),
+            r#"
+http {
+    server {
+        location / {
+            proxy_set_header X-Forwarded-For $remote_addr;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert_eq!(
+            errors[0].message,
+            "X-Forwarded-For set to $remote_addr discards the addresses added by earlier \
+             proxies; use $proxy_add_x_forwarded_for"
+        );
+        assert_eq!(errors[0].fixes[0].new_text, "$proxy_add_x_forwarded_for");
+    }
+
+    #[test]
+    fn test_forwarded_chain_without_deployment() {
+        let runner = PluginTestRunner::new(UnsafeXForwardedForPlugin::default());
+        runner.assert_no_errors(FORWARDED_CHAIN);
+    }
+
+    #[test]
+    fn test_edge_forwarding_client_chain() {
+        let errors = check(
+            &UnsafeXForwardedForPlugin::with_deployment("edge"),
+            FORWARDED_CHAIN,
+        );
+
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert!(
+            errors[0]
+                .message
+                .starts_with("X-Forwarded-For from clients is forwarded")
+        );
+        assert_eq!(errors[0].fixes[0].new_text, "$remote_addr");
+        // A value mixing the chain with other text has no single replacement
+        assert!(errors[1].fixes.is_empty());
+    }
+
+    #[test]
+    fn test_edge_allows_remote_addr() {
+        let errors = check(
+            &UnsafeXForwardedForPlugin::with_deployment("edge"),
+            "http {\n    proxy_set_header X-Forwarded-For $remote_addr;\n}\n",
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_internal_without_real_ip() {
+        let errors = check(
+            &UnsafeXForwardedForPlugin::with_deployment("internal"),
+            FORWARDED_CHAIN,
+        );
+
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert!(errors[0].message.contains("add set_real_ip_from"));
+        assert!(errors[0].fixes.is_empty());
+
+        // Discarding the chain is still reported behind another proxy
+        let errors = check(
+            &UnsafeXForwardedForPlugin::with_deployment("internal"),
+            "http {\n    proxy_set_header X-Forwarded-For $remote_addr;\n}\n",
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_deployment_from_environment() {
+        let config = parse_string(FORWARDED_CHAIN).unwrap();
+        let check_behind = |plugin: &UnsafeXForwardedForPlugin, behind: bool| {
+            let environment = Environment {
+                behind_load_balancer: Some(behind),
+                ..Default::default()
+            };
+            let ctx = CheckContext::new(&config, "test.conf").with_environment(&environment);
+            plugin.check_with_context(&config, &ctx)
+        };
+
+        let errors = check_behind(&UnsafeXForwardedForPlugin::default(), true);
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert!(errors[0].message.contains("add set_real_ip_from"));
+
+        let errors = check_behind(&UnsafeXForwardedForPlugin::default(), false);
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert!(
+            errors[0]
+                .message
+                .starts_with("X-Forwarded-For from clients")
+        );
+
+        // The rule's own deployment setting wins
+        let errors = check_behind(&UnsafeXForwardedForPlugin::with_deployment("edge"), true);
+        assert!(
+            errors[0]
+                .message
+                .starts_with("X-Forwarded-For from clients")
+        );
+    }
+
+    #[test]
+    fn test_real_ip_inherited_from_http() {
+        let errors = check(
+            &UnsafeXForwardedForPlugin::with_deployment("internal"),
+            &FORWARDED_CHAIN.replacen(
+                "http {",
+                "http {\n    set_real_ip_from 10.0.0.0/8;\n    real_ip_header X-Forwarded-For;",
+                1,
+            ),
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_included_file_skips_real_ip_check() {
+        let mut config = parse_string(
+            "location / {\n    proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n}\n",
+        )
+        .unwrap();
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
+
+        let errors = UnsafeXForwardedForPlugin::with_deployment("edge").check(&config, "test.conf");
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_examples_with_fix() {
+        let runner = PluginTestRunner::new(UnsafeXForwardedForPlugin::default());
+        runner.test_examples_with_fix(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(UnsafeXForwardedForPlugin::default());
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
@@ -0,0 +1,67 @@
+//! Container-based integration tests for the unsafe-x-forwarded-for rule.
+//!
+//! A second server in the same container plays the backend and echoes the
+//! X-Forwarded-For header it receives. Shows that `$remote_addr` drops the
+//! chain sent by a proxy in front, and that `$proxy_add_x_forwarded_for`
+//! forwards whatever the sender put there.
+//!
+//! Run with:
+//!   cargo test -p unsafe-x-forwarded-for-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p unsafe-x-forwarded-for-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::{NginxContainer, reqwest};
+
+const CONFIG: &str = r#"
+events { worker_connections 1024; }
+http {
+    server {
+        listen 8080;
+        location / { return 200 "$http_x_forwarded_for"; }
+    }
+    server {
+        listen 80;
+        location = / { return 200 "ok"; }
+        location /overwrite {
+            proxy_set_header X-Forwarded-For $remote_addr;
+            proxy_pass http://127.0.0.1:8080;
+        }
+        location /append {
+            proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+            proxy_pass http://127.0.0.1:8080;
+        }
+    }
+}
+"#;
+
+/// X-Forwarded-For seen by the backend for a request sent with `header`
+async fn forwarded(nginx: &NginxContainer, path: &str, header: &str) -> String {
+    let resp = reqwest::Client::new()
+        .get(nginx.url(path))
+        .header("X-Forwarded-For", header)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    resp.text().await.unwrap()
+}
+
+/// `$remote_addr` replaces the chain with the address of the last hop.
+#[tokio::test]
+#[ignore]
+async fn remote_addr_discards_chain() {
+    let nginx = NginxContainer::start(CONFIG).await;
+    let received = forwarded(&nginx, "/overwrite", "203.0.113.7").await;
+    assert!(!received.contains("203.0.113.7"), "got: {}", received);
+}
+
+/// `$proxy_add_x_forwarded_for` keeps the received value in front, whoever
+/// sent it.
+#[tokio::test]
+#[ignore]
+async fn proxy_add_x_forwarded_for_keeps_received_value() {
+    let nginx = NginxContainer::start(CONFIG).await;
+    let received = forwarded(&nginx, "/append", "203.0.113.7").await;
+    assert!(received.starts_with("203.0.113.7, "), "got: {}", received);
+}
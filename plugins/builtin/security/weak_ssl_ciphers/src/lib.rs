@@ -30,6 +30,18 @@ const DEFAULT_WEAK_CIPHERS: &[&str] = &[
 const DEFAULT_REQUIRED_EXCLUSIONS: &[&str] =
     &["!aNULL", "!eNULL", "!EXPORT", "!DES", "!RC4", "!MD5"];
 
+/// Cipher list the fix rewrites weak configurations to: the ECDHE suites of
+/// Mozilla's "intermediate" profile (TLSv1.3 suites are not affected by
+/// `ssl_ciphers`)
+const MODERN_CIPHERS: &[&str] = &[
+    "ECDHE-ECDSA-AES128-GCM-SHA256",
+    "ECDHE-RSA-AES128-GCM-SHA256",
+    "ECDHE-ECDSA-AES256-GCM-SHA384",
+    "ECDHE-RSA-AES256-GCM-SHA384",
+    "ECDHE-ECDSA-CHACHA20-POLY1305",
+    "ECDHE-RSA-CHACHA20-POLY1305",
+];
+
 /// Check for weak SSL/TLS cipher suites
 #[derive(Default)]
 pub struct WeakSslCiphersPlugin;
@@ -42,10 +54,14 @@ impl Plugin for WeakSslCiphersPlugin {
             "Detects weak or insecure SSL/TLS cipher suites",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "Weak cipher suites (NULL, EXPORT, DES, RC4, MD5, etc.) have insufficient \
              cryptographic strength or known vulnerabilities. Using only strong cipher suites \
-             and explicitly excluding weak ones ensures secure communication.",
+             and explicitly excluding weak ones ensures secure communication.\n\n\
+             `--fix` rewrites a list that contains weak suites to the ECDHE suites of \
+             Mozilla's \"intermediate\" configuration; a list that only lacks exclusions gets \
+             them appended.",
         )
         .with_bad_example(include_str!("../examples/bad.conf").trim())
         .with_good_example(include_str!("../examples/good.conf").trim())
@@ -76,51 +92,51 @@ impl Plugin for WeakSslCiphersPlugin {
 
             let cipher_string = cipher_arg.as_str();
 
-            // Check for weak cipher patterns
+            // Determine if original uses quotes
+            let quote_char = if cipher_arg.raw.starts_with('\'') {
+                "'"
+            } else if cipher_arg.raw.starts_with('"') {
+                "\""
+            } else {
+                ""
+            };
+            let replace_ciphers = |ciphers: &str| {
+                // Use range-based fix to replace the directive content
+                directive.replace_with(&format!(
+                    "ssl_ciphers {}{}{};",
+                    quote_char, ciphers, quote_char
+                ))
+            };
+
+            // Check for weak cipher patterns. Excluding them is not enough to
+            // get a sound list, so the fix (attached to the first one only)
+            // rewrites it to a modern suite
             let weak_ciphers = find_weak_ciphers(cipher_string);
-            for weak in &weak_ciphers {
+            for (i, weak) in weak_ciphers.iter().enumerate() {
                 let message = format!("Weak cipher suite '{}' should not be used", weak);
-                errors.push(err.warning(
-                    &message,
-                    cipher_arg.span.start.line,
-                    cipher_arg.span.start.column,
-                ));
+                let mut error = err.warning_at_arg(&message, directive, 0);
+                if i == 0 {
+                    error = error.with_fix(replace_ciphers(&modern_cipher_string()));
+                }
+                errors.push(error);
             }
 
             // Check for missing exclusions
             let missing_exclusions = find_missing_exclusions(cipher_string);
             if !missing_exclusions.is_empty() {
-                // Generate fix with required exclusions added
-                let fixed_cipher_string =
-                    generate_fixed_cipher_string(cipher_string, &missing_exclusions);
-
-                // Determine if original uses quotes
-                let quote_char = if cipher_arg.raw.starts_with('\'') {
-                    "'"
-                } else if cipher_arg.raw.starts_with('"') {
-                    "\""
-                } else {
-                    ""
-                };
-
-                // Use range-based fix to replace the directive content
-                let fix = directive.replace_with(&format!(
-                    "ssl_ciphers {}{}{};",
-                    quote_char, fixed_cipher_string, quote_char
-                ));
-
                 let message = format!(
                     "Missing cipher exclusions: {}",
                     missing_exclusions.join(", ")
                 );
-                errors.push(
-                    err.warning(
-                        &message,
-                        cipher_arg.span.start.line,
-                        cipher_arg.span.start.column,
-                    )
-                    .with_fix(fix),
-                );
+                let mut error = err.warning_at_arg(&message, directive, 0);
+                // The modern suite already carries the exclusions
+                if weak_ciphers.is_empty() {
+                    // Generate fix with required exclusions added
+                    let fixed_cipher_string =
+                        generate_fixed_cipher_string(cipher_string, &missing_exclusions);
+                    error = error.with_fix(replace_ciphers(&fixed_cipher_string));
+                }
+                errors.push(error);
             }
         }
 
@@ -178,6 +194,17 @@ fn find_missing_exclusions(cipher_string: &str) -> Vec<String> {
         .collect()
 }
 
+/// The modern suite followed by the required exclusions, so the rewritten
+/// list passes this rule
+fn modern_cipher_string() -> String {
+    MODERN_CIPHERS
+        .iter()
+        .chain(DEFAULT_REQUIRED_EXCLUSIONS)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
 /// Generate a fixed cipher string with missing exclusions added
 fn generate_fixed_cipher_string(original: &str, missing: &[String]) -> String {
     if missing.is_empty() {
@@ -204,13 +231,13 @@ mod tests {
     fn test_weak_cipher_rc4() {
         let runner = PluginTestRunner::new(WeakSslCiphersPlugin);
 
-        runner.assert_has_errors(
-            r#"
-server {
-    ssl_ciphers RC4:AES256;
-}
-"#,
-        );
+        let errors = runner
+            .check_string("server {\n    ssl_ciphers RC4:AES256;\n}\n")
+            .unwrap();
+        assert!(!errors.is_empty());
+        // The cipher string is underlined
+        assert_eq!(errors[0].column, Some(17));
+        assert_eq!(errors[0].end_column, Some(27));
     }
 
     #[test]
@@ -306,6 +333,36 @@ server {
         assert!(fix.new_text.contains("!MD5"));
     }
 
+    #[test]
+    fn test_fix_rewrites_weak_ciphers_to_modern_suite() {
+        let runner = PluginTestRunner::new(WeakSslCiphersPlugin);
+
+        let errors = runner
+            .check_string(
+                r#"
+server {
+    ssl_ciphers 'RC4-SHA:DES-CBC3-SHA:AES256-SHA';
+}
+"#,
+            )
+            .unwrap();
+
+        // Two weak suites plus the missing exclusions, but a single fix
+        let fixes: Vec<_> = errors.iter().flat_map(|e| &e.fixes).collect();
+        assert_eq!(errors.len(), 3, "got: {:?}", errors);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(
+            fixes[0].new_text.trim(),
+            "ssl_ciphers 'ECDHE-ECDSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256:\
+             ECDHE-ECDSA-AES256-GCM-SHA384:ECDHE-RSA-AES256-GCM-SHA384:\
+             ECDHE-ECDSA-CHACHA20-POLY1305:ECDHE-RSA-CHACHA20-POLY1305:\
+             !aNULL:!eNULL:!EXPORT:!DES:!RC4:!MD5';"
+        );
+
+        // The rewritten list passes the rule
+        runner.assert_no_errors(&format!("ssl_ciphers {};\n", modern_cipher_string()));
+    }
+
     #[test]
     fn test_examples() {
         let runner = PluginTestRunner::new(WeakSslCiphersPlugin);
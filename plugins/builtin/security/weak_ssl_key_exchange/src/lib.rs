@@ -0,0 +1,446 @@
+//! weak-ssl-key-exchange plugin
+//!
+//! This plugin checks the key exchange settings of TLS servers:
+//!
+//! - DHE cipher suites enabled without `ssl_dhparam` (taking inheritance from
+//!   the `http`/`stream` block into account), which nginx silently disables
+//! - `ssl_ecdh_curve` lists that pin outdated curves (binary curves and
+//!   curves smaller than 256 bits)
+//!
+//! Checking the size of the `ssl_dhparam` file needs filesystem access, which
+//! plugins do not have; the CLI does it with `--check-paths`.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::inherit::{Inherited, InheritedSettings};
+use nginx_lint_plugin::prelude::*;
+
+/// Cipher aliases that select (ephemeral) finite-field Diffie-Hellman
+const DHE_ALIASES: &[&str] = &["DHE", "EDH", "KDHE", "KEDH"];
+
+/// Exclusions that remove every DHE cipher from the list
+const DHE_EXCLUSIONS: &[&str] = &["DH", "KDH", "DHE", "EDH", "KDHE", "KEDH"];
+
+/// Prime curves smaller than 256 bits
+const OUTDATED_CURVES: &[&str] = &[
+    "secp160k1",
+    "secp160r1",
+    "secp160r2",
+    "secp192k1",
+    "secp192r1",
+    "prime192v1",
+    "prime192v2",
+    "prime192v3",
+    "P-192",
+    "secp224k1",
+    "secp224r1",
+    "P-224",
+    "prime239v1",
+    "prime239v2",
+    "prime239v3",
+    "brainpoolP160r1",
+    "brainpoolP160t1",
+    "brainpoolP192r1",
+    "brainpoolP192t1",
+    "brainpoolP224r1",
+    "brainpoolP224t1",
+];
+
+/// Curves used when `ssl_ecdh_curve` has nothing modern left
+const RECOMMENDED_CURVES: &str = "X25519:prime256v1:secp384r1";
+
+/// Check DHE parameters and ECDH curves
+#[derive(Default)]
+pub struct WeakSslKeyExchangePlugin;
+
+/// `ssl_ciphers` / `ssl_dhparam` in effect for a block
+type KeyExchange<'a> = InheritedSettings<'a, 2>;
+
+/// The directives followed through the blocks
+const KEY_EXCHANGE: [&str; 2] = ["ssl_ciphers", "ssl_dhparam"];
+
+impl WeakSslKeyExchangePlugin {
+    /// Walk `http`/`stream`/`server` blocks and check the settings in effect
+    /// for every server (or for a block that has no servers below it)
+    fn check_dhe<'a>(
+        &self,
+        items: &'a [ConfigItem],
+        inherited: KeyExchange<'a>,
+        reported: &mut Vec<(usize, usize)>,
+        errors: &mut Vec<LintError>,
+    ) {
+        let effective = inherited.enter(items);
+
+        let mut has_children = false;
+        for item in items {
+            if let ConfigItem::Directive(d) = item
+                && (d.is("http") || d.is("stream") || d.is("server"))
+                && let Some(block) = &d.block
+            {
+                has_children = true;
+                self.check_dhe(&block.items, effective, reported, errors);
+            }
+        }
+        if has_children || effective.get("ssl_dhparam").directive().is_some() {
+            return;
+        }
+
+        let Some(ciphers) = effective.get("ssl_ciphers").directive() else {
+            return;
+        };
+        let Some(arg) = ciphers.args.first() else {
+            return;
+        };
+        let dhe = dhe_ciphers(arg.as_str());
+        if dhe.is_empty() {
+            return;
+        }
+
+        // An http-level ssl_ciphers is reported once, not for every server
+        let position = (arg.span.start.line, arg.span.start.column);
+        if reported.contains(&position) {
+            return;
+        }
+        reported.push(position);
+
+        let err = self.spec().error_builder();
+        errors.push(err.warning_at_arg(
+            &format!(
+                "DHE cipher(s) {} are enabled without ssl_dhparam, so nginx will not use them; \
+                 add 'ssl_dhparam' with 2048-bit or larger parameters or remove them",
+                dhe.iter()
+                    .map(|c| format!("'{}'", c))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ciphers,
+            0,
+        ));
+    }
+
+    fn check_ecdh_curve(&self, directive: &Directive, errors: &mut Vec<LintError>) {
+        let Some(arg) = directive.args.first() else {
+            return;
+        };
+        let curves: Vec<&str> = arg.as_str().split(':').collect();
+        let (outdated, modern): (Vec<&str>, Vec<&str>) =
+            curves.into_iter().partition(|c| is_outdated_curve(c));
+        if outdated.is_empty() {
+            return;
+        }
+
+        let replacement = if modern.is_empty() {
+            RECOMMENDED_CURVES.to_string()
+        } else {
+            modern.join(":")
+        };
+
+        let err = self.spec().error_builder();
+        errors.push(
+            err.warning_at_arg(
+                &format!(
+                    "ssl_ecdh_curve pins outdated curve(s) {}; use '{}' or remove the \
+                     directive to use the default 'auto'",
+                    outdated
+                        .iter()
+                        .map(|c| format!("'{}'", c))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    replacement
+                ),
+                directive,
+                0,
+            )
+            .with_fix(directive.replace_with(&format!("ssl_ecdh_curve {};", replacement))),
+        );
+    }
+}
+
+/// DHE ciphers enabled by `cipher_string` (e.g. `DHE-RSA-AES128-GCM-SHA256`
+/// or `EDH+AESGCM`), unless DHE is excluded as a whole
+fn dhe_ciphers(cipher_string: &str) -> Vec<&str> {
+    let specs: Vec<&str> = cipher_string
+        .split([':', ',', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let excluded = specs.iter().any(|spec| {
+        spec.strip_prefix('!')
+            .is_some_and(|name| DHE_EXCLUSIONS.contains(&name.to_uppercase().as_str()))
+    });
+    if excluded {
+        return Vec::new();
+    }
+
+    specs
+        .into_iter()
+        .filter(|spec| !spec.starts_with(['!', '-', '+']))
+        .filter(|spec| {
+            spec.split('+').any(|part| {
+                let upper = part.to_uppercase();
+                upper.starts_with("DHE-")
+                    || upper.starts_with("EDH-")
+                    || DHE_ALIASES.contains(&upper.as_str())
+            })
+        })
+        .collect()
+}
+
+/// Binary curves (deprecated by RFC 8422) and prime curves under 256 bits
+fn is_outdated_curve(curve: &str) -> bool {
+    curve.starts_with("sect") || curve.starts_with("c2") || OUTDATED_CURVES.contains(&curve)
+}
+
+impl Plugin for WeakSslKeyExchangePlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "weak-ssl-key-exchange",
+            "security",
+            "Detects DHE ciphers without ssl_dhparam and outdated ECDH curves",
+        )
+        .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
+        .with_why(
+            "Since nginx 1.11.0 no Diffie-Hellman parameters are set by default, so DHE cipher \
+             suites listed in `ssl_ciphers` are silently unusable until `ssl_dhparam` points \
+             at parameters of 2048 bits or more (smaller groups are vulnerable to Logjam-style \
+             precomputation). Either configure `ssl_dhparam` or drop the DHE suites; ECDHE \
+             provides forward secrecy without extra parameters.\n\n\
+             `ssl_ecdh_curve` defaults to `auto`, which lets OpenSSL pick modern curves. \
+             Pinning binary curves (`sect*`) or curves smaller than 256 bits (e.g. \
+             `secp192r1`, `secp224r1`) weakens the key exchange; use \
+             `X25519:prime256v1:secp384r1` or leave the directive out.\n\n\
+             Run `nginx-lint --check-paths` to also check that the `ssl_dhparam` file \
+             holds at least 2048-bit parameters.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_ssl_module.html#ssl_dhparam".to_string(),
+            "https://nginx.org/en/docs/http/ngx_http_ssl_module.html#ssl_ecdh_curve".to_string(),
+            "https://weakdh.org/".to_string(),
+            "https://wiki.mozilla.org/Security/Server_Side_TLS".to_string(),
+            "https://github.com/walf443/nginx-lint/blob/main/plugins/builtin/security/weak_ssl_key_exchange/tests/container_test.rs".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["ssl_ciphers", "ssl_ecdh_curve"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+
+        // In an included file the including block may set ssl_dhparam, so
+        // only whole configurations are checked for DHE
+        if config.include_context.is_empty() {
+            self.check_dhe(
+                &config.items,
+                KeyExchange::new(KEY_EXCHANGE, Inherited::Default),
+                &mut Vec::new(),
+                &mut errors,
+            );
+        }
+
+        for directive in config.all_directives() {
+            if directive.is("ssl_ecdh_curve") {
+                self.check_ecdh_curve(directive, &mut errors);
+            }
+        }
+
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(WeakSslKeyExchangePlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::parse_string;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    fn check(source: &str) -> Vec<LintError> {
+        let config = parse_string(source).unwrap();
+        WeakSslKeyExchangePlugin.check(&config, "test.conf")
+    }
+
+    #[test]
+    fn test_dhe_without_dhparam() {
+        let errors = check(
+            r#"
+http {
+    server {
+        listen 443 ssl;
+        ssl_ciphers ECDHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES128-GCM-SHA256;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert!(
+            errors[0]
+                .message
+                .contains("DHE cipher(s) 'DHE-RSA-AES128-GCM-SHA256' are enabled without")
+        );
+    }
+
+    #[test]
+    fn test_dhparam_inherited_from_http() {
+        let runner = PluginTestRunner::new(WeakSslKeyExchangePlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    ssl_dhparam /etc/nginx/dhparam.pem;
+
+    server {
+        listen 443 ssl;
+        ssl_ciphers DHE-RSA-AES128-GCM-SHA256;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_http_ciphers_reported_once() {
+        // Only the server without its own ssl_dhparam is affected, and the
+        // shared ssl_ciphers line is reported a single time
+        let errors = check(
+            r#"
+http {
+    ssl_ciphers EDH+AESGCM:ECDHE+AESGCM;
+
+    server {
+        listen 443 ssl;
+        ssl_dhparam /etc/nginx/dhparam.pem;
+    }
+    server {
+        listen 8443 ssl;
+    }
+    server {
+        listen 9443 ssl;
+    }
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(3));
+        assert!(errors[0].message.contains("'EDH+AESGCM'"));
+    }
+
+    #[test]
+    fn test_dhe_excluded_or_absent() {
+        let runner = PluginTestRunner::new(WeakSslKeyExchangePlugin);
+
+        runner.assert_no_errors(
+            r#"
+http {
+    ssl_ciphers HIGH:!aNULL:!MD5;
+
+    server {
+        ssl_ciphers EDH+AESGCM:ECDHE+AESGCM:!DHE;
+    }
+    server {
+        ssl_ciphers ECDHE-RSA-AES128-GCM-SHA256:ECDHE-RSA-CHACHA20-POLY1305;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_included_file_is_not_checked_for_dhe() {
+        let mut config = parse_string(
+            r#"
+server {
+    listen 443 ssl;
+    ssl_ciphers DHE-RSA-AES128-GCM-SHA256;
+}
+"#,
+        )
+        .unwrap();
+        config.include_context = vec!["http".to_string()].into();
+
+        let errors = WeakSslKeyExchangePlugin.check(&config, "test.conf");
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_outdated_ecdh_curve() {
+        let errors = check(
+            r#"
+http {
+    ssl_ecdh_curve sect163k1:secp224r1:prime256v1;
+}
+"#,
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(
+            errors[0]
+                .message
+                .contains("outdated curve(s) 'sect163k1', 'secp224r1'; use 'prime256v1'")
+        );
+        assert_eq!(
+            errors[0].fixes[0].new_text.trim(),
+            "ssl_ecdh_curve prime256v1;"
+        );
+    }
+
+    #[test]
+    fn test_only_outdated_curves_suggest_recommended() {
+        let errors = check("ssl_ecdh_curve secp192r1;\n");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].fixes[0].new_text,
+            "ssl_ecdh_curve X25519:prime256v1:secp384r1;"
+        );
+    }
+
+    #[test]
+    fn test_modern_curves_are_ok() {
+        let runner = PluginTestRunner::new(WeakSslKeyExchangePlugin);
+
+        runner.assert_no_errors("ssl_ecdh_curve X25519:prime256v1:secp384r1:secp521r1;\n");
+        runner.assert_no_errors("ssl_ecdh_curve auto;\n");
+    }
+
+    #[test]
+    fn test_dhe_ciphers() {
+        assert_eq!(
+            dhe_ciphers("DHE-RSA-AES256-GCM-SHA384:EDH-RSA-DES-CBC3-SHA:kEDH+AESGCM"),
+            vec![
+                "DHE-RSA-AES256-GCM-SHA384",
+                "EDH-RSA-DES-CBC3-SHA",
+                "kEDH+AESGCM"
+            ]
+        );
+        assert!(dhe_ciphers("ECDHE-RSA-AES128-GCM-SHA256:ECDHE+AESGCM").is_empty());
+        assert!(dhe_ciphers("DHE-RSA-AES128-GCM-SHA256:!kEDH").is_empty());
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(WeakSslKeyExchangePlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(WeakSslKeyExchangePlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
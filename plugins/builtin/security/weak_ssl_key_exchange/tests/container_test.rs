@@ -0,0 +1,65 @@
+//! Container-based integration tests for the weak-ssl-key-exchange rule.
+//!
+//! Verifies that DHE cipher suites cannot be negotiated when no `ssl_dhparam`
+//! is configured, even though they are listed in `ssl_ciphers`, and that an
+//! ECDHE suite still works with the same configuration.
+//!
+//! Run with:
+//!   cargo test -p weak-ssl-key-exchange-plugin --test container_test -- --ignored
+//!
+//! Specify nginx version via environment variable (default: "1.27"):
+//!   NGINX_VERSION=1.26 cargo test -p weak-ssl-key-exchange-plugin --test container_test -- --ignored
+
+use nginx_lint_plugin::container_testing::NginxContainer;
+
+/// TLSv1.2 server listing a DHE and an ECDHE suite, without ssl_dhparam.
+const DHE_WITHOUT_DHPARAM: &str = r#"
+events { worker_connections 1024; }
+http {
+    server {
+        listen 443 ssl;
+        ssl_certificate /tmp/cert.pem;
+        ssl_certificate_key /tmp/key.pem;
+        ssl_protocols TLSv1.2;
+        ssl_ciphers DHE-RSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256;
+        location / { return 200 "ssl-ok"; }
+    }
+}
+"#;
+
+/// Without ssl_dhparam the listed DHE suite is never negotiated.
+#[tokio::test]
+#[ignore]
+async fn dhe_cipher_without_dhparam_is_not_negotiated() {
+    let nginx = NginxContainer::start_ssl(DHE_WITHOUT_DHPARAM).await;
+    let output = nginx
+        .exec_shell(
+            "echo | openssl s_client -connect 127.0.0.1:443 -tls1_2 -cipher DHE-RSA-AES128-GCM-SHA256 2>&1",
+        )
+        .await;
+    assert!(
+        !output
+            .stdout
+            .contains("Cipher is DHE-RSA-AES128-GCM-SHA256"),
+        "Expected the DHE handshake to fail without ssl_dhparam, got: {}",
+        output.output()
+    );
+}
+
+/// The ECDHE suite of the same configuration works, showing that only the
+/// DHE part of `ssl_ciphers` is silently unusable.
+#[tokio::test]
+#[ignore]
+async fn ecdhe_cipher_without_dhparam_is_negotiated() {
+    let nginx = NginxContainer::start_ssl(DHE_WITHOUT_DHPARAM).await;
+    let output = nginx
+        .exec_shell(
+            "echo | openssl s_client -connect 127.0.0.1:443 -tls1_2 -cipher ECDHE-RSA-AES128-GCM-SHA256 2>&1 | grep 'Cipher is'",
+        )
+        .await;
+    assert!(
+        output.stdout.contains("ECDHE-RSA-AES128-GCM-SHA256"),
+        "Expected the ECDHE cipher to be negotiated, got: {}",
+        output.output()
+    );
+}
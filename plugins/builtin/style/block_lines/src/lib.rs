@@ -41,6 +41,7 @@ impl Plugin for BlockLinesPlugin {
             "Warns when a block directive exceeds the maximum number of lines",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "Blocks with too many lines are difficult to read and maintain. \
              Consider splitting large blocks into smaller files using the include directive.",
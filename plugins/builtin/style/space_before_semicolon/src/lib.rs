@@ -21,6 +21,7 @@ impl Plugin for SpaceBeforeSemicolonPlugin {
             "Detects spaces or tabs before semicolons",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "Spaces before semicolons violate common coding style conventions \
              and reduce readability. Semicolons should be placed immediately \
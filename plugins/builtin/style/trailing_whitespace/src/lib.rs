@@ -21,6 +21,7 @@ impl Plugin for TrailingWhitespacePlugin {
             "Detects trailing whitespace at the end of lines",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Strict)
         .with_why(
             "Trailing whitespace is invisible and can cause unnecessary diffs \
              in version control and hinder code reviews. \
@@ -42,6 +42,10 @@ const HTTP_UNIQUE_DIRECTIVES: &[&str] = &[
     "server_names_hash_max_size",
     "variables_hash_max_size",
     "variables_hash_bucket_size",
+    "charset",
+    "source_charset",
+    "override_charset",
+    "charset_types",
 ];
 
 /// Directives that should only appear once per server context
@@ -52,6 +56,11 @@ const SERVER_UNIQUE_DIRECTIVES: &[&str] = &[
     "client_max_body_size",
     "access_log",
     "error_log",
+    "default_type",
+    "charset",
+    "source_charset",
+    "override_charset",
+    "charset_types",
 ];
 
 /// Directives that should only appear once per location context
@@ -63,6 +72,11 @@ const LOCATION_UNIQUE_DIRECTIVES: &[&str] = &[
     "internal",
     "autoindex",
     "client_max_body_size",
+    "default_type",
+    "charset",
+    "source_charset",
+    "override_charset",
+    "charset_types",
 ];
 
 /// Directives that should only appear once per upstream context
@@ -138,6 +152,7 @@ impl Plugin for DuplicateDirectivePlugin {
             "Detects duplicate directives in the same context",
         )
         .with_severity("warning")
+        .with_preset(RulePreset::Recommended)
         .with_why(
             "Some directives cannot be specified multiple times in the same context. \
              When duplicated, nginx may use only the last value or throw an error. \
@@ -313,6 +328,27 @@ http {
         );
     }
 
+    #[test]
+    fn test_duplicate_charset_settings() {
+        let runner = PluginTestRunner::new(DuplicateDirectivePlugin);
+
+        runner.assert_errors(
+            r#"
+http {
+    charset utf-8;
+    charset koi8-r;
+    server {
+        location / {
+            default_type text/html;
+            default_type application/json;
+        }
+    }
+}
+"#,
+            2,
+        );
+    }
+
     #[test]
     fn test_same_directive_different_servers_is_ok() {
         // Same directive in different server blocks is fine
@@ -382,7 +418,7 @@ root /var/www/html;
         .unwrap();
 
         // Simulate being included from http > server context
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = DuplicateDirectivePlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -23,6 +23,7 @@ impl Plugin for InvalidDirectiveContextPlugin {
             "Detects directives placed in invalid parent contexts",
         )
         .with_severity("error")
+        .with_preset(RulePreset::Minimal)
         .with_why(
             "nginx directives must be placed in the correct context (parent block). \
              For example, 'server' can only be inside 'http', 'stream', or 'mail' blocks, \
@@ -42,7 +43,7 @@ impl Plugin for InvalidDirectiveContextPlugin {
         let mut errors = Vec::new();
 
         // Build initial parent stack from include_context
-        let parent_stack: Vec<&str> = config.include_context.iter().map(|s| s.as_str()).collect();
+        let parent_stack: Vec<&str> = config.include_context.names().collect();
 
         check_context(&config.items, &parent_stack, &mut errors);
 
@@ -479,7 +480,7 @@ location / {
         )
         .unwrap();
 
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = InvalidDirectiveContextPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -500,7 +501,7 @@ server {
         )
         .unwrap();
 
-        config.include_context = vec!["http".to_string(), "server".to_string()];
+        config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
         let plugin = InvalidDirectiveContextPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -529,7 +530,7 @@ server {
         )
         .unwrap();
 
-        config.include_context = vec!["http".to_string()];
+        config.include_context = vec!["http".to_string()].into();
 
         let plugin = InvalidDirectiveContextPlugin;
         let errors = plugin.check(&config, "test.conf");
@@ -0,0 +1,357 @@
+//! listen-conflict plugin
+//!
+//! This plugin compares the `listen` directives of the servers in an `http`
+//! block and reports the combinations nginx refuses to start with:
+//!
+//! - the same address listened on twice in one server
+//! - more than one `default_server` for the same address
+//! - socket options (`reuseport`, `backlog`, `so_keepalive`, ...) given on
+//!   more than one `listen` of the same address
+//!
+//! `listen 80`, `listen *:80` and `listen 0.0.0.0:80` are the same address;
+//! `quic` listens are UDP sockets, separate from the TCP ones. Servers are
+//! compared within one file; default servers in different files are reported
+//! by the cross-file `duplicate-default-server` rule. `ssl` without a
+//! certificate and the deprecated `http2` parameter are reported by
+//! `ssl-certificate-missing` and `listen-http2-deprecated`.
+//!
+//! Build with:
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --release
+//! ```
+
+use nginx_lint_plugin::helpers::normalize_listen_address;
+use nginx_lint_plugin::prelude::*;
+use std::collections::HashMap;
+
+/// Parameters that set options of the listening socket, which nginx accepts
+/// on only one `listen` per address
+const SOCKET_OPTIONS: &[&str] = &[
+    "backlog",
+    "rcvbuf",
+    "sndbuf",
+    "accept_filter",
+    "deferred",
+    "bind",
+    "ipv6only",
+    "reuseport",
+    "so_keepalive",
+    "fastopen",
+    "setfib",
+];
+
+/// Check listen directives for addresses nginx cannot set up
+#[derive(Default)]
+pub struct ListenConflictPlugin;
+
+/// A `listen` directive of a server
+struct Listen<'a> {
+    directive: &'a Directive,
+    /// The normalized address, with ` quic` appended for UDP sockets
+    address: String,
+    default_server: bool,
+    /// The socket options it sets
+    options: Vec<&'a str>,
+}
+
+impl<'a> Listen<'a> {
+    fn parse(directive: &'a Directive) -> Option<Self> {
+        let address = directive.args.first()?;
+        // The address is only known at runtime
+        if address.raw.contains('$') {
+            return None;
+        }
+        let params: Vec<&str> = directive.args[1..].iter().map(|a| a.as_str()).collect();
+        let mut address = normalize_listen_address(address.as_str());
+        if params.contains(&"quic") {
+            address.push_str(" quic");
+        }
+        Some(Self {
+            directive,
+            address,
+            default_server: params
+                .iter()
+                .any(|p| matches!(*p, "default_server" | "default")),
+            options: params
+                .iter()
+                .map(|p| p.split('=').next().unwrap_or(p))
+                .filter(|name| SOCKET_OPTIONS.contains(name))
+                .collect(),
+        })
+    }
+}
+
+/// The `listen` directives of each server block directly in `items`
+fn servers(items: &[ConfigItem]) -> Vec<Vec<Listen<'_>>> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            ConfigItem::Directive(d) if d.is("server") => d.block.as_ref(),
+            _ => None,
+        })
+        .map(|block| listens(&block.items))
+        .collect()
+}
+
+/// The `listen` directives directly in `items`
+fn listens(items: &[ConfigItem]) -> Vec<Listen<'_>> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            ConfigItem::Directive(d) if d.is("listen") => Listen::parse(d),
+            _ => None,
+        })
+        .collect()
+}
+
+impl ListenConflictPlugin {
+    /// Compare the listens of `servers`, which share an `http` block
+    fn check_servers(&self, servers: &[Vec<Listen<'_>>], errors: &mut Vec<LintError>) {
+        let err = self.spec().error_builder();
+        // The first listen of each address setting these, across servers
+        let mut defaults: HashMap<&str, &Listen<'_>> = HashMap::new();
+        let mut options: HashMap<&str, &Listen<'_>> = HashMap::new();
+
+        for listens in servers {
+            let mut seen: HashMap<&str, &Listen<'_>> = HashMap::new();
+            for listen in listens {
+                let address = listen.address.as_str();
+                if let Some(first) = seen.get(address) {
+                    let message = format!(
+                        "{} is already listened on at line {} of this server; nginx refuses to \
+                         start with a duplicate listen",
+                        address,
+                        first.directive.line()
+                    );
+                    errors.push(err.error_at(&message, listen.directive));
+                    continue;
+                }
+                seen.insert(address, listen);
+
+                if listen.default_server {
+                    if let Some(first) = defaults.get(address) {
+                        let message = format!(
+                            "{} already has a default_server at line {}; nginx refuses to start \
+                             with two",
+                            address,
+                            first.directive.line()
+                        );
+                        errors.push(err.error_at(&message, listen.directive));
+                    } else {
+                        defaults.insert(address, listen);
+                    }
+                }
+
+                if !listen.options.is_empty() {
+                    if let Some(first) = options.get(address) {
+                        let message = format!(
+                            "socket options for {} are already set at line {}; nginx accepts \
+                             {} on only one listen per address",
+                            address,
+                            first.directive.line(),
+                            listen.options.join(", ")
+                        );
+                        errors.push(err.error_at(&message, listen.directive));
+                    } else {
+                        options.insert(address, listen);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Plugin for ListenConflictPlugin {
+    fn spec(&self) -> PluginSpec {
+        PluginSpec::new(
+            "listen-conflict",
+            "syntax",
+            "Detects listen directives that conflict across the servers of an http block",
+        )
+        .with_severity("error")
+        .with_preset(RulePreset::Minimal)
+        .with_why(
+            "nginx sets up one listening socket per address and port, shared by every server \
+             that listens on it, and refuses to start when the servers disagree about it: when \
+             one server lists the same address twice (\"a duplicate listen\"), when two servers \
+             mark it `default_server` (\"a duplicate default server\"), or when socket options \
+             such as `reuseport`, `backlog` or `so_keepalive` appear on more than one `listen` \
+             for it (\"duplicate listen options\").\n\n\
+             Mark one server as the default for each address, and give the socket options on \
+             that server's `listen` only; the other servers list the bare address.\n\n\
+             Servers are compared within one file. `duplicate-default-server` compares the \
+             default servers of every file of the configuration.",
+        )
+        .with_bad_example(include_str!("../examples/bad.conf").trim())
+        .with_good_example(include_str!("../examples/good.conf").trim())
+        .with_references(vec![
+            "https://nginx.org/en/docs/http/ngx_http_core_module.html#listen".to_string(),
+            "https://nginx.org/en/docs/http/request_processing.html".to_string(),
+        ])
+    }
+
+    fn relevant_directives(&self) -> Option<&'static [&'static str]> {
+        Some(&["listen"])
+    }
+
+    fn check(&self, config: &Config, _path: &str) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        let context: Vec<&str> = config.include_context.names().collect();
+        match context.last() {
+            None => {
+                for item in &config.items {
+                    if let ConfigItem::Directive(d) = item
+                        && d.is("http")
+                        && let Some(block) = &d.block
+                    {
+                        self.check_servers(&servers(&block.items), &mut errors);
+                    }
+                }
+            }
+            Some(&"http") => self.check_servers(&servers(&config.items), &mut errors),
+            Some(&"server") if context.contains(&"http") => {
+                self.check_servers(&[listens(&config.items)], &mut errors)
+            }
+            _ => {}
+        }
+        errors
+    }
+}
+
+nginx_lint_plugin::export_component_plugin!(ListenConflictPlugin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nginx_lint_plugin::testing::PluginTestRunner;
+
+    #[test]
+    fn test_duplicate_default_server() {
+        let runner = PluginTestRunner::new(ListenConflictPlugin);
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        listen 80 default_server;
+        listen [::]:80 default_server;
+    }
+
+    server {
+        listen 0.0.0.0:80 default_server;
+        listen [::]:80;
+        listen 8080 default_server;
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(9));
+        assert_eq!(
+            errors[0].message,
+            "*:80 already has a default_server at line 4; nginx refuses to start with two"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_listen_in_server() {
+        let runner = PluginTestRunner::new(ListenConflictPlugin);
+        runner.assert_error_on_line(
+            "http {\n    server {\n        listen 443 ssl;\n        listen *:443 ssl;\n    }\n}\n",
+            4,
+        );
+        // TCP and QUIC on the same port are separate sockets
+        runner.assert_no_errors(
+            "http {\n    server {\n        listen 443 ssl;\n        listen 443 quic reuseport;\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_repeated_socket_options() {
+        let runner = PluginTestRunner::new(ListenConflictPlugin);
+        let errors = runner
+            .check_string(
+                r#"
+http {
+    server {
+        listen 443 ssl reuseport backlog=4096;
+    }
+
+    server {
+        listen 443 ssl reuseport;
+    }
+
+    server {
+        listen 127.0.0.1:443 ssl reuseport;
+    }
+}
+"#,
+            )
+            .unwrap();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(8));
+        assert_eq!(
+            errors[0].message,
+            "socket options for *:443 are already set at line 4; nginx accepts reuseport on \
+             only one listen per address"
+        );
+    }
+
+    #[test]
+    fn test_distinct_addresses() {
+        let runner = PluginTestRunner::new(ListenConflictPlugin);
+        runner.assert_no_errors(
+            r#"
+http {
+    server {
+        listen 80 default_server;
+        listen unix:/run/nginx.sock default_server;
+        listen $port default_server;
+    }
+
+    server {
+        listen 81 default_server;
+        listen 127.0.0.1:80 default_server;
+        listen unix:/run/other.sock default_server;
+        listen $port default_server;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_included_file() {
+        let plugin = ListenConflictPlugin;
+        let mut config = nginx_lint_plugin::parse_string(
+            "server {\n    listen 80 default_server;\n}\nserver {\n    listen 80 default_server;\n}\n",
+        )
+        .unwrap();
+        config.include_context = IncludeContext::parse("http");
+        assert_eq!(plugin.check(&config, "test.conf").len(), 1);
+
+        let mut config =
+            nginx_lint_plugin::parse_string("listen 80 reuseport;\nlisten 80;\n").unwrap();
+        config.include_context = IncludeContext::parse("http,server");
+        assert_eq!(plugin.check(&config, "test.conf").len(), 1);
+
+        config.include_context = IncludeContext::parse("stream,server");
+        assert!(plugin.check(&config, "test.conf").is_empty());
+    }
+
+    #[test]
+    fn test_examples() {
+        let runner = PluginTestRunner::new(ListenConflictPlugin);
+        runner.test_examples(
+            include_str!("../examples/bad.conf"),
+            include_str!("../examples/good.conf"),
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let runner = PluginTestRunner::new(ListenConflictPlugin);
+        runner.test_fixtures(nginx_lint_plugin::fixtures_dir!());
+    }
+}
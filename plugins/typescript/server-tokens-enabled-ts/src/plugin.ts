@@ -39,7 +39,7 @@ export function spec(): PluginSpec {
     // Keep in sync with API_VERSION from nginx-lint-plugin (enforced by a
     // test; a runtime import would break jco componentize, which cannot
     // resolve bare module specifiers)
-    apiVersion: "1.2",
+    apiVersion: "2.0",
     severity: "warning",
     why: "When server_tokens is 'on' (the default), nginx includes its version number in " +
       "the Server response header and on default error pages. This information can help " +
@@ -114,6 +114,7 @@ export function check(rawCfg: Config, path: string): LintError[] {
           line: directive.line(),
           column: directive.column(),
           fixes: [directive.replaceWith("server_tokens off;")],
+          alternatives: [],
         });
       }
     }
@@ -138,6 +139,7 @@ export function check(rawCfg: Config, path: string): LintError[] {
       line: httpBlockLine,
       column: 1,
       fixes: [],
+      alternatives: [],
     });
   }
 
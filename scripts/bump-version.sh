@@ -27,6 +27,8 @@ CARGO_FILES=(
     "$ROOT_DIR/crates/nginx-lint-parser/Cargo.toml"
     "$ROOT_DIR/crates/nginx-lint-common/Cargo.toml"
     "$ROOT_DIR/crates/nginx-lint-plugin/Cargo.toml"
+    "$ROOT_DIR/crates/nginx-lint-report/Cargo.toml"
+    "$ROOT_DIR/crates/nginx-lint-ffi/Cargo.toml"
 )
 
 # Add all plugin Cargo.toml files
@@ -0,0 +1,283 @@
+//! Configuration trees packed in tar archives.
+//!
+//! [`read_tar`] loads an archive (a backup of `/etc/nginx`, or the
+//! filesystem of a container exported with `docker export`) into a
+//! [`MemoryFileSystem`], so the tree is linted and its includes followed
+//! without extracting it to disk. Symbolic links between files of the
+//! archive (e.g. `sites-enabled/default -> ../sites-available/default`)
+//! are resolved while reading.
+//!
+//! ```
+//! use nginx_lint::archive::{find_root, read_tar};
+//! use std::path::Path;
+//!
+//! # let mut builder = tar::Builder::new(Vec::new());
+//! # let mut header = tar::Header::new_gnu();
+//! # header.set_size(10);
+//! # header.set_mode(0o644);
+//! # builder.append_data(&mut header, "nginx/nginx.conf", &b"events {}\n"[..]).unwrap();
+//! # let backup = builder.into_inner().unwrap();
+//! // A backup made with `tar -C /etc -cf backup.tar nginx`
+//! let source = read_tar(&backup[..], Path::new("/etc")).unwrap();
+//! assert_eq!(find_root(&source), Some(Path::new("/etc/nginx/nginx.conf").to_path_buf()));
+//! ```
+
+use crate::config_source::{ConfigSource, MemoryFileSystem, normalize};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+/// Files larger than this are kept as existing but not read; configuration
+/// files are far smaller, and a container filesystem holds large binaries
+const MAX_TEXT_SIZE: u64 = 1024 * 1024;
+
+/// Symbolic links pointing at other links are followed this many times
+const MAX_LINK_DEPTH: usize = 8;
+
+/// A file or directory of the archive
+#[derive(Clone)]
+enum Node {
+    Text(String),
+    Binary,
+    Dir,
+}
+
+/// Read the tar archive from `reader`, compressed with gzip or not, as if
+/// extracted at `mount_point` (`/` for a container filesystem).
+///
+/// Only regular files, directories and links are kept; links pointing
+/// outside the archive are dropped, as if dangling.
+pub fn read_tar(reader: impl Read, mount_point: &Path) -> io::Result<MemoryFileSystem> {
+    read_tar_within(reader, mount_point, mount_point)
+}
+
+/// Like [`read_tar`], but only the files below `tree` (e.g. `/etc/nginx` of
+/// a container filesystem) are read; the others are kept as existing but
+/// not read, as files too large to be configuration are.
+pub fn read_tar_within(
+    reader: impl Read,
+    mount_point: &Path,
+    tree: &Path,
+) -> io::Result<MemoryFileSystem> {
+    let mut reader = BufReader::new(reader);
+    let is_gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+    if is_gzip {
+        read_entries(flate2::read::GzDecoder::new(reader), mount_point, tree)
+    } else {
+        read_entries(reader, mount_point, tree)
+    }
+}
+
+/// The configuration file nginx would load from the tree: the `nginx.conf`
+/// closest to the root
+pub fn find_root(source: &MemoryFileSystem) -> Option<PathBuf> {
+    source
+        .glob("/**/nginx.conf")
+        .into_iter()
+        .min_by_key(|path| path.components().count())
+}
+
+fn read_entries(
+    reader: impl Read,
+    mount_point: &Path,
+    tree: &Path,
+) -> io::Result<MemoryFileSystem> {
+    let mut nodes: BTreeMap<PathBuf, Node> = BTreeMap::new();
+    // (link, target), both as mounted
+    let mut links: Vec<(PathBuf, PathBuf)> = Vec::new();
+
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = mount_point.join(relative(&entry.path()?));
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            nodes.insert(path, Node::Dir);
+        } else if entry_type.is_file() {
+            let node = if entry.size() > MAX_TEXT_SIZE || !path.starts_with(tree) {
+                Node::Binary
+            } else {
+                let mut contents = Vec::new();
+                entry.read_to_end(&mut contents)?;
+                String::from_utf8(contents).map_or(Node::Binary, Node::Text)
+            };
+            nodes.insert(path, node);
+        } else if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let Some(target) = entry.link_name()? else {
+                continue;
+            };
+            // Hard links name the target by its path in the archive;
+            // symbolic links are relative to their directory or absolute
+            // within the container
+            let target = if entry_type.is_hard_link() || target.is_absolute() {
+                mount_point.join(relative(&target))
+            } else {
+                normalize(&path.parent().unwrap_or(mount_point).join(&target))
+            };
+            links.push((path, target));
+        }
+    }
+
+    resolve_links(&mut nodes, links);
+
+    let mut source = MemoryFileSystem::new();
+    for (path, node) in nodes {
+        source = match node {
+            Node::Text(contents) => source.with_file(path, contents),
+            Node::Binary => source.with_binary_file(path),
+            Node::Dir => source.with_dir(path),
+        };
+    }
+    Ok(source)
+}
+
+/// `path` of an archive entry without its root and `..` components, so
+/// that entries cannot land outside the mount point
+fn relative(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+/// Copy the files links point at to the links' paths; a link to a
+/// directory gets a copy of everything below it
+fn resolve_links(nodes: &mut BTreeMap<PathBuf, Node>, mut links: Vec<(PathBuf, PathBuf)>) {
+    for _ in 0..MAX_LINK_DEPTH {
+        let mut pending = Vec::new();
+        for (link, target) in links {
+            let copies: Vec<(PathBuf, Node)> = nodes
+                .range(target.clone()..)
+                .take_while(|(path, _)| path.starts_with(&target))
+                .map(|(path, node)| {
+                    let rebased = match path.strip_prefix(&target) {
+                        Ok(rest) if !rest.as_os_str().is_empty() => link.join(rest),
+                        _ => link.clone(),
+                    };
+                    (rebased, node.clone())
+                })
+                .collect();
+            if copies.is_empty() {
+                // The target may be another link resolved in this pass
+                pending.push((link, target));
+            } else {
+                nodes.extend(copies);
+            }
+        }
+        if pending.is_empty() {
+            return;
+        }
+        links = pending;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_source::FileStatus;
+
+    fn file(builder: &mut tar::Builder<Vec<u8>>, path: &str, contents: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, path, contents).unwrap();
+    }
+
+    fn link(builder: &mut tar::Builder<Vec<u8>>, path: &str, target: &str) {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder.append_link(&mut header, path, target).unwrap();
+    }
+
+    fn container() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        file(
+            &mut builder,
+            "etc/nginx/nginx.conf",
+            b"http {\n    include sites-enabled/*;\n}\n",
+        );
+        file(
+            &mut builder,
+            "etc/nginx/sites-available/default",
+            b"server {}\n",
+        );
+        link(
+            &mut builder,
+            "etc/nginx/sites-enabled/default",
+            "../sites-available/default",
+        );
+        link(&mut builder, "etc/nginx/ssl", "/etc/ssl/nginx");
+        file(&mut builder, "etc/ssl/nginx/cert.der", &[0x30, 0x82, 0xff]);
+        link(&mut builder, "etc/nginx/dangling.conf", "/nowhere.conf");
+        file(&mut builder, "usr/share/nginx/nginx.conf", b"");
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_read_tar() {
+        let source = read_tar(&container()[..], Path::new("/")).unwrap();
+
+        assert_eq!(
+            source
+                .read_file(Path::new("/etc/nginx/sites-enabled/default"))
+                .unwrap(),
+            "server {}\n"
+        );
+        assert_eq!(
+            source.glob("/etc/nginx/sites-enabled/*"),
+            [PathBuf::from("/etc/nginx/sites-enabled/default")]
+        );
+        assert_eq!(
+            source.status(Path::new("/etc/nginx/ssl/cert.der")),
+            FileStatus::Readable
+        );
+        assert_eq!(
+            source.status(Path::new("/etc/nginx/dangling.conf")),
+            FileStatus::Missing
+        );
+        assert_eq!(
+            find_root(&source),
+            Some(PathBuf::from("/etc/nginx/nginx.conf"))
+        );
+    }
+
+    #[test]
+    fn test_read_tar_within() {
+        let source =
+            read_tar_within(&container()[..], Path::new("/"), Path::new("/etc/nginx")).unwrap();
+
+        assert_eq!(
+            source
+                .read_file(Path::new("/etc/nginx/sites-enabled/default"))
+                .unwrap(),
+            "server {}\n"
+        );
+        // Files outside the tree exist, but are not read
+        assert_eq!(
+            source.status(Path::new("/usr/share/nginx/nginx.conf")),
+            FileStatus::Readable
+        );
+        assert!(
+            source
+                .read_file(Path::new("/usr/share/nginx/nginx.conf"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_read_gzip_at_mount_point() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut builder = tar::Builder::new(Vec::new());
+        file(&mut builder, "./nginx/conf.d/app.conf", b"server {}\n");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&builder.into_inner().unwrap()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let source = read_tar(&compressed[..], Path::new("/etc")).unwrap();
+        assert!(source.exists(Path::new("/etc/nginx/conf.d/app.conf")));
+        assert_eq!(find_root(&source), None);
+    }
+}
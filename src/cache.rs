@@ -3,14 +3,18 @@
 //! nginx-lint keeps all cacheable artifacts under a single cache root so
 //! that the cache location can be configured in one place (the `cache_dir`
 //! config value or the `--cache-dir` CLI flag). Each cache consumer owns a
-//! subdirectory beneath the root; currently the only consumer is the WASM
-//! plugin compilation cache under [`PLUGIN_CACHE_SUBDIR`].
+//! subdirectory beneath the root: the WASM plugin compilation cache under
+//! [`PLUGIN_CACHE_SUBDIR`] and the lint results of `--cache` under
+//! [`LINT_CACHE_SUBDIR`].
 
 use std::path::{Path, PathBuf};
 
 /// Subdirectory under the cache root for the WASM plugin compilation cache
 pub const PLUGIN_CACHE_SUBDIR: &str = "plugins";
 
+/// Subdirectory under the cache root for the lint results of `--cache`
+pub const LINT_CACHE_SUBDIR: &str = "lint";
+
 /// Per-user default cache root for nginx-lint:
 ///
 /// - Linux and other Unix: `$XDG_CACHE_HOME/nginx-lint` or `~/.cache/nginx-lint`
@@ -53,6 +57,11 @@ pub fn plugin_cache_dir(root: &Path) -> PathBuf {
     root.join(PLUGIN_CACHE_SUBDIR)
 }
 
+/// Lint result cache directory under the given cache root
+pub fn lint_cache_dir(root: &Path) -> PathBuf {
+    root.join(LINT_CACHE_SUBDIR)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,6 +72,12 @@ mod tests {
         assert_eq!(dir, PathBuf::from("/var/cache/nginx-lint/plugins"));
     }
 
+    #[test]
+    fn test_lint_cache_dir_is_under_root() {
+        let dir = lint_cache_dir(Path::new("/var/cache/nginx-lint"));
+        assert_eq!(dir, PathBuf::from("/var/cache/nginx-lint/lint"));
+    }
+
     #[test]
     fn test_default_cache_root_ends_with_app_name() {
         // Hermetic build sandboxes may run tests without HOME (or
@@ -0,0 +1,259 @@
+//! `image` and `archive`: lint the configuration tree inside a container
+//! image or a tar archive without extracting it; `--from-crossplane`: lint
+//! the tree dumped by crossplane.
+//!
+//! The tree is read into memory (see [`nginx_lint::archive`] and
+//! [`nginx_lint::crossplane`]) and linted from there, so includes and the
+//! files the rules check are looked up in the image, archive or dump rather
+//! than on the host.
+
+use super::lint::UNTRUSTED_DISABLED_RULES;
+use super::verify::container_engine;
+use super::{ExitArgs, Format, Trust};
+use nginx_lint::archive::{find_root, read_tar, read_tar_within};
+use nginx_lint::config::RuleConfig;
+use nginx_lint::config_source::MemoryFileSystem;
+use nginx_lint::crossplane::{FORMATTING_RULES, read_crossplane};
+use nginx_lint::{LintConfig, LintOptions, Reporter, TrustPolicy, lint_path};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
+use std::sync::Arc;
+
+/// The configuration file the official nginx images load
+const IMAGE_ROOT: &str = "/etc/nginx/nginx.conf";
+
+#[derive(clap::Args)]
+pub struct TreeArgs {
+    /// Configuration file to lint, as a path in the image or archive
+    /// [default: /etc/nginx/nginx.conf in an image, the nginx.conf closest
+    /// to the root of an archive]
+    #[arg(long, value_name = "PATH")]
+    pub root: Option<PathBuf>,
+
+    /// Path to configuration file
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short = 'o', long, value_enum, default_value = "errorformat")]
+    pub format: Format,
+
+    #[command(flatten)]
+    pub exit: ExitArgs,
+}
+
+/// Lint the configuration of the container image `image`, exported with
+/// `docker create` and `docker export`.
+///
+/// Only the files in the directory of the root configuration file are read;
+/// the rest of the filesystem is large and rarely configuration.
+pub fn run_image(image: &str, args: &TreeArgs, trust: Option<Trust>) -> ExitCode {
+    let root = match &args.root {
+        Some(root) => Path::new("/").join(root),
+        None => PathBuf::from(IMAGE_ROOT),
+    };
+    let tree = root.parent().unwrap_or(Path::new("/"));
+    match export_image(image, tree) {
+        Ok(source) => run_tree(source, Path::new("/"), args, trust, Some(&root), &[]),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Lint the configuration in the tar archive `file`, created from the
+/// directory `mount_point`.
+pub fn run_archive(
+    file: &Path,
+    mount_point: &Path,
+    args: &TreeArgs,
+    trust: Option<Trust>,
+) -> ExitCode {
+    let read = File::open(file).and_then(|f| read_tar(f, mount_point));
+    match read {
+        Ok(source) => run_tree(source, mount_point, args, trust, None, &[]),
+        Err(e) => {
+            eprintln!("Error reading {}: {}", file.display(), e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Lint the configuration in the crossplane dump `file` (`-` for stdin).
+pub fn run_crossplane(file: &Path, args: &TreeArgs, trust: Option<Trust>) -> ExitCode {
+    let json = if file.as_os_str() == "-" {
+        let mut json = String::new();
+        std::io::stdin().read_to_string(&mut json).map(|_| json)
+    } else {
+        std::fs::read_to_string(file)
+    };
+    let tree = match json {
+        Ok(json) => read_crossplane(&json),
+        Err(e) => Err(e.to_string()),
+    };
+    match tree {
+        Ok(tree) => {
+            for error in &tree.errors {
+                eprintln!("Warning: crossplane: {}", error);
+            }
+            run_tree(
+                tree.source,
+                Path::new("/"),
+                args,
+                trust,
+                Some(&tree.root),
+                FORMATTING_RULES,
+            )
+        }
+        Err(e) => {
+            eprintln!("Error reading {}: {}", file.display(), e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// The filesystem of a container created from `image`, with the files below
+/// `tree` read; the container is removed afterwards.
+fn export_image(image: &str, tree: &Path) -> Result<MemoryFileSystem, String> {
+    let engine = container_engine();
+    let output = Command::new(&engine)
+        .args(["create", image])
+        .output()
+        .map_err(|e| format!("failed to run {}: {}", engine, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} create {} failed:\n{}",
+            engine,
+            image,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let exported = export_container(&engine, &id, tree);
+    let _ = Command::new(&engine).args(["rm", &id]).output();
+    exported
+}
+
+fn export_container(engine: &str, id: &str, tree: &Path) -> Result<MemoryFileSystem, String> {
+    let mut child = Command::new(engine)
+        .args(["export", id])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", engine, e))?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let read = read_tar_within(stdout, Path::new("/"), tree);
+    let status = child
+        .wait()
+        .map_err(|e| format!("failed to run {}: {}", engine, e))?;
+    if !status.success() {
+        return Err(format!("{} export failed", engine));
+    }
+    read.map_err(|e| format!("reading the exported filesystem: {}", e))
+}
+
+/// Lint the tree in `source` from its root; `disabled` rules are turned off
+/// whatever the configuration says, and so are the rules reading what the
+/// configuration points to under trust policy untrusted.
+fn run_tree(
+    source: MemoryFileSystem,
+    mount_point: &Path,
+    args: &TreeArgs,
+    trust: Option<Trust>,
+    default_root: Option<&Path>,
+    disabled: &[&str],
+) -> ExitCode {
+    let mut lint_config = if let Some(config_path) = &args.config {
+        match LintConfig::from_file(config_path) {
+            Ok(cfg) => Some(cfg),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(2);
+            }
+        }
+    } else {
+        LintConfig::find_and_load(Path::new(".")).map(|(cfg, _)| cfg)
+    };
+    let mut disabled = disabled.to_vec();
+    if super::trust_policy(trust, lint_config.as_ref()) == TrustPolicy::Untrusted {
+        disabled.extend(UNTRUSTED_DISABLED_RULES);
+    }
+    if !disabled.is_empty() {
+        let config = lint_config.get_or_insert_with(LintConfig::default);
+        for name in disabled {
+            let rule = RuleConfig {
+                enabled: false,
+                ..RuleConfig::default()
+            };
+            config.rules.insert(name.to_string(), rule);
+        }
+    }
+
+    let root = match (&args.root, default_root) {
+        (Some(root), _) => mount_point.join(root),
+        (None, Some(root)) => root.to_path_buf(),
+        (None, None) => match find_root(&source) {
+            Some(root) => root,
+            None => {
+                eprintln!("Error: no nginx.conf found; choose the file to lint with --root");
+                return ExitCode::from(2);
+            }
+        },
+    };
+
+    let color_config = lint_config
+        .as_ref()
+        .map(|c| c.color.clone())
+        .unwrap_or_default();
+    let reporter =
+        Reporter::with_colors(args.format.into(), color_config).with_policy(args.exit.policy());
+
+    // Relative includes resolve against the directory of nginx.conf, as
+    // nginx's default prefix does
+    let mut options = LintOptions::new();
+    if let Some(config) = lint_config {
+        options = options.with_config(config);
+    }
+    if let Some(dir) = root.parent() {
+        options = options.with_include_prefix(dir);
+    }
+    let options = options.with_source(Arc::new(source));
+    let report = match lint_path(&root, &options) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", root.display(), e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut files = report.files;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let written = reporter.begin(false).and_then(|()| {
+        for file in &files {
+            reporter.report(
+                &file.errors,
+                &file.path,
+                Some(&file.content),
+                file.ignored_count,
+            )?;
+        }
+        reporter.finish(false)
+    });
+    match written {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+            eprintln!("Error writing report: {}", e);
+            return ExitCode::from(2);
+        }
+        _ => {}
+    }
+
+    let errors: Vec<_> = files.into_iter().flat_map(|f| f.errors).collect();
+    if reporter.fails(&errors) {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
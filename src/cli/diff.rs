@@ -0,0 +1,112 @@
+use super::parse::{raw_block_directives, read_input};
+use nginx_lint::diff::{Change, DirectiveText, diff_configs};
+use nginx_lint::parser::ast::Config;
+use nginx_lint::parser::line_index::LineIndex;
+use nginx_lint::parser::parse_string_with_errors_and_raw_blocks;
+use std::io::Write;
+use std::path::Path;
+use std::process::ExitCode;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum DiffFormat {
+    Text,
+    Json,
+}
+
+/// Compare `old` and `new` semantically and print the changed directives.
+///
+/// Exit codes follow diff(1): 0 when the files are equivalent, 1 when they
+/// differ, 2 when one cannot be read or has syntax errors.
+pub fn run_diff(
+    old: &Path,
+    new: &Path,
+    format: DiffFormat,
+    config_path: Option<&Path>,
+) -> ExitCode {
+    let raw_block_directives = match raw_block_directives(new, config_path) {
+        Ok(directives) => directives,
+        Err(code) => return code,
+    };
+    let parse = |file: &Path| -> Result<Config, ExitCode> {
+        let content = read_input(file)?;
+        let (config, syntax_errors) =
+            parse_string_with_errors_and_raw_blocks(&content, &raw_block_directives);
+        if syntax_errors.is_empty() {
+            return Ok(config);
+        }
+        let line_index = LineIndex::new(&content);
+        for error in &syntax_errors {
+            let pos = line_index.position(error.offset);
+            eprintln!(
+                "{}:{}:{}: {}",
+                file.display(),
+                pos.line,
+                pos.column,
+                error.message
+            );
+        }
+        Err(ExitCode::from(2))
+    };
+    let (old_config, new_config) = match (parse(old), parse(new)) {
+        (Ok(old_config), Ok(new_config)) => (old_config, new_config),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+
+    let changes = diff_configs(&old_config, &new_config);
+    let out = match format {
+        DiffFormat::Text => changes
+            .iter()
+            .map(|change| format!("{}  ({})\n", change, locations(change, old, new)))
+            .collect::<String>(),
+        DiffFormat::Json => {
+            let json: Vec<serde_json::Value> = changes
+                .iter()
+                .map(|change| {
+                    let side = |directive: &Option<DirectiveText>, file: &Path| {
+                        directive.as_ref().map(|d| {
+                            serde_json::json!({
+                                "file": file.to_string_lossy(),
+                                "line": d.line,
+                                "text": d.text,
+                            })
+                        })
+                    };
+                    serde_json::json!({
+                        "kind": change.kind.as_str(),
+                        "context": change.context,
+                        "old": side(&change.old, old),
+                        "new": side(&change.new, new),
+                    })
+                })
+                .collect();
+            format!("{}\n", serde_json::to_string_pretty(&json).unwrap())
+        }
+    };
+    // Large diffs are often piped to `head` or a pager
+    match std::io::stdout().write_all(out.as_bytes()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+            eprintln!("Error writing report: {}", e);
+            return ExitCode::from(2);
+        }
+        _ => {}
+    }
+
+    if changes.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+/// `old.conf:3, new.conf:4`, for the sides the change has
+fn locations(change: &Change, old: &Path, new: &Path) -> String {
+    [(&change.old, old), (&change.new, new)]
+        .into_iter()
+        .filter_map(|(directive, file)| {
+            directive
+                .as_ref()
+                .map(|d| format!("{}:{}", file.display(), d.line))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,103 @@
+use clap::Args;
+use nginx_lint::docgen::{DocFormat, check_examples, example_linter, render_pages};
+use nginx_lint::docs::RuleDocOwned;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct DocgenArgs {
+    /// Directory to write the pages into (created if missing)
+    #[arg(short, long, value_name = "DIR")]
+    pub output: PathBuf,
+
+    /// Page format
+    #[arg(long, value_enum, default_value = "markdown")]
+    pub format: PageFormat,
+
+    /// Directory containing WASM plugins to document too (requires plugins feature)
+    #[cfg(feature = "plugins")]
+    #[arg(long, value_name = "DIR")]
+    pub plugins: Option<PathBuf>,
+
+    /// Registry file listing WASM plugins to document too (requires plugins feature)
+    #[cfg(feature = "plugins")]
+    #[arg(long, value_name = "FILE")]
+    pub plugin_registry: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum PageFormat {
+    Markdown,
+    Html,
+}
+
+impl From<PageFormat> for DocFormat {
+    fn from(format: PageFormat) -> Self {
+        match format {
+            PageFormat::Markdown => DocFormat::Markdown,
+            PageFormat::Html => DocFormat::Html,
+        }
+    }
+}
+
+pub fn run_docgen(args: &DocgenArgs) -> ExitCode {
+    #[cfg(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins"))]
+    let mut docs = nginx_lint::docs::all_rule_docs_with_plugins();
+    #[cfg(not(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins")))]
+    let mut docs: Vec<RuleDocOwned> = nginx_lint::docs::all_rule_docs()
+        .iter()
+        .map(|doc| (*doc).into())
+        .collect();
+
+    #[cfg(feature = "plugins")]
+    let plugins = match super::rules::load_plugin_rules(
+        args.plugins.as_deref(),
+        args.plugin_registry.as_deref(),
+    ) {
+        Ok(plugins) => plugins,
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            return ExitCode::from(2);
+        }
+    };
+    #[cfg(not(feature = "plugins"))]
+    let plugins: Vec<Box<dyn nginx_lint::LintRule>> = Vec::new();
+    docs.extend(
+        plugins
+            .iter()
+            .map(|plugin| RuleDocOwned::from_plugin(plugin.as_ref())),
+    );
+
+    // Pages must not document examples that behave differently
+    let linter = example_linter(plugins);
+    let mut failed = false;
+    for doc in &docs {
+        for problem in check_examples(doc, &linter) {
+            failed = true;
+            eprintln!("{}: {}", doc.name, problem);
+        }
+    }
+    if failed {
+        eprintln!("Error: rule examples do not match their rules; no pages written");
+        return ExitCode::from(1);
+    }
+
+    if let Err(e) = std::fs::create_dir_all(&args.output) {
+        eprintln!("Error: {}: {}", args.output.display(), e);
+        return ExitCode::from(2);
+    }
+    let pages = render_pages(&docs, args.format.into());
+    for page in &pages {
+        let path = args.output.join(&page.file_name);
+        if let Err(e) = std::fs::write(&path, &page.content) {
+            eprintln!("Error: {}: {}", path.display(), e);
+            return ExitCode::from(2);
+        }
+    }
+    eprintln!(
+        "Wrote {} rule page(s) and an index to {}",
+        pages.len() - 1,
+        args.output.display()
+    );
+    ExitCode::SUCCESS
+}
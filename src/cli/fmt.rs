@@ -0,0 +1,75 @@
+use nginx_lint::LintConfig;
+use nginx_lint::parser::format::format_string;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Format files with the `[format]` style from `.nginx-lint.toml`.
+///
+/// Exit codes: 0 when everything is formatted, 1 when `check` finds a file
+/// that would change, 2 on read/parse/write errors.
+pub fn run_fmt(files: &[PathBuf], check: bool, config_path: Option<&Path>) -> ExitCode {
+    let lint_config = if let Some(config_path) = config_path {
+        match LintConfig::from_file(config_path) {
+            Ok(cfg) => Some(cfg),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(2);
+            }
+        }
+    } else {
+        let search_dir = files
+            .first()
+            .and_then(|p| p.parent())
+            .unwrap_or(Path::new("."));
+        LintConfig::find_and_load(search_dir).map(|(cfg, _)| cfg)
+    };
+    let style = lint_config
+        .as_ref()
+        .map(|c| c.format.style())
+        .unwrap_or_default();
+
+    let mut has_errors = false;
+    let mut unformatted = 0;
+
+    for path in files {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Error reading {}: {}", path.display(), e);
+                has_errors = true;
+                continue;
+            }
+        };
+        let formatted = match format_string(&content, &style) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                has_errors = true;
+                continue;
+            }
+        };
+        if formatted == content {
+            continue;
+        }
+
+        if check {
+            eprintln!("{}: would be reformatted", path.display());
+            unformatted += 1;
+        } else if let Err(e) = fs::write(path, &formatted) {
+            eprintln!("Error writing {}: {}", path.display(), e);
+            has_errors = true;
+        } else {
+            eprintln!("Formatted {}", path.display());
+        }
+    }
+
+    if has_errors {
+        ExitCode::from(2)
+    } else if unformatted > 0 {
+        eprintln!("\n{} file(s) would be reformatted", unformatted);
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
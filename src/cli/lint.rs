@@ -1,17 +1,54 @@
 use super::Cli;
+use super::archive::{TreeArgs, run_crossplane};
+use super::summary::Summary;
+use super::verify;
 use clap::CommandFactory;
 use colored::control;
+use nginx_lint::config::RuleConfig;
+use nginx_lint::lint_cache::{self, CachedResult, DependencyGraph, LintCache};
+use nginx_lint::parser::ast::Config;
+use nginx_lint::parser::context::IncludeContext;
+use nginx_lint::parser::source::SourceText;
+use nginx_lint::preset::Preset;
+use nginx_lint::semantic::SourceFile;
+use nginx_lint::template::{Normalized, TemplateMode, normalize};
 use nginx_lint::{
-    ColorMode, IncludedFile, LintConfig, LintError, Linter, Reporter, RuleProfile, Severity,
-    apply_fixes, apply_fixes_to_content_detailed, collect_included_files,
-    collect_included_files_with_context, parse_config, parse_string_with_errors,
-    syntax_errors_to_lint_errors,
+    ColorMode, Fix, IncludedFile, LintConfig, LintError, Linter, Reporter, RuleProfile, Severity,
+    TrustPolicy, apply_fixes_to_content_detailed, apply_fixes_to_file, collect_included_files,
+    collect_included_files_with_context, parse_config_with_raw_blocks, parse_context_comment,
+    sort_diagnostics, syntax_errors_to_lint_errors,
 };
 use rayon::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Rules not run under trust policy untrusted: they read files outside the
+/// configuration (precompressed-assets-not-served scans the document root,
+/// auth-basic-user-file-missing looks for password files) or run programs
+/// named in it (lua-block-lint, rego-policy)
+pub(super) const UNTRUSTED_DISABLED_RULES: &[&str] = &[
+    "precompressed-assets-not-served",
+    "auth-basic-user-file-missing",
+    "lua-block-lint",
+    "rego-policy",
+];
+
+/// Native rules that only check files on disk, turned on by --check-paths
+const CHECK_PATHS_RULES: &[&str] = &["auth-basic-user-file-missing"];
+
+/// Rules whose diagnostics depend on files other than the one they check;
+/// under --cache they run on every file instead of being cached
+const FILE_READING_RULES: &[&str] = &[
+    "include-path-exists",
+    "include-cycle",
+    "include-layering",
+    "precompressed-assets-not-served",
+    "auth-basic-user-file-missing",
+    "rego-policy",
+];
 
 /// Warn about fixes that were skipped due to invalid offsets (out of range
 /// or not on UTF-8 char boundaries), which indicates a buggy or misbehaving
@@ -26,6 +63,68 @@ fn warn_skipped_fixes(skipped: usize, path: &Path) {
     }
 }
 
+/// Which fixes `--fix` applies
+struct FixSelection<'a> {
+    /// Labels of the alternatives chosen with `--fix-alternative`
+    alternatives: &'a [String],
+    /// Whether fixes that are not machine-applicable are applied too
+    /// (`--fix-suggestions`)
+    suggestions: bool,
+}
+
+impl FixSelection<'_> {
+    /// The fixes to apply for `error`: those of the alternative chosen
+    /// with `--fix-alternative` where one is offered, otherwise the default
+    /// fixes, unless one of them is only a suggestion
+    fn fixes<'e>(&self, error: &'e LintError) -> &'e [Fix] {
+        if let Some(alternative) = error
+            .alternatives
+            .iter()
+            .find(|a| self.alternatives.contains(&a.label))
+        {
+            return &alternative.fixes;
+        }
+        if self.suggestions
+            || error
+                .fixes
+                .iter()
+                .all(|f| f.applicability.is_machine_applicable())
+        {
+            &error.fixes
+        } else {
+            &[]
+        }
+    }
+
+    /// `errors` with only the fixes to apply
+    fn select(&self, errors: &[LintError]) -> Vec<LintError> {
+        errors
+            .iter()
+            .map(|e| LintError {
+                fixes: self.fixes(e).to_vec(),
+                alternatives: Vec::new(),
+                ..e.clone()
+            })
+            .collect()
+    }
+
+    /// Tell the user about the suggested fixes in `errors` that were not
+    /// applied
+    fn warn_skipped_suggestions(&self, errors: &[LintError], path: &Path) {
+        let skipped = errors
+            .iter()
+            .filter(|e| !e.fixes.is_empty() && self.fixes(e).is_empty())
+            .count();
+        if skipped > 0 {
+            eprintln!(
+                "Skipped {} suggested fix(es) in {}; apply them with --fix-suggestions",
+                skipped,
+                path.display()
+            );
+        }
+    }
+}
+
 /// Extend `errors` with `additional`, dropping exact duplicates.
 ///
 /// Used to merge in rowan's own generic syntax errors (always tagged
@@ -64,12 +163,22 @@ fn resolve_against_config_dir(path: PathBuf, config_dir: Option<&Path>) -> PathB
 enum FileResult {
     LintErrors {
         path: PathBuf,
+        /// The content the error positions refer to, for reporters that
+        /// show source lines (kept as read, so mapped files are not copied)
+        content: SourceText,
         errors: Vec<LintError>,
         ignored_count: usize,
         profiles: Option<Vec<RuleProfile>>,
     },
 }
 
+impl FileResult {
+    fn path(&self) -> &Path {
+        let FileResult::LintErrors { path, .. } = self;
+        path
+    }
+}
+
 /// Check whether a rule name corresponds to a known builtin rule, regardless
 /// of whether it is currently registered on the linter. Used by `--rule-only`
 /// validation to distinguish "disabled in config" from "no such rule".
@@ -174,61 +283,79 @@ fn format_duration(d: std::time::Duration) -> String {
     }
 }
 
-/// Run linter on a parsed config and return a FileResult
+/// Run linter on a parsed config and return its errors, the number of
+/// ignored ones, and the rule profiles if `profile` is set
 fn run_lint_on_config(
     config: &nginx_lint::parser::ast::Config,
     path: &Path,
     content: &str,
     linter: &Linter,
     profile: bool,
-) -> FileResult {
+) -> (Vec<LintError>, usize, Option<Vec<RuleProfile>>) {
     if profile {
         let (errors, ignored_count, profiles) =
             linter.lint_with_content_and_profile(config, path, content);
-        FileResult::LintErrors {
-            path: path.to_path_buf(),
-            errors,
-            ignored_count,
-            profiles: Some(profiles),
-        }
+        (errors, ignored_count, Some(profiles))
     } else {
         let (errors, ignored_count) = linter.lint_with_content(config, path, content);
-        FileResult::LintErrors {
-            path: path.to_path_buf(),
-            errors,
-            ignored_count,
-            profiles: None,
-        }
+        (errors, ignored_count, None)
     }
 }
 
+/// Map the positions and fixes of `errors`, linted on the normalized text
+/// of a template, back to the template
+fn map_to_template(errors: &mut Vec<LintError>, normalized: &Normalized) {
+    *errors = std::mem::take(errors)
+        .into_iter()
+        .map(|error| normalized.map_error(error))
+        .collect();
+}
+
 /// Lint a single included file and return the result
-fn lint_file(included: &IncludedFile, linter: &Linter, profile: bool) -> FileResult {
+fn lint_file(
+    included: &IncludedFile,
+    linter: &Linter,
+    profile: bool,
+    template: Option<TemplateMode>,
+) -> FileResult {
     let path = &included.path;
 
-    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let content = SourceText::read(path).unwrap_or_else(|_| SourceText::from(String::new()));
+    // Under --template-mode, the placeholders are replaced before parsing
+    // (`included.config` was parsed from the same text)
+    let normalized = template.map(|mode| normalize(&content, mode));
+    let text = normalized.as_ref().map_or(&*content, |n| n.text());
 
     // Always parse with error recovery — rowan produces a usable AST even with errors
     let (config, syntax_errors) = if let Some(ref config) = included.config {
         (config.clone(), Vec::new())
     } else {
-        let (config, errors) = parse_string_with_errors(&content);
+        let (config, errors) = linter.parse(text);
         (config, errors)
     };
 
-    let mut result = run_lint_on_config(&config, path, &content, linter, profile);
+    let (mut errors, ignored_count, profiles) =
+        run_lint_on_config(&config, path, text, linter, profile);
 
     // Merge in rowan's own generic syntax errors, dropping exact duplicates
     // among themselves (see extend_errors_dedup's doc comment).
     if !syntax_errors.is_empty() {
-        let FileResult::LintErrors { ref mut errors, .. } = result;
         extend_errors_dedup(
-            errors,
-            syntax_errors_to_lint_errors(&syntax_errors, &content),
+            &mut errors,
+            syntax_errors_to_lint_errors(&syntax_errors, text),
         );
     }
 
-    result
+    if let Some(normalized) = &normalized {
+        map_to_template(&mut errors, normalized);
+    }
+    FileResult::LintErrors {
+        path: path.clone(),
+        content,
+        errors,
+        ignored_count,
+        profiles,
+    }
 }
 
 /// Lint a file, apply autofixes, and re-lint the fixed content.
@@ -238,30 +365,41 @@ fn lint_file(included: &IncludedFile, linter: &Linter, profile: bool) -> FileRes
 /// in the written file: positions are computed against the rewritten
 /// content, and problems left behind by fixes that failed to apply or were
 /// skipped stay visible.
-fn fix_file(inc: &IncludedFile, linter: &Linter, profile: bool) -> FileResult {
+fn fix_file(
+    inc: &IncludedFile,
+    linter: &Linter,
+    profile: bool,
+    selection: &FixSelection<'_>,
+    template: Option<TemplateMode>,
+) -> FileResult {
     let FileResult::LintErrors {
         path,
+        content,
         errors,
         ignored_count,
         profiles,
-    } = lint_file(inc, linter, profile);
+    } = lint_file(inc, linter, profile, template);
 
-    if errors.iter().all(|e| e.fixes.is_empty()) {
+    selection.warn_skipped_suggestions(&errors, &path);
+    let fixing = selection.select(&errors);
+    if fixing.iter().all(|e| e.fixes.is_empty()) {
         return FileResult::LintErrors {
             path,
+            content,
             errors,
             ignored_count,
             profiles,
         };
     }
 
-    match apply_fixes(&path, &errors) {
+    match apply_fixes_to_file(&path, &fixing) {
         Ok(result) => {
             warn_skipped_fixes(result.skipped_invalid, &path);
             if result.applied == 0 {
                 // Nothing was written: the original lint results still hold.
                 return FileResult::LintErrors {
                     path,
+                    content,
                     errors,
                     ignored_count,
                     profiles,
@@ -269,6 +407,7 @@ fn fix_file(inc: &IncludedFile, linter: &Linter, profile: bool) -> FileResult {
             }
             eprintln!("Applied {} fix(es) to {}", result.applied, path.display());
             let FileResult::LintErrors {
+                content: remaining_content,
                 errors: remaining,
                 ignored_count: remaining_ignored,
                 ..
@@ -278,9 +417,11 @@ fn fix_file(inc: &IncludedFile, linter: &Linter, profile: bool) -> FileResult {
                 linter,
                 false,
                 inc.include_context.clone(),
+                template,
             );
             FileResult::LintErrors {
                 path,
+                content: remaining_content,
                 errors: remaining,
                 ignored_count: remaining_ignored,
                 profiles,
@@ -291,6 +432,7 @@ fn fix_file(inc: &IncludedFile, linter: &Linter, profile: bool) -> FileResult {
             // Nothing was written: every error still stands.
             FileResult::LintErrors {
                 path,
+                content,
                 errors,
                 ignored_count,
                 profiles,
@@ -301,45 +443,586 @@ fn fix_file(inc: &IncludedFile, linter: &Linter, profile: bool) -> FileResult {
 
 /// Apply autofixes to stdin content, print the result to stdout, and
 /// re-lint the fixed content for the stderr report and the exit code.
+///
+/// Also returns the final content so later passes see what was printed.
 fn fix_stdin(
     result: FileResult,
-    content: &str,
     linter: &Linter,
-    initial_context: Vec<String>,
-) -> FileResult {
+    initial_context: IncludeContext,
+    selection: &FixSelection<'_>,
+    template: Option<TemplateMode>,
+) -> (FileResult, String) {
     let FileResult::LintErrors {
         path,
+        content,
         errors,
         ignored_count,
         profiles,
     } = result;
 
-    let fixes: Vec<_> = errors.iter().flat_map(|e| e.fixes.iter()).collect();
-    let apply_result = apply_fixes_to_content_detailed(content, &fixes);
+    let fixes: Vec<_> = errors.iter().flat_map(|e| selection.fixes(e)).collect();
+    selection.warn_skipped_suggestions(&errors, &path);
+    let apply_result = apply_fixes_to_content_detailed(&content, &fixes);
     warn_skipped_fixes(apply_result.skipped_invalid, &path);
 
     if apply_result.applied == 0 {
         // Nothing was fixed: echo the input and keep the original results.
-        print!("{}", content);
-        return FileResult::LintErrors {
-            path,
-            errors,
-            ignored_count,
-            profiles,
-        };
+        print!("{}", &*content);
+        let fixed = content.to_string();
+        return (
+            FileResult::LintErrors {
+                path,
+                content,
+                errors,
+                ignored_count,
+                profiles,
+            },
+            fixed,
+        );
     }
 
     print!("{}", apply_result.content);
     let FileResult::LintErrors {
+        content: remaining_content,
         errors: remaining,
         ignored_count: remaining_ignored,
         ..
-    } = lint_content(&apply_result.content, &path, linter, false, initial_context);
-    FileResult::LintErrors {
-        path,
-        errors: remaining,
-        ignored_count: remaining_ignored,
-        profiles,
+    } = lint_content(
+        &apply_result.content,
+        &path,
+        linter,
+        false,
+        initial_context,
+        template,
+    );
+    (
+        FileResult::LintErrors {
+            path,
+            content: remaining_content,
+            errors: remaining,
+            ignored_count: remaining_ignored,
+            profiles,
+        },
+        apply_result.content,
+    )
+}
+
+/// Run the cross-file pass over the linted files, mapping the positions
+/// back to the templates under `--template-mode`.
+///
+/// Callers re-read the files from disk so that, under `--fix`, positions
+/// refer to the content that was actually written.
+fn cross_file_results(
+    linter: &Linter,
+    files: &[(PathBuf, SourceText, IncludeContext)],
+    template: Option<TemplateMode>,
+) -> HashMap<PathBuf, (Vec<LintError>, usize)> {
+    CrossFilePass::parse(linter, files, template).run(linter)
+}
+
+/// The files of a run parsed for the cross-file pass
+struct CrossFilePass<'a> {
+    files: &'a [(PathBuf, SourceText, IncludeContext)],
+    normalized: Vec<Option<Normalized>>,
+    configs: Vec<Config>,
+}
+
+impl<'a> CrossFilePass<'a> {
+    fn parse(
+        linter: &Linter,
+        files: &'a [(PathBuf, SourceText, IncludeContext)],
+        template: Option<TemplateMode>,
+    ) -> Self {
+        let normalized: Vec<Option<Normalized>> = files
+            .iter()
+            .map(|(_, content, _)| template.map(|mode| normalize(content, mode)))
+            .collect();
+        let configs = files
+            .iter()
+            .zip(&normalized)
+            .map(|((_, content, include_context), normalized)| {
+                let text = normalized.as_ref().map_or(&**content, |n| n.text());
+                let (mut config, _) = linter.parse(text);
+                config.include_context = include_context.clone();
+                config
+            })
+            .collect();
+        Self {
+            files,
+            normalized,
+            configs,
+        }
+    }
+
+    /// The files as the cross-file rules see them
+    fn sources(&self) -> Vec<SourceFile<'_>> {
+        self.files
+            .iter()
+            .zip(&self.configs)
+            .zip(&self.normalized)
+            .map(|(((path, content, _), config), normalized)| SourceFile {
+                path,
+                config,
+                content: normalized.as_ref().map_or(&**content, |n| n.text()),
+            })
+            .collect()
+    }
+
+    fn run(&self, linter: &Linter) -> HashMap<PathBuf, (Vec<LintError>, usize)> {
+        let mut cross_file = linter.lint_cross_file(&self.sources());
+        for ((path, _, _), normalized) in self.files.iter().zip(&self.normalized) {
+            if let (Some((errors, _)), Some(normalized)) = (cross_file.get_mut(path), normalized) {
+                *errors = std::mem::take(errors)
+                    .into_iter()
+                    .map(|error| normalized.map_error(error))
+                    .collect();
+            }
+        }
+        cross_file
+    }
+}
+
+/// Merge the errors of the cross-file pass into the per-file results
+fn merge_cross_file_results(
+    results: &mut [FileResult],
+    mut cross_file: HashMap<PathBuf, (Vec<LintError>, usize)>,
+) {
+    for result in results {
+        let FileResult::LintErrors {
+            path,
+            errors,
+            ignored_count,
+            ..
+        } = result;
+        if let Some((extra, extra_ignored)) = cross_file.remove(path.as_path()) {
+            errors.extend(extra);
+            *ignored_count += extra_ignored;
+        }
+    }
+}
+
+/// `--cache`: the results of files unchanged since an earlier run come
+/// from a [`LintCache`]. The rules reading other files (the
+/// [`FILE_READING_RULES`] and those of --check-paths) are moved to a linter
+/// of their own that runs on every file, so a changed include or
+/// certificate is noticed even when the file naming it did not change.
+/// Unused ignore comments for those rules are not reported.
+struct ResultCache {
+    cache: LintCache,
+    file_rules: Linter,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    /// What became of the cross-file results, once the pass ran
+    cross_file: Option<CrossFileRun>,
+}
+
+enum CrossFileRun {
+    Reused,
+    /// Run again; `changed` are the files whose dependencies changed
+    /// (none on the first run or when only templates changed)
+    Recomputed {
+        changed: BTreeSet<PathBuf>,
+    },
+}
+
+impl ResultCache {
+    fn new(cache: LintCache, file_rules: Linter) -> Self {
+        Self {
+            cache,
+            file_rules,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            cross_file: None,
+        }
+    }
+
+    /// Lint `files` in parallel, taking the results of unchanged files from
+    /// the cache and storing the others
+    fn lint_files(
+        &mut self,
+        files: &[IncludedFile],
+        linter: &Linter,
+        template: Option<TemplateMode>,
+    ) -> Vec<FileResult> {
+        let linted: Vec<(FileResult, Option<(String, CachedResult)>)> = files
+            .par_iter()
+            .map(|inc| self.lint_file(inc, linter, template))
+            .collect();
+
+        let mut results = Vec::with_capacity(linted.len());
+        for (result, fresh) in linted {
+            if let Some((key, cached)) = fresh {
+                self.cache.insert(result.path(), key, cached);
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    /// Lint one file, returning the key and result to cache if it was not
+    /// cached yet
+    fn lint_file(
+        &self,
+        inc: &IncludedFile,
+        linter: &Linter,
+        template: Option<TemplateMode>,
+    ) -> (FileResult, Option<(String, CachedResult)>) {
+        let content =
+            SourceText::read(&inc.path).unwrap_or_else(|_| SourceText::from(String::new()));
+        let key = LintCache::file_key(&content, &inc.include_context);
+
+        let (mut result, fresh) = match self.cache.get(&inc.path, &key) {
+            Some(cached) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                let result = FileResult::LintErrors {
+                    path: inc.path.clone(),
+                    content,
+                    errors: cached.errors.clone(),
+                    ignored_count: cached.ignored_count,
+                    profiles: None,
+                };
+                (result, None)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                let result = lint_file(inc, linter, false, template);
+                let FileResult::LintErrors {
+                    errors,
+                    ignored_count,
+                    ..
+                } = &result;
+                let cached = CachedResult {
+                    errors: errors.clone(),
+                    ignored_count: *ignored_count,
+                };
+                (result, Some((key, cached)))
+            }
+        };
+
+        let names = self.file_rules.rule_names();
+        if !names.is_empty() {
+            let FileResult::LintErrors {
+                errors: extra,
+                ignored_count: extra_ignored,
+                ..
+            } = lint_file(inc, &self.file_rules, false, template);
+            let FileResult::LintErrors {
+                errors,
+                ignored_count,
+                ..
+            } = &mut result;
+            // Ignore-comment warnings are the main linter's to report
+            errors.extend(extra.into_iter().filter(|e| names.contains(&e.rule)));
+            *ignored_count += extra_ignored;
+        }
+        (result, fresh)
+    }
+
+    /// The cross-file results, from the cache when the
+    /// [`DependencyGraph`] of the run is the one they were stored for
+    fn cross_file_results(
+        &mut self,
+        linter: &Linter,
+        files: &[(PathBuf, SourceText, IncludeContext)],
+        template: Option<TemplateMode>,
+    ) -> HashMap<PathBuf, (Vec<LintError>, usize)> {
+        let pass = CrossFilePass::parse(linter, files, template);
+        let mut graph = DependencyGraph::build(&pass.sources());
+        if template.is_some() {
+            // Positions are mapped back through the templates, which the
+            // symbols do not cover
+            graph = graph.with_contents(files.iter().map(|(_, content, _)| &**content));
+        }
+        if let Some(cached) = self.cache.cross_file(&graph) {
+            self.cross_file = Some(CrossFileRun::Reused);
+            return cached
+                .iter()
+                .map(|(path, result)| (path.clone(), (result.errors.clone(), result.ignored_count)))
+                .collect();
+        }
+
+        let changed = self
+            .cache
+            .cross_file_graph()
+            .map(|previous| graph.changed_files(previous))
+            .unwrap_or_default();
+        self.cross_file = Some(CrossFileRun::Recomputed { changed });
+        let results = pass.run(linter);
+        let cached = results
+            .iter()
+            .map(|(path, (errors, ignored_count))| {
+                let result = CachedResult {
+                    errors: errors.clone(),
+                    ignored_count: *ignored_count,
+                };
+                (path.clone(), result)
+            })
+            .collect();
+        self.cache.set_cross_file(graph, cached);
+        results
+    }
+
+    fn save(&self, verbose: bool) {
+        if let Err(e) = self.cache.save() {
+            eprintln!(
+                "Warning: failed to write lint cache {}: {}",
+                self.cache.path().display(),
+                e
+            );
+        }
+        if verbose {
+            eprintln!(
+                "Lint cache: {} ({} hit(s), {} miss(es))",
+                self.cache.path().display(),
+                self.hits.load(Ordering::Relaxed),
+                self.misses.load(Ordering::Relaxed)
+            );
+            match &self.cross_file {
+                Some(CrossFileRun::Reused) => eprintln!("Lint cache: cross-file results reused"),
+                Some(CrossFileRun::Recomputed { changed }) if !changed.is_empty() => {
+                    let changed: Vec<String> =
+                        changed.iter().map(|p| p.display().to_string()).collect();
+                    eprintln!(
+                        "Lint cache: cross-file rules run again, dependencies changed in {}",
+                        changed.join(", ")
+                    );
+                }
+                Some(CrossFileRun::Recomputed { .. }) => {
+                    eprintln!("Lint cache: cross-file rules run again")
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// Move the rules reading other files out of `linter` into a linter of
+/// their own, together with the --check-paths rules, for [`ResultCache`].
+///
+/// Each linter treats the other's rules as inactive, so ignore comments
+/// naming them stay valid.
+fn split_file_rules(
+    linter: &mut Linter,
+    path_rules: Vec<Box<dyn nginx_lint::LintRule>>,
+    config: Option<&LintConfig>,
+    include_prefix: Option<&Path>,
+) -> Linter {
+    let registered = linter.rule_names();
+    let moved: HashSet<String> = FILE_READING_RULES
+        .iter()
+        .filter(|name| registered.contains(**name))
+        .map(|name| name.to_string())
+        .collect();
+    linter.remove_rules_by_name(|name| moved.contains(name));
+
+    let mut file_rules = Linter::with_config_and_rule_only(config, include_prefix, Some(&moved));
+    for rule in path_rules {
+        file_rules.add_rule(rule);
+    }
+
+    let own = file_rules.rule_names();
+    let mut inactive = file_rules.inactive_rule_names().clone();
+    inactive.extend(
+        linter
+            .rule_names()
+            .into_iter()
+            .chain(linter.inactive_rule_names().iter().cloned())
+            .filter(|name| !own.contains(name)),
+    );
+    file_rules.set_inactive_rules(inactive);
+
+    let mut inactive = linter.inactive_rule_names().clone();
+    inactive.extend(moved);
+    linter.set_inactive_rules(inactive);
+    file_rules
+}
+
+/// Where the --cache results of runs from the current directory are kept
+/// under the cache root
+fn lint_cache_file(root: &Path) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let name = lint_cache::digest([cwd.to_string_lossy().as_bytes()]);
+    nginx_lint::cache::lint_cache_dir(root).join(format!("{}.json", &name[..16]))
+}
+
+/// What the --cache results depend on besides the linted files: the
+/// version, the configuration, the options changing what rules report,
+/// the rules that run and the plugin files they were loaded from
+fn cache_fingerprint(
+    cli: &Cli,
+    config_file: Option<&Path>,
+    linter: &Linter,
+    path_rules: &[Box<dyn nginx_lint::LintRule>],
+) -> String {
+    let mut rules: Vec<String> = linter
+        .rule_names()
+        .into_iter()
+        .chain(
+            path_rules
+                .iter()
+                .map(|rule| format!("{} (path)", rule.name())),
+        )
+        .collect();
+    rules.sort();
+    let mut rule_only = cli.rule_only.clone();
+    rule_only.sort();
+
+    #[allow(unused_mut)]
+    let mut parts = vec![
+        env!("CARGO_PKG_VERSION").to_string(),
+        config_file
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default(),
+        format!(
+            "preset={:?} context={:?} prefix={:?} template={:?} rule-only={:?}",
+            cli.preset.map(|p| Preset::from(p).as_str()),
+            cli.context,
+            cli.prefix,
+            cli.template_mode.map(|t| TemplateMode::from(t).as_str()),
+            rule_only
+        ),
+        rules.join(","),
+    ];
+
+    // Plugins keep their rule names across versions; stamp their files
+    #[cfg(feature = "plugins")]
+    {
+        let mut plugin_paths: Vec<PathBuf> = nginx_lint::plugin::default_plugin_dir()
+            .into_iter()
+            .chain(cli.plugins.clone())
+            .chain(cli.plugin_registry.clone())
+            .collect();
+        if let Some(registry) = &cli.plugin_registry {
+            plugin_paths.extend(nginx_lint::plugin::read_registry(registry).unwrap_or_default());
+        }
+        for path in plugin_paths {
+            parts.extend(file_stamps(&path));
+        }
+    }
+
+    lint_cache::digest(parts)
+}
+
+/// Path, size and modification time of the file at `path`, or of every
+/// file below the directory at `path`
+#[cfg(feature = "plugins")]
+fn file_stamps(path: &Path) -> Vec<String> {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return Vec::new();
+    };
+    if metadata.is_dir() {
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        entries.sort();
+        entries
+            .iter()
+            .flat_map(|entry| file_stamps(entry))
+            .collect()
+    } else {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        vec![format!(
+            "{} {} {}",
+            path.display(),
+            metadata.len(),
+            modified.as_nanos()
+        )]
+    }
+}
+
+/// Run `nginx -t` on each root file once the static checks report no
+/// errors, and merge what nginx reports into the results.
+///
+/// The root file's directory, or `include_prefix` when it contains the root
+/// file, is mounted into the container. Messages about files that were not
+/// linted (e.g. the image's own `mime.types`) are reported on the root file
+/// with nginx's location kept in the message.
+fn verify_with_nginx(
+    results: &mut Vec<FileResult>,
+    image: &str,
+    roots: &[PathBuf],
+    include_prefix: Option<&Path>,
+    verbose: bool,
+) -> Result<(), String> {
+    let has_errors = results.iter().any(|result| {
+        let FileResult::LintErrors { errors, .. } = result;
+        errors.iter().any(|e| e.severity == Severity::Error)
+    });
+    if has_errors {
+        if verbose {
+            eprintln!("Skipping nginx -t: the static checks reported errors");
+        }
+        return Ok(());
+    }
+
+    let prefix = include_prefix.and_then(|p| p.canonicalize().ok());
+    let mut canonical: Vec<Option<PathBuf>> = results
+        .iter()
+        .map(|result| result.path().canonicalize().ok())
+        .collect();
+    for root in roots {
+        let root = root
+            .canonicalize()
+            .map_err(|e| format!("{}: {}", root.display(), e))?;
+        let mount = match &prefix {
+            Some(prefix) if root.starts_with(prefix) => prefix.clone(),
+            _ => root.parent().unwrap_or(Path::new("/")).to_path_buf(),
+        };
+        if verbose {
+            eprintln!(
+                "Running nginx -t in {} (mounting {})",
+                image,
+                mount.display()
+            );
+        }
+        let messages = verify::nginx_test(image, &root, &mount)?;
+
+        let root_index = match canonical.iter().position(|p| p.as_ref() == Some(&root)) {
+            Some(index) => index,
+            None => {
+                results.push(FileResult::LintErrors {
+                    path: root.clone(),
+                    content: SourceText::read(&root)
+                        .unwrap_or_else(|_| SourceText::from(String::new())),
+                    errors: Vec::new(),
+                    ignored_count: 0,
+                    profiles: None,
+                });
+                canonical.push(Some(root.clone()));
+                results.len() - 1
+            }
+        };
+        for verify::NginxMessage { path, mut error } in messages {
+            let index = match &path {
+                Some(path) => canonical.iter().position(|p| p.as_ref() == Some(path)),
+                None => Some(root_index),
+            };
+            let index = index.unwrap_or_else(|| {
+                if let (Some(path), Some(line)) = (&path, error.line.take()) {
+                    error.message = format!("{} in {}:{}", error.message, path.display(), line);
+                }
+                root_index
+            });
+            let FileResult::LintErrors { errors, .. } = &mut results[index];
+            errors.push(error);
+        }
+    }
+    Ok(())
+}
+
+/// Handle an error writing the report: once the output consumer closes the
+/// stream, reporting stops; any other error fails the run.
+fn report_write_failed(e: std::io::Error, output_closed: &mut bool) -> Option<ExitCode> {
+    if e.kind() == std::io::ErrorKind::BrokenPipe {
+        *output_closed = true;
+        None
+    } else {
+        eprintln!("Error writing report: {}", e);
+        Some(ExitCode::from(2))
     }
 }
 
@@ -350,10 +1033,10 @@ fn fix_stdin(
 /// in stdin mode the report goes to stderr because stdout carries the fixed
 /// content.
 fn process_results(
-    results: Vec<FileResult>,
+    mut results: Vec<FileResult>,
     fix: bool,
-    no_fail_on_warnings: bool,
     profile: bool,
+    mut summary: Option<Summary>,
     reporter: &Reporter,
     stdin_mode: bool,
 ) -> ExitCode {
@@ -363,53 +1046,71 @@ fn process_results(
     // stop reporting but still compute the exit code.
     let mut output_closed = false;
 
+    // Report files in path order so output does not depend on include
+    // order or on which file finished linting first.
+    results.sort_by(|a, b| a.path().cmp(b.path()));
+
+    // stdout carries the fixed content in stdin mode, so report to stderr
+    let to_stderr = fix && stdin_mode;
+    if let Err(e) = reporter.begin(to_stderr)
+        && let Some(code) = report_write_failed(e, &mut output_closed)
+    {
+        return code;
+    }
+
     for result in results {
         let FileResult::LintErrors {
             path,
-            errors,
+            content,
+            mut errors,
             ignored_count,
             profiles,
         } = result;
+        sort_diagnostics(&mut errors);
 
         let report_result = if output_closed {
             Ok(())
-        } else if fix && stdin_mode {
-            // stdout carries the fixed content, so report to stderr
+        } else if to_stderr {
             if !errors.is_empty() || ignored_count > 0 {
-                reporter.report_to_stderr(&errors, &path, ignored_count)
+                reporter.report_to_stderr(&errors, &path, Some(&content), ignored_count)
             } else {
                 Ok(())
             }
         } else {
-            reporter.report(&errors, &path, ignored_count)
+            reporter.report(&errors, &path, Some(&content), ignored_count)
         };
-        if let Err(e) = report_result {
-            if e.kind() == std::io::ErrorKind::BrokenPipe {
-                output_closed = true;
-            } else {
-                eprintln!("Error writing report: {}", e);
-                return ExitCode::from(2);
-            }
+        if let Err(e) = report_result
+            && let Some(code) = report_write_failed(e, &mut output_closed)
+        {
+            return code;
         }
 
+        if let Some(summary) = &mut summary {
+            summary.add_file(&path, &errors);
+        }
         all_errors.extend(errors);
         if let Some(p) = profiles {
             all_profiles.extend(p);
         }
     }
 
+    if !output_closed
+        && let Err(e) = reporter.finish(to_stderr)
+        && let Some(code) = report_write_failed(e, &mut output_closed)
+    {
+        return code;
+    }
+
     // Display profile results if requested
     if profile && !all_profiles.is_empty() {
         display_profile(&all_profiles);
     }
 
-    let has_issues = if no_fail_on_warnings {
-        all_errors.iter().any(|e| e.severity == Severity::Error)
-    } else {
-        !all_errors.is_empty()
-    };
+    if let Some(summary) = &summary {
+        summary.display();
+    }
 
-    if has_issues {
+    if reporter.fails(&all_errors) {
         ExitCode::from(1)
     } else {
         ExitCode::SUCCESS
@@ -422,32 +1123,55 @@ fn lint_content(
     path: &Path,
     linter: &Linter,
     profile: bool,
-    initial_context: Vec<String>,
+    initial_context: IncludeContext,
+    template: Option<TemplateMode>,
 ) -> FileResult {
+    let normalized = template.map(|mode| normalize(content, mode));
+    let text = normalized.as_ref().map_or(content, |n| n.text());
+
     // Parse the content (always produces AST, even with syntax errors)
-    let (mut parse_result, syntax_errors) = parse_string_with_errors(content);
+    let (mut parse_result, syntax_errors) = linter.parse(text);
 
     // Set context if specified
     if !initial_context.is_empty() {
         parse_result.include_context = initial_context;
     }
 
-    let mut result = run_lint_on_config(&parse_result, path, content, linter, profile);
+    let (mut errors, ignored_count, profiles) =
+        run_lint_on_config(&parse_result, path, text, linter, profile);
 
     // Merge in rowan's own generic syntax errors, dropping exact duplicates
     // (see lint_file)
     if !syntax_errors.is_empty() {
-        let FileResult::LintErrors { ref mut errors, .. } = result;
         extend_errors_dedup(
-            errors,
-            syntax_errors_to_lint_errors(&syntax_errors, content),
+            &mut errors,
+            syntax_errors_to_lint_errors(&syntax_errors, text),
         );
     }
 
-    result
+    if let Some(normalized) = &normalized {
+        map_to_template(&mut errors, normalized);
+    }
+    FileResult::LintErrors {
+        path: path.to_path_buf(),
+        content: SourceText::from(content.to_string()),
+        errors,
+        ignored_count,
+        profiles,
+    }
 }
 
 pub fn run_lint(cli: Cli) -> ExitCode {
+    if let Some(dump) = &cli.from_crossplane {
+        let args = TreeArgs {
+            root: None,
+            config: cli.config.clone(),
+            format: cli.format,
+            exit: cli.exit,
+        };
+        return run_crossplane(dump, &args, cli.trust_policy);
+    }
+
     // 1. Detect stdin mode and read content if applicable
     let stdin_mode = cli.files.len() == 1 && cli.files[0].as_os_str() == "-";
     let stdin_content = if stdin_mode {
@@ -490,15 +1214,19 @@ pub fn run_lint(cli: Cli) -> ExitCode {
         Vec::new()
     };
 
+    if stdin_mode && cli.verify_with_nginx.is_some() {
+        eprintln!("Error: --verify-with-nginx cannot be used with stdin");
+        return ExitCode::from(2);
+    }
+
     // 3. Load configuration
-    let (lint_config, config_dir) = if let Some(config_path) = &cli.config {
+    let (lint_config, config_file) = if let Some(config_path) = &cli.config {
         match LintConfig::from_file(config_path) {
             Ok(cfg) => {
                 if cli.verbose {
                     eprintln!("Using config: {}", config_path.display());
                 }
-                let dir = config_path.parent().map(|p| p.to_path_buf());
-                (Some(cfg), dir)
+                (Some(cfg), Some(config_path.clone()))
             }
             Err(e) => {
                 eprintln!("Error: {}", e);
@@ -519,12 +1247,86 @@ pub fn run_lint(cli: Cli) -> ExitCode {
                 if cli.verbose {
                     eprintln!("Found .nginx-lint.toml: {}", config_path.display());
                 }
-                let dir = config_path.parent().map(|p| p.to_path_buf());
-                (Some(cfg), dir)
+                (Some(cfg), Some(config_path))
             }
             None => (None, None),
         }
     };
+    let config_dir = config_file
+        .as_ref()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf);
+
+    // --preset turns rules on and off, except those the config sets up
+    let preset = cli.preset.map(Preset::from);
+    let lint_config = match preset {
+        Some(preset) => {
+            let mut config = lint_config.unwrap_or_default();
+            preset.apply(&mut config);
+            if cli.verbose {
+                eprintln!("Using preset: {}", preset.as_str());
+            }
+            Some(config)
+        }
+        None => lint_config,
+    };
+
+    // --check-paths turns on the native file checks, except those the config
+    // sets up
+    let lint_config = if cli.check_paths {
+        let mut config = lint_config.unwrap_or_default();
+        for name in CHECK_PATHS_RULES {
+            config
+                .rules
+                .entry(name.to_string())
+                .or_insert_with(|| RuleConfig {
+                    enabled: true,
+                    ..RuleConfig::default()
+                });
+        }
+        Some(config)
+    } else {
+        lint_config
+    };
+
+    // The stricter of --trust-policy and the config's trust_policy. Under
+    // "untrusted", options that would read or load more than the
+    // configuration are refused rather than silently ignored.
+    let trust_policy = super::trust_policy(cli.trust_policy, lint_config.as_ref());
+    if trust_policy == TrustPolicy::Untrusted {
+        let mut refused = Vec::new();
+        #[cfg(feature = "plugins")]
+        {
+            if cli.plugins.is_some() {
+                refused.push("--plugins");
+            }
+            if cli.plugin_registry.is_some() {
+                refused.push("--plugin-registry");
+            }
+        }
+        if cli.cache {
+            refused.push("--cache");
+        }
+        if cli.cache_dir.is_some() {
+            refused.push("--cache-dir");
+        }
+        if cli.check_paths {
+            refused.push("--check-paths");
+        }
+        if cli.verify_with_nginx.is_some() {
+            refused.push("--verify-with-nginx");
+        }
+        if !refused.is_empty() {
+            eprintln!(
+                "Error: {} cannot be used with trust policy untrusted",
+                refused.join(", ")
+            );
+            return ExitCode::from(2);
+        }
+    }
+    if cli.verbose {
+        eprintln!("Trust policy: {}", trust_policy.as_str());
+    }
 
     // 4. Configure color output (CLI flags take precedence over config)
     if cli.color {
@@ -544,17 +1346,26 @@ pub fn run_lint(cli: Cli) -> ExitCode {
         .as_ref()
         .map(|c| c.color.clone())
         .unwrap_or_default();
-    let reporter = Reporter::with_colors(cli.format.into(), color_config);
-
-    // 6. Parse context option if specified (comma-separated list of block names)
-    let initial_context: Vec<String> = cli
-        .context
-        .as_ref()
-        .map(|s| s.split(',').map(|c| c.trim().to_string()).collect())
-        .unwrap_or_default();
+    let reporter =
+        Reporter::with_colors(cli.format.into(), color_config).with_policy(cli.exit.policy());
+
+    // 6. Parse context option if specified (comma-separated list of blocks,
+    //    each optionally carrying its arguments, e.g. "location=/api/"). A
+    //    preset also accepts its snippet names and has a default context.
+    let context = match (preset, cli.context.as_deref()) {
+        (Some(preset), Some(context)) => Some(preset.resolve_context(context)),
+        (Some(preset), None) => preset.default_context(),
+        (None, context) => context,
+    };
+    let initial_context = context.map(IncludeContext::parse).unwrap_or_default();
 
     if cli.verbose && !initial_context.is_empty() {
-        eprintln!("Using context: {}", initial_context.join(" > "));
+        let frames: Vec<String> = initial_context
+            .frames()
+            .iter()
+            .map(|frame| frame.to_string())
+            .collect();
+        eprintln!("Using context: {}", frames.join(" > "));
     }
 
     // 7. Resolve include prefix (CLI --prefix takes precedence over config)
@@ -588,7 +1399,9 @@ pub fn run_lint(cli: Cli) -> ExitCode {
     let compilation_cache = {
         use nginx_lint::plugin::CompilationCache;
 
-        if cli.no_cache {
+        // A cache shared between runs could carry artifacts from one
+        // untrusted configuration's run into another's
+        if cli.no_cache || trust_policy == TrustPolicy::Untrusted {
             CompilationCache::Disabled
         } else if let Some(ref cache_dir) = cli.cache_dir {
             CompilationCache::Directory(cache_dir.clone())
@@ -605,9 +1418,9 @@ pub fn run_lint(cli: Cli) -> ExitCode {
     // In builds without the plugins feature the cache is never used; tell the
     // user instead of silently ignoring their configuration.
     #[cfg(not(feature = "plugins"))]
-    if lint_config.as_ref().and_then(|c| c.cache_dir()).is_some() {
+    if !cli.cache && lint_config.as_ref().and_then(|c| c.cache_dir()).is_some() {
         eprintln!(
-            "Warning: cache_dir in the configuration file has no effect in this build (compiled without the plugins feature)"
+            "Warning: cache_dir in the configuration file has no effect without --cache in this build (compiled without the plugins feature)"
         );
     }
 
@@ -642,48 +1455,90 @@ pub fn run_lint(cli: Cli) -> ExitCode {
         }
     }
 
-    // Load custom plugins if specified
+    // Load custom plugins: the installed plugins (`nginx-lint plugins
+    // install`), then the --plugins directory and the --plugin-registry
+    // entries
+    #[cfg(feature = "plugins")]
+    let installed_dir = nginx_lint::plugin::default_plugin_dir()
+        .filter(|dir| dir.is_dir() && trust_policy == TrustPolicy::Trusted);
     #[cfg(feature = "plugins")]
-    if let Some(ref plugins_dir) = cli.plugins {
+    if installed_dir.is_some() || cli.plugins.is_some() || cli.plugin_registry.is_some() {
         use nginx_lint::plugin::PluginLoader;
 
-        match PluginLoader::new_with_cache(compilation_cache) {
-            Ok(loader) => match loader.load_plugins(plugins_dir) {
-                Ok(plugins) => {
-                    if cli.verbose {
-                        eprintln!(
-                            "Loaded {} plugin(s) from {}",
-                            plugins.len(),
-                            plugins_dir.display()
-                        );
-                        if let (Some(cache_dir), Some((hits, misses))) =
-                            (loader.cache_directory(), loader.cache_stats())
-                        {
-                            eprintln!(
-                                "Plugin compilation cache: {} ({} hit(s), {} miss(es))",
-                                cache_dir.display(),
-                                hits,
-                                misses
-                            );
-                        }
-                    }
-                    for plugin in plugins {
-                        if cli.verbose {
-                            eprintln!("  - {} ({})", plugin.name(), plugin.description());
-                        }
-                        linter.add_rule(plugin);
-                    }
-                }
+        let loader = match PluginLoader::new_with_cache(compilation_cache) {
+            Ok(loader) => loader,
+            Err(e) => {
+                eprintln!("Error initializing plugin loader: {}", e);
+                return ExitCode::from(2);
+            }
+        };
+        let sources = [
+            installed_dir
+                .as_deref()
+                .map(|dir| (dir, loader.load_installed(dir))),
+            cli.plugins
+                .as_deref()
+                .map(|dir| (dir, loader.load_plugins(dir))),
+            cli.plugin_registry
+                .as_deref()
+                .map(|registry| (registry, loader.load_registry(registry))),
+        ];
+        for (source, result) in sources.into_iter().flatten() {
+            let plugins = match result {
+                Ok(plugins) => plugins,
                 Err(e) => {
                     eprintln!("Error loading plugins: {}", e);
                     return ExitCode::from(2);
                 }
-            },
-            Err(e) => {
-                eprintln!("Error initializing plugin loader: {}", e);
-                return ExitCode::from(2);
+            };
+            if cli.verbose {
+                eprintln!(
+                    "Loaded {} plugin(s) from {}",
+                    plugins.len(),
+                    source.display()
+                );
             }
+            // Plugins outside the configured preset are not run; ignore
+            // comments naming them stay valid
+            let mut inactive = linter.inactive_rule_names().clone();
+            for mut plugin in plugins {
+                if cli.verbose {
+                    eprintln!("  - {} ({})", plugin.name(), plugin.description());
+                }
+                if !nginx_lint::linter::in_preset(
+                    plugin.name(),
+                    plugin.preset(),
+                    lint_config.as_ref(),
+                ) {
+                    inactive.insert(plugin.name().to_string());
+                    continue;
+                }
+                nginx_lint::linter::configure_rule(plugin.as_mut(), lint_config.as_ref());
+                linter.add_rule(plugin);
+            }
+            linter.set_inactive_rules(inactive);
         }
+        if cli.verbose
+            && let (Some(cache_dir), Some((hits, misses))) =
+                (loader.cache_directory(), loader.cache_stats())
+        {
+            eprintln!(
+                "Plugin compilation cache: {} ({} hit(s), {} miss(es))",
+                cache_dir.display(),
+                hits,
+                misses
+            );
+        }
+    }
+
+    // Rules that read what the configuration points to (rather than the
+    // configuration itself) do not run on untrusted configurations; ignore
+    // comments naming them stay valid
+    if trust_policy == TrustPolicy::Untrusted {
+        let mut inactive = linter.inactive_rule_names().clone();
+        inactive.extend(UNTRUSTED_DISABLED_RULES.iter().map(|name| name.to_string()));
+        linter.remove_rules_by_name(|name| UNTRUSTED_DISABLED_RULES.contains(&name));
+        linter.set_inactive_rules(inactive);
     }
 
     // Finish the --rule-only handling: builtin rules were already filtered
@@ -768,20 +1623,124 @@ pub fn run_lint(cli: Cli) -> ExitCode {
         }
     }
 
+    // --check-paths: plugins cannot read the filesystem, so their file checks
+    // run as native rules under the plugin's name, whenever the plugin itself
+    // is enabled
+    let mut path_rules: Vec<Box<dyn nginx_lint::LintRule>> = Vec::new();
+    if cli.check_paths {
+        let enabled = linter.rule_names();
+        let candidates: [Box<dyn nginx_lint::LintRule>; 3] = [
+            Box::new(nginx_lint::rules::SslCertificatePaths::with_prefix(
+                include_prefix.clone(),
+            )),
+            Box::new(nginx_lint::rules::SslDhparamSize::with_prefix(
+                include_prefix.clone(),
+            )),
+            Box::new(nginx_lint::rules::NjsModulePaths::with_prefix(
+                include_prefix.clone(),
+            )),
+        ];
+        path_rules.extend(
+            candidates
+                .into_iter()
+                .filter(|rule| enabled.contains(rule.name())),
+        );
+    }
+
+    // --cache: files linted by earlier runs are looked up by content; the
+    // rules reading other files run on every file (see ResultCache). Fixing
+    // rewrites the files and profiling needs every rule to run, so neither
+    // uses the cache.
+    let cache_root = if cli.cache && !stdin_mode && !cli.fix && !cli.profile {
+        let root = cli
+            .cache_dir
+            .clone()
+            .or_else(|| {
+                lint_config.as_ref().and_then(|c| c.cache_dir()).map(|dir| {
+                    resolve_against_config_dir(PathBuf::from(dir), config_dir.as_deref())
+                })
+            })
+            .or_else(nginx_lint::cache::default_cache_root);
+        if root.is_none() {
+            eprintln!("Warning: --cache ignored: no cache directory (set --cache-dir)");
+        }
+        root
+    } else {
+        None
+    };
+    let mut result_cache = match cache_root {
+        Some(root) => {
+            let fingerprint = cache_fingerprint(&cli, config_file.as_deref(), &linter, &path_rules);
+            let cache = LintCache::load(&lint_cache_file(&root), &fingerprint);
+            let file_rules = split_file_rules(
+                &mut linter,
+                path_rules,
+                lint_config.as_ref(),
+                include_prefix.as_deref(),
+            );
+            Some(ResultCache::new(cache, file_rules))
+        }
+        None => {
+            for rule in path_rules {
+                linter.add_rule(rule);
+            }
+            None
+        }
+    };
+
+    let template = cli.template_mode.map(TemplateMode::from);
+    if cli.verbose
+        && let Some(mode) = template
+    {
+        eprintln!("Template mode: {}", mode.as_str());
+    }
+
+    let fix_selection = FixSelection {
+        alternatives: &cli.fix_alternative,
+        suggestions: cli.fix_suggestions,
+    };
+
+    // Whether any root file is a fragment (via --context or a
+    // `# nginx-lint:context` comment) rather than a complete config
+    let mut has_fragment_root = !initial_context.is_empty();
+
     // 8. Build results: stdin mode vs file mode
-    let results: Vec<FileResult> = if let Some(ref content) = stdin_content {
+    let mut results: Vec<FileResult> = if let Some(ref content) = stdin_content {
         let result = lint_content(
             content,
             Path::new("<stdin>"),
             &linter,
             cli.profile,
             initial_context.clone(),
+            template,
         );
-        if cli.fix {
-            vec![fix_stdin(result, content, &linter, initial_context)]
+        let (mut results, final_content) = if cli.fix {
+            let (result, fixed) = fix_stdin(
+                result,
+                &linter,
+                initial_context.clone(),
+                &fix_selection,
+                template,
+            );
+            (vec![result], fixed)
         } else {
-            vec![result]
+            (vec![result], content.clone())
+        };
+        // A fragment linted with --context cannot see the rest of the
+        // config, so cross-file rules would only produce false positives.
+        if initial_context.is_empty() && parse_context_comment(&final_content).is_none() {
+            let cross_file = cross_file_results(
+                &linter,
+                &[(
+                    PathBuf::from("<stdin>"),
+                    SourceText::from(final_content),
+                    IncludeContext::new(),
+                )],
+                template,
+            );
+            merge_cross_file_results(&mut results, cross_file);
         }
+        results
     } else {
         // Collect all files to lint (including files referenced by include directives)
         let mut seen_paths: std::collections::HashSet<PathBuf> = std::collections::HashSet::new();
@@ -792,24 +1751,46 @@ pub fn run_lint(cli: Cli) -> ExitCode {
             .map(|c| c.include_path_mappings())
             .unwrap_or(&[]);
 
+        // Templates are parsed from their normalized text, so the includes
+        // of a template are followed too
+        let parse_file = |path: &Path| match template {
+            None => parse_config_with_raw_blocks(path, linter.raw_block_directives())
+                .map_err(|e| e.to_string()),
+            Some(mode) => {
+                let content = SourceText::read(path).map_err(|e| e.to_string())?;
+                let (config, errors) = linter.parse(normalize(&content, mode).text());
+                match errors.first() {
+                    None => Ok(config),
+                    Some(error) => Err(error.message.clone()),
+                }
+            }
+        };
+
         for file_path in &file_paths {
             let files_for_path = if initial_context.is_empty() {
                 collect_included_files(
                     file_path,
-                    |path| parse_config(path).map_err(|e| e.to_string()),
+                    parse_file,
                     path_mappings,
                     include_prefix.as_deref(),
                 )
             } else {
                 collect_included_files_with_context(
                     file_path,
-                    |path| parse_config(path).map_err(|e| e.to_string()),
+                    parse_file,
                     initial_context.clone(),
                     path_mappings,
                     include_prefix.as_deref(),
                 )
             };
 
+            if files_for_path
+                .first()
+                .is_some_and(|root| !root.include_context.is_empty())
+            {
+                has_fragment_root = true;
+            }
+
             for inc in files_for_path {
                 let canonical = inc.path.canonicalize().unwrap_or_else(|_| inc.path.clone());
                 if !seen_paths.contains(&canonical) {
@@ -827,30 +1808,71 @@ pub fn run_lint(cli: Cli) -> ExitCode {
         }
 
         // Lint files (parallel when not fixing and not profiling, sequential otherwise)
-        if cli.fix {
+        let mut results: Vec<FileResult> = if cli.fix {
             included_files
                 .iter()
-                .map(|inc| fix_file(inc, &linter, cli.profile))
+                .map(|inc| fix_file(inc, &linter, cli.profile, &fix_selection, template))
                 .collect()
         } else if cli.profile {
             included_files
                 .iter()
-                .map(|inc| lint_file(inc, &linter, true))
+                .map(|inc| lint_file(inc, &linter, true, template))
                 .collect()
+        } else if let Some(cache) = &mut result_cache {
+            cache.lint_files(&included_files, &linter, template)
         } else {
             included_files
                 .par_iter()
-                .map(|inc| lint_file(inc, &linter, false))
+                .map(|inc| lint_file(inc, &linter, false, template))
                 .collect()
+        };
+
+        // Cross-file pass (skipped for fragments, see stdin mode above)
+        if !has_fragment_root {
+            let files: Vec<(PathBuf, SourceText, IncludeContext)> = included_files
+                .iter()
+                .map(|inc| {
+                    let content = SourceText::read(&inc.path)
+                        .unwrap_or_else(|_| SourceText::from(String::new()));
+                    (inc.path.clone(), content, inc.include_context.clone())
+                })
+                .collect();
+            let cross_file = match &mut result_cache {
+                Some(cache) => cache.cross_file_results(&linter, &files, template),
+                None => cross_file_results(&linter, &files, template),
+            };
+            merge_cross_file_results(&mut results, cross_file);
         }
+        if let Some(cache) = &result_cache {
+            cache.save(cli.verbose);
+        }
+        results
     };
 
-    // 9. Process results (report/exit code)
+    // 9. Verify with nginx -t in a container
+    if let Some(image) = &cli.verify_with_nginx {
+        if has_fragment_root {
+            eprintln!(
+                "Warning: --verify-with-nginx skipped: nginx -t needs a complete configuration, not a fragment"
+            );
+        } else if let Err(e) = verify_with_nginx(
+            &mut results,
+            image,
+            &file_paths,
+            include_prefix.as_deref(),
+            cli.verbose,
+        ) {
+            eprintln!("Error: --verify-with-nginx: {}", e);
+            return ExitCode::from(2);
+        }
+    }
+
+    // 10. Process results (report/exit code)
     process_results(
         results,
         cli.fix,
-        cli.no_fail_on_warnings,
         cli.profile,
+        cli.summary.then(|| Summary::new(cli.top)),
         &reporter,
         stdin_mode,
     )
@@ -1,16 +1,30 @@
+pub mod archive;
 pub mod config;
+pub mod diff;
+pub mod docgen;
+pub mod fmt;
 pub mod guide;
 pub mod lint;
+pub mod parse;
+#[cfg(feature = "plugins")]
+pub mod plugins;
+pub mod rules;
+pub mod summary;
+pub mod verify;
+pub mod version;
 pub mod web;
 pub mod why;
 
 use clap::{Parser, Subcommand};
-use nginx_lint::OutputFormat;
+use nginx_lint::preset::Preset;
+use nginx_lint::template::TemplateMode;
+use nginx_lint::{ExitPolicy, LintConfig, OutputFormat, RulePreset, Severity, TrustPolicy};
 use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "nginx-lint")]
 #[command(author, version, about = "Lint nginx configuration files", long_about = None)]
+#[command(disable_version_flag = true)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -27,6 +41,16 @@ pub struct Cli {
     #[arg(long)]
     pub fix: bool,
 
+    /// With --fix, resolve diagnostics that offer an alternative fix with
+    /// this label by it instead of their default fix. Can be repeated
+    #[arg(long, value_name = "LABEL", requires = "fix")]
+    pub fix_alternative: Vec<String>,
+
+    /// With --fix, also apply fixes that may change behavior or need
+    /// review, not only the safe ones
+    #[arg(long, requires = "fix")]
+    pub fix_suggestions: bool,
+
     /// Path to configuration file
     #[arg(short, long, value_name = "FILE")]
     pub config: Option<PathBuf>,
@@ -43,37 +67,73 @@ pub struct Cli {
     #[arg(short, long)]
     pub verbose: bool,
 
-    /// Do not exit with non-zero code on warnings (only fail on errors)
-    #[arg(long)]
-    pub no_fail_on_warnings: bool,
+    #[command(flatten)]
+    pub exit: ExitArgs,
 
     /// Specify parent context for files not included from a parent config.
     /// Comma-separated list of block names (e.g., "http,server" for sites-available files).
+    /// A block may carry its arguments after `=` (e.g., "http,server,location=~ ^/api")
+    /// so rules that depend on the location match (alias, unreachable-location) work too.
     /// This enables context-aware rules like server_tokens detection.
+    /// With --preset, CONTEXT may also name a snippet of the preset (e.g.
+    /// "server-snippet"), and defaults to the preset's most common snippet.
     #[arg(long, value_name = "CONTEXT")]
     pub context: Option<String>,
 
+    /// Start from a rule set, or lint snippets of a deployment. `minimal`
+    /// runs the rules for errors that break nginx and well-known holes,
+    /// `recommended` adds likely bugs, security and deprecation warnings,
+    /// and `strict` runs every rule, also turning on the ones that are off
+    /// by default unless they need setup (overriding `preset` in
+    /// .nginx-lint.toml). `ingress-nginx` lints the snippet annotations and
+    /// ConfigMap snippets of the Kubernetes ingress-nginx controller, in the
+    /// context the controller inserts them into, without the rules about
+    /// settings the controller manages. Rules configured in .nginx-lint.toml
+    /// keep their settings.
+    #[arg(long, value_enum, value_name = "PRESET")]
+    pub preset: Option<PresetName>,
+
     /// Directory containing WASM plugins for custom lint rules (requires plugins feature)
     #[cfg(feature = "plugins")]
     #[arg(long, value_name = "DIR")]
     pub plugins: Option<PathBuf>,
 
+    /// Registry file listing WASM plugins to load, one .wasm path per line
+    /// (relative paths are resolved against the registry's directory;
+    /// requires plugins feature)
+    #[cfg(feature = "plugins")]
+    #[arg(long, value_name = "FILE")]
+    pub plugin_registry: Option<PathBuf>,
+
+    /// Skip files that have not changed since the last run with --cache,
+    /// reporting their diagnostics from the cache (stored under "lint/" in
+    /// the cache directory)
+    #[arg(long)]
+    pub cache: bool,
+
     /// Cache directory for nginx-lint (the WASM plugin compilation cache is stored
     /// under "plugins/" beneath it). Defaults to the per-user cache directory
     /// (e.g. ~/.cache/nginx-lint on Linux). Overrides cache_dir in .nginx-lint.toml.
-    #[cfg(feature = "plugins")]
     #[arg(long, value_name = "DIR", conflicts_with = "no_cache")]
     pub cache_dir: Option<PathBuf>,
 
-    /// Disable the cache (WASM plugins are compiled on every run)
-    #[cfg(feature = "plugins")]
-    #[arg(long)]
+    /// Disable the caches (WASM plugins are compiled on every run, and
+    /// --cache cannot be used)
+    #[arg(long, conflicts_with = "cache")]
     pub no_cache: bool,
 
     /// Show profiling information (time spent per rule)
     #[arg(long)]
     pub profile: bool,
 
+    /// After the diagnostics, show issue counts per severity, rule and file
+    #[arg(long)]
+    pub summary: bool,
+
+    /// With --summary, also list the N files with the most issues
+    #[arg(long, value_name = "N", requires = "summary")]
+    pub top: Option<usize>,
+
     /// Base directory for resolving relative include paths (similar to nginx -p prefix).
     /// Overrides include.prefix in .nginx-lint.toml.
     #[arg(short = 'p', long, value_name = "DIR")]
@@ -85,6 +145,105 @@ pub struct Cli {
     /// comma-separated, e.g. `--rule-only indent` or `--rule-only indent,gzip-not-enabled`.
     #[arg(long, value_name = "RULE", value_delimiter = ',')]
     pub rule_only: Vec<String>,
+
+    /// Also check that files referenced by the configuration exist on disk
+    /// and can be read (ssl_certificate, ssl_certificate_key,
+    /// proxy_ssl_trusted_certificate and other certificates, reported by
+    /// ssl-certificate-missing; js_import modules and the handlers they
+    /// export, reported by njs-module-reference; auth_basic_user_file,
+    /// reported by auth-basic-user-file-missing) and that ssl_dhparam holds
+    /// at least 2048-bit parameters (weak-ssl-key-exchange). Relative paths
+    /// are resolved like includes, against --prefix when given.
+    #[arg(long)]
+    pub check_paths: bool,
+
+    /// Once the static checks report no errors, run `nginx -t` on the
+    /// configuration in a Docker container of IMAGE (default nginx:stable)
+    /// and report what it rejects as `nginx-test` diagnostics. The
+    /// directory of the linted file (or --prefix, when it contains the file)
+    /// is mounted read-only at the same path. Set NGINX_LINT_DOCKER to use
+    /// another container engine, e.g. podman.
+    #[arg(
+        long,
+        value_name = "IMAGE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = verify::DEFAULT_IMAGE
+    )]
+    pub verify_with_nginx: Option<String>,
+
+    /// Lint templates that are rendered into nginx configurations: before
+    /// parsing, replace the placeholders of MODE (`${VAR}` for envsubst,
+    /// `{{ ... }}` for jinja and consul) with `${template_*}` variables and
+    /// drop template statements (`{% if %}`, `{{ range }}`, ...). Reported
+    /// positions and fixes refer to the template.
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub template_mode: Option<Template>,
+
+    /// Lint the configuration dumped by `crossplane parse` in FILE (`-` for
+    /// stdin) instead of files on disk. The dump is turned back into nginx
+    /// source with each directive on its original line; includes are looked
+    /// up among the files of the dump.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["files", "fix", "cache", "verify_with_nginx"]
+    )]
+    pub from_crossplane: Option<PathBuf>,
+
+    /// How far the linted configuration is trusted. `untrusted` disables
+    /// third-party plugins, the compilation cache, --check-paths,
+    /// --verify-with-nginx and other checks reading files outside the
+    /// configuration, and plugin downloads, for linting configurations
+    /// written by others (e.g. on shared CI runners). The stricter of this
+    /// and trust_policy in .nginx-lint.toml applies.
+    #[arg(long, value_enum, value_name = "POLICY", global = true)]
+    pub trust_policy: Option<Trust>,
+
+    /// Print version; with --verbose, also the build features and the trust
+    /// policy
+    #[arg(short = 'V', long)]
+    pub version: bool,
+}
+
+/// When the exit code is non-zero and which diagnostics are shown, shared by
+/// the lint command and the subcommands linting a whole tree
+#[derive(Clone, Copy, clap::Args)]
+pub struct ExitArgs {
+    /// Do not exit with non-zero code on warnings (only fail on errors);
+    /// same as `--error-on error`
+    #[arg(long, conflicts_with = "error_on")]
+    pub no_fail_on_warnings: bool,
+
+    /// Lowest severity that makes the exit code non-zero
+    #[arg(long, value_enum, value_name = "SEVERITY")]
+    pub error_on: Option<ErrorOn>,
+
+    /// Exit with non-zero code when there are more than N warnings, whatever
+    /// --error-on says (errors always fail)
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+
+    /// Report errors only; warnings are hidden but still count towards the
+    /// exit code (combine with `--error-on error` to ignore them)
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+impl ExitArgs {
+    /// The reporter's exit policy for these flags
+    pub fn policy(&self) -> ExitPolicy {
+        let error_on = match (self.error_on, self.no_fail_on_warnings) {
+            (Some(level), _) => level.into(),
+            (None, true) => Severity::Error,
+            (None, false) => Severity::Warning,
+        };
+        ExitPolicy {
+            error_on,
+            max_warnings: self.max_warnings,
+            quiet: self.quiet,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -94,6 +253,85 @@ pub enum Commands {
         #[command(subcommand)]
         command: config::ConfigCommands,
     },
+    /// Format configuration files using the [format] style from .nginx-lint.toml
+    Fmt {
+        /// Path to nginx configuration file(s)
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
+
+        /// Do not write files; exit with 1 if any file would be reformatted
+        #[arg(long)]
+        check: bool,
+
+        /// Path to configuration file
+        #[arg(short, long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
+    /// Compare two configuration files semantically, ignoring whitespace,
+    /// comments, quoting and the order of directives, and list the
+    /// directives added, removed or modified with the blocks they are in
+    Diff {
+        /// The configuration before the change
+        #[arg(value_name = "OLD")]
+        old: PathBuf,
+
+        /// The configuration after the change (`-` to read stdin)
+        #[arg(value_name = "NEW")]
+        new: PathBuf,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "text")]
+        format: diff::DiffFormat,
+
+        /// Path to configuration file (for the [parser] settings)
+        #[arg(short, long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
+    /// Print the AST of a configuration file (directives, arguments with
+    /// their kinds, spans, comments) as JSON for external tools
+    Parse {
+        /// Path to nginx configuration file, or `-` to read stdin
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "json")]
+        format: parse::ParseFormat,
+
+        /// Parent context of the file, as for linting (e.g. "http,server"),
+        /// recorded as the AST's include context
+        #[arg(long, value_name = "CONTEXT")]
+        context: Option<String>,
+
+        /// Path to configuration file (for the [parser] settings)
+        #[arg(short, long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
+    /// Lint the nginx configuration in a tar archive (.tar or .tar.gz)
+    Archive {
+        /// Path to the archive
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Directory the archive was created from, which absolute paths in
+        /// the configuration refer to (e.g. /etc for `tar -C /etc -czf
+        /// backup.tar.gz nginx`)
+        #[arg(long, value_name = "DIR", default_value = "/")]
+        mount_point: PathBuf,
+
+        #[command(flatten)]
+        args: archive::TreeArgs,
+    },
+    /// Lint the nginx configuration in a container image, read with
+    /// `docker create` and `docker export` (the engine is taken from
+    /// NGINX_LINT_DOCKER)
+    Image {
+        /// Image name (e.g. nginx:1.25)
+        image: String,
+
+        #[command(flatten)]
+        args: archive::TreeArgs,
+    },
     /// Start a web server to try nginx-lint in the browser
     Web {
         /// Port to listen on
@@ -104,6 +342,23 @@ pub enum Commands {
         #[arg(long)]
         open: bool,
     },
+    /// Manage installed WASM plugins (requires plugins feature)
+    #[cfg(feature = "plugins")]
+    Plugins {
+        #[command(subcommand)]
+        command: plugins::PluginsCommands,
+    },
+    /// List every rule with its category, severity and summary
+    Rules {
+        #[command(subcommand)]
+        command: Option<rules::RulesCommands>,
+
+        #[command(flatten)]
+        args: rules::RulesArgs,
+    },
+    /// Generate Markdown or HTML documentation pages for every rule, after
+    /// checking that each bad example triggers its rule and each good one does not
+    Docgen(docgen::DocgenArgs),
     /// Show getting started guide (installation, usage, configuration)
     Guide,
     /// Show detailed documentation for a rule
@@ -122,6 +377,84 @@ pub enum Format {
     Errorformat,
     Json,
     GithubActions,
+    Checkstyle,
+    Rdjson,
+    Pretty,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Trust {
+    Trusted,
+    Untrusted,
+}
+
+impl From<Trust> for TrustPolicy {
+    fn from(trust: Trust) -> Self {
+        match trust {
+            Trust::Trusted => TrustPolicy::Trusted,
+            Trust::Untrusted => TrustPolicy::Untrusted,
+        }
+    }
+}
+
+/// The trust policy of a run: the stricter of `--trust-policy` and
+/// `trust_policy` in the config file, so a config cannot loosen what the
+/// command line restricts
+pub fn trust_policy(cli: Option<Trust>, config: Option<&LintConfig>) -> TrustPolicy {
+    let cli = cli.map(TrustPolicy::from).unwrap_or_default();
+    let config = config.and_then(|c| c.trust_policy()).unwrap_or_default();
+    cli.max(config)
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum PresetName {
+    IngressNginx,
+    Minimal,
+    Recommended,
+    Strict,
+}
+
+impl From<PresetName> for Preset {
+    fn from(preset: PresetName) -> Self {
+        match preset {
+            PresetName::IngressNginx => Preset::IngressNginx,
+            PresetName::Minimal => Preset::Rules(RulePreset::Minimal),
+            PresetName::Recommended => Preset::Rules(RulePreset::Recommended),
+            PresetName::Strict => Preset::Rules(RulePreset::Strict),
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Template {
+    Envsubst,
+    Jinja,
+    Consul,
+}
+
+impl From<Template> for TemplateMode {
+    fn from(template: Template) -> Self {
+        match template {
+            Template::Envsubst => TemplateMode::Envsubst,
+            Template::Jinja => TemplateMode::Jinja,
+            Template::Consul => TemplateMode::Consul,
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum ErrorOn {
+    Warning,
+    Error,
+}
+
+impl From<ErrorOn> for Severity {
+    fn from(level: ErrorOn) -> Self {
+        match level {
+            ErrorOn::Warning => Severity::Warning,
+            ErrorOn::Error => Severity::Error,
+        }
+    }
 }
 
 impl From<Format> for OutputFormat {
@@ -130,6 +463,9 @@ impl From<Format> for OutputFormat {
             Format::Errorformat => OutputFormat::ErrorFormat,
             Format::Json => OutputFormat::Json,
             Format::GithubActions => OutputFormat::GithubActions,
+            Format::Checkstyle => OutputFormat::Checkstyle,
+            Format::Rdjson => OutputFormat::Rdjson,
+            Format::Pretty => OutputFormat::Pretty,
         }
     }
 }
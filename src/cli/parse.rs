@@ -0,0 +1,108 @@
+use nginx_lint::LintConfig;
+use nginx_lint::parser::context::IncludeContext;
+use nginx_lint::parser::line_index::LineIndex;
+use nginx_lint::parser::parse_string_with_errors_and_raw_blocks;
+use std::io::Read;
+use std::path::Path;
+use std::process::ExitCode;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum ParseFormat {
+    Json,
+}
+
+/// Print the AST of `file` (`-` for stdin) for external tools.
+///
+/// Syntax errors are printed on stderr; the AST is still printed (the parser
+/// recovers from them) and the exit code is 1. Exit code 2 means the file or
+/// the configuration could not be read.
+pub fn run_parse(
+    file: &Path,
+    format: ParseFormat,
+    context: Option<&str>,
+    config_path: Option<&Path>,
+) -> ExitCode {
+    let raw_block_directives = match raw_block_directives(file, config_path) {
+        Ok(directives) => directives,
+        Err(code) => return code,
+    };
+    let content = match read_input(file) {
+        Ok(content) => content,
+        Err(code) => return code,
+    };
+
+    let (mut config, syntax_errors) =
+        parse_string_with_errors_and_raw_blocks(&content, &raw_block_directives);
+    if let Some(context) = context {
+        config.include_context = IncludeContext::parse(context);
+    }
+
+    match format {
+        ParseFormat::Json => match serde_json::to_string_pretty(&config) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(2);
+            }
+        },
+    }
+
+    if syntax_errors.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+    let line_index = LineIndex::new(&content);
+    for error in &syntax_errors {
+        let pos = line_index.position(error.offset);
+        eprintln!(
+            "{}:{}:{}: {}",
+            file.display(),
+            pos.line,
+            pos.column,
+            error.message
+        );
+    }
+    ExitCode::from(1)
+}
+
+/// `[parser] raw_block_directives` of the configuration given with
+/// `--config`, or found from the directory of `file`; the error has been
+/// printed
+pub(super) fn raw_block_directives(
+    file: &Path,
+    config_path: Option<&Path>,
+) -> Result<Vec<String>, ExitCode> {
+    let lint_config = if let Some(config_path) = config_path {
+        match LintConfig::from_file(config_path) {
+            Ok(cfg) => Some(cfg),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return Err(ExitCode::from(2));
+            }
+        }
+    } else {
+        let search_dir = match file.parent() {
+            Some(dir) if file.as_os_str() != "-" && !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        LintConfig::find_and_load(search_dir).map(|(cfg, _)| cfg)
+    };
+    Ok(lint_config
+        .map(|c| c.additional_raw_block_directives().to_vec())
+        .unwrap_or_default())
+}
+
+/// Contents of `file`, or of stdin for `-`; the error has been printed
+pub(super) fn read_input(file: &Path) -> Result<String, ExitCode> {
+    let content = if file.as_os_str() == "-" {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .map(|_| content)
+    } else {
+        std::fs::read_to_string(file)
+    };
+    content.map_err(|e| {
+        eprintln!("Error reading {}: {}", file.display(), e);
+        ExitCode::from(2)
+    })
+}
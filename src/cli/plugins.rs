@@ -0,0 +1,105 @@
+use clap::Subcommand;
+use nginx_lint::TrustPolicy;
+use nginx_lint::plugin::{CompilationCache, PluginLoader};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Subcommand)]
+pub enum PluginsCommands {
+    /// Install a plugin from its manifest (nginx-lint-plugin.toml): a file,
+    /// a directory containing one, or an https URL
+    Install {
+        /// Manifest path, plugin directory or manifest URL
+        source: String,
+
+        /// Plugin directory to install into (default: ~/.config/nginx-lint/plugins)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+    /// List installed plugins and whether they load
+    List {
+        /// Plugin directory to list (default: ~/.config/nginx-lint/plugins)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+}
+
+pub fn run_plugins(command: &PluginsCommands, trust_policy: TrustPolicy) -> ExitCode {
+    // Installing downloads and listing compiles third-party plugins
+    if trust_policy == TrustPolicy::Untrusted {
+        eprintln!("Error: plugins cannot be installed or loaded with --trust-policy untrusted");
+        return ExitCode::from(2);
+    }
+    let (PluginsCommands::Install { dir, .. } | PluginsCommands::List { dir }) = command;
+    let Some(dir) = dir.clone().or_else(nginx_lint::plugin::default_plugin_dir) else {
+        eprintln!("Error: could not determine the plugin directory; use --dir");
+        return ExitCode::from(2);
+    };
+    let loader = match PluginLoader::new_with_cache(CompilationCache::Default) {
+        Ok(loader) => loader,
+        Err(e) => {
+            eprintln!("Error initializing plugin loader: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    match command {
+        PluginsCommands::Install { source, .. } => run_install(&loader, source, dir),
+        PluginsCommands::List { .. } => run_list(&loader, dir),
+    }
+}
+
+fn run_install(loader: &PluginLoader, source: &str, dir: PathBuf) -> ExitCode {
+    match loader.install(source, &dir) {
+        Ok(manifest) => {
+            eprintln!(
+                "Installed {} {} ({}) into {}",
+                manifest.name,
+                manifest.version,
+                manifest.rules.join(", "),
+                dir.join(&manifest.name).display()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run_list(loader: &PluginLoader, dir: PathBuf) -> ExitCode {
+    let plugin_dirs = match nginx_lint::plugin::installed_plugin_dirs(&dir) {
+        Ok(plugin_dirs) => plugin_dirs,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+    if plugin_dirs.is_empty() {
+        eprintln!("No plugins installed in {}", dir.display());
+        return ExitCode::SUCCESS;
+    }
+
+    let mut failed = false;
+    for plugin_dir in &plugin_dirs {
+        match loader.load_installed_plugin(plugin_dir) {
+            Ok((manifest, _)) => println!(
+                "{} {} ({}): active",
+                manifest.name,
+                manifest.version,
+                manifest.rules.join(", ")
+            ),
+            Err(e) => {
+                failed = true;
+                let name = plugin_dir.file_name().unwrap_or_default().to_string_lossy();
+                println!("{}: not loaded: {}", name, e);
+            }
+        }
+    }
+    if failed {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,182 @@
+use clap::{Args, Subcommand};
+use nginx_lint::docs::RuleDocOwned;
+#[cfg(feature = "plugins")]
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Subcommand)]
+pub enum RulesCommands {
+    /// Show the documentation of a rule: why it exists, examples and references
+    Explain {
+        /// Rule name (e.g., "server-tokens-enabled")
+        rule: String,
+    },
+}
+
+#[derive(Args)]
+pub struct RulesArgs {
+    /// Output format
+    #[arg(
+        id = "rules_format",
+        short = 'o',
+        long = "format",
+        value_enum,
+        default_value = "text",
+        global = true
+    )]
+    pub format: RulesFormat,
+
+    /// Directory containing WASM plugins whose rules are listed too
+    /// (requires plugins feature)
+    #[cfg(feature = "plugins")]
+    #[arg(
+        id = "rules_plugins",
+        long = "plugins",
+        value_name = "DIR",
+        global = true
+    )]
+    pub plugins: Option<PathBuf>,
+
+    /// Registry file listing WASM plugins whose rules are listed too
+    /// (requires plugins feature)
+    #[cfg(feature = "plugins")]
+    #[arg(
+        id = "rules_plugin_registry",
+        long = "plugin-registry",
+        value_name = "FILE",
+        global = true
+    )]
+    pub plugin_registry: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RulesFormat {
+    Text,
+    /// Full documentation of each rule as JSON (for generating docs)
+    Json,
+}
+
+pub fn run_rules(command: Option<&RulesCommands>, args: &RulesArgs) -> ExitCode {
+    let docs = match rule_docs(args) {
+        Ok(docs) => docs,
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            return ExitCode::from(2);
+        }
+    };
+
+    match command {
+        None => {
+            match args.format {
+                RulesFormat::Text => print_rule_list(&docs),
+                RulesFormat::Json => print_json(&docs),
+            }
+            ExitCode::SUCCESS
+        }
+        Some(RulesCommands::Explain { rule }) => {
+            let Some(doc) = docs.iter().find(|doc| &doc.name == rule) else {
+                eprintln!("Error: Unknown rule: {}", rule);
+                eprintln!();
+                eprintln!("Use `nginx-lint rules` to see all available rules.");
+                return ExitCode::from(1);
+            };
+            match args.format {
+                RulesFormat::Text => super::why::print_rule_doc_owned(doc),
+                RulesFormat::Json => print_json(doc),
+            }
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Documentation of every builtin rule, followed by the rules of the
+/// installed plugins and those given with --plugins / --plugin-registry
+fn rule_docs(args: &RulesArgs) -> Result<Vec<RuleDocOwned>, String> {
+    #[cfg(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins"))]
+    let docs = nginx_lint::docs::all_rule_docs_with_plugins();
+    #[cfg(not(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins")))]
+    let docs: Vec<RuleDocOwned> = nginx_lint::docs::all_rule_docs()
+        .iter()
+        .map(|doc| (*doc).into())
+        .collect();
+
+    #[cfg(feature = "plugins")]
+    let mut docs = docs;
+    #[cfg(feature = "plugins")]
+    docs.extend(
+        load_plugin_rules(args.plugins.as_deref(), args.plugin_registry.as_deref())?
+            .iter()
+            .map(|plugin| RuleDocOwned::from_plugin(plugin.as_ref())),
+    );
+    #[cfg(not(feature = "plugins"))]
+    let _ = args;
+
+    Ok(docs)
+}
+
+/// The rules of the installed plugins, then those of the `plugins`
+/// directory and the `registry` entries
+#[cfg(feature = "plugins")]
+pub fn load_plugin_rules(
+    plugins: Option<&std::path::Path>,
+    registry: Option<&std::path::Path>,
+) -> Result<Vec<Box<dyn nginx_lint::LintRule>>, String> {
+    use nginx_lint::plugin::{CompilationCache, PluginLoader};
+
+    let installed_dir = nginx_lint::plugin::default_plugin_dir().filter(|dir| dir.is_dir());
+    if installed_dir.is_none() && plugins.is_none() && registry.is_none() {
+        return Ok(Vec::new());
+    }
+    let loader = PluginLoader::new_with_cache(CompilationCache::Default)
+        .map_err(|e| format!("initializing plugin loader: {}", e))?;
+    let sources = [
+        installed_dir
+            .as_deref()
+            .map(|dir| loader.load_installed(dir)),
+        plugins.map(|dir| loader.load_plugins(dir)),
+        registry.map(|registry| loader.load_registry(registry)),
+    ];
+    let mut rules = Vec::new();
+    for result in sources.into_iter().flatten() {
+        rules.extend(result.map_err(|e| format!("loading plugins: {}", e))?);
+    }
+    Ok(rules)
+}
+
+/// One line per rule, grouped by category
+fn print_rule_list(docs: &[RuleDocOwned]) {
+    let name_width = docs.iter().map(|doc| doc.name.len()).max().unwrap_or(0);
+    let mut categories: Vec<&str> = nginx_lint::RULE_CATEGORIES.to_vec();
+    // Plugins may declare categories of their own
+    for doc in docs {
+        if !categories.contains(&doc.category.as_str()) {
+            categories.push(&doc.category);
+        }
+    }
+
+    for category in categories {
+        let rules: Vec<_> = docs.iter().filter(|doc| doc.category == category).collect();
+        if rules.is_empty() {
+            continue;
+        }
+        println!("{}", category);
+        for doc in rules {
+            println!(
+                "  {:name_width$}  {:7}  {}{}",
+                doc.name,
+                doc.severity,
+                doc.description,
+                if doc.is_plugin { " (plugin)" } else { "" },
+            );
+        }
+        println!();
+    }
+    println!("Use `nginx-lint rules explain <rule-name>` to see detailed documentation.");
+}
+
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Error: failed to serialize rules: {}", e),
+    }
+}
@@ -0,0 +1,141 @@
+use colored::Colorize;
+use nginx_lint::{LintError, Severity};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Issue counts of a lint run, for `--summary`
+pub struct Summary {
+    /// Length of the `--top` offender list
+    top: Option<usize>,
+    /// Number of files linted
+    files: usize,
+    by_rule: BTreeMap<String, Counts>,
+    /// Files with at least one issue
+    by_file: BTreeMap<PathBuf, Counts>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Counts {
+    errors: usize,
+    warnings: usize,
+}
+
+impl Counts {
+    fn add(&mut self, severity: Severity) {
+        match severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.errors + self.warnings
+    }
+}
+
+impl Summary {
+    /// Empty summary; `top` is the number of files in the offender list
+    pub fn new(top: Option<usize>) -> Self {
+        Self {
+            top,
+            files: 0,
+            by_rule: BTreeMap::new(),
+            by_file: BTreeMap::new(),
+        }
+    }
+
+    /// Count the issues reported for `path`
+    pub fn add_file(&mut self, path: &Path, errors: &[LintError]) {
+        self.files += 1;
+        for error in errors {
+            self.by_rule
+                .entry(error.rule.clone())
+                .or_default()
+                .add(error.severity);
+            self.by_file
+                .entry(path.to_path_buf())
+                .or_default()
+                .add(error.severity);
+        }
+    }
+
+    /// Print the summary to stderr
+    pub fn display(&self) {
+        let total = self
+            .by_file
+            .values()
+            .fold(Counts::default(), |sum, c| Counts {
+                errors: sum.errors + c.errors,
+                warnings: sum.warnings + c.warnings,
+            });
+
+        eprintln!();
+        eprintln!("{}", "Summary".bold().underline());
+        eprintln!();
+        eprintln!(
+            "{} file(s) linted, {} with issues: {} error(s), {} warning(s)",
+            self.files,
+            self.by_file.len(),
+            total.errors,
+            total.warnings
+        );
+        if total.total() == 0 {
+            eprintln!();
+            return;
+        }
+
+        // Most frequent rules first
+        let mut rules: Vec<_> = self.by_rule.iter().collect();
+        rules.sort_by_key(|(_, counts)| std::cmp::Reverse(counts.total()));
+        print_table(
+            "Rule",
+            rules.into_iter().map(|(rule, c)| (rule.clone(), *c)),
+        );
+
+        print_table(
+            "File",
+            self.by_file
+                .iter()
+                .map(|(path, c)| (path.display().to_string(), *c)),
+        );
+
+        if let Some(top) = self.top {
+            let mut files: Vec<_> = self.by_file.iter().collect();
+            files.sort_by_key(|(_, counts)| std::cmp::Reverse((counts.errors, counts.total())));
+            eprintln!("{}", format!("Top {} files", top).bold());
+            for (rank, (path, counts)) in files.into_iter().take(top).enumerate() {
+                eprintln!(
+                    "{:>4}. {}  ({} error(s), {} warning(s))",
+                    rank + 1,
+                    path.display(),
+                    counts.errors,
+                    counts.warnings
+                );
+            }
+            eprintln!();
+        }
+    }
+}
+
+/// One row per entry with its error, warning and total counts
+fn print_table(label: &str, rows: impl Iterator<Item = (String, Counts)>) {
+    eprintln!();
+    eprintln!(
+        "{:>6}  {:>8}  {:>5}  {}",
+        "Errors".bold(),
+        "Warnings".bold(),
+        "Total".bold(),
+        label.bold()
+    );
+    eprintln!("{}", "-".repeat(70));
+    for (name, counts) in rows {
+        eprintln!(
+            "{:>6}  {:>8}  {:>5}  {}",
+            counts.errors,
+            counts.warnings,
+            counts.total(),
+            name
+        );
+    }
+    eprintln!();
+}
@@ -0,0 +1,114 @@
+//! `--verify-with-nginx`: run `nginx -t` on the linted configuration in a
+//! container and report what nginx itself rejects.
+//!
+//! The static rules cannot see everything nginx checks when it loads a
+//! configuration (modules compiled into the binary, upstream host names,
+//! files referenced by directives). The configuration tree is mounted
+//! read-only into a container at the same path, so the paths nginx prints
+//! are the host paths and its messages map back to the linted files.
+
+use nginx_lint::{LintError, Severity};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Rule name of the diagnostics reported by `nginx -t`
+pub const NGINX_TEST_RULE: &str = "nginx-test";
+
+/// Image used when `--verify-with-nginx` is given without one
+pub const DEFAULT_IMAGE: &str = "nginx:stable";
+
+/// Environment variable naming the container engine (`docker` by default,
+/// e.g. `podman`)
+const ENGINE_ENV: &str = "NGINX_LINT_DOCKER";
+
+/// The container engine to run, from `NGINX_LINT_DOCKER`
+pub fn container_engine() -> String {
+    std::env::var(ENGINE_ENV).unwrap_or_else(|_| "docker".to_string())
+}
+
+/// A message printed by `nginx -t`, and the file it refers to when nginx
+/// named one
+#[derive(Debug)]
+pub struct NginxMessage {
+    pub path: Option<PathBuf>,
+    pub error: LintError,
+}
+
+/// Run `nginx -t -c root` in a container of `image`, with the directory
+/// `mount` (holding the configuration tree) mounted read-only at the same
+/// path.
+///
+/// Fails if the container could not be run at all (no container engine,
+/// unknown image); a configuration rejected by nginx is reported through
+/// the returned messages.
+pub fn nginx_test(image: &str, root: &Path, mount: &Path) -> Result<Vec<NginxMessage>, String> {
+    let engine = container_engine();
+    let volume = format!("{}:{}:ro", mount.display(), mount.display());
+    let output = Command::new(&engine)
+        .args(["run", "--rm", "-v", &volume, "--entrypoint", "nginx", image])
+        .arg("-t")
+        .arg("-c")
+        .arg(root)
+        .output()
+        .map_err(|e| format!("failed to run {}: {}", engine, e))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let messages = parse_output(&stderr);
+    if !output.status.success() && !messages.iter().any(|m| m.error.severity == Severity::Error) {
+        // Not a message from nginx: the engine could not start the container
+        return Err(format!(
+            "{} run {} failed:\n{}",
+            engine,
+            image,
+            stderr.trim()
+        ));
+    }
+    Ok(messages)
+}
+
+/// Parse the `nginx: [level] message in file:line` lines of `nginx -t`.
+///
+/// Errors (`emerg`, `alert`, `crit`, `error`) and warnings (`warn`) are
+/// kept; the summary lines and anything else the container prints (image
+/// pull progress) are skipped.
+pub fn parse_output(output: &str) -> Vec<NginxMessage> {
+    output.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<NginxMessage> {
+    let start = line.find('[')?;
+    let (level, message) = line[start + 1..].split_once("] ")?;
+    let severity = match level {
+        "emerg" | "alert" | "crit" | "error" => Severity::Error,
+        "warn" => Severity::Warning,
+        _ => return None,
+    };
+    // Messages written through error_log carry the pid and connection
+    // ("1#1: ") before the text
+    let message = match message.split_once(": ") {
+        Some((pid, rest)) if is_pid(pid) => rest,
+        _ => message,
+    };
+
+    // nginx names the file and line but no column
+    let (text, path, line) = match message.rsplit_once(" in ").and_then(|(text, location)| {
+        let (path, line) = location.rsplit_once(':')?;
+        Some((text, path, line.trim_end().parse::<usize>().ok()?))
+    }) {
+        Some((text, path, line)) => (text, Some(PathBuf::from(path)), Some(line)),
+        None => (message.trim_end(), None, None),
+    };
+    let mut error = LintError::new(NGINX_TEST_RULE, "syntax", text, severity);
+    error.line = line;
+    Some(NginxMessage { path, error })
+}
+
+/// Whether `s` is the `pid#tid` of an error log line
+fn is_pid(s: &str) -> bool {
+    s.split_once('#').is_some_and(|(pid, tid)| {
+        !pid.is_empty()
+            && !tid.is_empty()
+            && pid.bytes().all(|b| b.is_ascii_digit())
+            && tid.bytes().all(|b| b.is_ascii_digit())
+    })
+}
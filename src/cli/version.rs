@@ -0,0 +1,66 @@
+use super::Cli;
+use nginx_lint::{LintConfig, TrustPolicy};
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Optional features and whether this binary was built with them
+const FEATURES: &[(&str, bool)] = &[
+    ("plugins", cfg!(feature = "plugins")),
+    (
+        "wasm-builtin-plugins",
+        cfg!(feature = "wasm-builtin-plugins"),
+    ),
+    (
+        "native-builtin-plugins",
+        cfg!(feature = "native-builtin-plugins"),
+    ),
+    ("web-server", cfg!(feature = "web-server")),
+];
+
+pub fn run_version(cli: &Cli) -> ExitCode {
+    println!("nginx-lint {}", env!("CARGO_PKG_VERSION"));
+    if !cli.verbose {
+        return ExitCode::SUCCESS;
+    }
+
+    let features: Vec<&str> = FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+    println!(
+        "features: {}",
+        if features.is_empty() {
+            "none".to_string()
+        } else {
+            features.join(", ")
+        }
+    );
+
+    #[cfg(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins"))]
+    println!(
+        "builtin plugins: {}",
+        nginx_lint::plugin::BUILTIN_PLUGIN_NAMES.len()
+    );
+
+    // The config a lint run from here would use
+    let config = match &cli.config {
+        Some(path) => LintConfig::from_file(path)
+            .ok()
+            .map(|config| (config, path.clone())),
+        None => LintConfig::find_and_load(Path::new(".")),
+    };
+    let policy = super::trust_policy(cli.trust_policy, config.as_ref().map(|(c, _)| c));
+    let source = match &config {
+        _ if cli.trust_policy.map(TrustPolicy::from) == Some(policy) => {
+            " (--trust-policy)".to_string()
+        }
+        Some((config, path)) if config.trust_policy() == Some(policy) => {
+            format!(" ({})", path.display())
+        }
+        _ => " (default)".to_string(),
+    };
+    println!("trust policy: {}{}", policy.as_str(), source);
+
+    ExitCode::SUCCESS
+}
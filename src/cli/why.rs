@@ -128,6 +128,11 @@ fn print_rule_doc(doc: &nginx_lint::docs::RuleDoc) {
     if let Some(range) = format_range(doc.min_nginx_version, doc.max_nginx_version) {
         eprintln!("{} {}", "Applies to:".bold(), range);
     }
+    eprintln!(
+        "{} {}",
+        "Presets:".bold(),
+        nginx_lint::RulePreset::describe_membership(doc.preset)
+    );
     eprintln!();
     eprintln!("{}", "Why:".bold());
     for line in doc.why.lines() {
@@ -158,8 +163,7 @@ fn print_rule_doc(doc: &nginx_lint::docs::RuleDoc) {
     eprintln!();
 }
 
-#[cfg(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins"))]
-fn print_rule_doc_owned(doc: &nginx_lint::docs::RuleDocOwned) {
+pub fn print_rule_doc_owned(doc: &nginx_lint::docs::RuleDocOwned) {
     use colored::Colorize;
 
     eprintln!();
@@ -181,6 +185,7 @@ fn print_rule_doc_owned(doc: &nginx_lint::docs::RuleDocOwned) {
     ) {
         eprintln!("{} {}", "Applies to:".bold(), range);
     }
+    eprintln!("{} {}", "Presets:".bold(), doc.presets());
     eprintln!();
     if !doc.why.is_empty() {
         eprintln!("{}", "Why:".bold());
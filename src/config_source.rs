@@ -0,0 +1,338 @@
+//! Where configuration files and the files they refer to are read from.
+//!
+//! Include resolution and the rules checking referenced files go through a
+//! [`ConfigSource`] instead of `std::fs`: [`RealFileSystem`] reads the disk,
+//! and [`MemoryFileSystem`] holds files given up front, for testing
+//! multi-file analyses without temporary directories. Other backends (a
+//! tarball, a snapshot in object storage) implement the same trait.
+//!
+//! ```
+//! use nginx_lint::config_source::{ConfigSource, FileStatus, MemoryFileSystem};
+//! use std::path::Path;
+//!
+//! let source = MemoryFileSystem::new()
+//!     .with_file("/etc/nginx/nginx.conf", "include conf.d/*.conf;\n")
+//!     .with_file("/etc/nginx/conf.d/app.conf", "server {}\n")
+//!     .with_unreadable_file("/etc/nginx/htpasswd");
+//!
+//! assert_eq!(source.glob("/etc/nginx/conf.d/*.conf"), [Path::new("/etc/nginx/conf.d/app.conf")]);
+//! assert!(source.exists(Path::new("/etc/nginx/conf.d")));
+//! assert_eq!(source.status(Path::new("/etc/nginx/htpasswd")), FileStatus::Unreadable);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// What nginx would find at a referenced path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    /// A regular file that can be opened for reading
+    Readable,
+    /// A regular file that cannot be opened, e.g. for lack of permission
+    Unreadable,
+    /// Something other than a regular file, e.g. a directory
+    NotAFile,
+    /// Nothing
+    Missing,
+}
+
+impl FileStatus {
+    /// What is wrong with the file, as in "file 'x' does not exist"; `None`
+    /// for a readable file
+    pub fn problem(self) -> Option<&'static str> {
+        match self {
+            FileStatus::Readable => None,
+            FileStatus::Unreadable => Some("is not readable"),
+            FileStatus::NotAFile => Some("is not a regular file"),
+            FileStatus::Missing => Some("does not exist"),
+        }
+    }
+}
+
+/// Files a configuration is read from and refers to
+pub trait ConfigSource: std::fmt::Debug + Send + Sync {
+    /// Contents of the file at `path`
+    fn read_file(&self, path: &Path) -> io::Result<String>;
+
+    /// Regular files matching the glob `pattern`, sorted; a pattern that is
+    /// not a valid glob is taken as a literal path
+    fn glob(&self, pattern: &str) -> Vec<PathBuf>;
+
+    /// Whether anything (file or directory) is at `path`
+    fn exists(&self, path: &Path) -> bool;
+
+    /// What is at `path`, for reporting referenced files
+    fn status(&self, path: &Path) -> FileStatus {
+        if !self.exists(path) {
+            return FileStatus::Missing;
+        }
+        match self.read_file(path) {
+            // Binary files (e.g. DER certificates) are not text, but readable
+            Ok(_) => FileStatus::Readable,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => FileStatus::Readable,
+            Err(e) if e.kind() == io::ErrorKind::IsADirectory => FileStatus::NotAFile,
+            Err(_) => FileStatus::Unreadable,
+        }
+    }
+
+    /// The path with `.` and `..` resolved, identifying the file when it is
+    /// reached through different paths; fails if nothing is there
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if self.exists(path) {
+            Ok(normalize(path))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            ))
+        }
+    }
+}
+
+/// The files on disk
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFileSystem;
+
+impl ConfigSource for RealFileSystem {
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn glob(&self, pattern: &str) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = match glob::glob(pattern) {
+            Ok(entries) => entries.flatten().filter(|p| p.is_file()).collect(),
+            Err(_) => {
+                let literal = PathBuf::from(pattern);
+                if literal.is_file() {
+                    vec![literal]
+                } else {
+                    Vec::new()
+                }
+            }
+        };
+        paths.sort();
+        paths
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn status(&self, path: &Path) -> FileStatus {
+        match std::fs::metadata(path) {
+            Err(_) => FileStatus::Missing,
+            Ok(metadata) if !metadata.is_file() => FileStatus::NotAFile,
+            Ok(_) if std::fs::File::open(path).is_err() => FileStatus::Unreadable,
+            Ok(_) => FileStatus::Readable,
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+}
+
+/// A file of a [`MemoryFileSystem`]
+#[derive(Debug, Clone)]
+enum Entry {
+    File(String),
+    /// Readable, but not held as text (binary or too large)
+    Binary,
+    Unreadable,
+}
+
+/// Files given up front; the directories containing them exist as well.
+/// Paths are taken with `.` and `..` resolved and should be absolute.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFileSystem {
+    files: BTreeMap<PathBuf, Entry>,
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file holding `contents`
+    pub fn with_file(self, path: impl AsRef<Path>, contents: impl Into<String>) -> Self {
+        self.with_entry(path.as_ref(), Entry::File(contents.into()))
+    }
+
+    /// Add a file that is readable but whose contents are not text, such as
+    /// a DER certificate; reading it fails with
+    /// [`InvalidData`](io::ErrorKind::InvalidData)
+    pub fn with_binary_file(self, path: impl AsRef<Path>) -> Self {
+        self.with_entry(path.as_ref(), Entry::Binary)
+    }
+
+    /// Add a file that exists but cannot be read
+    pub fn with_unreadable_file(self, path: impl AsRef<Path>) -> Self {
+        self.with_entry(path.as_ref(), Entry::Unreadable)
+    }
+
+    /// Add an empty directory
+    pub fn with_dir(mut self, path: impl AsRef<Path>) -> Self {
+        self.add_dirs(&normalize(path.as_ref()));
+        self
+    }
+
+    fn with_entry(mut self, path: &Path, entry: Entry) -> Self {
+        let path = normalize(path);
+        if let Some(parent) = path.parent() {
+            self.add_dirs(parent);
+        }
+        self.files.insert(path, entry);
+        self
+    }
+
+    fn add_dirs(&mut self, dir: &Path) {
+        self.dirs.extend(dir.ancestors().map(Path::to_path_buf));
+    }
+}
+
+impl ConfigSource for MemoryFileSystem {
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        let path = normalize(path);
+        match self.files.get(&path) {
+            Some(Entry::File(contents)) => Ok(contents.clone()),
+            Some(Entry::Binary) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a text file", path.display()),
+            )),
+            Some(Entry::Unreadable) => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is not readable", path.display()),
+            )),
+            None if self.dirs.contains(&path) => Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("{} is a directory", path.display()),
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            )),
+        }
+    }
+
+    fn glob(&self, pattern: &str) -> Vec<PathBuf> {
+        let pattern = normalize(Path::new(pattern));
+        let Ok(glob) = glob::Pattern::new(&pattern.to_string_lossy()) else {
+            let literal = pattern;
+            return if self.files.contains_key(&literal) {
+                vec![literal]
+            } else {
+                Vec::new()
+            };
+        };
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            require_literal_leading_dot: true,
+            ..glob::MatchOptions::new()
+        };
+        // BTreeMap keys are already sorted
+        self.files
+            .keys()
+            .filter(|path| glob.matches_path_with(path, options))
+            .cloned()
+            .collect()
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        self.files.contains_key(&path) || self.dirs.contains(&path)
+    }
+}
+
+/// `path` with `.` and `..` components resolved lexically
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_file_system() {
+        let source = MemoryFileSystem::new()
+            .with_file("/etc/nginx/nginx.conf", "events {}\n")
+            .with_file("/etc/nginx/conf.d/a.conf", "")
+            .with_file("/etc/nginx/conf.d/.hidden.conf", "")
+            .with_file("/etc/nginx/conf.d/sub/b.conf", "")
+            .with_binary_file("/etc/nginx/cert.der")
+            .with_dir("/etc/nginx/empty");
+
+        assert_eq!(
+            source
+                .read_file(Path::new("/etc/nginx/conf.d/../nginx.conf"))
+                .unwrap(),
+            "events {}\n"
+        );
+        assert_eq!(
+            source.glob("/etc/nginx/conf.d/*.conf"),
+            [PathBuf::from("/etc/nginx/conf.d/a.conf")]
+        );
+        // Invalid patterns are literal paths
+        assert_eq!(source.glob("/etc/nginx/[nginx.conf"), Vec::<PathBuf>::new());
+        assert!(source.exists(Path::new("/etc/nginx/empty")));
+        assert_eq!(
+            source.status(Path::new("/etc/nginx/cert.der")),
+            FileStatus::Readable
+        );
+        assert_eq!(
+            source.status(Path::new("/etc/nginx/empty")),
+            FileStatus::NotAFile
+        );
+        assert_eq!(
+            source.status(Path::new("/etc/nginx/missing.conf")),
+            FileStatus::Missing
+        );
+        assert_eq!(
+            source
+                .canonicalize(Path::new("/etc/nginx/./conf.d/a.conf"))
+                .unwrap(),
+            Path::new("/etc/nginx/conf.d/a.conf")
+        );
+    }
+
+    #[test]
+    fn test_real_file_system() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("b.conf"), "").unwrap();
+        std::fs::write(temp.path().join("a.conf"), "").unwrap();
+        std::fs::create_dir(temp.path().join("dir.conf")).unwrap();
+
+        let source = RealFileSystem;
+        let pattern = format!("{}/*.conf", temp.path().display());
+        assert_eq!(
+            source.glob(&pattern),
+            [temp.path().join("a.conf"), temp.path().join("b.conf")]
+        );
+        assert_eq!(
+            source.status(&temp.path().join("a.conf")),
+            FileStatus::Readable
+        );
+        assert_eq!(
+            source.status(&temp.path().join("dir.conf")),
+            FileStatus::NotAFile
+        );
+        assert_eq!(
+            source.status(&temp.path().join("missing")),
+            FileStatus::Missing
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Configuration trees dumped by crossplane.
+//!
+//! [`read_crossplane`] takes the JSON printed by `crossplane parse` (the
+//! parser nginx publishes in Python) and turns every file of it back into
+//! nginx source, held in a [`MemoryFileSystem`], so the tree is linted
+//! without reading the configuration on disk. Each directive is written on
+//! the line crossplane recorded, so diagnostics point at the original
+//! lines; columns and formatting are those of the generated text.
+//!
+//! ```
+//! use nginx_lint::config_source::ConfigSource;
+//! use nginx_lint::crossplane::read_crossplane;
+//!
+//! let dump = r#"{"status": "ok", "errors": [], "config": [{
+//!     "file": "/etc/nginx/nginx.conf", "status": "ok", "errors": [],
+//!     "parsed": [{"directive": "events", "line": 1, "args": [], "block": []},
+//!                {"directive": "user", "line": 3, "args": ["nginx"]}]}]}"#;
+//! let tree = read_crossplane(dump).unwrap();
+//! assert_eq!(tree.root.to_str(), Some("/etc/nginx/nginx.conf"));
+//! assert_eq!(
+//!     tree.source.read_file(&tree.root).unwrap(),
+//!     "events {\n}\nuser nginx;\n"
+//! );
+//! ```
+
+use crate::config_source::MemoryFileSystem;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Output of `crossplane parse`
+#[derive(Deserialize)]
+struct Payload {
+    #[serde(default)]
+    errors: Vec<PayloadError>,
+    config: Vec<FileEntry>,
+}
+
+#[derive(Deserialize)]
+struct PayloadError {
+    file: Option<String>,
+    line: Option<usize>,
+    error: String,
+}
+
+/// One parsed file; the first one is the file crossplane was given
+#[derive(Deserialize)]
+struct FileEntry {
+    file: String,
+    #[serde(default)]
+    parsed: Vec<Directive>,
+}
+
+#[derive(Deserialize)]
+struct Directive {
+    directive: String,
+    #[serde(default)]
+    line: usize,
+    #[serde(default)]
+    args: Vec<String>,
+    block: Option<Vec<Directive>>,
+    /// Text after `#`, for comments (`directive` is `#`)
+    comment: Option<String>,
+}
+
+/// Rules about the layout of the source, which the generated text does not
+/// share with the original and which are turned off for a dump
+pub const FORMATTING_RULES: &[&str] = &["indent", "trailing-whitespace", "space-before-semicolon"];
+
+/// The files of a crossplane dump
+#[derive(Debug)]
+pub struct CrossplaneTree {
+    /// The files, as nginx source
+    pub source: MemoryFileSystem,
+    /// The file crossplane parsed first, which includes the others
+    pub root: PathBuf,
+    /// Errors crossplane reported (`file:line: error`); the files it could
+    /// not parse are missing or incomplete
+    pub errors: Vec<String>,
+}
+
+/// Read the JSON printed by `crossplane parse`.
+///
+/// Relative paths (crossplane run on a relative path) are taken from `/`, so
+/// they keep resolving against each other.
+pub fn read_crossplane(json: &str) -> Result<CrossplaneTree, String> {
+    let payload: Payload =
+        serde_json::from_str(json).map_err(|e| format!("not a crossplane dump: {}", e))?;
+    let root = payload
+        .config
+        .first()
+        .map(|entry| absolute(&entry.file))
+        .ok_or_else(|| "the crossplane dump has no files".to_string())?;
+
+    let mut source = MemoryFileSystem::new();
+    for entry in &payload.config {
+        let mut writer = Writer::new();
+        writer.block(&entry.parsed, 0, None);
+        source = source.with_file(absolute(&entry.file), writer.finish());
+    }
+    let errors = payload
+        .errors
+        .iter()
+        .map(|e| match (&e.file, e.line) {
+            (Some(file), Some(line)) => format!("{}:{}: {}", file, line, e.error),
+            (Some(file), None) => format!("{}: {}", file, e.error),
+            (None, _) => e.error.clone(),
+        })
+        .collect();
+    Ok(CrossplaneTree {
+        source,
+        root,
+        errors,
+    })
+}
+
+fn absolute(file: &str) -> PathBuf {
+    Path::new("/").join(file)
+}
+
+/// Writes directives at their lines
+struct Writer {
+    out: String,
+    /// Line being written, 1-based
+    line: usize,
+    /// The line ends with a comment, so nothing more can go on it
+    in_comment: bool,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Self {
+            out: String::new(),
+            line: 1,
+            in_comment: false,
+        }
+    }
+
+    /// Write `text` on `line`, or on the current line if it is already past
+    /// it
+    fn write_at(&mut self, line: usize, depth: usize, text: &str) {
+        let newlines = if self.out.is_empty() {
+            line.saturating_sub(1)
+        } else if line > self.line || self.in_comment {
+            line.saturating_sub(self.line).max(1)
+        } else {
+            0
+        };
+        if newlines > 0 || self.out.is_empty() {
+            self.out.push_str(&"\n".repeat(newlines));
+            self.out.push_str(&"    ".repeat(depth));
+            self.line += newlines;
+        } else {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+        self.line += text.matches('\n').count();
+        self.in_comment = false;
+    }
+
+    /// Write `directives`; `next_line` is the line of what follows the
+    /// block, if known, which the closing brace should leave free
+    fn block(&mut self, directives: &[Directive], depth: usize, next_line: Option<usize>) {
+        for (i, directive) in directives.iter().enumerate() {
+            let next = directives.get(i + 1).map(|d| d.line).or(next_line);
+            self.directive(directive, depth, next);
+        }
+    }
+
+    fn directive(&mut self, directive: &Directive, depth: usize, next_line: Option<usize>) {
+        if let Some(comment) = &directive.comment {
+            self.write_at(directive.line, depth, &format!("#{}", comment));
+            self.in_comment = true;
+            return;
+        }
+
+        let mut text = directive.directive.clone();
+        // crossplane gives the code of *_by_lua_block as one argument
+        if directive.directive.ends_with("_by_lua_block")
+            && directive.block.is_none()
+            && directive.args.len() == 1
+        {
+            text.push_str(&format!(" {{{}}}", directive.args[0]));
+            self.write_at(directive.line, depth, &text);
+            return;
+        }
+        for arg in &directive.args {
+            text.push(' ');
+            text.push_str(&quote(arg));
+        }
+        let Some(block) = &directive.block else {
+            text.push(';');
+            self.write_at(directive.line, depth, &text);
+            return;
+        };
+        if block.is_empty() && next_line.is_some_and(|next| next <= directive.line + 1) {
+            text.push_str(" {}");
+            self.write_at(directive.line, depth, &text);
+            return;
+        }
+        text.push_str(" {");
+        self.write_at(directive.line, depth, &text);
+        self.block(block, depth + 1, next_line);
+        // `}` on a line of its own, unless what follows is on the next line
+        let close_line = match next_line {
+            Some(next) if next <= self.line + 1 => self.line,
+            _ => self.line + 1,
+        };
+        self.write_at(close_line, depth, "}");
+    }
+
+    fn finish(mut self) -> String {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+}
+
+/// `arg` as an nginx token: quoted if it is empty or holds characters that
+/// would end or split it. crossplane keeps the backslash escapes of the
+/// source, so only bare quotes need escaping.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && is_bare_token(arg) {
+        return arg.to_string();
+    }
+    if !arg.contains('"') {
+        format!("\"{}\"", arg)
+    } else if !arg.contains('\'') {
+        format!("'{}'", arg)
+    } else {
+        let mut quoted = String::from("\"");
+        let mut escaped = false;
+        for c in arg.chars() {
+            if c == '"' && !escaped {
+                quoted.push('\\');
+            }
+            escaped = c == '\\' && !escaped;
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    }
+}
+
+/// Whether `arg` can be written unquoted: no whitespace, `;`, quotes, nor
+/// braces other than those of `${name}` variables
+fn is_bare_token(arg: &str) -> bool {
+    let mut rest = arg;
+    let mut without = String::new();
+    while let Some(start) = rest.find("${") {
+        without.push_str(&rest[..start]);
+        match rest[start..].find('}') {
+            Some(end) => rest = &rest[start + end + 1..],
+            None => return false,
+        }
+    }
+    without.push_str(rest);
+    !without
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, ';' | '"' | '\'' | '{' | '}'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_source::ConfigSource;
+
+    fn source_of(parsed: &str) -> String {
+        let dump = format!(
+            r#"{{"status": "ok", "errors": [], "config": [{{"file": "nginx.conf", "status": "ok", "errors": [], "parsed": {}}}]}}"#,
+            parsed
+        );
+        let tree = read_crossplane(&dump).unwrap();
+        assert_eq!(tree.root, Path::new("/nginx.conf"));
+        tree.source.read_file(&tree.root).unwrap()
+    }
+
+    #[test]
+    fn test_lines_are_kept() {
+        let source = source_of(
+            r##"[
+                {"directive": "#", "line": 1, "args": [], "comment": " main"},
+                {"directive": "http", "line": 3, "args": [], "block": [
+                    {"directive": "server", "line": 4, "args": [], "block": [
+                        {"directive": "listen", "line": 5, "args": ["80"]},
+                        {"directive": "#", "line": 5, "args": [], "comment": " plain"}
+                    ]},
+                    {"directive": "server", "line": 8, "args": [], "block": []}
+                ]}
+            ]"##,
+        );
+        assert_eq!(
+            source,
+            "# main\n\nhttp {\n    server {\n        listen 80; # plain\n    }\n\n    server {\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_first_directive_not_on_line_1() {
+        assert_eq!(
+            source_of(r#"[{"directive": "user", "line": 3, "args": ["nginx"]}]"#),
+            "\n\nuser nginx;\n"
+        );
+    }
+
+    #[test]
+    fn test_closing_brace_on_the_line_of_what_follows() {
+        assert_eq!(
+            source_of(
+                r#"[{"directive": "events", "line": 1, "args": [], "block": []},
+                    {"directive": "user", "line": 1, "args": ["nginx"]}]"#
+            ),
+            "events {} user nginx;\n"
+        );
+    }
+
+    #[test]
+    fn test_arguments_are_quoted_when_needed() {
+        assert_eq!(quote("80"), "80");
+        assert_eq!(quote("${host}$request_uri"), "${host}$request_uri");
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(quote("a b"), "\"a b\"");
+        assert_eq!(quote("^/(a|b){2}$"), "\"^/(a|b){2}$\"");
+        assert_eq!(quote(r#"say "hi""#), r#"'say "hi"'"#);
+        assert_eq!(quote(r#"it's "x" \"y\""#), r#""it's \"x\" \"y\"""#);
+    }
+
+    #[test]
+    fn test_lua_block() {
+        assert_eq!(
+            source_of(
+                r#"[{"directive": "content_by_lua_block", "line": 1, "args": ["\n    ngx.say(1)\n"]}]"#
+            ),
+            "content_by_lua_block {\n    ngx.say(1)\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_errors_and_invalid_dump() {
+        let tree = read_crossplane(
+            r#"{"status": "failed", "errors": [{"file": "/etc/nginx/a.conf", "line": 2, "error": "unexpected \"}\""}],
+                "config": [{"file": "/etc/nginx/nginx.conf", "parsed": []}]}"#,
+        )
+        .unwrap();
+        assert_eq!(tree.errors, ["/etc/nginx/a.conf:2: unexpected \"}\""]);
+        assert!(read_crossplane("{}").is_err());
+        assert!(read_crossplane(r#"{"config": []}"#).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+//! Semantic comparison of two configurations (`nginx-lint diff`).
+//!
+//! [`diff_configs`] compares the directives of two parsed files block by
+//! block, ignoring what does not change nginx's behavior as far as the
+//! syntax goes: whitespace, comments, blank lines, quoting and the order of
+//! directives within a block. Blocks are matched by name and arguments
+//! (`server` blocks by their `server_name`), so a change deep in a large
+//! generated file is reported with the blocks it is in rather than as a
+//! textual hunk.
+//!
+//! ```
+//! use nginx_lint::diff::{ChangeKind, diff_configs};
+//! use nginx_lint::parser::parse_string;
+//!
+//! let old = parse_string("http {\n    server {\n        server_name a;\n        listen 80;\n    }\n}\n").unwrap();
+//! let new = parse_string("http {\n  server {\n    listen 8080; # moved\n    server_name \"a\";\n  }\n}\n").unwrap();
+//! let changes = diff_configs(&old, &new);
+//! assert_eq!(changes.len(), 1);
+//! assert_eq!(changes[0].kind, ChangeKind::Modified);
+//! assert_eq!(changes[0].context, ["http", "server [a]"]);
+//! assert_eq!(changes[0].to_string(), "~ http > server [a]: listen 80; -> listen 8080;");
+//! ```
+
+use crate::parser::ast::{Block, Config, ConfigItem, Directive};
+use std::fmt;
+
+/// How a directive differs between the two configurations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Only in the new configuration
+    Added,
+    /// Only in the old configuration
+    Removed,
+    /// Paired with a directive of the same name (and first argument, for
+    /// repeated directives) with other arguments or another raw block body
+    Modified,
+}
+
+impl ChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Modified => "modified",
+        }
+    }
+
+    fn marker(self) -> char {
+        match self {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Modified => '~',
+        }
+    }
+}
+
+/// A directive as written in one of the configurations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveText {
+    /// The directive with its arguments as written, e.g. `listen 80;` or
+    /// `location /api {` for a block
+    pub text: String,
+    pub line: usize,
+}
+
+/// A difference between the two configurations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// Labels of the enclosing blocks, outermost first (e.g. `["http",
+    /// "server [example.com]", "location /api"]`)
+    pub context: Vec<String>,
+    /// The directive in the old configuration (removed or modified)
+    pub old: Option<DirectiveText>,
+    /// The directive in the new configuration (added or modified)
+    pub new: Option<DirectiveText>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.kind.marker())?;
+        if !self.context.is_empty() {
+            write!(f, "{}: ", self.context.join(" > "))?;
+        }
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "{} -> {}", old.text, new.text),
+            (Some(directive), None) | (None, Some(directive)) => write!(f, "{}", directive.text),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// The differences from `old` to `new`, block by block in the order of the
+/// blocks in `new` (then of the blocks only in `old`).
+///
+/// Within a block, directives are compared as a multiset of names with
+/// argument values: reordering them is not a change, and a directive whose
+/// name appears once on each side with other arguments, or a repeated one
+/// whose first argument does, is [`Modified`](ChangeKind::Modified). Blocks only on one side are reported
+/// once, as the block directive, not directive by directive.
+pub fn diff_configs(old: &Config, new: &Config) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_items(&old.items, &new.items, &mut Vec::new(), &mut changes);
+    changes
+}
+
+fn diff_items(
+    old: &[ConfigItem],
+    new: &[ConfigItem],
+    context: &mut Vec<String>,
+    changes: &mut Vec<Change>,
+) {
+    let (old_simple, old_blocks) = split(old);
+    let (new_simple, new_blocks) = split(new);
+
+    // Simple directives (and raw blocks), grouped by name
+    let mut names: Vec<&str> = Vec::new();
+    for d in new_simple.iter().chain(&old_simple) {
+        if !names.contains(&d.name.as_str()) {
+            names.push(&d.name);
+        }
+    }
+    for name in names {
+        let mut old_rest: Vec<&Directive> = old_simple
+            .iter()
+            .copied()
+            .filter(|d| d.name == name)
+            .collect();
+        let mut new_rest: Vec<&Directive> = Vec::new();
+        for d in new_simple.iter().copied().filter(|d| d.name == name) {
+            match old_rest.iter().position(|o| same_directive(o, d)) {
+                Some(i) => {
+                    old_rest.remove(i);
+                }
+                None => new_rest.push(d),
+            }
+        }
+        if let ([old], [new]) = (old_rest.as_slice(), new_rest.as_slice()) {
+            changes.push(change(ChangeKind::Modified, context, Some(old), Some(new)));
+            continue;
+        }
+        // Repeated directives (add_header, proxy_set_header) pair up by
+        // their first argument when it is unique on both sides
+        let unique = |rest: &[&Directive], arg: &str| {
+            rest.iter().filter(|d| d.first_arg() == Some(arg)).count() == 1
+        };
+        let old_all = old_rest.clone();
+        let mut unpaired = Vec::new();
+        for old in old_rest {
+            let pair = old
+                .first_arg()
+                .filter(|arg| unique(&old_all, arg) && unique(&new_rest, arg))
+                .and_then(|arg| new_rest.iter().position(|new| new.first_arg() == Some(arg)));
+            match pair {
+                Some(i) => {
+                    let new = new_rest.remove(i);
+                    changes.push(change(ChangeKind::Modified, context, Some(old), Some(new)));
+                }
+                None => unpaired.push(old),
+            }
+        }
+        for d in unpaired {
+            changes.push(change(ChangeKind::Removed, context, Some(d), None));
+        }
+        for d in new_rest {
+            changes.push(change(ChangeKind::Added, context, None, Some(d)));
+        }
+    }
+
+    // Blocks, matched by key
+    let old_keyed = keyed(&old_blocks);
+    let new_keyed = keyed(&new_blocks);
+    for (key, new_block) in &new_keyed {
+        match old_keyed.iter().find(|(k, _)| k == key) {
+            Some((_, old_block)) => {
+                context.push(label(new_block));
+                diff_items(
+                    &block_of(old_block).items,
+                    &block_of(new_block).items,
+                    context,
+                    changes,
+                );
+                context.pop();
+            }
+            None => changes.push(change(ChangeKind::Added, context, None, Some(new_block))),
+        }
+    }
+    for (key, old_block) in &old_keyed {
+        if !new_keyed.iter().any(|(k, _)| k == key) {
+            changes.push(change(ChangeKind::Removed, context, Some(old_block), None));
+        }
+    }
+}
+
+/// Directives of `items`: those compared as a whole (simple directives and
+/// raw blocks), and blocks to descend into
+fn split(items: &[ConfigItem]) -> (Vec<&Directive>, Vec<&Directive>) {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            ConfigItem::Directive(d) => Some(d.as_ref()),
+            _ => None,
+        })
+        .partition(|d| d.block.as_ref().is_none_or(Block::is_raw))
+}
+
+/// Blocks with the key they are matched by: name, argument values, the
+/// `server_name`s of a `server`, and how many blocks with the same key
+/// come before it
+fn keyed<'a>(blocks: &[&'a Directive]) -> Vec<(Vec<String>, &'a Directive)> {
+    let mut keyed: Vec<(Vec<String>, &Directive)> = Vec::new();
+    for &block in blocks {
+        let mut key: Vec<String> = std::iter::once(block.name.clone())
+            .chain(block.args.iter().map(|a| a.as_str().to_string()))
+            .chain(server_names(block))
+            .collect();
+        let occurrence = keyed
+            .iter()
+            .filter(|(k, _)| k[..k.len() - 1] == key[..])
+            .count();
+        key.push(occurrence.to_string());
+        keyed.push((key, block));
+    }
+    keyed
+}
+
+/// Values of the `server_name` directives of a `server` block
+fn server_names(directive: &Directive) -> Vec<String> {
+    if directive.name != "server" {
+        return Vec::new();
+    }
+    block_of(directive)
+        .directives()
+        .filter(|d| d.name == "server_name")
+        .flat_map(|d| d.args.iter().map(|a| a.as_str().to_string()))
+        .collect()
+}
+
+fn block_of(directive: &Directive) -> &Block {
+    directive.block.as_ref().expect("block directive")
+}
+
+/// How a block is shown in [`Change::context`]: name and arguments as
+/// written, with the `server_name`s of a `server` in brackets
+fn label(directive: &Directive) -> String {
+    let mut label = head(directive);
+    let names = server_names(directive);
+    if !names.is_empty() {
+        label.push_str(&format!(" [{}]", names.join(" ")));
+    }
+    label
+}
+
+/// Name and arguments as written
+fn head(directive: &Directive) -> String {
+    std::iter::once(directive.name.as_str())
+        .chain(directive.args.iter().map(|a| a.raw.as_str()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Same name, argument values and raw block body (ignoring indentation and
+/// blank lines)
+fn same_directive(a: &Directive, b: &Directive) -> bool {
+    a.name == b.name
+        && a.args.len() == b.args.len()
+        && a.args
+            .iter()
+            .zip(&b.args)
+            .all(|(x, y)| x.as_str() == y.as_str())
+        && raw_lines(a) == raw_lines(b)
+}
+
+/// Non-blank lines of a raw block body, trimmed
+fn raw_lines(directive: &Directive) -> Option<Vec<&str>> {
+    let raw = directive.block.as_ref()?.raw_content.as_deref()?;
+    Some(
+        raw.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect(),
+    )
+}
+
+fn change(
+    kind: ChangeKind,
+    context: &[String],
+    old: Option<&Directive>,
+    new: Option<&Directive>,
+) -> Change {
+    let text = |d: &Directive| DirectiveText {
+        text: match &d.block {
+            Some(_) => format!("{} {{", head(d)),
+            None => format!("{};", head(d)),
+        },
+        line: d.span.start.line,
+    };
+    Change {
+        kind,
+        context: context.to_vec(),
+        old: old.map(text),
+        new: new.map(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_string;
+
+    fn diff(old: &str, new: &str) -> Vec<String> {
+        diff_configs(&parse_string(old).unwrap(), &parse_string(new).unwrap())
+            .iter()
+            .map(Change::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_formatting_comments_and_order_are_ignored() {
+        let old = "# old\nhttp {\n    gzip on;\n    server {\n        listen 80;\n        server_name a;\n    }\n}\n";
+        let new =
+            "http {\n  server { server_name 'a'; listen \"80\"; }\n\n  gzip on; # still on\n}\n";
+        assert!(diff(old, new).is_empty());
+    }
+
+    #[test]
+    fn test_changes_in_context() {
+        let old = "http {\n    server {\n        server_name a;\n        add_header X-A 1;\n        add_header X-B 2;\n        location /old {\n        }\n    }\n    server {\n        server_name b;\n        listen 80;\n    }\n}\n";
+        let new = "http {\n    server {\n        server_name a;\n        add_header X-A 1;\n        add_header X-C 3;\n        location /api {\n            proxy_pass http://app;\n        }\n    }\n    server {\n        server_name b;\n        listen 8080;\n    }\n}\n";
+        assert_eq!(
+            diff(old, new),
+            [
+                "~ http > server [a]: add_header X-B 2; -> add_header X-C 3;",
+                "+ http > server [a]: location /api {",
+                "- http > server [a]: location /old {",
+                "~ http > server [b]: listen 80; -> listen 8080;",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeated_directives_and_unnamed_blocks() {
+        let old = "http {\n    add_header A 1;\n    add_header B 2;\n}\nevents {\n}\n";
+        let new = "http {\n    add_header A 9;\n    add_header B 8;\n    add_header B 2;\n}\n";
+        assert_eq!(
+            diff(old, new),
+            [
+                "~ http: add_header A 1; -> add_header A 9;",
+                "+ http: add_header B 8;",
+                "- events {",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw_blocks() {
+        let old = "location / {\n    content_by_lua_block {\n        ngx.say(1)\n    }\n}\n";
+        let same = "location / {\n  content_by_lua_block {\n    ngx.say(1)\n\n  }\n}\n";
+        let new = "location / {\n    content_by_lua_block {\n        ngx.say(2)\n    }\n}\n";
+        assert!(diff(old, same).is_empty());
+        let changes = diff_configs(&parse_string(old).unwrap(), &parse_string(new).unwrap());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Modified);
+        assert_eq!(changes[0].context, ["location /"]);
+        assert_eq!(changes[0].new.as_ref().unwrap().line, 2);
+    }
+}
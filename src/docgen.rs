@@ -0,0 +1,410 @@
+//! Rule documentation pages
+//!
+//! Renders [`RuleDocOwned`] entries into one Markdown or HTML page per rule
+//! plus an index page, and checks that each rule's examples behave as
+//! documented: the bad example triggers the rule and the good example does
+//! not.
+
+use crate::config::LintConfig;
+use crate::docs::RuleDocOwned;
+use crate::linter::{LintError, LintRule, Linter};
+use crate::parser::parse_string_with_errors;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Rules whose examples refer to files on disk or need an external tool, so
+/// they cannot be checked from the example text alone
+const FILESYSTEM_RULES: &[&str] = &[
+    "include-path-exists",
+    "include-cycle",
+    "precompressed-assets-not-served",
+    "include-layering",
+    "lua-block-lint",
+    "auth-basic-user-file-missing",
+    "rego-policy",
+    "duplicate-default-server",
+];
+
+/// Output format of the generated pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    /// File extension of the generated pages
+    pub fn extension(self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        }
+    }
+}
+
+/// A generated page
+#[derive(Debug, Clone)]
+pub struct DocPage {
+    /// File name relative to the output directory (e.g. `indent.md`)
+    pub file_name: String,
+    /// Page content
+    pub content: String,
+}
+
+/// Render one page per rule plus an `index` page linking them, grouped by
+/// category
+pub fn render_pages(docs: &[RuleDocOwned], format: DocFormat) -> Vec<DocPage> {
+    let ext = format.extension();
+    let mut pages: Vec<DocPage> = docs
+        .iter()
+        .map(|doc| DocPage {
+            file_name: format!("{}.{}", doc.name, ext),
+            content: match format {
+                DocFormat::Markdown => render_markdown(doc),
+                DocFormat::Html => render_html(doc),
+            },
+        })
+        .collect();
+    pages.push(DocPage {
+        file_name: format!("index.{}", ext),
+        content: match format {
+            DocFormat::Markdown => render_markdown_index(docs),
+            DocFormat::Html => render_html_index(docs),
+        },
+    });
+    pages
+}
+
+/// Categories in display order: the builtin ones, then any declared only by
+/// plugins
+fn categories(docs: &[RuleDocOwned]) -> Vec<&str> {
+    let mut categories: Vec<&str> = crate::RULE_CATEGORIES
+        .iter()
+        .copied()
+        .filter(|category| docs.iter().any(|doc| doc.category == *category))
+        .collect();
+    for doc in docs {
+        if !categories.contains(&doc.category.as_str()) {
+            categories.push(&doc.category);
+        }
+    }
+    categories
+}
+
+/// The nginx versions a rule applies to, if it declares any
+fn applies_to(doc: &RuleDocOwned) -> Option<String> {
+    nginx_lint_common::nginx_version::format_range(
+        doc.min_nginx_version.as_deref(),
+        doc.max_nginx_version.as_deref(),
+    )
+}
+
+fn render_markdown(doc: &RuleDocOwned) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", doc.name);
+    let _ = writeln!(out, "{}\n", doc.description);
+    let _ = writeln!(out, "- **Category:** {}", doc.category);
+    let _ = writeln!(out, "- **Severity:** {}", doc.severity);
+    if let Some(range) = applies_to(doc) {
+        let _ = writeln!(out, "- **Applies to:** {}", range);
+    }
+    let _ = writeln!(out, "- **Presets:** {}", doc.presets());
+    if !doc.why.is_empty() {
+        let _ = writeln!(out, "\n## Why\n\n{}", doc.why.trim_end());
+    }
+    if !doc.bad_example.is_empty() {
+        let _ = writeln!(
+            out,
+            "\n## Bad Example\n\n```nginx\n{}\n```",
+            doc.bad_example.trim_end()
+        );
+    }
+    if !doc.good_example.is_empty() {
+        let _ = writeln!(
+            out,
+            "\n## Good Example\n\n```nginx\n{}\n```",
+            doc.good_example.trim_end()
+        );
+    }
+    if !doc.references.is_empty() {
+        out.push_str("\n## References\n\n");
+        for reference in &doc.references {
+            let _ = writeln!(out, "- <{}>", reference);
+        }
+    }
+    out
+}
+
+fn render_markdown_index(docs: &[RuleDocOwned]) -> String {
+    let mut out = String::from("# Rules\n");
+    for category in categories(docs) {
+        let _ = writeln!(out, "\n## {}\n", category);
+        out.push_str("| Rule | Severity | Description |\n");
+        out.push_str("|------|----------|-------------|\n");
+        for doc in docs.iter().filter(|doc| doc.category == category) {
+            let _ = writeln!(
+                out,
+                "| [{}]({}.md) | {} | {} |",
+                doc.name,
+                doc.name,
+                doc.severity,
+                doc.description.replace('|', "\\|")
+            );
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+fn render_html(doc: &RuleDocOwned) -> String {
+    let mut body = String::new();
+    let _ = writeln!(body, "<h1>{}</h1>", escape_html(&doc.name));
+    let _ = writeln!(body, "<p>{}</p>", escape_html(&doc.description));
+    body.push_str("<ul>\n");
+    let _ = writeln!(
+        body,
+        "<li><strong>Category:</strong> {}</li>",
+        escape_html(&doc.category)
+    );
+    let _ = writeln!(
+        body,
+        "<li><strong>Severity:</strong> {}</li>",
+        escape_html(&doc.severity)
+    );
+    if let Some(range) = applies_to(doc) {
+        let _ = writeln!(
+            body,
+            "<li><strong>Applies to:</strong> {}</li>",
+            escape_html(&range)
+        );
+    }
+    let _ = writeln!(
+        body,
+        "<li><strong>Presets:</strong> {}</li>",
+        escape_html(&doc.presets())
+    );
+    body.push_str("</ul>\n");
+    if !doc.why.is_empty() {
+        body.push_str("<h2>Why</h2>\n");
+        for paragraph in doc.why.split("\n\n") {
+            let _ = writeln!(body, "<p>{}</p>", escape_html(paragraph.trim()));
+        }
+    }
+    for (title, example) in [
+        ("Bad Example", &doc.bad_example),
+        ("Good Example", &doc.good_example),
+    ] {
+        if !example.is_empty() {
+            let _ = writeln!(
+                body,
+                "<h2>{}</h2>\n<pre><code class=\"language-nginx\">{}</code></pre>",
+                title,
+                escape_html(example.trim_end())
+            );
+        }
+    }
+    if !doc.references.is_empty() {
+        body.push_str("<h2>References</h2>\n<ul>\n");
+        for reference in &doc.references {
+            let reference = escape_html(reference);
+            let _ = writeln!(body, "<li><a href=\"{}\">{}</a></li>", reference, reference);
+        }
+        body.push_str("</ul>\n");
+    }
+    body.push_str("<p><a href=\"index.html\">All rules</a></p>\n");
+    html_page(&doc.name, &body)
+}
+
+fn render_html_index(docs: &[RuleDocOwned]) -> String {
+    let mut body = String::from("<h1>Rules</h1>\n");
+    for category in categories(docs) {
+        let _ = writeln!(body, "<h2>{}</h2>", escape_html(category));
+        body.push_str("<table>\n<tr><th>Rule</th><th>Severity</th><th>Description</th></tr>\n");
+        for doc in docs.iter().filter(|doc| doc.category == category) {
+            let name = escape_html(&doc.name);
+            let _ = writeln!(
+                body,
+                "<tr><td><a href=\"{}.html\">{}</a></td><td>{}</td><td>{}</td></tr>",
+                name,
+                name,
+                escape_html(&doc.severity),
+                escape_html(&doc.description)
+            );
+        }
+        body.push_str("</table>\n");
+    }
+    html_page("Rules", &body)
+}
+
+/// A linter running every builtin rule, including those disabled by
+/// default, plus `plugins`, for checking examples
+pub fn example_linter(plugins: Vec<Box<dyn LintRule>>) -> Linter {
+    let config: String = LintConfig::DISABLED_BY_DEFAULT
+        .iter()
+        .map(|name| format!("[rules.{}]\nenabled = true\n", name))
+        .collect();
+    let config = LintConfig::parse(&config).ok();
+    let mut linter = Linter::with_config(config.as_ref(), None);
+    for plugin in plugins {
+        linter.add_rule(plugin);
+    }
+    linter
+}
+
+/// Problems with a rule's examples: the bad example must trigger the rule
+/// and the good example must not. Missing examples are not checked.
+pub fn check_examples(doc: &RuleDocOwned, linter: &Linter) -> Vec<String> {
+    if FILESYSTEM_RULES.contains(&doc.name.as_str()) {
+        return Vec::new();
+    }
+
+    let mut problems = Vec::new();
+    if !doc.bad_example.is_empty() && example_errors(linter, &doc.name, &doc.bad_example).is_empty()
+    {
+        problems.push("bad example does not trigger the rule".to_string());
+    }
+    if !doc.good_example.is_empty() {
+        for error in example_errors(linter, &doc.name, &doc.good_example) {
+            problems.push(format!(
+                "good example triggers the rule at line {}: {}",
+                error.line.unwrap_or(0),
+                error.message
+            ));
+        }
+    }
+    problems
+}
+
+/// Errors reported by `rule` for `example`, linted as a whole configuration
+fn example_errors(linter: &Linter, rule: &str, example: &str) -> Vec<LintError> {
+    let mut errors = linter.lint_fragment(example, &[]);
+
+    let (config, _) = parse_string_with_errors(example);
+    let sources = [crate::semantic::SourceFile {
+        path: Path::new("example.conf"),
+        config: &config,
+        content: example,
+    }];
+    errors.extend(
+        linter
+            .lint_cross_file(&sources)
+            .into_values()
+            .flat_map(|(errors, _)| errors),
+    );
+
+    errors.retain(|e| e.rule == rule);
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(name: &str, bad: &str, good: &str) -> RuleDocOwned {
+        RuleDocOwned {
+            name: name.to_string(),
+            category: "security".to_string(),
+            description: "Detects <things>".to_string(),
+            severity: "warning".to_string(),
+            why: "First paragraph.\n\nSecond paragraph.".to_string(),
+            bad_example: bad.to_string(),
+            good_example: good.to_string(),
+            references: vec!["https://nginx.org/".to_string()],
+            is_plugin: false,
+            min_nginx_version: None,
+            max_nginx_version: None,
+            preset: None,
+        }
+    }
+
+    #[test]
+    fn test_render_markdown_pages() {
+        let docs = [doc("my-rule", "bad;", "good;")];
+        let pages = render_pages(&docs, DocFormat::Markdown);
+        let names: Vec<_> = pages.iter().map(|p| p.file_name.as_str()).collect();
+        assert_eq!(names, ["my-rule.md", "index.md"]);
+
+        let page = &pages[0].content;
+        assert!(page.starts_with("# my-rule\n\nDetects <things>\n"));
+        assert!(page.contains("- **Severity:** warning\n"));
+        assert!(page.contains("## Bad Example\n\n```nginx\nbad;\n```"));
+        assert!(page.contains("- <https://nginx.org/>"));
+        assert!(
+            pages[1]
+                .content
+                .contains("| [my-rule](my-rule.md) | warning | Detects <things> |")
+        );
+    }
+
+    #[test]
+    fn test_render_html_escapes() {
+        let docs = [doc("my-rule", "return 200 \"<b>\";", "")];
+        let pages = render_pages(&docs, DocFormat::Html);
+        let page = &pages[0].content;
+        assert!(page.contains("<p>Detects &lt;things&gt;</p>"));
+        assert!(page.contains("return 200 &quot;&lt;b&gt;&quot;;"));
+        assert!(page.contains("<p>Second paragraph.</p>"));
+        assert!(!page.contains("Good Example"));
+        assert!(
+            pages[1]
+                .content
+                .contains("<a href=\"my-rule.html\">my-rule</a>")
+        );
+    }
+
+    #[test]
+    fn test_check_examples() {
+        let linter = example_linter(Vec::new());
+        let mut rule = doc(
+            "unmatched-braces",
+            "http {\n    server {\n}\n",
+            "http {\n}\n",
+        );
+        assert!(check_examples(&rule, &linter).is_empty());
+
+        rule.bad_example = "http {\n}\n".to_string();
+        rule.good_example = "http {\n".to_string();
+        let problems = check_examples(&rule, &linter);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert_eq!(problems[0], "bad example does not trigger the rule");
+        assert!(problems[1].starts_with("good example triggers the rule at line"));
+    }
+
+    /// Every builtin rule documents examples that behave as described
+    #[test]
+    fn test_builtin_examples() {
+        #[cfg(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins"))]
+        let docs = crate::docs::all_rule_docs_with_plugins();
+        #[cfg(not(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins")))]
+        let docs: Vec<RuleDocOwned> = crate::docs::all_rule_docs()
+            .iter()
+            .map(|doc| (*doc).into())
+            .collect();
+
+        let linter = example_linter(Vec::new());
+        let problems: Vec<String> = docs
+            .iter()
+            .flat_map(|doc| {
+                check_examples(doc, &linter)
+                    .into_iter()
+                    .map(move |problem| format!("{}: {}", doc.name, problem))
+            })
+            .collect();
+        assert!(problems.is_empty(), "{:#?}", problems);
+    }
+}
@@ -3,6 +3,8 @@
 //! This module provides detailed documentation for each lint rule,
 //! explaining why the rule exists and what the recommended configuration is.
 
+use crate::config::RulePreset;
+
 /// Documentation for a lint rule (static version for native rules)
 pub struct RuleDoc {
     /// Rule name (e.g., "server-tokens-enabled")
@@ -25,6 +27,8 @@ pub struct RuleDoc {
     pub min_nginx_version: Option<&'static str>,
     /// Maximum nginx version this rule applies to (inclusive), if declared.
     pub max_nginx_version: Option<&'static str>,
+    /// Smallest rule preset this rule belongs to; `None` for `strict` only.
+    pub preset: Option<RulePreset>,
 }
 
 impl RuleDoc {
@@ -39,7 +43,7 @@ impl RuleDoc {
     /// ```
     ///
     /// Currently only the optional `min_nginx_version` / `max_nginx_version`
-    /// fields have meaningful defaults; the rest are empty placeholders that
+    /// and `preset` fields have meaningful defaults; the rest are empty placeholders that
     /// you should override. Future additive fields with sensible defaults
     /// can be added here so existing DOC literals automatically pick them
     /// up via `..RuleDoc::DEFAULTS` without each call site needing edits.
@@ -54,11 +58,12 @@ impl RuleDoc {
         references: &[],
         min_nginx_version: None,
         max_nginx_version: None,
+        preset: None,
     };
 }
 
 /// Documentation for a lint rule (owned version, supports plugins)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct RuleDocOwned {
     /// Rule name (e.g., "server-tokens-enabled")
     pub name: String,
@@ -82,6 +87,8 @@ pub struct RuleDocOwned {
     pub min_nginx_version: Option<String>,
     /// Maximum nginx version this rule applies to (inclusive), if declared.
     pub max_nginx_version: Option<String>,
+    /// Smallest rule preset this rule belongs to, if declared.
+    pub preset: Option<String>,
 }
 
 impl From<&RuleDoc> for RuleDocOwned {
@@ -98,10 +105,36 @@ impl From<&RuleDoc> for RuleDocOwned {
             is_plugin: false,
             min_nginx_version: doc.min_nginx_version.map(String::from),
             max_nginx_version: doc.max_nginx_version.map(String::from),
+            preset: doc.preset.map(|p| p.as_str().to_string()),
         }
     }
 }
 
+impl RuleDocOwned {
+    /// Documentation declared by a plugin rule (builtin or loaded at runtime)
+    pub fn from_plugin(rule: &dyn crate::linter::LintRule) -> Self {
+        Self {
+            name: rule.name().to_string(),
+            category: rule.category().to_string(),
+            description: rule.description().to_string(),
+            severity: rule.severity().unwrap_or("warning").to_string(),
+            why: rule.why().unwrap_or("").to_string(),
+            bad_example: rule.bad_example().unwrap_or("").to_string(),
+            good_example: rule.good_example().unwrap_or("").to_string(),
+            references: rule.references().unwrap_or_default(),
+            is_plugin: true,
+            min_nginx_version: rule.min_nginx_version().map(String::from),
+            max_nginx_version: rule.max_nginx_version().map(String::from),
+            preset: rule.preset().map(String::from),
+        }
+    }
+
+    /// The rule presets this rule is in (e.g. `recommended, strict`)
+    pub fn presets(&self) -> String {
+        RulePreset::describe_membership(self.preset.as_deref().and_then(RulePreset::from_name))
+    }
+}
+
 /// Get documentation for a rule by name
 pub fn get_rule_doc(name: &str) -> Option<&'static RuleDoc> {
     all_rule_docs().iter().find(|doc| doc.name == name).copied()
@@ -116,17 +149,154 @@ static INCLUDE_PATH_EXISTS_DOC: RuleDoc = RuleDoc {
     severity: "error",
     why: r#"When an include directive references a file that does not exist,
 nginx will fail to start. Glob patterns that match no files are
-accepted by nginx but may indicate a misconfiguration."#,
+accepted by nginx without error, but silently include nothing, which
+usually means the pattern or the directory is wrong; they are reported
+as warnings."#,
     bad_example: include_str!("rules/syntax/include_path_exists/bad.conf"),
     good_example: include_str!("rules/syntax/include_path_exists/good.conf"),
     references: &["https://nginx.org/en/docs/ngx_core_module.html#include"],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Rule documentation for include-cycle (cli-only rule, but docs are always available)
+static INCLUDE_CYCLE_DOC: RuleDoc = RuleDoc {
+    name: "include-cycle",
+    category: "syntax",
+    description: "Detects include directives that lead back to the including file",
+    severity: "error",
+    why: r#"nginx does not detect circular includes. A file that includes itself,
+directly or through other included files, makes nginx open the same files
+over and over until it runs out of file descriptors and fails to start."#,
+    bad_example: include_str!("rules/syntax/include_cycle/bad.conf"),
+    good_example: include_str!("rules/syntax/include_cycle/good.conf"),
+    references: &["https://nginx.org/en/docs/ngx_core_module.html#include"],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Rule documentation for precompressed-assets-not-served (cli-only rule, but docs are always available)
+static PRECOMPRESSED_ASSETS_NOT_SERVED_DOC: RuleDoc = RuleDoc {
+    name: "precompressed-assets-not-served",
+    category: "best-practices",
+    description: "Detects precompressed .gz/.br files that nginx is not configured to serve",
+    severity: "warning",
+    why: r#"Build tools often emit `app.js.gz` and `app.js.br` next to `app.js`. nginx
+only serves those files when `gzip_static on;` (and `brotli_static on;` from
+the ngx_brotli module) is in effect for the location. This rule scans the
+document root on disk (or the directory set with `docroot`), so it is
+disabled by default."#,
+    bad_example: include_str!("rules/best_practices/precompressed_assets_not_served/bad.conf"),
+    good_example: include_str!("rules/best_practices/precompressed_assets_not_served/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_gzip_static_module.html",
+        "https://github.com/google/ngx_brotli#brotli_static",
+    ],
+    preset: Some(RulePreset::Strict),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Rule documentation for auth-basic-user-file-missing (cli-only rule, but docs are always available)
+static AUTH_BASIC_USER_FILE_MISSING_DOC: RuleDoc = RuleDoc {
+    name: "auth-basic-user-file-missing",
+    category: "security",
+    description: "Detects auth_basic_user_file files that do not exist or cannot be read",
+    severity: "error",
+    why: r#"nginx opens the password file of `auth_basic_user_file` when a request
+comes in, not at startup, so `nginx -t` accepts a path that does not exist
+or that the worker processes cannot read, and every request to the protected
+location then fails with 500. Relative
+paths are resolved against the prefix (`--prefix` or `include_prefix`) like
+includes. This rule reads the filesystem, so it is disabled by default and
+turned on by `--check-paths`."#,
+    bad_example: include_str!("rules/security/auth_basic_user_file_missing/bad.conf"),
+    good_example: include_str!("rules/security/auth_basic_user_file_missing/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_auth_basic_module.html#auth_basic_user_file",
+    ],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Rule documentation for include-layering (cli-only rule, but docs are always available)
+static INCLUDE_LAYERING_DOC: RuleDoc = RuleDoc {
+    name: "include-layering",
+    category: "best-practices",
+    description: "Detects directives placed outside the files the project's layering allows",
+    severity: "warning",
+    why: r#"Large configurations are usually split by convention: server blocks in
+`sites-enabled/`, TLS settings in shared snippets, nothing but global
+settings in the root `nginx.conf`. This rule enforces such a layout, set as
+`[[rules.include-layering.layers]]` entries in `.nginx-lint.toml` (directive
+selectors with allowed and denied path globs), so it is disabled by
+default."#,
+    bad_example: include_str!("rules/best_practices/include_layering/bad.conf"),
+    good_example: include_str!("rules/best_practices/include_layering/good.conf"),
+    references: &["https://nginx.org/en/docs/ngx_core_module.html#include"],
+    preset: Some(RulePreset::Strict),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Rule documentation for rego-policy (cli-only rule, but docs are always available)
+static REGO_POLICY_DOC: RuleDoc = RuleDoc {
+    name: "rego-policy",
+    category: "security",
+    description: "Reports the denials of Rego policies evaluated with opa against each file",
+    severity: "error",
+    why: r#"Organisations often have rules of their own (no `autoindex`, TLS
+settings only in the shared snippet, every location behind an allowlist)
+that are easier to keep as Rego policies next to their other OPA policies
+than as nginx-lint plugins. This rule runs `opa eval` with the files or
+directories of `policies` on the AST of each file, given as `input.config`
+with the file's path as `input.path`, and reports every value of `query`
+(`data.nginx.deny` by default): a message, or an object with `msg` and
+optionally `line`, `column` and `severity`. It runs a program from the
+configuration, so it is disabled by default."#,
+    bad_example: include_str!("rules/security/rego_policy/bad.conf"),
+    good_example: include_str!("rules/security/rego_policy/good.conf"),
+    references: &[
+        "https://www.openpolicyagent.org/docs/latest/policy-language/",
+        "https://www.openpolicyagent.org/docs/latest/cli/#opa-eval",
+    ],
+    preset: Some(RulePreset::Recommended),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Rule documentation for lua-block-lint (cli-only rule, but docs are always available)
+static LUA_BLOCK_LINT_DOC: RuleDoc = RuleDoc {
+    name: "lua-block-lint",
+    category: "best-practices",
+    description: "Runs an external Lua linter (luacheck by default) on *_by_lua_block bodies",
+    severity: "warning",
+    why: r#"The bodies of OpenResty's `*_by_lua_block` directives are Lua code that
+nginx only compiles when a request reaches them. This rule pipes each body to
+the linter set with `command` (luacheck by default) and reports its messages
+at their lines in the nginx file. It runs a program from the configuration,
+so it is disabled by default."#,
+    bad_example: include_str!("rules/best_practices/lua_block_lint/bad.conf"),
+    good_example: include_str!("rules/best_practices/lua_block_lint/good.conf"),
+    references: &[
+        "https://github.com/openresty/lua-nginx-module#content_by_lua_block",
+        "https://luacheck.readthedocs.io/en/stable/cli.html",
+    ],
+    preset: Some(RulePreset::Recommended),
     ..RuleDoc::DEFAULTS
 };
 
 pub fn all_rule_docs() -> &'static [&'static RuleDoc] {
     use crate::rules::{
+        best_practices::{
+            access_log_misconfigured, generated_region_modified, https_redirect_misconfigured,
+            missing_resolver, missing_server_name, proxy_cache_incomplete,
+            proxy_pass_upstream_mismatch, undefined_limit_zone, undefined_upstream,
+            undefined_variable, unused_limit_zone, unused_upstream, unused_variable,
+            upstream_keepalive_incomplete,
+        },
         style::indent,
-        syntax::{invalid_directive_context, missing_semicolon, unclosed_quote, unmatched_braces},
+        syntax::{
+            duplicate_default_server, invalid_directive_context, missing_semicolon, unclosed_quote,
+            unmatched_braces,
+        },
     };
 
     static DOCS: &[&RuleDoc] = &[
@@ -136,8 +306,33 @@ pub fn all_rule_docs() -> &'static [&'static RuleDoc] {
         &missing_semicolon::DOC,
         &invalid_directive_context::DOC,
         &INCLUDE_PATH_EXISTS_DOC,
+        &INCLUDE_CYCLE_DOC,
+        // Syntax (cross-file)
+        &duplicate_default_server::DOC,
         // Style
         &indent::DOC,
+        // Best practices
+        &PRECOMPRESSED_ASSETS_NOT_SERVED_DOC,
+        &INCLUDE_LAYERING_DOC,
+        &LUA_BLOCK_LINT_DOC,
+        &generated_region_modified::DOC,
+        // Best practices (cross-file)
+        &undefined_upstream::DOC,
+        &unused_upstream::DOC,
+        &upstream_keepalive_incomplete::DOC,
+        &proxy_pass_upstream_mismatch::DOC,
+        &missing_resolver::DOC,
+        &access_log_misconfigured::DOC,
+        &undefined_limit_zone::DOC,
+        &unused_limit_zone::DOC,
+        &proxy_cache_incomplete::DOC,
+        &undefined_variable::DOC,
+        &unused_variable::DOC,
+        &missing_server_name::DOC,
+        &https_redirect_misconfigured::DOC,
+        // Security
+        &AUTH_BASIC_USER_FILE_MISSING_DOC,
+        &REGO_POLICY_DOC,
     ];
 
     DOCS
@@ -177,20 +372,8 @@ fn get_builtin_plugin_docs() -> Vec<RuleDocOwned> {
 
     let plugins: Vec<Box<dyn LintRule>> = load_native_builtin_plugins();
     plugins
-        .into_iter()
-        .map(|rule| RuleDocOwned {
-            name: rule.name().to_string(),
-            category: rule.category().to_string(),
-            description: rule.description().to_string(),
-            severity: rule.severity().unwrap_or("warning").to_string(),
-            why: rule.why().unwrap_or("").to_string(),
-            bad_example: rule.bad_example().unwrap_or("").to_string(),
-            good_example: rule.good_example().unwrap_or("").to_string(),
-            references: rule.references().unwrap_or_default(),
-            is_plugin: true,
-            min_nginx_version: rule.min_nginx_version().map(String::from),
-            max_nginx_version: rule.max_nginx_version().map(String::from),
-        })
+        .iter()
+        .map(|rule| RuleDocOwned::from_plugin(rule.as_ref()))
         .collect()
 }
 
@@ -200,26 +383,13 @@ fn get_builtin_plugin_docs() -> Vec<RuleDocOwned> {
     not(feature = "native-builtin-plugins")
 ))]
 fn get_builtin_plugin_docs() -> Vec<RuleDocOwned> {
-    use crate::linter::LintRule;
     use crate::plugin::builtin::load_builtin_plugins;
 
     let mut docs = Vec::new();
 
     if let Ok(plugins) = load_builtin_plugins() {
         for plugin in plugins {
-            docs.push(RuleDocOwned {
-                name: plugin.name().to_string(),
-                category: plugin.category().to_string(),
-                description: plugin.description().to_string(),
-                severity: plugin.severity().unwrap_or("warning").to_string(),
-                why: plugin.why().unwrap_or("").to_string(),
-                bad_example: plugin.bad_example().unwrap_or("").to_string(),
-                good_example: plugin.good_example().unwrap_or("").to_string(),
-                references: plugin.references().unwrap_or_default(),
-                is_plugin: true,
-                min_nginx_version: plugin.min_nginx_version().map(String::from),
-                max_nginx_version: plugin.max_nginx_version().map(String::from),
-            });
+            docs.push(RuleDocOwned::from_plugin(&plugin));
         }
     }
 
@@ -269,6 +439,25 @@ mod example_tests {
         result
     }
 
+    /// Run the cross-file rules on a single example, treated as a whole config
+    fn cross_file_errors(
+        linter: &Linter,
+        config: &crate::parser::ast::Config,
+        content: &str,
+    ) -> Vec<crate::linter::LintError> {
+        let path = Path::new("test.conf");
+        let sources = [crate::semantic::SourceFile {
+            path,
+            config,
+            content,
+        }];
+        linter
+            .lint_cross_file(&sources)
+            .into_values()
+            .flat_map(|(errors, _)| errors)
+            .collect()
+    }
+
     /// Test that each rule's bad_example produces at least one error from that rule
     #[test]
     fn test_bad_examples_produce_errors() {
@@ -287,16 +476,25 @@ mod example_tests {
                 continue;
             }
 
-            // Skip syntax rules that check content directly - tested in test_syntax_bad_examples
+            // Skip rules that check content directly - tested in test_syntax_bad_examples
             if doc.name == "unclosed-quote"
                 || doc.name == "missing-semicolon"
                 || doc.name == "unmatched-braces"
+                || doc.name == "generated-region-modified"
             {
                 continue;
             }
 
-            // Skip rules that require real filesystem access
-            if doc.name == "include-path-exists" {
+            // Skip rules that require real filesystem access or an external tool
+            if doc.name == "include-path-exists"
+                || doc.name == "include-cycle"
+                || doc.name == "precompressed-assets-not-served"
+                || doc.name == "include-layering"
+                || doc.name == "lua-block-lint"
+                || doc.name == "rego-policy"
+                || doc.name == "auth-basic-user-file-missing"
+                || doc.name == "duplicate-default-server"
+            {
                 continue;
             }
 
@@ -311,7 +509,8 @@ mod example_tests {
                 }
             };
 
-            let errors = linter.lint(&config, dummy_path);
+            let mut errors = linter.lint(&config, dummy_path);
+            errors.extend(cross_file_errors(&linter, &config, doc.bad_example));
             let rule_errors: Vec<_> = errors.iter().filter(|e| e.rule == doc.name).collect();
 
             assert!(
@@ -335,16 +534,25 @@ mod example_tests {
                 continue;
             }
 
-            // Skip syntax rules that check content directly - tested in test_syntax_good_examples
+            // Skip rules that check content directly - tested in test_syntax_good_examples
             if doc.name == "unclosed-quote"
                 || doc.name == "missing-semicolon"
                 || doc.name == "unmatched-braces"
+                || doc.name == "generated-region-modified"
             {
                 continue;
             }
 
-            // Skip rules that require real filesystem access
-            if doc.name == "include-path-exists" {
+            // Skip rules that require real filesystem access or an external tool
+            if doc.name == "include-path-exists"
+                || doc.name == "include-cycle"
+                || doc.name == "precompressed-assets-not-served"
+                || doc.name == "include-layering"
+                || doc.name == "lua-block-lint"
+                || doc.name == "rego-policy"
+                || doc.name == "auth-basic-user-file-missing"
+                || doc.name == "duplicate-default-server"
+            {
                 continue;
             }
 
@@ -359,7 +567,8 @@ mod example_tests {
                 }
             };
 
-            let errors = linter.lint(&config, dummy_path);
+            let mut errors = linter.lint(&config, dummy_path);
+            errors.extend(cross_file_errors(&linter, &config, doc.good_example));
             let rule_errors: Vec<_> = errors.iter().filter(|e| e.rule == doc.name).collect();
 
             assert!(
@@ -409,9 +618,10 @@ mod example_tests {
         }
     }
 
-    /// Test bad examples for syntax rules that check content directly
+    /// Test bad examples for syntax (and other) rules that check content directly
     #[test]
     fn test_syntax_bad_examples() {
+        use crate::rules::best_practices::generated_region_modified::GeneratedRegionModified;
         use crate::rules::syntax::{
             missing_semicolon::MissingSemicolon, unclosed_quote::UnclosedQuote,
             unmatched_braces::UnmatchedBraces,
@@ -432,7 +642,7 @@ mod example_tests {
         // Test unclosed-quote
         {
             let doc = get_rule_doc("unclosed-quote").unwrap();
-            let rule = UnclosedQuote;
+            let rule = UnclosedQuote::default();
             let errors = rule.check_content(doc.bad_example);
             assert!(
                 !errors.is_empty(),
@@ -444,7 +654,7 @@ mod example_tests {
         // Test missing-semicolon
         {
             let doc = get_rule_doc("missing-semicolon").unwrap();
-            let rule = MissingSemicolon;
+            let rule = MissingSemicolon::default();
             let errors = rule.check_content(doc.bad_example);
             assert!(
                 !errors.is_empty(),
@@ -452,11 +662,23 @@ mod example_tests {
                 doc.bad_example
             );
         }
+
+        // Test generated-region-modified
+        {
+            let doc = get_rule_doc("generated-region-modified").unwrap();
+            let errors = GeneratedRegionModified.check_content(doc.bad_example);
+            assert!(
+                !errors.is_empty(),
+                "generated-region-modified bad_example should produce errors:\n{}",
+                doc.bad_example
+            );
+        }
     }
 
-    /// Test good examples for syntax rules that check content directly
+    /// Test good examples for syntax (and other) rules that check content directly
     #[test]
     fn test_syntax_good_examples() {
+        use crate::rules::best_practices::generated_region_modified::GeneratedRegionModified;
         use crate::rules::syntax::{
             missing_semicolon::MissingSemicolon, unclosed_quote::UnclosedQuote,
             unmatched_braces::UnmatchedBraces,
@@ -478,7 +700,7 @@ mod example_tests {
         // Test unclosed-quote
         {
             let doc = get_rule_doc("unclosed-quote").unwrap();
-            let rule = UnclosedQuote;
+            let rule = UnclosedQuote::default();
             let errors = rule.check_content(doc.good_example);
             assert!(
                 errors.is_empty(),
@@ -491,7 +713,7 @@ mod example_tests {
         // Test missing-semicolon
         {
             let doc = get_rule_doc("missing-semicolon").unwrap();
-            let rule = MissingSemicolon;
+            let rule = MissingSemicolon::default();
             let errors = rule.check_content(doc.good_example);
             assert!(
                 errors.is_empty(),
@@ -500,6 +722,18 @@ mod example_tests {
                 doc.good_example
             );
         }
+
+        // Test generated-region-modified
+        {
+            let doc = get_rule_doc("generated-region-modified").unwrap();
+            let errors = GeneratedRegionModified.check_content(doc.good_example);
+            assert!(
+                errors.is_empty(),
+                "generated-region-modified good_example should not produce errors, but got {:?}:\n{}",
+                errors,
+                doc.good_example
+            );
+        }
     }
 
     /// Test that applying fixes to style bad examples produces the good example
@@ -560,7 +794,7 @@ mod example_tests {
         // Note: unclosed-quote may not have automatic fixes
         {
             let doc = get_rule_doc("unclosed-quote").unwrap();
-            let rule = UnclosedQuote;
+            let rule = UnclosedQuote::default();
             let errors = rule.check_content(doc.bad_example);
             if !errors.is_empty() && errors.iter().all(|e| !e.fixes.is_empty()) {
                 let fixed = apply_fixes(doc.bad_example, &errors);
@@ -580,7 +814,7 @@ mod example_tests {
         // Test missing-semicolon fix
         {
             let doc = get_rule_doc("missing-semicolon").unwrap();
-            let rule = MissingSemicolon;
+            let rule = MissingSemicolon::default();
             let errors = rule.check_content(doc.bad_example);
             if !errors.is_empty() && errors.iter().all(|e| !e.fixes.is_empty()) {
                 let fixed = apply_fixes(doc.bad_example, &errors);
@@ -0,0 +1,548 @@
+//! Linting from other programs
+//!
+//! [`lint_str`] and [`lint_path`] run the linter the way the `nginx-lint`
+//! command does, for programs such as deployment tools or admission
+//! controllers that embed it instead of invoking the binary.
+//! [`LintOptions`] takes the place of `.nginx-lint.toml` and the
+//! command-line options.
+//!
+//! ```
+//! use nginx_lint::{LintOptions, lint_str};
+//!
+//! let options = LintOptions::new()
+//!     .with_only_rules(["server-tokens-enabled"])
+//!     .with_context("http");
+//! let report = lint_str("server {\n    server_tokens on;\n}\n", &options);
+//!
+//! let rules: Vec<&str> = report.errors().map(|e| e.rule.as_str()).collect();
+//! assert_eq!(rules, ["server-tokens-enabled"]);
+//! ```
+
+#[cfg(feature = "cli")]
+use crate::config_source::ConfigSource;
+use crate::linter::Linter;
+use crate::preset::Preset;
+use crate::semantic::SourceFile;
+use crate::{LintConfig, LintError, Severity};
+use nginx_lint_common::ignore::parse_context_comment;
+use nginx_lint_common::nginx_version::NginxVersion;
+use nginx_lint_common::parser::ast::Config;
+use nginx_lint_common::parser::context::IncludeContext;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "cli")]
+use std::sync::Arc;
+
+/// The path reported for the source given to [`lint_str`]
+const STR_PATH: &str = "<string>";
+
+/// Which rules run and how, in place of `.nginx-lint.toml` and the
+/// command-line options
+///
+/// The default runs the rules a missing `.nginx-lint.toml` would.
+#[derive(Debug, Default)]
+pub struct LintOptions {
+    config: LintConfig,
+    /// `--rule-only`
+    only_rules: Option<HashSet<String>>,
+    /// `--context`, as given
+    context: Option<String>,
+    /// `--prefix`
+    include_prefix: Option<PathBuf>,
+    /// `--preset`
+    preset: Option<Preset>,
+    /// Where [`lint_path`] reads files from; the disk when unset
+    #[cfg(feature = "cli")]
+    source: Option<Arc<dyn ConfigSource>>,
+}
+
+impl LintOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a loaded `.nginx-lint.toml` instead of the defaults.
+    ///
+    /// Options set before this call are kept, except those the config also
+    /// holds (rules enabled with [`with_rule`](Self::with_rule) and the
+    /// nginx version). The rules of a preset are applied to the new config.
+    pub fn with_config(mut self, config: LintConfig) -> Self {
+        self.config = config;
+        if let Some(preset) = self.preset {
+            preset.apply(&mut self.config);
+        }
+        self
+    }
+
+    /// Lint snippets of a deployment, like `--preset`: the preset's rules
+    /// are enabled and disabled (except those set with
+    /// [`with_rule`](Self::with_rule) or in the config), and the context
+    /// defaults to the preset's most common snippet.
+    pub fn with_preset(mut self, preset: Preset) -> Self {
+        preset.apply(&mut self.config);
+        self.preset = Some(preset);
+        self
+    }
+
+    /// Enable or disable `rule`, as `enabled` in its `[rules.<rule>]`
+    /// section does. Rules disabled by default must be enabled this way.
+    pub fn with_rule(mut self, rule: &str, enabled: bool) -> Self {
+        self.config
+            .rules
+            .entry(rule.to_string())
+            .or_default()
+            .enabled = enabled;
+        self
+    }
+
+    /// Run only the given rules, like `--rule-only`.
+    ///
+    /// Names of rules that are disabled or do not exist are ignored.
+    pub fn with_only_rules<I, S>(mut self, rules: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.only_rules = Some(rules.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Lint the source as if included in `context`, like `--context`
+    ///
+    /// `context` is the comma-separated stack of enclosing blocks, outermost
+    /// first, such as `"http,server"`, or with a preset the name of one of
+    /// its snippets, such as `"server-snippet"`. Without a context, a
+    /// `# nginx-lint:context` comment in the source is honoured.
+    pub fn with_context(mut self, context: &str) -> Self {
+        self.context = Some(context.to_string());
+        self
+    }
+
+    /// Skip the rules that do not apply to nginx `version`, like
+    /// `target_nginx_version`.
+    pub fn with_nginx_version(mut self, version: &NginxVersion) -> Self {
+        self.config.target_nginx_version = Some(version.to_string());
+        self
+    }
+
+    /// Resolve relative `include` paths against `prefix`, like `--prefix`.
+    pub fn with_include_prefix(mut self, prefix: impl Into<PathBuf>) -> Self {
+        self.include_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Read the files [`lint_path`] lints and the files they refer to from
+    /// `source` instead of the disk, e.g. a configuration tree read from an
+    /// archive.
+    #[cfg(feature = "cli")]
+    pub fn with_source(mut self, source: Arc<dyn ConfigSource>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// The context given, or the preset's default
+    fn context(&self) -> IncludeContext {
+        let context = match (self.preset, self.context.as_deref()) {
+            (Some(preset), Some(context)) => Some(preset.resolve_context(context)),
+            (Some(preset), None) => preset.default_context(),
+            (None, context) => context,
+        };
+        context.map(IncludeContext::parse).unwrap_or_default()
+    }
+
+    fn linter(&self) -> Linter {
+        #[cfg(feature = "cli")]
+        if let Some(source) = &self.source {
+            return Linter::with_config_source(
+                Some(&self.config),
+                self.include_prefix.as_deref(),
+                self.only_rules.as_ref(),
+                source.clone(),
+            );
+        }
+        Linter::with_config_and_rule_only(
+            Some(&self.config),
+            self.include_prefix.as_deref(),
+            self.only_rules.as_ref(),
+        )
+    }
+}
+
+/// [`LintOptions`] as plain data, for bindings that receive the options as
+/// JSON or JavaScript objects
+///
+/// Every field is optional; unknown fields are rejected.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintSettings {
+    /// Content of a `.nginx-lint.toml`
+    pub config: Option<String>,
+    /// Rules to enable (`true`) or disable (`false`)
+    pub rules: HashMap<String, bool>,
+    /// See [`LintOptions::with_only_rules`]
+    pub only_rules: Option<Vec<String>>,
+    /// See [`LintOptions::with_context`]
+    pub context: Option<String>,
+    /// Name of a preset, see [`LintOptions::with_preset`]
+    pub preset: Option<String>,
+    /// Target nginx version, such as `"1.26.0"`
+    pub nginx_version: Option<String>,
+}
+
+impl TryFrom<LintSettings> for LintOptions {
+    /// Why the config or the nginx version is invalid
+    type Error = String;
+
+    fn try_from(settings: LintSettings) -> Result<Self, Self::Error> {
+        let mut options = LintOptions::new();
+        if let Some(config) = &settings.config {
+            let config = LintConfig::parse(config).map_err(|e| format!("invalid config: {}", e))?;
+            options = options.with_config(config);
+        }
+        if let Some(preset) = &settings.preset {
+            options = options.with_preset(preset.parse()?);
+        }
+        for (rule, enabled) in &settings.rules {
+            options = options.with_rule(rule, *enabled);
+        }
+        if let Some(rules) = settings.only_rules {
+            options = options.with_only_rules(rules);
+        }
+        if let Some(context) = &settings.context {
+            options = options.with_context(context);
+        }
+        if let Some(version) = &settings.nginx_version {
+            let version: NginxVersion = version
+                .parse()
+                .map_err(|e| format!("invalid nginx_version: {}", e))?;
+            options = options.with_nginx_version(&version);
+        }
+        Ok(options)
+    }
+}
+
+/// The diagnostics of one linted file
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    /// Path of the file, or `<string>` for [`lint_str`]
+    pub path: PathBuf,
+    /// The content the error positions refer to
+    pub content: String,
+    /// Diagnostics in canonical order
+    pub errors: Vec<LintError>,
+    /// Number of diagnostics suppressed by ignore comments
+    pub ignored_count: usize,
+}
+
+/// The result of [`lint_str`] or [`lint_path`]
+#[derive(Debug, Clone)]
+pub struct LintReport {
+    /// One report per linted file, the root file first
+    pub files: Vec<FileReport>,
+}
+
+impl LintReport {
+    /// The diagnostics of all files
+    pub fn errors(&self) -> impl Iterator<Item = &LintError> {
+        self.files.iter().flat_map(|file| &file.errors)
+    }
+
+    /// Number of diagnostics with severity `Error`
+    pub fn error_count(&self) -> usize {
+        self.errors()
+            .filter(|e| e.severity == Severity::Error)
+            .count()
+    }
+
+    /// Number of diagnostics with severity `Warning`
+    pub fn warning_count(&self) -> usize {
+        self.errors()
+            .filter(|e| e.severity == Severity::Warning)
+            .count()
+    }
+
+    /// Whether any diagnostic has severity `Error`, the condition under
+    /// which the command fails by default
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+}
+
+/// Lint a configuration given as a string.
+///
+/// `include` directives are not followed; see [`lint_path`] for that.
+pub fn lint_str(source: &str, options: &LintOptions) -> LintReport {
+    let linter = options.linter();
+    let context = effective_context(source, &options.context());
+    let is_fragment = !context.is_empty();
+
+    let (report, config) = lint_source(&linter, Path::new(STR_PATH), source.to_string(), context);
+    let mut files = vec![report];
+    if !is_fragment {
+        add_cross_file_errors(&linter, &mut files, &[config]);
+    }
+    LintReport { files }
+}
+
+/// Lint the configuration file at `path` and the files it includes.
+///
+/// Files are read from disk, or from the source given with
+/// [`LintOptions::with_source`]. Fails if one of the files cannot be read.
+#[cfg(feature = "cli")]
+pub fn lint_path(path: &Path, options: &LintOptions) -> std::io::Result<LintReport> {
+    use crate::config_source::RealFileSystem;
+    use crate::include::collect_included_files_from_source;
+
+    let source: &dyn ConfigSource = options.source.as_deref().unwrap_or(&RealFileSystem);
+    // Report a missing root file instead of an empty result
+    source.read_file(path)?;
+
+    let linter = options.linter();
+    let included = collect_included_files_from_source(
+        source,
+        path,
+        |path| {
+            let content = source.read_file(path).map_err(|e| e.to_string())?;
+            match linter.parse(&content) {
+                (_, errors) if !errors.is_empty() => Err(errors[0].message.clone()),
+                (config, _) => Ok(config),
+            }
+        },
+        options.context(),
+        options.config.include_path_mappings(),
+        options.include_prefix.as_deref(),
+    );
+    let is_fragment = included
+        .first()
+        .is_some_and(|root| !root.include_context.is_empty());
+
+    let mut files = Vec::new();
+    let mut configs = Vec::new();
+    for inc in &included {
+        let content = source.read_file(&inc.path)?;
+        let (report, config) =
+            lint_source(&linter, &inc.path, content, inc.include_context.clone());
+        files.push(report);
+        configs.push(config);
+    }
+    // Cross-file rules would only produce false positives on a fragment
+    if !is_fragment {
+        add_cross_file_errors(&linter, &mut files, &configs);
+    }
+    Ok(LintReport { files })
+}
+
+/// `context`, or the context of a `# nginx-lint:context` comment in `source`
+fn effective_context(source: &str, context: &IncludeContext) -> IncludeContext {
+    if context.is_empty() {
+        parse_context_comment(source).unwrap_or_default()
+    } else {
+        context.clone()
+    }
+}
+
+/// Lint one file, returning its report and parsed config
+fn lint_source(
+    linter: &Linter,
+    path: &Path,
+    content: String,
+    context: IncludeContext,
+) -> (FileReport, Config) {
+    let (mut config, syntax_errors) = linter.parse(&content);
+    config.include_context = context;
+
+    let (mut errors, ignored_count) = linter.lint_with_content(&config, path, &content);
+    // Some rules report the same problem as the parser; keep one of each
+    for error in crate::syntax_errors_to_lint_errors(&syntax_errors, &content) {
+        let is_duplicate = errors.iter().any(|e| {
+            e.rule == error.rule
+                && e.line == error.line
+                && e.column == error.column
+                && e.message == error.message
+        });
+        if !is_duplicate {
+            errors.push(error);
+        }
+    }
+    crate::sort_diagnostics(&mut errors);
+
+    let report = FileReport {
+        path: path.to_path_buf(),
+        content,
+        errors,
+        ignored_count,
+    };
+    (report, config)
+}
+
+/// Run the cross-file rules over `files` (parsed into `configs`) and add
+/// their errors to the reports
+fn add_cross_file_errors(linter: &Linter, files: &mut [FileReport], configs: &[Config]) {
+    let sources: Vec<SourceFile> = files
+        .iter()
+        .zip(configs)
+        .map(|(file, config)| SourceFile {
+            path: &file.path,
+            config,
+            content: &file.content,
+        })
+        .collect();
+    let mut cross_file = linter.lint_cross_file(&sources);
+    for file in files {
+        if let Some((errors, ignored_count)) = cross_file.remove(&file.path) {
+            file.errors.extend(errors);
+            crate::sort_diagnostics(&mut file.errors);
+            file.ignored_count += ignored_count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(report: &LintReport) -> Vec<&str> {
+        report.errors().map(|e| e.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn test_lint_str_defaults() {
+        let report = lint_str("http {\n    server_tokens on;\n}\n", &LintOptions::new());
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].path, Path::new(STR_PATH));
+        assert!(rules(&report).contains(&"server-tokens-enabled"));
+    }
+
+    #[test]
+    fn test_lint_str_reports_syntax_errors() {
+        let options = LintOptions::new().with_only_rules(["unmatched-braces"]);
+        let report = lint_str("http {\n    server {\n}\n", &options);
+        assert!(report.has_errors());
+        assert!(rules(&report).contains(&"unmatched-braces"));
+    }
+
+    #[test]
+    fn test_with_rule() {
+        let source = "http {\n    server_tokens on;\n}\n";
+        let options = LintOptions::new().with_rule("server-tokens-enabled", false);
+        assert!(!rules(&lint_str(source, &options)).contains(&"server-tokens-enabled"));
+    }
+
+    #[test]
+    fn test_with_context() {
+        let source = "location / {\n    proxy_pass http://backend;\n}\n";
+        let options = LintOptions::new().with_only_rules(["invalid-directive-context"]);
+        assert_eq!(
+            rules(&lint_str(source, &options)),
+            ["invalid-directive-context"]
+        );
+
+        let options = options.with_context("http,server");
+        assert!(rules(&lint_str(source, &options)).is_empty());
+
+        let commented = format!("# nginx-lint:context http,server\n{}", source);
+        let options = LintOptions::new().with_only_rules(["invalid-directive-context"]);
+        assert!(rules(&lint_str(&commented, &options)).is_empty());
+    }
+
+    #[test]
+    fn test_cross_file_rules_skip_fragments() {
+        let source = "upstream backend {\n    server 127.0.0.1:8080;\n}\n";
+        let options = LintOptions::new().with_only_rules(["unused-upstream"]);
+        let complete = format!("http {{\n{}}}\n", source);
+        assert_eq!(rules(&lint_str(&complete, &options)), ["unused-upstream"]);
+
+        let options = options.with_context("http");
+        assert!(rules(&lint_str(source, &options)).is_empty());
+    }
+
+    #[test]
+    fn test_with_nginx_version() {
+        let version: NginxVersion = "1.24.0".parse().unwrap();
+        let options = LintOptions::new().with_nginx_version(&version);
+        assert_eq!(options.config.target_nginx_version(), Some("1.24.0"));
+    }
+
+    #[test]
+    fn test_settings() {
+        let settings: LintSettings = serde_json::from_str(
+            r#"{"rules": {"server-tokens-enabled": false}, "context": "http"}"#,
+        )
+        .unwrap();
+        let options = LintOptions::try_from(settings).unwrap();
+        assert!(!options.config.is_rule_enabled("server-tokens-enabled"));
+        assert!(!options.context().is_empty());
+
+        let settings: LintSettings = serde_json::from_str(r#"{"preset": "k8s"}"#).unwrap();
+        let error = LintOptions::try_from(settings).unwrap_err();
+        assert!(error.starts_with("unknown preset 'k8s'"), "{}", error);
+
+        let settings: LintSettings = serde_json::from_str(r#"{"nginx_version": "x"}"#).unwrap();
+        let error = LintOptions::try_from(settings).unwrap_err();
+        assert!(error.starts_with("invalid nginx_version: "), "{}", error);
+        assert!(serde_json::from_str::<LintSettings>(r#"{"unknown": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_with_preset() {
+        // A configuration-snippet annotation: lines indented by the YAML,
+        // linted inside a location
+        let snippet =
+            "  more_set_headers \"X-Frame-Options: DENY\";\n  proxy_pass http://10.0.0.12:8080;\n";
+        let options = LintOptions::new()
+            .with_rule("proxy-pass-hardcoded-ip", false)
+            .with_preset(Preset::IngressNginx);
+        assert!(rules(&lint_str(snippet, &options)).is_empty());
+
+        let options = LintOptions::new().with_preset(Preset::IngressNginx);
+        assert_eq!(
+            rules(&lint_str(snippet, &options)),
+            ["proxy-pass-hardcoded-ip"]
+        );
+
+        // A location belongs in a server-snippet, not in an http-snippet
+        let snippet = "location /extra {\n    return 204;\n}\n";
+        let options = options.with_only_rules(["invalid-directive-context"]);
+        assert!(rules(&lint_str(snippet, &options.with_context("server-snippet"))).is_empty());
+        let options = LintOptions::new()
+            .with_preset(Preset::IngressNginx)
+            .with_context("http-snippet")
+            .with_only_rules(["invalid-directive-context"]);
+        assert_eq!(
+            rules(&lint_str(snippet, &options)),
+            ["invalid-directive-context"]
+        );
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_lint_path_follows_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("nginx.conf"),
+            "http {\n    server_tokens off;\n    include app.conf;\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("app.conf"),
+            "server {\n    server_tokens on;\n}\n",
+        )
+        .unwrap();
+
+        let options = LintOptions::new()
+            .with_only_rules(["server-tokens-enabled"])
+            .with_include_prefix(dir.path());
+        let report = lint_path(&dir.path().join("nginx.conf"), &options).unwrap();
+        let paths: Vec<&Path> = report.files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [dir.path().join("nginx.conf"), dir.path().join("app.conf")]
+        );
+        assert!(report.files[0].errors.is_empty());
+        assert_eq!(report.files[1].errors[0].rule, "server-tokens-enabled");
+        assert_eq!(report.files[1].errors[0].line, Some(2));
+
+        let missing = lint_path(&dir.path().join("missing.conf"), &options);
+        assert!(missing.is_err());
+    }
+}
@@ -1,11 +1,14 @@
 //! Include directive resolution for nginx configuration files
 //!
 //! This module provides functionality to recursively resolve `include` directives
-//! and collect all files that should be linted.
+//! and collect all files that should be linted. Files are looked up through a
+//! [`ConfigSource`], the disk unless given otherwise.
 
+use crate::config_source::{ConfigSource, RealFileSystem};
 use crate::parser::ast::Config;
-use glob::glob;
+use crate::parser::context::{ContextFrame, IncludeContext};
 use nginx_lint_common::config::PathMapping;
+use nginx_lint_common::paths::base_dir;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
@@ -16,8 +19,9 @@ pub struct IncludedFile {
     pub config: Option<Config>,
     pub parse_error: Option<String>,
     /// The context (parent directive stack) where this file was included
-    /// Empty for root file, e.g., ["http", "server"] for a file included in server block
-    pub include_context: Vec<String>,
+    /// Empty for root file, e.g., `http,server` for a file included in server block.
+    /// Each frame keeps the block's arguments (e.g. `location=/api/`).
+    pub include_context: IncludeContext,
 }
 
 /// Collect all files to lint, including those referenced by `include` directives.
@@ -41,7 +45,13 @@ pub fn collect_included_files<F>(
 where
     F: Fn(&Path) -> Result<Config, String> + Copy,
 {
-    collect_included_files_with_context(root_path, parse_fn, Vec::new(), path_mappings, prefix)
+    collect_included_files_with_context(
+        root_path,
+        parse_fn,
+        IncludeContext::new(),
+        path_mappings,
+        prefix,
+    )
 }
 
 /// Collect all files to lint with a specified initial context.
@@ -53,7 +63,7 @@ where
 /// # Arguments
 /// * `root_path` - The root configuration file to start from
 /// * `parse_fn` - A function to parse a config file
-/// * `initial_context` - The parent context (e.g., ["http", "server"])
+/// * `initial_context` - The parent context (e.g., `http,server,location=/api/`)
 /// * `path_mappings` - Path mappings applied (in order) to include patterns before resolving
 ///
 /// # Returns
@@ -61,7 +71,32 @@ where
 pub fn collect_included_files_with_context<F>(
     root_path: &Path,
     parse_fn: F,
-    initial_context: Vec<String>,
+    initial_context: IncludeContext,
+    path_mappings: &[PathMapping],
+    prefix: Option<&Path>,
+) -> Vec<IncludedFile>
+where
+    F: Fn(&Path) -> Result<Config, String> + Copy,
+{
+    collect_included_files_from_source(
+        &RealFileSystem,
+        root_path,
+        parse_fn,
+        initial_context,
+        path_mappings,
+        prefix,
+    )
+}
+
+/// Collect all files to lint from `source` instead of the disk.
+///
+/// Includes are resolved, and `# nginx-lint:context` comments read, through
+/// `source`; `parse_fn` is expected to read the files from it as well.
+pub fn collect_included_files_from_source<F>(
+    source: &dyn ConfigSource,
+    root_path: &Path,
+    parse_fn: F,
+    initial_context: IncludeContext,
     path_mappings: &[PathMapping],
     prefix: Option<&Path>,
 ) -> Vec<IncludedFile>
@@ -71,32 +106,43 @@ where
     let mut visited: HashSet<PathBuf> = HashSet::new();
     let mut result: Vec<IncludedFile> = Vec::new();
 
+    let lookup = Lookup {
+        source,
+        path_mappings,
+        prefix,
+    };
     collect_recursive(
+        &lookup,
         root_path,
         &mut visited,
         &mut result,
         parse_fn,
         initial_context,
-        path_mappings,
-        prefix,
     );
 
     result
 }
 
+/// Where included files are looked up while collecting them
+struct Lookup<'a> {
+    source: &'a dyn ConfigSource,
+    path_mappings: &'a [PathMapping],
+    prefix: Option<&'a Path>,
+}
+
 fn collect_recursive<F>(
+    lookup: &Lookup,
     path: &Path,
     visited: &mut HashSet<PathBuf>,
     result: &mut Vec<IncludedFile>,
     parse_fn: F,
-    include_context: Vec<String>,
-    path_mappings: &[PathMapping],
-    prefix: Option<&Path>,
+    include_context: IncludeContext,
 ) where
     F: Fn(&Path) -> Result<Config, String> + Copy,
 {
+    let source = lookup.source;
     // Canonicalize path to detect circular includes
-    let canonical = match path.canonicalize() {
+    let canonical = match source.canonicalize(path) {
         Ok(p) => p,
         Err(_) => {
             // File doesn't exist or can't be accessed
@@ -123,7 +169,8 @@ fn collect_recursive<F>(
         include_context.clone()
     } else {
         // Try to read file and parse context comment
-        std::fs::read_to_string(path)
+        source
+            .read_file(path)
             .ok()
             .and_then(|content| crate::ignore::parse_context_comment(&content))
             .unwrap_or_default()
@@ -136,7 +183,13 @@ fn collect_recursive<F>(
             config.include_context = effective_context.clone();
 
             // Find include directives with their contexts and resolve them
-            let includes = find_include_paths_with_context(&config, path, path_mappings, prefix);
+            let includes = find_include_paths_with_context(
+                source,
+                &config,
+                path,
+                lookup.path_mappings,
+                lookup.prefix,
+            );
 
             // Add this file to results
             result.push(IncludedFile {
@@ -149,13 +202,12 @@ fn collect_recursive<F>(
             // Recursively process includes with their contexts
             for (include_path, child_context) in includes {
                 collect_recursive(
+                    lookup,
                     &include_path,
                     visited,
                     result,
                     parse_fn,
                     child_context,
-                    path_mappings,
-                    prefix,
                 );
             }
         }
@@ -170,22 +222,66 @@ fn collect_recursive<F>(
     }
 }
 
+/// Search for a chain of includes leading from `from` back to `target`.
+///
+/// `target` must be canonicalized. Returns the files along the chain,
+/// starting with `from` and ending with the file that is (or resolves to)
+/// `target`. `visited` holds canonical paths already explored, so shared
+/// subtrees and cycles that do not pass through `target` are walked once.
+pub(crate) fn find_include_chain(
+    source: &dyn ConfigSource,
+    from: &Path,
+    target: &Path,
+    path_mappings: &[PathMapping],
+    prefix: Option<&Path>,
+    visited: &mut HashSet<PathBuf>,
+) -> Option<Vec<PathBuf>> {
+    let canonical = source.canonicalize(from).ok()?;
+    if canonical == target {
+        return Some(vec![from.to_path_buf()]);
+    }
+    if !visited.insert(canonical) {
+        return None;
+    }
+
+    // Files with syntax errors still have usable include directives
+    let content = source.read_file(from).ok()?;
+    let (config, _) = crate::parse_string_with_errors(&content);
+    for (include_path, _) in
+        find_include_paths_with_context(source, &config, from, path_mappings, prefix)
+    {
+        if let Some(mut chain) = find_include_chain(
+            source,
+            &include_path,
+            target,
+            path_mappings,
+            prefix,
+            visited,
+        ) {
+            chain.insert(0, from.to_path_buf());
+            return Some(chain);
+        }
+    }
+    None
+}
+
 /// Find all include directives in a config and resolve their paths with context
 fn find_include_paths_with_context(
+    source: &dyn ConfigSource,
     config: &Config,
     parent_path: &Path,
     path_mappings: &[PathMapping],
     prefix: Option<&Path>,
-) -> Vec<(PathBuf, Vec<String>)> {
+) -> Vec<(PathBuf, IncludeContext)> {
     let mut results = Vec::new();
-    let parent_dir = parent_path.parent().unwrap_or(Path::new("."));
-    let resolve_dir = prefix.unwrap_or(parent_dir);
+    let resolve_dir = base_dir(prefix, parent_path);
 
     // Start with the include context from the config (from parent file)
     let base_context = config.include_context.clone();
 
     // Recursively find includes and track their context
     find_includes_recursive(
+        source,
         &config.items,
         resolve_dir,
         &base_context,
@@ -198,11 +294,12 @@ fn find_include_paths_with_context(
 
 /// Recursively find include directives while tracking the context stack
 fn find_includes_recursive(
+    source: &dyn ConfigSource,
     items: &[crate::parser::ast::ConfigItem],
     parent_dir: &Path,
-    context: &[String],
+    context: &IncludeContext,
     path_mappings: &[PathMapping],
-    results: &mut Vec<(PathBuf, Vec<String>)>,
+    results: &mut Vec<(PathBuf, IncludeContext)>,
 ) {
     use crate::parser::ast::ConfigItem;
 
@@ -211,17 +308,18 @@ fn find_includes_recursive(
             if directive.is("include")
                 && let Some(pattern) = directive.first_arg()
             {
-                let resolved = resolve_include_pattern(pattern, parent_dir, path_mappings);
+                let resolved = resolve_include_pattern(source, pattern, parent_dir, path_mappings);
                 for path in resolved {
-                    results.push((path, context.to_vec()));
+                    results.push((path, context.clone()));
                 }
             }
 
             // Recurse into blocks with updated context
             if let Some(block) = &directive.block {
-                let mut new_context = context.to_vec();
-                new_context.push(directive.name.clone());
+                let mut new_context = context.clone();
+                new_context.push(ContextFrame::from_directive(directive));
                 find_includes_recursive(
+                    source,
                     &block.items,
                     parent_dir,
                     &new_context,
@@ -297,12 +395,11 @@ pub(crate) fn apply_path_mapping(pattern: &str, mapping: &PathMapping) -> String
 /// Resolve an include pattern (which may contain glob wildcards) to actual file paths.
 /// Path mappings are applied in order before glob expansion.
 pub(crate) fn resolve_include_pattern(
+    source: &dyn ConfigSource,
     pattern: &str,
     parent_dir: &Path,
     path_mappings: &[PathMapping],
 ) -> Vec<PathBuf> {
-    let mut paths = Vec::new();
-
     // Make the pattern absolute if it's relative
     let full_pattern = if Path::new(pattern).is_absolute() {
         pattern.to_string()
@@ -329,27 +426,9 @@ pub(crate) fn resolve_include_pattern(
         full_pattern
     };
 
-    // Expand glob pattern
-    match glob(&full_pattern) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                if entry.is_file() {
-                    paths.push(entry);
-                }
-            }
-        }
-        Err(_) => {
-            // If glob fails, try as literal path (use the mapped pattern, not the original)
-            let literal_path = PathBuf::from(&full_pattern);
-            if literal_path.is_file() {
-                paths.push(literal_path);
-            }
-        }
-    }
-
-    // Sort for consistent ordering
-    paths.sort();
-    paths
+    // Expand the glob pattern (sorted for consistent ordering); an invalid
+    // pattern is taken as a literal path
+    source.glob(&full_pattern)
 }
 
 #[cfg(test)]
@@ -479,7 +558,7 @@ mod tests {
         create_test_file(dir, "conf.d/b.conf", "server {}");
         create_test_file(dir, "conf.d/c.txt", "not a conf");
 
-        let paths = resolve_include_pattern("conf.d/*.conf", dir, &[]);
+        let paths = resolve_include_pattern(&RealFileSystem, "conf.d/*.conf", dir, &[]);
         assert_eq!(paths.len(), 2);
         assert!(paths.iter().any(|p| p.ends_with("a.conf")));
         assert!(paths.iter().any(|p| p.ends_with("b.conf")));
@@ -492,7 +571,7 @@ mod tests {
 
         create_test_file(dir, "servers/default.conf", "server {}");
 
-        let paths = resolve_include_pattern("servers/default.conf", dir, &[]);
+        let paths = resolve_include_pattern(&RealFileSystem, "servers/default.conf", dir, &[]);
         assert_eq!(paths.len(), 1);
         assert!(paths[0].ends_with("default.conf"));
     }
@@ -500,7 +579,8 @@ mod tests {
     #[test]
     fn test_resolve_include_pattern_not_found() {
         let temp = TempDir::new().unwrap();
-        let paths = resolve_include_pattern("nonexistent/*.conf", temp.path(), &[]);
+        let paths =
+            resolve_include_pattern(&RealFileSystem, "nonexistent/*.conf", temp.path(), &[]);
         assert!(paths.is_empty());
     }
 
@@ -517,7 +597,8 @@ mod tests {
             to: "sites-available".to_string(),
         }];
 
-        let paths = resolve_include_pattern("sites-enabled/*.conf", dir, &mappings);
+        let paths =
+            resolve_include_pattern(&RealFileSystem, "sites-enabled/*.conf", dir, &mappings);
         assert_eq!(paths.len(), 1);
         assert!(paths[0].ends_with("app.conf"));
     }
@@ -537,21 +618,24 @@ mod tests {
 
         // "asites-enabled" is a different segment name; mapping must not alter it,
         // so the glob resolves to asites-available/ which does not exist → empty
-        let paths = resolve_include_pattern("asites-enabled/*.conf", dir, &mappings);
+        let paths =
+            resolve_include_pattern(&RealFileSystem, "asites-enabled/*.conf", dir, &mappings);
         assert!(
             paths.is_empty(),
             "asites-enabled should not be mapped to asites-available"
         );
 
         // "sites-enabled-old" is also a different segment → not mapped → empty
-        let paths = resolve_include_pattern("sites-enabled-old/*.conf", dir, &mappings);
+        let paths =
+            resolve_include_pattern(&RealFileSystem, "sites-enabled-old/*.conf", dir, &mappings);
         assert!(
             paths.is_empty(),
             "sites-enabled-old should not be mapped to sites-available-old"
         );
 
         // But the exact segment "sites-enabled" IS mapped → finds the file
-        let paths = resolve_include_pattern("sites-enabled/*.conf", dir, &mappings);
+        let paths =
+            resolve_include_pattern(&RealFileSystem, "sites-enabled/*.conf", dir, &mappings);
         assert_eq!(
             paths.len(),
             1,
@@ -572,7 +656,12 @@ mod tests {
             to: "nginx/sites-available".to_string(),
         }];
 
-        let paths = resolve_include_pattern("nginx/sites-enabled/*.conf", dir, &mappings);
+        let paths = resolve_include_pattern(
+            &RealFileSystem,
+            "nginx/sites-enabled/*.conf",
+            dir,
+            &mappings,
+        );
         assert_eq!(paths.len(), 1);
         assert!(paths[0].ends_with("app.conf"));
     }
@@ -595,7 +684,8 @@ mod tests {
             },
         ];
 
-        let paths = resolve_include_pattern("sites-enabled/*.conf", dir, &mappings);
+        let paths =
+            resolve_include_pattern(&RealFileSystem, "sites-enabled/*.conf", dir, &mappings);
         assert_eq!(paths.len(), 1);
         assert!(paths[0].ends_with("app.conf"));
     }
@@ -617,7 +707,8 @@ mod tests {
         // The pattern includes `[` which is invalid glob syntax.
         // After mapping: sites-enabled → sites-available, so the fallback should
         // try the mapped literal path and find the file.
-        let paths = resolve_include_pattern("sites-enabled/app[.conf", dir, &mappings);
+        let paths =
+            resolve_include_pattern(&RealFileSystem, "sites-enabled/app[.conf", dir, &mappings);
         assert_eq!(
             paths.len(),
             1,
@@ -651,7 +742,12 @@ mod tests {
             },
         ];
 
-        let paths = resolve_include_pattern("/etc/nginx/sites-enabled/*.conf", dir, &mappings);
+        let paths = resolve_include_pattern(
+            &RealFileSystem,
+            "/etc/nginx/sites-enabled/*.conf",
+            dir,
+            &mappings,
+        );
         assert_eq!(
             paths.len(),
             1,
@@ -660,6 +756,45 @@ mod tests {
         assert!(paths[0].ends_with("app.conf"));
     }
 
+    #[test]
+    fn test_find_include_chain() {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path();
+        let root = create_test_file(
+            base,
+            "nginx.conf",
+            "http {\n    include conf.d/*.conf;\n}\n",
+        );
+        create_test_file(base, "conf.d/a.conf", "include snippets/b.conf;");
+        create_test_file(base, "conf.d/snippets/b.conf", "include ../../nginx.conf;");
+        create_test_file(base, "conf.d/c.conf", "server {}");
+
+        let target = root.canonicalize().unwrap();
+        let chain = find_include_chain(
+            &RealFileSystem,
+            &base.join("conf.d/a.conf"),
+            &target,
+            &[],
+            None,
+            &mut HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(chain.len(), 3);
+        assert!(chain[0].ends_with("conf.d/a.conf"));
+        assert!(chain[1].ends_with("snippets/b.conf"));
+        assert!(chain[2].ends_with("nginx.conf"));
+
+        let chain = find_include_chain(
+            &RealFileSystem,
+            &base.join("conf.d/c.conf"),
+            &target,
+            &[],
+            None,
+            &mut HashSet::new(),
+        );
+        assert!(chain.is_none());
+    }
+
     #[test]
     fn test_collect_included_files_with_prefix() {
         // Simulate nested includes where paths are relative to a prefix directory,
@@ -728,4 +863,46 @@ mod tests {
         );
         assert_eq!(files.len(), 3, "Should collect all 3 files with prefix");
     }
+
+    #[test]
+    fn test_collect_from_memory_source() {
+        use crate::config_source::MemoryFileSystem;
+
+        let source = MemoryFileSystem::new()
+            .with_file(
+                "/etc/nginx/nginx.conf",
+                "http {\n    include conf.d/*.conf;\n    include missing.conf;\n}\n",
+            )
+            .with_file(
+                "/etc/nginx/conf.d/b.conf",
+                "server {\n    include snippets/ssl.conf;\n}\n",
+            )
+            .with_file("/etc/nginx/conf.d/a.conf", "server {}\n")
+            .with_file("/etc/nginx/snippets/ssl.conf", "ssl_protocols TLSv1.3;\n");
+
+        let files = collect_included_files_from_source(
+            &source,
+            Path::new("/etc/nginx/nginx.conf"),
+            |path| {
+                let content = source.read_file(path).map_err(|e| e.to_string())?;
+                crate::parser::parse_string(&content).map_err(|e| e.to_string())
+            },
+            IncludeContext::new(),
+            &[],
+            Some(Path::new("/etc/nginx")),
+        );
+        let collected: Vec<_> = files
+            .iter()
+            .map(|f| (f.path.to_str().unwrap(), f.include_context.to_string()))
+            .collect();
+        assert_eq!(
+            collected,
+            [
+                ("/etc/nginx/nginx.conf", String::new()),
+                ("/etc/nginx/conf.d/a.conf", "http".to_string()),
+                ("/etc/nginx/conf.d/b.conf", "http".to_string()),
+                ("/etc/nginx/snippets/ssl.conf", "http,server".to_string()),
+            ]
+        );
+    }
 }
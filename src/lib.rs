@@ -2,17 +2,32 @@
 pub use nginx_lint_common::config;
 pub use nginx_lint_common::ignore;
 pub use nginx_lint_common::parser;
+pub use nginx_lint_common::variables;
 
 // Local modules with CLI-specific functionality
 pub mod cache;
+pub mod diff;
+pub mod docgen;
 pub mod docs;
+pub mod embed;
 pub mod linter;
+pub mod preset;
 pub mod rules;
+pub mod semantic;
+pub mod template;
 
 // CLI-only modules (require filesystem access)
 #[cfg(feature = "cli")]
+pub mod archive;
+#[cfg(feature = "cli")]
+pub mod config_source;
+#[cfg(feature = "cli")]
+pub mod crossplane;
+#[cfg(feature = "cli")]
 pub mod include;
 #[cfg(feature = "cli")]
+pub mod lint_cache;
+#[cfg(feature = "cli")]
 pub mod reporter;
 
 // WASM module
@@ -25,26 +40,34 @@ pub mod plugin;
 
 // Re-export commonly used types from nginx-lint-common
 pub use nginx_lint_common::{
-    Color, ColorConfig, ColorMode, FilterResult, IgnoreTracker, IgnoreWarning, IncludeConfig,
-    LintConfig, PathMapping, ValidationError, filter_errors, parse_config, parse_context_comment,
-    parse_string, parse_string_with_errors,
+    Color, ColorConfig, ColorMode, Environment, FilterResult, IgnoreTracker, IgnoreWarning,
+    IncludeConfig, LintConfig, PathMapping, RulePreset, TrustPolicy, ValidationError,
+    filter_errors, parse_config, parse_config_with_raw_blocks, parse_context_comment, parse_string,
+    parse_string_with_errors, parse_string_with_errors_and_raw_blocks,
 };
 
 // Re-export from local modules
 pub use docs::{RuleDoc, RuleDocOwned};
 #[cfg(feature = "cli")]
+pub use embed::lint_path;
+pub use embed::{FileReport, LintOptions, LintReport, LintSettings, lint_str};
+#[cfg(feature = "cli")]
 pub use linter::RuleProfile;
-pub use linter::{Fix, LintError, LintRule, Linter, Severity};
-pub use nginx_lint_common::RULE_CATEGORIES;
+pub use linter::{Applicability, Fix, FixAlternative, LintError, LintRule, Linter, Severity};
 pub use nginx_lint_common::{
-    FixApplyResult, apply_fixes_to_content, apply_fixes_to_content_detailed, compute_line_starts,
-    normalize_line_fix,
+    FixApplyResult, FixConflict, apply_fixes, apply_fixes_to_content,
+    apply_fixes_to_content_detailed, compare_diagnostics, compute_line_starts, normalize_line_fix,
+    sort_diagnostics,
 };
+pub use nginx_lint_common::{PLUGIN_ERROR_RULE, RULE_CATEGORIES};
 
 #[cfg(feature = "cli")]
-pub use include::{IncludedFile, collect_included_files, collect_included_files_with_context};
+pub use include::{
+    IncludedFile, collect_included_files, collect_included_files_from_source,
+    collect_included_files_with_context,
+};
 #[cfg(feature = "cli")]
-pub use reporter::{OutputFormat, Reporter};
+pub use reporter::{ExitPolicy, OutputFormat, Reporter};
 
 #[cfg(feature = "cli")]
 use std::fs;
@@ -70,7 +93,9 @@ pub fn syntax_errors_to_lint_errors(
                 severity: Severity::Error,
                 line: Some(pos.line),
                 column: Some(pos.column),
+                end_column: None,
                 fixes: Vec::new(),
+                alternatives: Vec::new(),
             }
         })
         .collect()
@@ -78,8 +103,10 @@ pub fn syntax_errors_to_lint_errors(
 
 /// Apply fixes to a file
 /// Returns the application result, including applied and skipped fix counts
+///
+/// Use [`apply_fixes`] to apply fixes to an in-memory source instead.
 #[cfg(feature = "cli")]
-pub fn apply_fixes(path: &Path, errors: &[LintError]) -> std::io::Result<FixApplyResult> {
+pub fn apply_fixes_to_file(path: &Path, errors: &[LintError]) -> std::io::Result<FixApplyResult> {
     let content = fs::read_to_string(path)?;
     let fixes: Vec<_> = errors.iter().flat_map(|e| e.fixes.iter()).collect();
 
@@ -0,0 +1,377 @@
+//! Lint results kept between runs (`--cache`)
+//!
+//! Like ESLint's `--cache`, a file whose content has not changed since the
+//! last run is not linted again; its diagnostics are taken from the cache.
+//! Entries are keyed by a hash of the file's content and the context it is
+//! included in, and the whole cache is dropped when its fingerprint (the
+//! nginx-lint version, the configuration and the options that change what
+//! the rules report) differs from the run's.
+//!
+//! Cross-file rules see every file at once, so their results are cached as
+//! one entry keyed by the [`DependencyGraph`] of the run: the files linted
+//! and the shared symbols each of them defines and references. They run
+//! again when a file is added or removed or its symbols change, not on
+//! every edit. Rules reading other files from disk are not cached at all;
+//! the caller runs them on every file.
+//!
+//! ```
+//! use nginx_lint::lint_cache::{CachedResult, LintCache, digest};
+//! use nginx_lint::parser::context::IncludeContext;
+//! use std::path::Path;
+//!
+//! let dir = tempfile::TempDir::new().unwrap();
+//! let path = dir.path().join("cache.json");
+//! let fingerprint = digest(["0.18.0", "[rules.indent]\nindent_size = 2\n"]);
+//! let key = LintCache::file_key("server {}\n", &IncludeContext::new());
+//!
+//! let mut cache = LintCache::load(&path, &fingerprint);
+//! assert!(cache.get(Path::new("app.conf"), &key).is_none());
+//! cache.insert(Path::new("app.conf"), key.clone(), CachedResult::default());
+//! cache.save().unwrap();
+//!
+//! let cache = LintCache::load(&path, &fingerprint);
+//! assert!(cache.get(Path::new("app.conf"), &key).is_some());
+//! // Another configuration starts from an empty cache
+//! let cache = LintCache::load(&path, &digest(["0.18.0", ""]));
+//! assert!(cache.get(Path::new("app.conf"), &key).is_none());
+//! ```
+
+use crate::LintError;
+use crate::semantic::{SourceFile, SymbolTable};
+use nginx_lint_common::parser::context::IncludeContext;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Version of the cache file layout
+const FORMAT_VERSION: u32 = 2;
+
+/// Hex-encoded SHA-256 of `parts`; each part is length-prefixed, so moving
+/// bytes from one part to the next changes the digest
+pub fn digest<I, P>(parts: I) -> String
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<[u8]>,
+{
+    let mut hasher = Sha256::new();
+    for part in parts {
+        let part = part.as_ref();
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The diagnostics of one file, as reported after ignore comments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedResult {
+    pub errors: Vec<LintError>,
+    pub ignored_count: usize,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheData {
+    format: u32,
+    fingerprint: String,
+    files: BTreeMap<PathBuf, FileEntry>,
+    cross_file: Option<CrossFileEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileEntry {
+    key: String,
+    result: CachedResult,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CrossFileEntry {
+    graph: DependencyGraph,
+    results: BTreeMap<PathBuf, CachedResult>,
+}
+
+/// What the results of the cross-file pass depend on: the files of the
+/// run, in order, and what each contributes to the
+/// [`SymbolTable`](crate::semantic::SymbolTable).
+///
+/// Each file is a node holding its include context (the blocks of the
+/// including file it is included in) and a digest of its symbol summary
+/// (see [`SymbolTable::file_summary`]) and ignore comments. Adding or
+/// removing an include, or editing an upstream, a `map` or a zone, changes
+/// the graph; editing other directives does not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    nodes: Vec<DependencyNode>,
+    /// Digest of the files' contents, when the results depend on them
+    /// beyond the symbols (see [`with_contents`](Self::with_contents))
+    contents: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct DependencyNode {
+    path: PathBuf,
+    context: String,
+    symbols: String,
+}
+
+impl DependencyGraph {
+    /// The graph of `files`, given in the order they are linted
+    pub fn build(files: &[SourceFile<'_>]) -> Self {
+        let nodes = files
+            .iter()
+            .map(|file| DependencyNode {
+                path: file.path.to_path_buf(),
+                context: file.config.include_context.to_string(),
+                symbols: digest([
+                    SymbolTable::file_summary(file),
+                    ignore_comments(file.content),
+                ]),
+            })
+            .collect();
+        Self {
+            nodes,
+            contents: None,
+        }
+    }
+
+    /// Also depend on the exact `contents` of the files, e.g. when the
+    /// positions of diagnostics are mapped back through templates
+    pub fn with_contents<'a>(mut self, contents: impl IntoIterator<Item = &'a str>) -> Self {
+        self.contents = Some(digest(contents));
+        self
+    }
+
+    /// Files whose node differs from the one in `previous`: added,
+    /// removed, moved to another context, or with other symbols
+    pub fn changed_files(&self, previous: &Self) -> BTreeSet<PathBuf> {
+        let differing = |a: &Self, b: &Self| {
+            a.nodes
+                .iter()
+                .filter(|node| !b.nodes.contains(node))
+                .map(|node| node.path.clone())
+                .collect::<Vec<_>>()
+        };
+        let mut changed: BTreeSet<PathBuf> = differing(self, previous).into_iter().collect();
+        changed.extend(differing(previous, self));
+        changed
+    }
+}
+
+/// The ignore comments of `content` with their line numbers, which decide
+/// what cross-file diagnostics are filtered out
+fn ignore_comments(content: &str) -> String {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.contains("nginx-lint:"))
+        .map(|(index, line)| format!("{}:{}\n", index + 1, line.trim()))
+        .collect()
+}
+
+/// The lint results of previous runs, stored as JSON in one file
+pub struct LintCache {
+    path: PathBuf,
+    data: CacheData,
+}
+
+impl LintCache {
+    /// Load the cache stored at `path`.
+    ///
+    /// A missing or unreadable file, or one written with another
+    /// `fingerprint`, gives an empty cache.
+    pub fn load(path: &Path, fingerprint: &str) -> Self {
+        let data = std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheData>(&bytes).ok())
+            .filter(|data| data.format == FORMAT_VERSION && data.fingerprint == fingerprint)
+            .unwrap_or_else(|| CacheData {
+                format: FORMAT_VERSION,
+                fingerprint: fingerprint.to_string(),
+                ..CacheData::default()
+            });
+        Self {
+            path: path.to_path_buf(),
+            data,
+        }
+    }
+
+    /// Where the cache is stored
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Key of a file with `content`, included in `context`
+    pub fn file_key(content: &str, context: &IncludeContext) -> String {
+        digest([context.to_string().as_bytes(), content.as_bytes()])
+    }
+
+    /// The cached result of the file at `path`, if its key is still `key`
+    pub fn get(&self, path: &Path, key: &str) -> Option<&CachedResult> {
+        self.data
+            .files
+            .get(path)
+            .filter(|entry| entry.key == key)
+            .map(|entry| &entry.result)
+    }
+
+    pub fn insert(&mut self, path: &Path, key: String, result: CachedResult) {
+        self.data
+            .files
+            .insert(path.to_path_buf(), FileEntry { key, result });
+    }
+
+    /// The cached cross-file results, if they were computed for `graph`
+    pub fn cross_file(&self, graph: &DependencyGraph) -> Option<&BTreeMap<PathBuf, CachedResult>> {
+        self.data
+            .cross_file
+            .as_ref()
+            .filter(|entry| entry.graph == *graph)
+            .map(|entry| &entry.results)
+    }
+
+    /// The dependency graph of the cached cross-file results
+    pub fn cross_file_graph(&self) -> Option<&DependencyGraph> {
+        self.data.cross_file.as_ref().map(|entry| &entry.graph)
+    }
+
+    /// Replace the cross-file results; the cache holds those of one graph
+    pub fn set_cross_file(
+        &mut self,
+        graph: DependencyGraph,
+        results: BTreeMap<PathBuf, CachedResult>,
+    ) {
+        self.data.cross_file = Some(CrossFileEntry { graph, results });
+    }
+
+    /// Write the cache back, creating its directory if needed.
+    ///
+    /// The file is replaced atomically, so concurrent runs never read a
+    /// partially written cache.
+    pub fn save(&self) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let temp = self
+            .path
+            .with_extension(format!("tmp.{}", std::process::id()));
+        std::fs::write(&temp, serde_json::to_vec(&self.data)?)?;
+        std::fs::rename(&temp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Severity;
+    use crate::parser::parse_string;
+
+    fn graph(files: &[(&str, &str)]) -> DependencyGraph {
+        let configs: Vec<_> = files
+            .iter()
+            .map(|(_, src)| parse_string(src).unwrap())
+            .collect();
+        let sources: Vec<SourceFile> = files
+            .iter()
+            .zip(&configs)
+            .map(|((path, src), config)| SourceFile {
+                path: Path::new(path),
+                config,
+                content: src,
+            })
+            .collect();
+        DependencyGraph::build(&sources)
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let upstreams = "upstream backend {\n    server 127.0.0.1:8080;\n}\n";
+        let site = "server {\n    location / {\n        proxy_pass http://backend;\n    }\n}\n";
+        let base = graph(&[("upstreams.conf", upstreams), ("site.conf", site)]);
+
+        // Edits that leave the symbols alone keep the graph
+        let edited = site.replace("location / {", "location / { # app");
+        assert_eq!(
+            base,
+            graph(&[("upstreams.conf", upstreams), ("site.conf", &edited)])
+        );
+
+        let edited = site.replace("http://backend", "http://other");
+        let changed = graph(&[("upstreams.conf", upstreams), ("site.conf", &edited)]);
+        assert_eq!(
+            changed.changed_files(&base),
+            BTreeSet::from([PathBuf::from("site.conf")])
+        );
+
+        let ignored = format!("# nginx-lint:ignore unused-upstream\n{}", upstreams);
+        assert_ne!(
+            base,
+            graph(&[("upstreams.conf", &ignored), ("site.conf", site)])
+        );
+        let removed = graph(&[("upstreams.conf", upstreams)]);
+        assert_eq!(
+            removed.changed_files(&base),
+            BTreeSet::from([PathBuf::from("site.conf")])
+        );
+        assert_ne!(base.clone().with_contents([upstreams, site]), base);
+    }
+
+    #[test]
+    fn test_file_key_includes_context() {
+        let content = "server_name example.com;\n";
+        assert_ne!(
+            LintCache::file_key(content, &IncludeContext::new()),
+            LintCache::file_key(content, &IncludeContext::parse("http,server"))
+        );
+        assert_ne!(digest(["ab", "c"]), digest(["a", "bc"]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("lint/cache.json");
+        let error = LintError::new(
+            "server-tokens-enabled",
+            "security",
+            "server_tokens should be 'off'",
+            Severity::Warning,
+        )
+        .with_location(2, 5);
+
+        let mut cache = LintCache::load(&path, "fingerprint");
+        cache.insert(
+            Path::new("a.conf"),
+            "key-a".to_string(),
+            CachedResult {
+                errors: vec![error],
+                ignored_count: 1,
+            },
+        );
+        let cross_graph = graph(&[("a.conf", "upstream backend {}\n")]);
+        cache.set_cross_file(cross_graph.clone(), BTreeMap::new());
+        cache.save().unwrap();
+
+        let cache = LintCache::load(&path, "fingerprint");
+        let cached = cache.get(Path::new("a.conf"), "key-a").unwrap();
+        assert_eq!(cached.errors[0].rule, "server-tokens-enabled");
+        assert_eq!(cached.errors[0].line, Some(2));
+        assert_eq!(cached.ignored_count, 1);
+        assert!(cache.get(Path::new("a.conf"), "changed").is_none());
+        assert!(cache.cross_file(&cross_graph).is_some());
+        assert!(
+            cache
+                .cross_file(&graph(&[("a.conf", "upstream other {}\n")]))
+                .is_none()
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(
+            LintCache::load(&path, "fingerprint")
+                .get(Path::new("a.conf"), "key-a")
+                .is_none()
+        );
+    }
+}
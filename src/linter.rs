@@ -1,14 +1,19 @@
 // Re-export core types from nginx-lint-common
-use nginx_lint_common::config::LintConfig;
+use crate::semantic::{CrossFileRule, SourceFile, SymbolTable};
+use nginx_lint_common::config::{LintConfig, RulePreset};
 use nginx_lint_common::ignore::IgnoreTracker;
-pub use nginx_lint_common::linter::{Fix, LintError, LintRule, Severity};
-use nginx_lint_common::linter::{run_rule, run_rule_with_content};
+use nginx_lint_common::linter::{AnalysisCache, run_rule, run_rule_with_content, sort_diagnostics};
+pub use nginx_lint_common::linter::{
+    Applicability, Fix, FixAlternative, LintError, LintRule, Severity,
+};
 use nginx_lint_common::nginx_version::{NginxVersion, format_range, is_in_range};
 use nginx_lint_common::parser::ast::Config;
+use nginx_lint_common::parser::parse_string_with_errors_and_raw_blocks;
+use nginx_lint_common::parser::parser::SyntaxError;
 #[cfg(feature = "cli")]
 use rayon::prelude::*;
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::{Mutex, OnceLock};
 
 /// Process-wide set of `(rule_name, "min"|"max")` pairs for which we have
@@ -47,6 +52,100 @@ fn parse_plugin_bound(
     }
 }
 
+/// Process-wide set of `(rule_name, problem)` pairs already reported by
+/// [`configure_rule`], for the same reason as [`warned_invalid_bounds`].
+fn warned_option_problems() -> &'static Mutex<HashSet<(String, String)>> {
+    static WARNED: OnceLock<Mutex<HashSet<(String, String)>>> = OnceLock::new();
+    WARNED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Pass the configured `target_nginx_version`, the `[environment]` settings
+/// and the options set in the
+/// rule's `[rules.<name>]` section (other than the builtin
+/// [`RuleConfig`](nginx_lint_common::config::RuleConfig) fields) to the rule,
+/// warning once about option values it could not use.
+///
+/// [`Linter::with_config`] does this for builtin rules; callers adding rules
+/// of their own (e.g. external plugins) call it before
+/// [`add_rule`](Linter::add_rule).
+pub fn configure_rule(rule: &mut dyn LintRule, config: Option<&LintConfig>) {
+    let Some(config) = config else {
+        return;
+    };
+    if let Some(version) = config.target_nginx_version() {
+        rule.set_target_nginx_version(version);
+    }
+    if !config.environment().is_empty() {
+        rule.set_environment(config.environment());
+    }
+    if let Some(options) = config.rule_options(rule.name()) {
+        for problem in rule.configure(options) {
+            warn_option_problem(rule.name(), &problem);
+        }
+    }
+}
+
+/// Warn about a problem with a configured option of `rule`, once per process
+pub(crate) fn warn_option_problem(rule: &str, problem: &str) {
+    let mut warned = warned_option_problems()
+        .lock()
+        .expect("warned set poisoned");
+    if warned.insert((rule.to_string(), problem.to_string())) {
+        eprintln!("warning: rule '{}': {}", rule, problem);
+    }
+}
+
+/// Process-wide set of rules already warned about for declaring an unknown
+/// preset, for the same reason as [`warned_invalid_bounds`].
+fn warned_unknown_presets() -> &'static Mutex<HashSet<String>> {
+    static WARNED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    WARNED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// The smallest preset of the rule `name`: `declared` by a plugin in its
+/// spec, or by a native rule in its [`RuleDoc`](crate::docs::RuleDoc).
+/// An unknown name is warned about once and taken as `strict`.
+fn declared_preset(name: &str, declared: Option<&str>) -> Option<RulePreset> {
+    let Some(declared) = declared else {
+        return crate::docs::get_rule_doc(name).and_then(|doc| doc.preset);
+    };
+    let preset = RulePreset::from_name(declared);
+    if preset.is_none() {
+        let mut warned = warned_unknown_presets()
+            .lock()
+            .expect("warned set poisoned");
+        if warned.insert(name.to_string()) {
+            eprintln!(
+                "warning: rule '{}' declares an unknown preset '{}' (treated as strict)",
+                name, declared
+            );
+        }
+    }
+    preset
+}
+
+/// Whether a rule is in the preset `config` starts from, if any.
+///
+/// Rules the config has a `[rules.<name>]` section for, and the rules it
+/// defines (`[[custom_rules]]`), are always in, so that configuring a rule is
+/// enough to run it. [`Linter::with_config`]
+/// applies this to builtin rules; callers adding rules of their own (e.g.
+/// external plugins) check it before [`add_rule`](Linter::add_rule).
+pub fn in_preset(name: &str, declared: Option<&str>, config: Option<&LintConfig>) -> bool {
+    let Some(config) = config else {
+        return true;
+    };
+    match config.preset() {
+        Some(preset) => {
+            config.rule_explicitly_configured(name)
+                || (!LintConfig::KNOWN_RULE_NAMES.contains(&name)
+                    && config.custom_rules().iter().any(|r| r.name == name))
+                || preset.includes(declared_preset(name, declared))
+        }
+        None => true,
+    }
+}
+
 /// Result of evaluating a rule against the configured target nginx version.
 enum VersionGate {
     /// Rule applies to the target version, or filtering is disabled.
@@ -107,22 +206,45 @@ fn evaluate_version_gate(
 
 pub struct Linter {
     rules: Vec<Box<dyn LintRule>>,
+    /// Rules that need the whole config tree; run by
+    /// [`lint_cross_file`](Self::lint_cross_file) rather than per file.
+    cross_file_rules: Vec<Box<dyn CrossFileRule>>,
     /// Rule names that exist in the catalog but are intentionally not running
     /// in this `Linter` (e.g. filtered out by the CLI's `--rule-only`). They
     /// are still recognised by ignore-comment parsing — both as valid rule
     /// names *and* as dormant rules whose unused ignore directives are
     /// suppressed — so toggling the filter does not churn the user's config.
     inactive_rules: HashSet<String>,
+    /// Severities configured per rule with `severity = "..."`, applied to
+    /// every diagnostic the rule reports.
+    severity_overrides: HashMap<String, Severity>,
+    /// Raw block directives configured beyond the built-ins; files must be
+    /// parsed with them (see [`parse`](Self::parse))
+    raw_block_directives: Vec<String>,
 }
 
 impl Linter {
     pub fn new() -> Self {
         Self {
             rules: Vec::new(),
+            cross_file_rules: Vec::new(),
             inactive_rules: HashSet::new(),
+            severity_overrides: HashMap::new(),
+            raw_block_directives: Vec::new(),
         }
     }
 
+    /// Raw block directives configured beyond the built-ins
+    pub fn raw_block_directives(&self) -> &[String] {
+        &self.raw_block_directives
+    }
+
+    /// Parse `content` with error recovery, treating the configured raw
+    /// block directives as raw blocks
+    pub fn parse(&self, content: &str) -> (Config, Vec<SyntaxError>) {
+        parse_string_with_errors_and_raw_blocks(content, &self.raw_block_directives)
+    }
+
     pub fn with_default_rules() -> Self {
         Self::with_config(None, None)
     }
@@ -150,12 +272,60 @@ impl Linter {
         rule_only: Option<&HashSet<String>>,
     ) -> Self {
         #[cfg(feature = "cli")]
-        use crate::rules::IncludePathExists;
+        return Self::with_config_source(
+            config,
+            include_prefix,
+            rule_only,
+            std::sync::Arc::new(crate::config_source::RealFileSystem),
+        );
+        #[cfg(not(feature = "cli"))]
+        Self::build(config, include_prefix, rule_only)
+    }
+
+    /// Like [`with_config_and_rule_only`](Self::with_config_and_rule_only),
+    /// but the rules following includes and checking referenced files look
+    /// them up in `source` instead of on disk, e.g. for a configuration
+    /// tree read from an archive.
+    #[cfg(feature = "cli")]
+    pub fn with_config_source(
+        config: Option<&LintConfig>,
+        include_prefix: Option<&Path>,
+        rule_only: Option<&HashSet<String>>,
+        source: std::sync::Arc<dyn crate::config_source::ConfigSource>,
+    ) -> Self {
+        Self::build(config, include_prefix, rule_only, source)
+    }
+
+    fn build(
+        config: Option<&LintConfig>,
+        include_prefix: Option<&Path>,
+        rule_only: Option<&HashSet<String>>,
+        #[cfg(feature = "cli")] source: std::sync::Arc<dyn crate::config_source::ConfigSource>,
+    ) -> Self {
+        use crate::rules::{
+            AccessLogMisconfigured, CustomRule, DuplicateDefaultServer, GeneratedRegionModified,
+            HttpsRedirectMisconfigured, Indent, InvalidDirectiveContext, MissingResolver,
+            MissingSemicolon, MissingServerName, ProxyCacheIncomplete, ProxyPassUpstreamMismatch,
+            UnclosedQuote, UndefinedLimitZone, UndefinedUpstream, UndefinedVariable,
+            UnmatchedBraces, UnusedLimitZone, UnusedUpstream, UnusedVariable,
+            UpstreamKeepaliveIncomplete,
+        };
+        #[cfg(feature = "cli")]
         use crate::rules::{
-            Indent, InvalidDirectiveContext, MissingSemicolon, UnclosedQuote, UnmatchedBraces,
+            AuthBasicUserFileMissing, IncludeCycle, IncludeLayering, IncludePathExists,
+            LuaBlockLint, PrecompressedAssetsNotServed, RegoPolicy,
         };
 
+        // Only the rules reading the filesystem use the prefix
+        #[cfg(not(feature = "cli"))]
+        let _ = include_prefix;
+
         let mut linter = Self::new();
+        if let Some(config) = config {
+            linter.severity_overrides = config.severity_overrides();
+            linter.raw_block_directives = config.additional_raw_block_directives().to_vec();
+        }
+        let raw_block_directives = linter.raw_block_directives.clone();
 
         let enabled_in_config = |name: &str| {
             config
@@ -175,6 +345,13 @@ impl Linter {
             let mut catalog: Vec<&str> = LintConfig::NATIVE_RULE_NAMES.to_vec();
             #[cfg(any(feature = "wasm-builtin-plugins", feature = "native-builtin-plugins"))]
             catalog.extend_from_slice(crate::plugin::BUILTIN_PLUGIN_NAMES);
+            catalog.extend(
+                config
+                    .map(|c| c.custom_rules())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|r| r.name.as_str()),
+            );
             linter.inactive_rules.extend(
                 catalog
                     .into_iter()
@@ -188,15 +365,20 @@ impl Linter {
             let additional_block_directives = config
                 .map(|c| c.additional_block_directives().to_vec())
                 .unwrap_or_default();
-            linter.add_rule(Box::new(UnmatchedBraces::with_additional_block_directives(
-                additional_block_directives,
-            )));
+            linter.add_rule(Box::new(
+                UnmatchedBraces::with_additional_block_directives(additional_block_directives)
+                    .with_raw_block_directives(raw_block_directives.clone()),
+            ));
         }
         if is_enabled("unclosed-quote") {
-            linter.add_rule(Box::new(UnclosedQuote));
+            linter.add_rule(Box::new(
+                UnclosedQuote::default().with_raw_block_directives(raw_block_directives.clone()),
+            ));
         }
         if is_enabled("missing-semicolon") {
-            linter.add_rule(Box::new(MissingSemicolon));
+            linter.add_rule(Box::new(
+                MissingSemicolon::default().with_raw_block_directives(raw_block_directives),
+            ));
         }
         #[cfg(feature = "cli")]
         if is_enabled("include-path-exists") {
@@ -218,7 +400,29 @@ impl Linter {
             } else {
                 IncludePathExists::new()
             };
-            linter.add_rule(Box::new(rule));
+            linter.add_rule(Box::new(rule.with_source(source.clone())));
+        }
+        // include-cycle follows includes the same way the CLI collects them
+        #[cfg(feature = "cli")]
+        if is_enabled("include-cycle") {
+            let mappings = config
+                .map(|c| c.include_path_mappings().to_vec())
+                .unwrap_or_default();
+            linter.add_rule(Box::new(
+                IncludeCycle::with_path_mappings_and_prefix(
+                    mappings,
+                    include_prefix.map(|p| p.to_path_buf()),
+                )
+                .with_source(source.clone()),
+            ));
+        }
+        // Password files, resolved like includes (turned on by --check-paths)
+        #[cfg(feature = "cli")]
+        if is_enabled("auth-basic-user-file-missing") {
+            linter.add_rule(Box::new(
+                AuthBasicUserFileMissing::with_prefix(include_prefix.map(|p| p.to_path_buf()))
+                    .with_source(source),
+            ));
         }
         // invalid-directive-context: use native implementation when additional_contexts is configured
         // (for extension modules like nginx-rtmp-module); otherwise use WASM/native plugin
@@ -241,16 +445,133 @@ impl Linter {
 
         // Style rules
         if is_enabled("indent") {
-            let rule = if let Some(indent_size) = config
-                .and_then(|c| c.get_rule_config("indent"))
-                .and_then(|cfg| cfg.indent_size)
-            {
-                Indent { indent_size }
-            } else {
-                Indent::default()
+            linter.add_rule(Box::new(Indent::from_config(config)));
+        }
+
+        // Best practices rules that look at the document root on disk
+        #[cfg(feature = "cli")]
+        if is_enabled("precompressed-assets-not-served") {
+            let docroot = config
+                .and_then(|c| c.precompressed_assets_docroot())
+                .map(PathBuf::from);
+            linter.add_rule(Box::new(
+                PrecompressedAssetsNotServed::with_docroot_and_prefix(
+                    docroot,
+                    include_prefix.map(|p| p.to_path_buf()),
+                ),
+            ));
+        }
+
+        // Regions written by config-management tools
+        if is_enabled("generated-region-modified") {
+            linter.add_rule(Box::new(GeneratedRegionModified));
+        }
+
+        // Layering of the project's files, set in the rule config
+        #[cfg(feature = "cli")]
+        if is_enabled("include-layering") {
+            let layers = config
+                .map(|c| c.include_layering_layers())
+                .unwrap_or_default();
+            let (rule, problems) = IncludeLayering::new(layers);
+            for problem in problems {
+                warn_option_problem(rule.name(), &problem);
+            }
+            linter.add_rule(Box::new(rule));
+        }
+
+        // Lua code of *_by_lua_block directives, checked by an external linter
+        #[cfg(feature = "cli")]
+        if is_enabled("lua-block-lint") {
+            let rule = match config.and_then(|c| c.lua_block_lint_command()) {
+                Some([]) => {
+                    warn_option_problem("lua-block-lint", "command is empty; using luacheck");
+                    LuaBlockLint::new()
+                }
+                Some(command) => LuaBlockLint::with_command(command.to_vec()),
+                None => LuaBlockLint::new(),
             };
             linter.add_rule(Box::new(rule));
         }
+
+        // Organisation policies in Rego, evaluated by opa on the AST
+        #[cfg(feature = "cli")]
+        if is_enabled("rego-policy") {
+            let policies = config
+                .map(|c| c.rego_policy_policies().to_vec())
+                .unwrap_or_default();
+            let mut rule = RegoPolicy::new(policies);
+            if let Some(query) = config.and_then(|c| c.rego_policy_query()) {
+                rule = rule.with_query(query.to_string());
+            }
+            match config.and_then(|c| c.rego_policy_command()) {
+                Some([]) => warn_option_problem("rego-policy", "command is empty; using opa eval"),
+                Some(command) => rule = rule.with_command(command.to_vec()),
+                None => {}
+            }
+            linter.add_rule(Box::new(rule));
+        }
+
+        // Cross-file rules
+        if is_enabled("undefined-upstream") {
+            linter.add_cross_file_rule(Box::new(UndefinedUpstream));
+        }
+        if is_enabled("unused-upstream") {
+            linter.add_cross_file_rule(Box::new(UnusedUpstream));
+        }
+        if is_enabled("upstream-keepalive-incomplete") {
+            linter.add_cross_file_rule(Box::new(UpstreamKeepaliveIncomplete));
+        }
+        if is_enabled("proxy-pass-upstream-mismatch") {
+            linter.add_cross_file_rule(Box::new(ProxyPassUpstreamMismatch));
+        }
+        if is_enabled("missing-resolver") {
+            linter.add_cross_file_rule(Box::new(MissingResolver));
+        }
+        if is_enabled("access-log-misconfigured") {
+            linter.add_cross_file_rule(Box::new(AccessLogMisconfigured));
+        }
+        if is_enabled("undefined-limit-zone") {
+            linter.add_cross_file_rule(Box::new(UndefinedLimitZone));
+        }
+        if is_enabled("unused-limit-zone") {
+            linter.add_cross_file_rule(Box::new(UnusedLimitZone));
+        }
+        if is_enabled("proxy-cache-incomplete") {
+            linter.add_cross_file_rule(Box::new(ProxyCacheIncomplete));
+        }
+        if is_enabled("undefined-variable") {
+            let known_variables = config
+                .and_then(|c| c.undefined_variable_known_variables())
+                .map(|v| v.to_vec())
+                .unwrap_or_default();
+            let known_prefixes = config
+                .and_then(|c| c.undefined_variable_known_prefixes())
+                .map(|v| v.to_vec())
+                .unwrap_or_default();
+            linter.add_cross_file_rule(Box::new(UndefinedVariable::new(
+                known_variables,
+                known_prefixes,
+            )));
+        }
+        if is_enabled("unused-variable") {
+            linter.add_cross_file_rule(Box::new(UnusedVariable));
+        }
+        if is_enabled("missing-server-name") {
+            linter.add_cross_file_rule(Box::new(MissingServerName));
+        }
+        if is_enabled("https-redirect-misconfigured") {
+            let require_redirect_server = config
+                .and_then(|c| c.https_redirect_require_redirect_server())
+                .unwrap_or(true);
+            linter.add_cross_file_rule(Box::new(HttpsRedirectMisconfigured::new(
+                require_redirect_server,
+            )));
+        }
+        if is_enabled("duplicate-default-server") {
+            linter.add_cross_file_rule(Box::new(DuplicateDefaultServer));
+        }
+
         // block-lines: use configured max_block_lines if specified
         #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
         let use_configured_block_lines = config
@@ -267,41 +588,91 @@ impl Linter {
             let plugin = block_lines_plugin::BlockLinesPlugin::with_max_lines(max_lines);
             linter.add_rule(Box::new(NativePluginRule::with_plugin(plugin)));
         }
-        // directive-inheritance: use configured excluded/additional directives if specified
+        // proxy-pass-hardcoded-ip: use configured allowed_addresses if specified
         #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
-        let use_configured_directive_inheritance = config
-            .map(|c| {
-                c.directive_inheritance_excluded()
-                    .is_some_and(|v| !v.is_empty())
-                    || c.directive_inheritance_additional()
-                        .is_some_and(|v| !v.is_empty())
-            })
-            .unwrap_or(false);
+        let use_configured_proxy_pass_hardcoded_ip = config
+            .and_then(|c| c.proxy_pass_allowed_addresses())
+            .is_some();
         #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
-        if is_enabled("directive-inheritance") && use_configured_directive_inheritance {
+        if is_enabled("proxy-pass-hardcoded-ip") && use_configured_proxy_pass_hardcoded_ip {
             use nginx_lint_plugin::native::NativePluginRule;
-            let excluded = config
-                .and_then(|c| c.directive_inheritance_excluded())
-                .map(|v| v.to_vec())
-                .unwrap_or_default();
-            let additional = config
-                .and_then(|c| c.directive_inheritance_additional())
-                .map(|v| {
-                    v.iter()
-                        .map(|a| directive_inheritance_plugin::DirectiveSpecOwned {
-                            name: a.name.clone(),
-                            case_insensitive: a.case_insensitive,
-                            multi_key: a.multi_key,
-                        })
-                        .collect()
-                })
-                .unwrap_or_default();
-            let plugin = directive_inheritance_plugin::DirectiveInheritancePlugin::with_config(
-                excluded, additional,
+            let allowed = config
+                .and_then(|c| c.proxy_pass_allowed_addresses())
+                .unwrap();
+            let plugin =
+                proxy_pass_hardcoded_ip_plugin::ProxyPassHardcodedIpPlugin::with_allowed_addresses(
+                    allowed,
+                );
+            linter.add_rule(Box::new(NativePluginRule::with_plugin(plugin)));
+        }
+
+        // missing-security-headers: use configured required_headers if specified
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        let use_configured_missing_security_headers =
+            config.and_then(|c| c.required_security_headers()).is_some();
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        if is_enabled("missing-security-headers") && use_configured_missing_security_headers {
+            use nginx_lint_plugin::native::NativePluginRule;
+            let headers = config.and_then(|c| c.required_security_headers()).unwrap();
+            let plugin =
+                missing_security_headers_plugin::MissingSecurityHeadersPlugin::with_required_headers(
+                    headers,
+                );
+            linter.add_rule(Box::new(NativePluginRule::with_plugin(plugin)));
+        }
+
+        // proxy-timeout-not-set: use configured maximum timeouts if specified
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        let max_connect_timeout = config.and_then(|c| c.proxy_max_connect_timeout());
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        let max_read_timeout = config.and_then(|c| c.proxy_max_read_timeout());
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        let use_configured_proxy_timeout_not_set =
+            max_connect_timeout.is_some() || max_read_timeout.is_some();
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        if is_enabled("proxy-timeout-not-set") && use_configured_proxy_timeout_not_set {
+            use nginx_lint_plugin::native::NativePluginRule;
+            let plugin = proxy_timeout_not_set_plugin::ProxyTimeoutNotSetPlugin::with_max_timeouts(
+                max_connect_timeout,
+                max_read_timeout,
             );
             linter.add_rule(Box::new(NativePluginRule::with_plugin(plugin)));
         }
 
+        // unsafe-x-forwarded-for: use configured deployment if specified
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        let xff_deployment = config.and_then(|c| c.x_forwarded_for_deployment());
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        let use_configured_unsafe_x_forwarded_for = xff_deployment.is_some();
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        if is_enabled("unsafe-x-forwarded-for")
+            && let Some(deployment) = xff_deployment
+        {
+            use nginx_lint_plugin::native::NativePluginRule;
+            let plugin = unsafe_x_forwarded_for_plugin::UnsafeXForwardedForPlugin::with_deployment(
+                deployment,
+            );
+            linter.add_rule(Box::new(NativePluginRule::with_plugin(plugin)));
+        }
+
+        // suspicious-proxy-settings: use configured maximum read timeout if specified
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        let suspicious_proxy_max_read_timeout =
+            config.and_then(|c| c.suspicious_proxy_max_read_timeout());
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        let use_configured_suspicious_proxy_settings = suspicious_proxy_max_read_timeout.is_some();
+        #[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+        if is_enabled("suspicious-proxy-settings")
+            && let Some(max_read_timeout) = suspicious_proxy_max_read_timeout
+        {
+            use nginx_lint_plugin::native::NativePluginRule;
+            let plugin =
+                suspicious_proxy_settings_plugin::SuspiciousProxySettingsPlugin::with_max_read_timeout(
+                    max_read_timeout,
+                );
+            linter.add_rule(Box::new(NativePluginRule::with_plugin(plugin)));
+        }
+
         // Load native plugins when native-builtin-plugins feature is enabled
         #[cfg(feature = "native-builtin-plugins")]
         {
@@ -319,8 +690,32 @@ impl Linter {
                 if plugin.name() == "block-lines" && use_configured_block_lines {
                     continue;
                 }
-                // Skip directive-inheritance if configured excluded/additional is used
-                if plugin.name() == "directive-inheritance" && use_configured_directive_inheritance
+                // Skip proxy-pass-hardcoded-ip if configured allowed_addresses is used
+                if plugin.name() == "proxy-pass-hardcoded-ip"
+                    && use_configured_proxy_pass_hardcoded_ip
+                {
+                    continue;
+                }
+                // Skip missing-security-headers if configured required_headers is used
+                if plugin.name() == "missing-security-headers"
+                    && use_configured_missing_security_headers
+                {
+                    continue;
+                }
+                // Skip proxy-timeout-not-set if configured maximum timeouts are used
+                if plugin.name() == "proxy-timeout-not-set" && use_configured_proxy_timeout_not_set
+                {
+                    continue;
+                }
+                // Skip unsafe-x-forwarded-for if configured deployment is used
+                if plugin.name() == "unsafe-x-forwarded-for"
+                    && use_configured_unsafe_x_forwarded_for
+                {
+                    continue;
+                }
+                // Skip suspicious-proxy-settings if configured maximum read timeout is used
+                if plugin.name() == "suspicious-proxy-settings"
+                    && use_configured_suspicious_proxy_settings
                 {
                     continue;
                 }
@@ -350,8 +745,24 @@ impl Linter {
                 if name == "block-lines" && use_configured_block_lines {
                     return false;
                 }
-                // Skip directive-inheritance if configured excluded/additional is used
-                if name == "directive-inheritance" && use_configured_directive_inheritance {
+                // Skip proxy-pass-hardcoded-ip if configured allowed_addresses is used
+                if name == "proxy-pass-hardcoded-ip" && use_configured_proxy_pass_hardcoded_ip {
+                    return false;
+                }
+                // Skip missing-security-headers if configured required_headers is used
+                if name == "missing-security-headers" && use_configured_missing_security_headers {
+                    return false;
+                }
+                // Skip proxy-timeout-not-set if configured maximum timeouts are used
+                if name == "proxy-timeout-not-set" && use_configured_proxy_timeout_not_set {
+                    return false;
+                }
+                // Skip unsafe-x-forwarded-for if configured deployment is used
+                if name == "unsafe-x-forwarded-for" && use_configured_unsafe_x_forwarded_for {
+                    return false;
+                }
+                // Skip suspicious-proxy-settings if configured maximum read timeout is used
+                if name == "suspicious-proxy-settings" && use_configured_suspicious_proxy_settings {
                     return false;
                 }
                 is_enabled(name)
@@ -363,6 +774,28 @@ impl Linter {
             }
         }
 
+        // Rules defined in the config; a name taken by a builtin rule or an
+        // earlier custom rule, or an invalid pattern, is warned about
+        let mut custom_names: HashSet<&str> = HashSet::new();
+        for custom in config.map(|c| c.custom_rules()).unwrap_or_default() {
+            let name = custom.name.as_str();
+            if LintConfig::KNOWN_RULE_NAMES.contains(&name) || !custom_names.insert(name) {
+                warn_option_problem(name, "a rule with this name already exists");
+                continue;
+            }
+            if !is_enabled(name) {
+                continue;
+            }
+            match CustomRule::new(custom) {
+                Ok(rule) => linter.add_rule(Box::new(rule)),
+                Err(problem) => warn_option_problem(name, &problem),
+            }
+        }
+
+        for rule in &mut linter.rules {
+            configure_rule(rule.as_mut(), config);
+        }
+
         // Apply target_nginx_version filter (if configured). Rules whose
         // declared min/max range does not include the configured version are
         // dropped; if the user explicitly enabled such a rule without
@@ -416,6 +849,27 @@ impl Linter {
             linter.inactive_rules.extend(filtered_out);
         }
 
+        // Leave out the rules outside the configured preset, keeping them
+        // in `inactive_rules` like the version-filtered ones
+        if config.is_some_and(|c| c.preset().is_some()) {
+            let mut filtered_out: HashSet<String> = HashSet::new();
+            linter.rules.retain(|rule| {
+                let kept = in_preset(rule.name(), rule.preset(), config);
+                if !kept {
+                    filtered_out.insert(rule.name().to_string());
+                }
+                kept
+            });
+            linter.cross_file_rules.retain(|rule| {
+                let kept = in_preset(rule.name(), None, config);
+                if !kept {
+                    filtered_out.insert(rule.name().to_string());
+                }
+                kept
+            });
+            linter.inactive_rules.extend(filtered_out);
+        }
+
         linter
     }
 
@@ -423,12 +877,19 @@ impl Linter {
         self.rules.push(rule);
     }
 
+    /// Register a rule for the cross-file pass (see [`lint_cross_file`](Self::lint_cross_file))
+    pub fn add_cross_file_rule(&mut self, rule: Box<dyn CrossFileRule>) {
+        self.cross_file_rules.push(rule);
+    }
+
     /// Remove rules that match the predicate
     pub fn remove_rules_by_name<F>(&mut self, should_remove: F)
     where
         F: Fn(&str) -> bool,
     {
         self.rules.retain(|rule| !should_remove(rule.name()));
+        self.cross_file_rules
+            .retain(|rule| !should_remove(rule.name()));
     }
 
     /// Get a reference to all rules
@@ -436,9 +897,15 @@ impl Linter {
         &self.rules
     }
 
-    /// Get a set of all rule names registered in this linter
+    /// Get a set of all rule names registered in this linter, including
+    /// cross-file rules
     pub fn rule_names(&self) -> HashSet<String> {
-        self.rules.iter().map(|r| r.name().to_string()).collect()
+        self.rules
+            .iter()
+            .map(|r| r.name())
+            .chain(self.cross_file_rules.iter().map(|r| r.name()))
+            .map(String::from)
+            .collect()
     }
 
     /// Register rule names that are intentionally not running in this linter
@@ -457,7 +924,8 @@ impl Linter {
 
     /// Rule names currently registered as inactive (see
     /// [`set_inactive_rules`](Self::set_inactive_rules)): rules excluded by
-    /// version filtering or by the rule-only restriction of
+    /// version filtering, by the configured preset, or by the rule-only
+    /// restriction of
     /// [`with_config_and_rule_only`](Self::with_config_and_rule_only).
     pub fn inactive_rule_names(&self) -> &HashSet<String> {
         &self.inactive_rules
@@ -471,6 +939,18 @@ impl Linter {
         names
     }
 
+    /// Apply the configured [severity overrides](LintConfig::severity_overrides)
+    fn apply_severity_overrides(&self, errors: &mut [LintError]) {
+        if self.severity_overrides.is_empty() {
+            return;
+        }
+        for error in errors {
+            if let Some(severity) = self.severity_overrides.get(&error.rule) {
+                error.severity = *severity;
+            }
+        }
+    }
+
     /// Build an `IgnoreTracker` for the given content, wiring up both the
     /// valid-rule-name set (for unknown-rule warnings) and the dormant-rule
     /// set (for unused-ignore suppression). Centralised so the three
@@ -482,15 +962,64 @@ impl Linter {
         let valid_rules = self.valid_rule_names_for_ignore();
         let (mut tracker, warnings) =
             IgnoreTracker::from_content_with_rules(content, Some(&valid_rules));
-        if !self.inactive_rules.is_empty() {
-            tracker.set_dormant_rules(&self.inactive_rules);
+        // Cross-file rules do not run in the per-file pass, so their ignore
+        // directives are dormant here; `lint_cross_file` consumes them.
+        if !self.inactive_rules.is_empty() || !self.cross_file_rules.is_empty() {
+            let mut dormant = self.inactive_rules.clone();
+            dormant.extend(self.cross_file_rules.iter().map(|r| r.name().to_string()));
+            tracker.set_dormant_rules(&dormant);
         }
         (tracker, warnings)
     }
 
+    /// Run the cross-file rules over a whole config tree.
+    ///
+    /// Errors are grouped by the file they belong to and filtered through
+    /// that file's ignore comments. Returns `(errors, ignored_count)` per
+    /// file; files without cross-file errors are omitted. Unknown-rule and
+    /// unused-ignore warnings are left to the per-file pass, which already
+    /// reports them once per file.
+    pub fn lint_cross_file(
+        &self,
+        files: &[SourceFile<'_>],
+    ) -> HashMap<PathBuf, (Vec<LintError>, usize)> {
+        use nginx_lint_common::ignore::filter_errors;
+
+        if self.cross_file_rules.is_empty() {
+            return HashMap::new();
+        }
+
+        let symbols = SymbolTable::build(files);
+        let mut by_path: HashMap<PathBuf, Vec<LintError>> = HashMap::new();
+        for rule in &self.cross_file_rules {
+            for (path, error) in rule.check(&symbols) {
+                by_path.entry(path).or_default().push(error);
+            }
+        }
+        for errors in by_path.values_mut() {
+            self.apply_severity_overrides(errors);
+        }
+
+        by_path
+            .into_iter()
+            .map(|(path, errors)| {
+                let content = files
+                    .iter()
+                    .find(|f| f.path == path)
+                    .map(|f| f.content)
+                    .unwrap_or_default();
+                let (mut tracker, _) = self.make_ignore_tracker(content);
+                let result = filter_errors(errors, &mut tracker);
+                (path, (result.errors, result.ignored_count))
+            })
+            .collect()
+    }
+
     /// Run all lint rules and collect errors
     ///
-    /// Uses parallel iteration when the cli feature is enabled (via rayon)
+    /// Uses parallel iteration when the cli feature is enabled (via rayon).
+    /// Errors are returned in [canonical order](nginx_lint_common::linter::compare_diagnostics)
+    /// regardless of which rule finished first.
     #[cfg(feature = "cli")]
     pub fn lint(&self, config: &Config, path: &Path) -> Vec<LintError> {
         self.lint_internal(config, path, None)
@@ -510,33 +1039,41 @@ impl Linter {
     /// receive it directly instead of re-reading the file from disk.
     #[cfg(feature = "cli")]
     fn lint_internal(&self, config: &Config, path: &Path, content: Option<&str>) -> Vec<LintError> {
-        let shared_config = std::sync::OnceLock::new();
+        let cache = AnalysisCache::new();
 
-        self.rules
+        let mut errors = self
+            .rules
             .par_iter()
             .map(|rule| match content {
-                Some(c) => run_rule_with_content(rule.as_ref(), config, path, c, &shared_config),
-                None => run_rule(rule.as_ref(), config, path, &shared_config),
+                Some(c) => run_rule_with_content(rule.as_ref(), config, path, c, &cache),
+                None => run_rule(rule.as_ref(), config, path, &cache),
             })
             .collect::<Vec<_>>()
             .into_iter()
             .flatten()
-            .collect()
+            .collect::<Vec<_>>();
+        self.apply_severity_overrides(&mut errors);
+        sort_diagnostics(&mut errors);
+        errors
     }
 
     /// Shared implementation behind [`lint`](Self::lint)/[`lint_with_content`](Self::lint_with_content)
     /// (sequential version for WASM). See the `cli`-feature variant for details.
     #[cfg(not(feature = "cli"))]
     fn lint_internal(&self, config: &Config, path: &Path, content: Option<&str>) -> Vec<LintError> {
-        let shared_config = std::sync::OnceLock::new();
+        let cache = AnalysisCache::new();
 
-        self.rules
+        let mut errors: Vec<LintError> = self
+            .rules
             .iter()
             .flat_map(|rule| match content {
-                Some(c) => run_rule_with_content(rule.as_ref(), config, path, c, &shared_config),
-                None => run_rule(rule.as_ref(), config, path, &shared_config),
+                Some(c) => run_rule_with_content(rule.as_ref(), config, path, c, &cache),
+                None => run_rule(rule.as_ref(), config, path, &cache),
             })
-            .collect()
+            .collect();
+        self.apply_severity_overrides(&mut errors);
+        sort_diagnostics(&mut errors);
+        errors
     }
 
     /// Run all lint rules with ignore comment support
@@ -558,6 +1095,7 @@ impl Linter {
         let mut errors = result.errors;
         errors.extend(warnings_to_errors(warnings));
         errors.extend(warnings_to_errors(result.unused_warnings));
+        sort_diagnostics(&mut errors);
         (errors, result.ignored_count)
     }
 
@@ -577,9 +1115,40 @@ impl Linter {
         let mut errors = result.errors;
         errors.extend(warnings_to_errors(warnings));
         errors.extend(warnings_to_errors(result.unused_warnings));
+        sort_diagnostics(&mut errors);
         (errors, result.ignored_count)
     }
 
+    /// Lint a configuration snippet as if it were included at `context`
+    ///
+    /// `context` is the stack of enclosing blocks, outermost first: for
+    /// example `&["http", "server"]` for the body of a server block. An empty
+    /// slice lints the snippet as a complete file. The result matches what
+    /// the CLI reports for the same text with `--context`: ignore comments
+    /// are honoured and syntax errors are reported as `syntax-error`. Line
+    /// and column numbers are relative to the snippet. Cross-file rules,
+    /// which need the whole configuration, are not run.
+    pub fn lint_fragment(&self, fragment: &str, context: &[&str]) -> Vec<LintError> {
+        let (mut config, syntax_errors) = self.parse(fragment);
+        config.include_context = context.to_vec().into();
+
+        let (mut errors, _) = self.lint_with_content(&config, Path::new("fragment.conf"), fragment);
+        // Some rules report the same problem as the parser; keep one of each
+        for error in crate::syntax_errors_to_lint_errors(&syntax_errors, fragment) {
+            let is_duplicate = errors.iter().any(|e| {
+                e.rule == error.rule
+                    && e.line == error.line
+                    && e.column == error.column
+                    && e.message == error.message
+            });
+            if !is_duplicate {
+                errors.push(error);
+            }
+        }
+        sort_diagnostics(&mut errors);
+        errors
+    }
+
     /// Run all lint rules with profiling and collect errors with timing information
     ///
     /// Returns a tuple of (errors, profile_results) where profile_results contains
@@ -605,7 +1174,7 @@ impl Linter {
     ) -> (Vec<LintError>, Vec<RuleProfile>) {
         use std::time::Instant;
 
-        let shared_config = std::sync::OnceLock::new();
+        let cache = AnalysisCache::new();
 
         let results: Vec<(Vec<LintError>, RuleProfile)> = self
             .rules
@@ -613,10 +1182,8 @@ impl Linter {
             .map(|rule| {
                 let start = Instant::now();
                 let errors = match content {
-                    Some(c) => {
-                        run_rule_with_content(rule.as_ref(), config, path, c, &shared_config)
-                    }
-                    None => run_rule(rule.as_ref(), config, path, &shared_config),
+                    Some(c) => run_rule_with_content(rule.as_ref(), config, path, c, &cache),
+                    None => run_rule(rule.as_ref(), config, path, &cache),
                 };
                 let duration = start.elapsed();
                 let profile = RuleProfile {
@@ -629,7 +1196,9 @@ impl Linter {
             })
             .collect();
 
-        let errors: Vec<LintError> = results.iter().flat_map(|(e, _)| e.clone()).collect();
+        let mut errors: Vec<LintError> = results.iter().flat_map(|(e, _)| e.clone()).collect();
+        self.apply_severity_overrides(&mut errors);
+        sort_diagnostics(&mut errors);
         let profiles: Vec<RuleProfile> = results.into_iter().map(|(_, p)| p).collect();
 
         (errors, profiles)
@@ -651,6 +1220,7 @@ impl Linter {
         let mut errors = result.errors;
         errors.extend(warnings_to_errors(warnings));
         errors.extend(warnings_to_errors(result.unused_warnings));
+        sort_diagnostics(&mut errors);
         (errors, result.ignored_count, profiles)
     }
 }
@@ -874,3 +1444,119 @@ mod version_filter_tests {
         assert!(matches!(gate, VersionGate::SkipSilently));
     }
 }
+
+#[cfg(test)]
+mod preset_tests {
+    use super::*;
+
+    fn names(linter: &Linter) -> Vec<&str> {
+        linter.rules().iter().map(|r| r.name()).collect()
+    }
+
+    #[test]
+    fn in_preset_uses_declared_membership() {
+        let config = LintConfig::parse("preset = \"recommended\"\n").unwrap();
+        assert!(in_preset("demo", Some("minimal"), Some(&config)));
+        assert!(in_preset("demo", Some("recommended"), Some(&config)));
+        assert!(!in_preset("demo", Some("strict"), Some(&config)));
+        // Undeclared and unknown presets are strict
+        assert!(!in_preset("demo", None, Some(&config)));
+        assert!(!in_preset(
+            "demo-unknown-preset",
+            Some("typo"),
+            Some(&config)
+        ));
+        // No preset: every rule
+        assert!(in_preset("demo", None, Some(&LintConfig::default())));
+        assert!(in_preset("demo", None, None));
+    }
+
+    #[test]
+    fn preset_drops_rules_outside_it() {
+        let config = LintConfig::parse("preset = \"minimal\"\n").unwrap();
+        let linter = Linter::with_config(Some(&config), None);
+        let rules = names(&linter);
+        assert!(rules.contains(&"missing-semicolon"), "got: {:?}", rules);
+        assert!(!rules.contains(&"indent"), "got: {:?}", rules);
+        assert!(linter.inactive_rule_names().contains("indent"));
+
+        let all = Linter::with_config(None, None);
+        assert!(names(&all).contains(&"indent"));
+    }
+
+    #[test]
+    fn configured_rules_stay_in_any_preset() {
+        let config =
+            LintConfig::parse("preset = \"minimal\"\n\n[rules.indent]\nindent_size = 2\n").unwrap();
+        let linter = Linter::with_config(Some(&config), None);
+        assert!(names(&linter).contains(&"indent"));
+    }
+
+    #[test]
+    fn custom_rules_stay_in_any_preset() {
+        let config = LintConfig::parse(
+            "preset = \"minimal\"\n\n[[custom_rules]]\nname = \"my-rule\"\ndirective = \"user\"\nmessage = \"m\"\n\n[[custom_rules]]\nname = \"indent\"\ndirective = \"user\"\nmessage = \"m\"\n",
+        )
+        .unwrap();
+        let linter = Linter::with_config(Some(&config), None);
+        let rules = names(&linter);
+        assert!(rules.contains(&"my-rule"), "got: {:?}", rules);
+        // A custom rule cannot take the name of a builtin one
+        assert!(!rules.contains(&"indent"), "got: {:?}", rules);
+    }
+}
+
+#[cfg(test)]
+mod fragment_tests {
+    use super::*;
+
+    fn rules(errors: &[LintError]) -> Vec<&str> {
+        errors.iter().map(|e| e.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn fragment_uses_given_context() {
+        let linter = Linter::with_default_rules();
+        let fragment = "location / {\n    try_files $uri =404;\n}\n";
+
+        let errors = linter.lint_fragment(fragment, &["http", "server"]);
+        assert!(errors.is_empty(), "got: {:?}", errors);
+
+        // At the top level, location is in the wrong context
+        let errors = linter.lint_fragment(fragment, &[]);
+        assert!(
+            rules(&errors).contains(&"invalid-directive-context"),
+            "got: {:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn fragment_reports_syntax_errors_once() {
+        let linter = Linter::with_default_rules();
+        let errors =
+            linter.lint_fragment("location / {\n    root /var/www;\n", &["http", "server"]);
+
+        assert!(!errors.is_empty());
+        let mut seen = HashSet::new();
+        for error in &errors {
+            assert!(
+                seen.insert((&error.rule, error.line, error.column, &error.message)),
+                "duplicate: {:?}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn fragment_honours_ignore_comments() {
+        let linter = Linter::with_default_rules();
+        let fragment = "# nginx-lint:ignore invalid-directive-context not in a server here\nlocation / {\n    root /var/www;\n}\n";
+        let errors = linter.lint_fragment(fragment, &[]);
+        assert!(
+            !rules(&errors).contains(&"invalid-directive-context"),
+            "got: {:?}",
+            errors
+        );
+    }
+}
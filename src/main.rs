@@ -7,9 +7,44 @@ use std::process::ExitCode;
 fn main() -> ExitCode {
     let cli = Cli::parse();
 
+    if cli.version {
+        return cli::version::run_version(&cli);
+    }
+
     match &cli.command {
+        Some(Commands::Archive {
+            file,
+            mount_point,
+            args,
+        }) => cli::archive::run_archive(file, mount_point, args, cli.trust_policy),
         Some(Commands::Config { command }) => cli::config::run_config(command),
+        Some(Commands::Diff {
+            old,
+            new,
+            format,
+            config,
+        }) => cli::diff::run_diff(old, new, *format, config.as_deref()),
+        Some(Commands::Docgen(args)) => cli::docgen::run_docgen(args),
+        Some(Commands::Fmt {
+            files,
+            check,
+            config,
+        }) => cli::fmt::run_fmt(files, *check, config.as_deref()),
         Some(Commands::Guide) => cli::guide::run_guide(),
+        Some(Commands::Image { image, args }) => {
+            cli::archive::run_image(image, args, cli.trust_policy)
+        }
+        Some(Commands::Parse {
+            file,
+            format,
+            context,
+            config,
+        }) => cli::parse::run_parse(file, *format, context.as_deref(), config.as_deref()),
+        #[cfg(feature = "plugins")]
+        Some(Commands::Plugins { command }) => {
+            cli::plugins::run_plugins(command, cli::trust_policy(cli.trust_policy, None))
+        }
+        Some(Commands::Rules { command, args }) => cli::rules::run_rules(command.as_ref(), args),
         Some(Commands::Web { port, open }) => cli::web::run_web(*port, *open),
         Some(Commands::Why { rule, list }) => cli::why::run_why(rule.clone(), *list),
         None => cli::lint::run_lint(cli),
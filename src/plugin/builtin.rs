@@ -91,6 +91,96 @@ mod embedded {
     /// client-max-body-size-not-set plugin
     pub const CLIENT_MAX_BODY_SIZE_NOT_SET: &[u8] =
         include_bytes!("../../target/builtin-plugins/client_max_body_size_not_set.wasm");
+    /// large-client-header-buffers-not-set plugin
+    pub const LARGE_CLIENT_HEADER_BUFFERS_NOT_SET: &[u8] =
+        include_bytes!("../../target/builtin-plugins/large_client_header_buffers_not_set.wasm");
+    /// proxy-cache-key-missing-discriminators plugin
+    pub const PROXY_CACHE_KEY_MISSING_DISCRIMINATORS: &[u8] =
+        include_bytes!("../../target/builtin-plugins/proxy_cache_key_missing_discriminators.wasm");
+    /// add-header-in-if-in-location plugin
+    pub const ADD_HEADER_IN_IF_IN_LOCATION: &[u8] =
+        include_bytes!("../../target/builtin-plugins/add_header_in_if_in_location.wasm");
+    /// ssl-certificate-missing plugin
+    pub const SSL_CERTIFICATE_MISSING: &[u8] =
+        include_bytes!("../../target/builtin-plugins/ssl_certificate_missing.wasm");
+    /// weak-ssl-key-exchange plugin
+    pub const WEAK_SSL_KEY_EXCHANGE: &[u8] =
+        include_bytes!("../../target/builtin-plugins/weak_ssl_key_exchange.wasm");
+    /// proxy-pass-hardcoded-ip plugin
+    pub const PROXY_PASS_HARDCODED_IP: &[u8] =
+        include_bytes!("../../target/builtin-plugins/proxy_pass_hardcoded_ip.wasm");
+    /// missing-security-headers plugin
+    pub const MISSING_SECURITY_HEADERS: &[u8] =
+        include_bytes!("../../target/builtin-plugins/missing_security_headers.wasm");
+    /// redirect-to-http plugin
+    pub const REDIRECT_TO_HTTP: &[u8] =
+        include_bytes!("../../target/builtin-plugins/redirect_to_http.wasm");
+    /// allow-deny-order plugin
+    pub const ALLOW_DENY_ORDER: &[u8] =
+        include_bytes!("../../target/builtin-plugins/allow_deny_order.wasm");
+    /// proxy-timeout-not-set plugin
+    pub const PROXY_TIMEOUT_NOT_SET: &[u8] =
+        include_bytes!("../../target/builtin-plugins/proxy_timeout_not_set.wasm");
+    /// unsafe-x-forwarded-for plugin
+    pub const UNSAFE_X_FORWARDED_FOR: &[u8] =
+        include_bytes!("../../target/builtin-plugins/unsafe_x_forwarded_for.wasm");
+    /// permissive-cors plugin
+    pub const PERMISSIVE_CORS: &[u8] =
+        include_bytes!("../../target/builtin-plugins/permissive_cors.wasm");
+    /// alias-traversal plugin
+    pub const ALIAS_TRAVERSAL: &[u8] =
+        include_bytes!("../../target/builtin-plugins/alias_traversal.wasm");
+    /// suspicious-proxy-settings plugin
+    pub const SUSPICIOUS_PROXY_SETTINGS: &[u8] =
+        include_bytes!("../../target/builtin-plugins/suspicious_proxy_settings.wasm");
+    /// charset-conflict plugin
+    pub const CHARSET_CONFLICT: &[u8] =
+        include_bytes!("../../target/builtin-plugins/charset_conflict.wasm");
+    /// location-access-bypass plugin
+    pub const LOCATION_ACCESS_BYPASS: &[u8] =
+        include_bytes!("../../target/builtin-plugins/location_access_bypass.wasm");
+    /// upstream-zone-too-small plugin
+    pub const UPSTREAM_ZONE_TOO_SMALL: &[u8] =
+        include_bytes!("../../target/builtin-plugins/upstream_zone_too_small.wasm");
+    /// http2-keepalive-not-tuned plugin
+    pub const HTTP2_KEEPALIVE_NOT_TUNED: &[u8] =
+        include_bytes!("../../target/builtin-plugins/http2_keepalive_not_tuned.wasm");
+    /// auth-request-body-forwarded plugin
+    pub const AUTH_REQUEST_BODY_FORWARDED: &[u8] =
+        include_bytes!("../../target/builtin-plugins/auth_request_body_forwarded.wasm");
+    /// plaintext-sensitive-upstream plugin
+    pub const PLAINTEXT_SENSITIVE_UPSTREAM: &[u8] =
+        include_bytes!("../../target/builtin-plugins/plaintext_sensitive_upstream.wasm");
+    /// limit-req-not-customized plugin
+    pub const LIMIT_REQ_NOT_CUSTOMIZED: &[u8] =
+        include_bytes!("../../target/builtin-plugins/limit_req_not_customized.wasm");
+    /// worker-settings-mistuned plugin
+    pub const WORKER_SETTINGS_MISTUNED: &[u8] =
+        include_bytes!("../../target/builtin-plugins/worker_settings_mistuned.wasm");
+    /// listen-conflict plugin
+    pub const LISTEN_CONFLICT: &[u8] =
+        include_bytes!("../../target/builtin-plugins/listen_conflict.wasm");
+    /// suspicious-server-name plugin
+    pub const SUSPICIOUS_SERVER_NAME: &[u8] =
+        include_bytes!("../../target/builtin-plugins/suspicious_server_name.wasm");
+    /// suspicious-rewrite plugin
+    pub const SUSPICIOUS_REWRITE: &[u8] =
+        include_bytes!("../../target/builtin-plugins/suspicious_rewrite.wasm");
+    /// if-host-comparison plugin
+    pub const IF_HOST_COMPARISON: &[u8] =
+        include_bytes!("../../target/builtin-plugins/if_host_comparison.wasm");
+    /// client-max-body-size-mismatch plugin
+    pub const CLIENT_MAX_BODY_SIZE_MISMATCH: &[u8] =
+        include_bytes!("../../target/builtin-plugins/client_max_body_size_mismatch.wasm");
+    /// ssl-performance-not-tuned plugin
+    pub const SSL_PERFORMANCE_NOT_TUNED: &[u8] =
+        include_bytes!("../../target/builtin-plugins/ssl_performance_not_tuned.wasm");
+    /// deprecated-directive plugin
+    pub const DEPRECATED_DIRECTIVE: &[u8] =
+        include_bytes!("../../target/builtin-plugins/deprecated_directive.wasm");
+    /// njs-module-reference plugin
+    pub const NJS_MODULE_REFERENCE: &[u8] =
+        include_bytes!("../../target/builtin-plugins/njs_module_reference.wasm");
     /// nginx-rift plugin
     pub const NGINX_RIFT: &[u8] = include_bytes!("../../target/builtin-plugins/nginx_rift.wasm");
 }
@@ -307,6 +397,72 @@ const PLUGIN_ENTRIES: &[(&str, &[u8])] = &[
     ),
     ("nginx-rift", embedded::NGINX_RIFT),
     ("map-unnamed-capture", embedded::MAP_UNNAMED_CAPTURE),
+    (
+        "large-client-header-buffers-not-set",
+        embedded::LARGE_CLIENT_HEADER_BUFFERS_NOT_SET,
+    ),
+    (
+        "proxy-cache-key-missing-discriminators",
+        embedded::PROXY_CACHE_KEY_MISSING_DISCRIMINATORS,
+    ),
+    (
+        "add-header-in-if-in-location",
+        embedded::ADD_HEADER_IN_IF_IN_LOCATION,
+    ),
+    ("ssl-certificate-missing", embedded::SSL_CERTIFICATE_MISSING),
+    ("weak-ssl-key-exchange", embedded::WEAK_SSL_KEY_EXCHANGE),
+    ("proxy-pass-hardcoded-ip", embedded::PROXY_PASS_HARDCODED_IP),
+    (
+        "missing-security-headers",
+        embedded::MISSING_SECURITY_HEADERS,
+    ),
+    ("redirect-to-http", embedded::REDIRECT_TO_HTTP),
+    ("allow-deny-order", embedded::ALLOW_DENY_ORDER),
+    ("proxy-timeout-not-set", embedded::PROXY_TIMEOUT_NOT_SET),
+    ("unsafe-x-forwarded-for", embedded::UNSAFE_X_FORWARDED_FOR),
+    ("permissive-cors", embedded::PERMISSIVE_CORS),
+    ("alias-traversal", embedded::ALIAS_TRAVERSAL),
+    (
+        "suspicious-proxy-settings",
+        embedded::SUSPICIOUS_PROXY_SETTINGS,
+    ),
+    ("charset-conflict", embedded::CHARSET_CONFLICT),
+    ("location-access-bypass", embedded::LOCATION_ACCESS_BYPASS),
+    ("upstream-zone-too-small", embedded::UPSTREAM_ZONE_TOO_SMALL),
+    (
+        "http2-keepalive-not-tuned",
+        embedded::HTTP2_KEEPALIVE_NOT_TUNED,
+    ),
+    (
+        "auth-request-body-forwarded",
+        embedded::AUTH_REQUEST_BODY_FORWARDED,
+    ),
+    (
+        "plaintext-sensitive-upstream",
+        embedded::PLAINTEXT_SENSITIVE_UPSTREAM,
+    ),
+    (
+        "limit-req-not-customized",
+        embedded::LIMIT_REQ_NOT_CUSTOMIZED,
+    ),
+    (
+        "worker-settings-mistuned",
+        embedded::WORKER_SETTINGS_MISTUNED,
+    ),
+    ("listen-conflict", embedded::LISTEN_CONFLICT),
+    ("suspicious-server-name", embedded::SUSPICIOUS_SERVER_NAME),
+    ("suspicious-rewrite", embedded::SUSPICIOUS_REWRITE),
+    ("if-host-comparison", embedded::IF_HOST_COMPARISON),
+    (
+        "client-max-body-size-mismatch",
+        embedded::CLIENT_MAX_BODY_SIZE_MISMATCH,
+    ),
+    (
+        "ssl-performance-not-tuned",
+        embedded::SSL_PERFORMANCE_NOT_TUNED,
+    ),
+    ("deprecated-directive", embedded::DEPRECATED_DIRECTIVE),
+    ("njs-module-reference", embedded::NJS_MODULE_REFERENCE),
 ];
 
 #[cfg(all(test, feature = "wasm-builtin-plugins"))]
@@ -5,8 +5,10 @@
 //! eliminating the need for JSON serialization.
 
 use super::error::PluginError;
-use crate::linter::{LintError, LintRule, Severity};
+use crate::config::Environment;
+use crate::linter::{Applicability, LintError, LintRule, Severity};
 use crate::parser::ast::{self, Config};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use wasmtime::component::{Resource, ResourceTable};
@@ -18,7 +20,6 @@ pub struct PluginSpec {
     pub name: String,
     pub category: String,
     pub description: String,
-    #[allow(dead_code)]
     pub api_version: String,
     #[allow(dead_code)]
     pub severity: Option<String>,
@@ -28,6 +29,7 @@ pub struct PluginSpec {
     pub references: Option<Vec<String>>,
     pub min_nginx_version: Option<String>,
     pub max_nginx_version: Option<String>,
+    pub preset: Option<String>,
 }
 
 /// Host-side config resource, holding the parsed Config.
@@ -69,6 +71,27 @@ use bindings::{Plugin, PluginPre};
 struct ComponentStoreData {
     limits: StoreLimits,
     table: ResourceTable,
+    /// Rule options returned by `rule-options` (name, JSON value)
+    rule_options: Arc<Vec<(String, String)>>,
+    /// Messages passed to `report-option-problem` during the call
+    option_problems: Vec<String>,
+    /// Version returned by `target-nginx-version`
+    target_nginx_version: Option<Arc<str>>,
+    /// Settings returned by `environment`
+    environment: Arc<Environment>,
+}
+
+impl ComponentStoreData {
+    fn new(limits: StoreLimits) -> Self {
+        Self {
+            limits,
+            table: ResourceTable::new(),
+            rule_options: Arc::default(),
+            option_problems: Vec::new(),
+            target_nginx_version: None,
+            environment: Arc::default(),
+        }
+    }
 }
 
 impl wasmtime::component::HasData for ComponentStoreData {
@@ -159,7 +182,27 @@ impl bindings::nginx_lint::plugin::data_types::Host for ComponentStoreData {}
 
 impl bindings::nginx_lint::plugin::parser_types::Host for ComponentStoreData {}
 
-impl config_api::Host for ComponentStoreData {}
+impl config_api::Host for ComponentStoreData {
+    fn rule_options(&mut self) -> Vec<(String, String)> {
+        self.rule_options.as_ref().clone()
+    }
+
+    fn report_option_problem(&mut self, message: String) {
+        self.option_problems.push(message);
+    }
+
+    fn target_nginx_version(&mut self) -> Option<String> {
+        self.target_nginx_version.as_deref().map(str::to_string)
+    }
+
+    fn environment(&mut self) -> config_api::DeploymentEnvironment {
+        config_api::DeploymentEnvironment {
+            os: self.environment.os.clone(),
+            container: self.environment.container,
+            behind_load_balancer: self.environment.behind_load_balancer,
+        }
+    }
+}
 
 impl config_api::HostConfig for ComponentStoreData {
     fn snapshot(&mut self, self_: Resource<ConfigResource>) -> config_api::ConfigSnapshot {
@@ -173,7 +216,7 @@ impl config_api::HostConfig for ComponentStoreData {
         config_api::ConfigSnapshot {
             all_items,
             top_level_indices,
-            include_context: config.include_context.clone(),
+            include_context: config.include_context.to_names(),
         }
     }
 
@@ -192,7 +235,7 @@ impl config_api::HostConfig for ComponentStoreData {
         config_api::ConfigSnapshot {
             all_items,
             top_level_indices,
-            include_context: config.include_context.clone(),
+            include_context: config.include_context.to_names(),
         }
     }
 
@@ -204,7 +247,7 @@ impl config_api::HostConfig for ComponentStoreData {
         let mut collected = Vec::new();
         collect_directive_paths_with_context(
             &config.items,
-            &config.include_context,
+            &config.include_context.to_names(),
             &mut Vec::new(),
             &mut collected,
         );
@@ -245,50 +288,48 @@ impl config_api::HostConfig for ComponentStoreData {
     }
 
     fn include_context(&mut self, self_: Resource<ConfigResource>) -> Vec<String> {
-        self.get_config(&self_).include_context.clone()
+        self.get_config(&self_).include_context.to_names()
+    }
+
+    fn include_context_frames(
+        &mut self,
+        self_: Resource<ConfigResource>,
+    ) -> Vec<config_api::ContextFrame> {
+        self.get_config(&self_)
+            .include_context
+            .frames()
+            .iter()
+            .map(|frame| config_api::ContextFrame {
+                name: frame.name.clone(),
+                args: frame.args.clone(),
+            })
+            .collect()
     }
 
     fn is_included_from(&mut self, self_: Resource<ConfigResource>, context: String) -> bool {
-        let ctx = &self.get_config(&self_).include_context;
-        ctx.iter().any(|c| c == &context)
+        self.get_config(&self_).is_included_from(&context)
     }
 
     fn is_included_from_http(&mut self, self_: Resource<ConfigResource>) -> bool {
-        let ctx = &self.get_config(&self_).include_context;
-        ctx.iter().any(|c| c == "http")
+        self.get_config(&self_).is_included_from_http()
     }
 
     fn is_included_from_http_server(&mut self, self_: Resource<ConfigResource>) -> bool {
-        let ctx = &self.get_config(&self_).include_context;
-        if let (Some(http_pos), Some(server_pos)) = (
-            ctx.iter().position(|c| c == "http"),
-            ctx.iter().position(|c| c == "server"),
-        ) {
-            http_pos < server_pos
-        } else {
-            false
-        }
+        self.get_config(&self_).is_included_from_http_server()
     }
 
     fn is_included_from_http_location(&mut self, self_: Resource<ConfigResource>) -> bool {
-        let ctx = &self.get_config(&self_).include_context;
-        if let (Some(http_pos), Some(location_pos)) = (
-            ctx.iter().position(|c| c == "http"),
-            ctx.iter().position(|c| c == "location"),
-        ) {
-            http_pos < location_pos
-        } else {
-            false
-        }
+        self.get_config(&self_).is_included_from_http_location()
     }
 
     fn is_included_from_stream(&mut self, self_: Resource<ConfigResource>) -> bool {
-        let ctx = &self.get_config(&self_).include_context;
-        ctx.iter().any(|c| c == "stream")
+        self.get_config(&self_).is_included_from_stream()
     }
 
     fn immediate_parent_context(&mut self, self_: Resource<ConfigResource>) -> Option<String> {
-        self.get_config(&self_).include_context.last().cloned()
+        self.get_config(&self_)
+            .immediate_parent_context()
+            .map(str::to_string)
     }
 
     fn drop(&mut self, rep: Resource<ConfigResource>) -> wasmtime::Result<()> {
@@ -427,6 +468,7 @@ impl config_api::HostDirective for ComponentStoreData {
             insert_after: false,
             start_offset: None,
             end_offset: None,
+            applicability: bindings::nginx_lint::plugin::types::Applicability::MachineApplicable,
         }
     }
 
@@ -436,7 +478,7 @@ impl config_api::HostDirective for ComponentStoreData {
         new_text: String,
     ) -> config_api::Fix {
         let d = self.get_directive(&self_);
-        let indent = " ".repeat(d.span.start.column.saturating_sub(1));
+        let indent = d.line_indent();
         let fix_text = format!("\n{}{}", indent, new_text);
         let offset = d.span.end.offset;
         make_range_fix(offset, offset, fix_text)
@@ -448,7 +490,7 @@ impl config_api::HostDirective for ComponentStoreData {
         new_text: String,
     ) -> config_api::Fix {
         let d = self.get_directive(&self_);
-        let indent = " ".repeat(d.span.start.column.saturating_sub(1));
+        let indent = d.line_indent();
         let fix_text = format!("{}{}\n", indent, new_text);
         let offset = d
             .span
@@ -464,7 +506,7 @@ impl config_api::HostDirective for ComponentStoreData {
         lines: Vec<String>,
     ) -> config_api::Fix {
         let d = self.get_directive(&self_);
-        let indent = " ".repeat(d.span.start.column.saturating_sub(1));
+        let indent = d.line_indent();
         let fix_text: String = lines
             .iter()
             .map(|line| format!("\n{}{}", indent, line))
@@ -479,7 +521,7 @@ impl config_api::HostDirective for ComponentStoreData {
         lines: Vec<String>,
     ) -> config_api::Fix {
         let d = self.get_directive(&self_);
-        let indent = " ".repeat(d.span.start.column.saturating_sub(1));
+        let indent = d.line_indent();
         let fix_text: String = lines
             .iter()
             .map(|line| format!("{}{}\n", indent, line))
@@ -510,6 +552,7 @@ fn make_range_fix(start: usize, end: usize, new_text: String) -> config_api::Fix
         insert_after: false,
         start_offset: Some(start as u32),
         end_offset: Some(end as u32),
+        applicability: bindings::nginx_lint::plugin::types::Applicability::MachineApplicable,
     }
 }
 
@@ -813,6 +856,17 @@ fn convert_fix(fix: &bindings::nginx_lint::plugin::types::Fix) -> crate::linter:
         insert_after: fix.insert_after,
         start_offset: fix.start_offset.map(|v| v as usize),
         end_offset: fix.end_offset.map(|v| v as usize),
+        applicability: match fix.applicability {
+            bindings::nginx_lint::plugin::types::Applicability::MachineApplicable => {
+                Applicability::MachineApplicable
+            }
+            bindings::nginx_lint::plugin::types::Applicability::MaybeIncorrect => {
+                Applicability::MaybeIncorrect
+            }
+            bindings::nginx_lint::plugin::types::Applicability::HasPlaceholders => {
+                Applicability::HasPlaceholders
+            }
+        },
     }
 }
 
@@ -828,6 +882,9 @@ fn convert_lint_error(error: &bindings::nginx_lint::plugin::types::LintError) ->
 
     if let (Some(line), Some(column)) = (error.line, error.column) {
         lint_error = lint_error.with_location(line as usize, column as usize);
+        if let Some(end_column) = error.end_column.filter(|&end| end > column) {
+            lint_error = lint_error.with_end_column(end_column as usize);
+        }
     } else if let Some(line) = error.line {
         lint_error = lint_error.with_location(line as usize, 1);
     }
@@ -836,6 +893,11 @@ fn convert_lint_error(error: &bindings::nginx_lint::plugin::types::LintError) ->
         lint_error = lint_error.with_fix(convert_fix(fix));
     }
 
+    for alternative in &error.alternatives {
+        let fixes = alternative.fixes.iter().map(convert_fix).collect();
+        lint_error = lint_error.with_alternative(&sanitize_text(&alternative.label), fixes);
+    }
+
     lint_error
 }
 
@@ -856,7 +918,86 @@ fn convert_plugin_spec(spec: &bindings::nginx_lint::plugin::types::PluginSpec) -
             .map(|refs| refs.iter().map(|r| sanitize_text(r)).collect()),
         min_nginx_version: sanitize_opt(&spec.min_nginx_version),
         max_nginx_version: sanitize_opt(&spec.max_nginx_version),
+        preset: sanitize_opt(&spec.preset),
+    }
+}
+
+/// Prefix of every import a plugin may have. The host provides only the
+/// `nginx-lint:plugin` interfaces, whose functions give read-only access to
+/// the config being checked and the rule's settings; a plugin importing
+/// anything else (WASI filesystem, sockets, clocks, ...) is rejected.
+const HOST_INTERFACE_PREFIX: &str = "nginx-lint:plugin/";
+
+/// Plugin API versions of the earlier `nginx-lint:plugin` WIT packages, to
+/// name the version a plugin built against one of them was made for
+const EARLIER_WIT_PACKAGES: &[(&str, &str)] = &[("4.0.0", "1.x")];
+
+/// Reject a component that imports interfaces the host does not provide,
+/// naming the import instead of failing with a generic linker error.
+///
+/// The interfaces must also come from the WIT package version this host
+/// implements: a plugin built for another major API version has records of
+/// another shape, which would otherwise only surface as a type mismatch
+/// when it is instantiated.
+fn check_imports(
+    engine: &Engine,
+    component: &wasmtime::component::Component,
+    path: &Path,
+) -> Result<(), PluginError> {
+    let ty = component.component_type();
+    for (name, _) in ty.imports(engine) {
+        if !name.starts_with(HOST_INTERFACE_PREFIX) {
+            return Err(PluginError::forbidden_import(path, sanitize_text(name)));
+        }
+        let package_version = name.rsplit_once('@').map_or("", |(_, version)| version);
+        if package_version != super::WIT_PACKAGE_VERSION {
+            let package = format!("nginx-lint:plugin@{}", sanitize_text(package_version));
+            let plugin_version = match EARLIER_WIT_PACKAGES
+                .iter()
+                .find(|(version, _)| *version == package_version)
+            {
+                Some((_, api_version)) => format!("{} ({})", api_version, package),
+                None => package,
+            };
+            return Err(PluginError::incompatible_api_version(
+                path,
+                plugin_version,
+                super::API_VERSION,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a `MAJOR.MINOR` API version
+pub(super) fn parse_api_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// The API version handshake: a plugin must report an API version with the
+/// host's major version. Minor versions only add host functions, and a
+/// plugin using functions this host lacks already fails to instantiate.
+fn check_api_version(path: &Path, plugin_version: &str) -> Result<(), PluginError> {
+    let Some((plugin_major, _)) = parse_api_version(plugin_version) else {
+        return Err(PluginError::invalid_plugin_spec(
+            path,
+            format!(
+                "invalid api_version '{}' (expected MAJOR.MINOR)",
+                plugin_version
+            ),
+        ));
+    };
+    let (host_major, _) =
+        parse_api_version(super::API_VERSION).expect("API_VERSION is MAJOR.MINOR");
+    if plugin_major != host_major {
+        return Err(PluginError::incompatible_api_version(
+            path,
+            plugin_version,
+            super::API_VERSION,
+        ));
     }
+    Ok(())
 }
 
 // === ComponentLintRule ===
@@ -877,6 +1018,14 @@ pub struct ComponentLintRule {
     /// Execution timeout per call in epoch ticks (None = no timeout, for
     /// trusted plugins)
     timeout_ticks: Option<u64>,
+    /// Options from the rule's config section, handed to the plugin on
+    /// every check (name, JSON value)
+    rule_options: Arc<Vec<(String, String)>>,
+    /// The `target_nginx_version` setting, handed to the plugin on every
+    /// check
+    target_nginx_version: Option<Arc<str>>,
+    /// The `[environment]` settings, handed to the plugin on every check
+    environment: Arc<Environment>,
     /// Leaked static strings for LintRule trait
     name: &'static str,
     category: &'static str,
@@ -895,6 +1044,7 @@ impl ComponentLintRule {
         // Compile the component
         let component = wasmtime::component::Component::new(engine, component_bytes)
             .map_err(|e| PluginError::compile_error(&path, e.to_string()))?;
+        check_imports(engine, &component, &path)?;
 
         // Register all host functions (types + config-api) and resolve the
         // component's imports once; per-call work is instantiation only
@@ -912,6 +1062,7 @@ impl ComponentLintRule {
         // Get plugin spec
         let spec_wit = Self::get_plugin_spec(&plugin_pre, &path, memory_limit, timeout_ticks)?;
         let spec = convert_plugin_spec(&spec_wit);
+        check_api_version(&path, &spec.api_version)?;
 
         // Leak strings for 'static lifetime required by the LintRule trait.
         // These live for the entire program duration. Since plugins are loaded once
@@ -926,6 +1077,9 @@ impl ComponentLintRule {
             plugin_pre,
             memory_limit,
             timeout_ticks,
+            rule_options: Arc::default(),
+            target_nginx_version: None,
+            environment: Arc::default(),
             name,
             category,
             description,
@@ -941,13 +1095,7 @@ impl ComponentLintRule {
         let limits = StoreLimitsBuilder::new()
             .memory_size(memory_limit as usize)
             .build();
-        let mut store = Store::new(
-            engine,
-            ComponentStoreData {
-                limits,
-                table: ResourceTable::new(),
-            },
-        );
+        let mut store = Store::new(engine, ComponentStoreData::new(limits));
         store.limiter(|data| &mut data.limits);
         if let Some(ticks) = timeout_ticks {
             // The loader's epoch ticker advances the engine epoch at a fixed
@@ -990,6 +1138,9 @@ impl ComponentLintRule {
             .plugin_pre
             .instantiate(&mut store)
             .map_err(|e| PluginError::instantiate_error(&self.path, e.to_string()))?;
+        store.data_mut().rule_options = self.rule_options.clone();
+        store.data_mut().target_nginx_version = self.target_nginx_version.clone();
+        store.data_mut().environment = self.environment.clone();
 
         // Create config resource handle
         let config_resource = store
@@ -1007,14 +1158,25 @@ impl ComponentLintRule {
         let wit_errors = plugin
             .call_check(&mut store, config_resource, &path_str)
             .map_err(|e| {
-                // Epoch deadline expiry surfaces as Trap::Interrupt
-                if e.downcast_ref::<Trap>() == Some(&Trap::Interrupt) {
-                    PluginError::timeout(&self.path)
-                } else {
-                    PluginError::execution_error(&self.path, format!("check() failed: {}", e))
+                // Epoch deadline expiry surfaces as Trap::Interrupt. Other traps
+                // (e.g. a panic in the guest) are reported by their trap code;
+                // the wasm backtrace in the outer error spans several lines.
+                match e.downcast_ref::<Trap>() {
+                    Some(Trap::Interrupt) => PluginError::timeout(&self.path),
+                    Some(trap) => PluginError::execution_error(
+                        &self.path,
+                        format!("check() trapped: {}", trap),
+                    ),
+                    None => {
+                        PluginError::execution_error(&self.path, format!("check() failed: {}", e))
+                    }
                 }
             })?;
 
+        for problem in std::mem::take(&mut store.data_mut().option_problems) {
+            crate::linter::warn_option_problem(self.name, &problem);
+        }
+
         // Note: The config resource and any directive resources created during
         // the check are cleaned up when `store` is dropped at function exit.
         // A fresh store is created for each execute_check call, so resources
@@ -1023,19 +1185,17 @@ impl ComponentLintRule {
         Ok(wit_errors.iter().map(convert_lint_error).collect())
     }
 
-    /// Run a check with a shared config handle, converting failures into a
-    /// reported lint error
+    /// Run a check with a shared config handle, converting traps, timeouts
+    /// and other failures into a `plugin-error` diagnostic so the rest of
+    /// the run continues
     fn run_check(&self, config: Arc<Config>, path: &Path) -> Vec<LintError> {
         match self.execute_check(config, path) {
             Ok(errors) => errors,
-            Err(e) => {
-                vec![LintError::new(
-                    self.name,
-                    self.category,
-                    &format!("Plugin execution failed: {}", e),
-                    Severity::Error,
-                )]
-            }
+            Err(e) => vec![LintError::plugin_error(
+                self.name,
+                self.category,
+                &e.to_string(),
+            )],
         }
     }
 }
@@ -1090,6 +1250,30 @@ impl LintRule for ComponentLintRule {
     fn max_nginx_version(&self) -> Option<&str> {
         self.spec.max_nginx_version.as_deref()
     }
+
+    fn preset(&self) -> Option<&str> {
+        self.spec.preset.as_deref()
+    }
+
+    /// Options are resolved against their declarations inside the plugin,
+    /// which reports problems with them while checking
+    fn configure(&mut self, options: &HashMap<String, serde_json::Value>) -> Vec<String> {
+        let mut options: Vec<(String, String)> = options
+            .iter()
+            .map(|(name, value)| (name.clone(), value.to_string()))
+            .collect();
+        options.sort();
+        self.rule_options = Arc::new(options);
+        Vec::new()
+    }
+
+    fn set_target_nginx_version(&mut self, version: &str) {
+        self.target_nginx_version = Some(Arc::from(version));
+    }
+
+    fn set_environment(&mut self, environment: &Environment) {
+        self.environment = Arc::new(environment.clone());
+    }
 }
 
 #[cfg(test)]
@@ -1193,7 +1377,7 @@ mod tests {
 
         for (src, include_context, expected_count) in cases {
             let mut config = crate::parser::parse_string(src).unwrap();
-            config.include_context = include_context.iter().map(|s| s.to_string()).collect();
+            config.include_context = include_context.to_vec().into();
             let config = Arc::new(config);
             let errors = rule.check_shared(&config, Path::new("test.conf"));
             assert_eq!(
@@ -1314,6 +1498,123 @@ mod tests {
             "map_unnamed_capture",
             "plugins/builtin/security/map_unnamed_capture",
         ),
+        (
+            "large_client_header_buffers_not_set",
+            "plugins/builtin/best_practices/large_client_header_buffers_not_set",
+        ),
+        (
+            "proxy_cache_key_missing_discriminators",
+            "plugins/builtin/security/proxy_cache_key_missing_discriminators",
+        ),
+        (
+            "add_header_in_if_in_location",
+            "plugins/builtin/best_practices/add_header_in_if_in_location",
+        ),
+        (
+            "ssl_certificate_missing",
+            "plugins/builtin/security/ssl_certificate_missing",
+        ),
+        (
+            "weak_ssl_key_exchange",
+            "plugins/builtin/security/weak_ssl_key_exchange",
+        ),
+        (
+            "proxy_pass_hardcoded_ip",
+            "plugins/builtin/best_practices/proxy_pass_hardcoded_ip",
+        ),
+        (
+            "missing_security_headers",
+            "plugins/builtin/security/missing_security_headers",
+        ),
+        (
+            "redirect_to_http",
+            "plugins/builtin/security/redirect_to_http",
+        ),
+        (
+            "allow_deny_order",
+            "plugins/builtin/security/allow_deny_order",
+        ),
+        (
+            "proxy_timeout_not_set",
+            "plugins/builtin/best_practices/proxy_timeout_not_set",
+        ),
+        (
+            "unsafe_x_forwarded_for",
+            "plugins/builtin/security/unsafe_x_forwarded_for",
+        ),
+        (
+            "permissive_cors",
+            "plugins/builtin/security/permissive_cors",
+        ),
+        (
+            "alias_traversal",
+            "plugins/builtin/security/alias_traversal",
+        ),
+        (
+            "suspicious_proxy_settings",
+            "plugins/builtin/best_practices/suspicious_proxy_settings",
+        ),
+        (
+            "charset_conflict",
+            "plugins/builtin/best_practices/charset_conflict",
+        ),
+        (
+            "location_access_bypass",
+            "plugins/builtin/security/location_access_bypass",
+        ),
+        (
+            "upstream_zone_too_small",
+            "plugins/builtin/best_practices/upstream_zone_too_small",
+        ),
+        (
+            "http2_keepalive_not_tuned",
+            "plugins/builtin/best_practices/http2_keepalive_not_tuned",
+        ),
+        (
+            "auth_request_body_forwarded",
+            "plugins/builtin/best_practices/auth_request_body_forwarded",
+        ),
+        (
+            "plaintext_sensitive_upstream",
+            "plugins/builtin/security/plaintext_sensitive_upstream",
+        ),
+        (
+            "limit_req_not_customized",
+            "plugins/builtin/best_practices/limit_req_not_customized",
+        ),
+        (
+            "worker_settings_mistuned",
+            "plugins/builtin/performance/worker_settings_mistuned",
+        ),
+        ("listen_conflict", "plugins/builtin/syntax/listen_conflict"),
+        (
+            "suspicious_server_name",
+            "plugins/builtin/best_practices/suspicious_server_name",
+        ),
+        (
+            "suspicious_rewrite",
+            "plugins/builtin/best_practices/suspicious_rewrite",
+        ),
+        (
+            "if_host_comparison",
+            "plugins/builtin/best_practices/if_host_comparison",
+        ),
+        (
+            "client_max_body_size_mismatch",
+            "plugins/builtin/best_practices/client_max_body_size_mismatch",
+        ),
+        (
+            "ssl_performance_not_tuned",
+            "plugins/builtin/performance/ssl_performance_not_tuned",
+        ),
+        (
+            "deprecated_directive",
+            "plugins/builtin/deprecation/deprecated_directive",
+        ),
+        (
+            "njs_module_reference",
+            "plugins/builtin/interop/njs_module_reference",
+        ),
     ];
 
     /// `ALL_BUILTIN_PLUGIN_DIRS` is a third, hand-maintained table alongside
@@ -1479,10 +1780,7 @@ mod tests {
             // guest walks items/data/block-items), no WIT lowering, no guest
             let start = Instant::now();
             for _ in 0..iters {
-                let mut data = ComponentStoreData {
-                    limits: StoreLimitsBuilder::new().build(),
-                    table: ResourceTable::new(),
-                };
+                let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
                 let cfg_res = data
                     .table
                     .push(ConfigResource {
@@ -1603,7 +1901,7 @@ http {
                 .iter()
                 .map(|&index| rebuild_item(&mut slots, index))
                 .collect(),
-            include_context: snapshot.include_context,
+            include_context: snapshot.include_context.into(),
         };
         assert!(
             slots.iter().all(Option::is_none),
@@ -1612,7 +1910,7 @@ http {
 
         let mut original = Config {
             items: config.items,
-            include_context: vec!["http".to_string()],
+            include_context: vec!["http".to_string()].into(),
         };
         normalize_known_lossy_fields(&mut original.items);
         assert_eq!(
@@ -1625,10 +1923,9 @@ http {
 
     /// The WIT boundary intentionally does not carry two pieces of data, and
     /// has not since the original per-directive reconstruction path; plugins
-    /// have never observed them. They cannot be "fixed" either: adding
-    /// fields to the existing WIT records would be a breaking change that
-    /// fails instantiation of plugins built against the old SDK (see the
-    /// API_VERSION note in plugin/mod.rs). Normalize the original AST to
+    /// have never observed them. Adding fields to the existing WIT records
+    /// would be a breaking change, which needs a major API version bump
+    /// (see the API_VERSION note in plugin/mod.rs). Normalize the original AST to
     /// the guest-visible form so the round-trip comparison checks
     /// everything else exactly:
     /// - a trailing comment transfers only its text (span and whitespace are
@@ -1843,7 +2140,9 @@ http {
             severity: bindings::nginx_lint::plugin::types::Severity::Warning,
             line: Some(1),
             column: Some(1),
+            end_column: None,
             fixes: vec![],
+            alternatives: vec![],
         };
         let error = convert_lint_error(&wit_error);
         assert_eq!(error.rule, "evil\u{FFFD}rule");
@@ -1865,6 +2164,7 @@ http {
             references: Some(vec!["https://example.com/\x1b[31m".to_string()]),
             min_nginx_version: Some("1.0\x1b".to_string()),
             max_nginx_version: None,
+            preset: None,
         };
         let spec = convert_plugin_spec(&wit_spec);
         assert_eq!(spec.name, "na\u{FFFD}me");
@@ -1904,6 +2204,7 @@ http {
             insert_after: true,
             start_offset: Some(5),
             end_offset: Some(8),
+            applicability: bindings::nginx_lint::plugin::types::Applicability::MaybeIncorrect,
         };
         let fix = convert_fix(&wit_fix);
         assert_eq!(fix.line, 10);
@@ -1913,6 +2214,7 @@ http {
         assert!(fix.insert_after);
         assert_eq!(fix.start_offset, Some(5));
         assert_eq!(fix.end_offset, Some(8));
+        assert_eq!(fix.applicability, Applicability::MaybeIncorrect);
     }
 
     #[test]
@@ -1925,6 +2227,7 @@ http {
             insert_after: false,
             start_offset: None,
             end_offset: None,
+            applicability: bindings::nginx_lint::plugin::types::Applicability::MachineApplicable,
         };
         let fix = convert_fix(&wit_fix);
         assert!(fix.old_text.is_none());
@@ -1942,7 +2245,9 @@ http {
             severity: bindings::nginx_lint::plugin::types::Severity::Warning,
             line: Some(42),
             column: Some(10),
+            end_column: None,
             fixes: vec![],
+            alternatives: vec![],
         };
         let error = convert_lint_error(&wit_error);
         assert_eq!(error.rule, "test-rule");
@@ -1962,7 +2267,9 @@ http {
             severity: bindings::nginx_lint::plugin::types::Severity::Error,
             line: Some(5),
             column: None,
+            end_column: None,
             fixes: vec![],
+            alternatives: vec![],
         };
         let error = convert_lint_error(&wit_error);
         assert_eq!(error.line, Some(5));
@@ -1978,7 +2285,9 @@ http {
             severity: bindings::nginx_lint::plugin::types::Severity::Error,
             line: None,
             column: None,
+            end_column: None,
             fixes: vec![],
+            alternatives: vec![],
         };
         let error = convert_lint_error(&wit_error);
         assert_eq!(error.line, None);
@@ -1994,6 +2303,7 @@ http {
             severity: bindings::nginx_lint::plugin::types::Severity::Warning,
             line: Some(1),
             column: Some(1),
+            end_column: None,
             fixes: vec![bindings::nginx_lint::plugin::types::Fix {
                 line: 1,
                 old_text: Some("bad".to_string()),
@@ -2002,11 +2312,30 @@ http {
                 insert_after: false,
                 start_offset: None,
                 end_offset: None,
+                applicability:
+                    bindings::nginx_lint::plugin::types::Applicability::MachineApplicable,
+            }],
+            alternatives: vec![bindings::nginx_lint::plugin::types::FixAlternative {
+                label: "remove it".to_string(),
+                fixes: vec![bindings::nginx_lint::plugin::types::Fix {
+                    line: 0,
+                    old_text: None,
+                    new_text: String::new(),
+                    delete_line: false,
+                    insert_after: false,
+                    start_offset: Some(0),
+                    end_offset: Some(3),
+                    applicability:
+                        bindings::nginx_lint::plugin::types::Applicability::MachineApplicable,
+                }],
             }],
         };
         let error = convert_lint_error(&wit_error);
         assert_eq!(error.fixes.len(), 1);
         assert_eq!(error.fixes[0].new_text, "good");
+        assert_eq!(error.alternatives.len(), 1);
+        assert_eq!(error.alternatives[0].label, "remove it");
+        assert_eq!(error.alternatives[0].fixes[0].end_offset, Some(3));
     }
 
     #[test]
@@ -2023,6 +2352,7 @@ http {
             references: Some(vec!["https://example.com".to_string()]),
             min_nginx_version: Some("0.6.27".to_string()),
             max_nginx_version: Some("1.30.0".to_string()),
+            preset: Some("recommended".to_string()),
         };
         let spec = convert_plugin_spec(&wit_spec);
         assert_eq!(spec.name, "test-plugin");
@@ -2039,6 +2369,7 @@ http {
         );
         assert_eq!(spec.min_nginx_version.as_deref(), Some("0.6.27"));
         assert_eq!(spec.max_nginx_version.as_deref(), Some("1.30.0"));
+        assert_eq!(spec.preset.as_deref(), Some("recommended"));
     }
 
     #[test]
@@ -2055,6 +2386,7 @@ http {
             references: None,
             min_nginx_version: None,
             max_nginx_version: None,
+            preset: None,
         };
         let spec = convert_plugin_spec(&wit_spec);
         assert_eq!(spec.name, "minimal");
@@ -2063,6 +2395,7 @@ http {
         assert!(spec.references.is_none());
         assert!(spec.min_nginx_version.is_none());
         assert!(spec.max_nginx_version.is_none());
+        assert!(spec.preset.is_none());
     }
 
     #[test]
@@ -2080,20 +2413,123 @@ http {
         assert!(matches!(result, Err(PluginError::CompileError { .. })));
     }
 
+    #[test]
+    fn test_check_api_version() {
+        let path = Path::new("test.wasm");
+        assert!(check_api_version(path, "2.0").is_ok());
+        assert!(check_api_version(path, super::super::API_VERSION).is_ok());
+        assert!(matches!(
+            check_api_version(path, "1.5"),
+            Err(PluginError::IncompatibleApiVersion { .. })
+        ));
+        assert!(matches!(
+            check_api_version(path, "latest"),
+            Err(PluginError::InvalidPluginSpec { .. })
+        ));
+    }
+
+    #[test]
+    fn test_new_rejects_foreign_imports() {
+        let mut config = wasmtime::Config::new();
+        config.wasm_component_model(true);
+        let engine = Engine::new(&config).unwrap();
+        let wat = r#"(component
+            (import "wasi:filesystem/types@0.2.0" (instance))
+        )"#;
+        let result = ComponentLintRule::new(
+            &engine,
+            PathBuf::from("test.wasm"),
+            wat.as_bytes(),
+            256 * 1024 * 1024,
+            Some(100),
+        );
+        match result {
+            Err(PluginError::ForbiddenImport { import, .. }) => {
+                assert_eq!(import, "wasi:filesystem/types@0.2.0");
+            }
+            Err(e) => panic!("expected ForbiddenImport, got {}", e),
+            Ok(_) => panic!("expected ForbiddenImport, got a rule"),
+        }
+    }
+
+    #[test]
+    fn test_new_rejects_earlier_wit_package() {
+        let mut config = wasmtime::Config::new();
+        config.wasm_component_model(true);
+        let engine = Engine::new(&config).unwrap();
+        let wat = r#"(component
+            (import "nginx-lint:plugin/types@4.0.0" (instance))
+        )"#;
+        let result = ComponentLintRule::new(
+            &engine,
+            PathBuf::from("test.wasm"),
+            wat.as_bytes(),
+            256 * 1024 * 1024,
+            Some(100),
+        );
+        match result {
+            Err(PluginError::IncompatibleApiVersion { plugin_version, .. }) => {
+                assert_eq!(plugin_version, "1.x (nginx-lint:plugin@4.0.0)");
+            }
+            Err(e) => panic!("expected IncompatibleApiVersion, got {}", e),
+            Ok(_) => panic!("expected IncompatibleApiVersion, got a rule"),
+        }
+    }
+
     // === Host trait method tests ===
 
+    #[test]
+    fn test_rule_options_host_functions() {
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
+        data.rule_options = Arc::new(vec![("max_depth".to_string(), "3".to_string())]);
+
+        assert_eq!(
+            config_api::Host::rule_options(&mut data),
+            vec![("max_depth".to_string(), "3".to_string())]
+        );
+        config_api::Host::report_option_problem(&mut data, "unknown option 'x'".to_string());
+        assert_eq!(data.option_problems, vec!["unknown option 'x'"]);
+    }
+
+    #[test]
+    fn test_target_nginx_version_host_function() {
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
+        assert_eq!(config_api::Host::target_nginx_version(&mut data), None);
+
+        data.target_nginx_version = Some(Arc::from("1.24.0"));
+        assert_eq!(
+            config_api::Host::target_nginx_version(&mut data),
+            Some("1.24.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_environment_host_function() {
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
+        let environment = config_api::Host::environment(&mut data);
+        assert_eq!(environment.os, None);
+        assert_eq!(environment.behind_load_balancer, None);
+
+        data.environment = Arc::new(Environment {
+            os: Some("linux".to_string()),
+            container: Some(true),
+            behind_load_balancer: Some(false),
+        });
+        let environment = config_api::Host::environment(&mut data);
+        assert_eq!(environment.os.as_deref(), Some("linux"));
+        assert_eq!(environment.container, Some(true));
+        assert_eq!(environment.behind_load_balancer, Some(false));
+    }
+
     /// Create a ComponentStoreData with a config resource for testing host methods.
     fn setup_store_with_config(
         include_context: Vec<String>,
         items: Vec<ast::ConfigItem>,
     ) -> (ComponentStoreData, Resource<ConfigResource>) {
-        let mut data = ComponentStoreData {
-            limits: StoreLimitsBuilder::new().build(),
-            table: ResourceTable::new(),
-        };
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
         let config = Arc::new(Config {
             items,
-            include_context,
+            include_context: include_context.into(),
         });
         let resource = data
             .table
@@ -2148,7 +2584,7 @@ http {
     ) -> Resource<DirectiveResource> {
         let config = Arc::new(Config {
             items: vec![ast::ConfigItem::Directive(Box::new(directive))],
-            include_context: vec![],
+            include_context: Default::default(),
         });
         data.table
             .push(DirectiveResource {
@@ -2249,12 +2685,32 @@ http {
         ));
     }
 
+    #[test]
+    fn test_include_context_frames_carry_block_args() {
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
+        let config = Arc::new(Config {
+            items: vec![],
+            include_context: crate::parser::context::IncludeContext::parse(
+                "http,server,location=~ ^/api",
+            ),
+        });
+        let resource = data.table.push(ConfigResource { config }).unwrap();
+        let frames = config_api::HostConfig::include_context_frames(
+            &mut data,
+            Resource::new_own(resource.rep()),
+        );
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].name, "location");
+        assert_eq!(frames[2].args, ["~", "^/api"]);
+        assert_eq!(
+            config_api::HostConfig::include_context(&mut data, resource),
+            ["http", "server", "location"]
+        );
+    }
+
     #[test]
     fn test_insert_before_column_1() {
-        let mut data = ComponentStoreData {
-            limits: StoreLimitsBuilder::new().build(),
-            table: ResourceTable::new(),
-        };
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
         let dir = make_directive("listen", 2, 1, 10, 20);
         let resource = push_test_directive(&mut data, dir);
 
@@ -2268,10 +2724,7 @@ http {
 
     #[test]
     fn test_insert_before_indented() {
-        let mut data = ComponentStoreData {
-            limits: StoreLimitsBuilder::new().build(),
-            table: ResourceTable::new(),
-        };
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
         let dir = make_directive("listen", 2, 5, 15, 25);
         let resource = push_test_directive(&mut data, dir);
 
@@ -2284,12 +2737,24 @@ http {
         assert!(fix.new_text.starts_with("    "));
     }
 
+    #[test]
+    fn test_insert_after_many_keeps_tab_indent() {
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
+        let mut dir = make_directive("listen", 3, 3, 20, 30);
+        dir.leading_whitespace = "\t\t".to_string();
+        let resource = push_test_directive(&mut data, dir);
+
+        let fix = config_api::HostDirective::insert_after_many(
+            &mut data,
+            resource,
+            vec!["line1;".to_string(), "line2;".to_string()],
+        );
+        assert_eq!(fix.new_text, "\n\t\tline1;\n\t\tline2;");
+    }
+
     #[test]
     fn test_insert_before_many_multiple_lines() {
-        let mut data = ComponentStoreData {
-            limits: StoreLimitsBuilder::new().build(),
-            table: ResourceTable::new(),
-        };
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
         let dir = make_directive("listen", 2, 5, 15, 25);
         let resource = push_test_directive(&mut data, dir);
 
@@ -2338,10 +2803,7 @@ http {
 
     #[test]
     fn test_block_items_no_block() {
-        let mut data = ComponentStoreData {
-            limits: StoreLimitsBuilder::new().build(),
-            table: ResourceTable::new(),
-        };
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
         let dir = make_directive("listen", 1, 1, 0, 10);
         let resource = push_test_directive(&mut data, dir);
 
@@ -2351,10 +2813,7 @@ http {
 
     #[test]
     fn test_block_items_with_block() {
-        let mut data = ComponentStoreData {
-            limits: StoreLimitsBuilder::new().build(),
-            table: ResourceTable::new(),
-        };
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
         let mut dir = make_directive("http", 1, 1, 0, 30);
         dir.block = Some(ast::Block {
             items: vec![ast::ConfigItem::Directive(Box::new(make_directive(
@@ -2457,10 +2916,7 @@ http {
             1,
             vec![ast::ConfigItem::Directive(Box::new(server))],
         );
-        let mut data = ComponentStoreData {
-            limits: StoreLimitsBuilder::new().build(),
-            table: ResourceTable::new(),
-        };
+        let mut data = ComponentStoreData::new(StoreLimitsBuilder::new().build());
         let http_resource = push_test_directive(&mut data, http);
 
         let items = config_api::HostDirective::block_items(&mut data, http_resource);
@@ -39,6 +39,33 @@ pub enum PluginError {
 
     #[error("Failed to initialize plugin compilation cache at '{path}': {message}")]
     CacheError { path: PathBuf, message: String },
+
+    #[error(
+        "Plugin '{path}' was built for plugin API {plugin_version}, which this nginx-lint (plugin API {host_version}) does not support"
+    )]
+    IncompatibleApiVersion {
+        path: PathBuf,
+        plugin_version: String,
+        host_version: String,
+    },
+
+    #[error(
+        "Plugin '{path}' imports '{import}', which nginx-lint does not provide (plugins may only use the nginx-lint:plugin interfaces)"
+    )]
+    ForbiddenImport { path: PathBuf, import: String },
+
+    #[error("Invalid plugin manifest '{path}': {message}")]
+    InvalidManifest { path: PathBuf, message: String },
+
+    #[error("Failed to download '{url}': {message}")]
+    DownloadError { url: String, message: String },
+
+    #[error("Invalid plugin registry '{path}' line {line}: {message}")]
+    InvalidRegistry {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
 }
 
 impl PluginError {
@@ -102,4 +129,49 @@ impl PluginError {
             message: message.into(),
         }
     }
+
+    pub fn incompatible_api_version(
+        path: impl Into<PathBuf>,
+        plugin_version: impl Into<String>,
+        host_version: impl Into<String>,
+    ) -> Self {
+        Self::IncompatibleApiVersion {
+            path: path.into(),
+            plugin_version: plugin_version.into(),
+            host_version: host_version.into(),
+        }
+    }
+
+    pub fn forbidden_import(path: impl Into<PathBuf>, import: impl Into<String>) -> Self {
+        Self::ForbiddenImport {
+            path: path.into(),
+            import: import.into(),
+        }
+    }
+
+    pub fn invalid_registry(
+        path: impl Into<PathBuf>,
+        line: usize,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::InvalidRegistry {
+            path: path.into(),
+            line,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_manifest(path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Self::InvalidManifest {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn download_error(url: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::DownloadError {
+            url: url.into(),
+            message: message.into(),
+        }
+    }
 }
@@ -0,0 +1,471 @@
+//! Installed plugins
+//!
+//! `nginx-lint plugins install` copies a plugin into the per-user plugin
+//! directory (see [`default_plugin_dir`]), one subdirectory per plugin:
+//!
+//! ```text
+//! ~/.config/nginx-lint/plugins/
+//! └── no-wildcard-server-name/
+//!     ├── nginx-lint-plugin.toml
+//!     └── no_wildcard_server_name.wasm
+//! ```
+//!
+//! Every plugin there is loaded on each lint run. The manifest describes
+//! the plugin and is validated against the host and the plugin itself when
+//! it is loaded:
+//!
+//! ```toml
+//! [plugin]
+//! name = "no-wildcard-server-name"
+//! version = "0.1.0"
+//! rules = ["no-wildcard-server-name"]
+//! min_api_version = "2.0"
+//! wasm = "no_wildcard_server_name.wasm"
+//! ```
+
+use super::component_rule::parse_api_version;
+use super::error::PluginError;
+use super::loader::PluginLoader;
+use crate::linter::LintRule;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of a plugin manifest
+pub const MANIFEST_FILE_NAME: &str = "nginx-lint-plugin.toml";
+
+/// Per-user directory of installed plugins:
+///
+/// - Windows: `%APPDATA%\nginx-lint\plugins`
+/// - Elsewhere: `$XDG_CONFIG_HOME/nginx-lint/plugins` or
+///   `~/.config/nginx-lint/plugins`
+///
+/// Returns `None` when the relevant environment variables are unset, empty,
+/// or relative.
+pub fn default_plugin_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        // The XDG spec says a relative $XDG_CONFIG_HOME must be ignored
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.filter(|base| base.is_absolute())
+        .map(|base| base.join("nginx-lint").join("plugins"))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    plugin: PluginManifest,
+}
+
+/// The `[plugin]` table of a plugin manifest
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginManifest {
+    /// Plugin name, also the name of its directory once installed
+    pub name: String,
+    /// Plugin version (`MAJOR.MINOR.PATCH`)
+    pub version: String,
+    /// Rule names the plugin provides
+    pub rules: Vec<String>,
+    /// Oldest nginx-lint plugin API (`MAJOR.MINOR`) the plugin works with
+    pub min_api_version: String,
+    /// File name of the plugin's WASM component, next to the manifest
+    pub wasm: String,
+}
+
+impl PluginManifest {
+    /// Parse and validate a manifest; `path` is used for error messages
+    pub fn parse(content: &str, path: &Path) -> Result<Self, PluginError> {
+        let file: ManifestFile = toml::from_str(content)
+            .map_err(|e| PluginError::invalid_manifest(path, e.message()))?;
+        let manifest = file.plugin;
+        manifest.validate(path)?;
+        Ok(manifest)
+    }
+
+    /// Read and validate the manifest of an installed (or to be installed)
+    /// plugin directory
+    pub fn read(plugin_dir: &Path) -> Result<Self, PluginError> {
+        let path = plugin_dir.join(MANIFEST_FILE_NAME);
+        let content = fs::read_to_string(&path).map_err(|e| PluginError::io_error(&path, e))?;
+        Self::parse(&content, &path)
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), PluginError> {
+        let invalid = |message: String| Err(PluginError::invalid_manifest(path, message));
+
+        // The name becomes a directory name and the wasm file is resolved
+        // next to the manifest, so neither may contain path components
+        if !is_file_name(&self.name) {
+            return invalid(format!(
+                "name '{}' may only contain letters, digits, '-' and '_'",
+                self.name
+            ));
+        }
+        if !is_file_name(self.wasm.trim_end_matches(".wasm")) || !self.wasm.ends_with(".wasm") {
+            return invalid(format!(
+                "wasm '{}' must be a .wasm file name next to the manifest",
+                self.wasm
+            ));
+        }
+        let is_version = self.version.split('.').count() == 3
+            && self
+                .version
+                .split('.')
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+        if !is_version {
+            return invalid(format!(
+                "version '{}' is not MAJOR.MINOR.PATCH",
+                self.version
+            ));
+        }
+        if self.rules.is_empty() {
+            return invalid("rules must list at least one rule".to_string());
+        }
+
+        let Some((major, minor)) = parse_api_version(&self.min_api_version) else {
+            return invalid(format!(
+                "min_api_version '{}' is not MAJOR.MINOR",
+                self.min_api_version
+            ));
+        };
+        let (host_major, host_minor) =
+            parse_api_version(super::API_VERSION).expect("API_VERSION is MAJOR.MINOR");
+        if major != host_major || minor > host_minor {
+            return Err(PluginError::incompatible_api_version(
+                path,
+                &self.min_api_version,
+                super::API_VERSION,
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Directories of the plugins installed in `plugins_dir`, sorted by name;
+/// empty when the directory does not exist
+pub fn installed_plugin_dirs(plugins_dir: &Path) -> Result<Vec<PathBuf>, PluginError> {
+    if !plugins_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(plugins_dir).map_err(|e| PluginError::io_error(plugins_dir, e))?;
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| PluginError::io_error(plugins_dir, e))?
+            .path();
+        // Skip interrupted installs (see PluginLoader::install)
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if !hidden && path.join(MANIFEST_FILE_NAME).is_file() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+impl PluginLoader {
+    /// Load an installed plugin, checking it against its manifest
+    pub fn load_installed_plugin(
+        &self,
+        plugin_dir: &Path,
+    ) -> Result<(PluginManifest, Box<dyn LintRule>), PluginError> {
+        let manifest = PluginManifest::read(plugin_dir)?;
+        let rule = self.load_plugin(&plugin_dir.join(&manifest.wasm))?;
+        if !manifest.rules.iter().any(|name| name == rule.name()) {
+            return Err(PluginError::invalid_manifest(
+                plugin_dir.join(MANIFEST_FILE_NAME),
+                format!(
+                    "the plugin provides rule '{}', which is not listed in rules",
+                    rule.name()
+                ),
+            ));
+        }
+        Ok((manifest, rule))
+    }
+
+    /// Load every plugin installed in `plugins_dir`.
+    ///
+    /// A plugin that fails to load or does not match its manifest is
+    /// reported as a warning and skipped.
+    pub fn load_installed(
+        &self,
+        plugins_dir: &Path,
+    ) -> Result<Vec<Box<dyn LintRule>>, PluginError> {
+        let mut plugins = Vec::new();
+        for dir in installed_plugin_dirs(plugins_dir)? {
+            match self.load_installed_plugin(&dir) {
+                Ok((_, rule)) => plugins.push(rule),
+                Err(e) => eprintln!("Warning: Failed to load plugin {:?}: {}", dir, e),
+            }
+        }
+        Ok(plugins)
+    }
+
+    /// Install a plugin into `plugins_dir` from its manifest: a manifest
+    /// file, a directory containing one, or an `https://` URL of one.
+    /// The WASM component is fetched from next to the manifest. Plain
+    /// `http://` URLs are refused, as a plugin runs on every lint.
+    ///
+    /// The plugin is loaded and checked against its manifest before it
+    /// replaces an installed plugin of the same name.
+    pub fn install(&self, source: &str, plugins_dir: &Path) -> Result<PluginManifest, PluginError> {
+        if source.starts_with("http://") {
+            return Err(PluginError::download_error(
+                source,
+                "plugins are only downloaded over https://",
+            ));
+        }
+        let (manifest_text, wasm_source) = if is_url(source) {
+            let text = String::from_utf8_lossy(&download(source)?).into_owned();
+            (text, Source::Url(source.to_string()))
+        } else {
+            let path = Path::new(source);
+            let manifest_path = if path.is_dir() {
+                path.join(MANIFEST_FILE_NAME)
+            } else {
+                path.to_path_buf()
+            };
+            let text = fs::read_to_string(&manifest_path)
+                .map_err(|e| PluginError::io_error(&manifest_path, e))?;
+            (text, Source::File(manifest_path))
+        };
+        let manifest = PluginManifest::parse(&manifest_text, Path::new(source))?;
+        let wasm = wasm_source.sibling(&manifest.wasm)?;
+
+        // Stage the plugin next to its final location and validate it there,
+        // so a broken download never replaces a working install
+        fs::create_dir_all(plugins_dir).map_err(|e| PluginError::io_error(plugins_dir, e))?;
+        let staging = plugins_dir.join(format!(".{}.tmp", manifest.name));
+        let target = plugins_dir.join(&manifest.name);
+        let result = (|| {
+            if staging.exists() {
+                fs::remove_dir_all(&staging).map_err(|e| PluginError::io_error(&staging, e))?;
+            }
+            fs::create_dir(&staging).map_err(|e| PluginError::io_error(&staging, e))?;
+            let manifest_path = staging.join(MANIFEST_FILE_NAME);
+            fs::write(&manifest_path, &manifest_text)
+                .map_err(|e| PluginError::io_error(&manifest_path, e))?;
+            let wasm_path = staging.join(&manifest.wasm);
+            fs::write(&wasm_path, &wasm).map_err(|e| PluginError::io_error(&wasm_path, e))?;
+            self.load_installed_plugin(&staging)?;
+
+            if target.exists() {
+                fs::remove_dir_all(&target).map_err(|e| PluginError::io_error(&target, e))?;
+            }
+            fs::rename(&staging, &target).map_err(|e| PluginError::io_error(&target, e))
+        })();
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&staging);
+        }
+        result.map(|()| manifest)
+    }
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("https://")
+}
+
+/// Where a manifest was read from, for fetching the files next to it
+enum Source {
+    File(PathBuf),
+    Url(String),
+}
+
+impl Source {
+    fn sibling(&self, file_name: &str) -> Result<Vec<u8>, PluginError> {
+        match self {
+            Source::File(manifest) => {
+                let path = manifest.parent().unwrap_or(Path::new("")).join(file_name);
+                fs::read(&path).map_err(|e| PluginError::io_error(&path, e))
+            }
+            Source::Url(manifest) => {
+                let base = manifest.split(['?', '#']).next().unwrap_or(manifest);
+                let dir = &base[..base.rfind('/').map_or(base.len(), |i| i + 1)];
+                download(&format!("{}{}", dir, file_name))
+            }
+        }
+    }
+}
+
+/// Fetch `url`, refusing redirects to anything but `https://`
+fn download(url: &str) -> Result<Vec<u8>, PluginError> {
+    let response = reqwest::blocking::Client::builder()
+        .https_only(true)
+        .build()
+        .and_then(|client| client.get(url).send())
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| PluginError::download_error(url, e.to_string()))?;
+    let bytes = response
+        .bytes()
+        .map_err(|e| PluginError::download_error(url, e.to_string()))?;
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::CompilationCache;
+    use tempfile::tempdir;
+
+    const MANIFEST: &str = r#"
+[plugin]
+name = "no-wildcard-server-name"
+version = "0.1.0"
+rules = ["no-wildcard-server-name"]
+min_api_version = "2.0"
+wasm = "no_wildcard_server_name.wasm"
+"#;
+
+    fn parse(content: &str) -> Result<PluginManifest, PluginError> {
+        PluginManifest::parse(content, Path::new(MANIFEST_FILE_NAME))
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = parse(MANIFEST).unwrap();
+        assert_eq!(manifest.name, "no-wildcard-server-name");
+        assert_eq!(manifest.version, "0.1.0");
+        assert_eq!(manifest.rules, ["no-wildcard-server-name"]);
+        assert_eq!(manifest.wasm, "no_wildcard_server_name.wasm");
+    }
+
+    #[test]
+    fn test_manifest_rejects_path_components() {
+        let manifest = MANIFEST.replace(
+            r#"name = "no-wildcard-server-name""#,
+            r#"name = "../escape""#,
+        );
+        assert!(matches!(
+            parse(&manifest),
+            Err(PluginError::InvalidManifest { .. })
+        ));
+        let manifest = MANIFEST.replace(
+            "no_wildcard_server_name.wasm",
+            "../no_wildcard_server_name.wasm",
+        );
+        assert!(matches!(
+            parse(&manifest),
+            Err(PluginError::InvalidManifest { .. })
+        ));
+    }
+
+    #[test]
+    fn test_manifest_rejects_invalid_fields() {
+        for (from, to) in [
+            (r#"version = "0.1.0""#, r#"version = "0.1""#),
+            (r#"rules = ["no-wildcard-server-name"]"#, "rules = []"),
+            (r#"min_api_version = "2.0""#, r#"min_api_version = "one""#),
+            (
+                r#"version = "0.1.0""#,
+                "version = \"0.1.0\"\nauthor = \"me\"",
+            ),
+        ] {
+            let manifest = MANIFEST.replace(from, to);
+            assert!(
+                matches!(parse(&manifest), Err(PluginError::InvalidManifest { .. })),
+                "{}",
+                to
+            );
+        }
+    }
+
+    #[test]
+    fn test_manifest_min_api_version() {
+        let newer = MANIFEST.replace(r#""2.0""#, r#""2.99""#);
+        assert!(matches!(
+            parse(&newer),
+            Err(PluginError::IncompatibleApiVersion { .. })
+        ));
+        let previous_major = MANIFEST.replace(r#""2.0""#, r#""1.5""#);
+        assert!(matches!(
+            parse(&previous_major),
+            Err(PluginError::IncompatibleApiVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_installed_plugin_dirs() {
+        let dir = tempdir().unwrap();
+        assert!(
+            installed_plugin_dirs(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+
+        fs::create_dir(dir.path().join("b")).unwrap();
+        fs::write(dir.path().join("b").join(MANIFEST_FILE_NAME), MANIFEST).unwrap();
+        fs::create_dir(dir.path().join("a")).unwrap();
+        fs::write(dir.path().join("a").join(MANIFEST_FILE_NAME), MANIFEST).unwrap();
+        fs::create_dir(dir.path().join("not-a-plugin")).unwrap();
+        fs::create_dir(dir.path().join(".c.tmp")).unwrap();
+        fs::write(dir.path().join(".c.tmp").join(MANIFEST_FILE_NAME), MANIFEST).unwrap();
+
+        let dirs = installed_plugin_dirs(dir.path()).unwrap();
+        assert_eq!(dirs, [dir.path().join("a"), dir.path().join("b")]);
+    }
+
+    #[test]
+    fn test_install_rejects_invalid_wasm_and_keeps_nothing() {
+        let loader = PluginLoader::new_with_cache(CompilationCache::Disabled).unwrap();
+        let source = tempdir().unwrap();
+        fs::write(source.path().join(MANIFEST_FILE_NAME), MANIFEST).unwrap();
+        fs::write(
+            source.path().join("no_wildcard_server_name.wasm"),
+            b"not a wasm file",
+        )
+        .unwrap();
+        let plugins = tempdir().unwrap();
+
+        let result = loader.install(source.path().to_str().unwrap(), plugins.path());
+        assert!(matches!(result, Err(PluginError::InvalidWasmFile { .. })));
+        assert_eq!(fs::read_dir(plugins.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_install_refuses_plain_http() {
+        let loader = PluginLoader::new_with_cache(CompilationCache::Disabled).unwrap();
+        let plugins = tempdir().unwrap();
+
+        let result = loader.install("http://example.com/nginx-lint-plugin.toml", plugins.path());
+        assert!(matches!(result, Err(PluginError::DownloadError { .. })));
+        assert!(!plugins.path().exists() || fs::read_dir(plugins.path()).unwrap().count() == 0);
+    }
+
+    #[test]
+    fn test_install_missing_wasm() {
+        let loader = PluginLoader::new_with_cache(CompilationCache::Disabled).unwrap();
+        let source = tempdir().unwrap();
+        fs::write(source.path().join(MANIFEST_FILE_NAME), MANIFEST).unwrap();
+        let plugins = tempdir().unwrap();
+
+        let result = loader.install(source.path().to_str().unwrap(), plugins.path());
+        assert!(matches!(result, Err(PluginError::IoError { .. })));
+    }
+
+    #[test]
+    fn test_load_installed_skips_broken_plugins() {
+        let loader = PluginLoader::new_with_cache(CompilationCache::Disabled).unwrap();
+        let dir = tempdir().unwrap();
+        let plugin = dir.path().join("no-wildcard-server-name");
+        fs::create_dir(&plugin).unwrap();
+        fs::write(plugin.join(MANIFEST_FILE_NAME), MANIFEST).unwrap();
+
+        let plugins = loader.load_installed(dir.path()).unwrap();
+        assert!(plugins.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 //! WASM plugin loader
 //!
-//! Handles discovering, loading, and validating WASM component model plugins from a directory.
+//! Handles discovering, loading, and validating WASM component model plugins
+//! from a directory or from a registry file listing plugin paths.
 
 use super::component_rule::ComponentLintRule;
 use super::error::PluginError;
@@ -60,6 +61,30 @@ fn is_component_model(bytes: &[u8]) -> Option<bool> {
     }
 }
 
+/// Read the plugin paths listed in a registry file (see
+/// [`PluginLoader::load_registry`])
+pub fn read_registry(registry: &Path) -> Result<Vec<PathBuf>, PluginError> {
+    let content = fs::read_to_string(registry).map_err(|e| PluginError::io_error(registry, e))?;
+    let base = registry.parent().unwrap_or(Path::new(""));
+    let mut paths = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let entry = line.trim();
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+        let path = Path::new(entry);
+        if path.extension().is_none_or(|ext| ext != "wasm") {
+            return Err(PluginError::invalid_registry(
+                registry,
+                index + 1,
+                format!("'{}' is not a .wasm file", entry),
+            ));
+        }
+        paths.push(base.join(path));
+    }
+    Ok(paths)
+}
+
 /// Plugin loader that discovers and loads WASM plugins from a directory
 pub struct PluginLoader {
     engine: Engine,
@@ -234,8 +259,6 @@ impl PluginLoader {
     /// which speeds up cache-miss/first runs.
     /// Results are ordered by file name so the rule order is deterministic.
     pub fn load_plugins(&self, dir: &Path) -> Result<Vec<Box<dyn LintRule>>, PluginError> {
-        use rayon::prelude::*;
-
         if !dir.exists() || !dir.is_dir() {
             return Err(PluginError::directory_not_found(dir));
         }
@@ -251,6 +274,14 @@ impl PluginLoader {
         }
         paths.sort();
 
+        Ok(self.load_paths(&paths))
+    }
+
+    /// Load plugins in parallel, warning about (and skipping) the ones that
+    /// fail; the rules keep the order of `paths`
+    fn load_paths(&self, paths: &[PathBuf]) -> Vec<Box<dyn LintRule>> {
+        use rayon::prelude::*;
+
         let results: Vec<Result<Box<dyn LintRule>, PluginError>> = paths
             .par_iter()
             .map(|path| self.load_plugin(path))
@@ -266,7 +297,26 @@ impl PluginLoader {
             }
         }
 
-        Ok(plugins)
+        plugins
+    }
+
+    /// Load the WASM plugins listed in a registry file.
+    ///
+    /// The registry lists one `.wasm` path per line; blank lines and lines
+    /// starting with `#` are ignored, and relative paths are resolved
+    /// against the registry's directory:
+    ///
+    /// ```text
+    /// # Plugins shared by every site
+    /// vendor/no-wildcard-server-name.wasm
+    /// /opt/nginx-lint/plugins/company-policy.wasm
+    /// ```
+    ///
+    /// A malformed registry is an error; a listed plugin that fails to load
+    /// is reported as a warning and skipped, as with [`load_plugins`](Self::load_plugins).
+    pub fn load_registry(&self, registry: &Path) -> Result<Vec<Box<dyn LintRule>>, PluginError> {
+        let paths = read_registry(registry)?;
+        Ok(self.load_paths(&paths))
     }
 
     /// Load a single WASM plugin from a file
@@ -442,6 +492,58 @@ mod tests {
         assert!(plugins.is_empty());
     }
 
+    #[test]
+    fn test_read_registry() {
+        let dir = tempdir().unwrap();
+        let registry = dir.path().join("plugins.txt");
+        fs::write(
+            &registry,
+            "# shared rules\n\nvendor/a.wasm\n  /opt/plugins/b.wasm  \n",
+        )
+        .unwrap();
+
+        let paths = read_registry(&registry).unwrap();
+        assert_eq!(
+            paths,
+            [
+                dir.path().join("vendor/a.wasm"),
+                PathBuf::from("/opt/plugins/b.wasm")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_registry_rejects_non_wasm_entry() {
+        let dir = tempdir().unwrap();
+        let registry = dir.path().join("plugins.txt");
+        fs::write(&registry, "a.wasm\nplugins/\n").unwrap();
+
+        let result = read_registry(&registry);
+        assert!(matches!(
+            result,
+            Err(PluginError::InvalidRegistry { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_load_registry_skips_broken_plugins() {
+        let loader = test_loader();
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("invalid.wasm"), b"not a wasm file").unwrap();
+        let registry = dir.path().join("plugins.txt");
+        fs::write(&registry, "invalid.wasm\nmissing.wasm\n").unwrap();
+
+        let plugins = loader.load_registry(&registry).unwrap();
+        assert!(plugins.is_empty());
+    }
+
+    #[test]
+    fn test_load_registry_missing_file() {
+        let loader = test_loader();
+        let result = loader.load_registry(Path::new("/nonexistent/plugins.txt"));
+        assert!(matches!(result, Err(PluginError::IoError { .. })));
+    }
+
     #[test]
     fn test_new_with_cache_custom_directory() {
         let dir = tempdir().unwrap();
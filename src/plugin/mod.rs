@@ -10,6 +10,8 @@ mod component_rule;
 #[cfg(feature = "plugins")]
 mod error;
 #[cfg(feature = "plugins")]
+mod installed;
+#[cfg(feature = "plugins")]
 mod loader;
 #[cfg(feature = "native-builtin-plugins")]
 pub mod native_builtin;
@@ -18,19 +20,36 @@ pub use component_rule::ComponentLintRule;
 #[cfg(feature = "plugins")]
 pub use error::PluginError;
 #[cfg(feature = "plugins")]
-pub use loader::{CompilationCache, PluginLoader};
+pub use installed::{
+    MANIFEST_FILE_NAME, PluginManifest, default_plugin_dir, installed_plugin_dirs,
+};
+#[cfg(feature = "plugins")]
+pub use loader::{CompilationCache, PluginLoader, read_registry};
 
 /// Current API version for the plugin interface.
 ///
-/// Informational only: plugins report the SDK's version in
-/// `PluginSpec.api_version`, and nothing compares it at runtime. Actual
+/// Plugins report the SDK's version in `PluginSpec.api_version`, and the
+/// loader rejects a plugin whose major version differs from this one.
+/// Within a major version the WIT interface only gains functions, so
 /// compatibility is enforced structurally by WIT import resolution — a
 /// plugin instantiates iff the host provides every function the plugin
 /// imports. Hosts therefore stay compatible with plugins built against
-/// older SDKs (the WIT interface only ever gains functions), while a
-/// plugin built against a newer SDK fails to instantiate on an older host
-/// with a missing-import error.
-pub const API_VERSION: &str = "1.2";
+/// older SDKs of the same major version, while a plugin built against a
+/// newer SDK fails to instantiate on an older host with a missing-import
+/// error.
+///
+/// Changing an existing record or function changes the ABI: it bumps the
+/// major version here together with the version of the WIT package
+/// (`WIT_PACKAGE_VERSION`). The loader compares the package version of a
+/// plugin's imports before instantiating it, so a plugin built for another
+/// major version is rejected by name rather than with a type mismatch.
+/// Version 2.0 added `applicability` to `fix`, `end-column` and
+/// `alternatives` to `lint-error`, and `preset` to `plugin-spec`.
+pub const API_VERSION: &str = "2.0";
+
+/// Version of the `nginx-lint:plugin` WIT package (wit/nginx-lint-plugin.wit)
+/// implemented by this host
+pub const WIT_PACKAGE_VERSION: &str = "5.0.0";
 
 /// Names of builtin plugins
 pub const BUILTIN_PLUGIN_NAMES: &[&str] = &[
@@ -62,6 +81,36 @@ pub const BUILTIN_PLUGIN_NAMES: &[&str] = &[
     "client-max-body-size-not-set",
     "nginx-rift",
     "map-unnamed-capture",
+    "large-client-header-buffers-not-set",
+    "proxy-cache-key-missing-discriminators",
+    "add-header-in-if-in-location",
+    "ssl-certificate-missing",
+    "weak-ssl-key-exchange",
+    "proxy-pass-hardcoded-ip",
+    "missing-security-headers",
+    "redirect-to-http",
+    "allow-deny-order",
+    "proxy-timeout-not-set",
+    "unsafe-x-forwarded-for",
+    "permissive-cors",
+    "alias-traversal",
+    "suspicious-proxy-settings",
+    "charset-conflict",
+    "location-access-bypass",
+    "upstream-zone-too-small",
+    "http2-keepalive-not-tuned",
+    "auth-request-body-forwarded",
+    "plaintext-sensitive-upstream",
+    "limit-req-not-customized",
+    "worker-settings-mistuned",
+    "listen-conflict",
+    "suspicious-server-name",
+    "suspicious-rewrite",
+    "if-host-comparison",
+    "client-max-body-size-mismatch",
+    "ssl-performance-not-tuned",
+    "deprecated-directive",
+    "njs-module-reference",
 ];
 
 /// Check if a rule name is a builtin plugin
@@ -71,7 +120,7 @@ pub fn is_builtin_plugin(name: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::API_VERSION;
+    use super::{API_VERSION, WIT_PACKAGE_VERSION};
 
     /// Extract the value of an `API_VERSION = "..."` declaration from source
     /// code, tolerating Rust and TypeScript syntax.
@@ -117,4 +166,17 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_wit_package_version_matches_wit_file() {
+        let wit = include_str!("../../wit/nginx-lint-plugin.wit");
+        assert!(
+            wit.starts_with(&format!(
+                "package nginx-lint:plugin@{};",
+                WIT_PACKAGE_VERSION
+            )),
+            "WIT_PACKAGE_VERSION \"{}\" does not match the package of wit/nginx-lint-plugin.wit",
+            WIT_PACKAGE_VERSION
+        );
+    }
 }
@@ -26,6 +26,85 @@ pub fn load_native_builtin_plugins() -> Vec<Box<dyn LintRule>> {
         Box::new(NativePluginRule::<
             map_unnamed_capture_plugin::MapUnnamedCapturePlugin,
         >::new()),
+        Box::new(NativePluginRule::<
+            proxy_cache_key_missing_discriminators_plugin::ProxyCacheKeyMissingDiscriminatorsPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            ssl_certificate_missing_plugin::SslCertificateMissingPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            weak_ssl_key_exchange_plugin::WeakSslKeyExchangePlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            missing_security_headers_plugin::MissingSecurityHeadersPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            redirect_to_http_plugin::RedirectToHttpPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            allow_deny_order_plugin::AllowDenyOrderPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            unsafe_x_forwarded_for_plugin::UnsafeXForwardedForPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            permissive_cors_plugin::PermissiveCorsPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            alias_traversal_plugin::AliasTraversalPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            suspicious_proxy_settings_plugin::SuspiciousProxySettingsPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            charset_conflict_plugin::CharsetConflictPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            location_access_bypass_plugin::LocationAccessBypassPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            upstream_zone_too_small_plugin::UpstreamZoneTooSmallPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            http2_keepalive_not_tuned_plugin::Http2KeepaliveNotTunedPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            auth_request_body_forwarded_plugin::AuthRequestBodyForwardedPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            plaintext_sensitive_upstream_plugin::PlaintextSensitiveUpstreamPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            limit_req_not_customized_plugin::LimitReqNotCustomizedPlugin,
+        >::new()),
+        // Performance plugins
+        Box::new(NativePluginRule::<
+            worker_settings_mistuned_plugin::WorkerSettingsMistunedPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            listen_conflict_plugin::ListenConflictPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            suspicious_server_name_plugin::SuspiciousServerNamePlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            suspicious_rewrite_plugin::SuspiciousRewritePlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            if_host_comparison_plugin::IfHostComparisonPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            client_max_body_size_mismatch_plugin::ClientMaxBodySizeMismatchPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            ssl_performance_not_tuned_plugin::SslPerformanceNotTunedPlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            deprecated_directive_plugin::DeprecatedDirectivePlugin,
+        >::new()),
+        Box::new(NativePluginRule::<
+            njs_module_reference_plugin::NjsModuleReferencePlugin,
+        >::new()),
         // Style plugins
         Box::new(NativePluginRule::<
             space_before_semicolon_plugin::SpaceBeforeSemicolonPlugin,
@@ -45,6 +124,9 @@ pub fn load_native_builtin_plugins() -> Vec<Box<dyn LintRule>> {
         Box::new(NativePluginRule::<
             directive_inheritance_plugin::DirectiveInheritancePlugin,
         >::new()),
+        Box::new(NativePluginRule::<
+            add_header_in_if_in_location_plugin::AddHeaderInIfInLocationPlugin,
+        >::new()),
         Box::new(NativePluginRule::<
             alias_location_slash_mismatch_plugin::AliasLocationSlashMismatchPlugin,
         >::new()),
@@ -57,6 +139,9 @@ pub fn load_native_builtin_plugins() -> Vec<Box<dyn LintRule>> {
         Box::new(NativePluginRule::<
             if_is_evil_in_location_plugin::IfIsEvilInLocationPlugin,
         >::new()),
+        Box::new(NativePluginRule::<
+            large_client_header_buffers_not_set_plugin::LargeClientHeaderBuffersNotSetPlugin,
+        >::new()),
         Box::new(NativePluginRule::<
             map_missing_default_plugin::MapMissingDefaultPlugin,
         >::new()),
@@ -72,9 +157,15 @@ pub fn load_native_builtin_plugins() -> Vec<Box<dyn LintRule>> {
         Box::new(NativePluginRule::<
             proxy_pass_domain_plugin::ProxyPassDomainPlugin,
         >::new()),
+        Box::new(NativePluginRule::<
+            proxy_pass_hardcoded_ip_plugin::ProxyPassHardcodedIpPlugin,
+        >::new()),
         Box::new(NativePluginRule::<
             proxy_pass_with_uri_plugin::ProxyPassWithUriPlugin,
         >::new()),
+        Box::new(NativePluginRule::<
+            proxy_timeout_not_set_plugin::ProxyTimeoutNotSetPlugin,
+        >::new()),
         Box::new(NativePluginRule::<
             root_in_location_plugin::RootInLocationPlugin,
         >::new()),
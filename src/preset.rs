@@ -0,0 +1,217 @@
+//! Presets (`--preset`): rule selections, and contexts for configurations
+//! that are only part of what nginx loads.
+//!
+//! `minimal`, `recommended` and `strict` select the [`RulePreset`] the
+//! linter starts from, as `preset` in `.nginx-lint.toml` does; each rule
+//! declares the smallest of them it belongs to.
+//!
+//! The `ingress-nginx` preset lints the snippets of the Kubernetes
+//! ingress-nginx controller, which inserts them into the configuration it
+//! generates. [`Preset::snippet_context`] gives the block each snippet ends
+//! up in, and [`Preset::apply`] turns off the rules about settings the
+//! controller manages.
+//!
+//! ```
+//! use nginx_lint::LintConfig;
+//! use nginx_lint::preset::Preset;
+//!
+//! let preset: Preset = "ingress-nginx".parse().unwrap();
+//! assert_eq!(preset.snippet_context("server-snippet"), Some("http,server"));
+//!
+//! let mut config = LintConfig::default();
+//! preset.apply(&mut config);
+//! assert!(!config.is_rule_enabled("indent"));
+//! assert!(config.is_rule_enabled("proxy-pass-hardcoded-ip"));
+//!
+//! let preset: Preset = "recommended".parse().unwrap();
+//! preset.apply(&mut config);
+//! assert_eq!(config.preset(), Some(RulePreset::Recommended));
+//! # use nginx_lint::RulePreset;
+//! ```
+
+use crate::LintConfig;
+use crate::config::{RuleConfig, RulePreset};
+
+/// A deployment whose configuration fragments get linted, or a rule set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Snippet annotations and ConfigMap snippets of the Kubernetes
+    /// ingress-nginx controller
+    IngressNginx,
+    /// The rules of a rule set
+    Rules(RulePreset),
+}
+
+/// ingress-nginx snippets and the blocks the controller puts them in
+const INGRESS_NGINX_SNIPPETS: &[(&str, &str)] = &[
+    // nginx.ingress.kubernetes.io/configuration-snippet, in the location of
+    // each path of the Ingress
+    ("configuration-snippet", "http,server,location"),
+    // nginx.ingress.kubernetes.io/server-snippet
+    ("server-snippet", "http,server"),
+    // nginx.ingress.kubernetes.io/auth-snippet, in the location of the
+    // external authentication subrequest
+    ("auth-snippet", "http,server,location"),
+    // ConfigMap keys
+    ("location-snippet", "http,server,location"),
+    ("http-snippet", "http"),
+    ("stream-snippet", "stream"),
+];
+
+/// Rules about what the ingress-nginx controller configures itself (from
+/// its ConfigMap, the annotations and the Secrets), or that cannot hold for
+/// a snippet
+const INGRESS_NGINX_DISABLED: &[&str] = &[
+    // Snippets are indented by the YAML they are written in
+    "indent",
+    // Included files live in the controller's pod
+    "include-path-exists",
+    // server-tokens in the ConfigMap
+    "server-tokens-enabled",
+    // nginx.ingress.kubernetes.io/proxy-body-size
+    "client-max-body-size-not-set",
+    // Set in every location the controller generates
+    "proxy-missing-host-header",
+    // TLS certificates come from the Ingress's Secrets
+    "ssl-certificate-missing",
+];
+
+/// Rules for mistakes particular to snippets
+const INGRESS_NGINX_ENABLED: &[&str] = &[
+    // Pod and cluster IPs change; backends are reached by Service name
+    "proxy-pass-hardcoded-ip",
+];
+
+impl Preset {
+    /// Every preset
+    pub const ALL: &'static [Preset] = &[
+        Preset::IngressNginx,
+        Preset::Rules(RulePreset::Minimal),
+        Preset::Rules(RulePreset::Recommended),
+        Preset::Rules(RulePreset::Strict),
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Preset::IngressNginx => "ingress-nginx",
+            Preset::Rules(rules) => rules.as_str(),
+        }
+    }
+
+    /// Names of the snippets the preset knows, see
+    /// [`snippet_context`](Self::snippet_context)
+    pub fn snippet_names(self) -> impl Iterator<Item = &'static str> {
+        self.snippets().iter().map(|(name, _)| *name)
+    }
+
+    /// The context (as given to `--context`) of the snippet called `name`,
+    /// e.g. `configuration-snippet`; `None` for other names
+    pub fn snippet_context(self, name: &str) -> Option<&'static str> {
+        self.snippets()
+            .iter()
+            .find(|(snippet, _)| *snippet == name)
+            .map(|(_, context)| *context)
+    }
+
+    /// The context of files linted without `--context`: that of the most
+    /// common snippet; `None` for presets without snippets
+    pub fn default_context(self) -> Option<&'static str> {
+        match self {
+            Preset::IngressNginx => Some("http,server,location"),
+            Preset::Rules(_) => None,
+        }
+    }
+
+    /// `context` with a snippet name replaced by the snippet's context
+    pub fn resolve_context(self, context: &str) -> &str {
+        self.snippet_context(context.trim()).unwrap_or(context)
+    }
+
+    /// Enable and disable the rules of the preset in `config`. Rules the
+    /// configuration has a `[rules.<name>]` section for are left as they
+    /// are.
+    ///
+    /// A rule set replaces the config's `preset`; the linter leaves out the
+    /// rules outside it.
+    pub fn apply(self, config: &mut LintConfig) {
+        let (enabled, disabled) = match self {
+            Preset::IngressNginx => (INGRESS_NGINX_ENABLED, INGRESS_NGINX_DISABLED),
+            Preset::Rules(rules) => {
+                config.preset = Some(rules);
+                return;
+            }
+        };
+        let rules = enabled
+            .iter()
+            .map(|name| (name, true))
+            .chain(disabled.iter().map(|name| (name, false)));
+        for (name, enabled) in rules {
+            config
+                .rules
+                .entry(name.to_string())
+                .or_insert_with(|| RuleConfig {
+                    enabled,
+                    ..RuleConfig::default()
+                });
+        }
+    }
+
+    fn snippets(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Preset::IngressNginx => INGRESS_NGINX_SNIPPETS,
+            Preset::Rules(_) => &[],
+        }
+    }
+}
+
+impl std::str::FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .iter()
+            .copied()
+            .find(|preset| preset.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Preset::ALL.iter().map(|p| p.as_str()).collect();
+                format!("unknown preset '{}' (expected {})", s, names.join(", "))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_contexts() {
+        let preset = Preset::IngressNginx;
+        assert_eq!(
+            preset.resolve_context("configuration-snippet"),
+            "http,server,location"
+        );
+        assert_eq!(preset.resolve_context("http-snippet"), "http");
+        // Other contexts are kept
+        assert_eq!(preset.resolve_context("http,server"), "http,server");
+        assert!(preset.snippet_names().any(|name| name == "stream-snippet"));
+    }
+
+    #[test]
+    fn test_apply_keeps_configured_rules() {
+        let mut config = LintConfig::parse("[rules.indent]\nindent_size = 2\n").unwrap();
+        Preset::IngressNginx.apply(&mut config);
+        assert!(config.is_rule_enabled("indent"));
+        assert!(!config.is_rule_enabled("include-path-exists"));
+        assert!(config.is_rule_enabled("proxy-pass-hardcoded-ip"));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("ingress-nginx".parse(), Ok(Preset::IngressNginx));
+        assert_eq!("strict".parse(), Ok(Preset::Rules(RulePreset::Strict)));
+        assert_eq!(
+            "nginx-ingress".parse::<Preset>(),
+            Err("unknown preset 'nginx-ingress' (expected ingress-nginx, minimal, recommended, strict)".to_string())
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::LintError;
+use crate::Severity;
+use crate::compare_diagnostics;
+use std::path::Path;
+
+/// Written before the first file; Jenkins warnings-ng and other Checkstyle
+/// consumers expect a single document
+pub(crate) const HEADER: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">";
+
+/// Written after the last file
+pub(crate) const FOOTER: &str = "</checkstyle>";
+
+pub(crate) fn report(
+    writer: &mut dyn std::io::Write,
+    errors: &[LintError],
+    path: &Path,
+) -> std::io::Result<()> {
+    for line in format(errors, path) {
+        writeln!(writer, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// The `<file>` element for `path`
+pub(crate) fn format(errors: &[LintError], path: &Path) -> Vec<String> {
+    let mut sorted_errors: Vec<_> = errors.iter().collect();
+    sorted_errors.sort_by(|a, b| compare_diagnostics(a, b));
+
+    let mut lines = vec![format!(
+        "  <file name=\"{}\">",
+        escape(&path.display().to_string())
+    )];
+    for error in sorted_errors {
+        let mut attributes = String::new();
+        if let Some(line) = error.line {
+            attributes.push_str(&format!(" line=\"{}\"", line));
+        }
+        if let Some(column) = error.column {
+            attributes.push_str(&format!(" column=\"{}\"", column));
+        }
+        let severity = match error.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        lines.push(format!(
+            "    <error{} severity=\"{}\" message=\"{}\" source=\"nginx-lint.{}.{}\"/>",
+            attributes,
+            severity,
+            escape(&error.message),
+            escape(&error.category),
+            escape(&error.rule)
+        ));
+    }
+    lines.push("  </file>".to_string());
+    lines
+}
+
+/// Escape `text` for an XML attribute value. Characters XML 1.0 does not
+/// allow (most control characters) are dropped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\t' => escaped.push_str("&#9;"),
+            '\r' => escaped.push_str("&#13;"),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_error(
+        rule: &str,
+        category: &str,
+        message: &str,
+        severity: Severity,
+        line: Option<usize>,
+        column: Option<usize>,
+    ) -> LintError {
+        LintError {
+            rule: rule.to_string(),
+            category: category.to_string(),
+            message: message.to_string(),
+            severity,
+            line,
+            column,
+            end_column: None,
+            fixes: vec![],
+            alternatives: vec![],
+        }
+    }
+
+    #[test]
+    fn test_file_element() {
+        let errors = vec![
+            make_error(
+                "server-tokens-enabled",
+                "security",
+                "server_tokens is enabled",
+                Severity::Warning,
+                Some(3),
+                Some(5),
+            ),
+            make_error(
+                "missing-semicolon",
+                "syntax",
+                "Missing semicolon at end of directive",
+                Severity::Error,
+                Some(1),
+                None,
+            ),
+        ];
+        let lines = format(&errors, Path::new("conf.d/app.conf"));
+        assert_eq!(
+            lines,
+            [
+                "  <file name=\"conf.d/app.conf\">",
+                "    <error line=\"1\" severity=\"error\" message=\"Missing semicolon at end of directive\" source=\"nginx-lint.syntax.missing-semicolon\"/>",
+                "    <error line=\"3\" column=\"5\" severity=\"warning\" message=\"server_tokens is enabled\" source=\"nginx-lint.security.server-tokens-enabled\"/>",
+                "  </file>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_file() {
+        let lines = format(&[], Path::new("nginx.conf"));
+        assert_eq!(lines, ["  <file name=\"nginx.conf\">", "  </file>"]);
+    }
+
+    #[test]
+    fn test_escaping() {
+        let errors = vec![make_error(
+            "r1",
+            "cat",
+            "'a' & \"b\" <c>\nd\u{1b}",
+            Severity::Error,
+            None,
+            None,
+        )];
+        let lines = format(&errors, Path::new("a&b.conf"));
+        assert_eq!(lines[0], "  <file name=\"a&amp;b.conf\">");
+        assert_eq!(
+            lines[1],
+            "    <error severity=\"error\" message=\"&apos;a&apos; &amp; &quot;b&quot; &lt;c&gt;&#10;d\" source=\"nginx-lint.cat.r1\"/>"
+        );
+    }
+}
@@ -1,35 +1,26 @@
 use crate::LintError;
 use crate::Severity;
+use crate::compare_diagnostics;
 use crate::config::{Color, ColorConfig};
 use colored::{ColoredString, Colorize};
 use std::io::Write;
 use std::path::Path;
 
+/// Writes one line per diagnostic. Diagnostics that cover a part of a line
+/// (see [`LintError::end_column`]) are followed by that line of `source`
+/// with the part underlined.
 pub(crate) fn report(
     writer: &mut dyn Write,
     errors: &[LintError],
     path: &Path,
+    source: Option<&str>,
     colors: &ColorConfig,
     ignored_count: usize,
 ) -> std::io::Result<()> {
     let path_str = path.display();
 
     let mut sorted_errors: Vec<_> = errors.iter().collect();
-    sorted_errors.sort_by(|a, b| match (a.line, b.line) {
-        (Some(line_a), Some(line_b)) => {
-            line_a
-                .cmp(&line_b)
-                .then_with(|| match (a.column, b.column) {
-                    (Some(col_a), Some(col_b)) => col_a.cmp(&col_b),
-                    (Some(_), None) => std::cmp::Ordering::Less,
-                    (None, Some(_)) => std::cmp::Ordering::Greater,
-                    (None, None) => std::cmp::Ordering::Equal,
-                })
-        }
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => std::cmp::Ordering::Equal,
-    });
+    sorted_errors.sort_by(|a, b| compare_diagnostics(a, b));
 
     for error in sorted_errors {
         let location = match (error.line, error.column) {
@@ -50,8 +41,25 @@ pub(crate) fn report(
         .bold();
 
         writeln!(writer, "{}: {}: {}", location, severity_str, error.message)?;
+        if let Some((source, marker)) = source.and_then(|c| underline(c, error)) {
+            writeln!(writer, "    {}", source)?;
+            writeln!(writer, "    {}", apply_color(&marker, color).bold())?;
+        }
+        // The choices for --fix-alternative
+        for alternative in &error.alternatives {
+            writeln!(writer, "    alternative fix: {}", alternative.label)?;
+        }
     }
 
+    write_summary(writer, errors, ignored_count)
+}
+
+/// Write the "Found N error(s), ..." line closing a file's report
+pub(super) fn write_summary(
+    writer: &mut dyn Write,
+    errors: &[LintError],
+    ignored_count: usize,
+) -> std::io::Result<()> {
     if !errors.is_empty() || ignored_count > 0 {
         writeln!(writer)?;
         let error_count = errors
@@ -82,6 +90,25 @@ pub(crate) fn report(
     Ok(())
 }
 
+/// The line of `content` that `error` covers part of, and a line with
+/// `^` under that part (aligned through tabs), or `None` when the error
+/// does not cover part of a line of `content`
+pub(super) fn underline(content: &str, error: &LintError) -> Option<(String, String)> {
+    let (line, column, end_column) = (error.line?, error.column?, error.end_column?);
+    let source = content.lines().nth(line.checked_sub(1)?)?;
+    let before = source.get(..column.checked_sub(1)?)?;
+    let marked = source.get(column - 1..end_column.checked_sub(1)?)?;
+    if marked.is_empty() {
+        return None;
+    }
+    let padding: String = before
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let carets = "^".repeat(marked.chars().count());
+    Some((source.to_string(), format!("{}{}", padding, carets)))
+}
+
 #[cfg(test)]
 fn format_line(error: &LintError, path: &Path) -> String {
     let path_str = path.display();
@@ -104,7 +131,7 @@ fn format_line(error: &LintError, path: &Path) -> String {
 }
 
 /// Apply a color to a string
-fn apply_color(s: &str, color: Color) -> ColoredString {
+pub(super) fn apply_color(s: &str, color: Color) -> ColoredString {
     match color {
         Color::Black => s.black(),
         Color::Red => s.red(),
@@ -144,7 +171,9 @@ mod tests {
             severity,
             line,
             column,
+            end_column: None,
             fixes: vec![],
+            alternatives: vec![],
         }
     }
 
@@ -199,6 +228,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_report_lists_alternative_fixes() {
+        let error = make_error(
+            "root-in-location",
+            "best-practices",
+            "every location in this server sets root /srv",
+            Severity::Warning,
+            Some(7),
+            Some(13),
+        )
+        .with_alternative("keep per-location roots", vec![]);
+        let mut output = Vec::new();
+        report(
+            &mut output,
+            &[error],
+            Path::new("nginx.conf"),
+            None,
+            &ColorConfig::default(),
+            0,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains("root /srv\n    alternative fix: keep per-location roots\n"),
+            "got: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_underline_argument() {
+        let content = "server {\n\tproxy_pass http://backend/path;\n}\n";
+        let error = make_error(
+            "proxy-pass-uri",
+            "best-practices",
+            "the URI replaces the location prefix",
+            Severity::Warning,
+            Some(2),
+            Some(13),
+        )
+        .with_end_column(32);
+        assert_eq!(
+            underline(content, &error),
+            Some((
+                "\tproxy_pass http://backend/path;".to_string(),
+                format!("\t{}{}", " ".repeat(11), "^".repeat(19))
+            ))
+        );
+
+        // Only errors covering part of an existing line are underlined
+        let whole = make_error("r", "c", "m", Severity::Warning, Some(2), Some(2));
+        assert_eq!(underline(content, &whole), None);
+        assert_eq!(underline(content, &error.clone().with_location(9, 1)), None);
+    }
+
+    #[test]
+    fn test_report_underlines_argument() {
+        let error = make_error(
+            "weak-ssl-protocols",
+            "security",
+            "TLSv1 is deprecated",
+            Severity::Warning,
+            Some(1),
+            Some(15),
+        )
+        .with_end_column(20);
+        let mut output = Vec::new();
+        colored::control::set_override(false);
+        report(
+            &mut output,
+            &[error],
+            Path::new("nginx.conf"),
+            Some("ssl_protocols TLSv1 TLSv1.2;\n"),
+            &ColorConfig::default(),
+            0,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains(
+                "TLSv1 is deprecated\n    ssl_protocols TLSv1 TLSv1.2;\n                  ^^^^^\n"
+            ),
+            "got: {}",
+            output
+        );
+    }
+
     #[test]
     fn test_format_line_without_line_and_column() {
         let error = make_error(
@@ -1,5 +1,6 @@
 use crate::LintError;
 use crate::Severity;
+use crate::compare_diagnostics;
 use std::path::Path;
 
 pub(crate) fn report(
@@ -17,21 +18,7 @@ pub(crate) fn format(errors: &[LintError], path: &Path) -> Vec<String> {
     let path_str = path.display();
 
     let mut sorted_errors: Vec<_> = errors.iter().collect();
-    sorted_errors.sort_by(|a, b| match (a.line, b.line) {
-        (Some(line_a), Some(line_b)) => {
-            line_a
-                .cmp(&line_b)
-                .then_with(|| match (a.column, b.column) {
-                    (Some(col_a), Some(col_b)) => col_a.cmp(&col_b),
-                    (Some(_), None) => std::cmp::Ordering::Less,
-                    (None, Some(_)) => std::cmp::Ordering::Greater,
-                    (None, None) => std::cmp::Ordering::Equal,
-                })
-        }
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => std::cmp::Ordering::Equal,
-    });
+    sorted_errors.sort_by(|a, b| compare_diagnostics(a, b));
 
     let mut lines = Vec::new();
     for error in sorted_errors {
@@ -47,6 +34,10 @@ pub(crate) fn format(errors: &[LintError], path: &Path) -> Vec<String> {
         if let Some(col) = error.column {
             params.push_str(&format!(",col={}", col));
         }
+        // endColumn is inclusive, end_column is not
+        if let Some(end_col) = error.end_column {
+            params.push_str(&format!(",endColumn={}", end_col - 1));
+        }
         params.push_str(&format!(",title={}/{}", error.category, error.rule));
 
         lines.push(format!("::{} {}::{}", level, params, error.message));
@@ -73,7 +64,9 @@ mod tests {
             severity,
             line,
             column,
+            end_column: None,
             fixes: vec![],
+            alternatives: vec![],
         }
     }
 
@@ -115,6 +108,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_argument_range() {
+        let errors = vec![
+            make_error(
+                "weak-ssl-protocols",
+                "security",
+                "TLSv1 is deprecated",
+                Severity::Warning,
+                Some(1),
+                Some(15),
+            )
+            .with_end_column(20),
+        ];
+        assert_eq!(
+            format(&errors, Path::new("nginx.conf")),
+            [
+                "::warning file=nginx.conf,line=1,col=15,endColumn=19,title=security/weak-ssl-protocols::TLSv1 is deprecated"
+            ]
+        );
+    }
+
     #[test]
     fn test_without_column() {
         let errors = vec![make_error(
@@ -1,5 +1,6 @@
 use crate::LintError;
 use crate::Severity;
+use crate::compare_diagnostics;
 use std::path::Path;
 
 #[derive(serde::Serialize)]
@@ -26,23 +27,8 @@ pub(crate) fn report(
 }
 
 pub(crate) fn format(errors: &[LintError], path: &Path, ignored_count: usize) -> String {
-    // Sort errors by line number, then by column number
     let mut sorted_errors: Vec<_> = errors.to_vec();
-    sorted_errors.sort_by(|a, b| match (a.line, b.line) {
-        (Some(line_a), Some(line_b)) => {
-            line_a
-                .cmp(&line_b)
-                .then_with(|| match (a.column, b.column) {
-                    (Some(col_a), Some(col_b)) => col_a.cmp(&col_b),
-                    (Some(_), None) => std::cmp::Ordering::Less,
-                    (None, Some(_)) => std::cmp::Ordering::Greater,
-                    (None, None) => std::cmp::Ordering::Equal,
-                })
-        }
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => std::cmp::Ordering::Equal,
-    });
+    sorted_errors.sort_by(compare_diagnostics);
 
     let report = JsonReport {
         file: path.display().to_string(),
@@ -82,7 +68,9 @@ mod tests {
             severity,
             line,
             column,
+            end_column: None,
             fixes: vec![],
+            alternatives: vec![],
         }
     }
 
@@ -1,9 +1,13 @@
+mod checkstyle;
 mod errorformat;
 mod github_actions;
 mod json;
+mod pretty;
+mod rdjson;
 
-use crate::LintError;
 use crate::config::ColorConfig;
+use crate::{LintError, Severity};
+use std::cell::Cell;
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -12,11 +16,79 @@ pub enum OutputFormat {
     ErrorFormat,
     Json,
     GithubActions,
+    /// Checkstyle XML, for Jenkins warnings-ng and other CI tools
+    Checkstyle,
+    /// Reviewdog Diagnostic Format, with fixes as suggestions
+    Rdjson,
+    /// Human-readable diagnostics with source snippets
+    Pretty,
+}
+
+/// Which diagnostics are reported and when a lint run fails.
+///
+/// Applied by [`Reporter`] for every output format, so the format printers
+/// only ever see the diagnostics to show.
+#[derive(Debug, Clone, Copy)]
+pub struct ExitPolicy {
+    /// Lowest severity that fails the run
+    pub error_on: Severity,
+    /// Number of warnings tolerated before the run fails. Takes precedence
+    /// over `error_on` for warnings, so the run can fail on too many
+    /// warnings even when `error_on` is [`Severity::Error`].
+    pub max_warnings: Option<usize>,
+    /// Report errors only (warnings still count towards the exit status)
+    pub quiet: bool,
+}
+
+impl Default for ExitPolicy {
+    fn default() -> Self {
+        Self {
+            error_on: Severity::Warning,
+            max_warnings: None,
+            quiet: false,
+        }
+    }
+}
+
+impl ExitPolicy {
+    /// Whether `error` is reported
+    pub fn shows(&self, error: &LintError) -> bool {
+        !self.quiet || error.severity == Severity::Error
+    }
+
+    /// Whether a run that found `errors` (across all files) fails
+    pub fn fails(&self, errors: &[LintError]) -> bool {
+        if errors.iter().any(|e| e.severity == Severity::Error) {
+            return true;
+        }
+        let warnings = errors.len();
+        match (self.max_warnings, self.error_on) {
+            (Some(max), _) => warnings > max,
+            (None, Severity::Warning) => warnings > 0,
+            (None, Severity::Error) => false,
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Whether reporting `errors` in this format uses the source
+    fn shows_source(&self, errors: &[LintError]) -> bool {
+        match self {
+            OutputFormat::ErrorFormat => errors.iter().any(|e| e.end_column.is_some()),
+            OutputFormat::Rdjson => errors.iter().any(|e| !e.fixes.is_empty()),
+            OutputFormat::Pretty => errors.iter().any(|e| e.line.is_some()),
+            _ => false,
+        }
+    }
 }
 
 pub struct Reporter {
     format: OutputFormat,
     colors: ColorConfig,
+    policy: ExitPolicy,
+    /// Whether an rdjson diagnostic has been written, so the next one needs
+    /// a separator
+    rdjson_started: Cell<bool>,
 }
 
 impl Reporter {
@@ -24,22 +96,71 @@ impl Reporter {
         Self {
             format,
             colors: ColorConfig::default(),
+            policy: ExitPolicy::default(),
+            rdjson_started: Cell::new(false),
         }
     }
 
     pub fn with_colors(format: OutputFormat, colors: ColorConfig) -> Self {
-        Self { format, colors }
+        Self {
+            format,
+            colors,
+            policy: ExitPolicy::default(),
+            rdjson_started: Cell::new(false),
+        }
+    }
+
+    /// Use `policy` to select the reported diagnostics and the exit status
+    pub fn with_policy(mut self, policy: ExitPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Whether a run that found `errors` (across all files) fails
+    pub fn fails(&self, errors: &[LintError]) -> bool {
+        self.policy.fails(errors)
+    }
+
+    /// Write what the format needs before the first file's report (the
+    /// Checkstyle root element, the rdjson document), to stderr when reports go there (see
+    /// [`report_to_stderr`](Self::report_to_stderr))
+    pub fn begin(&self, to_stderr: bool) -> std::io::Result<()> {
+        match self.format {
+            OutputFormat::Checkstyle => write_line(to_stderr, checkstyle::HEADER),
+            OutputFormat::Rdjson => write_line(to_stderr, rdjson::HEADER),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write what the format needs after the last file's report
+    pub fn finish(&self, to_stderr: bool) -> std::io::Result<()> {
+        match self.format {
+            OutputFormat::Checkstyle => write_line(to_stderr, checkstyle::FOOTER),
+            OutputFormat::Rdjson => write_line(to_stderr, rdjson::FOOTER),
+            _ => Ok(()),
+        }
     }
 
     /// Report to stdout. Returns any write error so the caller can react
     /// (e.g. a broken pipe, or a full disk truncating JSON output).
+    ///
+    /// `source` is the content the positions of `errors` refer to, used by
+    /// the formats that show source lines or resolve fix offsets; without
+    /// it they fall back to reading `path`.
     pub fn report(
         &self,
         errors: &[LintError],
         path: &Path,
+        source: Option<&str>,
         ignored_count: usize,
     ) -> std::io::Result<()> {
-        self.report_to(&mut std::io::stdout().lock(), errors, path, ignored_count)
+        self.report_to(
+            &mut std::io::stdout().lock(),
+            errors,
+            path,
+            source,
+            ignored_count,
+        )
     }
 
     /// Report to stderr instead of stdout. Used in `--fix` stdin mode, where
@@ -48,9 +169,16 @@ impl Reporter {
         &self,
         errors: &[LintError],
         path: &Path,
+        source: Option<&str>,
         ignored_count: usize,
     ) -> std::io::Result<()> {
-        self.report_to(&mut std::io::stderr().lock(), errors, path, ignored_count)
+        self.report_to(
+            &mut std::io::stderr().lock(),
+            errors,
+            path,
+            source,
+            ignored_count,
+        )
     }
 
     fn report_to(
@@ -58,14 +186,121 @@ impl Reporter {
         writer: &mut dyn std::io::Write,
         errors: &[LintError],
         path: &Path,
+        source: Option<&str>,
         ignored_count: usize,
     ) -> std::io::Result<()> {
+        let shown: Vec<LintError>;
+        let errors = if errors.iter().all(|e| self.policy.shows(e)) {
+            errors
+        } else {
+            shown = errors
+                .iter()
+                .filter(|e| self.policy.shows(e))
+                .cloned()
+                .collect();
+            &shown
+        };
+        let read;
+        let source = match source {
+            Some(source) => Some(source),
+            None if self.format.shows_source(errors) => {
+                read = std::fs::read_to_string(path).ok();
+                read.as_deref()
+            }
+            None => None,
+        };
         match self.format {
             OutputFormat::ErrorFormat => {
-                errorformat::report(writer, errors, path, &self.colors, ignored_count)
+                errorformat::report(writer, errors, path, source, &self.colors, ignored_count)
             }
             OutputFormat::Json => json::report(writer, errors, path, ignored_count),
             OutputFormat::GithubActions => github_actions::report(writer, errors, path),
+            OutputFormat::Checkstyle => checkstyle::report(writer, errors, path),
+            OutputFormat::Rdjson => {
+                rdjson::report(writer, errors, path, source, &self.rdjson_started)
+            }
+            OutputFormat::Pretty => {
+                pretty::report(writer, errors, path, source, &self.colors, ignored_count)
+            }
         }
     }
 }
+
+fn write_line(to_stderr: bool, line: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if to_stderr {
+        writeln!(std::io::stderr().lock(), "{}", line)
+    } else {
+        writeln!(std::io::stdout().lock(), "{}", line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostics(errors: usize, warnings: usize) -> Vec<LintError> {
+        let error = LintError::new("rule", "style", "message", Severity::Error);
+        let warning = LintError::new("rule", "style", "message", Severity::Warning);
+        std::iter::repeat_n(error, errors)
+            .chain(std::iter::repeat_n(warning, warnings))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_policy_fails_on_warnings() {
+        let policy = ExitPolicy::default();
+        assert!(!policy.fails(&[]));
+        assert!(policy.fails(&diagnostics(0, 1)));
+        assert!(policy.fails(&diagnostics(1, 0)));
+    }
+
+    #[test]
+    fn test_error_on_error() {
+        let policy = ExitPolicy {
+            error_on: Severity::Error,
+            ..Default::default()
+        };
+        assert!(!policy.fails(&diagnostics(0, 3)));
+        assert!(policy.fails(&diagnostics(1, 0)));
+    }
+
+    #[test]
+    fn test_max_warnings() {
+        for error_on in [Severity::Warning, Severity::Error] {
+            let policy = ExitPolicy {
+                error_on,
+                max_warnings: Some(2),
+                ..Default::default()
+            };
+            assert!(!policy.fails(&diagnostics(0, 2)));
+            assert!(policy.fails(&diagnostics(0, 3)));
+            assert!(policy.fails(&diagnostics(1, 0)));
+        }
+    }
+
+    #[test]
+    fn test_quiet_reports_errors_only() {
+        let reporter = Reporter::new(OutputFormat::Json).with_policy(ExitPolicy {
+            quiet: true,
+            ..Default::default()
+        });
+        let mut output = Vec::new();
+        reporter
+            .report_to(
+                &mut output,
+                &diagnostics(1, 2),
+                Path::new("a.conf"),
+                None,
+                0,
+            )
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["summary"]["errors"], 1);
+        assert_eq!(report["summary"]["warnings"], 0);
+
+        // Hidden warnings still count
+        assert!(reporter.fails(&diagnostics(0, 2)));
+    }
+}
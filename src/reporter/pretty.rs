@@ -0,0 +1,230 @@
+use super::errorformat::{apply_color, write_summary};
+use crate::LintError;
+use crate::Severity;
+use crate::compare_diagnostics;
+use crate::config::ColorConfig;
+use colored::Colorize;
+use std::io::Write;
+use std::path::Path;
+
+/// Lines of source shown before and after the reported line
+const CONTEXT_LINES: usize = 2;
+
+/// Writes each diagnostic as a header followed by the reported line of
+/// `source` with its surroundings and a marker under the reported column
+/// (or the whole range, see [`LintError::end_column`]), in the style of
+/// rustc. Without `source` only the headers are written.
+pub(crate) fn report(
+    writer: &mut dyn Write,
+    errors: &[LintError],
+    path: &Path,
+    source: Option<&str>,
+    colors: &ColorConfig,
+    ignored_count: usize,
+) -> std::io::Result<()> {
+    let lines: Vec<&str> = source.map(|s| s.lines().collect()).unwrap_or_default();
+
+    let mut sorted_errors: Vec<_> = errors.iter().collect();
+    sorted_errors.sort_by(|a, b| compare_diagnostics(a, b));
+
+    for (i, error) in sorted_errors.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(writer)?;
+        }
+        let (severity_label, color) = match error.severity {
+            Severity::Error => ("error", colors.error),
+            Severity::Warning => ("warning", colors.warning),
+        };
+        writeln!(
+            writer,
+            "{}: {}",
+            apply_color(
+                &format!("{}[{}/{}]", severity_label, error.category, error.rule),
+                color
+            )
+            .bold(),
+            error.message.bold()
+        )?;
+
+        let location = match (error.line, error.column) {
+            (Some(line), Some(col)) => format!("{}:{}:{}", path.display(), line, col),
+            (Some(line), None) => format!("{}:{}", path.display(), line),
+            _ => format!("{}", path.display()),
+        };
+        let snippet = error.line.and_then(|line| snippet(&lines, line, error));
+        let width = snippet
+            .as_ref()
+            .map_or(1, |s| (s.first + s.lines.len() - 1).to_string().len());
+        let gutter = |text: &str| format!("{:>width$} |", text).blue().bold();
+
+        writeln!(
+            writer,
+            "{}{} {}",
+            " ".repeat(width),
+            "-->".blue().bold(),
+            location
+        )?;
+        if let Some(snippet) = snippet {
+            writeln!(writer, "{}", gutter(""))?;
+            for (offset, text) in snippet.lines.iter().enumerate() {
+                let number = snippet.first + offset;
+                writeln!(writer, "{} {}", gutter(&number.to_string()), text)?;
+                if number == snippet.line
+                    && let Some(marker) = &snippet.marker
+                {
+                    writeln!(
+                        writer,
+                        "{} {}",
+                        gutter(""),
+                        apply_color(marker, color).bold()
+                    )?;
+                }
+            }
+            writeln!(writer, "{}", gutter(""))?;
+        }
+        // The choices for --fix-alternative
+        for alternative in &error.alternatives {
+            writeln!(
+                writer,
+                "{} alternative fix: {}",
+                format!("{:>width$} =", "").blue().bold(),
+                alternative.label
+            )?;
+        }
+    }
+
+    write_summary(writer, errors, ignored_count)
+}
+
+/// The source lines shown for a diagnostic
+struct Snippet<'a> {
+    /// Number of the first line shown
+    first: usize,
+    lines: &'a [&'a str],
+    /// The reported line
+    line: usize,
+    /// `^` under the reported column or range of `line`, aligned through
+    /// tabs
+    marker: Option<String>,
+}
+
+/// The lines around `line` (1-indexed), or `None` if `lines` has no such line
+fn snippet<'a>(lines: &'a [&'a str], line: usize, error: &LintError) -> Option<Snippet<'a>> {
+    let text = lines.get(line.checked_sub(1)?)?;
+    let first = line.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (line + CONTEXT_LINES).min(lines.len());
+    let marker = error.column.and_then(|column| {
+        let before = text.get(..column.checked_sub(1)?)?;
+        let width = match error.end_column {
+            Some(end) => text.get(column - 1..end.checked_sub(1)?)?.chars().count(),
+            None => 1,
+        };
+        let padding: String = before
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        Some(format!("{}{}", padding, "^".repeat(width.max(1))))
+    });
+    Some(Snippet {
+        first,
+        lines: &lines[first - 1..last],
+        line,
+        marker,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(errors: &[LintError], source: Option<&str>) -> String {
+        colored::control::set_override(false);
+        let mut output = Vec::new();
+        report(
+            &mut output,
+            errors,
+            Path::new("nginx.conf"),
+            source,
+            &ColorConfig::default(),
+            0,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    const SOURCE: &str =
+        "http {\n    server {\n        listen 80;\n        ssl_ciphers RC4:HIGH;\n    }\n}\n";
+
+    #[test]
+    fn test_snippet_with_range() {
+        let error = LintError::new(
+            "weak-ssl-ciphers",
+            "security",
+            "Weak cipher suite 'RC4' should not be used",
+            Severity::Warning,
+        )
+        .with_location(4, 21)
+        .with_end_column(29);
+        assert_eq!(
+            render(&[error], Some(SOURCE)),
+            "warning[security/weak-ssl-ciphers]: Weak cipher suite 'RC4' should not be used
+ --> nginx.conf:4:21
+  |
+2 |     server {
+3 |         listen 80;
+4 |         ssl_ciphers RC4:HIGH;
+  |                     ^^^^^^^^
+5 |     }
+6 | }
+  |
+
+Found 1 warning(s)
+"
+        );
+    }
+
+    #[test]
+    fn test_snippet_at_start_of_file() {
+        let error = LintError::new(
+            "server-tokens-enabled",
+            "security",
+            "server_tokens is enabled",
+            Severity::Error,
+        )
+        .with_location(1, 1)
+        .with_alternative("keep the default", vec![]);
+        assert_eq!(
+            render(&[error], Some(SOURCE)),
+            "error[security/server-tokens-enabled]: server_tokens is enabled
+ --> nginx.conf:1:1
+  |
+1 | http {
+  | ^
+2 |     server {
+3 |         listen 80;
+  |
+  = alternative fix: keep the default
+
+Found 1 error(s)
+"
+        );
+    }
+
+    #[test]
+    fn test_without_source() {
+        let located =
+            LintError::new("rule", "style", "located", Severity::Warning).with_location(12, 3);
+        let unlocated = LintError::new("rule", "style", "unlocated", Severity::Warning);
+        assert_eq!(
+            render(&[located, unlocated], None),
+            "warning[style/rule]: located
+ --> nginx.conf:12:3
+
+warning[style/rule]: unlocated
+ --> nginx.conf
+
+Found 2 warning(s)
+"
+        );
+    }
+}
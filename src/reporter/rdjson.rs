@@ -0,0 +1,250 @@
+use crate::LintError;
+use crate::Severity;
+use crate::compare_diagnostics;
+use crate::{Fix, compute_line_starts, normalize_line_fix};
+use std::cell::Cell;
+use std::path::Path;
+
+/// Written before the first diagnostic; reviewdog reads a single
+/// `DiagnosticResult` document
+pub(crate) const HEADER: &str = r#"{"source":{"name":"nginx-lint","url":"https://github.com/walf443/nginx-lint"},"diagnostics":["#;
+
+/// Written after the last diagnostic
+pub(crate) const FOOTER: &str = "]}";
+
+#[derive(serde::Serialize)]
+struct Diagnostic<'a> {
+    message: &'a str,
+    location: Location,
+    severity: &'static str,
+    code: Code<'a>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<Suggestion>,
+}
+
+#[derive(serde::Serialize)]
+struct Location {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<Range>,
+}
+
+#[derive(serde::Serialize)]
+struct Range {
+    start: Position,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<Position>,
+}
+
+/// 1-based line, and 1-based column in UTF-8 bytes
+#[derive(serde::Serialize)]
+struct Position {
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+}
+
+#[derive(serde::Serialize)]
+struct Code<'a> {
+    value: &'a str,
+}
+
+#[derive(serde::Serialize)]
+struct Suggestion {
+    range: Range,
+    text: String,
+}
+
+/// Write the diagnostics for `path`, one per line. `started` tracks whether
+/// an earlier file already wrote one, which the next must be separated from.
+///
+/// Fix offsets are resolved against `source` (the content `--fix` has
+/// already rewritten and re-linted); without it the diagnostics carry no
+/// suggestions.
+pub(crate) fn report(
+    writer: &mut dyn std::io::Write,
+    errors: &[LintError],
+    path: &Path,
+    source: Option<&str>,
+    started: &Cell<bool>,
+) -> std::io::Result<()> {
+    for line in format(errors, path, source) {
+        let separator = if started.replace(true) { "," } else { "" };
+        writeln!(writer, "{}{}", separator, line)?;
+    }
+    Ok(())
+}
+
+/// One JSON object per diagnostic for `path`, whose content is `content`
+pub(crate) fn format(errors: &[LintError], path: &Path, content: Option<&str>) -> Vec<String> {
+    let mut sorted_errors: Vec<_> = errors.iter().collect();
+    sorted_errors.sort_by(|a, b| compare_diagnostics(a, b));
+
+    let line_starts = content.map(compute_line_starts);
+    let path = path.display().to_string();
+    sorted_errors
+        .into_iter()
+        .map(|error| {
+            let suggestions = match (content, &line_starts) {
+                (Some(content), Some(line_starts)) => error
+                    .fixes
+                    .iter()
+                    .filter_map(|fix| suggestion(fix, content, line_starts))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let diagnostic = Diagnostic {
+                message: &error.message,
+                location: Location {
+                    path: path.clone(),
+                    range: error.line.map(|line| Range {
+                        start: Position {
+                            line,
+                            column: error.column,
+                        },
+                        end: error.end_column.map(|column| Position {
+                            line,
+                            column: Some(column),
+                        }),
+                    }),
+                },
+                severity: match error.severity {
+                    Severity::Error => "ERROR",
+                    Severity::Warning => "WARNING",
+                },
+                code: Code { value: &error.rule },
+                suggestions,
+            };
+            serde_json::to_string(&diagnostic).unwrap()
+        })
+        .collect()
+}
+
+/// The suggestion replacing the range `fix` covers, or `None` if the fix
+/// does not apply to `content`
+fn suggestion(fix: &Fix, content: &str, line_starts: &[usize]) -> Option<Suggestion> {
+    let normalized;
+    let fix = if fix.is_range_based() {
+        fix
+    } else {
+        normalized = normalize_line_fix(fix, content, line_starts)?;
+        &normalized
+    };
+    let (start, end) = (fix.start_offset?, fix.end_offset?);
+    if start > end || end > content.len() {
+        return None;
+    }
+    Some(Suggestion {
+        range: Range {
+            start: position(start, line_starts),
+            end: Some(position(end, line_starts)),
+        },
+        text: fix.new_text.clone(),
+    })
+}
+
+/// The position of byte `offset`
+fn position(offset: usize, line_starts: &[usize]) -> Position {
+    // The last entry is the end of the content, not the start of a line
+    let lines = &line_starts[..line_starts.len() - 1];
+    let line = lines.partition_point(|&start| start <= offset).max(1);
+    Position {
+        line,
+        column: Some(offset - lines[line - 1] + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_error(
+        rule: &str,
+        message: &str,
+        severity: Severity,
+        line: Option<usize>,
+        column: Option<usize>,
+    ) -> LintError {
+        LintError {
+            rule: rule.to_string(),
+            category: "style".to_string(),
+            message: message.to_string(),
+            severity,
+            line,
+            column,
+            end_column: None,
+            fixes: vec![],
+            alternatives: vec![],
+        }
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let errors = vec![
+            make_error(
+                "server-tokens-enabled",
+                "server_tokens is enabled",
+                Severity::Warning,
+                Some(2),
+                Some(5),
+            ),
+            make_error("plugin-error", "plugin failed", Severity::Error, None, None),
+            make_error(
+                "weak-ssl-protocols",
+                "TLSv1 is deprecated",
+                Severity::Warning,
+                Some(3),
+                Some(15),
+            )
+            .with_end_column(20),
+        ];
+        let lines = format(&errors, Path::new("conf.d/app.conf"), None);
+        assert_eq!(
+            lines,
+            [
+                r#"{"message":"server_tokens is enabled","location":{"path":"conf.d/app.conf","range":{"start":{"line":2,"column":5}}},"severity":"WARNING","code":{"value":"server-tokens-enabled"}}"#,
+                r#"{"message":"TLSv1 is deprecated","location":{"path":"conf.d/app.conf","range":{"start":{"line":3,"column":15},"end":{"line":3,"column":20}}},"severity":"WARNING","code":{"value":"weak-ssl-protocols"}}"#,
+                r#"{"message":"plugin failed","location":{"path":"conf.d/app.conf"},"severity":"ERROR","code":{"value":"plugin-error"}}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let content = "http {\n    server_tokens on;\n}\n";
+        let error = make_error(
+            "server-tokens-enabled",
+            "m",
+            Severity::Warning,
+            Some(2),
+            None,
+        )
+        .with_fix(Fix::replace_range(25, 28, "off;"));
+        #[allow(deprecated)]
+        let line_fix =
+            make_error("trailing", "m", Severity::Warning, Some(3), None).with_fix(Fix::delete(3));
+
+        let lines = format(&[error, line_fix], Path::new("nginx.conf"), Some(content));
+        let diagnostic: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(
+            diagnostic["suggestions"],
+            serde_json::json!([{
+                "range": {"start": {"line": 2, "column": 19}, "end": {"line": 2, "column": 22}},
+                "text": "off;"
+            }])
+        );
+        let diagnostic: serde_json::Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(
+            diagnostic["suggestions"][0]["range"],
+            serde_json::json!({"start": {"line": 3, "column": 1}, "end": {"line": 4, "column": 1}})
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_fix_is_dropped() {
+        let error = make_error("r", "m", Severity::Warning, Some(1), None)
+            .with_fix(Fix::replace_range(5, 50, ""));
+        let lines = format(&[error], Path::new("nginx.conf"), Some("events {}\n"));
+        assert!(!lines[0].contains("suggestions"));
+    }
+}
@@ -0,0 +1,282 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable, UpstreamNamespace};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "access-log-misconfigured",
+    category: "best-practices",
+    description: "Detects access_log directives naming undefined log formats, http-level access_log off, and JSON log formats without escape=json",
+    severity: "error",
+    why: r#"nginx refuses to start when an `access_log` names a `log_format` that
+is not defined ("unknown log format"). Only `combined` is predefined; the
+`main` format of the stock nginx.conf is defined there, and is missing once
+that file is replaced.
+
+`access_log off` in the `http` block turns access logging off for every
+server and location. It is reported as a warning when no server or location
+turns it back on, as requests then leave no trace at all.
+
+A `log_format` writing a JSON object needs `escape=json`: with the default
+escaping, a `"` in a header is logged as `\x22`, which is not valid JSON, and
+with `escape=none` it is logged as is, so a crafted header can break the
+record or inject fields. An unknown `escape=` value makes nginx refuse to
+start.
+
+Log formats and the directives that use them are joined across every file
+of the configuration."#,
+    bad_example: include_str!("access_log_misconfigured/bad.conf"),
+    good_example: include_str!("access_log_misconfigured/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_log_module.html#access_log",
+        "https://nginx.org/en/docs/http/ngx_http_log_module.html#log_format",
+    ],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Log format every `http` context has without defining it
+const PREDEFINED_FORMAT: &str = "combined";
+
+/// Check `access_log` and `log_format` directives against each other
+pub struct AccessLogMisconfigured;
+
+impl AccessLogMisconfigured {
+    fn error(&self, message: &str, severity: Severity) -> LintError {
+        LintError::new(self.name(), self.category(), message, severity)
+    }
+}
+
+impl CrossFileRule for AccessLogMisconfigured {
+    fn name(&self) -> &'static str {
+        "access-log-misconfigured"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects access_log directives naming undefined log formats, http-level access_log off, and JSON log formats without escape=json"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        let mut errors = Vec::new();
+
+        for format in symbols.log_formats() {
+            let error = match format.escape.as_deref() {
+                None | Some("default") if format.is_json => self.error(
+                    &format!(
+                        "log_format '{}' writes JSON without escape=json; quotes in logged values become \\x22 and break the JSON",
+                        format.name
+                    ),
+                    Severity::Warning,
+                ),
+                Some("none") if format.is_json => self.error(
+                    &format!(
+                        "log_format '{}' writes JSON with escape=none; a quote in a logged header breaks the record or injects fields, use escape=json",
+                        format.name
+                    ),
+                    Severity::Warning,
+                ),
+                None | Some("default" | "json" | "none") => continue,
+                Some(escape) => self.error(
+                    &format!(
+                        "log_format '{}' has unknown escape={}; nginx accepts default, json or none",
+                        format.name, escape
+                    ),
+                    Severity::Error,
+                ),
+            };
+            errors.push((
+                format.path.clone(),
+                error.with_location(format.line, format.column),
+            ));
+        }
+
+        let http_logs_somewhere = symbols
+            .access_logs()
+            .iter()
+            .any(|l| l.namespace == UpstreamNamespace::Http && !l.is_off);
+        for log in symbols.access_logs() {
+            let error = if let Some(name) = &log.format {
+                let predefined =
+                    log.namespace == UpstreamNamespace::Http && name == PREDEFINED_FORMAT;
+                if predefined || symbols.has_log_format(name, log.namespace) {
+                    continue;
+                }
+                self.error(
+                    &format!(
+                        "access_log uses log format '{}' which is not defined; nginx refuses to start with \"unknown log format\"",
+                        name
+                    ),
+                    Severity::Error,
+                )
+            } else if log.is_off
+                && log.is_http_level
+                && log.namespace == UpstreamNamespace::Http
+                && !http_logs_somewhere
+            {
+                self.error(
+                    "access_log off in the http block disables access logging for every server, and no server or location turns it back on",
+                    Severity::Warning,
+                )
+            } else {
+                continue;
+            };
+            errors.push((log.path.clone(), error.with_location(log.line, log.column)));
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    fn lines(errors: &[(PathBuf, LintError)]) -> Vec<(usize, Severity)> {
+        errors
+            .iter()
+            .map(|(_, e)| (e.line.unwrap(), e.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_bad_example() {
+        let errors = check_cross_file(
+            &AccessLogMisconfigured,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("access_log_misconfigured/bad.conf"),
+            )],
+        );
+        assert_eq!(
+            lines(&errors),
+            [(2, Severity::Warning), (10, Severity::Error)]
+        );
+        assert_eq!(
+            errors[1].1.message,
+            "access_log uses log format 'main' which is not defined; nginx refuses to start with \"unknown log format\""
+        );
+    }
+
+    #[test]
+    fn test_good_example() {
+        let errors = check_cross_file(
+            &AccessLogMisconfigured,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("access_log_misconfigured/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_format_names() {
+        let errors = check_cross_file(
+            &AccessLogMisconfigured,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    log_format main '$remote_addr';\n    access_log /var/log/a.log;\n    access_log /var/log/b.log combined;\n    access_log /var/log/c.log main buffer=32k;\n    access_log syslog:server=10.0.0.1 mian;\n}\nstream {\n    access_log /var/log/stream.log main;\n}\n",
+            )],
+        );
+        // Formats are per namespace, and stream has no predefined one
+        assert_eq!(lines(&errors), [(6, Severity::Error), (9, Severity::Error)]);
+    }
+
+    #[test]
+    fn test_escape() {
+        let cases = [
+            (r#"log_format j '{"uri":"$uri"}';"#, Some(Severity::Warning)),
+            (
+                r#"log_format j escape=default '{"uri":"$uri"}';"#,
+                Some(Severity::Warning),
+            ),
+            (
+                r#"log_format j escape=none '{"uri":"$uri"}';"#,
+                Some(Severity::Warning),
+            ),
+            (r#"log_format j escape=json '{"uri":"$uri"}';"#, None),
+            ("log_format p escape=none '$uri';", None),
+            ("log_format p escape=xml '$uri';", Some(Severity::Error)),
+        ];
+        for (directive, expected) in cases {
+            let errors = check_cross_file(
+                &AccessLogMisconfigured,
+                &[(
+                    "nginx.conf",
+                    "",
+                    &format!("http {{\n    {}\n}}\n", directive),
+                )],
+            );
+            assert_eq!(
+                errors.first().map(|(_, e)| e.severity),
+                expected,
+                "{}: {:?}",
+                directive,
+                errors
+            );
+        }
+    }
+
+    #[test]
+    fn test_access_log_off() {
+        let errors = check_cross_file(
+            &AccessLogMisconfigured,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    access_log off;\n    server {\n        access_log off;\n    }\n}\n",
+            )],
+        );
+        assert_eq!(lines(&errors), [(2, Severity::Warning)]);
+
+        // Turned back on by a server
+        let errors = check_cross_file(
+            &AccessLogMisconfigured,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    access_log off;\n    include conf.d/*.conf;\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    access_log /var/log/nginx/app.log;\n}\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_join_across_files() {
+        let errors = check_cross_file(
+            &AccessLogMisconfigured,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    log_format main '$remote_addr - $request';\n    include conf.d/*.conf;\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    access_log /var/log/nginx/app.log main;\n    access_log /var/log/nginx/api.log api;\n}\n",
+                ),
+            ],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].0, PathBuf::from("conf.d/app.conf"));
+        assert_eq!(errors[0].1.line, Some(3));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::ignore::{format_checksum, parse_generated_regions};
+use crate::linter::{LintError, LintRule, Severity};
+use crate::parser::ast::Config;
+use std::fs;
+use std::path::Path;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "generated-region-modified",
+    category: "best-practices",
+    description: "Detects manual edits inside regions written by a config-management tool",
+    severity: "warning",
+    why: r#"Configuration written by a tool (Ansible, Chef, Terraform, a template
+renderer) is overwritten on the tool's next run. A quick fix made by hand in
+such a file works until then and is silently lost, or the tool's run is
+blocked on an unexpected diff.
+
+Mark the region the tool writes with comments. The tool records the
+CRC-32 (as computed by zlib, e.g. Python's `zlib.crc32`) of the lines
+between the markers, each ending in a newline:
+
+```nginx
+# nginx-lint:generated-by ansible checksum=crc32:3b50ca2d
+server_tokens off;
+# nginx-lint:generated-end
+```
+
+This rule reports a region whose content no longer matches its checksum;
+make the change in the tool's source instead. Without `generated-end` the
+region runs to the end of the file. Style rules are not reported inside
+generated regions, with or without a checksum."#,
+    bad_example: include_str!("generated_region_modified/bad.conf"),
+    good_example: include_str!("generated_region_modified/good.conf"),
+    references: &[],
+    preset: Some(RulePreset::Recommended),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that generated regions still match their recorded checksum
+pub struct GeneratedRegionModified;
+
+impl GeneratedRegionModified {
+    /// Check the generated regions of `content`
+    pub fn check_content(&self, content: &str) -> Vec<LintError> {
+        // Malformed markers are reported with the ignore comments
+        let (regions, _) = parse_generated_regions(content);
+
+        regions
+            .iter()
+            .filter(|region| region.is_modified())
+            .map(|region| {
+                let message = format!(
+                    "region generated by {} has been edited by hand (content checksum {}, expected {}); make the change in {} instead",
+                    region.tool,
+                    format_checksum(region.actual_checksum),
+                    format_checksum(region.checksum.unwrap_or_default()),
+                    region.tool
+                );
+                LintError::new(self.name(), self.category(), &message, Severity::Warning)
+                    .with_location(region.start_line, 1)
+            })
+            .collect()
+    }
+}
+
+impl LintRule for GeneratedRegionModified {
+    fn name(&self) -> &'static str {
+        "generated-region-modified"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects manual edits inside regions written by a config-management tool"
+    }
+
+    fn check(&self, _config: &Config, path: &Path) -> Vec<LintError> {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return Vec::new(),
+        };
+
+        self.check_content(&content)
+    }
+
+    fn wants_content(&self) -> bool {
+        true
+    }
+
+    fn check_with_content(&self, _config: &Config, _path: &Path, content: &str) -> Vec<LintError> {
+        self.check_content(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unmodified_region() {
+        let content = "# nginx-lint:generated-by ansible checksum=crc32:3b50ca2d\nserver_tokens off;\n# nginx-lint:generated-end\n";
+        assert!(GeneratedRegionModified.check_content(content).is_empty());
+    }
+
+    #[test]
+    fn test_modified_region() {
+        let content = "events {}\n# nginx-lint:generated-by ansible checksum=crc32:3b50ca2d\nserver_tokens on;\n# nginx-lint:generated-end\nserver_tokens on;\n";
+        let errors = GeneratedRegionModified.check_content(content);
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(2));
+        assert_eq!(
+            errors[0].message,
+            "region generated by ansible has been edited by hand (content checksum crc32:6158e797, expected crc32:3b50ca2d); make the change in ansible instead"
+        );
+    }
+
+    #[test]
+    fn test_region_to_end_of_file() {
+        let content =
+            "# nginx-lint:generated-by terraform checksum=crc32:3b50ca2d\nserver_tokens off;\n";
+        assert!(GeneratedRegionModified.check_content(content).is_empty());
+
+        let content = "# nginx-lint:generated-by terraform checksum=crc32:3b50ca2d\nserver_tokens off;\ngzip on;\n";
+        assert_eq!(GeneratedRegionModified.check_content(content).len(), 1);
+    }
+
+    #[test]
+    fn test_region_without_checksum() {
+        let content =
+            "# nginx-lint:generated-by chef\nserver_tokens on;\n# nginx-lint:generated-end\n";
+        assert!(GeneratedRegionModified.check_content(content).is_empty());
+    }
+}
@@ -0,0 +1,489 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, HttpServer, HttpsRedirect, RedirectHost, SymbolTable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "https-redirect-misconfigured",
+    category: "best-practices",
+    description: "Detects HTTP servers that redirect to HTTPS but also serve content, inconsistent redirect hosts, and HTTPS names without a redirect",
+    severity: "warning",
+    why: r#"A plain HTTP server that redirects to HTTPS should do only that.
+
+A `return` (or a catch-all `rewrite`) at server level runs before locations
+are matched, so the server's `location` blocks are never used. This often
+breaks certificate renewal: the
+`/.well-known/acme-challenge/` location meant to answer HTTP-01 challenges
+is redirected like everything else. Put the redirect in `location /` when
+some locations must stay reachable over HTTP.
+
+With the redirect in `location /`, other locations that proxy or serve
+files do so over plain HTTP, usually left over from before the site moved
+to HTTPS. ACME challenge locations are not reported.
+
+Redirects to `https://$host...` keep the name the client asked for, while
+redirects to a hard-coded host send every name of the server to that one.
+When the HTTP servers mix both, the ones using the less common style are
+reported, as they are usually copied from another site.
+
+Finally, a name only served by an HTTPS server gets no redirect: visitors
+typing the bare name reach the default HTTP server, or nothing at all. Set
+`require_redirect_server = false` for HTTPS-only services.
+
+Servers are collected across every file of the configuration."#,
+    bad_example: include_str!("https_redirect_misconfigured/bad.conf"),
+    good_example: include_str!("https_redirect_misconfigured/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_rewrite_module.html#return",
+        "https://nginx.org/en/docs/http/request_processing.html",
+        "https://letsencrypt.org/docs/challenge-types/#http-01-challenge",
+    ],
+    preset: Some(RulePreset::Recommended),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check HTTP to HTTPS redirect servers
+pub struct HttpsRedirectMisconfigured {
+    /// Whether names served over HTTPS need a plain HTTP server
+    require_redirect_server: bool,
+}
+
+impl Default for HttpsRedirectMisconfigured {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl HttpsRedirectMisconfigured {
+    pub fn new(require_redirect_server: bool) -> Self {
+        Self {
+            require_redirect_server,
+        }
+    }
+
+    fn error(&self, message: &str, at: (usize, usize)) -> LintError {
+        LintError::new(self.name(), self.category(), message, Severity::Warning)
+            .with_location(at.0, at.1)
+    }
+
+    /// Locations of a redirecting server that are never used, or serve
+    /// content over plain HTTP
+    fn check_locations(
+        &self,
+        server: &HttpServer,
+        redirect: &HttpsRedirect,
+        errors: &mut Vec<(PathBuf, LintError)>,
+    ) {
+        if !redirect.in_location {
+            if server.locations.is_empty() {
+                return;
+            }
+            let locations: Vec<_> = server
+                .locations
+                .iter()
+                .map(|l| format!("location {} (line {})", l.uri, l.line))
+                .collect();
+            let acme = if server.locations.iter().any(|l| is_acme(&l.uri)) {
+                "; ACME HTTP-01 challenges are redirected too, so certificate renewals fail"
+            } else {
+                ""
+            };
+            let message = format!(
+                "{} at server level redirects every request to HTTPS before locations are matched, so {} {} never used{}; move the redirect into location / if they should be",
+                redirect.directive,
+                locations.join(", "),
+                if locations.len() == 1 { "is" } else { "are" },
+                acme
+            );
+            errors.push((
+                server.path.clone(),
+                self.error(&message, (redirect.line, redirect.column)),
+            ));
+            return;
+        }
+
+        for location in &server.locations {
+            if !location.serves_content || is_acme(&location.uri) {
+                continue;
+            }
+            let message = format!(
+                "location {} serves content over plain HTTP while location / redirects to HTTPS (line {}); serve it from the HTTPS server or redirect it too",
+                location.uri, redirect.line
+            );
+            errors.push((
+                server.path.clone(),
+                self.error(&message, (location.line, location.column)),
+            ));
+        }
+    }
+
+    /// Redirects using the less common of `$host`-style and hard-coded hosts
+    fn check_redirect_hosts(
+        &self,
+        servers: &[&HttpServer],
+        errors: &mut Vec<(PathBuf, LintError)>,
+    ) {
+        let (variable, literal): (Vec<(&HttpServer, &HttpsRedirect)>, Vec<_>) = servers
+            .iter()
+            .filter_map(|s| Some((*s, s.https_redirect.as_ref()?)))
+            .filter(|(_, r)| r.host != RedirectHost::Other)
+            .partition(|(_, r)| matches!(r.host, RedirectHost::Variable(_)));
+        let (Some(first_variable), Some(first_literal)) = (variable.first(), literal.first())
+        else {
+            return;
+        };
+
+        let (reported, example) = if literal.len() <= variable.len() {
+            (&literal, first_variable)
+        } else {
+            (&variable, first_literal)
+        };
+        let example = format!(
+            "https://{} at {}:{}",
+            host_text(&example.1.host),
+            example.0.path.display(),
+            example.1.line
+        );
+        for (server, redirect) in reported {
+            let message = format!(
+                "redirect to https://{} {} while other HTTP servers redirect to {}; use one style for every redirect",
+                host_text(&redirect.host),
+                match redirect.host {
+                    RedirectHost::Variable(_) => "keeps the requested host",
+                    _ => "hard-codes the host",
+                },
+                example
+            );
+            errors.push((
+                server.path.clone(),
+                self.error(&message, (redirect.line, redirect.column)),
+            ));
+        }
+    }
+
+    /// HTTPS server names that no plain HTTP server handles
+    fn check_redirect_servers(
+        &self,
+        servers: &[HttpServer],
+        errors: &mut Vec<(PathBuf, LintError)>,
+    ) {
+        let plain: Vec<_> = servers.iter().filter(|s| s.listens_plain).collect();
+        // A catch-all redirecting to the requested host covers every name
+        let catch_all = plain.iter().any(|s| {
+            (s.is_default || s.server_names.is_empty() || s.server_names.iter().any(|n| n == "_"))
+                && s.https_redirect
+                    .as_ref()
+                    .is_some_and(|r| matches!(r.host, RedirectHost::Variable(_)))
+        });
+        if catch_all {
+            return;
+        }
+
+        for server in servers.iter().filter(|s| s.listens_tls && !s.listens_plain) {
+            let missing: Vec<_> = server
+                .server_names
+                .iter()
+                .filter(|name| !matches!(name.as_str(), "" | "_") && !name.starts_with('~'))
+                .filter(|name| {
+                    !plain
+                        .iter()
+                        .flat_map(|s| &s.server_names)
+                        .any(|pattern| name_matches(pattern, name))
+                })
+                .map(|name| name.as_str())
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            let message = format!(
+                "no plain HTTP server handles {}, so http:// requests for it get no redirect to HTTPS; add a port 80 server redirecting to https://$host$request_uri",
+                missing.join(", ")
+            );
+            errors.push((
+                server.path.clone(),
+                self.error(&message, (server.line, server.column)),
+            ));
+        }
+    }
+}
+
+/// Whether a location answers ACME HTTP-01 challenges
+fn is_acme(uri: &str) -> bool {
+    uri.contains("/.well-known/")
+}
+
+/// A redirect host as written in the target
+fn host_text(host: &RedirectHost) -> String {
+    match host {
+        RedirectHost::Variable(name) => format!("${}", name),
+        RedirectHost::Literal(name) => name.clone(),
+        RedirectHost::Other => "...".to_string(),
+    }
+}
+
+/// Whether the `server_name` `pattern` matches `name`; regular expressions
+/// are assumed to match
+fn name_matches(pattern: &str, name: &str) -> bool {
+    if pattern.starts_with('~') {
+        return true;
+    }
+    let name = name.to_ascii_lowercase();
+    let pattern = pattern.to_ascii_lowercase();
+    if let Some(suffix) = pattern.strip_prefix('*') {
+        name.ends_with(suffix)
+    } else if let Some(domain) = pattern.strip_prefix('.') {
+        name == domain || name.ends_with(&pattern)
+    } else if let Some(prefix) = pattern.strip_suffix('*') {
+        name.starts_with(prefix)
+    } else {
+        name == pattern
+    }
+}
+
+impl CrossFileRule for HttpsRedirectMisconfigured {
+    fn name(&self) -> &'static str {
+        "https-redirect-misconfigured"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects HTTP servers that redirect to HTTPS but also serve content, inconsistent redirect hosts, and HTTPS names without a redirect"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        let mut errors = Vec::new();
+        let servers = symbols.http_servers();
+        let redirecting: Vec<_> = servers
+            .iter()
+            .filter(|s| s.listens_plain && !s.listens_tls && s.https_redirect.is_some())
+            .collect();
+
+        for server in &redirecting {
+            if let Some(redirect) = &server.https_redirect {
+                self.check_locations(server, redirect, &mut errors);
+            }
+        }
+        self.check_redirect_hosts(&redirecting, &mut errors);
+        if self.require_redirect_server {
+            self.check_redirect_servers(servers, &mut errors);
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_string;
+    use crate::semantic::SourceFile;
+    use std::path::Path;
+
+    fn check_with(
+        rule: HttpsRedirectMisconfigured,
+        files: &[(&str, &str)],
+    ) -> Vec<(PathBuf, LintError)> {
+        let configs: Vec<_> = files
+            .iter()
+            .map(|(_, src)| parse_string(src).unwrap())
+            .collect();
+        let sources: Vec<SourceFile> = files
+            .iter()
+            .zip(&configs)
+            .map(|((path, src), config)| SourceFile {
+                path: Path::new(path),
+                config,
+                content: src,
+            })
+            .collect();
+        rule.check(&SymbolTable::build(&sources))
+    }
+
+    fn check(src: &str) -> Vec<usize> {
+        check_with(
+            HttpsRedirectMisconfigured::default(),
+            &[("nginx.conf", src)],
+        )
+        .iter()
+        .map(|(_, e)| e.line.unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_bad_example() {
+        let errors = check_with(
+            HttpsRedirectMisconfigured::default(),
+            &[(
+                "nginx.conf",
+                include_str!("https_redirect_misconfigured/bad.conf"),
+            )],
+        );
+        let messages: Vec<_> = errors
+            .iter()
+            .map(|(_, e)| (e.line.unwrap(), e.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (
+                    5,
+                    "return at server level redirects every request to HTTPS before locations are matched, so location /.well-known/acme-challenge/ (line 8) is never used; ACME HTTP-01 challenges are redirected too, so certificate renewals fail; move the redirect into location / if they should be"
+                ),
+                (
+                    22,
+                    "location /api/ serves content over plain HTTP while location / redirects to HTTPS (line 18); serve it from the HTTPS server or redirect it too"
+                ),
+                (
+                    18,
+                    "redirect to https://shop.example.com hard-codes the host while other HTTP servers redirect to https://$host at nginx.conf:5; use one style for every redirect"
+                ),
+                (
+                    27,
+                    "no plain HTTP server handles api.example.com, so http:// requests for it get no redirect to HTTPS; add a port 80 server redirecting to https://$host$request_uri"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_good_example() {
+        assert!(check(include_str!("https_redirect_misconfigured/good.conf")).is_empty());
+    }
+
+    #[test]
+    fn test_redirect_forms() {
+        let server = |redirect: &str| {
+            format!(
+                "http {{\n    server {{\n        listen 80 default_server;\n        {}\n        location /app/ {{\n            proxy_pass http://app;\n        }}\n    }}\n}}\n",
+                redirect
+            )
+        };
+        assert_eq!(check(&server("return 301 https://$host$request_uri;")), [4]);
+        assert_eq!(check(&server("return https://$host$request_uri;")), [4]);
+        assert_eq!(
+            check(&server(
+                "rewrite ^ https://$server_name$request_uri? permanent;"
+            )),
+            [4]
+        );
+        // Not redirects to HTTPS
+        assert!(check(&server("return 301 http://$host$request_uri;")).is_empty());
+        assert!(check(&server("return 404;")).is_empty());
+        assert!(
+            check(&server(
+                "rewrite ^/old/(.*)$ https://$host/new/$1 permanent;"
+            ))
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_locations_next_to_redirect() {
+        let config = |location: &str| {
+            format!(
+                "http {{\n    server {{\n        listen 80 default_server;\n        location / {{\n            return 301 https://$host$request_uri;\n        }}\n{}    }}\n}}\n",
+                location
+            )
+        };
+        assert!(check(&config("")).is_empty());
+        assert_eq!(
+            check(&config(
+                "        location /static/ {\n            root /srv;\n        }\n"
+            )),
+            [7]
+        );
+        // ACME challenges and locations without content handlers
+        assert!(
+            check(&config(
+                "        location ^~ /.well-known/acme-challenge/ {\n            root /srv/acme;\n        }\n        location = /health {\n            return 200;\n        }\n"
+            ))
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_redirect_host_style() {
+        let server = |name: &str, host: &str| {
+            format!(
+                "    server {{\n        listen 80;\n        server_name {};\n        return 301 https://{}$request_uri;\n    }}\n",
+                name, host
+            )
+        };
+        // The minority style is reported
+        let config = format!(
+            "http {{\n{}{}{}}}\n",
+            server("a.example.com", "a.example.com"),
+            server("b.example.com", "b.example.com"),
+            server("c.example.com", "$host")
+        );
+        assert_eq!(check(&config), [15]);
+        // Captures and several variables are not compared
+        let config = format!(
+            "http {{\n{}{}}}\n",
+            server("a.example.com", "a.example.com"),
+            server("b.example.com", "$1.example.com")
+        );
+        assert!(check(&config).is_empty());
+    }
+
+    #[test]
+    fn test_missing_redirect_server() {
+        let tls = "    server {\n        listen 443 ssl;\n        server_name example.com *.example.net;\n    }\n";
+        assert_eq!(check(&format!("http {{\n{}}}\n", tls)), [2]);
+
+        // Names, wildcards and redirecting catch-alls
+        let cases = [
+            ("server_name example.com *.example.net;", true),
+            ("server_name .example.com *.example.net;", true),
+            ("server_name ~^(.+)$;", true),
+            (
+                "listen 80 default_server;\n        return 301 https://$host$request_uri;",
+                true,
+            ),
+            ("server_name example.com www.example.net;", false),
+            ("listen 80 default_server;\n        return 444;", false),
+        ];
+        for (plain, covered) in cases {
+            let config = format!(
+                "http {{\n    server {{\n        {}\n    }}\n{}}}\n",
+                plain, tls
+            );
+            assert_eq!(check(&config).is_empty(), covered, "{}", plain);
+        }
+
+        // A server listening on both handles plain HTTP itself
+        assert!(
+            check("http {\n    server {\n        listen 80;\n        listen 443 ssl;\n        server_name example.com;\n    }\n}\n")
+                .is_empty()
+        );
+
+        let errors = check_with(
+            HttpsRedirectMisconfigured::new(false),
+            &[("nginx.conf", &format!("http {{\n{}}}\n", tls))],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_join_across_files() {
+        let errors = check_with(
+            HttpsRedirectMisconfigured::default(),
+            &[
+                (
+                    "nginx.conf",
+                    "http {\n    server {\n        listen 80 default_server;\n        return 301 https://$host$request_uri;\n    }\n    include conf.d/*.conf;\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "server {\n    listen 443 ssl;\n    server_name app.example.com;\n}\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
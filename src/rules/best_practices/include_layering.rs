@@ -0,0 +1,320 @@
+use crate::config::LayerPolicy;
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, LintRule, Severity};
+use crate::parser::ast::Config;
+use glob::{MatchOptions, Pattern};
+use std::path::{Component, Path};
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "include-layering",
+    category: "best-practices",
+    description: "Detects directives placed outside the files the project's layering allows",
+    severity: "warning",
+    why: r#"Large configurations are usually split by convention: server blocks in
+`sites-enabled/`, TLS settings in shared snippets, nothing but global
+settings in the root `nginx.conf`. nginx accepts a directive in any file
+that is included in a valid context, so the convention erodes one quick
+fix at a time, and the next reader no longer knows where to look.
+
+This rule enforces the layers set in `.nginx-lint.toml`, so it is disabled
+by default. Each layer selects directives by name (`*` and `?` wildcards
+are allowed), optionally only those directly inside `context`, and lists
+the files they may appear in (`allowed_paths`) or must not appear in
+(`denied_paths`). A path glob matches the end of the file's path, so
+`sites-enabled/*.conf` matches `/etc/nginx/sites-enabled/app.conf`; a glob
+starting with `/` must match the whole path.
+
+```toml
+[rules.include-layering]
+enabled = true
+
+[[rules.include-layering.layers]]
+directives = ["server"]
+context = "http"
+allowed_paths = ["sites-enabled/*.conf"]
+
+[[rules.include-layering.layers]]
+directives = ["proxy_pass"]
+denied_paths = ["nginx.conf"]
+```"#,
+    bad_example: include_str!("include_layering/bad.conf"),
+    good_example: include_str!("include_layering/good.conf"),
+    references: &["https://nginx.org/en/docs/ngx_core_module.html#include"],
+    preset: Some(RulePreset::Strict),
+    ..RuleDoc::DEFAULTS
+};
+
+/// A [`LayerPolicy`] with its globs compiled
+struct Layer {
+    directives: Vec<Pattern>,
+    context: Option<String>,
+    allowed_paths: Vec<Pattern>,
+    denied_paths: Vec<Pattern>,
+}
+
+impl Layer {
+    fn selects(&self, name: &str, parent: Option<&str>) -> bool {
+        self.context
+            .as_deref()
+            .is_none_or(|context| parent == Some(context))
+            && self.directives.iter().any(|pattern| pattern.matches(name))
+    }
+}
+
+/// Check that directives only appear in the files their layer allows
+#[derive(Default)]
+pub struct IncludeLayering {
+    layers: Vec<Layer>,
+}
+
+impl IncludeLayering {
+    /// Compile `policies`, returning the rule and the problems with the
+    /// globs that could not be used (those globs are skipped)
+    pub fn new(policies: &[LayerPolicy]) -> (Self, Vec<String>) {
+        let mut problems = Vec::new();
+        let mut compile = |globs: &[String]| -> Vec<Pattern> {
+            globs
+                .iter()
+                .filter_map(|glob| {
+                    Pattern::new(glob)
+                        .map_err(|e| problems.push(format!("invalid glob '{}': {}", glob, e)))
+                        .ok()
+                })
+                .collect()
+        };
+        let layers = policies
+            .iter()
+            .map(|policy| Layer {
+                directives: compile(&policy.directives),
+                context: policy.context.clone(),
+                allowed_paths: compile(&policy.allowed_paths),
+                denied_paths: compile(&policy.denied_paths),
+            })
+            .collect();
+        (Self { layers }, problems)
+    }
+}
+
+/// Whether `pattern` matches `path`, or the end of it at a component boundary
+fn path_matches(pattern: &Pattern, path: &Path) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    if pattern.as_str().starts_with('/') {
+        return pattern.matches_path_with(path, options);
+    }
+    let components: Vec<&str> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+    (0..components.len()).any(|start| pattern.matches_with(&components[start..].join("/"), options))
+}
+
+/// The globs of `patterns`, for messages
+fn list(patterns: &[Pattern]) -> String {
+    patterns
+        .iter()
+        .map(Pattern::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl LintRule for IncludeLayering {
+    fn name(&self) -> &'static str {
+        "include-layering"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects directives placed outside the files the project's layering allows"
+    }
+
+    fn check(&self, config: &Config, path: &Path) -> Vec<LintError> {
+        if self.layers.is_empty() {
+            return Vec::new();
+        }
+        let mut errors = Vec::new();
+
+        for ctx in config.all_directives_with_context() {
+            let directive = ctx.directive;
+            // The first layer selecting the directive decides
+            let Some(layer) = self
+                .layers
+                .iter()
+                .find(|layer| layer.selects(&directive.name, ctx.parent()))
+            else {
+                continue;
+            };
+
+            let message = if let Some(denied) = layer
+                .denied_paths
+                .iter()
+                .find(|pattern| path_matches(pattern, path))
+            {
+                format!(
+                    "'{}' must not be placed in files matching '{}'",
+                    directive.name,
+                    denied.as_str()
+                )
+            } else if !layer.allowed_paths.is_empty()
+                && !layer
+                    .allowed_paths
+                    .iter()
+                    .any(|pattern| path_matches(pattern, path))
+            {
+                format!(
+                    "'{}' belongs in files matching {}",
+                    directive.name,
+                    list(&layer.allowed_paths)
+                )
+            } else {
+                continue;
+            };
+
+            errors.push(
+                LintError::new(self.name(), self.category(), &message, Severity::Warning)
+                    .with_location(directive.span.start.line, directive.span.start.column),
+            );
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_string;
+
+    fn rule(toml: &str) -> IncludeLayering {
+        let config = crate::LintConfig::parse(toml).unwrap();
+        let (rule, problems) = IncludeLayering::new(config.include_layering_layers());
+        assert!(problems.is_empty(), "{:?}", problems);
+        rule
+    }
+
+    const LAYERS: &str = r#"
+[[rules.include-layering.layers]]
+directives = ["server"]
+context = "http"
+allowed_paths = ["sites-enabled/*.conf"]
+
+[[rules.include-layering.layers]]
+directives = ["ssl_*"]
+allowed_paths = ["snippets/ssl-*.conf"]
+
+[[rules.include-layering.layers]]
+directives = ["proxy_pass"]
+denied_paths = ["nginx.conf"]
+"#;
+
+    fn check(path: &str, source: &str) -> Vec<LintError> {
+        rule(LAYERS).check(&parse_string(source).unwrap(), Path::new(path))
+    }
+
+    #[test]
+    fn test_server_outside_sites_enabled() {
+        let source = "http {\n    upstream app {\n        server 127.0.0.1:8080;\n    }\n    server {\n        listen 80;\n    }\n}\n";
+
+        let errors = check("/etc/nginx/nginx.conf", source);
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(5));
+        assert_eq!(
+            errors[0].message,
+            "'server' belongs in files matching sites-enabled/*.conf"
+        );
+
+        // `server` inside upstream is not selected
+        let errors = check("/etc/nginx/sites-enabled/app.conf", source);
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_included_file_context() {
+        let mut config = parse_string("server {\n    listen 80;\n}\n").unwrap();
+        config.include_context = crate::parser::context::IncludeContext::parse("http");
+        let rule = rule(LAYERS);
+
+        let errors = rule.check(&config, Path::new("conf.d/app.conf"));
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(
+            rule.check(&config, Path::new("./sites-enabled/app.conf"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_wildcard_directives_and_denied_paths() {
+        let source = "server {\n    ssl_certificate /etc/ssl/a.pem;\n    location / {\n        proxy_pass http://app;\n    }\n}\n";
+
+        let errors = check("/etc/nginx/nginx.conf", source);
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "'ssl_certificate' belongs in files matching snippets/ssl-*.conf"
+        );
+        assert_eq!(
+            errors[1].message,
+            "'proxy_pass' must not be placed in files matching 'nginx.conf'"
+        );
+
+        let errors = check("/etc/nginx/snippets/ssl-app.conf", source);
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_absolute_and_nested_globs() {
+        let rule = rule(
+            r#"
+[[rules.include-layering.layers]]
+directives = ["listen"]
+allowed_paths = ["/srv/nginx/*.conf"]
+"#,
+        );
+        let config = parse_string("server {\n    listen 80;\n}\n").unwrap();
+        assert!(
+            rule.check(&config, Path::new("/srv/nginx/a.conf"))
+                .is_empty()
+        );
+        // `*` does not cross directories, and absolute globs match whole paths
+        assert_eq!(
+            rule.check(&config, Path::new("/srv/nginx/x/a.conf")).len(),
+            1
+        );
+        assert_eq!(
+            rule.check(&config, Path::new("/opt/srv/nginx/a.conf"))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_invalid_glob_reported() {
+        let config = crate::LintConfig::parse(
+            "[[rules.include-layering.layers]]\ndirectives = [\"server\"]\nallowed_paths = [\"sites-[.conf\"]\n",
+        )
+        .unwrap();
+        let (_, problems) = IncludeLayering::new(config.include_layering_layers());
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("invalid glob 'sites-[.conf'"));
+    }
+
+    #[test]
+    fn test_no_layers() {
+        let config = parse_string("http {\n    server {}\n}\n").unwrap();
+        assert!(
+            IncludeLayering::default()
+                .check(&config, Path::new("nginx.conf"))
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, LintRule, Severity, warn_option_problem};
+use crate::parser::ast::{Block, Config};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "lua-block-lint",
+    category: "best-practices",
+    description: "Runs an external Lua linter (luacheck by default) on *_by_lua_block bodies",
+    severity: "warning",
+    why: r#"The bodies of OpenResty's `*_by_lua_block` directives are Lua code that
+nginx only compiles when a request reaches them: a typo in a variable name
+or a syntax error goes unnoticed until it fails in production.
+
+This rule pipes each body to an external linter on stdin and reports what it
+prints on the nginx file, at the line inside the block. The command is set
+with `command` and defaults to
+`["luacheck", "--formatter", "plain", "--codes", "--std", "ngx_lua", "-"]`;
+any tool printing `name:line:column: message` lines works. Messages with an
+error code (`(E...)`) are reported as errors, the others as warnings.
+
+The rule runs a program from the configuration, so it is disabled by default
+and never runs under trust policy untrusted."#,
+    bad_example: include_str!("lua_block_lint/bad.conf"),
+    good_example: include_str!("lua_block_lint/good.conf"),
+    references: &[
+        "https://github.com/openresty/lua-nginx-module#content_by_lua_block",
+        "https://luacheck.readthedocs.io/en/stable/cli.html",
+    ],
+    preset: Some(RulePreset::Recommended),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Command run when `command` is not configured
+pub const DEFAULT_COMMAND: &[&str] = &[
+    "luacheck",
+    "--formatter",
+    "plain",
+    "--codes",
+    "--std",
+    "ngx_lua",
+    "-",
+];
+
+/// Lint the Lua code of `*_by_lua_block` directives with an external command
+pub struct LuaBlockLint {
+    command: Vec<String>,
+}
+
+impl Default for LuaBlockLint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LuaBlockLint {
+    /// Run [`DEFAULT_COMMAND`]
+    pub fn new() -> Self {
+        Self::with_command(DEFAULT_COMMAND.iter().map(|s| s.to_string()).collect())
+    }
+
+    /// Run `command` (program and arguments), which reads the Lua code on
+    /// stdin
+    pub fn with_command(command: Vec<String>) -> Self {
+        Self { command }
+    }
+
+    /// Name of the tool shown in front of its messages
+    fn tool_name(&self) -> &str {
+        let program = self.command.first().map(String::as_str).unwrap_or("");
+        Path::new(program)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(program)
+    }
+
+    /// Run the command on `code` and return what it printed on stdout
+    fn run(&self, code: &str) -> Result<String, String> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or_else(|| "command is empty".to_string())?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("cannot run '{}': {}", program, e))?;
+
+        // Write from another thread so a tool printing before it has read
+        // all of its input cannot block on a full pipe
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let output = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                // A tool that exits without reading everything closes the pipe
+                let _ = stdin.write_all(code.as_bytes());
+            });
+            child.wait_with_output()
+        })
+        .map_err(|e| format!("'{}' failed: {}", program, e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        // Linters exit with a failure status when they report something, so
+        // only a failure without any report is a problem
+        if !output.status.success() && parse_output(&stdout).next().is_none() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "'{}' failed ({}): {}",
+                program,
+                output.status,
+                stderr.trim()
+            ));
+        }
+        Ok(stdout)
+    }
+
+    fn check_block(&self, block: &Block, errors: &mut Vec<LintError>) {
+        let Some(content) = &block.raw_content else {
+            return;
+        };
+        // The indentation before `}` is not part of the code
+        let code = content.trim_end_matches([' ', '\t']);
+        if code.trim().is_empty() {
+            return;
+        }
+
+        let stdout = match self.run(code) {
+            Ok(stdout) => stdout,
+            Err(problem) => {
+                warn_option_problem(self.name(), &problem);
+                return;
+            }
+        };
+        for message in parse_output(&stdout) {
+            let (line, column) = map_position(block, message.line, message.column);
+            let severity = if message.text.starts_with("(E") {
+                Severity::Error
+            } else {
+                Severity::Warning
+            };
+            errors.push(
+                LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!("{}: {}", self.tool_name(), message.text),
+                    severity,
+                )
+                .with_location(line, column),
+            );
+        }
+    }
+}
+
+/// A message printed by the linter, at a position in the code it was given
+#[derive(Debug, PartialEq)]
+struct ToolMessage<'a> {
+    line: usize,
+    column: usize,
+    text: &'a str,
+}
+
+/// Messages in the `name:line:column: message` (or `name:line: message`)
+/// lines of the linter's output; other lines (summaries) are skipped
+fn parse_output(stdout: &str) -> impl Iterator<Item = ToolMessage<'_>> {
+    stdout.lines().filter_map(|line| {
+        let mut parts = line.splitn(3, ':');
+        let _name = parts.next()?;
+        let line = parts.next()?.trim().parse().ok()?;
+        let rest = parts.next()?;
+        let (column, text) = rest
+            .split_once(':')
+            .and_then(|(column, text)| Some((column.trim().parse().ok()?, text)))
+            .unwrap_or((1, rest));
+        let text = text.trim();
+        (!text.is_empty()).then_some(ToolMessage { line, column, text })
+    })
+}
+
+/// Position in the nginx file of `line`:`column` of the block's code, which
+/// starts right after `{`
+fn map_position(block: &Block, line: usize, column: usize) -> (usize, usize) {
+    let start = &block.span.start;
+    if line <= 1 {
+        (start.line, start.column + column.max(1))
+    } else {
+        (start.line + line - 1, column.max(1))
+    }
+}
+
+impl LintRule for LuaBlockLint {
+    fn name(&self) -> &'static str {
+        "lua-block-lint"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Runs an external Lua linter (luacheck by default) on *_by_lua_block bodies"
+    }
+
+    fn check(&self, config: &Config, _path: &Path) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        for directive in config.all_directives() {
+            if directive.name.ends_with("_by_lua_block")
+                && let Some(block) = &directive.block
+            {
+                self.check_block(block, &mut errors);
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(command: &[&str], source: &str) -> Vec<LintError> {
+        let config = crate::parser::parse_string(source).unwrap();
+        LuaBlockLint::with_command(command.iter().map(|s| s.to_string()).collect())
+            .check(&config, Path::new("nginx.conf"))
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let messages: Vec<_> = parse_output(
+            "stdin:3:11: (W211) unused variable 'name'\nstdin:5: expected 'end'\n\nTotal: 2 warnings\n",
+        )
+        .collect();
+        assert_eq!(
+            messages,
+            [
+                ToolMessage {
+                    line: 3,
+                    column: 11,
+                    text: "(W211) unused variable 'name'"
+                },
+                ToolMessage {
+                    line: 5,
+                    column: 1,
+                    text: "expected 'end'"
+                },
+            ]
+        );
+    }
+
+    /// The code is piped to the command and reported lines are mapped into
+    /// the block: `grep -n` prints the 1-based line numbers it was given
+    #[cfg(unix)]
+    #[test]
+    fn test_lines_are_mapped_into_the_block() {
+        let errors = check(
+            &[
+                "sh",
+                "-c",
+                "grep -n bad | sed 's/^\\([0-9]*\\):.*/stdin:\\1:3: (W000) bad line/'",
+            ],
+            "http {\n    server {\n        location / {\n            content_by_lua_block {\n                local ok = 1\n                local bad = 2\n            }\n            access_by_lua_block { bad() }\n        }\n    }\n}\n",
+        );
+        let positions: Vec<_> = errors
+            .iter()
+            .map(|e| (e.line.unwrap(), e.column.unwrap()))
+            .collect();
+        // Line 3 of the first block's code, and the code right after the
+        // second block's `{` (column 33)
+        assert_eq!(positions, [(6, 3), (8, 36)], "got: {:?}", errors);
+        assert_eq!(errors[0].message, "sh: (W000) bad line");
+        assert_eq!(errors[0].severity, Severity::Warning);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_error_codes_are_errors() {
+        let errors = check(
+            &[
+                "sh",
+                "-c",
+                "cat >/dev/null; echo \"stdin:2:5: (E011) expected '=' near 'x'\"; exit 2",
+            ],
+            "init_by_lua_block {\n    local x x\n}\n",
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert_eq!((errors[0].line, errors[0].column), (Some(2), Some(5)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_command_reports_nothing() {
+        let source = "init_by_lua_block {\n    ngx.log(ngx.ERR, 'x')\n}\n";
+        assert!(check(&["nginx-lint-no-such-command"], source).is_empty());
+        assert!(check(&["sh", "-c", "echo oops >&2; exit 3"], source).is_empty());
+        assert!(check(&[], source).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_empty_and_other_blocks_are_skipped() {
+        let errors = check(
+            &[
+                "sh",
+                "-c",
+                "cat >/dev/null; echo 'stdin:1:1: (W000) called'",
+            ],
+            "http {\n    init_by_lua_block {\n    }\n    server {\n        location / {\n            js_body { x }\n        }\n    }\n}\n",
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable, UpstreamReference};
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "missing-resolver",
+    category: "best-practices",
+    description: "Detects proxy_pass/fastcgi_pass/grpc_pass targets with variables and no resolver in scope",
+    severity: "warning",
+    why: r#"When a `*_pass` target contains a variable, nginx does not resolve its
+host name at startup: it looks the host up among the `upstream` blocks at
+request time, and otherwise resolves it through the `resolver` in effect.
+Without a `resolver`, the configuration loads fine and every such request
+fails with a 502 and "no resolver defined to resolve ..." in the error log.
+
+The `resolver` is looked up through the enclosing `location`, `server` and
+`http` blocks, across every file of the configuration. Targets whose host is
+an upstream name or an IP address need no resolver and are not reported; a
+host that is entirely a variable is reported, as its value is only known at
+request time (add an ignore comment if it always names an upstream block)."#,
+    bad_example: include_str!("missing_resolver/bad.conf"),
+    good_example: include_str!("missing_resolver/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_core_module.html#resolver",
+        "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_pass",
+    ],
+    preset: Some(RulePreset::Recommended),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that targets resolved at request time have a `resolver`
+pub struct MissingResolver;
+
+/// Whether nginx has to resolve the host of `r` through a resolver
+fn needs_resolver(r: &UpstreamReference, symbols: &SymbolTable) -> bool {
+    r.has_variables
+        && (r.is_dynamic
+            || (!symbols.has_upstream(&r.host, r.namespace) && r.host.parse::<Ipv4Addr>().is_err()))
+}
+
+impl CrossFileRule for MissingResolver {
+    fn name(&self) -> &'static str {
+        "missing-resolver"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects proxy_pass/fastcgi_pass/grpc_pass targets with variables and no resolver in scope"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        symbols
+            .upstream_references()
+            .iter()
+            .filter(|r| r.has_resolver == Some(false) && needs_resolver(r, symbols))
+            .map(|r| {
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "{} target with variables needs a resolver for '{}', but none is in scope; requests fail with \"no resolver defined\"",
+                        r.directive, r.host
+                    ),
+                    Severity::Warning,
+                )
+                .with_location(r.line, r.column);
+                (r.path.clone(), error)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    fn location(body: &str) -> String {
+        format!(
+            "http {{\n    upstream app {{\n        server 10.0.0.1:8080;\n    }}\n    server {{\n        location / {{\n{}\n        }}\n    }}\n}}\n",
+            body
+        )
+    }
+
+    #[test]
+    fn test_examples() {
+        let errors = check_cross_file(
+            &MissingResolver,
+            &[("nginx.conf", "", include_str!("missing_resolver/bad.conf"))],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].1.line, Some(8));
+        assert_eq!(
+            errors[0].1.message,
+            "proxy_pass target with variables needs a resolver for '$api_host', but none is in scope; requests fail with \"no resolver defined\""
+        );
+
+        let errors = check_cross_file(
+            &MissingResolver,
+            &[("nginx.conf", "", include_str!("missing_resolver/good.conf"))],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_targets() {
+        let cases = [
+            ("proxy_pass http://api.example.com$request_uri;", 1),
+            ("fastcgi_pass $fpm_backend;", 1),
+            ("grpc_pass grpc://$grpc_host:50051;", 1),
+            // Resolved at startup
+            ("proxy_pass http://api.example.com;", 0),
+            // Upstream names and addresses need no resolver
+            ("proxy_pass http://app$request_uri;", 0),
+            ("proxy_pass http://10.0.0.2:8080$request_uri;", 0),
+        ];
+        for (directive, expected) in cases {
+            let errors = check_cross_file(
+                &MissingResolver,
+                &[("nginx.conf", "", &location(directive))],
+            );
+            assert_eq!(errors.len(), expected, "{}: {:?}", directive, errors);
+        }
+    }
+
+    #[test]
+    fn test_resolver_scopes() {
+        for body in [
+            "resolver 127.0.0.53;\nproxy_pass http://$host;",
+            // Inherited from the location
+            "resolver 127.0.0.53;\nif ($arg_debug) {\n    proxy_pass http://$host;\n}",
+        ] {
+            let errors = check_cross_file(&MissingResolver, &[("nginx.conf", "", &location(body))]);
+            assert!(errors.is_empty(), "{}: {:?}", body, errors);
+        }
+
+        // A resolver in a sibling location does not apply
+        let errors = check_cross_file(
+            &MissingResolver,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    server {\n        location /a {\n            resolver 127.0.0.53;\n        }\n        location /b {\n            proxy_pass http://$host;\n        }\n    }\n}\n",
+            )],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].1.line, Some(7));
+    }
+
+    #[test]
+    fn test_join_across_files() {
+        let app = (
+            "conf.d/app.conf",
+            "http",
+            "server {\n    location / {\n        proxy_pass http://$host;\n    }\n}\n",
+        );
+
+        // The http block of nginx.conf applies to conf.d/*.conf
+        let errors = check_cross_file(
+            &MissingResolver,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    resolver 127.0.0.53;\n    include conf.d/*.conf;\n}\n",
+                ),
+                app,
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+
+        let errors = check_cross_file(
+            &MissingResolver,
+            &[
+                ("nginx.conf", "", "http {\n    include conf.d/*.conf;\n}\n"),
+                app,
+            ],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].0, PathBuf::from("conf.d/app.conf"));
+        assert_eq!(errors[0].1.line, Some(3));
+
+        // A location snippet may get its resolver from the including server
+        let errors = check_cross_file(
+            &MissingResolver,
+            &[(
+                "snippets/proxy.conf",
+                "http,server,location",
+                "proxy_pass http://$host;\n",
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
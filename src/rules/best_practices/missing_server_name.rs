@@ -0,0 +1,151 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "missing-server-name",
+    category: "best-practices",
+    description: "Detects server blocks without server_name when http has several servers",
+    severity: "warning",
+    why: r#"A `server` block without `server_name` only matches requests without a
+`Host` header (its name is `""`). When it is the first server on a listen
+socket, or marked `default_server`, it also receives every request that
+matches no other name. With several servers, whether a block without a name
+ends up as a catch-all therefore depends on the order of the blocks (often
+the order in which files are included) rather than on an explicit decision.
+
+Give each server an explicit `server_name`. For an intentional catch-all,
+use `server_name _;` together with `listen ... default_server`; servers
+marked `default_server` are not reported.
+
+Servers are counted across every file of the configuration."#,
+    bad_example: include_str!("missing_server_name/bad.conf"),
+    good_example: include_str!("missing_server_name/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/server_names.html",
+        "https://nginx.org/en/docs/http/request_processing.html",
+    ],
+    preset: Some(RulePreset::Strict),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that every server of a multi-server `http` context has a `server_name`
+pub struct MissingServerName;
+
+impl CrossFileRule for MissingServerName {
+    fn name(&self) -> &'static str {
+        "missing-server-name"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects server blocks without server_name when http has several servers"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        let servers = symbols.http_servers();
+        if servers.len() < 2 {
+            return Vec::new();
+        }
+
+        servers
+            .iter()
+            .filter(|s| !s.has_server_name && !s.is_default)
+            .map(|s| {
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "server block has no server_name, but http has {} servers; add an \
+                         explicit server_name, or 'server_name _;' with 'listen ... \
+                         default_server' for an intentional catch-all",
+                        servers.len()
+                    ),
+                    Severity::Warning,
+                )
+                .with_location(s.line, s.column);
+                (s.path.clone(), error)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_missing_server_name() {
+        let errors = check_cross_file(
+            &MissingServerName,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("missing_server_name/bad.conf"),
+            )],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1.line, Some(10));
+        assert!(errors[0].1.message.contains("but http has 2 servers"));
+    }
+
+    #[test]
+    fn test_default_server_catch_all() {
+        let errors = check_cross_file(
+            &MissingServerName,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("missing_server_name/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_single_server_without_name() {
+        let errors = check_cross_file(
+            &MissingServerName,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    server {\n        listen 80;\n    }\n}\nstream {\n    server {\n        listen 5432;\n    }\n}\n",
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_servers_in_included_files() {
+        let errors = check_cross_file(
+            &MissingServerName,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    include sites-enabled/*.conf;\n}\n",
+                ),
+                (
+                    "sites-enabled/app.conf",
+                    "http",
+                    "server {\n    listen 80;\n    server_name app.example.com;\n}\n",
+                ),
+                (
+                    "sites-enabled/legacy.conf",
+                    "http",
+                    "server {\n    listen 80;\n    root /var/www/legacy;\n}\n",
+                ),
+            ],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].0, PathBuf::from("sites-enabled/legacy.conf"));
+        assert_eq!(errors[0].1.line, Some(1));
+    }
+}
@@ -1,2 +1,46 @@
-// Best practices rules are now WASM plugins only
-// See plugins/builtin/best_practices/ for implementations
+// Most best practices rules are WASM plugins; see plugins/builtin/best_practices/.
+// The rules here need the whole config tree and run in the cross-file pass, or
+// look at files on disk (precompressed-assets-not-served), at file paths
+// (include-layering) or at generated-region comments (generated-region-modified),
+// or run an external tool (lua-block-lint).
+pub mod access_log_misconfigured;
+pub mod generated_region_modified;
+pub mod https_redirect_misconfigured;
+#[cfg(feature = "cli")]
+pub mod include_layering;
+#[cfg(feature = "cli")]
+pub mod lua_block_lint;
+pub mod missing_resolver;
+pub mod missing_server_name;
+#[cfg(feature = "cli")]
+pub mod precompressed_assets_not_served;
+pub mod proxy_cache_incomplete;
+pub mod proxy_pass_upstream_mismatch;
+pub mod undefined_limit_zone;
+pub mod undefined_upstream;
+pub mod undefined_variable;
+pub mod unused_limit_zone;
+pub mod unused_upstream;
+pub mod unused_variable;
+pub mod upstream_keepalive_incomplete;
+
+pub use access_log_misconfigured::AccessLogMisconfigured;
+pub use generated_region_modified::GeneratedRegionModified;
+pub use https_redirect_misconfigured::HttpsRedirectMisconfigured;
+#[cfg(feature = "cli")]
+pub use include_layering::IncludeLayering;
+#[cfg(feature = "cli")]
+pub use lua_block_lint::LuaBlockLint;
+pub use missing_resolver::MissingResolver;
+pub use missing_server_name::MissingServerName;
+#[cfg(feature = "cli")]
+pub use precompressed_assets_not_served::PrecompressedAssetsNotServed;
+pub use proxy_cache_incomplete::ProxyCacheIncomplete;
+pub use proxy_pass_upstream_mismatch::ProxyPassUpstreamMismatch;
+pub use undefined_limit_zone::UndefinedLimitZone;
+pub use undefined_upstream::UndefinedUpstream;
+pub use undefined_variable::UndefinedVariable;
+pub use unused_limit_zone::UnusedLimitZone;
+pub use unused_upstream::UnusedUpstream;
+pub use unused_variable::UnusedVariable;
+pub use upstream_keepalive_incomplete::UpstreamKeepaliveIncomplete;
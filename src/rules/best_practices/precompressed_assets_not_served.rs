@@ -0,0 +1,404 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, LintRule, Severity};
+use crate::parser::ast::{Block, Config, ConfigItem, Directive};
+use std::path::{Path, PathBuf};
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "precompressed-assets-not-served",
+    category: "best-practices",
+    description: "Detects precompressed .gz/.br files that nginx is not configured to serve",
+    severity: "warning",
+    why: r#"Build tools often emit `app.js.gz` and `app.js.br` next to `app.js`. nginx
+only serves those files when `gzip_static on;` (and `brotli_static on;` from
+the ngx_brotli module) is in effect for the location; otherwise it sends the
+uncompressed file, or compresses it again on every request with `gzip on;`.
+
+This rule looks at the document root on disk, so it is disabled by default.
+When enabled, the directory of each static prefix location (from `root` or
+`alias`) is scanned for `.gz`/`.br` files whose uncompressed sibling exists.
+Set `docroot` to check a local copy of the site instead of the `root` paths
+written in the configuration. Regex and named locations, and locations that
+pass requests to a backend, are not checked."#,
+    bad_example: include_str!("precompressed_assets_not_served/bad.conf"),
+    good_example: include_str!("precompressed_assets_not_served/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_gzip_static_module.html",
+        "https://github.com/google/ngx_brotli#brotli_static",
+    ],
+    preset: Some(RulePreset::Strict),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Stop scanning a directory tree after this many entries
+const MAX_SCANNED_ENTRIES: usize = 10_000;
+
+/// Directives that hand the request to something other than the filesystem
+const NON_STATIC_DIRECTIVES: &[&str] = &[
+    "proxy_pass",
+    "fastcgi_pass",
+    "uwsgi_pass",
+    "scgi_pass",
+    "grpc_pass",
+    "memcached_pass",
+    "return",
+];
+
+/// Check that precompressed files on disk are served with gzip_static/brotli_static
+pub struct PrecompressedAssetsNotServed {
+    docroot: Option<PathBuf>,
+    prefix: Option<PathBuf>,
+}
+
+impl Default for PrecompressedAssetsNotServed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrecompressedAssetsNotServed {
+    pub fn new() -> Self {
+        Self {
+            docroot: None,
+            prefix: None,
+        }
+    }
+
+    /// Scan `docroot` in place of every `root` path, and resolve relative
+    /// `root`/`alias` paths against `prefix`
+    pub fn with_docroot_and_prefix(docroot: Option<PathBuf>, prefix: Option<PathBuf>) -> Self {
+        Self { docroot, prefix }
+    }
+
+    fn walk(&self, items: &[ConfigItem], scope: &Scope, errors: &mut Vec<LintError>) {
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            let Some(block) = &directive.block else {
+                continue;
+            };
+            if !matches!(directive.name.as_str(), "http" | "server" | "location") {
+                continue;
+            }
+
+            let scope = scope.enter(block);
+            if directive.is("location") {
+                self.check_location(directive, block, &scope, errors);
+            } else if directive.is("server")
+                && !block.directives().any(|d| d.is("location"))
+                && let Some(root) = scope.root.as_deref().and_then(|r| self.resolve_root(r))
+            {
+                self.check_dir(directive, "server", &root, &scope, errors);
+            }
+            self.walk(&block.items, &scope, errors);
+        }
+    }
+
+    fn check_location(
+        &self,
+        directive: &Directive,
+        block: &Block,
+        scope: &Scope,
+        errors: &mut Vec<LintError>,
+    ) {
+        let uri = match directive.args.as_slice() {
+            [modifier, uri] if modifier.as_str() == "^~" => uri.as_str(),
+            [uri] if !uri.as_str().starts_with('@') => uri.as_str(),
+            _ => return,
+        };
+        if block
+            .directives()
+            .any(|d| NON_STATIC_DIRECTIVES.contains(&d.name.as_str()))
+        {
+            return;
+        }
+
+        let dir = match block.directives().find(|d| d.is("alias")) {
+            Some(alias) => alias.first_arg().and_then(|a| self.resolve_path(a)),
+            None => scope
+                .root
+                .as_deref()
+                .and_then(|r| self.resolve_root(r))
+                .map(|root| root.join(uri.trim_start_matches('/'))),
+        };
+        if let Some(dir) = dir {
+            self.check_dir(
+                directive,
+                &format!("location '{}'", uri),
+                &dir,
+                scope,
+                errors,
+            );
+        }
+    }
+
+    fn check_dir(
+        &self,
+        directive: &Directive,
+        what: &str,
+        dir: &Path,
+        scope: &Scope,
+        errors: &mut Vec<LintError>,
+    ) {
+        if scope.gzip_static && scope.brotli_static {
+            return;
+        }
+        let found = scan_precompressed(dir);
+
+        let missing = [
+            (found.gzip, scope.gzip_static, "gzip_static on;"),
+            (found.brotli, scope.brotli_static, "brotli_static on;"),
+        ];
+        for (example, enabled, suggestion) in missing {
+            let Some(example) = example else {
+                continue;
+            };
+            if enabled {
+                continue;
+            }
+            let example = example.strip_prefix(dir).unwrap_or(&example);
+            errors.push(
+                LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "{} has precompressed files on disk (e.g. {}) that are not served; add '{}'",
+                        what,
+                        example.display(),
+                        suggestion
+                    ),
+                    Severity::Warning,
+                )
+                .with_location(directive.span.start.line, directive.span.start.column),
+            );
+        }
+    }
+
+    /// Directory on disk for a `root` path
+    fn resolve_root(&self, root: &str) -> Option<PathBuf> {
+        match &self.docroot {
+            Some(docroot) => Some(docroot.clone()),
+            None => self.resolve_path(root),
+        }
+    }
+
+    /// Resolve a path from the config; relative paths need the prefix
+    fn resolve_path(&self, path: &str) -> Option<PathBuf> {
+        // Paths built from variables cannot be resolved statically
+        if path.contains('$') {
+            return None;
+        }
+        let path = Path::new(path);
+        if path.is_absolute() {
+            Some(path.to_path_buf())
+        } else {
+            self.prefix.as_ref().map(|prefix| prefix.join(path))
+        }
+    }
+}
+
+/// Settings inherited from http → server → location
+#[derive(Clone, Default)]
+struct Scope {
+    root: Option<String>,
+    gzip_static: bool,
+    brotli_static: bool,
+}
+
+impl Scope {
+    fn enter(&self, block: &Block) -> Scope {
+        let mut scope = self.clone();
+        for directive in block.directives() {
+            match directive.name.as_str() {
+                "root" => scope.root = directive.first_arg().map(str::to_string),
+                "gzip_static" => scope.gzip_static = is_static_enabled(directive),
+                "brotli_static" => scope.brotli_static = is_static_enabled(directive),
+                _ => {}
+            }
+        }
+        scope
+    }
+}
+
+fn is_static_enabled(directive: &Directive) -> bool {
+    matches!(directive.first_arg(), Some("on" | "always"))
+}
+
+/// First `.gz` and `.br` files found whose uncompressed sibling exists
+#[derive(Default)]
+struct Precompressed {
+    gzip: Option<PathBuf>,
+    brotli: Option<PathBuf>,
+}
+
+fn scan_precompressed(dir: &Path) -> Precompressed {
+    let mut found = Precompressed::default();
+    let mut pending = vec![dir.to_path_buf()];
+    let mut scanned = 0;
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            scanned += 1;
+            if scanned > MAX_SCANNED_ENTRIES {
+                return found;
+            }
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let slot = match path.extension().and_then(|e| e.to_str()) {
+                Some("gz") => &mut found.gzip,
+                Some("br") => &mut found.brotli,
+                _ => continue,
+            };
+            if slot.is_none() && path.with_extension("").is_file() {
+                *slot = Some(path);
+            }
+        }
+        if found.gzip.is_some() && found.brotli.is_some() {
+            break;
+        }
+    }
+    found
+}
+
+impl LintRule for PrecompressedAssetsNotServed {
+    fn name(&self) -> &'static str {
+        "precompressed-assets-not-served"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects precompressed .gz/.br files that nginx is not configured to serve"
+    }
+
+    fn check(&self, config: &Config, _path: &Path) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        self.walk(&config.items, &Scope::default(), &mut errors);
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_file(dir: &Path, name: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "x").unwrap();
+    }
+
+    fn check(docroot: &Path, source: &str) -> Vec<LintError> {
+        let config = crate::parser::parse_string(source).unwrap();
+        PrecompressedAssetsNotServed::with_docroot_and_prefix(Some(docroot.to_path_buf()), None)
+            .check(&config, Path::new("nginx.conf"))
+    }
+
+    #[test]
+    fn test_gzip_and_brotli_siblings() {
+        let temp = TempDir::new().unwrap();
+        create_file(temp.path(), "assets/app.js");
+        create_file(temp.path(), "assets/app.js.gz");
+        create_file(temp.path(), "assets/app.js.br");
+
+        let errors = check(
+            temp.path(),
+            "http {\n    server {\n        root /var/www;\n        location /assets/ {\n        }\n    }\n}\n",
+        );
+
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(4));
+        assert!(errors[0].message.contains("location '/assets/'"));
+        assert!(errors[0].message.contains("app.js.gz"));
+        assert!(errors[0].message.contains("'gzip_static on;'"));
+        assert!(errors[1].message.contains("'brotli_static on;'"));
+    }
+
+    #[test]
+    fn test_enabled_in_http_is_inherited() {
+        let temp = TempDir::new().unwrap();
+        create_file(temp.path(), "app.css");
+        create_file(temp.path(), "app.css.gz");
+
+        let errors = check(
+            temp.path(),
+            "http {\n    gzip_static on;\n    server {\n        root /var/www;\n        location / {\n        }\n    }\n}\n",
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_gz_without_sibling_is_ignored() {
+        let temp = TempDir::new().unwrap();
+        create_file(temp.path(), "downloads/archive.tar.gz");
+
+        let errors = check(
+            temp.path(),
+            "http {\n    server {\n        root /var/www;\n        location /downloads/ {\n        }\n    }\n}\n",
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_server_without_locations() {
+        let temp = TempDir::new().unwrap();
+        create_file(temp.path(), "index.html");
+        create_file(temp.path(), "index.html.gz");
+
+        let errors = check(
+            temp.path(),
+            "http {\n    server {\n        root /var/www;\n    }\n}\n",
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(2));
+        assert!(
+            errors[0]
+                .message
+                .starts_with("server has precompressed files")
+        );
+    }
+
+    #[test]
+    fn test_proxied_and_regex_locations_are_skipped() {
+        let temp = TempDir::new().unwrap();
+        create_file(temp.path(), "app.js");
+        create_file(temp.path(), "app.js.gz");
+
+        let errors = check(
+            temp.path(),
+            "http {\n    server {\n        root /var/www;\n        location / {\n            proxy_pass http://backend;\n        }\n        location ~ \\.js$ {\n        }\n        location @fallback {\n        }\n    }\n}\n",
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_alias_and_root_from_config() {
+        let temp = TempDir::new().unwrap();
+        create_file(temp.path(), "static/logo.svg");
+        create_file(temp.path(), "static/logo.svg.gz");
+
+        let source = format!(
+            "http {{\n    server {{\n        location /img/ {{\n            alias {}/static/;\n        }}\n    }}\n}}\n",
+            temp.path().display()
+        );
+        let config = crate::parser::parse_string(&source).unwrap();
+        let errors = PrecompressedAssetsNotServed::new().check(&config, Path::new("nginx.conf"));
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].message.contains("logo.svg.gz"));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "proxy-cache-incomplete",
+    category: "best-practices",
+    description: "Detects proxy_cache with an undeclared zone, or without proxy_cache_key or proxy_cache_valid",
+    severity: "error",
+    why: r#"`proxy_cache NAME` stores responses in the zone declared by
+`proxy_cache_path ... keys_zone=NAME:size`. When no `proxy_cache_path`
+declares it, nginx refuses to start ("unknown zone").
+
+Without `proxy_cache_key`, responses are cached under
+`$scheme$proxy_host$request_uri`. `$proxy_host` is the backend named in
+`proxy_pass`, not the `Host` of the request: dynamic pages of every virtual
+host proxied to the same backend share cache entries, and a visitor of one
+site can be served a page rendered for another. Set a key that includes
+`$host` (`proxy-cache-key-missing-discriminators` checks what an explicit
+key leaves out).
+
+Without `proxy_cache_valid`, nginx only caches responses whose
+`Cache-Control`, `Expires` or `X-Accel-Expires` headers allow it. Dynamic
+applications rarely send them, so the cache silently stores nothing; set how
+long each status is cached.
+
+Zones are collected across every file of the configuration, and
+`proxy_cache_key` / `proxy_cache_valid` are looked up through the enclosing
+`location`, `server` and `http` blocks."#,
+    bad_example: include_str!("proxy_cache_incomplete/bad.conf"),
+    good_example: include_str!("proxy_cache_incomplete/good.conf"),
+    references: &[
+        "https://docs.nginx.com/nginx/admin-guide/content-cache/content-caching/",
+        "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_cache",
+        "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_cache_key",
+        "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_cache_valid",
+    ],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that `proxy_cache` has a declared zone, a key and validity times
+pub struct ProxyCacheIncomplete;
+
+impl ProxyCacheIncomplete {
+    fn error(&self, message: &str, severity: Severity) -> LintError {
+        LintError::new(self.name(), self.category(), message, severity)
+    }
+}
+
+impl CrossFileRule for ProxyCacheIncomplete {
+    fn name(&self) -> &'static str {
+        "proxy-cache-incomplete"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects proxy_cache with an undeclared zone, or without proxy_cache_key or proxy_cache_valid"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        let mut errors = Vec::new();
+        for cache in symbols.proxy_cache_uses() {
+            let mut push = |error: LintError| {
+                errors.push((
+                    cache.path.clone(),
+                    error.with_location(cache.line, cache.column),
+                ));
+            };
+            if let Some(zone) = cache
+                .zone
+                .as_deref()
+                .filter(|zone| !symbols.has_proxy_cache_zone(zone))
+            {
+                push(self.error(
+                    &format!(
+                        "proxy_cache uses zone '{}' which no proxy_cache_path declares with keys_zone; nginx refuses to start",
+                        zone
+                    ),
+                    Severity::Error,
+                ));
+            }
+            if cache.has_cache_key == Some(false) {
+                push(self.error(
+                    "proxy_cache without proxy_cache_key caches under $scheme$proxy_host$request_uri, which ignores the request's Host; include $host in a proxy_cache_key",
+                    Severity::Warning,
+                ));
+            }
+            if cache.has_cache_valid == Some(false) {
+                push(self.error(
+                    "proxy_cache without proxy_cache_valid only caches responses whose Cache-Control or Expires headers allow it",
+                    Severity::Warning,
+                ));
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    fn lines(errors: &[(PathBuf, LintError)]) -> Vec<(usize, Severity)> {
+        errors
+            .iter()
+            .map(|(_, e)| (e.line.unwrap(), e.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_bad_example() {
+        let errors = check_cross_file(
+            &ProxyCacheIncomplete,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("proxy_cache_incomplete/bad.conf"),
+            )],
+        );
+        assert_eq!(
+            lines(&errors),
+            [
+                (10, Severity::Warning),
+                (10, Severity::Warning),
+                (16, Severity::Error)
+            ]
+        );
+        assert_eq!(
+            errors[2].1.message,
+            "proxy_cache uses zone 'api' which no proxy_cache_path declares with keys_zone; nginx refuses to start"
+        );
+    }
+
+    #[test]
+    fn test_good_example() {
+        let errors = check_cross_file(
+            &ProxyCacheIncomplete,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("proxy_cache_incomplete/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_settings_scopes() {
+        let config = |server: &str, location: &str| {
+            format!(
+                "http {{\n    proxy_cache_path /c keys_zone=z:1m;\n    server {{\n{}        location / {{\n            proxy_cache z;\n{}        }}\n    }}\n}}\n",
+                server, location
+            )
+        };
+        let key = "            proxy_cache_key $host$request_uri;\n";
+        let valid = "            proxy_cache_valid 10m;\n";
+        assert!(
+            check_cross_file(
+                &ProxyCacheIncomplete,
+                &[("nginx.conf", "", &config(key, valid))]
+            )
+            .is_empty()
+        );
+        assert!(
+            check_cross_file(
+                &ProxyCacheIncomplete,
+                &[("nginx.conf", "", &config("", &format!("{}{}", key, valid)))]
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            check_cross_file(
+                &ProxyCacheIncomplete,
+                &[("nginx.conf", "", &config(key, ""))]
+            )
+            .len(),
+            1
+        );
+
+        // Disabled and variable zones
+        let errors = check_cross_file(
+            &ProxyCacheIncomplete,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    proxy_cache_key $host$uri;\n    proxy_cache_valid 1m;\n    proxy_cache off;\n    server {\n        proxy_cache $zone;\n    }\n}\n",
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_join_across_files() {
+        let app = (
+            "conf.d/app.conf",
+            "http",
+            "server {\n    location / {\n        proxy_cache pages;\n    }\n}\n",
+        );
+        let errors = check_cross_file(
+            &ProxyCacheIncomplete,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    proxy_cache_path /c keys_zone=pages:10m;\n    proxy_cache_key $host$request_uri;\n    proxy_cache_valid 10m;\n    include conf.d/*.conf;\n}\n",
+                ),
+                app,
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+
+        let errors = check_cross_file(
+            &ProxyCacheIncomplete,
+            &[
+                ("nginx.conf", "", "http {\n    include conf.d/*.conf;\n}\n"),
+                app,
+            ],
+        );
+        assert_eq!(
+            lines(&errors),
+            [
+                (3, Severity::Error),
+                (3, Severity::Warning),
+                (3, Severity::Warning)
+            ]
+        );
+        assert_eq!(errors[0].0, PathBuf::from("conf.d/app.conf"));
+
+        // A location snippet may get its settings from the including blocks
+        let errors = check_cross_file(
+            &ProxyCacheIncomplete,
+            &[(
+                "snippets/cache.conf",
+                "http,server,location",
+                "proxy_cache pages;\n",
+            )],
+        );
+        assert_eq!(lines(&errors), [(1, Severity::Error)]);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable, UpstreamDefinition, UpstreamNamespace};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "proxy-pass-upstream-mismatch",
+    category: "best-practices",
+    description: "Detects proxy_pass targets without a scheme, with a port on an upstream name, or with a scheme the upstream's ports do not serve",
+    severity: "error",
+    why: r#"nginx refuses to start when a `proxy_pass` target has no scheme
+(`proxy_pass backend;`), and when a `*_pass` target that names an `upstream`
+block adds a port (`proxy_pass http://backend:8080;`): the ports belong to
+the `server` entries of the upstream block.
+
+A scheme that does not match the upstream's ports starts fine but fails at
+request time: `https://` to an upstream whose servers all listen on port 80
+makes nginx attempt a TLS handshake with a plain HTTP backend, and `http://`
+to servers all on port 443 sends plain HTTP to a TLS port. These are reported
+as warnings.
+
+Upstream blocks and the directives that use them are joined across every
+file of the configuration. Targets built from variables are not checked."#,
+    bad_example: include_str!("proxy_pass_upstream_mismatch/bad.conf"),
+    good_example: include_str!("proxy_pass_upstream_mismatch/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_pass",
+        "https://nginx.org/en/docs/http/ngx_http_upstream_module.html#upstream",
+    ],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check `*_pass` targets against the scheme nginx needs and the upstream
+/// blocks they name
+pub struct ProxyPassUpstreamMismatch;
+
+impl ProxyPassUpstreamMismatch {
+    fn error(&self, message: &str, severity: Severity) -> LintError {
+        LintError::new(self.name(), self.category(), message, severity)
+    }
+}
+
+/// Whether every server of `upstream` listens on `port`
+fn all_servers_on(upstream: &UpstreamDefinition, port: u16) -> bool {
+    !upstream.server_ports.is_empty() && upstream.server_ports.iter().all(|&p| p == port)
+}
+
+impl CrossFileRule for ProxyPassUpstreamMismatch {
+    fn name(&self) -> &'static str {
+        "proxy-pass-upstream-mismatch"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects proxy_pass targets without a scheme, with a port on an upstream name, or with a scheme the upstream's ports do not serve"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        let mut errors = Vec::new();
+        for r in symbols.upstream_references() {
+            if r.namespace != UpstreamNamespace::Http || r.is_dynamic {
+                continue;
+            }
+            let is_proxy_pass = r.directive == "proxy_pass";
+            let upstream = symbols
+                .upstreams()
+                .iter()
+                .find(|u| u.namespace == r.namespace && u.name == r.host);
+
+            let error = if is_proxy_pass && r.scheme.is_none() {
+                self.error(
+                    &format!(
+                        "proxy_pass to '{}' has no scheme; nginx requires http:// or https://",
+                        r.host
+                    ),
+                    Severity::Error,
+                )
+            } else if let Some(upstream) = upstream.filter(|_| r.has_port) {
+                self.error(
+                    &format!(
+                        "{} names upstream '{}' with a port; nginx refuses this, the ports belong to the upstream's servers",
+                        r.directive, upstream.name
+                    ),
+                    Severity::Error,
+                )
+            } else if let Some(upstream) = upstream.filter(|_| is_proxy_pass) {
+                let message = match r.scheme.as_deref() {
+                    Some("https") if all_servers_on(upstream, 80) => format!(
+                        "proxy_pass uses https:// but every server of upstream '{}' is on port 80; the TLS handshake with a plain HTTP backend fails",
+                        upstream.name
+                    ),
+                    Some("http") if all_servers_on(upstream, 443) => format!(
+                        "proxy_pass uses http:// but every server of upstream '{}' is on port 443, which expects TLS",
+                        upstream.name
+                    ),
+                    _ => continue,
+                };
+                self.error(&message, Severity::Warning)
+            } else {
+                continue;
+            };
+            errors.push((r.path.clone(), error.with_location(r.line, r.column)));
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    fn lines(errors: &[(PathBuf, LintError)]) -> Vec<(usize, Severity)> {
+        errors
+            .iter()
+            .map(|(_, e)| (e.line.unwrap(), e.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_bad_example() {
+        let errors = check_cross_file(
+            &ProxyPassUpstreamMismatch,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("proxy_pass_upstream_mismatch/bad.conf"),
+            )],
+        );
+        assert_eq!(
+            lines(&errors),
+            [
+                (12, Severity::Warning),
+                (17, Severity::Error),
+                (22, Severity::Error)
+            ]
+        );
+        assert_eq!(
+            errors[1].1.message,
+            "proxy_pass names upstream 'app_backend' with a port; nginx refuses this, the ports belong to the upstream's servers"
+        );
+        assert_eq!(
+            errors[2].1.message,
+            "proxy_pass to 'app_backend' has no scheme; nginx requires http:// or https://"
+        );
+    }
+
+    #[test]
+    fn test_good_example() {
+        let errors = check_cross_file(
+            &ProxyPassUpstreamMismatch,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("proxy_pass_upstream_mismatch/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_scheme_against_ports() {
+        let config = |upstream_servers: &str, target: &str| {
+            format!(
+                "http {{\n    upstream app {{\n{}    }}\n    server {{\n        location / {{\n            proxy_pass {};\n        }}\n    }}\n}}\n",
+                upstream_servers, target
+            )
+        };
+        let cases = [
+            ("        server 10.0.0.1;\n", "https://app", 1),
+            ("        server 10.0.0.1:443;\n", "http://app", 1),
+            ("        server 10.0.0.1:443;\n", "https://app", 0),
+            // Mixed ports: one of them may well serve the scheme
+            (
+                "        server 10.0.0.1:443;\n        server 10.0.0.2:80;\n",
+                "http://app",
+                0,
+            ),
+            ("        server unix:/run/app.sock;\n", "https://app", 0),
+        ];
+        for (servers, target, expected) in cases {
+            let errors = check_cross_file(
+                &ProxyPassUpstreamMismatch,
+                &[("nginx.conf", "", &config(servers, target))],
+            );
+            assert_eq!(
+                errors.len(),
+                expected,
+                "{} -> {}: {:?}",
+                servers,
+                target,
+                errors
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_targets() {
+        let errors = check_cross_file(
+            &ProxyPassUpstreamMismatch,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    upstream php {\n        server 127.0.0.1:9000;\n    }\n    server {\n        location / {\n            proxy_pass $backend_url;\n            proxy_pass https://example.com:8443;\n            fastcgi_pass 127.0.0.1:9000;\n            fastcgi_pass php;\n        }\n        location /x {\n            fastcgi_pass php:9000;\n        }\n    }\n}\n",
+            )],
+        );
+        assert_eq!(lines(&errors), [(13, Severity::Error)]);
+        assert!(
+            errors[0]
+                .1
+                .message
+                .starts_with("fastcgi_pass names upstream 'php'")
+        );
+    }
+
+    #[test]
+    fn test_join_across_files() {
+        let errors = check_cross_file(
+            &ProxyPassUpstreamMismatch,
+            &[
+                (
+                    "conf.d/upstreams.conf",
+                    "",
+                    "upstream app {\n    server 10.0.0.1:8080;\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    location / {\n        proxy_pass http://app:8080;\n    }\n}\n",
+                ),
+            ],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].0, PathBuf::from("conf.d/app.conf"));
+        assert_eq!(errors[0].1.line, Some(3));
+    }
+}
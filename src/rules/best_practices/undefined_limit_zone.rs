@@ -0,0 +1,140 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "undefined-limit-zone",
+    category: "best-practices",
+    description: "Detects limit_req/limit_conn directives that name an undeclared zone",
+    severity: "error",
+    why: r#"`limit_req zone=NAME` and `limit_conn NAME` use a shared memory zone
+declared by `limit_req_zone` and `limit_conn_zone`. When no zone of that
+name is declared, nginx refuses to start with "zero size shared memory
+zone". This usually comes from a typo in the zone name, a zone declared
+with the other directive (`limit_conn` naming a `limit_req_zone`), or a
+missing include.
+
+Zones are collected across every file of the configuration; `http` and
+`stream` zones are separate."#,
+    bad_example: include_str!("undefined_limit_zone/bad.conf"),
+    good_example: include_str!("undefined_limit_zone/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_limit_req_module.html#limit_req_zone",
+        "https://nginx.org/en/docs/http/ngx_http_limit_conn_module.html#limit_conn_zone",
+    ],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that zones used by `limit_req` / `limit_conn` are declared
+pub struct UndefinedLimitZone;
+
+impl CrossFileRule for UndefinedLimitZone {
+    fn name(&self) -> &'static str {
+        "undefined-limit-zone"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects limit_req/limit_conn directives that name an undeclared zone"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        symbols
+            .limit_zone_references()
+            .iter()
+            .filter(|r| !symbols.has_limit_zone(r.kind, &r.name, r.namespace))
+            .map(|r| {
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "{} uses zone '{}' which no {} declares; nginx refuses to start",
+                        r.kind.using_directive(),
+                        r.name,
+                        r.kind.declaring_directive()
+                    ),
+                    Severity::Error,
+                )
+                .with_location(r.line, r.column);
+                (r.path.clone(), error)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_examples() {
+        let errors = check_cross_file(
+            &UndefinedLimitZone,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("undefined_limit_zone/bad.conf"),
+            )],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].1.line, Some(9));
+        assert_eq!(
+            errors[0].1.message,
+            "limit_req uses zone 'perip' which no limit_req_zone declares; nginx refuses to start"
+        );
+
+        let errors = check_cross_file(
+            &UndefinedLimitZone,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("undefined_limit_zone/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_zone_kinds_and_namespaces() {
+        let errors = check_cross_file(
+            &UndefinedLimitZone,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    limit_req_zone $binary_remote_addr zone=one:10m rate=1r/s;\n    server {\n        limit_conn one 10;\n        limit_req zone=one;\n    }\n}\nstream {\n    server {\n        limit_conn one 5;\n    }\n}\n",
+            )],
+        );
+        let lines: Vec<_> = errors.iter().map(|(_, e)| e.line.unwrap()).collect();
+        assert_eq!(lines, [4, 10]);
+    }
+
+    #[test]
+    fn test_join_across_files() {
+        let errors = check_cross_file(
+            &UndefinedLimitZone,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    limit_conn_zone $binary_remote_addr zone=addr:10m;\n    include conf.d/*.conf;\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    limit_conn addr 10;\n    limit_conn adr 10;\n}\n",
+                ),
+            ],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].0, PathBuf::from("conf.d/app.conf"));
+        assert_eq!(errors[0].1.line, Some(3));
+    }
+}
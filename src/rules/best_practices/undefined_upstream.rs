@@ -0,0 +1,178 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "undefined-upstream",
+    category: "best-practices",
+    description: "Detects proxy_pass/fastcgi_pass/grpc_pass targets that name an undefined upstream",
+    severity: "warning",
+    why: r#"When a `*_pass` target such as `http://app_backend` does not match any
+`upstream` block, nginx does not fail: it treats the name as a host name and
+tries to resolve it through DNS at startup. A typo in the upstream name (or a
+missing include) therefore silently sends traffic to the wrong place, or
+makes nginx refuse to start on a host where the name does not resolve.
+
+Upstream blocks are collected across every file of the configuration, so an
+upstream defined in one included file may be referenced from another. Targets
+with a port, IP addresses, dotted host names and `localhost` are addresses,
+not upstream names, and are not checked."#,
+    bad_example: include_str!("undefined_upstream/bad.conf"),
+    good_example: include_str!("undefined_upstream/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_upstream_module.html#upstream",
+        "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_pass",
+    ],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that upstream names referenced by `*_pass` directives are defined
+pub struct UndefinedUpstream;
+
+impl CrossFileRule for UndefinedUpstream {
+    fn name(&self) -> &'static str {
+        "undefined-upstream"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects proxy_pass/fastcgi_pass/grpc_pass targets that name an undefined upstream"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        symbols
+            .upstream_references()
+            .iter()
+            .filter(|r| r.looks_like_upstream_name())
+            .filter(|r| !symbols.has_upstream(&r.host, r.namespace))
+            .map(|r| {
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "{} references upstream '{}' which is not defined; nginx will resolve it as a host name",
+                        r.directive, r.host
+                    ),
+                    Severity::Warning,
+                )
+                .with_location(r.line, r.column);
+                (r.path.clone(), error)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_undefined_upstream() {
+        let errors = check_cross_file(
+            &UndefinedUpstream,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("undefined_upstream/bad.conf"),
+            )],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1.line, Some(7));
+        assert!(errors[0].1.message.contains("'app_backend'"));
+    }
+
+    #[test]
+    fn test_defined_upstream() {
+        let errors = check_cross_file(
+            &UndefinedUpstream,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("undefined_upstream/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_upstream_defined_in_other_file() {
+        let errors = check_cross_file(
+            &UndefinedUpstream,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    upstream php {\n        server unix:/run/php.sock;\n    }\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    location ~ \\.php$ {\n        fastcgi_pass php;\n    }\n}\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_error_attributed_to_referencing_file() {
+        let errors = check_cross_file(
+            &UndefinedUpstream,
+            &[
+                ("nginx.conf", "", "http {\n    include conf.d/*.conf;\n}\n"),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    location / {\n        grpc_pass grpc://missing;\n    }\n}\n",
+                ),
+            ],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, PathBuf::from("conf.d/app.conf"));
+        assert_eq!(errors[0].1.line, Some(3));
+    }
+
+    #[test]
+    fn test_addresses_are_not_checked() {
+        let errors = check_cross_file(
+            &UndefinedUpstream,
+            &[(
+                "nginx.conf",
+                "",
+                r#"http {
+    server {
+        location /a { proxy_pass http://127.0.0.1; }
+        location /b { proxy_pass http://backend:8080; }
+        location /c { proxy_pass http://api.example.com; }
+        location /d { proxy_pass http://localhost; }
+        location /e { proxy_pass http://$upstream; }
+        location /f { fastcgi_pass unix:/run/php.sock; }
+    }
+}
+"#,
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_stream_upstream_does_not_satisfy_http_reference() {
+        let errors = check_cross_file(
+            &UndefinedUpstream,
+            &[(
+                "nginx.conf",
+                "",
+                "stream {\n    upstream db {\n        server 10.0.0.1:5432;\n    }\n}\nhttp {\n    server {\n        location / {\n            proxy_pass http://db;\n        }\n    }\n}\n",
+            )],
+        );
+        assert_eq!(errors.len(), 1);
+    }
+}
@@ -0,0 +1,220 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable};
+use crate::variables::{is_builtin_variable, is_template_variable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "undefined-variable",
+    category: "best-practices",
+    description: "Detects variables that are used but never defined",
+    severity: "warning",
+    why: r#"nginx refuses to start when a configuration references an unknown
+variable, but only once the file is actually loaded on a server. A misspelled
+`$variable` or a `map` that lives in a file that is no longer included is
+easy to miss until deployment.
+
+A variable counts as defined when any file of the configuration creates it
+with `set`, `map`, `geo`, `split_clients`, `auth_request_set`, `js_set`,
+`perl_set`, `set_by_lua*` or a named regex capture. nginx built-in variables
+(`$host`, `$request_uri`, `$http_*`, `$arg_*`, ...) and numbered captures
+(`$1`) are always defined, as are the `$template_*` variables standing for
+template placeholders under `--template-mode`.
+
+Variables of third-party modules (`$geoip2_*`, `$ssl_ja3`, ...) can be
+declared with the `known_variables` and `known_prefixes` options, names
+given without the `$`."#,
+    bad_example: include_str!("undefined_variable/bad.conf"),
+    good_example: include_str!("undefined_variable/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/varindex.html",
+        "https://nginx.org/en/docs/http/ngx_http_map_module.html#map",
+    ],
+    preset: Some(RulePreset::Recommended),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that every variable used is built in or defined somewhere
+#[derive(Default)]
+pub struct UndefinedVariable {
+    /// Variables defined outside the configuration, lowercase
+    known_variables: Vec<String>,
+    /// Prefixes of such variable families, lowercase
+    known_prefixes: Vec<String>,
+}
+
+impl UndefinedVariable {
+    pub fn new(known_variables: Vec<String>, known_prefixes: Vec<String>) -> Self {
+        // Variable names are case-insensitive; a leading `$` is tolerated
+        let normalize = |names: Vec<String>| {
+            names
+                .into_iter()
+                .map(|name| name.trim_start_matches('$').to_ascii_lowercase())
+                .collect()
+        };
+        Self {
+            known_variables: normalize(known_variables),
+            known_prefixes: normalize(known_prefixes),
+        }
+    }
+
+    /// Whether `name` was declared with the `known_variables` or
+    /// `known_prefixes` options
+    fn is_known(&self, name: &str) -> bool {
+        let lower = name.to_ascii_lowercase();
+        self.known_variables.contains(&lower)
+            || self
+                .known_prefixes
+                .iter()
+                .any(|p| lower.starts_with(p.as_str()) && lower.len() > p.len())
+    }
+}
+
+impl CrossFileRule for UndefinedVariable {
+    fn name(&self) -> &'static str {
+        "undefined-variable"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects variables that are used but never defined"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        let mut errors = Vec::new();
+        for (path, table) in symbols.variable_tables() {
+            for u in table.uses() {
+                if is_builtin_variable(&u.name)
+                    || is_template_variable(&u.name)
+                    || self.is_known(&u.name)
+                    || symbols.is_variable_defined(&u.name)
+                {
+                    continue;
+                }
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "variable '${}' used in {} is never defined",
+                        u.name, u.directive
+                    ),
+                    Severity::Warning,
+                )
+                .with_location(u.line, u.column);
+                errors.push((path.clone(), error));
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_undefined_variable() {
+        let errors = check_cross_file(
+            &UndefinedVariable::default(),
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("undefined_variable/bad.conf"),
+            )],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1.line, Some(7));
+        assert!(errors[0].1.message.contains("'$backend_host'"));
+    }
+
+    #[test]
+    fn test_defined_variable() {
+        let errors = check_cross_file(
+            &UndefinedVariable::default(),
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("undefined_variable/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_variable_defined_in_other_file() {
+        let errors = check_cross_file(
+            &UndefinedVariable::default(),
+            &[
+                (
+                    "conf.d/geo.conf",
+                    "",
+                    "geo $remote_addr $is_internal {\n    default 0;\n    10.0.0.0/8 1;\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    if ($is_internal) {\n        return 403;\n    }\n}\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_builtin_and_captures_are_defined() {
+        let errors = check_cross_file(
+            &UndefinedVariable::default(),
+            &[(
+                "nginx.conf",
+                "",
+                r#"server {
+    location ~ ^/users/(?<id>\d+)/(.*)$ {
+        add_header X-Request-Id $request_id;
+        add_header X-Agent $http_user_agent;
+        rewrite ^ /profile?id=$id&rest=$2&page=$arg_page last;
+    }
+}
+"#,
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_template_placeholders_are_defined() {
+        let errors = check_cross_file(
+            &UndefinedVariable::default(),
+            &[(
+                "nginx.conf",
+                "",
+                "server {\n    listen ${template_NGINX_PORT};\n    return 200 $template_;\n}\n",
+            )],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].1.message.contains("'$template_'"));
+    }
+
+    #[test]
+    fn test_known_variables_option() {
+        let rule =
+            UndefinedVariable::new(vec!["$ssl_ja3".to_string()], vec!["GeoIP2_".to_string()]);
+        let errors = check_cross_file(
+            &rule,
+            &[(
+                "nginx.conf",
+                "",
+                "server {\n    add_header X-JA3 $ssl_ja3;\n    add_header X-Country $geoip2_country;\n    add_header X-Other $geoip2_ $ssl_ja4;\n}\n",
+            )],
+        );
+        let names: Vec<_> = errors.iter().map(|(_, e)| e.message.as_str()).collect();
+        assert_eq!(names.len(), 2, "got: {:?}", names);
+        assert!(names[0].contains("'$geoip2_'"));
+        assert!(names[1].contains("'$ssl_ja4'"));
+    }
+}
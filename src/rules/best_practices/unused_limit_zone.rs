@@ -0,0 +1,134 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "unused-limit-zone",
+    category: "best-practices",
+    description: "Detects limit_req_zone/limit_conn_zone zones that no limit_req/limit_conn uses",
+    severity: "warning",
+    why: r#"A zone declared by `limit_req_zone` or `limit_conn_zone` limits nothing
+on its own: requests are only limited where a `limit_req` or `limit_conn`
+uses it. An unused zone still reserves its shared memory, and usually means
+the limit was meant to apply somewhere but the directive using it was
+forgotten, removed, or names the zone with a typo.
+
+Uses are collected across every file of the configuration; `http` and
+`stream` zones are separate."#,
+    bad_example: include_str!("unused_limit_zone/bad.conf"),
+    good_example: include_str!("unused_limit_zone/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_limit_req_module.html#limit_req",
+        "https://nginx.org/en/docs/http/ngx_http_limit_conn_module.html#limit_conn",
+    ],
+    preset: Some(RulePreset::Strict),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that every declared limit zone is used
+pub struct UnusedLimitZone;
+
+impl CrossFileRule for UnusedLimitZone {
+    fn name(&self) -> &'static str {
+        "unused-limit-zone"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects limit_req_zone/limit_conn_zone zones that no limit_req/limit_conn uses"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        symbols
+            .limit_zones()
+            .iter()
+            .filter(|z| !symbols.is_limit_zone_referenced(z.kind, &z.name, z.namespace))
+            .map(|z| {
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "{} zone '{}' is never used by {}",
+                        z.kind.declaring_directive(),
+                        z.name,
+                        z.kind.using_directive()
+                    ),
+                    Severity::Warning,
+                )
+                .with_location(z.line, z.column);
+                (z.path.clone(), error)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_examples() {
+        let errors = check_cross_file(
+            &UnusedLimitZone,
+            &[("nginx.conf", "", include_str!("unused_limit_zone/bad.conf"))],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].1.line, Some(4));
+        assert_eq!(
+            errors[0].1.message,
+            "limit_conn_zone zone 'addr' is never used by limit_conn"
+        );
+
+        let errors = check_cross_file(
+            &UnusedLimitZone,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("unused_limit_zone/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_zone_kinds_are_separate() {
+        // A limit_conn naming a limit_req_zone does not use it
+        let errors = check_cross_file(
+            &UnusedLimitZone,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    limit_req_zone $binary_remote_addr zone=one:10m rate=1r/s;\n    limit_conn one 10;\n}\n",
+            )],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].1.line, Some(2));
+    }
+
+    #[test]
+    fn test_join_across_files() {
+        let errors = check_cross_file(
+            &UnusedLimitZone,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    limit_req_zone $binary_remote_addr zone=per_ip:10m rate=10r/s;\n    include conf.d/*.conf;\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    location /api/ {\n        limit_req zone=per_ip burst=20;\n    }\n}\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
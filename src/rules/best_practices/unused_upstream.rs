@@ -0,0 +1,134 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "unused-upstream",
+    category: "best-practices",
+    description: "Detects upstream blocks that are never referenced",
+    severity: "warning",
+    why: r#"An `upstream` block that no `proxy_pass`, `fastcgi_pass`, `grpc_pass`,
+`uwsgi_pass`, `scgi_pass` or `memcached_pass` refers to is dead configuration.
+It usually means a backend was retired without cleaning up, or that a
+reference to it contains a typo and traffic is going somewhere else.
+
+References are collected across every file of the configuration. When a
+target is built from a variable (e.g. `proxy_pass http://$backend;`), any
+upstream could be selected at runtime, so upstreams in that context are not
+reported."#,
+    bad_example: include_str!("unused_upstream/bad.conf"),
+    good_example: include_str!("unused_upstream/good.conf"),
+    references: &["https://nginx.org/en/docs/http/ngx_http_upstream_module.html#upstream"],
+    preset: Some(RulePreset::Strict),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that every upstream block is referenced by some `*_pass` directive
+pub struct UnusedUpstream;
+
+impl CrossFileRule for UnusedUpstream {
+    fn name(&self) -> &'static str {
+        "unused-upstream"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects upstream blocks that are never referenced"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        symbols
+            .upstreams()
+            .iter()
+            .filter(|u| !symbols.is_upstream_referenced(&u.name, u.namespace))
+            .map(|u| {
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!("upstream '{}' is never referenced", u.name),
+                    Severity::Warning,
+                )
+                .with_location(u.line, u.column);
+                (u.path.clone(), error)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_unused_upstream() {
+        let errors = check_cross_file(
+            &UnusedUpstream,
+            &[("nginx.conf", "", include_str!("unused_upstream/bad.conf"))],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1.line, Some(3));
+        assert!(errors[0].1.message.contains("'legacy_backend'"));
+    }
+
+    #[test]
+    fn test_used_upstream() {
+        let errors = check_cross_file(
+            &UnusedUpstream,
+            &[("nginx.conf", "", include_str!("unused_upstream/good.conf"))],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_referenced_from_other_file() {
+        let errors = check_cross_file(
+            &UnusedUpstream,
+            &[
+                (
+                    "conf.d/upstreams.conf",
+                    "",
+                    "upstream api {\n    server 10.0.0.1:8080;\n}\n",
+                ),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    location / {\n        proxy_pass https://api/v1/;\n    }\n}\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_variable_reference_suppresses_report() {
+        let errors = check_cross_file(
+            &UnusedUpstream,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    upstream blue {\n        server 10.0.0.1;\n    }\n    upstream green {\n        server 10.0.0.2;\n    }\n    server {\n        location / {\n            proxy_pass http://$deployment;\n        }\n    }\n}\n",
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_stream_upstream_referenced_by_stream_proxy_pass() {
+        let errors = check_cross_file(
+            &UnusedUpstream,
+            &[(
+                "nginx.conf",
+                "",
+                "stream {\n    upstream db {\n        server 10.0.0.1:5432;\n    }\n    server {\n        listen 5432;\n        proxy_pass db;\n    }\n}\n",
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
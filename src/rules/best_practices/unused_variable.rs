@@ -0,0 +1,144 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable};
+use crate::variables::{VariableSource, is_builtin_variable};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "unused-variable",
+    category: "best-practices",
+    description: "Detects variables created with set that are never used",
+    severity: "warning",
+    why: r#"A `set $name ...;` whose variable is never read anywhere is dead
+configuration. It usually means the directive that was meant to use it was
+removed or refers to a misspelled name.
+
+Uses are collected across every file of the configuration, including
+`ngx.var.<name>` reads inside Lua blocks. Assignments to writable built-in
+variables such as `set $limit_rate 1k;` change nginx behaviour and are not
+reported."#,
+    bad_example: include_str!("unused_variable/bad.conf"),
+    good_example: include_str!("unused_variable/good.conf"),
+    references: &["https://nginx.org/en/docs/http/ngx_http_rewrite_module.html#set"],
+    preset: Some(RulePreset::Strict),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that every variable created with `set` is used somewhere
+pub struct UnusedVariable;
+
+impl CrossFileRule for UnusedVariable {
+    fn name(&self) -> &'static str {
+        "unused-variable"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects variables created with set that are never used"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        let mut errors = Vec::new();
+        for (path, table) in symbols.variable_tables() {
+            for def in table.definitions() {
+                if def.source != VariableSource::Set
+                    || is_builtin_variable(&def.name)
+                    || symbols.is_variable_used(&def.name)
+                {
+                    continue;
+                }
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!("variable '${}' is set but never used", def.name),
+                    Severity::Warning,
+                )
+                .with_location(def.line, def.column);
+                errors.push((path.clone(), error));
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_unused_variable() {
+        let errors = check_cross_file(
+            &UnusedVariable,
+            &[("nginx.conf", "", include_str!("unused_variable/bad.conf"))],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1.line, Some(7));
+        assert!(errors[0].1.message.contains("'$cache_key'"));
+    }
+
+    #[test]
+    fn test_used_variable() {
+        let errors = check_cross_file(
+            &UnusedVariable,
+            &[("nginx.conf", "", include_str!("unused_variable/good.conf"))],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_used_in_other_file() {
+        let errors = check_cross_file(
+            &UnusedVariable,
+            &[
+                ("nginx.conf", "", "http {\n    set $tenant default;\n}\n"),
+                (
+                    "conf.d/app.conf",
+                    "",
+                    "server {\n    access_log /var/log/nginx/$tenant.log;\n}\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_builtin_assignment_and_lua_use_are_ignored() {
+        let errors = check_cross_file(
+            &UnusedVariable,
+            &[(
+                "nginx.conf",
+                "",
+                r#"server {
+    location / {
+        set $limit_rate 100k;
+        set $token "";
+        access_by_lua_block {
+            ngx.var.token = ngx.req.get_headers()["X-Token"]
+        }
+    }
+}
+"#,
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_map_definitions_are_not_reported() {
+        let errors = check_cross_file(
+            &UnusedVariable,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    map $uri $unused_map {\n        default 0;\n    }\n}\n",
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
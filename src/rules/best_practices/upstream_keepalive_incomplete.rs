@@ -0,0 +1,226 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, SymbolTable, UpstreamNamespace};
+use std::path::PathBuf;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "upstream-keepalive-incomplete",
+    category: "best-practices",
+    description: "Detects proxy_pass to a keepalive upstream without HTTP/1.1 and a cleared Connection header",
+    severity: "warning",
+    why: r#"`keepalive` in an `upstream` block only keeps connections open for the
+locations that proxy to it with `proxy_http_version 1.1` and
+`proxy_set_header Connection ""`. With nginx's defaults (HTTP/1.0 and
+`Connection: close`) the backend closes every connection after one request,
+so the keepalive cache stays empty and each request pays for a new TCP (and
+TLS) handshake.
+
+Upstream blocks and the `proxy_pass` directives that use them are joined
+across every file of the configuration. Settings inherited from an including
+`server` or `location` block cannot be seen from an included file and are
+not reported.
+
+Note: Starting with nginx 1.29.7, `proxy_http_version` defaults to `1.1` and
+the Connection header is no longer sent as `close`, so this rule only
+matters for older versions."#,
+    bad_example: include_str!("upstream_keepalive_incomplete/bad.conf"),
+    good_example: include_str!("upstream_keepalive_incomplete/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_upstream_module.html#keepalive",
+        "https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_http_version",
+    ],
+    preset: Some(RulePreset::Recommended),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that `proxy_pass` to an upstream with `keepalive` can reuse its connections
+pub struct UpstreamKeepaliveIncomplete;
+
+impl CrossFileRule for UpstreamKeepaliveIncomplete {
+    fn name(&self) -> &'static str {
+        "upstream-keepalive-incomplete"
+    }
+
+    fn category(&self) -> &'static str {
+        "best-practices"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects proxy_pass to a keepalive upstream without HTTP/1.1 and a cleared Connection header"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        symbols
+            .upstream_references()
+            .iter()
+            .filter(|r| r.namespace == UpstreamNamespace::Http && r.looks_like_upstream_name())
+            .filter(|r| {
+                symbols
+                    .upstreams()
+                    .iter()
+                    .any(|u| u.namespace == r.namespace && u.name == r.host && u.has_keepalive)
+            })
+            .filter_map(|r| {
+                let settings = r.proxy_settings.as_ref()?;
+                let mut missing = Vec::new();
+                if settings.http_version.as_deref() == Some("1.0") {
+                    missing.push("proxy_http_version 1.1");
+                }
+                if settings.sets_connection == Some(false) {
+                    missing.push("proxy_set_header Connection \"\"");
+                }
+                if missing.is_empty() {
+                    return None;
+                }
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "upstream '{}' has keepalive, but this proxy_pass lacks {}; its connections are closed after each request",
+                        r.host,
+                        missing.join(" and ")
+                    ),
+                    Severity::Warning,
+                )
+                .with_location(r.line, r.column);
+                Some((r.path.clone(), error))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_defaults_with_keepalive() {
+        let errors = check_cross_file(
+            &UpstreamKeepaliveIncomplete,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("upstream_keepalive_incomplete/bad.conf"),
+            )],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1.line, Some(12));
+        assert_eq!(
+            errors[0].1.message,
+            "upstream 'app_backend' has keepalive, but this proxy_pass lacks proxy_http_version 1.1 \
+             and proxy_set_header Connection \"\"; its connections are closed after each request"
+        );
+    }
+
+    #[test]
+    fn test_complete_settings() {
+        let errors = check_cross_file(
+            &UpstreamKeepaliveIncomplete,
+            &[(
+                "nginx.conf",
+                "",
+                include_str!("upstream_keepalive_incomplete/good.conf"),
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_inherited_settings() {
+        let errors = check_cross_file(
+            &UpstreamKeepaliveIncomplete,
+            &[(
+                "nginx.conf",
+                "",
+                r#"http {
+    proxy_http_version 1.1;
+    proxy_set_header Connection "";
+    upstream app { server 127.0.0.1:8080; keepalive 8; }
+    server {
+        location / { proxy_pass http://app; }
+        location /api/ {
+            # Replaces the inherited proxy_set_header list
+            proxy_set_header Host $host;
+            proxy_pass http://app;
+        }
+    }
+}
+"#,
+            )],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].1.line, Some(10));
+        assert!(
+            errors[0]
+                .1
+                .message
+                .contains("lacks proxy_set_header Connection")
+        );
+    }
+
+    #[test]
+    fn test_upstream_without_keepalive() {
+        let errors = check_cross_file(
+            &UpstreamKeepaliveIncomplete,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    upstream app { server 127.0.0.1:8080; }\n    server {\n        location / { proxy_pass http://app; }\n    }\n}\n",
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_join_across_files() {
+        let upstreams = (
+            "conf.d/upstreams.conf",
+            "http",
+            "upstream app {\n    server 127.0.0.1:8080;\n    keepalive 16;\n}\n",
+        );
+
+        // Settings from the http block of nginx.conf apply to conf.d/*.conf
+        let errors = check_cross_file(
+            &UpstreamKeepaliveIncomplete,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    proxy_http_version 1.1;\n    include conf.d/*.conf;\n}\n",
+                ),
+                upstreams,
+                (
+                    "conf.d/app.conf",
+                    "http",
+                    "server {\n    location / {\n        proxy_pass http://app;\n    }\n}\n",
+                ),
+            ],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].0, PathBuf::from("conf.d/app.conf"));
+        assert_eq!(errors[0].1.line, Some(3));
+        assert!(
+            errors[0]
+                .1
+                .message
+                .contains("lacks proxy_set_header Connection")
+        );
+
+        // A location snippet may get its settings from the including server
+        let errors = check_cross_file(
+            &UpstreamKeepaliveIncomplete,
+            &[
+                upstreams,
+                (
+                    "snippets/app.conf",
+                    "http,server",
+                    "proxy_pass http://app;\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
@@ -0,0 +1,345 @@
+//! Rules defined in the config (`[[custom_rules]]`)
+//!
+//! A [`CustomRule`] reports the directives matching a
+//! [`CustomRuleConfig`]: a directive name, a regular expression over the
+//! arguments and the blocks the directive may be in, optionally narrowed to
+//! blocks lacking a required directive. Org-specific policies that don't
+//! justify a plugin can be written this way.
+
+use crate::config::CustomRuleConfig;
+use crate::linter::{Fix, LintError, LintRule, Severity};
+use crate::parser::ast::{Config, ConfigItem, Directive};
+use crate::parser::format::IndentStyle;
+use regex::{Captures, Regex};
+use std::path::Path;
+
+/// Category of the diagnostics of custom rules
+pub const CATEGORY: &str = "custom";
+
+/// A compiled `[[custom_rules]]` entry
+#[derive(Debug)]
+pub struct CustomRule {
+    name: &'static str,
+    description: &'static str,
+    directive: String,
+    args: Option<Regex>,
+    context: Vec<String>,
+    require: Option<(String, Option<Regex>)>,
+    message: String,
+    severity: Severity,
+    fix: Option<String>,
+}
+
+impl CustomRule {
+    /// Compile a rule, failing on an invalid regular expression
+    pub fn new(config: &CustomRuleConfig) -> Result<Self, String> {
+        let compile = |field: &str, pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .map_err(|e| format!("invalid regular expression in `{}`: {}", field, e))
+        };
+        let require = match &config.require {
+            Some(pattern) => Some((
+                pattern.directive.clone(),
+                compile("require.args", &pattern.args)?,
+            )),
+            None => None,
+        };
+        Ok(Self {
+            // Rule names are `&'static str`; custom rules are created once
+            // per linter, from a config that lives for the whole run
+            name: Box::leak(config.name.clone().into_boxed_str()),
+            description: Box::leak(config.message.clone().into_boxed_str()),
+            directive: config.directive.clone(),
+            args: compile("args", &config.args)?,
+            context: config.context.clone(),
+            require,
+            message: config.message.clone(),
+            severity: config.severity.map_or(Severity::Warning, Severity::from),
+            fix: config.fix.clone(),
+        })
+    }
+
+    /// Captures of `args` if `directive` matches the rule
+    fn matches<'a>(
+        &self,
+        directive: &Directive,
+        parent: Option<&str>,
+        args: &'a str,
+    ) -> Option<Option<Captures<'a>>> {
+        if directive.name != self.directive {
+            return None;
+        }
+        let parent = parent.unwrap_or("main");
+        if !self.context.is_empty() && !self.context.iter().any(|c| c == parent) {
+            return None;
+        }
+        match &self.args {
+            Some(regex) => regex.captures(args).map(Some),
+            None => Some(None),
+        }
+    }
+
+    /// Whether the block of `directive` contains the required directive
+    fn has_required(&self, directive: &Directive) -> bool {
+        let Some((name, args)) = &self.require else {
+            return false;
+        };
+        let Some(block) = &directive.block else {
+            return false;
+        };
+        block.directives().any(|child| {
+            child.name == *name
+                && args
+                    .as_ref()
+                    .is_none_or(|regex| regex.is_match(&child.words().join(" ")))
+        })
+    }
+}
+
+impl LintRule for CustomRule {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn category(&self) -> &'static str {
+        CATEGORY
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn severity(&self) -> Option<&str> {
+        Some(match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+
+    fn check(&self, config: &Config, _path: &Path) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        for ctx in config.all_directives_with_context() {
+            let directive = ctx.directive;
+            let args = directive.words().join(" ");
+            let Some(captures) = self.matches(directive, ctx.parent(), &args) else {
+                continue;
+            };
+            if self.require.is_some()
+                && (directive.block.as_ref().is_some_and(|b| b.is_raw())
+                    || self.has_required(directive))
+            {
+                continue;
+            }
+
+            let expand = |template: &str| expand(template, directive, &args, captures.as_ref());
+            let mut error =
+                LintError::new(self.name, CATEGORY, &expand(&self.message), self.severity)
+                    .with_location(directive.span.start.line, directive.span.start.column);
+            let fix = self.fix.as_deref().map(expand).and_then(|text| {
+                if self.require.is_some() {
+                    insert_at_block_start(config, directive, &text)
+                } else {
+                    Some(Fix::replace_range(
+                        directive.span.start.offset,
+                        directive.span.end.offset,
+                        &text,
+                    ))
+                }
+            });
+            if let Some(fix) = fix {
+                error = error.with_fix(fix);
+            }
+            errors.push(error);
+        }
+        errors
+    }
+}
+
+/// Replace `{directive}`, `{args}` and the groups of `captures` (`{1}`,
+/// `{name}`) in `template`; other braces are kept as written
+fn expand(
+    template: &str,
+    directive: &Directive,
+    args: &str,
+    captures: Option<&Captures>,
+) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            rest = &rest[open..];
+            break;
+        };
+        let key = &after[..close];
+        let value = match key {
+            "directive" => Some(directive.name.as_str()),
+            "args" => Some(args),
+            _ => captures
+                .and_then(|c| match key.parse::<usize>() {
+                    Ok(index) => c.get(index),
+                    Err(_) => c.name(key),
+                })
+                .map(|m| m.as_str()),
+        };
+        match value {
+            Some(value) => {
+                out.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A fix inserting `text` as the first directive of the block of
+/// `directive`, indented like the block's contents
+fn insert_at_block_start(config: &Config, directive: &Directive, text: &str) -> Option<Fix> {
+    let block = directive.block.as_ref()?;
+    let open = block.span.start;
+    if block.span.end.line == open.line {
+        // One-line block: keep it on one line
+        return Some(Fix::replace_range(
+            open.offset + 1,
+            open.offset + 1,
+            &format!(" {}", text),
+        ));
+    }
+    let unit = IndentStyle::detect(&config.items)
+        .unwrap_or_default()
+        .unit();
+    let indent = block
+        .directives()
+        .find(|d| d.span.start.line != open.line)
+        .map(Directive::line_indent)
+        .unwrap_or_else(|| format!("{}{}", directive.line_indent(), unit));
+    // After the line of `{`, including a comment following it
+    let end_of_open_line = block
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ConfigItem::Directive(d) if d.span.start.line == open.line => Some(d.span.end.offset),
+            ConfigItem::Comment(c) if c.span.start.line == open.line => Some(c.span.end.offset),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(open.offset + 1 + directive.trailing_whitespace.len());
+    Some(Fix::replace_range(
+        end_of_open_line,
+        end_of_open_line,
+        &format!("\n{}{}", indent, text),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LintConfig;
+    use crate::parser::parse_string;
+    use nginx_lint_common::apply_fixes;
+
+    fn rule(toml: &str) -> CustomRule {
+        let config = LintConfig::parse(&format!("[[custom_rules]]\n{}", toml)).unwrap();
+        CustomRule::new(&config.custom_rules()[0]).unwrap()
+    }
+
+    fn check(rule: &CustomRule, source: &str) -> Vec<LintError> {
+        rule.check(&parse_string(source).unwrap(), Path::new("test.conf"))
+    }
+
+    fn fixed(rule: &CustomRule, source: &str) -> String {
+        let fixes: Vec<Fix> = check(rule, source)
+            .into_iter()
+            .flat_map(|e| e.fixes)
+            .collect();
+        apply_fixes(source, &fixes).unwrap()
+    }
+
+    #[test]
+    fn test_required_directive() {
+        let rule = rule(
+            r#"name = "internal-location-allow"
+directive = "location"
+args = "^/internal(?<rest>/.*)?$"
+context = ["server"]
+require = { directive = "allow", args = "^10\\.0\\.0\\.0/8$" }
+message = "location {args} must allow 10.0.0.0/8 ({rest})"
+severity = "error"
+fix = "allow 10.0.0.0/8;"
+"#,
+        );
+        let source = "server {\n    location /internal/api {\n        deny all;\n    }\n    location /internal/ok {\n        allow 10.0.0.0/8;\n    }\n    location /public {\n    }\n}\n";
+        let errors = check(&rule, source);
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].rule, "internal-location-allow");
+        assert_eq!(errors[0].category, CATEGORY);
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert_eq!(errors[0].line, Some(2));
+        assert_eq!(
+            errors[0].message,
+            "location /internal/api must allow 10.0.0.0/8 (/api)"
+        );
+        assert_eq!(
+            fixed(&rule, source),
+            source.replace(
+                "/internal/api {\n",
+                "/internal/api {\n        allow 10.0.0.0/8;\n"
+            )
+        );
+
+        // Outside the context
+        assert!(check(&rule, "http {\n    location /internal {\n    }\n}\n").is_empty());
+        // One-line block
+        assert_eq!(
+            fixed(&rule, "server {\n    location /internal { deny all; }\n}\n"),
+            "server {\n    location /internal { allow 10.0.0.0/8; deny all; }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_forbidden_directive() {
+        let rule = rule(
+            r#"name = "no-proxy-buffering-off"
+directive = "proxy_buffering"
+args = "^off$"
+message = "{directive} {args} is not allowed, nor {unknown}"
+fix = "{directive} on;"
+"#,
+        );
+        let source = "http {\n    proxy_buffering off;\n    server {\n        proxy_buffering on;\n    }\n}\n";
+        let errors = check(&rule, source);
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].severity, Severity::Warning);
+        assert_eq!(
+            errors[0].message,
+            "proxy_buffering off is not allowed, nor {unknown}"
+        );
+        assert_eq!(
+            fixed(&rule, source),
+            source.replace("proxy_buffering off;", "proxy_buffering on;")
+        );
+    }
+
+    #[test]
+    fn test_top_level_context_and_invalid_regex() {
+        let rule =
+            rule("name = \"x\"\ndirective = \"user\"\ncontext = [\"main\"]\nmessage = \"m\"\n");
+        assert_eq!(check(&rule, "user nginx;\n").len(), 1);
+
+        let config = LintConfig::parse(
+            "[[custom_rules]]\nname = \"x\"\ndirective = \"d\"\nargs = \"(\"\nmessage = \"m\"\n",
+        )
+        .unwrap();
+        let err = CustomRule::new(&config.custom_rules()[0]).unwrap_err();
+        assert!(err.contains("`args`"), "got: {}", err);
+    }
+}
@@ -0,0 +1,8 @@
+// Interop rules are WASM plugins; see plugins/builtin/interop/.
+// The rules here need the filesystem and back `--check-paths` for the
+// njs-module-reference plugin.
+#[cfg(feature = "cli")]
+pub mod njs_module_paths;
+
+#[cfg(feature = "cli")]
+pub use njs_module_paths::NjsModulePaths;
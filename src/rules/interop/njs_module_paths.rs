@@ -0,0 +1,220 @@
+use crate::config_source::{ConfigSource, FileStatus, RealFileSystem};
+use crate::linter::{LintError, LintRule, Severity};
+use crate::parser::ast::Config;
+use nginx_lint_common::njs::{FunctionRef, JsImport, exported_names, module_candidates};
+use nginx_lint_common::paths::base_dir;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Check that `js_import` module files exist and export the handlers the
+/// configuration names (`--check-paths`)
+///
+/// Runs under the name of the `njs-module-reference` plugin, which cannot
+/// access the filesystem itself.
+pub struct NjsModulePaths {
+    prefix: Option<PathBuf>,
+    source: Arc<dyn ConfigSource>,
+}
+
+impl Default for NjsModulePaths {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NjsModulePaths {
+    pub fn new() -> Self {
+        Self::with_prefix(None)
+    }
+
+    /// Resolve `js_path` and module paths against `prefix` instead of the
+    /// directory of the linted file
+    pub fn with_prefix(prefix: Option<PathBuf>) -> Self {
+        Self {
+            prefix,
+            source: Arc::new(RealFileSystem),
+        }
+    }
+
+    /// Look the files up in `source` instead of on disk
+    pub fn with_source(mut self, source: Arc<dyn ConfigSource>) -> Self {
+        self.source = source;
+        self
+    }
+
+    fn error(&self, message: &str) -> LintError {
+        LintError::new(self.name(), self.category(), message, Severity::Error)
+    }
+}
+
+/// What a module imported in the file exports, if it could be read
+struct Module {
+    file: String,
+    exports: Option<Vec<String>>,
+}
+
+impl LintRule for NjsModulePaths {
+    fn name(&self) -> &'static str {
+        "njs-module-reference"
+    }
+
+    fn category(&self) -> &'static str {
+        "interop"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects js_import modules that do not exist or do not export the handlers used"
+    }
+
+    fn check(&self, config: &Config, path: &Path) -> Vec<LintError> {
+        let base = base_dir(self.prefix.as_deref(), path);
+        let js_paths: Vec<&str> = config
+            .all_directives()
+            .filter(|d| d.is("js_path"))
+            .filter_map(|d| d.first_arg())
+            .collect();
+
+        let mut errors = Vec::new();
+        // Import name -> module; `None` when imported from several files
+        let mut modules: HashMap<String, Option<Module>> = HashMap::new();
+        for directive in config.all_directives() {
+            let Some(import) = JsImport::parse(directive) else {
+                continue;
+            };
+            let found = module_candidates(&import.module, &js_paths)
+                .into_iter()
+                .map(|candidate| base.join(candidate))
+                .find(|candidate| {
+                    !matches!(
+                        self.source.status(candidate),
+                        FileStatus::Missing | FileStatus::NotAFile
+                    )
+                });
+            let Some(found) = found else {
+                errors.push(
+                    self.error(&format!(
+                        "js_import module '{}' does not exist{}",
+                        import.module,
+                        if js_paths.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" (also looked in js_path {})", js_paths.join(", "))
+                        }
+                    ))
+                    .with_location(directive.span.start.line, directive.span.start.column),
+                );
+                modules.insert(import.name, None);
+                continue;
+            };
+            let module = Module {
+                file: import.module,
+                exports: self
+                    .source
+                    .read_file(&found)
+                    .ok()
+                    .and_then(|source| exported_names(&source)),
+            };
+            modules
+                .entry(import.name)
+                .and_modify(|known| {
+                    if known.as_ref().is_some_and(|m| m.file != module.file) {
+                        *known = None;
+                    }
+                })
+                .or_insert(Some(module));
+        }
+
+        for handler in config.all_directives().filter_map(FunctionRef::parse) {
+            let Some(name) = handler.module else {
+                continue;
+            };
+            let Some(Some(Module {
+                file,
+                exports: Some(exports),
+            })) = modules.get(name)
+            else {
+                continue;
+            };
+            let export = handler.function.split('.').next().unwrap_or_default();
+            if !exports.iter().any(|e| e == export) {
+                let directive = handler.directive;
+                errors.push(
+                    self.error(&format!(
+                        "{} refers to '{}.{}', but {} does not export '{}'",
+                        directive.name, name, handler.function, file, export
+                    ))
+                    .with_location(directive.span.start.line, directive.span.start.column),
+                );
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn check(dir: &Path, source: &str) -> Vec<LintError> {
+        let config = crate::parser::parse_string(source).unwrap();
+        NjsModulePaths::new().check(&config, &dir.join("nginx.conf"))
+    }
+
+    #[test]
+    fn test_missing_module() {
+        let temp = TempDir::new().unwrap();
+        let errors = check(temp.path(), "http {\n    js_import main from http.js;\n}\n");
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(2));
+        assert_eq!(
+            errors[0].message,
+            "js_import module 'http.js' does not exist"
+        );
+        assert_eq!(errors[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_js_path_and_exports() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join("njs")).unwrap();
+        fs::write(
+            temp.path().join("njs/http.js"),
+            "function hello(r) { r.return(200, 'hi'); }\nexport default { hello };\n",
+        )
+        .unwrap();
+
+        let errors = check(
+            temp.path(),
+            "http {\n    js_path njs;\n    js_import main from http.js;\n    js_import auth.js;\n    js_set $user main.user;\n    server {\n        location / {\n            js_content main.hello;\n        }\n    }\n}\n",
+        );
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "js_import module 'auth.js' does not exist (also looked in js_path njs)",
+                "js_set refers to 'main.user', but http.js does not export 'user'",
+            ]
+        );
+        assert_eq!(errors[1].line, Some(5));
+    }
+
+    #[test]
+    fn test_prefix_and_unknown_exports() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("handlers.js"),
+            "const handlers = { hello };\nexport default handlers;\n",
+        )
+        .unwrap();
+
+        let config =
+            crate::parser::parse_string("js_import handlers.js;\njs_content handlers.anything;\n")
+                .unwrap();
+        let errors = NjsModulePaths::with_prefix(Some(temp.path().to_path_buf()))
+            .check(&config, Path::new("/nonexistent/nginx.conf"));
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
@@ -1,9 +1,27 @@
 pub mod best_practices;
+pub mod custom;
+pub mod interop;
 pub mod security;
 pub mod style;
 pub mod syntax;
 
+pub use best_practices::{
+    AccessLogMisconfigured, GeneratedRegionModified, HttpsRedirectMisconfigured, MissingResolver,
+    MissingServerName, ProxyCacheIncomplete, ProxyPassUpstreamMismatch, UndefinedLimitZone,
+    UndefinedUpstream, UndefinedVariable, UnusedLimitZone, UnusedUpstream, UnusedVariable,
+    UpstreamKeepaliveIncomplete,
+};
+#[cfg(feature = "cli")]
+pub use best_practices::{IncludeLayering, LuaBlockLint, PrecompressedAssetsNotServed};
+pub use custom::CustomRule;
+#[cfg(feature = "cli")]
+pub use interop::NjsModulePaths;
+#[cfg(feature = "cli")]
+pub use security::{AuthBasicUserFileMissing, RegoPolicy, SslCertificatePaths, SslDhparamSize};
 pub use style::Indent;
+pub use syntax::{
+    DuplicateDefaultServer, InvalidDirectiveContext, MissingSemicolon, UnclosedQuote,
+    UnmatchedBraces,
+};
 #[cfg(feature = "cli")]
-pub use syntax::IncludePathExists;
-pub use syntax::{InvalidDirectiveContext, MissingSemicolon, UnclosedQuote, UnmatchedBraces};
+pub use syntax::{IncludeCycle, IncludePathExists};
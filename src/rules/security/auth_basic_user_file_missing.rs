@@ -0,0 +1,137 @@
+use crate::config_source::{ConfigSource, RealFileSystem};
+use crate::linter::{LintError, LintRule, Severity};
+use crate::parser::ast::Config;
+use nginx_lint_common::paths::resolve_file;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Check that the password files of `auth_basic_user_file` exist and are
+/// readable
+///
+/// nginx opens the file on each request, so a missing one starts fine and
+/// then answers every request of the location with 500.
+pub struct AuthBasicUserFileMissing {
+    prefix: Option<PathBuf>,
+    source: Arc<dyn ConfigSource>,
+}
+
+impl Default for AuthBasicUserFileMissing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuthBasicUserFileMissing {
+    pub fn new() -> Self {
+        Self::with_prefix(None)
+    }
+
+    /// Resolve relative paths against `prefix` instead of the directory of
+    /// the linted file
+    pub fn with_prefix(prefix: Option<PathBuf>) -> Self {
+        Self {
+            prefix,
+            source: Arc::new(RealFileSystem),
+        }
+    }
+
+    /// Look the files up in `source` instead of on disk
+    pub fn with_source(mut self, source: Arc<dyn ConfigSource>) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl LintRule for AuthBasicUserFileMissing {
+    fn name(&self) -> &'static str {
+        "auth-basic-user-file-missing"
+    }
+
+    fn category(&self) -> &'static str {
+        "security"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects auth_basic_user_file files that do not exist or cannot be read"
+    }
+
+    fn check(&self, config: &Config, path: &Path) -> Vec<LintError> {
+        config
+            .all_directives()
+            .filter(|d| d.is("auth_basic_user_file"))
+            .filter_map(|directive| {
+                let (file, resolved) = resolve_file(self.prefix.as_deref(), path, directive)?;
+                let problem = self.source.status(&resolved).problem()?;
+                Some(
+                    LintError::new(
+                        self.name(),
+                        self.category(),
+                        &format!("auth_basic_user_file '{}' {}", file, problem),
+                        Severity::Error,
+                    )
+                    .with_location(directive.span.start.line, directive.span.start.column),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_file() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("htpasswd"), "user:{PLAIN}secret\n").unwrap();
+
+        let config = crate::parser::parse_string(
+            "location /admin/ {\n    auth_basic \"Admin\";\n    auth_basic_user_file htpasswd;\n}\nlocation /ops/ {\n    auth_basic_user_file conf.d/ops.htpasswd;\n}\nlocation /$x/ {\n    auth_basic_user_file $realm.htpasswd;\n}\n",
+        )
+        .unwrap();
+        let errors =
+            AuthBasicUserFileMissing::new().check(&config, &temp.path().join("nginx.conf"));
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "auth_basic_user_file 'conf.d/ops.htpasswd' does not exist"
+        );
+        assert_eq!((errors[0].line, errors[0].column), (Some(6), Some(5)));
+    }
+
+    #[test]
+    fn test_prefix() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join("conf.d")).unwrap();
+        fs::write(temp.path().join("conf.d/htpasswd"), "").unwrap();
+
+        let config =
+            crate::parser::parse_string("auth_basic_user_file conf.d/htpasswd;\n").unwrap();
+        let sites = temp.path().join("sites-enabled/app.conf");
+        assert_eq!(
+            AuthBasicUserFileMissing::new().check(&config, &sites).len(),
+            1
+        );
+        let errors = AuthBasicUserFileMissing::with_prefix(Some(temp.path().to_path_buf()))
+            .check(&config, &sites);
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_unreadable_file() {
+        use crate::config_source::MemoryFileSystem;
+
+        let source = MemoryFileSystem::new().with_unreadable_file("/etc/nginx/htpasswd");
+        let config = crate::parser::parse_string("auth_basic_user_file htpasswd;\n").unwrap();
+        let errors = AuthBasicUserFileMissing::new()
+            .with_source(Arc::new(source))
+            .check(&config, Path::new("/etc/nginx/nginx.conf"));
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "auth_basic_user_file 'htpasswd' is not readable"
+        );
+    }
+}
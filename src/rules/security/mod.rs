@@ -1,2 +1,22 @@
-// Security rules are now WASM plugins only
-// See plugins/builtin/security/ for implementations
+// Security rules are WASM plugins; see plugins/builtin/security/.
+// The rules here need the filesystem and back `--check-paths`: for the
+// ssl-certificate-missing and weak-ssl-key-exchange plugins, and
+// auth-basic-user-file-missing, which `--check-paths` turns on. rego-policy
+// runs opa on organisation policies.
+#[cfg(feature = "cli")]
+pub mod auth_basic_user_file_missing;
+#[cfg(feature = "cli")]
+pub mod rego_policy;
+#[cfg(feature = "cli")]
+pub mod ssl_certificate_paths;
+#[cfg(feature = "cli")]
+pub mod ssl_dhparam_size;
+
+#[cfg(feature = "cli")]
+pub use auth_basic_user_file_missing::AuthBasicUserFileMissing;
+#[cfg(feature = "cli")]
+pub use rego_policy::RegoPolicy;
+#[cfg(feature = "cli")]
+pub use ssl_certificate_paths::SslCertificatePaths;
+#[cfg(feature = "cli")]
+pub use ssl_dhparam_size::SslDhparamSize;
@@ -0,0 +1,265 @@
+use crate::linter::{LintError, LintRule, Severity, warn_option_problem};
+use crate::parser::ast::Config;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Command run when `command` is not configured; the options, policies and
+/// query are appended
+pub const DEFAULT_COMMAND: &[&str] = &["opa", "eval"];
+
+/// Query evaluated when `query` is not configured
+pub const DEFAULT_QUERY: &str = "data.nginx.deny";
+
+/// Evaluate Rego policies against each file with an external `opa eval`
+///
+/// The policies get `{"path": ..., "config": ...}` as input, `config` being
+/// the file's AST serialized to JSON, and every value of the query (a
+/// message, or an object with `msg` and optionally `line`, `column` and
+/// `severity`) is reported.
+pub struct RegoPolicy {
+    command: Vec<String>,
+    policies: Vec<String>,
+    query: String,
+}
+
+impl RegoPolicy {
+    /// Evaluate [`DEFAULT_QUERY`] over `policies` (files or directories)
+    /// with [`DEFAULT_COMMAND`]
+    pub fn new(policies: Vec<String>) -> Self {
+        Self {
+            command: DEFAULT_COMMAND.iter().map(|s| s.to_string()).collect(),
+            policies,
+            query: DEFAULT_QUERY.to_string(),
+        }
+    }
+
+    /// Run `command` (program and arguments, e.g. `["opa", "eval"]`) instead
+    pub fn with_command(mut self, command: Vec<String>) -> Self {
+        self.command = command;
+        self
+    }
+
+    /// Evaluate `query` instead of [`DEFAULT_QUERY`]
+    pub fn with_query(mut self, query: String) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Run the command with `input` on stdin and return what it printed on
+    /// stdout
+    fn run(&self, input: &str) -> Result<String, String> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or_else(|| "command is empty".to_string())?;
+        let mut child = Command::new(program)
+            .args(args)
+            .args(["--format", "json", "--stdin-input"])
+            .args(self.policies.iter().flat_map(|p| ["--data", p.as_str()]))
+            .arg(&self.query)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("cannot run '{}': {}", program, e))?;
+
+        // Write from another thread, as lua-block-lint does, so a large input
+        // cannot deadlock against a full stdout pipe
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let output = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                let _ = stdin.write_all(input.as_bytes());
+            });
+            child.wait_with_output()
+        })
+        .map_err(|e| format!("'{}' failed: {}", program, e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "'{}' failed ({}): {}",
+                program,
+                output.status,
+                stderr.trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// A denial returned by the query
+#[derive(Debug, PartialEq)]
+struct Denial {
+    message: String,
+    line: Option<usize>,
+    column: Option<usize>,
+    severity: Severity,
+}
+
+/// Denials in the output of `opa eval --format json`: the values of the
+/// first expression of the first result, which is undefined (no result)
+/// when nothing is denied
+fn parse_output(stdout: &str) -> Result<Vec<Denial>, String> {
+    let output: Value =
+        serde_json::from_str(stdout).map_err(|e| format!("unexpected output: {}", e))?;
+    let value = &output["result"][0]["expressions"][0]["value"];
+    let values = match value {
+        Value::Null | Value::Bool(false) => return Ok(Vec::new()),
+        Value::Array(values) => values.iter().collect(),
+        value => vec![value],
+    };
+    values.into_iter().map(denial).collect()
+}
+
+fn denial(value: &Value) -> Result<Denial, String> {
+    let position = |key: &str| value[key].as_u64().map(|n| n as usize);
+    match value {
+        Value::String(message) => Ok(Denial {
+            message: message.clone(),
+            line: None,
+            column: None,
+            severity: Severity::Error,
+        }),
+        Value::Object(_) => Ok(Denial {
+            message: value["msg"]
+                .as_str()
+                .ok_or_else(|| format!("denial without a `msg`: {}", value))?
+                .to_string(),
+            line: position("line"),
+            column: position("column"),
+            severity: match value["severity"].as_str() {
+                Some("warning") => Severity::Warning,
+                _ => Severity::Error,
+            },
+        }),
+        value => Err(format!(
+            "denial is neither a message nor an object: {}",
+            value
+        )),
+    }
+}
+
+impl LintRule for RegoPolicy {
+    fn name(&self) -> &'static str {
+        "rego-policy"
+    }
+
+    fn category(&self) -> &'static str {
+        "security"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reports the denials of Rego policies evaluated with opa against each file"
+    }
+
+    fn check(&self, config: &Config, path: &Path) -> Vec<LintError> {
+        if self.policies.is_empty() {
+            warn_option_problem(self.name(), "no policies configured");
+            return Vec::new();
+        }
+        let input = serde_json::json!({
+            "path": path.to_string_lossy(),
+            "config": config,
+        });
+        let denials = self
+            .run(&input.to_string())
+            .and_then(|stdout| parse_output(&stdout));
+        let denials = match denials {
+            Ok(denials) => denials,
+            Err(problem) => {
+                warn_option_problem(self.name(), &problem);
+                return Vec::new();
+            }
+        };
+        denials
+            .into_iter()
+            .map(|denial| {
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &denial.message,
+                    denial.severity,
+                );
+                match denial.line {
+                    Some(line) => error.with_location(line, denial.column.unwrap_or(1)),
+                    None => error,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(script: &str, source: &str) -> Vec<LintError> {
+        let config = crate::parser::parse_string(source).unwrap();
+        RegoPolicy::new(vec!["policy.rego".to_string()])
+            .with_command(["sh", "-c", script, "sh"].map(String::from).to_vec())
+            .check(&config, Path::new("nginx.conf"))
+    }
+
+    #[test]
+    fn test_parse_output() {
+        assert_eq!(parse_output("{}").unwrap(), []);
+        let denials = parse_output(
+            r#"{"result":[{"expressions":[{"value":["plain",{"msg":"located","line":3,"column":5,"severity":"warning"}],"text":"data.nginx.deny"}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            denials,
+            [
+                Denial {
+                    message: "plain".to_string(),
+                    line: None,
+                    column: None,
+                    severity: Severity::Error,
+                },
+                Denial {
+                    message: "located".to_string(),
+                    line: Some(3),
+                    column: Some(5),
+                    severity: Severity::Warning,
+                },
+            ]
+        );
+        assert!(parse_output(r#"{"result":[{"expressions":[{"value":[{"line":1}]}]}]}"#).is_err());
+        assert!(parse_output("not json").is_err());
+    }
+
+    /// The command gets the options, policies and query as arguments and
+    /// the AST on stdin
+    #[cfg(unix)]
+    #[test]
+    fn test_input_and_arguments() {
+        let errors = check(
+            r#"[ "$*" = "--format json --stdin-input --data policy.rego data.nginx.deny" ] || exit 1
+input=$(cat)
+case "$input" in *'"Directive":'*'"name":"autoindex"'*'"path":"nginx.conf"'*) ;; *) exit 1 ;; esac
+echo '{"result":[{"expressions":[{"value":[{"msg":"autoindex is on","line":2,"column":5}]}]}]}'"#,
+            "http {\n    autoindex on;\n}\n",
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].rule, "rego-policy");
+        assert_eq!(errors[0].message, "autoindex is on");
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert_eq!((errors[0].line, errors[0].column), (Some(2), Some(5)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failures_report_nothing() {
+        let source = "http {\n}\n";
+        assert!(check("cat >/dev/null; echo oops >&2; exit 1", source).is_empty());
+        assert!(check("cat >/dev/null; echo oops", source).is_empty());
+        let config = crate::parser::parse_string(source).unwrap();
+        assert!(
+            RegoPolicy::new(Vec::new())
+                .check(&config, Path::new("nginx.conf"))
+                .is_empty()
+        );
+    }
+}
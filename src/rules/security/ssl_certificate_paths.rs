@@ -0,0 +1,175 @@
+use crate::config_source::{ConfigSource, RealFileSystem};
+use crate::linter::{LintError, LintRule, Severity};
+use crate::parser::ast::Config;
+use nginx_lint_common::paths::resolve_file;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Directives whose argument is a certificate or key file nginx loads at
+/// startup
+const FILE_DIRECTIVES: &[&str] = &[
+    "ssl_certificate",
+    "ssl_certificate_key",
+    "ssl_trusted_certificate",
+    "ssl_client_certificate",
+    "proxy_ssl_certificate",
+    "proxy_ssl_certificate_key",
+    "proxy_ssl_trusted_certificate",
+];
+
+/// Check that the certificate and key files of `ssl_certificate`,
+/// `proxy_ssl_trusted_certificate` and the like exist and are readable
+/// (`--check-paths`)
+///
+/// Runs under the name of the `ssl-certificate-missing` plugin, which cannot
+/// access the filesystem itself, so ignore comments and `--rule-only` cover
+/// both checks.
+pub struct SslCertificatePaths {
+    prefix: Option<PathBuf>,
+    source: Arc<dyn ConfigSource>,
+}
+
+impl Default for SslCertificatePaths {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SslCertificatePaths {
+    pub fn new() -> Self {
+        Self::with_prefix(None)
+    }
+
+    /// Resolve relative paths against `prefix` instead of the directory of
+    /// the linted file
+    pub fn with_prefix(prefix: Option<PathBuf>) -> Self {
+        Self {
+            prefix,
+            source: Arc::new(RealFileSystem),
+        }
+    }
+
+    /// Look the files up in `source` instead of on disk
+    pub fn with_source(mut self, source: Arc<dyn ConfigSource>) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl LintRule for SslCertificatePaths {
+    fn name(&self) -> &'static str {
+        "ssl-certificate-missing"
+    }
+
+    fn category(&self) -> &'static str {
+        "security"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects certificate and key files that do not exist or cannot be read"
+    }
+
+    fn check(&self, config: &Config, path: &Path) -> Vec<LintError> {
+        config
+            .all_directives()
+            .filter(|d| FILE_DIRECTIVES.contains(&d.name.as_str()))
+            .filter_map(|directive| {
+                let (file, resolved) = resolve_file(self.prefix.as_deref(), path, directive)?;
+                let problem = self.source.status(&resolved).problem()?;
+                Some(
+                    LintError::new(
+                        self.name(),
+                        self.category(),
+                        &format!("{} file '{}' {}", directive.name, file, problem),
+                        Severity::Error,
+                    )
+                    .with_location(directive.span.start.line, directive.span.start.column),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn check(dir: &Path, source: &str) -> Vec<LintError> {
+        let config = crate::parser::parse_string(source).unwrap();
+        SslCertificatePaths::new().check(&config, &dir.join("nginx.conf"))
+    }
+
+    #[test]
+    fn test_missing_files() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join("ssl")).unwrap();
+        fs::write(temp.path().join("ssl/cert.pem"), "").unwrap();
+
+        let errors = check(
+            temp.path(),
+            "http {\n    ssl_certificate ssl/cert.pem;\n    ssl_certificate_key ssl/key.pem;\n}\n",
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(3));
+        assert_eq!(
+            errors[0].message,
+            "ssl_certificate_key file 'ssl/key.pem' does not exist"
+        );
+        assert_eq!(errors[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_absolute_path_and_prefix() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("cert.pem"), "").unwrap();
+
+        let source = format!(
+            "ssl_certificate {}/cert.pem;\nssl_certificate_key key.pem;\n",
+            temp.path().display()
+        );
+        let config = crate::parser::parse_string(&source).unwrap();
+        let errors = SslCertificatePaths::with_prefix(Some(temp.path().to_path_buf()))
+            .check(&config, Path::new("/nonexistent/nginx.conf"));
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].message.contains("'key.pem'"));
+    }
+
+    #[test]
+    fn test_unreadable_files() {
+        use crate::config_source::MemoryFileSystem;
+
+        let source = MemoryFileSystem::new()
+            .with_file("/etc/nginx/ssl/cert.pem", "")
+            .with_unreadable_file("/etc/nginx/ssl/key.pem");
+        let config = crate::parser::parse_string(
+            "ssl_certificate ssl/cert.pem;\nssl_certificate_key ssl/key.pem;\nlocation / {\n    proxy_ssl_trusted_certificate ssl;\n    proxy_ssl_certificate_key ssl/client.key;\n}\n",
+        )
+        .unwrap();
+        let errors = SslCertificatePaths::new()
+            .with_source(Arc::new(source))
+            .check(&config, Path::new("/etc/nginx/nginx.conf"));
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "ssl_certificate_key file 'ssl/key.pem' is not readable",
+                "proxy_ssl_trusted_certificate file 'ssl' is not a regular file",
+                "proxy_ssl_certificate_key file 'ssl/client.key' does not exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variables_and_data_values_are_skipped() {
+        let temp = TempDir::new().unwrap();
+        let errors = check(
+            temp.path(),
+            "ssl_certificate /etc/ssl/$ssl_server_name.crt;\nssl_certificate data:$cert;\nssl_certificate_key engine:pkcs11:id;\n",
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+}
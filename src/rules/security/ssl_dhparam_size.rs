@@ -0,0 +1,267 @@
+use crate::config_source::{ConfigSource, RealFileSystem};
+use crate::linter::{LintError, LintRule, Severity};
+use crate::parser::ast::Config;
+use nginx_lint_common::paths::resolve_file;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Smallest DH group size considered safe
+const MIN_DH_BITS: usize = 2048;
+
+/// Check that the `ssl_dhparam` file holds parameters of at least 2048 bits
+/// (`--check-paths`)
+///
+/// Runs under the name of the `weak-ssl-key-exchange` plugin, which cannot
+/// access the filesystem itself.
+pub struct SslDhparamSize {
+    prefix: Option<PathBuf>,
+    source: Arc<dyn ConfigSource>,
+}
+
+impl Default for SslDhparamSize {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SslDhparamSize {
+    pub fn new() -> Self {
+        Self::with_prefix(None)
+    }
+
+    /// Resolve relative paths against `prefix` instead of the directory of
+    /// the linted file
+    pub fn with_prefix(prefix: Option<PathBuf>) -> Self {
+        Self {
+            prefix,
+            source: Arc::new(RealFileSystem),
+        }
+    }
+
+    /// Look the files up in `source` instead of on disk
+    pub fn with_source(mut self, source: Arc<dyn ConfigSource>) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl LintRule for SslDhparamSize {
+    fn name(&self) -> &'static str {
+        "weak-ssl-key-exchange"
+    }
+
+    fn category(&self) -> &'static str {
+        "security"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects ssl_dhparam files that are missing or smaller than 2048 bits"
+    }
+
+    fn check(&self, config: &Config, path: &Path) -> Vec<LintError> {
+        let mut errors = Vec::new();
+
+        for directive in config.all_directives().filter(|d| d.is("ssl_dhparam")) {
+            let Some((file, resolved)) = resolve_file(self.prefix.as_deref(), path, directive)
+            else {
+                continue;
+            };
+
+            let (message, severity) = if !self.source.exists(&resolved) {
+                (
+                    format!("ssl_dhparam file '{}' does not exist", file),
+                    Severity::Error,
+                )
+            } else {
+                // Files that are not PEM DH parameters are left to nginx
+                let Some(bits) = self
+                    .source
+                    .read_file(&resolved)
+                    .ok()
+                    .and_then(|pem| dh_prime_bits(&pem))
+                else {
+                    continue;
+                };
+                if bits >= MIN_DH_BITS {
+                    continue;
+                }
+                (
+                    format!(
+                        "ssl_dhparam file '{}' has {}-bit parameters; use at least {} bits \
+                         (e.g. 'openssl dhparam -out {} {}')",
+                        file, bits, MIN_DH_BITS, file, MIN_DH_BITS
+                    ),
+                    Severity::Warning,
+                )
+            };
+
+            errors.push(
+                LintError::new(self.name(), self.category(), &message, severity)
+                    .with_location(directive.span.start.line, directive.span.start.column),
+            );
+        }
+
+        errors
+    }
+}
+
+/// Size in bits of the prime `p` of the first DH parameters block in `pem`
+///
+/// Both `DH PARAMETERS` (PKCS#3) and `X9.42 DH PARAMETERS` start with
+/// `SEQUENCE { INTEGER p, ... }`.
+fn dh_prime_bits(pem: &str) -> Option<usize> {
+    let body = pem.split("-----BEGIN ").skip(1).find(|block| {
+        block.starts_with("DH PARAMETERS-----") || block.starts_with("X9.42 DH PARAMETERS-----")
+    })?;
+    let body = body.split_once("-----")?.1;
+    let body = body.split("-----END ").next()?;
+    let der = decode_base64(body)?;
+
+    let (tag, sequence) = read_der(&der)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (tag, prime) = read_der(sequence)?;
+    if tag != 0x02 {
+        return None;
+    }
+
+    let first = prime.iter().position(|&b| b != 0)?;
+    Some((prime.len() - first) * 8 - prime[first].leading_zeros() as usize)
+}
+
+/// Tag and content of the DER element at the start of `data`
+fn read_der(data: &[u8]) -> Option<(u8, &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (&len, rest) = rest.split_first()?;
+    let (len, rest) = if len < 0x80 {
+        (len as usize, rest)
+    } else {
+        let count = (len & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let (bytes, rest) = rest.split_at(count);
+        (bytes.iter().fold(0, |n, &b| (n << 8) | b as usize), rest)
+    };
+    rest.get(..len).map(|content| (tag, content))
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// PEM DH parameters with a `bits`-bit prime (generator 2)
+    fn dhparam_pem(bits: usize) -> String {
+        let mut prime = vec![0xff; bits / 8];
+        // The high bit is set, so DER needs a leading zero byte
+        prime.insert(0, 0);
+        let mut integer = vec![0x02, 0x82, (prime.len() >> 8) as u8, prime.len() as u8];
+        integer.extend(&prime);
+        integer.extend([0x02, 0x01, 0x02]);
+        let mut der = vec![0x30, 0x82, (integer.len() >> 8) as u8, integer.len() as u8];
+        der.extend(integer);
+
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut encoded = String::new();
+        for chunk in der.chunks(3) {
+            let n = chunk.iter().fold(0u32, |n, &b| (n << 8) | b as u32) << (8 * (3 - chunk.len()));
+            for i in 0..4 {
+                encoded.push(if i <= chunk.len() {
+                    ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f] as char
+                } else {
+                    '='
+                });
+            }
+        }
+
+        let lines: Vec<&str> = encoded
+            .as_bytes()
+            .chunks(64)
+            .map(|line| std::str::from_utf8(line).unwrap())
+            .collect();
+        format!(
+            "-----BEGIN DH PARAMETERS-----\n{}\n-----END DH PARAMETERS-----\n",
+            lines.join("\n")
+        )
+    }
+
+    fn check(dir: &Path, source: &str) -> Vec<LintError> {
+        let config = crate::parser::parse_string(source).unwrap();
+        SslDhparamSize::new().check(&config, &dir.join("nginx.conf"))
+    }
+
+    #[test]
+    fn test_dh_prime_bits() {
+        assert_eq!(dh_prime_bits(&dhparam_pem(1024)), Some(1024));
+        assert_eq!(dh_prime_bits(&dhparam_pem(2048)), Some(2048));
+        assert_eq!(dh_prime_bits("not a pem"), None);
+    }
+
+    #[test]
+    fn test_small_dhparam() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("dh1024.pem"), dhparam_pem(1024)).unwrap();
+        fs::write(temp.path().join("dh2048.pem"), dhparam_pem(2048)).unwrap();
+
+        let errors = check(
+            temp.path(),
+            "http {\n    ssl_dhparam dh2048.pem;\n    server {\n        ssl_dhparam dh1024.pem;\n    }\n}\n",
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].line, Some(4));
+        assert_eq!(errors[0].severity, Severity::Warning);
+        assert!(
+            errors[0]
+                .message
+                .starts_with("ssl_dhparam file 'dh1024.pem' has 1024-bit parameters")
+        );
+    }
+
+    #[test]
+    fn test_missing_and_unreadable_files() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("garbage.pem"), "garbage").unwrap();
+
+        let errors = check(
+            temp.path(),
+            "ssl_dhparam missing.pem;\nssl_dhparam garbage.pem;\nssl_dhparam $dhparam;\n",
+        );
+
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "ssl_dhparam file 'missing.pem' does not exist"
+        );
+        assert_eq!(errors[0].severity, Severity::Error);
+    }
+}
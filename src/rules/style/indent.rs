@@ -1,7 +1,9 @@
-use crate::config::IndentSize;
+use crate::config::RulePreset;
+use crate::config::{IndentSize, LintConfig};
 use crate::docs::RuleDoc;
 use crate::linter::{Fix, LintError, LintRule, Severity};
 use crate::parser::ast::{Config, ConfigItem};
+use crate::parser::format::IndentStyle;
 use std::path::Path;
 
 /// Rule documentation
@@ -15,10 +17,12 @@ Properly indented nested blocks make the structure visually clear
 and easier to understand.
 
 Using spaces instead of tabs ensures consistent appearance
-across different environments."#,
+across different environments. Teams that indent with tabs can set
+`use_tabs = true` in the `[format]` section of `.nginx-lint.toml`."#,
     bad_example: include_str!("indent/bad.conf"),
     good_example: include_str!("indent/good.conf"),
     references: &[],
+    preset: Some(RulePreset::Strict),
     ..RuleDoc::DEFAULTS
 };
 
@@ -26,12 +30,15 @@ across different environments."#,
 pub struct Indent {
     /// Indent size configuration: fixed number or auto-detect
     pub indent_size: IndentSize,
+    /// Expect one tab per nesting level instead of spaces
+    pub use_tabs: bool,
 }
 
 impl Default for Indent {
     fn default() -> Self {
         Self {
             indent_size: IndentSize::Auto,
+            use_tabs: false,
         }
     }
 }
@@ -41,13 +48,39 @@ impl Indent {
     pub fn with_size(size: usize) -> Self {
         Self {
             indent_size: IndentSize::Fixed(size),
+            use_tabs: false,
         }
     }
 
     /// Create with auto-detection
     pub fn auto() -> Self {
+        Self::default()
+    }
+
+    /// Create from the `[rules.indent]` and `[format]` settings
+    ///
+    /// `indent_size` falls back to `format.indent_width`, and `format.use_tabs`
+    /// switches to tab indentation, so `--fix` agrees with `nginx-lint fmt`.
+    pub fn from_config(config: Option<&LintConfig>) -> Self {
+        let Some(config) = config else {
+            return Self::default();
+        };
+        let indent_size = config
+            .get_rule_config("indent")
+            .and_then(|cfg| cfg.indent_size)
+            .or(config.format.indent_width.map(IndentSize::Fixed))
+            .unwrap_or_default();
         Self {
-            indent_size: IndentSize::Auto,
+            indent_size,
+            use_tabs: config.format.use_tabs,
+        }
+    }
+
+    /// Create expecting tab indentation
+    pub fn tabs() -> Self {
+        Self {
+            use_tabs: true,
+            ..Self::default()
         }
     }
 }
@@ -84,6 +117,15 @@ impl Indent {
         }
     }
 
+    /// The whitespace expected for one nesting level
+    fn indent_unit(&self, indent_size: usize) -> IndentStyle {
+        if self.use_tabs {
+            IndentStyle::Tabs
+        } else {
+            IndentStyle::Spaces(indent_size)
+        }
+    }
+
     /// Recursively walk the AST and check indentation at each depth
     fn check_items(
         &self,
@@ -100,7 +142,7 @@ impl Indent {
                         errors,
                         &d.leading_whitespace,
                         depth,
-                        indent_size,
+                        self.indent_unit(indent_size),
                         d.span.start.line,
                         ws_start,
                     );
@@ -124,7 +166,7 @@ impl Indent {
                                 errors,
                                 closing_ws,
                                 depth,
-                                indent_size,
+                                self.indent_unit(indent_size),
                                 closing_line,
                                 closing_ws_start,
                             );
@@ -137,7 +179,7 @@ impl Indent {
                         errors,
                         &c.leading_whitespace,
                         depth,
-                        indent_size,
+                        self.indent_unit(indent_size),
                         c.span.start.line,
                         ws_start,
                     );
@@ -192,26 +234,12 @@ pub(crate) fn has_brace_structure_error(errors: &[crate::parser::parser::SyntaxE
     errors.iter().any(|e| e.message.contains('}'))
 }
 
-/// Detect indent size from AST by finding the first item inside a top-level block
+/// Detect a space indent size from the AST (tab-indented files give `None`)
 fn detect_indent_size_from_ast(items: &[ConfigItem]) -> Option<usize> {
-    for item in items {
-        if let ConfigItem::Directive(d) = item
-            && let Some(block) = &d.block
-        {
-            // Find the first non-blank item in this block
-            for inner in &block.items {
-                let ws = match inner {
-                    ConfigItem::Directive(d) => &d.leading_whitespace,
-                    ConfigItem::Comment(c) => &c.leading_whitespace,
-                    ConfigItem::BlankLine(_) => continue,
-                };
-                if !ws.is_empty() && !ws.contains('\t') {
-                    return Some(ws.len());
-                }
-            }
-        }
+    match IndentStyle::detect(items)? {
+        IndentStyle::Spaces(size) => Some(size),
+        IndentStyle::Tabs => None,
     }
-    None
 }
 
 /// Check a single leading_whitespace value against expected indentation
@@ -219,46 +247,39 @@ fn check_whitespace(
     errors: &mut Vec<LintError>,
     leading_ws: &str,
     expected_depth: usize,
-    indent_size: usize,
+    unit: IndentStyle,
     line: usize,
     ws_start_offset: usize,
 ) {
-    let expected_spaces = expected_depth * indent_size;
-
-    // Detect tabs
-    if leading_ws.contains('\t') {
-        let correct_indent = " ".repeat(expected_spaces);
-        let fix = Fix::replace_range(
-            ws_start_offset,
-            ws_start_offset + leading_ws.len(),
-            &correct_indent,
-        );
+    let correct_indent = unit.indent(expected_depth);
+    let (wrong_char, mixed_message, unit_name) = match unit {
+        IndentStyle::Spaces(_) => ('\t', "Use spaces instead of tabs for indentation", "spaces"),
+        IndentStyle::Tabs => (' ', "Use tabs instead of spaces for indentation", "tabs"),
+    };
+    let fix = Fix::replace_range(
+        ws_start_offset,
+        ws_start_offset + leading_ws.len(),
+        &correct_indent,
+    );
+
+    // Detect the wrong indentation character
+    if leading_ws.contains(wrong_char) {
         errors.push(
-            LintError::new(
-                "indent",
-                "style",
-                "Use spaces instead of tabs for indentation",
-                Severity::Warning,
-            )
-            .with_location(line, 1)
-            .with_fix(fix),
+            LintError::new("indent", "style", mixed_message, Severity::Warning)
+                .with_location(line, 1)
+                .with_fix(fix),
         );
         return;
     }
 
-    // Check space count
-    if leading_ws.len() != expected_spaces {
+    // Check indentation width
+    if leading_ws.len() != correct_indent.len() {
         let message = format!(
-            "Expected {} spaces of indentation, found {}",
-            expected_spaces,
+            "Expected {} {} of indentation, found {}",
+            correct_indent.len(),
+            unit_name,
             leading_ws.len()
         );
-        let correct_indent = " ".repeat(expected_spaces);
-        let fix = Fix::replace_range(
-            ws_start_offset,
-            ws_start_offset + leading_ws.len(),
-            &correct_indent,
-        );
         errors.push(
             LintError::new("indent", "style", &message, Severity::Warning)
                 .with_location(line, 1)
@@ -277,6 +298,7 @@ mod tests {
     }
 
     /// Apply range-based fixes to content (sorted by offset descending)
+    #[allow(clippy::collapsible_if)]
     fn apply_range_fixes(content: &str, errors: &[LintError]) -> String {
         let mut fixes: Vec<&crate::linter::Fix> =
             errors.iter().flat_map(|e| e.fixes.iter()).collect();
@@ -585,6 +607,27 @@ content_by_lua_block {
         );
     }
 
+    #[test]
+    fn test_tab_style() {
+        let rule = Indent::tabs();
+
+        let errors = rule.check_content("http {\n\tserver {\n\t\tlisten 80;\n\t}\n}\n");
+        assert!(errors.is_empty(), "Expected no errors, got: {:?}", errors);
+
+        let content = "http {\n    server {\n\t\t\tlisten 80;\n\t}\n}\n";
+        let errors = rule.check_content(content);
+        assert_eq!(errors.len(), 2, "Expected 2 errors, got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "Use tabs instead of spaces for indentation"
+        );
+        assert_eq!(errors[1].message, "Expected 2 tabs of indentation, found 3");
+        assert_eq!(
+            apply_range_fixes(content, &errors),
+            "http {\n\tserver {\n\t\tlisten 80;\n\t}\n}\n"
+        );
+    }
+
     #[test]
     fn test_lua_block_autofix_preserves_content() {
         let content = concat!(
@@ -0,0 +1,171 @@
+use crate::config::RulePreset;
+use crate::docs::RuleDoc;
+use crate::linter::{LintError, Severity};
+use crate::semantic::{CrossFileRule, DefaultListen, SymbolTable};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "duplicate-default-server",
+    category: "syntax",
+    description: "Detects default_server set for the same address in servers of different files",
+    severity: "error",
+    why: r#"Each address and port has one default server, the one nginx hands
+requests matching no `server_name`. When two servers mark the same address
+`default_server`, nginx refuses to start ("a duplicate default server").
+This happens easily when the servers live in different files: a distribution
+`conf.d/default.conf` and a site added to `sites-enabled/` both claiming
+`listen 80 default_server`, for example.
+
+Keep `default_server` on one server per address. `listen-conflict` reports
+two default servers in the same file; this rule compares the servers of
+every file of the configuration."#,
+    bad_example: include_str!("duplicate_default_server/bad.conf"),
+    good_example: include_str!("duplicate_default_server/good.conf"),
+    references: &[
+        "https://nginx.org/en/docs/http/ngx_http_core_module.html#listen",
+        "https://nginx.org/en/docs/http/request_processing.html",
+    ],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that no address has default servers in several files
+pub struct DuplicateDefaultServer;
+
+impl CrossFileRule for DuplicateDefaultServer {
+    fn name(&self) -> &'static str {
+        "duplicate-default-server"
+    }
+
+    fn category(&self) -> &'static str {
+        "syntax"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects default_server set for the same address in servers of different files"
+    }
+
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)> {
+        // The first default listen of each address, and the files having one
+        let mut defaults: HashMap<&str, ((&Path, &DefaultListen), Vec<&Path>)> = HashMap::new();
+        let mut errors = Vec::new();
+
+        for server in symbols.http_servers() {
+            for listen in &server.default_listens {
+                let path = server.path.as_path();
+                let Some(((first_path, first), files)) = defaults.get_mut(listen.address.as_str())
+                else {
+                    defaults.insert(&listen.address, ((path, listen), vec![path]));
+                    continue;
+                };
+                // Duplicates within a file are reported by listen-conflict
+                if files.contains(&path) {
+                    continue;
+                }
+                files.push(path);
+                let error = LintError::new(
+                    self.name(),
+                    self.category(),
+                    &format!(
+                        "{} already has a default_server at {}:{}; nginx refuses to start with two",
+                        listen.address,
+                        first_path.display(),
+                        first.line
+                    ),
+                    Severity::Error,
+                )
+                .with_location(listen.line, listen.column);
+                errors.push((server.path.clone(), error));
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::check_cross_file;
+
+    #[test]
+    fn test_default_servers_in_different_files() {
+        let errors = check_cross_file(
+            &DuplicateDefaultServer,
+            &[
+                (
+                    "nginx.conf",
+                    "",
+                    "http {\n    include conf.d/*.conf;\n    include sites-enabled/*;\n}\n",
+                ),
+                (
+                    "conf.d/default.conf",
+                    "http",
+                    "server {\n    listen 80 default_server;\n    listen [::]:80 default_server;\n    return 444;\n}\n",
+                ),
+                (
+                    "sites-enabled/app",
+                    "http",
+                    "server {\n    listen 0.0.0.0:80 default_server;\n    listen 8080 default_server;\n    server_name app.example.com;\n}\n",
+                ),
+            ],
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].0, PathBuf::from("sites-enabled/app"));
+        assert_eq!(errors[0].1.line, Some(2));
+        assert_eq!(
+            errors[0].1.message,
+            "*:80 already has a default_server at conf.d/default.conf:2; nginx refuses to start with two"
+        );
+    }
+
+    #[test]
+    fn test_default_servers_in_one_file() {
+        // Left to listen-conflict, which sees the whole file
+        let errors = check_cross_file(
+            &DuplicateDefaultServer,
+            &[(
+                "nginx.conf",
+                "",
+                "http {\n    server {\n        listen 80 default_server;\n    }\n    server {\n        listen 80 default_server;\n    }\n}\n",
+            )],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_other_addresses_and_sockets() {
+        let errors = check_cross_file(
+            &DuplicateDefaultServer,
+            &[
+                (
+                    "a.conf",
+                    "http",
+                    "server {\n    listen 443 ssl default_server;\n    listen unix:/run/a.sock default_server;\n    listen $port default_server;\n}\n",
+                ),
+                (
+                    "b.conf",
+                    "http",
+                    "server {\n    listen 443 quic default_server;\n    listen 127.0.0.1:443 default_server;\n    listen $port default_server;\n}\n",
+                ),
+            ],
+        );
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_each_file_reported_once() {
+        let server = "server {\n    listen 80 default;\n}\n";
+        let errors = check_cross_file(
+            &DuplicateDefaultServer,
+            &[
+                ("a.conf", "http", server),
+                ("b.conf", "http", server),
+                ("c.conf", "http", server),
+            ],
+        );
+        let paths: Vec<_> = errors.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(paths, [PathBuf::from("b.conf"), PathBuf::from("c.conf")]);
+    }
+}
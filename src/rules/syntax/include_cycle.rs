@@ -0,0 +1,285 @@
+use crate::config::RulePreset;
+use crate::config_source::{ConfigSource, RealFileSystem};
+use crate::docs::RuleDoc;
+use crate::include::{find_include_chain, resolve_include_pattern};
+use crate::linter::{LintError, LintRule, Severity};
+use crate::parser::ast::Config;
+use nginx_lint_common::config::PathMapping;
+use nginx_lint_common::paths::base_dir;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Rule documentation
+pub static DOC: RuleDoc = RuleDoc {
+    name: "include-cycle",
+    category: "syntax",
+    description: "Detects include directives that lead back to the including file",
+    severity: "error",
+    why: r#"nginx does not detect circular includes. A file that includes itself,
+directly or through other included files, makes nginx open the same files
+over and over until it runs out of file descriptors and fails to start.
+
+Each `include` directive whose target eventually includes the current file
+again is reported, together with the chain of files that closes the loop."#,
+    bad_example: include_str!("include_cycle/bad.conf"),
+    good_example: include_str!("include_cycle/good.conf"),
+    references: &["https://nginx.org/en/docs/ngx_core_module.html#include"],
+    preset: Some(RulePreset::Minimal),
+    ..RuleDoc::DEFAULTS
+};
+
+/// Check that include directives do not form a cycle
+pub struct IncludeCycle {
+    path_mappings: Vec<PathMapping>,
+    prefix: Option<PathBuf>,
+    source: Arc<dyn ConfigSource>,
+}
+
+impl Default for IncludeCycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncludeCycle {
+    pub fn new() -> Self {
+        Self::with_path_mappings_and_prefix(Vec::new(), None)
+    }
+
+    pub fn with_path_mappings_and_prefix(
+        path_mappings: Vec<PathMapping>,
+        prefix: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            path_mappings,
+            prefix,
+            source: Arc::new(RealFileSystem),
+        }
+    }
+
+    /// Look the files up in `source` instead of on disk
+    pub fn with_source(mut self, source: Arc<dyn ConfigSource>) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+/// Format a chain of files for display, relative to the deepest directory
+/// containing all of them and with `..` segments resolved.
+fn display_chain(source: &dyn ConfigSource, files: &[&Path]) -> String {
+    let files: Vec<PathBuf> = files
+        .iter()
+        .map(|p| source.canonicalize(p).unwrap_or_else(|_| p.to_path_buf()))
+        .collect();
+    let mut base = files
+        .first()
+        .and_then(|p| p.parent())
+        .unwrap_or(Path::new(""))
+        .to_path_buf();
+    while !files.iter().all(|p| p.starts_with(&base)) {
+        if !base.pop() {
+            break;
+        }
+    }
+    files
+        .iter()
+        .map(|p| p.strip_prefix(&base).unwrap_or(p).display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+impl LintRule for IncludeCycle {
+    fn name(&self) -> &'static str {
+        "include-cycle"
+    }
+
+    fn category(&self) -> &'static str {
+        "syntax"
+    }
+
+    fn description(&self) -> &'static str {
+        "Detects include directives that lead back to the including file"
+    }
+
+    fn check(&self, config: &Config, path: &Path) -> Vec<LintError> {
+        let mut errors = Vec::new();
+        // Content that does not come from a real file cannot be part of a cycle
+        let Ok(canonical) = self.source.canonicalize(path) else {
+            return errors;
+        };
+        let resolve_dir = base_dir(self.prefix.as_deref(), path);
+
+        for directive in config.all_directives() {
+            if !directive.is("include") {
+                continue;
+            }
+            let Some(pattern) = directive.first_arg() else {
+                continue;
+            };
+
+            let targets = resolve_include_pattern(
+                self.source.as_ref(),
+                pattern,
+                resolve_dir,
+                &self.path_mappings,
+            );
+            for target in targets {
+                let mut visited = HashSet::new();
+                let Some(chain) = find_include_chain(
+                    self.source.as_ref(),
+                    &target,
+                    &canonical,
+                    &self.path_mappings,
+                    self.prefix.as_deref(),
+                    &mut visited,
+                ) else {
+                    continue;
+                };
+
+                let files: Vec<&Path> = std::iter::once(path)
+                    .chain(chain.iter().map(PathBuf::as_path))
+                    .collect();
+                errors.push(
+                    LintError::new(
+                        self.name(),
+                        self.category(),
+                        &format!(
+                            "Circular include: {}",
+                            display_chain(self.source.as_ref(), &files)
+                        ),
+                        Severity::Error,
+                    )
+                    .with_location(directive.span.start.line, directive.span.start.column),
+                );
+                // One report per directive, even if a glob matches several
+                // files of the cycle
+                break;
+            }
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_file(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn check(path: &Path) -> Vec<LintError> {
+        let content = fs::read_to_string(path).unwrap();
+        let config = crate::parser::parse_string(&content).unwrap();
+        IncludeCycle::new().check(&config, path)
+    }
+
+    #[test]
+    fn test_self_include() {
+        let temp = TempDir::new().unwrap();
+        let path = create_test_file(
+            temp.path(),
+            "nginx.conf",
+            "http {\n    include nginx.conf;\n}\n",
+        );
+
+        let errors = check(&path);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(2));
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert!(
+            errors[0].message.contains("nginx.conf -> nginx.conf"),
+            "got: {}",
+            errors[0].message
+        );
+    }
+
+    #[test]
+    fn test_indirect_cycle() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let root = create_test_file(dir, "nginx.conf", "http {\n    include conf.d/*.conf;\n}\n");
+        let app = create_test_file(dir, "conf.d/app.conf", "include ../nginx.conf;\n");
+        create_test_file(dir, "conf.d/other.conf", "server {}\n");
+
+        let errors = check(&root);
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0]
+                .message
+                .contains("nginx.conf -> conf.d/app.conf -> nginx.conf"),
+            "got: {}",
+            errors[0].message
+        );
+
+        // Reported from both ends of the loop
+        let errors = check(&app);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(1));
+    }
+
+    #[test]
+    fn test_shared_include_is_not_a_cycle() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let root = create_test_file(
+            dir,
+            "nginx.conf",
+            "http {\n    include a.conf;\n    include b.conf;\n}\n",
+        );
+        create_test_file(dir, "a.conf", "include common.conf;\n");
+        create_test_file(dir, "b.conf", "include common.conf;\n");
+        create_test_file(dir, "common.conf", "gzip on;\n");
+
+        let errors = check(&root);
+        assert!(errors.is_empty(), "got: {:?}", errors);
+    }
+
+    #[test]
+    fn test_cycle_not_through_file_is_not_reported() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let root = create_test_file(dir, "nginx.conf", "include a.conf;\n");
+        create_test_file(dir, "a.conf", "include b.conf;\n");
+        create_test_file(dir, "b.conf", "include a.conf;\n");
+
+        // The a <-> b loop is reported when linting a.conf and b.conf
+        let errors = check(&root);
+        assert!(errors.is_empty(), "got: {:?}", errors);
+        assert_eq!(check(&dir.join("a.conf")).len(), 1);
+    }
+
+    #[test]
+    fn test_missing_file_path_is_skipped() {
+        let config = crate::parser::parse_string("include nginx.conf;").unwrap();
+        let errors = IncludeCycle::new().check(&config, Path::new("does-not-exist.conf"));
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_cycle_in_memory_source() {
+        use crate::config_source::MemoryFileSystem;
+
+        let source = MemoryFileSystem::new()
+            .with_file("/etc/nginx/nginx.conf", "include conf.d/*.conf;\n")
+            .with_file("/etc/nginx/conf.d/app.conf", "include ../nginx.conf;\n");
+        let config = crate::parser::parse_string("include conf.d/*.conf;\n").unwrap();
+        let errors = IncludeCycle::new()
+            .with_source(Arc::new(source))
+            .check(&config, Path::new("/etc/nginx/nginx.conf"));
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(
+            errors[0].message,
+            "Circular include: nginx.conf -> conf.d/app.conf -> nginx.conf"
+        );
+    }
+}
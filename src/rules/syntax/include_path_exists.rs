@@ -1,9 +1,13 @@
+use crate::config::RulePreset;
+use crate::config_source::{ConfigSource, RealFileSystem};
 use crate::docs::RuleDoc;
 use crate::include::{apply_path_mapping, resolve_include_pattern};
 use crate::linter::{LintError, LintRule, Severity};
 use crate::parser::ast::Config;
 use nginx_lint_common::config::PathMapping;
+use nginx_lint_common::paths::base_dir;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Rule documentation
 pub static DOC: RuleDoc = RuleDoc {
@@ -13,10 +17,13 @@ pub static DOC: RuleDoc = RuleDoc {
     severity: "error",
     why: r#"When an include directive references a file that does not exist,
 nginx will fail to start. Glob patterns that match no files are
-accepted by nginx without error, so only literal paths are checked."#,
+accepted by nginx without error, but silently include nothing, which
+usually means the pattern or the directory is wrong; they are reported
+as warnings."#,
     bad_example: include_str!("include_path_exists/bad.conf"),
     good_example: include_str!("include_path_exists/good.conf"),
     references: &["https://nginx.org/en/docs/ngx_core_module.html#include"],
+    preset: Some(RulePreset::Minimal),
     ..RuleDoc::DEFAULTS
 };
 
@@ -24,6 +31,7 @@ accepted by nginx without error, so only literal paths are checked."#,
 pub struct IncludePathExists {
     path_mappings: Vec<PathMapping>,
     prefix: Option<PathBuf>,
+    source: Arc<dyn ConfigSource>,
 }
 
 impl Default for IncludePathExists {
@@ -34,17 +42,11 @@ impl Default for IncludePathExists {
 
 impl IncludePathExists {
     pub fn new() -> Self {
-        Self {
-            path_mappings: Vec::new(),
-            prefix: None,
-        }
+        Self::with_path_mappings_and_prefix(Vec::new(), None)
     }
 
     pub fn with_path_mappings(path_mappings: Vec<PathMapping>) -> Self {
-        Self {
-            path_mappings,
-            prefix: None,
-        }
+        Self::with_path_mappings_and_prefix(path_mappings, None)
     }
 
     pub fn with_path_mappings_and_prefix(
@@ -54,8 +56,15 @@ impl IncludePathExists {
         Self {
             path_mappings,
             prefix,
+            source: Arc::new(RealFileSystem),
         }
     }
+
+    /// Look the files up in `source` instead of on disk
+    pub fn with_source(mut self, source: Arc<dyn ConfigSource>) -> Self {
+        self.source = source;
+        self
+    }
 }
 
 /// Returns true if the pattern contains glob wildcard characters
@@ -78,8 +87,7 @@ impl LintRule for IncludePathExists {
 
     fn check(&self, config: &Config, path: &Path) -> Vec<LintError> {
         let mut errors = Vec::new();
-        let parent_dir = path.parent().unwrap_or(Path::new("."));
-        let resolve_dir = self.prefix.as_deref().unwrap_or(parent_dir);
+        let resolve_dir = base_dir(self.prefix.as_deref(), path);
 
         for directive in config.all_directives() {
             if !directive.is("include") {
@@ -105,26 +113,31 @@ impl LintRule for IncludePathExists {
             }
 
             // Resolve the pattern and check if any files match
-            let resolved = resolve_include_pattern(&mapped_pattern, resolve_dir, &[]);
+            let resolved =
+                resolve_include_pattern(self.source.as_ref(), &mapped_pattern, resolve_dir, &[]);
 
             if resolved.is_empty() {
-                // Glob patterns matching zero files are accepted by nginx, so skip them
-                if is_glob_pattern(&mapped_pattern) {
-                    continue;
-                }
-
                 let line = directive.span.start.line;
                 let column = directive.span.start.column;
 
-                errors.push(
+                // Glob patterns matching zero files are accepted by nginx,
+                // so they are only a warning
+                let error = if is_glob_pattern(&mapped_pattern) {
+                    LintError::new(
+                        self.name(),
+                        self.category(),
+                        &format!("Include pattern '{}' matches no files", mapped_pattern),
+                        Severity::Warning,
+                    )
+                } else {
                     LintError::new(
                         self.name(),
                         self.category(),
                         &format!("Included file '{}' does not exist", mapped_pattern),
                         Severity::Error,
                     )
-                    .with_location(line, column),
-                );
+                };
+                errors.push(error.with_location(line, column));
             }
         }
 
@@ -216,16 +229,21 @@ mod tests {
 
     #[test]
     fn test_glob_pattern_no_match() {
-        // nginx accepts glob patterns that match zero files without error
+        // nginx accepts glob patterns that match zero files, so this is
+        // only a warning
         let temp = TempDir::new().unwrap();
         let dir = temp.path();
 
-        let errors = parse_and_check(dir, "nginx.conf", "include conf.d/*.conf;", vec![]);
-        assert!(
-            errors.is_empty(),
-            "Glob patterns matching no files should not produce errors, got: {:?}",
-            errors
+        let errors = parse_and_check(
+            dir,
+            "nginx.conf",
+            "http {\n    include conf.d/*.conf;\n}",
+            vec![],
         );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].severity, Severity::Warning);
+        assert_eq!(errors[0].line, Some(2));
+        assert!(errors[0].message.contains("matches no files"));
     }
 
     #[test]
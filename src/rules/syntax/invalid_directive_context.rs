@@ -1,3 +1,4 @@
+use crate::config::RulePreset;
 use crate::docs::RuleDoc;
 use crate::linter::{LintError, LintRule, Severity};
 use crate::parser::ast::{Config, ConfigItem};
@@ -28,6 +29,7 @@ Or use the --context CLI flag:
     bad_example: include_str!("invalid_directive_context/bad.conf"),
     good_example: include_str!("invalid_directive_context/good.conf"),
     references: &["https://nginx.org/en/docs/beginners_guide.html"],
+    preset: Some(RulePreset::Minimal),
     ..RuleDoc::DEFAULTS
 };
 
@@ -193,7 +195,7 @@ impl LintRule for InvalidDirectiveContext {
         let mut errors = Vec::new();
 
         // Build initial parent stack from include_context
-        let parent_stack: Vec<&str> = config.include_context.iter().map(|s| s.as_str()).collect();
+        let parent_stack: Vec<&str> = config.include_context.names().collect();
 
         self.check_context(&config.items, &parent_stack, &mut errors);
 
@@ -215,7 +217,7 @@ mod tests {
 
     fn check_config_with_context(content: &str, context: Vec<String>) -> Vec<LintError> {
         let mut config = parse_string(content).expect("Failed to parse config");
-        config.include_context = context;
+        config.include_context = context.into();
         let rule = InvalidDirectiveContext::new();
         rule.check(&config, &PathBuf::from("test.conf"))
     }
@@ -1,8 +1,9 @@
+use crate::config::RulePreset;
 use crate::docs::RuleDoc;
 use crate::linter::{Fix, LintError, LintRule, Severity};
 use crate::parser::ast::Config;
 use crate::parser::line_index::LineIndex;
-use crate::parser::parse_string_rowan;
+use crate::parser::parse_string_rowan_with_raw_blocks;
 use crate::parser::syntax_kind::{SyntaxElement, SyntaxKind, SyntaxNode};
 use std::fs;
 use std::path::Path;
@@ -21,13 +22,25 @@ but regular directives always require them."#,
     bad_example: include_str!("missing_semicolon/bad.conf"),
     good_example: include_str!("missing_semicolon/good.conf"),
     references: &[],
+    preset: Some(RulePreset::Minimal),
     ..RuleDoc::DEFAULTS
 };
 
 /// Check for missing semicolons at the end of directives
-pub struct MissingSemicolon;
+#[derive(Default)]
+pub struct MissingSemicolon {
+    /// Raw block directives beyond the built-ins, whose content is skipped
+    raw_block_directives: Vec<String>,
+}
 
 impl MissingSemicolon {
+    /// Also skip the content of `raw_block_directives` (see
+    /// [`LintConfig::additional_raw_block_directives`](crate::config::LintConfig::additional_raw_block_directives))
+    pub fn with_raw_block_directives(mut self, raw_block_directives: Vec<String>) -> Self {
+        self.raw_block_directives = raw_block_directives;
+        self
+    }
+
     /// Check content directly (used by WASM)
     pub fn check_content(&self, content: &str) -> Vec<LintError> {
         self.check_cst(content)
@@ -54,7 +67,7 @@ impl MissingSemicolon {
     /// 2. Merged directives where a NEWLINE separates what should be
     ///    independent directives (the parser treats newlines as whitespace)
     fn check_cst(&self, source: &str) -> Vec<LintError> {
-        let (root, _) = parse_string_rowan(source);
+        let (root, _) = parse_string_rowan_with_raw_blocks(source, &self.raw_block_directives);
         let line_index = LineIndex::new(source);
         let mut errors = Vec::new();
 
@@ -270,7 +283,7 @@ mod tests {
         write!(file, "{}", content).unwrap();
         let path = file.path().to_path_buf();
 
-        let rule = MissingSemicolon;
+        let rule = MissingSemicolon::default();
         let config = Config::new();
         rule.check(&config, &path)
     }
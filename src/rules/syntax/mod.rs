@@ -1,3 +1,6 @@
+pub mod duplicate_default_server;
+#[cfg(feature = "cli")]
+pub mod include_cycle;
 #[cfg(feature = "cli")]
 pub mod include_path_exists;
 pub mod invalid_directive_context;
@@ -5,6 +8,9 @@ pub mod missing_semicolon;
 pub mod unclosed_quote;
 pub mod unmatched_braces;
 
+pub use duplicate_default_server::DuplicateDefaultServer;
+#[cfg(feature = "cli")]
+pub use include_cycle::IncludeCycle;
 #[cfg(feature = "cli")]
 pub use include_path_exists::IncludePathExists;
 pub use invalid_directive_context::InvalidDirectiveContext;
@@ -1,8 +1,9 @@
+use crate::config::RulePreset;
 use crate::docs::RuleDoc;
 use crate::linter::{Fix, LintError, LintRule, Severity};
 use crate::parser::ast::Config;
 use crate::parser::line_index::LineIndex;
-use crate::parser::parse_string_rowan;
+use crate::parser::parse_string_rowan_with_raw_blocks;
 use crate::parser::syntax_kind::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
 use std::fs;
 use std::path::Path;
@@ -20,13 +21,25 @@ Strings enclosed in quotes must be closed with the same quote type."#,
     bad_example: include_str!("unclosed_quote/bad.conf"),
     good_example: include_str!("unclosed_quote/good.conf"),
     references: &[],
+    preset: Some(RulePreset::Minimal),
     ..RuleDoc::DEFAULTS
 };
 
 /// Check for unclosed string quotes
-pub struct UnclosedQuote;
+#[derive(Default)]
+pub struct UnclosedQuote {
+    /// Raw block directives beyond the built-ins, whose content is skipped
+    raw_block_directives: Vec<String>,
+}
 
 impl UnclosedQuote {
+    /// Also skip the content of `raw_block_directives` (see
+    /// [`LintConfig::additional_raw_block_directives`](crate::config::LintConfig::additional_raw_block_directives))
+    pub fn with_raw_block_directives(mut self, raw_block_directives: Vec<String>) -> Self {
+        self.raw_block_directives = raw_block_directives;
+        self
+    }
+
     /// nginx directive keywords that should not be inside quoted strings
     const NGINX_KEYWORDS: &'static [&'static str] = &[
         "permanent",
@@ -52,7 +65,8 @@ impl UnclosedQuote {
     /// `content_by_lua_block`), and checks whether quoted-string tokens
     /// are properly terminated.
     fn check_cst(&self, source: &str) -> Vec<LintError> {
-        let (root, _syntax_errors) = parse_string_rowan(source);
+        let (root, _syntax_errors) =
+            parse_string_rowan_with_raw_blocks(source, &self.raw_block_directives);
         let line_index = LineIndex::new(source);
         let mut errors = Vec::new();
 
@@ -325,7 +339,7 @@ mod tests {
     use super::*;
 
     fn check_quotes(content: &str) -> Vec<LintError> {
-        let rule = UnclosedQuote;
+        let rule = UnclosedQuote::default();
         rule.check_content(content)
     }
 
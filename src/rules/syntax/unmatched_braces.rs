@@ -1,8 +1,9 @@
+use crate::config::RulePreset;
 use crate::docs::RuleDoc;
 use crate::linter::{Fix, LintError, LintRule, Severity};
 use crate::parser::ast::Config;
 use crate::parser::line_index::LineIndex;
-use crate::parser::parse_string_rowan;
+use crate::parser::parse_string_rowan_with_raw_blocks;
 use crate::parser::syntax_kind::{SyntaxElement, SyntaxKind, SyntaxNode};
 use std::fs;
 use std::path::Path;
@@ -21,6 +22,7 @@ are balanced, and that block directives have their opening brace."#,
     bad_example: include_str!("unmatched_braces/bad.conf"),
     good_example: include_str!("unmatched_braces/good.conf"),
     references: &["https://nginx.org/en/docs/beginners_guide.html"],
+    preset: Some(RulePreset::Minimal),
     ..RuleDoc::DEFAULTS
 };
 
@@ -31,6 +33,8 @@ pub struct UnmatchedBraces {
     /// (for extension modules), used when checking via the registered
     /// `LintRule` path (see [`check_with_content`](LintRule::check_with_content)).
     additional_block_directives: Vec<String>,
+    /// Raw block directives beyond the built-ins, whose content is skipped
+    raw_block_directives: Vec<String>,
 }
 
 impl UnmatchedBraces {
@@ -39,8 +43,16 @@ impl UnmatchedBraces {
     pub fn with_additional_block_directives(additional_block_directives: Vec<String>) -> Self {
         Self {
             additional_block_directives,
+            ..Self::default()
         }
     }
+
+    /// Also skip the content of `raw_block_directives` (see
+    /// [`LintConfig::additional_raw_block_directives`](crate::config::LintConfig::additional_raw_block_directives))
+    pub fn with_raw_block_directives(mut self, raw_block_directives: Vec<String>) -> Self {
+        self.raw_block_directives = raw_block_directives;
+        self
+    }
 }
 
 /// Information about an opening brace on the stack.
@@ -82,7 +94,7 @@ impl UnmatchedBraces {
     /// to the original text-based approach but benefiting from proper
     /// tokenization of strings, comments, and raw blocks.
     fn check_cst(&self, source: &str, additional_block_directives: &[String]) -> Vec<LintError> {
-        let (root, _) = parse_string_rowan(source);
+        let (root, _) = parse_string_rowan_with_raw_blocks(source, &self.raw_block_directives);
         let line_index = LineIndex::new(source);
 
         // Flatten CST tokens, skipping raw block internals.
@@ -0,0 +1,1411 @@
+//! Cross-file semantic analysis.
+//!
+//! Regular [`LintRule`](crate::linter::LintRule)s only ever see one parsed
+//! file at a time. Some problems can only be detected with the whole
+//! configuration in view — an `upstream` defined in `conf.d/upstreams.conf`
+//! and referenced from `sites-enabled/app.conf`, for example. This module
+//! builds a [`SymbolTable`] over every file of a config tree and runs
+//! [`CrossFileRule`]s against it.
+//!
+//! The pass is driven by [`Linter::lint_cross_file`](crate::linter::Linter::lint_cross_file).
+
+use crate::linter::LintError;
+use crate::parser::ast::{Block, Config, ConfigItem, Directive};
+use crate::variables::VariableTable;
+use nginx_lint_common::listen::normalize_listen_address;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A parsed file taking part in a cross-file analysis pass.
+#[derive(Debug, Clone, Copy)]
+pub struct SourceFile<'a> {
+    /// Path of the file (used to attribute diagnostics)
+    pub path: &'a Path,
+    /// Parsed config, with `include_context` set for included files
+    pub config: &'a Config,
+    /// Raw file content (used for ignore-comment handling)
+    pub content: &'a str,
+}
+
+/// Upstream names live in separate namespaces for `http` and `stream`:
+/// an `upstream` block in `stream` cannot be referenced from `http` and
+/// vice versa. The same holds for `log_format` names and limit zones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpstreamNamespace {
+    Http,
+    Stream,
+}
+
+impl UpstreamNamespace {
+    fn from_parents(parent_stack: &[String]) -> Self {
+        if parent_stack.iter().any(|p| p == "stream") {
+            Self::Stream
+        } else {
+            Self::Http
+        }
+    }
+}
+
+/// An `upstream <name> { ... }` block.
+#[derive(Debug, Clone)]
+pub struct UpstreamDefinition {
+    pub name: String,
+    pub namespace: UpstreamNamespace,
+    /// Whether the block has a `keepalive` connection cache
+    pub has_keepalive: bool,
+    /// Ports of its `server` entries (80 when not given), UNIX sockets
+    /// excluded
+    pub server_ports: Vec<u16>,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A `proxy_pass` / `fastcgi_pass` / `grpc_pass` / ... target that may name
+/// an upstream block.
+#[derive(Debug, Clone)]
+pub struct UpstreamReference {
+    /// Host part of the target (e.g. `backend` for `http://backend/api`)
+    pub host: String,
+    /// Directive the reference comes from (e.g. `proxy_pass`)
+    pub directive: String,
+    /// Scheme of the target, lowercased and without `://` (e.g. `https`);
+    /// `None` when the target has none
+    pub scheme: Option<String>,
+    pub namespace: UpstreamNamespace,
+    /// Whether the target carries an explicit port (`backend:8080`).
+    /// nginx never resolves such a target to an `upstream` block.
+    pub has_port: bool,
+    /// Whether the host contains a variable and is only known at runtime
+    pub is_dynamic: bool,
+    /// Whether the target contains a variable anywhere, which makes nginx
+    /// resolve a host that is not an upstream name at request time
+    pub has_variables: bool,
+    /// Whether a `resolver` is in effect at the directive; `None` when it is
+    /// not set in the file and the file is included from a block whose
+    /// settings are not known
+    pub has_resolver: Option<bool>,
+    /// Keepalive-related settings in effect (`proxy_pass` only)
+    pub proxy_settings: Option<ProxyKeepaliveSettings>,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl UpstreamReference {
+    /// Whether nginx would look this target up as a named upstream block
+    /// (as opposed to treating it as an address or a DNS name).
+    ///
+    /// Targets with a port, IP addresses, dotted host names and `localhost`
+    /// are addresses; a bare single-label name is what people write when
+    /// they mean an upstream.
+    pub fn looks_like_upstream_name(&self) -> bool {
+        !self.is_dynamic
+            && !self.has_port
+            && !self.host.is_empty()
+            && !self.host.contains('.')
+            && !self.host.eq_ignore_ascii_case("localhost")
+    }
+}
+
+/// Settings that decide whether a `proxy_pass` can reuse upstream
+/// keepalive connections, as in effect at the directive.
+///
+/// A value is `None` when it is not set in the file and the file is included
+/// from a block whose settings are not known.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyKeepaliveSettings {
+    /// `proxy_http_version` (`"1.0"` when not set, nginx's default)
+    pub http_version: Option<String>,
+    /// Whether the `proxy_set_header` list in effect sets `Connection`
+    pub sets_connection: Option<bool>,
+}
+
+impl ProxyKeepaliveSettings {
+    /// nginx's defaults: HTTP/1.0 and `Connection: close`
+    fn nginx_defaults() -> Self {
+        Self {
+            http_version: Some("1.0".to_string()),
+            sets_connection: Some(false),
+        }
+    }
+
+    /// Settings in effect inside `items`, inheriting from `self`
+    ///
+    /// `proxy_set_header` is only inherited when the level has none of its
+    /// own.
+    fn enter(&self, items: &[ConfigItem]) -> Self {
+        let mut effective = self.clone();
+        let mut headers = None;
+        for item in items {
+            let ConfigItem::Directive(d) = item else {
+                continue;
+            };
+            if d.is("proxy_http_version") {
+                effective.http_version = d.first_arg().map(str::to_string);
+            } else if d.is("proxy_set_header") {
+                let is_connection = d
+                    .first_arg()
+                    .is_some_and(|h| h.eq_ignore_ascii_case("connection"));
+                headers = Some(headers.unwrap_or(false) || is_connection);
+            }
+        }
+        if headers.is_some() {
+            effective.sets_connection = headers;
+        }
+        effective
+    }
+
+    /// Fill in values that are not known from `outer`
+    fn or(self, outer: &Self) -> Self {
+        Self {
+            http_version: self.http_version.or_else(|| outer.http_version.clone()),
+            sets_connection: self.sets_connection.or(outer.sets_connection),
+        }
+    }
+}
+
+/// A `server { ... }` block directly inside `http`.
+#[derive(Debug, Clone)]
+pub struct HttpServer {
+    /// Whether the block has a `server_name` directive
+    pub has_server_name: bool,
+    /// Names of its `server_name` directives
+    pub server_names: Vec<String>,
+    /// Whether a `listen` marks it as `default_server`
+    pub is_default: bool,
+    /// The `listen` directives marking it as `default_server`, whose address
+    /// is known before runtime
+    pub default_listens: Vec<DefaultListen>,
+    /// Whether it accepts plain HTTP: a `listen` without `ssl` or `quic`,
+    /// or no `listen` at all
+    pub listens_plain: bool,
+    /// Whether a `listen` has the `ssl` parameter
+    pub listens_tls: bool,
+    /// A redirect of every request to `https://`
+    pub https_redirect: Option<HttpsRedirect>,
+    /// The `location` blocks directly in the server, other than the one
+    /// holding `https_redirect`
+    pub locations: Vec<ServerLocation>,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A `listen ... default_server` of a server.
+#[derive(Debug, Clone)]
+pub struct DefaultListen {
+    /// The normalized address (see [`normalize_listen_address`]), with
+    /// ` quic` appended for UDP sockets
+    pub address: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A `return` or catch-all `rewrite` redirecting to `https://`, at server
+/// level or in `location /`.
+#[derive(Debug, Clone)]
+pub struct HttpsRedirect {
+    pub directive: String,
+    pub host: RedirectHost,
+    /// Whether it is in `location /` rather than at server level
+    pub in_location: bool,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Host part of a redirect target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedirectHost {
+    /// A single variable such as `$host`, without the `$`
+    Variable(String),
+    /// A hard-coded hostname, lowercased
+    Literal(String),
+    /// Anything else, e.g. a capture or several variables
+    Other,
+}
+
+/// A `location` block directly in a server.
+#[derive(Debug, Clone)]
+pub struct ServerLocation {
+    /// Its arguments, e.g. `= /health` or `/api/`
+    pub uri: String,
+    /// Whether it or a block nested in it has a content handler such as
+    /// `proxy_pass`, `root` or `try_files`
+    pub serves_content: bool,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A `log_format <name> [escape=...] <string> ...` definition.
+#[derive(Debug, Clone)]
+pub struct LogFormatDefinition {
+    pub name: String,
+    pub namespace: UpstreamNamespace,
+    /// Value of the `escape=` parameter, if given
+    pub escape: Option<String>,
+    /// Whether the format string is a JSON object (starts with `{`)
+    pub is_json: bool,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// An `access_log` directive.
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    /// Name of the log format it writes; `None` for `access_log off` and
+    /// when the format is omitted (`combined`)
+    pub format: Option<String>,
+    /// Whether it is `access_log off`
+    pub is_off: bool,
+    /// Whether it is directly in the `http` block
+    pub is_http_level: bool,
+    pub namespace: UpstreamNamespace,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A `proxy_cache <zone>` enabling a cache.
+#[derive(Debug, Clone)]
+pub struct ProxyCacheUse {
+    /// Name of the zone; `None` when it is built from variables
+    pub zone: Option<String>,
+    /// Whether a `proxy_cache_key` is in effect; `None` when it is not set
+    /// in the file and the file is included from a block whose settings are
+    /// not known
+    pub has_cache_key: Option<bool>,
+    /// Whether a `proxy_cache_valid` is in effect (as `has_cache_key`)
+    pub has_cache_valid: Option<bool>,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Whether `proxy_cache_key` and `proxy_cache_valid` are in effect, `None`
+/// when not known
+#[derive(Debug, Clone, Copy, Default)]
+struct CacheSettings {
+    key: Option<bool>,
+    valid: Option<bool>,
+}
+
+impl CacheSettings {
+    /// Settings in effect inside `items`, inheriting from `self`
+    fn enter(self, items: &[ConfigItem]) -> Self {
+        let has = |name: &str| {
+            items
+                .iter()
+                .any(|item| matches!(item, ConfigItem::Directive(d) if d.is(name)))
+        };
+        Self {
+            key: if has("proxy_cache_key") {
+                Some(true)
+            } else {
+                self.key
+            },
+            valid: if has("proxy_cache_valid") {
+                Some(true)
+            } else {
+                self.valid
+            },
+        }
+    }
+}
+
+/// Kind of a shared memory zone used to limit requests or connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitZoneKind {
+    /// Declared by `limit_req_zone`, used by `limit_req`
+    Req,
+    /// Declared by `limit_conn_zone`, used by `limit_conn`
+    Conn,
+}
+
+impl LimitZoneKind {
+    /// Directive declaring zones of this kind
+    pub fn declaring_directive(self) -> &'static str {
+        match self {
+            Self::Req => "limit_req_zone",
+            Self::Conn => "limit_conn_zone",
+        }
+    }
+
+    /// Directive using zones of this kind
+    pub fn using_directive(self) -> &'static str {
+        match self {
+            Self::Req => "limit_req",
+            Self::Conn => "limit_conn",
+        }
+    }
+}
+
+/// A `limit_req_zone` / `limit_conn_zone` declaration, or a `limit_req` /
+/// `limit_conn` naming a zone.
+#[derive(Debug, Clone)]
+pub struct LimitZone {
+    pub kind: LimitZoneKind,
+    pub name: String,
+    pub namespace: UpstreamNamespace,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Directives whose target can name an `upstream` block.
+const UPSTREAM_REFERENCE_DIRECTIVES: &[&str] = &[
+    "proxy_pass",
+    "fastcgi_pass",
+    "grpc_pass",
+    "uwsgi_pass",
+    "scgi_pass",
+    "memcached_pass",
+];
+
+/// Directives that make a location serve content.
+const CONTENT_DIRECTIVES: &[&str] = &[
+    "proxy_pass",
+    "fastcgi_pass",
+    "grpc_pass",
+    "uwsgi_pass",
+    "scgi_pass",
+    "memcached_pass",
+    "root",
+    "alias",
+    "try_files",
+];
+
+/// `rewrite` patterns matching every URI.
+const CATCH_ALL_PATTERNS: &[&str] = &["^", "^(.*)$", "^/(.*)$", "^(.*)", "^/(.*)", ".*", "(.*)"];
+
+/// Symbols collected from every file of a config tree.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    upstreams: Vec<UpstreamDefinition>,
+    upstream_references: Vec<UpstreamReference>,
+    http_servers: Vec<HttpServer>,
+    log_formats: Vec<LogFormatDefinition>,
+    access_logs: Vec<AccessLog>,
+    limit_zones: Vec<LimitZone>,
+    limit_zone_references: Vec<LimitZone>,
+    /// Zone names declared by `proxy_cache_path ... keys_zone=NAME:size`
+    proxy_cache_zones: Vec<String>,
+    proxy_cache_uses: Vec<ProxyCacheUse>,
+    variables: Vec<(PathBuf, VariableTable)>,
+    /// Proxy settings of the `http` block of a top-level file
+    http_proxy_settings: Option<ProxyKeepaliveSettings>,
+    /// Whether the `http` block of a top-level file has a `resolver`
+    http_has_resolver: Option<bool>,
+    /// Cache settings of the `http` block of a top-level file
+    http_cache_settings: Option<CacheSettings>,
+    /// References in files included directly from `http`, whose unset
+    /// proxy settings and resolver come from the `http` block
+    http_included_references: Vec<usize>,
+    /// `proxy_cache` uses in files included directly from `http`, whose
+    /// unset cache settings come from the `http` block
+    http_included_cache_uses: Vec<usize>,
+}
+
+impl SymbolTable {
+    /// Build a symbol table from a set of parsed files.
+    pub fn build(files: &[SourceFile<'_>]) -> Self {
+        let mut table = Self::default();
+        for file in files {
+            table.collect(file);
+        }
+        let http = table
+            .http_proxy_settings
+            .take()
+            .unwrap_or_else(ProxyKeepaliveSettings::nginx_defaults);
+        for index in std::mem::take(&mut table.http_included_references) {
+            let reference = &mut table.upstream_references[index];
+            reference.proxy_settings = reference.proxy_settings.take().map(|s| s.or(&http));
+            reference.has_resolver = reference.has_resolver.or(table.http_has_resolver);
+        }
+        let http = table.http_cache_settings.unwrap_or_default();
+        for index in std::mem::take(&mut table.http_included_cache_uses) {
+            let cache = &mut table.proxy_cache_uses[index];
+            cache.has_cache_key = cache.has_cache_key.or(http.key);
+            cache.has_cache_valid = cache.has_cache_valid.or(http.valid);
+        }
+        table
+    }
+
+    /// What `file` contributes to a symbol table, as text.
+    ///
+    /// [`CrossFileRule`]s only see the symbol table, so an edit that leaves
+    /// the summaries of all files unchanged (a comment, a directive no
+    /// symbol is collected from) leaves their diagnostics unchanged too.
+    /// The summary covers the symbols' positions, but not the settings the
+    /// file inherits from the files including it.
+    pub fn file_summary(file: &SourceFile<'_>) -> String {
+        let mut table = Self::default();
+        table.collect(file);
+        format!("{:?}", table)
+    }
+
+    fn collect(&mut self, file: &SourceFile<'_>) {
+        self.variables.push((
+            file.path.to_path_buf(),
+            VariableTable::from_config(file.config),
+        ));
+
+        let include_context: Vec<&str> = file.config.include_context.names().collect();
+        let outer = if include_context.is_empty() {
+            ProxyKeepaliveSettings::nginx_defaults()
+        } else {
+            ProxyKeepaliveSettings::default()
+        };
+        let mut proxy_settings = HashMap::new();
+        self.collect_proxy_settings(
+            &file.config.items,
+            &outer,
+            include_context.is_empty(),
+            &mut proxy_settings,
+        );
+        let mut resolvers = HashMap::new();
+        self.collect_resolvers(
+            &file.config.items,
+            include_context.is_empty().then_some(false),
+            include_context.is_empty(),
+            &mut resolvers,
+        );
+
+        let mut cache_settings = HashMap::new();
+        let outer = if include_context.is_empty() {
+            CacheSettings {
+                key: Some(false),
+                valid: Some(false),
+            }
+        } else {
+            CacheSettings::default()
+        };
+        self.collect_cache_settings(
+            &file.config.items,
+            outer,
+            include_context.is_empty(),
+            &mut cache_settings,
+        );
+
+        for ctx in file.config.all_directives_with_context() {
+            let directive = ctx.directive;
+            let namespace = UpstreamNamespace::from_parents(&ctx.parent_stack);
+
+            if directive.is("upstream") && directive.block.is_some() {
+                if let Some(name) = directive.first_arg() {
+                    self.upstreams.push(UpstreamDefinition {
+                        name: name.to_string(),
+                        namespace,
+                        has_keepalive: directive
+                            .block
+                            .iter()
+                            .flat_map(|b| b.directives())
+                            .any(|d| d.is("keepalive")),
+                        server_ports: directive
+                            .block
+                            .iter()
+                            .flat_map(|b| b.directives())
+                            .filter(|d| d.is("server"))
+                            .filter_map(|d| server_port(d.first_arg()?))
+                            .collect(),
+                        path: file.path.to_path_buf(),
+                        line: directive.span.start.line,
+                        column: directive.span.start.column,
+                    });
+                }
+                continue;
+            }
+
+            if directive.is("server") && ctx.parent() == Some("http") {
+                if let Some(block) = &directive.block {
+                    let listens: Vec<_> = block.directives().filter(|d| d.is("listen")).collect();
+                    let redirect_location = block.directives().find(|d| {
+                        d.is("location")
+                            && d.args.len() == 1
+                            && d.first_arg_is("/")
+                            && d.block
+                                .as_ref()
+                                .is_some_and(|b| find_https_redirect(b).is_some())
+                    });
+                    let https_redirect = find_https_redirect(block)
+                        .map(|redirect| (redirect, false))
+                        .or_else(|| {
+                            let location = redirect_location?.block.as_ref()?;
+                            Some((find_https_redirect(location)?, true))
+                        })
+                        .map(|((d, host), in_location)| HttpsRedirect {
+                            directive: d.name.clone(),
+                            host,
+                            in_location,
+                            line: d.span.start.line,
+                            column: d.span.start.column,
+                        });
+                    let redirect_block = https_redirect
+                        .as_ref()
+                        .filter(|r| r.in_location)
+                        .and(redirect_location);
+                    self.http_servers.push(HttpServer {
+                        has_server_name: block.directives().any(|d| d.is("server_name")),
+                        server_names: block
+                            .directives()
+                            .filter(|d| d.is("server_name"))
+                            .flat_map(|d| d.args.iter().map(|a| a.as_str().to_string()))
+                            .collect(),
+                        is_default: listens.iter().any(|d| is_default_listen(d)),
+                        default_listens: listens
+                            .iter()
+                            .filter(|d| is_default_listen(d))
+                            .filter_map(|d| {
+                                let address = d.args.first()?;
+                                // Only known at runtime
+                                if address.raw.contains('$') {
+                                    return None;
+                                }
+                                let mut address = normalize_listen_address(address.as_str());
+                                if d.args.iter().any(|a| a.as_str() == "quic") {
+                                    address.push_str(" quic");
+                                }
+                                Some(DefaultListen {
+                                    address,
+                                    line: d.span.start.line,
+                                    column: d.span.start.column,
+                                })
+                            })
+                            .collect(),
+                        listens_plain: listens.is_empty()
+                            || listens.iter().any(|d| {
+                                !d.args.iter().any(|a| matches!(a.as_str(), "ssl" | "quic"))
+                            }),
+                        listens_tls: listens
+                            .iter()
+                            .any(|d| d.args.iter().any(|a| a.as_str() == "ssl")),
+                        locations: block
+                            .directives()
+                            .filter(|d| d.is("location"))
+                            .filter(|d| !redirect_block.is_some_and(|l| std::ptr::eq(*d, l)))
+                            .map(|d| ServerLocation {
+                                uri: d
+                                    .args
+                                    .iter()
+                                    .map(|a| a.as_str())
+                                    .collect::<Vec<_>>()
+                                    .join(" "),
+                                serves_content: d.block.as_ref().is_some_and(serves_content),
+                                line: d.span.start.line,
+                                column: d.span.start.column,
+                            })
+                            .collect(),
+                        https_redirect,
+                        path: file.path.to_path_buf(),
+                        line: directive.span.start.line,
+                        column: directive.span.start.column,
+                    });
+                }
+                continue;
+            }
+
+            if directive.is("log_format") {
+                if let Some(name) = directive.first_arg() {
+                    let mut strings = directive.args[1..].iter().map(|a| a.as_str()).peekable();
+                    let escape = strings
+                        .next_if(|arg| arg.starts_with("escape="))
+                        .map(|arg| arg["escape=".len()..].to_string());
+                    self.log_formats.push(LogFormatDefinition {
+                        name: name.to_string(),
+                        namespace,
+                        escape,
+                        is_json: strings
+                            .next()
+                            .is_some_and(|format| format.trim_start().starts_with('{')),
+                        path: file.path.to_path_buf(),
+                        line: directive.span.start.line,
+                        column: directive.span.start.column,
+                    });
+                }
+                continue;
+            }
+
+            if directive.is("access_log") {
+                self.access_logs.push(AccessLog {
+                    format: directive.args.get(1).map(|a| a.as_str().to_string()),
+                    is_off: directive.first_arg_is("off"),
+                    is_http_level: ctx.parent() == Some("http"),
+                    namespace,
+                    path: file.path.to_path_buf(),
+                    line: directive.span.start.line,
+                    column: directive.span.start.column,
+                });
+                continue;
+            }
+
+            if directive.is("proxy_cache_path") {
+                if let Some(zone) = directive
+                    .args
+                    .iter()
+                    .find_map(|a| a.as_str().strip_prefix("keys_zone="))
+                {
+                    let name = zone.split(':').next().unwrap_or(zone);
+                    self.proxy_cache_zones.push(name.to_string());
+                }
+                continue;
+            }
+
+            if directive.is("proxy_cache") && !directive.first_arg_is("off") {
+                let settings = cache_settings
+                    .remove(&directive.span.start.offset)
+                    .unwrap_or_default();
+                if include_context == ["http"] {
+                    self.http_included_cache_uses
+                        .push(self.proxy_cache_uses.len());
+                }
+                self.proxy_cache_uses.push(ProxyCacheUse {
+                    zone: first_word(directive).filter(|zone| !zone.contains('$')),
+                    has_cache_key: settings.key,
+                    has_cache_valid: settings.valid,
+                    path: file.path.to_path_buf(),
+                    line: directive.span.start.line,
+                    column: directive.span.start.column,
+                });
+                continue;
+            }
+
+            if let Some((kind, declares)) = limit_zone_directive(directive) {
+                let name = if declares || kind == LimitZoneKind::Req {
+                    directive
+                        .args
+                        .iter()
+                        .find_map(|a| a.as_str().strip_prefix("zone="))
+                        .map(|zone| zone.split(':').next().unwrap_or(zone))
+                } else {
+                    directive.first_arg()
+                };
+                if let Some(name) = name {
+                    let zone = LimitZone {
+                        kind,
+                        name: name.to_string(),
+                        namespace,
+                        path: file.path.to_path_buf(),
+                        line: directive.span.start.line,
+                        column: directive.span.start.column,
+                    };
+                    if declares {
+                        self.limit_zones.push(zone);
+                    } else {
+                        self.limit_zone_references.push(zone);
+                    }
+                }
+                continue;
+            }
+
+            if !UPSTREAM_REFERENCE_DIRECTIVES.contains(&directive.name.as_str()) {
+                continue;
+            }
+            let Some(target) = first_word(directive) else {
+                continue;
+            };
+            let Some((host, has_port)) = upstream_target_host(&target) else {
+                continue;
+            };
+            let proxy_settings = proxy_settings.remove(&directive.span.start.offset);
+            if include_context == ["http"] {
+                self.http_included_references
+                    .push(self.upstream_references.len());
+            }
+            self.upstream_references.push(UpstreamReference {
+                is_dynamic: host.contains('$'),
+                has_variables: target.contains('$'),
+                has_resolver: resolvers
+                    .get(&directive.span.start.offset)
+                    .copied()
+                    .flatten(),
+                proxy_settings,
+                host: host.to_string(),
+                directive: directive.name.clone(),
+                scheme: target
+                    .split_once("://")
+                    .map(|(scheme, _)| scheme.to_ascii_lowercase()),
+                namespace,
+                has_port,
+                path: file.path.to_path_buf(),
+                line: directive.span.start.line,
+                column: directive.span.start.column,
+            });
+        }
+    }
+
+    /// Record the settings in effect at each `proxy_pass` in `items`, keyed
+    /// by the directive's offset. `top_level` is true for the items of a file
+    /// that is not included, whose `http` block settings are remembered.
+    fn collect_proxy_settings(
+        &mut self,
+        items: &[ConfigItem],
+        inherited: &ProxyKeepaliveSettings,
+        top_level: bool,
+        out: &mut HashMap<usize, ProxyKeepaliveSettings>,
+    ) {
+        let settings = inherited.enter(items);
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if let Some(block) = &directive.block {
+                if top_level && directive.is("http") && self.http_proxy_settings.is_none() {
+                    self.http_proxy_settings = Some(settings.enter(&block.items));
+                }
+                self.collect_proxy_settings(&block.items, &settings, false, out);
+            } else if directive.is("proxy_pass") {
+                out.insert(directive.span.start.offset, settings.clone());
+            }
+        }
+    }
+
+    /// Record whether a `resolver` is in effect at each upstream-capable
+    /// `*_pass` in `items`, keyed by the directive's offset. `inherited` is
+    /// `None` when it is not known; see `collect_proxy_settings` for
+    /// `top_level`.
+    fn collect_resolvers(
+        &mut self,
+        items: &[ConfigItem],
+        inherited: Option<bool>,
+        top_level: bool,
+        out: &mut HashMap<usize, Option<bool>>,
+    ) {
+        let has_resolver = items
+            .iter()
+            .any(|item| matches!(item, ConfigItem::Directive(d) if d.is("resolver")));
+        let in_effect = if has_resolver { Some(true) } else { inherited };
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if let Some(block) = &directive.block {
+                if top_level && directive.is("http") && self.http_has_resolver.is_none() {
+                    self.http_has_resolver = Some(block.directives().any(|d| d.is("resolver")));
+                }
+                self.collect_resolvers(&block.items, in_effect, false, out);
+            } else if UPSTREAM_REFERENCE_DIRECTIVES.contains(&directive.name.as_str()) {
+                out.insert(directive.span.start.offset, in_effect);
+            }
+        }
+    }
+
+    /// Record the cache settings in effect at each `proxy_cache` in `items`,
+    /// keyed by the directive's offset; see `collect_proxy_settings` for
+    /// `top_level`.
+    fn collect_cache_settings(
+        &mut self,
+        items: &[ConfigItem],
+        inherited: CacheSettings,
+        top_level: bool,
+        out: &mut HashMap<usize, CacheSettings>,
+    ) {
+        let settings = inherited.enter(items);
+        for item in items {
+            let ConfigItem::Directive(directive) = item else {
+                continue;
+            };
+            if let Some(block) = &directive.block {
+                if top_level && directive.is("http") && self.http_cache_settings.is_none() {
+                    self.http_cache_settings = Some(settings.enter(&block.items));
+                }
+                self.collect_cache_settings(&block.items, settings, false, out);
+            } else if directive.is("proxy_cache") {
+                out.insert(directive.span.start.offset, settings);
+            }
+        }
+    }
+
+    /// All `upstream` blocks, in file order.
+    pub fn upstreams(&self) -> &[UpstreamDefinition] {
+        &self.upstreams
+    }
+
+    /// All upstream-capable `*_pass` targets, in file order.
+    pub fn upstream_references(&self) -> &[UpstreamReference] {
+        &self.upstream_references
+    }
+
+    /// All `server` blocks of the `http` context, in file order.
+    pub fn http_servers(&self) -> &[HttpServer] {
+        &self.http_servers
+    }
+
+    /// All `log_format` definitions, in file order.
+    pub fn log_formats(&self) -> &[LogFormatDefinition] {
+        &self.log_formats
+    }
+
+    /// All `access_log` directives, in file order.
+    pub fn access_logs(&self) -> &[AccessLog] {
+        &self.access_logs
+    }
+
+    /// Whether a log format with the given name is defined in `namespace`.
+    pub fn has_log_format(&self, name: &str, namespace: UpstreamNamespace) -> bool {
+        self.log_formats
+            .iter()
+            .any(|f| f.namespace == namespace && f.name == name)
+    }
+
+    /// All `limit_req_zone` / `limit_conn_zone` declarations, in file order.
+    pub fn limit_zones(&self) -> &[LimitZone] {
+        &self.limit_zones
+    }
+
+    /// All `limit_req` / `limit_conn` directives naming a zone, in file
+    /// order.
+    pub fn limit_zone_references(&self) -> &[LimitZone] {
+        &self.limit_zone_references
+    }
+
+    /// Whether a zone of `kind` with the given name is declared in
+    /// `namespace`.
+    pub fn has_limit_zone(
+        &self,
+        kind: LimitZoneKind,
+        name: &str,
+        namespace: UpstreamNamespace,
+    ) -> bool {
+        self.limit_zones
+            .iter()
+            .any(|z| z.kind == kind && z.namespace == namespace && z.name == name)
+    }
+
+    /// Whether any `limit_req` / `limit_conn` in `namespace` uses the zone
+    /// of `kind` with the given name.
+    pub fn is_limit_zone_referenced(
+        &self,
+        kind: LimitZoneKind,
+        name: &str,
+        namespace: UpstreamNamespace,
+    ) -> bool {
+        self.limit_zone_references
+            .iter()
+            .any(|z| z.kind == kind && z.namespace == namespace && z.name == name)
+    }
+
+    /// All `proxy_cache` directives enabling a cache, in file order.
+    pub fn proxy_cache_uses(&self) -> &[ProxyCacheUse] {
+        &self.proxy_cache_uses
+    }
+
+    /// Whether a `proxy_cache_path` declares the zone `name`.
+    pub fn has_proxy_cache_zone(&self, name: &str) -> bool {
+        self.proxy_cache_zones.iter().any(|zone| zone == name)
+    }
+
+    /// Per-file variable tables, in file order.
+    pub fn variable_tables(&self) -> &[(PathBuf, VariableTable)] {
+        &self.variables
+    }
+
+    /// Whether any file defines the variable `name` (without `$`).
+    pub fn is_variable_defined(&self, name: &str) -> bool {
+        self.variables.iter().any(|(_, t)| t.is_defined(name))
+    }
+
+    /// Whether any file uses the variable `name` (without `$`).
+    pub fn is_variable_used(&self, name: &str) -> bool {
+        self.variables.iter().any(|(_, t)| t.is_used(name))
+    }
+
+    /// Whether an upstream with the given name is defined in `namespace`.
+    pub fn has_upstream(&self, name: &str, namespace: UpstreamNamespace) -> bool {
+        self.upstreams
+            .iter()
+            .any(|u| u.namespace == namespace && u.name == name)
+    }
+
+    /// Whether any target in `namespace` names `name`, or is built from a
+    /// variable and could therefore resolve to any upstream at runtime.
+    pub fn is_upstream_referenced(&self, name: &str, namespace: UpstreamNamespace) -> bool {
+        self.upstream_references
+            .iter()
+            .filter(|r| r.namespace == namespace)
+            .any(|r| r.is_dynamic || r.host == name)
+    }
+}
+
+/// The zone kind of a directive declaring (`true`) or using (`false`) a
+/// limit zone
+fn limit_zone_directive(directive: &Directive) -> Option<(LimitZoneKind, bool)> {
+    [LimitZoneKind::Req, LimitZoneKind::Conn]
+        .into_iter()
+        .find_map(|kind| {
+            if directive.is(kind.declaring_directive()) {
+                Some((kind, true))
+            } else if directive.is(kind.using_directive()) {
+                Some((kind, false))
+            } else {
+                None
+            }
+        })
+}
+
+/// Reassemble the first whitespace-separated word of a directive.
+fn first_word(directive: &Directive) -> Option<String> {
+    words(directive).into_iter().next()
+}
+
+/// Reassemble the whitespace-separated words of a directive (see
+/// [`Directive::words`]), without the quotes of quoted ones.
+fn words(directive: &Directive) -> Vec<String> {
+    directive
+        .words()
+        .into_iter()
+        .map(|word| {
+            let quoted = word.len() >= 2
+                && (word.starts_with('"') && word.ends_with('"')
+                    || word.starts_with('\'') && word.ends_with('\''));
+            if quoted {
+                word[1..word.len() - 1].to_string()
+            } else {
+                word
+            }
+        })
+        .collect()
+}
+
+/// The first `return` or catch-all `rewrite` directly in `block` that
+/// redirects to `https://`, with the host it redirects to
+fn find_https_redirect(block: &Block) -> Option<(&Directive, RedirectHost)> {
+    block.directives().find_map(|d| {
+        let words = words(d);
+        let target = match (d.name.as_str(), words.as_slice()) {
+            ("return", [code, target])
+                if matches!(code.as_str(), "301" | "302" | "303" | "307" | "308") =>
+            {
+                target
+            }
+            ("return", [target]) => target,
+            ("rewrite", [pattern, target, ..])
+                if CATCH_ALL_PATTERNS.contains(&pattern.as_str()) =>
+            {
+                target
+            }
+            _ => return None,
+        };
+        let authority = target.strip_prefix("https://")?;
+        let authority = authority.split('/').next().unwrap_or(authority);
+        let authority = &authority[..uri_variable_start(authority)];
+        let host = authority.split(':').next().unwrap_or(authority);
+        Some((d, redirect_host(host)))
+    })
+}
+
+fn redirect_host(host: &str) -> RedirectHost {
+    match host.strip_prefix('$') {
+        Some(name) => {
+            let name = name.trim_start_matches('{').trim_end_matches('}');
+            if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                RedirectHost::Variable(name.to_string())
+            } else {
+                RedirectHost::Other
+            }
+        }
+        None if !host.is_empty() && !host.contains('$') => {
+            RedirectHost::Literal(host.to_ascii_lowercase())
+        }
+        None => RedirectHost::Other,
+    }
+}
+
+/// Whether a `listen` marks its server as `default_server` (`default` is
+/// the old name of the parameter)
+fn is_default_listen(listen: &Directive) -> bool {
+    listen
+        .args
+        .iter()
+        .any(|a| matches!(a.as_str(), "default_server" | "default"))
+}
+
+/// Whether `block` or a block nested in it has a content handler
+fn serves_content(block: &Block) -> bool {
+    block.directives().any(|d| {
+        CONTENT_DIRECTIVES.iter().any(|name| d.is(name))
+            || d.block.as_ref().is_some_and(serves_content)
+    })
+}
+
+/// Extract the host part of a `*_pass` target, returning it together with
+/// whether an explicit port follows it.
+///
+/// Returns `None` for targets that can never name an upstream block:
+/// UNIX sockets and bracketed IPv6 literals.
+fn upstream_target_host(target: &str) -> Option<(&str, bool)> {
+    let rest = match target.find("://") {
+        Some(idx) => &target[idx + 3..],
+        None => target,
+    };
+    if rest.starts_with("unix:") || rest.starts_with('[') {
+        return None;
+    }
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let authority = &authority[..uri_variable_start(authority)];
+    match authority.split_once(':') {
+        Some((host, _port)) => Some((host, true)),
+        None => Some((authority, false)),
+    }
+}
+
+/// Variables expanding to the request URI or its parts: in a target such as
+/// `http://backend$request_uri` the host ends where they start.
+const URI_VARIABLES: &[&str] = &[
+    "request_uri",
+    "uri",
+    "document_uri",
+    "is_args",
+    "args",
+    "query_string",
+];
+
+/// Offset of the first URI variable in `authority`, or its length
+fn uri_variable_start(authority: &str) -> usize {
+    authority
+        .match_indices('$')
+        .map(|(i, _)| i)
+        .find(|&i| {
+            let name: String = authority[i + 1..]
+                .trim_start_matches('{')
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect();
+            URI_VARIABLES.contains(&name.as_str())
+        })
+        .unwrap_or(authority.len())
+}
+
+/// Port of an upstream `server` address, 80 when it has none. `None` for
+/// UNIX sockets and unparsable ports.
+fn server_port(address: &str) -> Option<u16> {
+    if address.starts_with("unix:") {
+        return None;
+    }
+    // Skip the brackets of an IPv6 literal
+    let host_end = if address.starts_with('[') {
+        address.find(']')?
+    } else {
+        0
+    };
+    match address[host_end..].rsplit_once(':') {
+        Some((_, port)) => port.parse().ok(),
+        None => Some(80),
+    }
+}
+
+/// A lint rule that needs to see every file of a config tree at once.
+///
+/// Cross-file rules are run by
+/// [`Linter::lint_cross_file`](crate::linter::Linter::lint_cross_file) after the
+/// per-file pass and report diagnostics against whichever file the offending
+/// directive lives in.
+pub trait CrossFileRule: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn category(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// Check the symbol table, returning each error with the file it belongs to
+    fn check(&self, symbols: &SymbolTable) -> Vec<(PathBuf, LintError)>;
+}
+
+/// Run `rule` over `files` of `(path, context, source)`, for the tests of
+/// cross-file rules. The context is parsed like a `--context` value; an
+/// empty one makes a top-level file.
+#[cfg(test)]
+pub(crate) fn check_cross_file(
+    rule: &dyn CrossFileRule,
+    files: &[(&str, &str, &str)],
+) -> Vec<(PathBuf, LintError)> {
+    let configs: Vec<Config> = files
+        .iter()
+        .map(|(_, context, src)| {
+            let mut config = crate::parser::parse_string(src).unwrap();
+            config.include_context = crate::parser::context::IncludeContext::parse(context);
+            config
+        })
+        .collect();
+    let sources: Vec<SourceFile> = files
+        .iter()
+        .zip(&configs)
+        .map(|((path, _, src), config)| SourceFile {
+            path: Path::new(path),
+            config,
+            content: src,
+        })
+        .collect();
+    rule.check(&SymbolTable::build(&sources))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_string;
+
+    fn build(files: &[(&str, &str)]) -> SymbolTable {
+        let configs: Vec<(PathBuf, Config)> = files
+            .iter()
+            .map(|(path, src)| (PathBuf::from(path), parse_string(src).unwrap()))
+            .collect();
+        let sources: Vec<SourceFile> = configs
+            .iter()
+            .zip(files)
+            .map(|((path, config), (_, src))| SourceFile {
+                path,
+                config,
+                content: src,
+            })
+            .collect();
+        SymbolTable::build(&sources)
+    }
+
+    #[test]
+    fn test_target_host() {
+        assert_eq!(
+            upstream_target_host("http://backend"),
+            Some(("backend", false))
+        );
+        assert_eq!(
+            upstream_target_host("http://backend/api/"),
+            Some(("backend", false))
+        );
+        assert_eq!(
+            upstream_target_host("grpcs://backend:443"),
+            Some(("backend", true))
+        );
+        assert_eq!(
+            upstream_target_host("http://backend$request_uri"),
+            Some(("backend", false))
+        );
+        assert_eq!(
+            upstream_target_host("http://backend:8080${uri}"),
+            Some(("backend", true))
+        );
+        assert_eq!(
+            upstream_target_host("http://$backend_host"),
+            Some(("$backend_host", false))
+        );
+        assert_eq!(upstream_target_host("php_fpm"), Some(("php_fpm", false)));
+        assert_eq!(upstream_target_host("unix:/run/php.sock"), None);
+        assert_eq!(upstream_target_host("http://unix:/run/app.sock:/"), None);
+        assert_eq!(upstream_target_host("http://[::1]:8080"), None);
+    }
+
+    #[test]
+    fn test_server_port() {
+        assert_eq!(server_port("127.0.0.1:8080"), Some(8080));
+        assert_eq!(server_port("backend.example.com"), Some(80));
+        assert_eq!(server_port("[::1]"), Some(80));
+        assert_eq!(server_port("[::1]:443"), Some(443));
+        assert_eq!(server_port("unix:/run/app.sock"), None);
+    }
+
+    #[test]
+    fn test_collects_across_files() {
+        let table = build(&[
+            (
+                "nginx.conf",
+                "http {\n    upstream backend {\n        server 127.0.0.1:8080;\n    }\n}\n",
+            ),
+            (
+                "conf.d/app.conf",
+                "server {\n    location / {\n        proxy_pass http://backend;\n    }\n}\n",
+            ),
+        ]);
+        assert_eq!(table.upstreams().len(), 1);
+        assert_eq!(table.upstream_references().len(), 1);
+        assert!(table.has_upstream("backend", UpstreamNamespace::Http));
+        assert!(table.is_upstream_referenced("backend", UpstreamNamespace::Http));
+        assert_eq!(table.upstreams()[0].server_ports, [8080]);
+        assert_eq!(
+            table.upstream_references()[0].scheme.as_deref(),
+            Some("http")
+        );
+        assert_eq!(
+            table.upstream_references()[0].path,
+            PathBuf::from("conf.d/app.conf")
+        );
+    }
+
+    #[test]
+    fn test_stream_namespace_is_separate() {
+        let table = build(&[(
+            "nginx.conf",
+            "stream {\n    upstream db {\n        server 10.0.0.1:5432;\n    }\n}\nhttp {\n    server {\n        location / {\n            proxy_pass http://db;\n        }\n    }\n}\n",
+        )]);
+        assert!(table.has_upstream("db", UpstreamNamespace::Stream));
+        assert!(!table.has_upstream("db", UpstreamNamespace::Http));
+        assert!(!table.is_upstream_referenced("db", UpstreamNamespace::Stream));
+    }
+
+    #[test]
+    fn test_log_formats_and_access_logs() {
+        let table = build(&[
+            (
+                "nginx.conf",
+                "http {\n    log_format json escape=json '{\"uri\":\"$uri\"}';\n    log_format main '$remote_addr - $request';\n    access_log off;\n}\n",
+            ),
+            (
+                "conf.d/app.conf",
+                "server {\n    access_log /var/log/nginx/app.log json buffer=32k;\n}\n",
+            ),
+        ]);
+        let formats = table.log_formats();
+        assert_eq!(formats.len(), 2);
+        assert_eq!(formats[0].escape.as_deref(), Some("json"));
+        assert!(formats[0].is_json);
+        assert!(formats[1].escape.is_none() && !formats[1].is_json);
+        assert!(table.has_log_format("main", UpstreamNamespace::Http));
+        assert!(!table.has_log_format("main", UpstreamNamespace::Stream));
+
+        let logs = table.access_logs();
+        assert_eq!(logs.len(), 2);
+        assert!(logs[0].is_off && logs[0].is_http_level && logs[0].format.is_none());
+        assert_eq!(logs[1].format.as_deref(), Some("json"));
+        assert!(!logs[1].is_http_level);
+    }
+
+    #[test]
+    fn test_https_redirects() {
+        let table = build(&[(
+            "nginx.conf",
+            "http {\n    server {\n        listen 80;\n        server_name a.example.com b.example.com;\n        location /static/ {\n            root /srv;\n        }\n        location / {\n            return 301 https://${host}:8443$request_uri;\n        }\n    }\n    server {\n        listen 443 ssl;\n        listen 443 quic;\n        rewrite ^ \"https://www.Example.com$request_uri\" permanent;\n    }\n}\n",
+        )]);
+        let [plain, tls] = table.http_servers() else {
+            panic!("expected two servers");
+        };
+        assert_eq!(plain.server_names, ["a.example.com", "b.example.com"]);
+        assert!(plain.listens_plain && !plain.listens_tls);
+        let redirect = plain.https_redirect.as_ref().unwrap();
+        assert_eq!(redirect.host, RedirectHost::Variable("host".to_string()));
+        assert!(redirect.in_location);
+        assert_eq!(redirect.line, 9);
+        // The redirecting location is not listed
+        assert_eq!(plain.locations.len(), 1);
+        assert!(plain.locations[0].serves_content);
+
+        assert!(!tls.listens_plain && tls.listens_tls);
+        let redirect = tls.https_redirect.as_ref().unwrap();
+        assert_eq!(
+            redirect.host,
+            RedirectHost::Literal("www.example.com".to_string())
+        );
+        assert!(!redirect.in_location);
+    }
+
+    #[test]
+    fn test_limit_zones() {
+        let table = build(&[
+            (
+                "nginx.conf",
+                "http {\n    limit_req_zone $binary_remote_addr zone=per_ip:10m rate=10r/s;\n    limit_conn_zone $binary_remote_addr zone=addr:10m;\n}\nstream {\n    limit_conn_zone $binary_remote_addr zone=addr:1m;\n}\n",
+            ),
+            (
+                "conf.d/app.conf",
+                "server {\n    limit_req zone=per_ip burst=20 nodelay;\n    limit_req_status 429;\n    limit_conn addr 10;\n}\n",
+            ),
+        ]);
+        let zones = table.limit_zones();
+        assert_eq!(zones.len(), 3);
+        assert_eq!(
+            (zones[0].kind, zones[0].name.as_str()),
+            (LimitZoneKind::Req, "per_ip")
+        );
+        assert_eq!(zones[2].namespace, UpstreamNamespace::Stream);
+
+        let references = table.limit_zone_references();
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[1].name, "addr");
+        assert!(table.has_limit_zone(LimitZoneKind::Conn, "addr", UpstreamNamespace::Http));
+        assert!(!table.has_limit_zone(LimitZoneKind::Req, "addr", UpstreamNamespace::Http));
+        assert!(table.is_limit_zone_referenced(
+            LimitZoneKind::Req,
+            "per_ip",
+            UpstreamNamespace::Http
+        ));
+        assert!(!table.is_limit_zone_referenced(
+            LimitZoneKind::Conn,
+            "addr",
+            UpstreamNamespace::Stream
+        ));
+    }
+
+    #[test]
+    fn test_proxy_cache() {
+        let table = build(&[
+            (
+                "nginx.conf",
+                "http {\n    proxy_cache_path /var/cache/nginx keys_zone=pages:10m;\n    proxy_cache_valid 200 10m;\n    server {\n        proxy_cache_key $host$request_uri;\n        location / {\n            proxy_cache pages;\n        }\n        location /api/ {\n            proxy_cache off;\n        }\n    }\n}\n",
+            ),
+            ("conf.d/app.conf", "server {\n    proxy_cache $zone;\n}\n"),
+        ]);
+        assert!(table.has_proxy_cache_zone("pages"));
+        assert!(!table.has_proxy_cache_zone("api"));
+
+        let uses = table.proxy_cache_uses();
+        assert_eq!(uses.len(), 2);
+        assert_eq!(uses[0].zone.as_deref(), Some("pages"));
+        assert_eq!(
+            (uses[0].has_cache_key, uses[0].has_cache_valid),
+            (Some(true), Some(true))
+        );
+        assert!(uses[1].zone.is_none());
+        assert_eq!(
+            (uses[1].has_cache_key, uses[1].has_cache_valid),
+            (Some(false), Some(false))
+        );
+    }
+
+    #[test]
+    fn test_variables_across_files() {
+        let table = build(&[
+            (
+                "nginx.conf",
+                "http {\n    map $http_upgrade $connection_upgrade {\n        default upgrade;\n    }\n}\n",
+            ),
+            (
+                "conf.d/app.conf",
+                "server {\n    proxy_set_header Connection $connection_upgrade;\n}\n",
+            ),
+        ]);
+        assert_eq!(table.variable_tables().len(), 2);
+        assert!(table.is_variable_defined("connection_upgrade"));
+        assert!(table.is_variable_used("connection_upgrade"));
+        assert!(!table.is_variable_defined("http_upgrade"));
+    }
+
+    #[test]
+    fn test_variable_target_references_everything() {
+        let table = build(&[(
+            "nginx.conf",
+            "http {\n    upstream a {\n        server 127.0.0.1:1;\n    }\n    server {\n        location / {\n            proxy_pass http://$backend;\n        }\n    }\n}\n",
+        )]);
+        assert!(table.is_upstream_referenced("a", UpstreamNamespace::Http));
+        assert!(!table.upstream_references()[0].looks_like_upstream_name());
+    }
+
+    #[test]
+    fn test_file_summary() {
+        let summary = |src: &str| {
+            let config = parse_string(src).unwrap();
+            SymbolTable::file_summary(&SourceFile {
+                path: Path::new("upstreams.conf"),
+                config: &config,
+                content: src,
+            })
+        };
+        let base = summary("upstream backend {\n    server 127.0.0.1:8080;\n}\n");
+        assert_eq!(
+            base,
+            summary("upstream backend { # app servers\n    server 127.0.0.1:8080;\n}\n")
+        );
+        assert_ne!(
+            base,
+            summary("upstream backend {\n    server 127.0.0.1:8081;\n}\n")
+        );
+        // Positions are part of the summary
+        assert_ne!(
+            base,
+            summary("\nupstream backend {\n    server 127.0.0.1:8080;\n}\n")
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! Template preprocessing (`--template-mode`).
+//!
+//! Configurations rendered by envsubst (the Docker official image's
+//! `/etc/nginx/templates`), Jinja or consul-template contain placeholders
+//! that are not nginx syntax. [`normalize`] replaces each placeholder with a
+//! synthetic variable `${template_NAME}` (see
+//! [`TEMPLATE_VARIABLE_PREFIX`]) and drops control statements such as
+//! `{% if %}` or `{{ range }}`, keeping the lines of the file where they
+//! were. [`Normalized::map_error`] maps the diagnostics found on the
+//! normalized text back to the template.
+//!
+//! ```
+//! use nginx_lint::template::{TemplateMode, normalize};
+//!
+//! let source = "server {\n    listen ${NGINX_PORT};\n}\n";
+//! let normalized = normalize(source, TemplateMode::Envsubst);
+//! assert_eq!(normalized.text(), "server {\n    listen ${template_NGINX_PORT};\n}\n");
+//!
+//! // `;` is at column 34 of the normalized text and column 25 of the template
+//! assert_eq!(normalized.original_position(2, 34), (2, 25));
+//! ```
+
+use crate::linter::{Fix, LintError};
+use crate::variables::TEMPLATE_VARIABLE_PREFIX;
+use nginx_lint_common::{compute_line_starts, normalize_line_fix};
+
+/// Template syntax of the linted files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateMode {
+    /// `${NAME}`, as substituted by `envsubst`
+    Envsubst,
+    /// `{{ expression }}`, with `{% statement %}` and `{# comment #}` dropped
+    Jinja,
+    /// Go templates as used by consul-template: `{{ expression }}`, with
+    /// `{{ range }}`, `{{ if }}`, `{{ end }}` and the other actions dropped
+    Consul,
+}
+
+impl TemplateMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TemplateMode::Envsubst => "envsubst",
+            TemplateMode::Jinja => "jinja",
+            TemplateMode::Consul => "consul",
+        }
+    }
+}
+
+/// Go template actions that produce no value
+const CONSUL_ACTIONS: &[&str] = &[
+    "if", "else", "end", "range", "with", "define", "template", "block", "break", "continue",
+];
+
+/// A placeholder of the template and what replaced it
+#[derive(Debug, Clone)]
+struct Segment {
+    /// Byte range in the normalized text
+    start: usize,
+    end: usize,
+    /// Byte range in the template
+    original_start: usize,
+    original_end: usize,
+}
+
+/// A template with its placeholders replaced, see [`normalize`]
+#[derive(Debug, Clone)]
+pub struct Normalized {
+    text: String,
+    segments: Vec<Segment>,
+    line_starts: Vec<usize>,
+    original_line_starts: Vec<usize>,
+}
+
+/// Replace the placeholders of `source` for `mode`.
+///
+/// Expressions become `${template_NAME}`, NAME being the letters and digits
+/// of the expression joined by `_` (`{{ key "app/port" }}` becomes
+/// `${template_key_app_port}`); statements and comments are removed. The
+/// newlines inside a placeholder are kept, so every line of the normalized
+/// text is the same line of the template. A placeholder that is not closed
+/// is left as it is.
+pub fn normalize(source: &str, mode: TemplateMode) -> Normalized {
+    let mut text = String::with_capacity(source.len());
+    let mut segments = Vec::new();
+    let mut copied = 0;
+    let mut from = 0;
+    while let Some((mut start, end, replacement)) = next_placeholder(source, from, mode) {
+        // A statement alone on its line takes its indentation with it, so
+        // no whitespace-only line is left behind
+        if replacement.is_none() {
+            let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+            if line_start >= copied
+                && source[line_start..start]
+                    .trim_matches([' ', '\t'])
+                    .is_empty()
+            {
+                start = line_start;
+            }
+        }
+        text.push_str(&source[copied..start]);
+        let normalized_start = text.len();
+        if let Some(expression) = replacement {
+            text.push_str("${");
+            text.push_str(&variable_name(expression));
+            text.push('}');
+        }
+        text.extend(source[start..end].chars().filter(|&c| c == '\n'));
+        segments.push(Segment {
+            start: normalized_start,
+            end: text.len(),
+            original_start: start,
+            original_end: end,
+        });
+        copied = end;
+        from = end;
+    }
+    text.push_str(&source[copied..]);
+
+    Normalized {
+        line_starts: compute_line_starts(&text),
+        original_line_starts: compute_line_starts(source),
+        text,
+        segments,
+    }
+}
+
+/// The next placeholder at or after `from`: its byte range and, for an
+/// expression, the expression (`None` for a statement or comment)
+fn next_placeholder(
+    source: &str,
+    mut from: usize,
+    mode: TemplateMode,
+) -> Option<(usize, usize, Option<&str>)> {
+    loop {
+        let start = from
+            + source[from..].find(if mode == TemplateMode::Envsubst {
+                "${"
+            } else {
+                "{"
+            })?;
+        let rest = &source[start..];
+        match mode {
+            TemplateMode::Envsubst => {
+                let name = &rest[2..rest.find('}').unwrap_or(2)];
+                if name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    return Some((start, start + name.len() + 3, Some(name)));
+                }
+            }
+            TemplateMode::Jinja => {
+                let close = match rest.as_bytes().get(1) {
+                    Some(b'{') => "}}",
+                    Some(b'%') => "%}",
+                    Some(b'#') => "#}",
+                    _ => {
+                        from = start + 1;
+                        continue;
+                    }
+                };
+                let Some(len) = closing(&rest[2..], close, close != "#}") else {
+                    from = start + 1;
+                    continue;
+                };
+                let len = len + 2;
+                let expression = trim_markers(&rest[2..len - 2], &['-', '+', '~']);
+                return Some((start, start + len, (close == "}}").then_some(expression)));
+            }
+            TemplateMode::Consul => {
+                if rest.starts_with("{{")
+                    && let Some(len) = closing(&rest[2..], "}}", true)
+                {
+                    let len = len + 2;
+                    let action = trim_markers(&rest[2..len - 2], &['-']);
+                    let keyword = action
+                        .split(|c: char| !c.is_ascii_alphabetic())
+                        .next()
+                        .unwrap_or_default();
+                    let is_statement = action.starts_with("/*")
+                        || CONSUL_ACTIONS.contains(&keyword)
+                        || (action.starts_with('$') && action.contains('='));
+                    return Some((start, start + len, (!is_statement).then_some(action)));
+                }
+            }
+        }
+        from = start + 1;
+    }
+}
+
+/// Length of `rest` up to and including `close`, skipping quoted strings
+/// when `quotes` is set; `None` when it is not closed
+fn closing(rest: &str, close: &str, quotes: bool) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        if quotes && matches!(c, '"' | '\'' | '`') {
+            quote = Some(c);
+        } else if rest[i..].starts_with(close) {
+            return Some(i + close.len());
+        }
+    }
+    None
+}
+
+/// `inner` without the whitespace control markers (`{{-`, `-}}`) and the
+/// surrounding whitespace
+fn trim_markers<'a>(inner: &'a str, markers: &[char]) -> &'a str {
+    let inner = inner.strip_prefix(markers).unwrap_or(inner);
+    let inner = inner.strip_suffix(markers).unwrap_or(inner);
+    inner.trim()
+}
+
+/// Synthetic variable name for a template expression
+fn variable_name(expression: &str) -> String {
+    let mut name = TEMPLATE_VARIABLE_PREFIX.to_string();
+    let mut separate = false;
+    for c in expression.chars() {
+        if c.is_ascii_alphanumeric() {
+            if separate && name.len() > TEMPLATE_VARIABLE_PREFIX.len() {
+                name.push('_');
+            }
+            name.push(c);
+            separate = false;
+        } else {
+            separate = true;
+        }
+    }
+    if name.len() == TEMPLATE_VARIABLE_PREFIX.len() {
+        name.push_str("value");
+    }
+    name
+}
+
+impl Normalized {
+    /// The text to parse and lint
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether the template had any placeholder
+    pub fn has_placeholders(&self) -> bool {
+        !self.segments.is_empty()
+    }
+
+    /// Offset in the template of `offset` in the normalized text; an offset
+    /// inside a replaced placeholder maps to the start of the placeholder
+    fn original_offset(&self, offset: usize) -> usize {
+        let mut shift = 0isize;
+        for segment in &self.segments {
+            if offset < segment.start {
+                break;
+            }
+            if offset < segment.end {
+                return segment.original_start;
+            }
+            shift = segment.original_end as isize - segment.end as isize;
+        }
+        offset.saturating_add_signed(shift)
+    }
+
+    /// Position in the template of `line`:`column` (1-based, byte columns)
+    /// of the normalized text
+    pub fn original_position(&self, line: usize, column: usize) -> (usize, usize) {
+        let Some(&line_start) = self.line_starts.get(line.saturating_sub(1)) else {
+            return (line, column);
+        };
+        let offset = self.original_offset(line_start + column.saturating_sub(1));
+        let line = self
+            .original_line_starts
+            .partition_point(|&start| start <= offset)
+            .max(1);
+        (line, offset - self.original_line_starts[line - 1] + 1)
+    }
+
+    /// Fixes applying to the template what `fixes` apply to the normalized
+    /// text; `None` when one of them touches a placeholder or would write
+    /// a synthetic variable into the file
+    fn original_fixes(&self, fixes: &[Fix]) -> Option<Vec<Fix>> {
+        fixes
+            .iter()
+            .map(|fix| {
+                let range_fix;
+                let fix = if fix.is_range_based() {
+                    fix
+                } else {
+                    range_fix = normalize_line_fix(fix, &self.text, &self.line_starts)?;
+                    &range_fix
+                };
+                let (start, end) = fix.start_offset.zip(fix.end_offset)?;
+                let touches_placeholder =
+                    self.segments.iter().any(|s| start < s.end && end > s.start);
+                if touches_placeholder || fix.new_text.contains(TEMPLATE_VARIABLE_PREFIX) {
+                    return None;
+                }
+                Some(
+                    Fix::replace_range(
+                        self.original_offset(start),
+                        self.original_offset(end),
+                        &fix.new_text,
+                    )
+                    .with_applicability(fix.applicability),
+                )
+            })
+            .collect()
+    }
+
+    /// `error`, found on the normalized text, with its position and fixes
+    /// in the template. Fixes that cannot be applied to the template are
+    /// dropped, as are alternatives.
+    pub fn map_error(&self, mut error: LintError) -> LintError {
+        if !self.has_placeholders() {
+            return error;
+        }
+        if let (Some(line), Some(column)) = (error.line, error.column) {
+            let (original_line, original_column) = self.original_position(line, column);
+            error.end_column = error.end_column.and_then(|end| {
+                let (end_line, end_column) = self.original_position(line, end);
+                (end_line == original_line).then_some(end_column)
+            });
+            error.line = Some(original_line);
+            error.column = Some(original_column);
+        }
+        error.fixes = self.original_fixes(&error.fixes).unwrap_or_default();
+        error.alternatives.retain_mut(|alternative| {
+            match self.original_fixes(&alternative.fixes) {
+                Some(fixes) => {
+                    alternative.fixes = fixes;
+                    true
+                }
+                None => false,
+            }
+        });
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linter::Severity;
+
+    #[test]
+    fn test_envsubst() {
+        let normalized = normalize(
+            "proxy_pass http://${BACKEND}:${PORT}/${1x} $host;\n",
+            TemplateMode::Envsubst,
+        );
+        assert_eq!(
+            normalized.text(),
+            "proxy_pass http://${template_BACKEND}:${template_PORT}/${1x} $host;\n"
+        );
+    }
+
+    #[test]
+    fn test_jinja() {
+        let normalized = normalize(
+            "{# site #}\nserver {\n    {%- if ssl %}\n    listen {{ port | default(443) }} ssl;\n    {% endif -%}\n    server_name {{- names|join(' ') -}};\n}\n",
+            TemplateMode::Jinja,
+        );
+        assert_eq!(
+            normalized.text(),
+            "\nserver {\n\n    listen ${template_port_default_443} ssl;\n\n    server_name ${template_names_join};\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_consul() {
+        let normalized = normalize(
+            "upstream app {\n{{ range service \"web\" }}\n    server {{ .Address }}:{{ .Port }};\n{{- end }}\n}\n{{ $x := \"}}\" }}{{/* note */}}{{ . }}\n",
+            TemplateMode::Consul,
+        );
+        assert_eq!(
+            normalized.text(),
+            "upstream app {\n\n    server ${template_Address}:${template_Port};\n\n}\n${template_value}\n"
+        );
+    }
+
+    #[test]
+    fn test_multiline_and_unclosed_placeholders() {
+        let normalized = normalize(
+            "{% if a\n   and b %}\nlisten {{ port\n}};\nroot {{ x",
+            TemplateMode::Jinja,
+        );
+        assert_eq!(
+            normalized.text(),
+            "\n\nlisten ${template_port}\n;\nroot {{ x"
+        );
+        assert_eq!(normalized.original_position(4, 1), (4, 3));
+    }
+
+    #[test]
+    fn test_positions_map_back() {
+        let source = "server {\n    listen ${PORT} ${OPTS};\n    root /srv;\n}\n";
+        let normalized = normalize(source, TemplateMode::Envsubst);
+        // Inside the first placeholder
+        assert_eq!(normalized.original_position(2, 16), (2, 12));
+        // After both placeholders
+        let semicolon = normalized.text().lines().nth(1).unwrap().find(';').unwrap() + 1;
+        assert_eq!(normalized.original_position(2, semicolon), (2, 27));
+        assert_eq!(normalized.original_position(3, 5), (3, 5));
+    }
+
+    #[test]
+    fn test_map_error_fixes() {
+        let source = "server {\n    listen ${PORT};  \n    root /srv ;\n}\n";
+        let normalized = normalize(source, TemplateMode::Envsubst);
+        let text = normalized.text();
+        let error = |line: usize, column: usize, start: usize, end: usize, new_text: &str| {
+            LintError::new("rule", "style", "problem", Severity::Warning)
+                .with_location(line, column)
+                .with_fix(Fix::replace_range(start, end, new_text))
+        };
+
+        // Trailing whitespace after the placeholder
+        let start = text.find(";  ").unwrap() + 1;
+        let mapped = normalized.map_error(error(2, 29, start, start + 2, ""));
+        assert_eq!((mapped.line, mapped.column), (Some(2), Some(20)));
+        let (fixed, _) = nginx_lint_common::apply_fixes_to_content(
+            source,
+            &mapped.fixes.iter().collect::<Vec<_>>(),
+        );
+        assert_eq!(fixed, "server {\n    listen ${PORT};\n    root /srv ;\n}\n");
+
+        // A fix rewriting the placeholder is dropped
+        let start = text.find("${").unwrap();
+        let mapped = normalized.map_error(error(2, 12, start, start + 4, "80"));
+        assert!(mapped.fixes.is_empty());
+
+        // Line-based fixes are mapped too
+        #[allow(deprecated)]
+        let line_fix = LintError::new("rule", "style", "problem", Severity::Warning)
+            .with_location(3, 14)
+            .with_fix(Fix::replace(3, "/srv ;", "/srv;"));
+        let mapped = normalized.map_error(line_fix);
+        let (fixed, _) = nginx_lint_common::apply_fixes_to_content(
+            source,
+            &mapped.fixes.iter().collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            fixed,
+            "server {\n    listen ${PORT};  \n    root /srv;\n}\n"
+        );
+    }
+}
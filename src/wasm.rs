@@ -5,9 +5,9 @@
 
 use wasm_bindgen::prelude::*;
 
-use crate::linter::{LintError, Linter, Severity};
-use crate::parser::parse_string_with_errors;
+use crate::linter::{Applicability, LintError, Linter, Severity};
 use crate::syntax_errors_to_lint_errors;
+use crate::{LintOptions, LintSettings, lint_str};
 
 /// Initialize the WASM module (sets up panic hook for better error messages)
 #[wasm_bindgen(start)]
@@ -75,6 +75,30 @@ struct JsFix {
     start_offset: Option<usize>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     end_offset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Applicability::is_machine_applicable")]
+    applicability: Applicability,
+}
+
+impl From<&crate::linter::Fix> for JsFix {
+    fn from(f: &crate::linter::Fix) -> Self {
+        JsFix {
+            line: f.line,
+            old_text: f.old_text.clone(),
+            new_text: f.new_text.clone(),
+            delete_line: f.delete_line,
+            insert_after: f.insert_after,
+            start_offset: f.start_offset,
+            end_offset: f.end_offset,
+            applicability: f.applicability,
+        }
+    }
+}
+
+/// A labeled alternative fix for JavaScript
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JsFixAlternative {
+    label: String,
+    fixes: Vec<JsFix>,
 }
 
 /// A single lint error for JavaScript
@@ -86,8 +110,12 @@ struct JsLintError {
     severity: String,
     line: Option<usize>,
     column: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     fixes: Vec<JsFix>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    alternatives: Vec<JsFixAlternative>,
 }
 
 impl From<&LintError> for JsLintError {
@@ -102,33 +130,123 @@ impl From<&LintError> for JsLintError {
             },
             line: error.line,
             column: error.column,
-            fixes: error
-                .fixes
+            end_column: error.end_column,
+            fixes: error.fixes.iter().map(JsFix::from).collect(),
+            alternatives: error
+                .alternatives
                 .iter()
-                .map(|f| JsFix {
-                    line: f.line,
-                    old_text: f.old_text.clone(),
-                    new_text: f.new_text.clone(),
-                    delete_line: f.delete_line,
-                    insert_after: f.insert_after,
-                    start_offset: f.start_offset,
-                    end_offset: f.end_offset,
+                .map(|a| JsFixAlternative {
+                    label: a.label.clone(),
+                    fixes: a.fixes.iter().map(JsFix::from).collect(),
                 })
                 .collect(),
         }
     }
 }
 
-/// Lint an nginx configuration string with default settings
+#[wasm_bindgen(typescript_custom_section)]
+const LINT_TYPES: &str = r#"
+/** Options of `lint`; every field is optional */
+export interface LintOptions {
+    /** Content of a `.nginx-lint.toml` */
+    config?: string;
+    /** Rules to enable (`true`) or disable (`false`) */
+    rules?: Record<string, boolean>;
+    /** Run only these rules */
+    only_rules?: string[];
+    /** Blocks the content is included in, such as `"http,server"` */
+    context?: string;
+    /** Target nginx version, such as `"1.26.0"` */
+    nginx_version?: string;
+    /** Rules and default context for snippets, such as `"ingress-nginx"`;
+     * `context` may then name a snippet, such as `"server-snippet"` */
+    preset?: string;
+}
+
+export interface LintFix {
+    line: number;
+    old_text: string | null;
+    new_text: string;
+    delete_line: boolean;
+    insert_after: boolean;
+    start_offset?: number;
+    end_offset?: number;
+    applicability?: "MaybeIncorrect" | "HasPlaceholders";
+}
+
+export interface LintDiagnostic {
+    rule: string;
+    category: string;
+    message: string;
+    severity: "error" | "warning";
+    line: number | null;
+    column: number | null;
+    end_column?: number;
+    fixes?: LintFix[];
+    alternatives?: { label: string; fixes: LintFix[] }[];
+}
+
+export interface LintReport {
+    errors: LintDiagnostic[];
+    error_count: number;
+    warning_count: number;
+    ignored_count: number;
+}
+"#;
+
+/// Result of [`lint`]
+#[derive(serde::Serialize)]
+struct JsLintReport {
+    errors: Vec<JsLintError>,
+    error_count: usize,
+    warning_count: usize,
+    ignored_count: usize,
+}
+
+/// Lint an nginx configuration string the way the CLI lints a file
 ///
 /// # Arguments
 /// * `content` - The nginx configuration content to lint
+/// * `options` - A `LintOptions` object, or `undefined` for the defaults
 ///
 /// # Returns
-/// A `WasmLintResult` containing the lint errors as JSON
-#[wasm_bindgen]
-pub fn lint(content: &str) -> Result<WasmLintResult, JsValue> {
-    lint_with_config(content, "")
+/// A `LintReport` object whose errors carry their fixes and alternatives
+#[wasm_bindgen(unchecked_return_type = "LintReport")]
+pub fn lint(
+    content: &str,
+    #[wasm_bindgen(unchecked_param_type = "LintOptions | undefined")] options: JsValue,
+) -> Result<JsValue, JsValue> {
+    let settings = if options.is_undefined() || options.is_null() {
+        LintSettings::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| JsValue::from_str(&format!("invalid options: {}", e)))?
+    };
+    let report = lint_report(content, settings).map_err(|e| JsValue::from_str(&e))?;
+    serde::Serialize::serialize(&report, &serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)
+}
+
+/// The report of [`lint`], before conversion to a JavaScript object
+fn lint_report(content: &str, settings: LintSettings) -> Result<JsLintReport, String> {
+    let options = LintOptions::try_from(settings)?;
+    let file = lint_str(content, &options)
+        .files
+        .into_iter()
+        .next()
+        .expect("lint_str reports one file");
+    let count = |severity| {
+        file.errors
+            .iter()
+            .filter(|e| e.severity == severity)
+            .count()
+    };
+    Ok(JsLintReport {
+        error_count: count(Severity::Error),
+        warning_count: count(Severity::Warning),
+        ignored_count: file.ignored_count,
+        errors: file.errors.iter().map(JsLintError::from).collect(),
+    })
 }
 
 /// Lint an nginx configuration string with custom settings
@@ -165,23 +283,28 @@ pub fn lint_with_config(content: &str, config_toml: &str) -> Result<WasmLintResu
         .as_ref()
         .map(|c| c.additional_block_directives().to_vec())
         .unwrap_or_default();
+    let raw_block_directives: Vec<String> = lint_config
+        .as_ref()
+        .map(|c| c.additional_raw_block_directives().to_vec())
+        .unwrap_or_default();
 
     // Run pre-parse checks first (syntax checks that don't require a valid AST)
     let mut pre_parse_errors = Vec::new();
 
     if is_enabled("unmatched-braces") {
-        let rule = UnmatchedBraces::default();
+        let rule =
+            UnmatchedBraces::default().with_raw_block_directives(raw_block_directives.clone());
         pre_parse_errors
             .extend(rule.check_content_with_extras(content, &additional_block_directives));
     }
 
     if is_enabled("unclosed-quote") {
-        let rule = UnclosedQuote;
+        let rule = UnclosedQuote::default().with_raw_block_directives(raw_block_directives.clone());
         pre_parse_errors.extend(rule.check_content(content));
     }
 
     if is_enabled("missing-semicolon") {
-        let rule = MissingSemicolon;
+        let rule = MissingSemicolon::default().with_raw_block_directives(raw_block_directives);
         pre_parse_errors
             .extend(rule.check_content_with_extras(content, &additional_block_directives));
     }
@@ -204,7 +327,7 @@ pub fn lint_with_config(content: &str, config_toml: &str) -> Result<WasmLintResu
     linter.remove_rules_by_name(|name| name == "indent");
 
     // Parse with error recovery — always produces an AST even with syntax errors
-    let (config, syntax_errors) = parse_string_with_errors(content);
+    let (config, syntax_errors) = linter.parse(content);
     let lint_errors = linter.lint(&config, std::path::Path::new("nginx.conf"));
     errors.extend(lint_errors);
 
@@ -220,15 +343,7 @@ pub fn lint_with_config(content: &str, config_toml: &str) -> Result<WasmLintResu
     if is_enabled("indent")
         && !crate::rules::style::indent::has_brace_structure_error(&syntax_errors)
     {
-        let indent_rule = if let Some(indent_size) = lint_config
-            .as_ref()
-            .and_then(|c| c.get_rule_config("indent"))
-            .and_then(|r| r.indent_size)
-        {
-            Indent { indent_size }
-        } else {
-            Indent::default()
-        };
+        let indent_rule = Indent::from_config(lint_config.as_ref());
         errors.extend(indent_rule.check_config(&config));
     }
 
@@ -316,6 +431,8 @@ struct JsRuleSpec {
     min_nginx_version: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_nginx_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preset: Option<String>,
 }
 
 /// Get rule spec (name -> extended spec mapping) as JSON
@@ -341,6 +458,11 @@ pub fn get_rule_spec() -> String {
                     references: r.references(),
                     min_nginx_version: r.min_nginx_version().map(String::from),
                     max_nginx_version: r.max_nginx_version().map(String::from),
+                    preset: r.preset().map(String::from).or_else(|| {
+                        crate::docs::get_rule_doc(r.name())?
+                            .preset
+                            .map(|p| p.as_str().to_string())
+                    }),
                 },
             )
         })
@@ -374,6 +496,7 @@ pub fn get_rule_spec() -> String {
             },
             min_nginx_version: doc.min_nginx_version.map(String::from),
             max_nginx_version: doc.max_nginx_version.map(String::from),
+            preset: doc.preset.map(|p| p.as_str().to_string()),
         });
     }
 
@@ -405,7 +528,8 @@ pub fn get_default_config() -> String {
 ///
 /// # Arguments
 /// * `content` - The original nginx configuration content
-/// * `errors_json` - JSON string of lint errors (as returned by `lint()`)
+/// * `errors_json` - JSON string of lint errors (as returned by
+///   `lint_with_config()`, or `JSON.stringify(lint(content).errors)`)
 ///
 /// # Returns
 /// The fixed content string
@@ -426,6 +550,7 @@ pub fn apply_fixes(content: &str, errors_json: &str) -> Result<String, JsValue>
                 insert_after: f.insert_after,
                 start_offset: f.start_offset,
                 end_offset: f.end_offset,
+                applicability: f.applicability,
             })
         })
         .collect();
@@ -467,6 +592,27 @@ mod tests {
         assert_eq!(result.ignored_count(), 1, "Expected 1 ignored error");
     }
 
+    #[test]
+    fn test_lint_report() {
+        let content = "http {\n    server {\n        server_tokens on;\n    }\n}\n";
+        let settings = LintSettings {
+            only_rules: Some(vec!["server-tokens-enabled".to_string()]),
+            ..LintSettings::default()
+        };
+        let report = lint_report(content, settings).unwrap();
+        assert_eq!(report.warning_count + report.error_count, 1);
+        let error = &report.errors[0];
+        assert_eq!(error.rule, "server-tokens-enabled");
+        assert_eq!(error.line, Some(3));
+        assert!(!error.fixes.is_empty());
+
+        let settings = LintSettings {
+            nginx_version: Some("latest".to_string()),
+            ..LintSettings::default()
+        };
+        assert!(lint_report(content, settings).is_err());
+    }
+
     #[test]
     fn test_wasm_ignore_inline_comment() {
         let content = r#"http {
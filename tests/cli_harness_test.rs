@@ -0,0 +1,201 @@
+//! End-to-end tests of the CLI through the `nginx-lint-test` harness
+
+use nginx_lint_test::{Cli, Fixture};
+
+fn cli() -> Cli {
+    Cli::new(env!("CARGO_BIN_EXE_nginx-lint"))
+}
+
+#[test]
+fn test_reports_diagnostics_per_included_file() {
+    let fixture = Fixture::new()
+        .file(
+            "nginx.conf",
+            "http {\n    server_tokens off;\n    client_max_body_size 1m;\n    include conf.d/*.conf;\n}\n",
+        )
+        .file(
+            "conf.d/app.conf",
+            "server {\n    listen 80;\n    server_tokens on;\n}\n",
+        );
+
+    let run = cli().lint(&fixture);
+    run.assert_rule_at("server-tokens-enabled", "conf.d/app.conf", 3);
+    run.assert_exit_code(1);
+    assert!(run.report("nginx.conf").unwrap().errors.is_empty());
+}
+
+#[test]
+fn test_lint_config_and_extra_args() {
+    let fixture = Fixture::new()
+        .file("nginx.conf", "http {\n    server_tokens on;\n}\n")
+        .lint_config("[rules.server-tokens-enabled]\nenabled = false\n");
+
+    cli().lint(&fixture).assert_no_rule("server-tokens-enabled");
+    cli()
+        .args(["--rule-only", "indent"])
+        .lint(&fixture)
+        .assert_clean();
+}
+
+#[test]
+fn test_fix_round_trip_across_includes() {
+    let fixture = Fixture::new()
+        .file("nginx.conf", "http {\n    include conf.d/*.conf;\n}\n")
+        .file(
+            "conf.d/app.conf",
+            "server {\n    listen 80;   \n    server_tokens on;\n}\n",
+        );
+
+    let fixed = cli().fix_round_trip(&fixture);
+    fixed.assert_file(
+        "conf.d/app.conf",
+        "server {\n    listen 80;\n    server_tokens off;\n}\n",
+    );
+    assert_eq!(fixed.changed_files().len(), 1);
+    fixed.lint.assert_no_rule("trailing-whitespace");
+}
+
+#[test]
+fn test_plugin_rule_options() {
+    let config = "http {\n    gzip on;\n    gzip_types text/css;\n}\n";
+    let fixture = Fixture::new()
+        .file("nginx.conf", config)
+        .lint_config("[rules.gzip-not-enabled]\nenabled = true\ncheck_gzip_types = false\n");
+    let run = cli().lint(&fixture);
+    run.assert_no_rule("gzip-not-enabled");
+    assert!(run.stderr.is_empty(), "unexpected stderr: {}", run.stderr);
+
+    // A value of the wrong type falls back to the default, with a warning
+    let fixture = Fixture::new()
+        .file("nginx.conf", config)
+        .lint_config("[rules.gzip-not-enabled]\nenabled = true\ncheck_gzip_types = \"no\"\n");
+    let run = cli().lint(&fixture);
+    run.assert_rule_at("gzip-not-enabled", "nginx.conf", 3);
+    assert!(
+        run.stderr
+            .contains("rule 'gzip-not-enabled': option 'check_gzip_types' must be a boolean"),
+        "stderr: {}",
+        run.stderr
+    );
+}
+
+#[test]
+fn test_summary_after_diagnostics() {
+    let fixture = Fixture::new()
+        .file(
+            "nginx.conf",
+            "http {\n    server_tokens off;\n    client_max_body_size 1m;\n    include conf.d/*.conf;\n}\n",
+        )
+        .file(
+            "conf.d/a.conf",
+            "server {\n    listen 80;\n    server_tokens on;\n}\n",
+        )
+        .file(
+            "conf.d/b.conf",
+            "server {\n    listen 80;   \n    server_tokens on;\n}\n",
+        );
+
+    // The summary goes to stderr, so the report on stdout stays parseable
+    let run = cli().args(["--summary", "--top", "1"]).lint(&fixture);
+    run.assert_rule_at("server-tokens-enabled", "conf.d/a.conf", 3);
+    assert!(
+        run.stderr.contains("3 file(s) linted, 2 with issues"),
+        "stderr: {}",
+        run.stderr
+    );
+    assert!(run.stderr.contains("server-tokens-enabled"));
+    let top = run
+        .stderr
+        .split("Top 1 files")
+        .nth(1)
+        .expect("offender list");
+    assert!(top.contains("b.conf"), "stderr: {}", run.stderr);
+    assert!(!top.contains("a.conf"), "stderr: {}", run.stderr);
+}
+
+#[test]
+fn test_exit_code_policy_flags() {
+    // Two warnings, no errors
+    let fixture = Fixture::new().file(
+        "nginx.conf",
+        "http {\n    server_tokens on;\n    client_max_body_size 1m;\n    autoindex on;\n}\n",
+    );
+    let run = cli().lint(&fixture);
+    assert!(!run.has_errors());
+    assert_eq!(run.diagnostics().count(), 2);
+    run.assert_exit_code(1);
+
+    cli()
+        .args(["--error-on", "error"])
+        .lint(&fixture)
+        .assert_exit_code(0);
+    cli()
+        .args(["--max-warnings", "2"])
+        .lint(&fixture)
+        .assert_exit_code(0);
+    cli()
+        .args(["--max-warnings", "1"])
+        .lint(&fixture)
+        .assert_exit_code(1);
+    cli()
+        .args(["--error-on", "error", "--max-warnings", "1"])
+        .lint(&fixture)
+        .assert_exit_code(1);
+
+    // --quiet hides the warnings but they still fail the run
+    let run = cli().arg("--quiet").lint(&fixture);
+    assert_eq!(run.diagnostics().count(), 0);
+    run.assert_exit_code(1);
+}
+
+#[test]
+fn test_include_layering_from_config() {
+    let fixture = Fixture::new()
+        .file(
+            "nginx.conf",
+            "http {\n    server_tokens off;\n    client_max_body_size 1m;\n    include sites-enabled/*.conf;\n    include conf.d/*.conf;\n}\n",
+        )
+        .file(
+            "sites-enabled/app.conf",
+            "server {\n    listen 80;\n    server_name app.example.com;\n}\n",
+        )
+        .file(
+            "conf.d/legacy.conf",
+            "server {\n    listen 80;\n    server_name legacy.example.com;\n}\n",
+        );
+
+    cli().lint(&fixture).assert_no_rule("include-layering");
+
+    let fixture = fixture.lint_config(
+        "[rules.include-layering]\nenabled = true\n\n[[rules.include-layering.layers]]\ndirectives = [\"server\"]\ncontext = \"http\"\nallowed_paths = [\"sites-enabled/*.conf\"]\n",
+    );
+    let run = cli().lint(&fixture);
+    run.assert_rule_at("include-layering", "conf.d/legacy.conf", 1);
+    assert_eq!(run.rule("include-layering").len(), 1);
+}
+
+#[test]
+fn test_generated_regions() {
+    let generated = "# nginx-lint:generated-by ansible checksum=crc32:268b5a59\nserver {\n  listen 80;   \n  server_name app.example.com;\n}\n# nginx-lint:generated-end\n";
+    let fixture = Fixture::new()
+        .file(
+            "nginx.conf",
+            "http {\n    server_tokens off;\n    client_max_body_size 1m;\n    include conf.d/*.conf;\n}\n",
+        )
+        .file("conf.d/app.conf", generated);
+
+    // Style is the generating tool's concern
+    cli().lint(&fixture).assert_clean();
+
+    let fixture = fixture.file(
+        "conf.d/app.conf",
+        &generated.replace(
+            "app.example.com;\n",
+            "app.example.com;\n  server_tokens on;\n",
+        ),
+    );
+    let run = cli().lint(&fixture);
+    run.assert_rule_at("generated-region-modified", "conf.d/app.conf", 1);
+    run.assert_no_rule("indent");
+    run.assert_no_rule("trailing-whitespace");
+}
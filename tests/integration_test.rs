@@ -1,5 +1,5 @@
 use nginx_lint::{
-    LintConfig, Linter, Severity, apply_fixes, parse_config, parse_string,
+    LintConfig, Linter, Severity, apply_fixes_to_file, parse_config, parse_string,
     parse_string_with_errors, syntax_errors_to_lint_errors,
 };
 use rayon::prelude::*;
@@ -287,7 +287,7 @@ server {
     .unwrap();
 
     // Simulate being included from http context
-    config.include_context = vec!["http".to_string()];
+    config.include_context = vec!["http".to_string()].into();
 
     let linter = get_default_linter();
     let errors = linter.lint(&config, Path::new("sites-available/example.conf"));
@@ -320,7 +320,7 @@ server {
     .unwrap();
 
     // Simulate being included from http context
-    config.include_context = vec!["http".to_string()];
+    config.include_context = vec!["http".to_string()].into();
 
     let linter = get_default_linter();
     let errors = linter.lint(&config, Path::new("sites-available/example.conf"));
@@ -358,7 +358,7 @@ location / {
     .unwrap();
 
     // Simulate being included from http > server context
-    config.include_context = vec!["http".to_string(), "server".to_string()];
+    config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
     let linter = get_default_linter();
     let errors = linter.lint(&config, Path::new("snippets/location.conf"));
@@ -387,7 +387,7 @@ fn test_context_comment_sets_include_context() {
     let context = parse_context_comment(content);
 
     assert_eq!(
-        context,
+        context.map(|c| c.to_names()),
         Some(vec!["http".to_string(), "server".to_string()]),
         "Expected context to be parsed from comment"
     );
@@ -417,7 +417,7 @@ fn test_context_comment_prevents_invalid_context_error() {
 
     let config = included_files[0].config.as_ref().unwrap();
     assert_eq!(
-        config.include_context,
+        config.include_context.to_names(),
         vec!["http".to_string(), "server".to_string()],
         "Expected include_context to be set from comment"
     );
@@ -438,6 +438,31 @@ fn test_context_comment_prevents_invalid_context_error() {
     );
 }
 
+#[test]
+fn test_include_context_keeps_enclosing_block_args() {
+    use nginx_lint::collect_included_files;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("nginx.conf"),
+        "http {\n    server {\n        location ~ ^/api/ {\n            include api.conf;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("api.conf"), "proxy_pass http://backend;\n").unwrap();
+
+    let included_files = collect_included_files(
+        &dir.path().join("nginx.conf"),
+        |path| parse_config(path).map_err(|e| e.to_string()),
+        &[],
+        None,
+    );
+
+    assert_eq!(included_files.len(), 2);
+    let context = &included_files[1].include_context;
+    assert_eq!(context.to_string(), "http,server,location=~ ^/api/");
+    assert_eq!(context.innermost("location").unwrap().args, ["~", "^/api/"]);
+}
+
 #[test]
 fn test_no_context_comment_causes_invalid_context_error() {
     use nginx_lint::collect_included_files;
@@ -592,6 +617,7 @@ struct RuleTestCase {
 /// Automatically discover and test all rule fixtures
 /// This test iterates over all fixtures in tests/fixtures/rules/ and runs appropriate tests
 #[test]
+#[allow(clippy::collapsible_if)]
 fn test_all_rule_fixtures() {
     use std::io::Write;
 
@@ -713,7 +739,7 @@ fn test_all_rule_fixtures() {
                                 .collect();
 
                             if !rule_errors_with_fixes.is_empty() {
-                                if let Ok(fix_result) = apply_fixes(temp_path, &rule_errors_with_fixes) {
+                                if let Ok(fix_result) = apply_fixes_to_file(temp_path, &rule_errors_with_fixes) {
                                     if fix_result.applied > 0 {
                                         if let (Ok(fixed_content), Ok(expected_content)) = (
                                             fs::read_to_string(temp_path),
@@ -1511,7 +1537,7 @@ location / {
     .expect("Failed to parse config");
 
     // Set include context as if this file was included from http { server { ... } }
-    config.include_context = vec!["http".to_string(), "server".to_string()];
+    config.include_context = vec!["http".to_string(), "server".to_string()].into();
 
     let linter = get_default_linter();
     let errors = linter.lint(&config, std::path::Path::new("test.conf"));
@@ -1541,7 +1567,7 @@ location / {
     .expect("Failed to parse config");
 
     // Set include context as if this file was included from http { ... } (no server)
-    config.include_context = vec!["http".to_string()];
+    config.include_context = vec!["http".to_string()].into();
 
     let linter = get_default_linter();
     let errors = linter.lint(&config, std::path::Path::new("test.conf"));
@@ -2790,6 +2816,103 @@ fn test_why_command_omits_applies_to_for_unversioned_rule() {
     );
 }
 
+// `nginx-lint rules` lists every rule; `--format json` is the input for
+// generating the documentation site, so lock down its shape.
+#[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+#[test]
+fn test_rules_command_json_lists_native_rules_and_plugins() {
+    use std::process::Command;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["rules", "--format", "json"])
+        .output()
+        .expect("Failed to run nginx-lint rules");
+    assert!(
+        output.status.success(),
+        "rules command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let rules: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rules = rules.as_array().unwrap();
+    let find = |name: &str| {
+        rules
+            .iter()
+            .find(|rule| rule["name"] == name)
+            .unwrap_or_else(|| panic!("{} missing from rules output", name))
+    };
+
+    let indent = find("indent");
+    assert_eq!(indent["category"], "style");
+    assert_eq!(indent["severity"], "warning");
+    assert_eq!(indent["is_plugin"], false);
+
+    let rift = find("nginx-rift");
+    assert_eq!(rift["is_plugin"], true);
+    assert_eq!(rift["max_nginx_version"], "1.30.1");
+    assert!(!rift["why"].as_str().unwrap().is_empty());
+    assert!(!rift["references"].as_array().unwrap().is_empty());
+}
+
+#[cfg(any(feature = "native-builtin-plugins", feature = "wasm-builtin-plugins"))]
+#[test]
+fn test_rules_explain_command() {
+    use std::process::Command;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["rules", "explain", "server-tokens-enabled", "-o", "json"])
+        .output()
+        .expect("Failed to run nginx-lint rules explain");
+    assert!(output.status.success());
+    let rule: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rule["name"], "server-tokens-enabled");
+    assert_eq!(rule["category"], "security");
+    assert!(
+        rule["bad_example"]
+            .as_str()
+            .unwrap()
+            .contains("server_tokens")
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["rules", "explain", "server-tokens-enabled"])
+        .output()
+        .expect("Failed to run nginx-lint rules explain");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Bad Example:"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["rules", "explain", "no-such-rule"])
+        .output()
+        .expect("Failed to run nginx-lint rules explain");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown rule: no-such-rule"));
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_docgen_command_writes_pages() {
+    use std::process::Command;
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["docgen", "-o"])
+        .arg(dir.path())
+        .output()
+        .expect("Failed to run nginx-lint docgen");
+    assert!(
+        output.status.success(),
+        "docgen failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let index = fs::read_to_string(dir.path().join("index.md")).unwrap();
+    assert!(index.contains("| [indent](indent.md) |"));
+    let page = fs::read_to_string(dir.path().join("indent.md")).unwrap();
+    assert!(page.starts_with("# indent\n"));
+    assert!(page.contains("## Bad Example"));
+}
+
 // ============================================================================
 // CLI --fix tests - unfixable errors must still be reported
 // ============================================================================
@@ -2892,6 +3015,94 @@ fn test_fix_exits_zero_when_all_errors_fixed() {
     );
 }
 
+/// Run nginx-lint with `args` on `config` given on stdin
+#[cfg(feature = "cli")]
+fn run_with_stdin(args: &[&str], config: &str) -> std::process::Output {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run nginx-lint");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(config.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// `--fix-alternative` applies the chosen alternative instead of the
+/// default fix of the diagnostics offering it
+#[cfg(feature = "cli")]
+#[test]
+fn test_fix_alternative() {
+    let config = "http {\n    server {\n        location / {\n            root /srv;\n        }\n        location /a/ {\n            root /srv;\n        }\n    }\n}\n";
+    let fix =
+        |args: &[&str]| String::from_utf8_lossy(&run_with_stdin(args, config).stdout).to_string();
+
+    // The default fix hoists the root to the server
+    let hoisted = fix(&["--fix", "-"]);
+    assert!(
+        hoisted.contains("    server {\n        root /srv;\n        location / {\n        }\n"),
+        "got:\n{}",
+        hoisted
+    );
+
+    let kept = fix(&["--fix", "--fix-alternative", "keep per-location roots", "-"]);
+    assert!(
+        kept.contains(
+            "        location / {\n            # nginx-lint:ignore root-in-location every location \
+             keeps its own root\n            root /srv;\n"
+        ),
+        "got:\n{}",
+        kept
+    );
+    assert!(kept.contains("        location /a/ {\n            root /srv;\n"));
+}
+
+/// `--fix` applies only machine-applicable fixes; `--fix-suggestions` adds
+/// the ones that may change behavior
+#[cfg(feature = "cli")]
+#[test]
+fn test_fix_suggestions() {
+    let config = r#"http {
+    server_tokens on;
+    proxy_set_header Host $host;
+    proxy_set_header X-Real-IP $remote_addr;
+
+    server {
+        location / {
+            proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+            proxy_pass http://backend;
+        }
+    }
+}
+"#;
+    let inherited = "            proxy_set_header Host $host;\n";
+
+    let output = run_with_stdin(&["--fix", "-"], config);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("server_tokens off;"), "got:\n{}", stdout);
+    assert!(!stdout.contains(inherited), "got:\n{}", stdout);
+    assert!(
+        stderr.contains("suggested fix(es) in <stdin>; apply them with --fix-suggestions"),
+        "got:\n{}",
+        stderr
+    );
+
+    let output = run_with_stdin(&["--fix", "--fix-suggestions", "-"], config);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("server_tokens off;"), "got:\n{}", stdout);
+    assert!(stdout.contains(inherited), "got:\n{}", stdout);
+}
+
 /// When applying fixes fails (e.g. read-only file), nothing was fixed, so
 /// every error must still be reported and the exit code must be non-zero.
 #[cfg(all(feature = "cli", unix))]
@@ -3033,6 +3244,55 @@ fn test_unmatched_braces_respects_configured_additional_block_directives_via_cli
     );
 }
 
+/// A raw block directive configured via `[parser] raw_block_directives`
+/// keeps its embedded code away from the nginx syntax checks, whose rules
+/// otherwise trip over its quotes, braces and statements.
+#[cfg(feature = "cli")]
+#[test]
+fn test_configured_raw_block_directives_via_cli() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let conf_path = temp_dir.path().join("nginx.conf");
+    std::fs::write(
+        &conf_path,
+        "http {\n    js_inline {\n        // don't touch\n        function h(r) { r.return(200, '}'); }\n    }\n}\n",
+    )
+    .unwrap();
+    let lint = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .arg(conf_path.to_str().unwrap())
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let syntax_rules = [
+        "unclosed-quote",
+        "unmatched-braces",
+        "missing-semicolon",
+        "syntax-error",
+    ];
+
+    let stdout = lint();
+    assert!(
+        syntax_rules.iter().any(|rule| stdout.contains(rule)),
+        "expected syntax errors without the configuration; got:\n{}",
+        stdout
+    );
+
+    std::fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        "[parser]\nraw_block_directives = [\"js_inline\"]\n",
+    )
+    .unwrap();
+    let stdout = lint();
+    assert!(
+        !syntax_rules.iter().any(|rule| stdout.contains(rule)),
+        "expected the raw block to be skipped; got:\n{}",
+        stdout
+    );
+}
+
 /// The report printed by --fix must describe the file as written: running a
 /// plain lint on the fixed file afterwards must produce the identical
 /// report (same errors, same positions).
@@ -3067,3 +3327,1897 @@ fn test_fix_reports_positions_of_fixed_file() {
         "--fix must report exactly what a plain lint of the fixed file reports"
     );
 }
+
+// ============================================================================
+// Cross-file pass: upstream references
+// ============================================================================
+
+/// Upstreams are resolved across the whole include tree: a definition in
+/// one included file satisfies a reference in another, and each diagnostic
+/// is reported against the file the offending directive lives in.
+#[cfg(feature = "cli")]
+#[test]
+fn test_upstream_references_resolved_across_includes() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf_d = temp_dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    fs::write(
+        temp_dir.path().join("nginx.conf"),
+        "http {\n    include conf.d/*.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        conf_d.join("00-upstreams.conf"),
+        "upstream app {\n    server 127.0.0.1:8080;\n}\n\nupstream legacy {\n    server 127.0.0.1:8081;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        conf_d.join("10-app.conf"),
+        "server {\n    location / {\n        proxy_pass http://app;\n    }\n\n    location /api/ {\n        proxy_pass http://api_backend;\n    }\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args([
+            "--rule-only",
+            "undefined-upstream",
+            "--rule-only",
+            "unused-upstream",
+            temp_dir.path().join("nginx.conf").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run nginx-lint");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().filter(|l| l.contains("warning[")).collect();
+    assert_eq!(lines.len(), 2, "got:\n{}", stdout);
+    assert!(
+        lines.iter().any(|l| l.contains("00-upstreams.conf:5:")
+            && l.contains("unused-upstream")
+            && l.contains("'legacy'")),
+        "expected unused-upstream for 'legacy'; got:\n{}",
+        stdout
+    );
+    assert!(
+        lines.iter().any(|l| l.contains("10-app.conf:7:")
+            && l.contains("undefined-upstream")
+            && l.contains("'api_backend'")),
+        "expected undefined-upstream for 'api_backend'; got:\n{}",
+        stdout
+    );
+}
+
+/// Servers are counted across the include tree, so a vhost without a
+/// server_name is reported even when every file holds a single server.
+#[cfg(feature = "cli")]
+#[test]
+fn test_missing_server_name_across_includes() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let sites = temp_dir.path().join("sites-enabled");
+    fs::create_dir(&sites).unwrap();
+    fs::write(
+        temp_dir.path().join("nginx.conf"),
+        "http {\n    include sites-enabled/*.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        sites.join("app.conf"),
+        "server {\n    listen 80;\n    server_name app.example.com;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        sites.join("legacy.conf"),
+        "server {\n    listen 80;\n    root /var/www/legacy;\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args([
+            "--rule-only",
+            "missing-server-name",
+            temp_dir.path().join("nginx.conf").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run nginx-lint");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().filter(|l| l.contains("warning[")).collect();
+    assert_eq!(lines.len(), 1, "got:\n{}", stdout);
+    assert!(
+        lines[0].contains("legacy.conf:1:1") && lines[0].contains("missing-server-name"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// Default servers are compared across the include tree: two files each
+/// marking `listen 80 default_server` keep nginx from starting.
+#[cfg(feature = "cli")]
+#[test]
+fn test_duplicate_default_server_across_includes() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf_d = temp_dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    fs::write(
+        temp_dir.path().join("nginx.conf"),
+        "http {\n    include conf.d/*.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        conf_d.join("app.conf"),
+        "server {\n    listen 80 default_server;\n    server_name app.example.com;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        conf_d.join("default.conf"),
+        "server {\n    listen 80 default_server;\n    return 444;\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args([
+            "--rule-only",
+            "duplicate-default-server",
+            temp_dir.path().join("nginx.conf").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run nginx-lint");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().filter(|l| l.contains("error[")).collect();
+    assert_eq!(lines.len(), 1, "got:\n{}", stdout);
+    assert!(
+        lines[0].contains("default.conf:2:5") && lines[0].contains("duplicate-default-server"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// Cross-file diagnostics honour ignore comments, and the comment is not
+/// reported as unused by the per-file pass.
+#[cfg(feature = "cli")]
+#[test]
+fn test_upstream_reference_ignore_comment() {
+    use std::io::Write;
+    use std::process::Command;
+
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(
+        b"http {\n    server {\n        location / {\n            # nginx-lint:ignore undefined-upstream resolved via docker DNS\n            proxy_pass http://app;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args([
+            "--rule-only",
+            "undefined-upstream",
+            file.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run nginx-lint");
+
+    assert!(
+        output.status.success(),
+        "stdout:\n{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+// ============================================================================
+// Output ordering
+// ============================================================================
+
+/// Diagnostics are reported by file path, then position, then rule id,
+/// independent of include order and of parallel rule execution, so the
+/// output of repeated runs is byte-for-byte identical.
+#[cfg(feature = "cli")]
+#[test]
+fn test_output_order_is_deterministic() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf_d = temp_dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    // Included in reverse path order
+    fs::write(
+        temp_dir.path().join("nginx.conf"),
+        "http {\n    server_tokens on;\n    include conf.d/z.conf;\n    include conf.d/a.conf;\n}\n",
+    )
+    .unwrap();
+    let server = "server {\n    autoindex on;   \n    server_tokens on;\n    location / {\n        root /var/www;\n    }\n}\n";
+    fs::write(conf_d.join("z.conf"), server).unwrap();
+    fs::write(conf_d.join("a.conf"), server).unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .arg(temp_dir.path().join("nginx.conf"))
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let first = run();
+    for _ in 0..5 {
+        assert_eq!(run(), first, "lint output changed between runs");
+    }
+
+    // (path, line, column) of each diagnostic, in output order
+    let keys: Vec<(String, usize, usize)> = first
+        .lines()
+        .filter(|l| l.contains(": warning[") || l.contains(": error["))
+        .map(|l| {
+            let mut parts = l.splitn(4, ':');
+            let path = parts.next().unwrap().to_string();
+            let line = parts.next().unwrap().parse().unwrap();
+            let column = parts.next().unwrap().parse().unwrap();
+            (path, line, column)
+        })
+        .collect();
+    assert!(keys.len() >= 5, "got:\n{}", first);
+    let mut sorted = keys.clone();
+    sorted.sort();
+    assert_eq!(keys, sorted, "got:\n{}", first);
+    assert!(keys[0].0.ends_with("a.conf"), "got:\n{}", first);
+}
+
+// ============================================================================
+// Include resolution: cycles and missing targets
+// ============================================================================
+
+/// A circular include is reported at the `include` directive of every file
+/// on the loop, and the run still terminates.
+#[cfg(feature = "cli")]
+#[test]
+fn test_include_cycle_reported() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf_d = temp_dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    fs::write(
+        temp_dir.path().join("nginx.conf"),
+        "http {\n    include conf.d/*.conf;\n    include missing.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(conf_d.join("app.conf"), "include ../nginx.conf;\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args([
+            "--rule-only",
+            "include-cycle",
+            "--rule-only",
+            "include-path-exists",
+            temp_dir.path().join("nginx.conf").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run nginx-lint");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "got:\n{}", stdout);
+    let lines: Vec<&str> = stdout.lines().filter(|l| l.contains("error[")).collect();
+    assert_eq!(lines.len(), 3, "got:\n{}", stdout);
+    assert!(
+        lines.iter().any(|l| l.contains("app.conf:1:1")
+            && l.contains("include-cycle")
+            && l.contains("conf.d/app.conf -> nginx.conf -> conf.d/app.conf")),
+        "got:\n{}",
+        stdout
+    );
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.contains("nginx.conf:2:5") && l.contains("include-cycle")),
+        "got:\n{}",
+        stdout
+    );
+    assert!(
+        lines.iter().any(|l| l.contains("nginx.conf:3:5")
+            && l.contains("include-path-exists")
+            && l.contains("missing.conf")),
+        "got:\n{}",
+        stdout
+    );
+}
+
+// ============================================================================
+// Formatter ([format] section and `nginx-lint fmt`)
+// ============================================================================
+
+#[test]
+fn test_format_use_tabs_drives_indent_rule() {
+    let config = LintConfig::parse("[format]\nuse_tabs = true\n").unwrap();
+    let linter = Linter::with_config_and_rule_only(
+        Some(&config),
+        None,
+        Some(&["indent".to_string()].into_iter().collect()),
+    );
+
+    let content = "http {\n\tserver {\n\t\tlisten 80;\n\t}\n}\n";
+    let parsed = parse_string(content).unwrap();
+    let (errors, _) = linter.lint_with_content(&parsed, Path::new("test.conf"), content);
+    assert!(errors.is_empty(), "Expected no errors, got: {:?}", errors);
+
+    let content = "http {\n    server {\n    }\n}\n";
+    let parsed = parse_string(content).unwrap();
+    let (errors, _) = linter.lint_with_content(&parsed, Path::new("test.conf"), content);
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.message == "Use tabs instead of spaces for indentation"),
+        "Expected tab indentation errors, got: {:?}",
+        errors
+    );
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_fmt_command_uses_format_config() {
+    use std::process::Command;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join(".nginx-lint.toml"),
+        "[format]\nindent_width = 2\nalign_proxy_set_header = true\n",
+    )
+    .unwrap();
+    let conf = dir.path().join("nginx.conf");
+    let unformatted = "http {\nserver {\n    proxy_set_header Host $host;\n    proxy_set_header X-Real-IP $remote_addr;\n}\n}\n";
+    fs::write(&conf, unformatted).unwrap();
+
+    // --check reports the file and leaves it untouched
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["fmt", "--check", conf.to_str().unwrap()])
+        .output()
+        .expect("Failed to run nginx-lint fmt --check");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("would be reformatted"));
+    assert_eq!(fs::read_to_string(&conf).unwrap(), unformatted);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["fmt", conf.to_str().unwrap()])
+        .output()
+        .expect("Failed to run nginx-lint fmt");
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(&conf).unwrap(),
+        "http {\n  server {\n    proxy_set_header Host      $host;\n    proxy_set_header X-Real-IP $remote_addr;\n  }\n}\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["fmt", "--check", conf.to_str().unwrap()])
+        .output()
+        .expect("Failed to run nginx-lint fmt --check");
+    assert!(output.status.success());
+}
+
+/// `diff` reports semantic changes with their blocks, exits with 1 when
+/// the files differ and 0 when only formatting and comments changed.
+#[cfg(feature = "cli")]
+#[test]
+fn test_diff_command() {
+    use std::process::Command;
+
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old.conf");
+    let new = dir.path().join("new.conf");
+    let reformatted = dir.path().join("reformatted.conf");
+    fs::write(
+        &old,
+        "http {\n    server {\n        server_name example.com;\n        listen 80;\n        location /old {\n        }\n    }\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        &new,
+        "http {\n    server {\n        server_name example.com;\n        listen 8080;\n        location /new {\n            return 204;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        &reformatted,
+        "# generated\nhttp {\n  server {\n    listen \"80\";\n    server_name example.com;\n    location /old { }\n  }\n}\n",
+    )
+    .unwrap();
+
+    let run = |args: &[&str], old: &Path, new: &Path| {
+        Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .current_dir(dir.path())
+            .arg("diff")
+            .args(args)
+            .arg(old.file_name().unwrap())
+            .arg(new.file_name().unwrap())
+            .output()
+            .expect("Failed to run nginx-lint diff")
+    };
+
+    let output = run(&[], &old, &new);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "~ http > server [example.com]: listen 80; -> listen 8080;  (old.conf:4, new.conf:4)\n\
+         + http > server [example.com]: location /new {  (new.conf:5)\n\
+         - http > server [example.com]: location /old {  (old.conf:5)\n"
+    );
+
+    let output = run(&["--format", "json"], &old, &new);
+    let changes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(changes[0]["kind"], "modified");
+    assert_eq!(changes[0]["old"]["text"], "listen 80;");
+    assert_eq!(changes[0]["new"]["file"], "new.conf");
+    assert_eq!(changes[1]["old"], serde_json::Value::Null);
+
+    let output = run(&[], &old, &reformatted);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+/// `parse` prints the AST as JSON: argument kinds, spans, comments and the
+/// include context from --context; syntax errors go to stderr with exit 1.
+#[cfg(feature = "cli")]
+#[test]
+fn test_parse_command_prints_ast_json() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let dir = tempfile::tempdir().unwrap();
+    let conf = dir.path().join("site.conf");
+    fs::write(&conf, "# api\nlocation /api {\n    return 200 \"ok\";\n}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["parse", "--context", "http,server", conf.to_str().unwrap()])
+        .output()
+        .expect("Failed to run nginx-lint parse");
+    assert!(output.status.success());
+    let ast: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(ast["items"][0]["Comment"]["text"], "# api");
+    let location = &ast["items"][1]["Directive"];
+    assert_eq!(location["name"], "location");
+    assert_eq!(location["span"]["start"]["line"], 2);
+    let ret = &location["block"]["items"][0]["Directive"];
+    assert_eq!(ret["args"][0]["value"]["Literal"], "200");
+    assert_eq!(ret["args"][1]["value"]["QuotedString"], "ok");
+    assert_eq!(ret["args"][1]["raw"], "\"ok\"");
+    assert_eq!(
+        ast["include_context"],
+        serde_json::json!([{ "name": "http" }, { "name": "server" }])
+    );
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["parse", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run nginx-lint parse -");
+    child.stdin.take().unwrap().write_all(b"http {\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let ast: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(ast["items"][0]["Directive"]["name"], "http");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).starts_with("-:2:1: "),
+        "got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+// ============================================================================
+// precompressed-assets-not-served (opt-in, reads the docroot)
+// ============================================================================
+
+/// The rule is off by default and, once enabled, scans the configured
+/// `docroot` for `.gz` files next to their originals.
+#[cfg(feature = "cli")]
+#[test]
+fn test_precompressed_assets_docroot_from_config() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let public = temp_dir.path().join("public");
+    fs::create_dir_all(public.join("js")).unwrap();
+    fs::write(public.join("js/app.js"), "app();").unwrap();
+    fs::write(public.join("js/app.js.gz"), "").unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        root /var/www/html;\n        location /js/ {\n        }\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(
+        !run().contains("precompressed-assets-not-served"),
+        "rule should be disabled by default"
+    );
+
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        format!(
+            "[rules.precompressed-assets-not-served]\nenabled = true\ndocroot = \"{}\"\n",
+            public.display()
+        ),
+    )
+    .unwrap();
+    let stdout = run();
+    assert!(
+        stdout.contains("nginx.conf:4:9")
+            && stdout.contains("location '/js/' has precompressed files on disk")
+            && stdout.contains("'gzip_static on;'"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+// ============================================================================
+// lua-block-lint (opt-in, runs an external linter)
+// ============================================================================
+
+/// The configured command receives the block's Lua code on stdin, and the
+/// lines it reports are mapped back to the nginx file; under trust policy
+/// untrusted the command is not run.
+#[cfg(all(feature = "cli", unix))]
+#[test]
+fn test_lua_block_lint_command_from_config() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        location / {\n            content_by_lua_block {\n                local name = ngx.var.arg_name\n                ngx.say(nmae)\n            }\n        }\n    }\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        r#"[rules.lua-block-lint]
+enabled = true
+command = ["sh", "-c", "grep -n nmae | sed 's/^\\([0-9]*\\):.*/stdin:\\1:25: (W113) accessing undefined variable nmae/'"]
+"#,
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(args)
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = run(&[]);
+    assert!(
+        stdout.contains(
+            "nginx.conf:6:25: warning[best-practices/lua-block-lint]: sh: (W113) accessing undefined variable nmae"
+        ),
+        "got:\n{}",
+        stdout
+    );
+
+    let stdout = run(&["--trust-policy", "untrusted"]);
+    assert!(!stdout.contains("lua-block-lint"), "got:\n{}", stdout);
+}
+
+/// rego-policy reports the denials printed by the configured command, which
+/// gets the policies and query as arguments; under trust policy untrusted
+/// the command is not run.
+#[cfg(all(feature = "cli", unix))]
+#[test]
+fn test_rego_policy_from_config() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        autoindex on;\n    }\n}\n",
+    )
+    .unwrap();
+    // A stand-in for opa denying the policy file given as the last --data
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        r#"[rules.rego-policy]
+enabled = true
+policies = ["org.rego"]
+query = "data.org.deny"
+command = ["sh", "-c", "cat >/dev/null; echo \"{\\\"result\\\":[{\\\"expressions\\\":[{\\\"value\\\":[{\\\"msg\\\":\\\"$5 denies $6\\\",\\\"line\\\":3,\\\"column\\\":9}]}]}]}\"", "sh"]
+"#,
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(args)
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = run(&[]);
+    assert!(
+        stdout
+            .contains("nginx.conf:3:9: error[security/rego-policy]: org.rego denies data.org.deny"),
+        "got:\n{}",
+        stdout
+    );
+
+    let stdout = run(&["--trust-policy", "untrusted"]);
+    assert!(!stdout.contains("rego-policy"), "got:\n{}", stdout);
+}
+
+/// proxy-pass-hardcoded-ip is opt-in, and `allowed_addresses` replaces the
+/// loopback allowlist.
+#[cfg(feature = "cli")]
+#[test]
+fn test_proxy_pass_hardcoded_ip_allowed_addresses_from_config() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        location /a {\n            proxy_pass http://10.1.2.3:8080;\n        }\n        location /b {\n            proxy_pass http://192.168.0.10;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(
+        !run().contains("proxy-pass-hardcoded-ip"),
+        "rule should be disabled by default"
+    );
+
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        "[rules.proxy-pass-hardcoded-ip]\nenabled = true\nallowed_addresses = [\"10.0.0.0/8\"]\n",
+    )
+    .unwrap();
+    let stdout = run();
+    assert!(
+        stdout.contains("nginx.conf:7:13")
+            && stdout.contains("hardcoded IP address '192.168.0.10'")
+            && !stdout.contains("'10.1.2.3'"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// `severity = "error"` promotes a rule's warnings, which also fails the run.
+#[cfg(feature = "cli")]
+#[test]
+fn test_rule_severity_override() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(&conf, "http {\n    ssl_protocols TLSv1 TLSv1.2;\n}\n").unwrap();
+
+    let run = || {
+        Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(["--rule-only", "deprecated-ssl-protocol"])
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint")
+    };
+
+    let output = run();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("warning[security/deprecated-ssl-protocol]"),
+        "got:\n{}",
+        stdout
+    );
+
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        "[rules.deprecated-ssl-protocol]\nseverity = \"error\"\n",
+    )
+    .unwrap();
+    let output = run();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("error[security/deprecated-ssl-protocol]"),
+        "got:\n{}",
+        stdout
+    );
+    assert!(!output.status.success());
+}
+
+/// `--check-paths` adds the on-disk checks of ssl-certificate-missing and
+/// weak-ssl-key-exchange.
+#[cfg(feature = "cli")]
+#[test]
+fn test_check_paths_reports_missing_certificate_files() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("cert.pem"), "").unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    ssl_certificate cert.pem;\n    ssl_certificate_key key.pem;\n    ssl_dhparam dhparam.pem;\n    server {\n        listen 443 ssl;\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(args)
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = run(&[]);
+    assert!(
+        !stdout.contains("does not exist"),
+        "files should only be checked with --check-paths, got:\n{}",
+        stdout
+    );
+
+    let stdout = run(&["--check-paths"]);
+    assert!(
+        stdout.contains("nginx.conf:3:5")
+            && stdout.contains("ssl_certificate_key file 'key.pem' does not exist")
+            && stdout.contains("nginx.conf:4:5")
+            && stdout.contains("ssl_dhparam file 'dhparam.pem' does not exist"),
+        "got:\n{}",
+        stdout
+    );
+    assert!(!stdout.contains("'cert.pem'"), "got:\n{}", stdout);
+}
+
+/// njs-module-reference checks handler names against the imports, and with
+/// `--check-paths` the module files and what they export.
+#[cfg(feature = "cli")]
+#[test]
+fn test_check_paths_reports_njs_modules() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join("njs")).unwrap();
+    fs::write(
+        temp_dir.path().join("njs/http.js"),
+        "function hello(r) {\n    r.return(200, 'hello');\n}\n\nexport default { hello };\n",
+    )
+    .unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    js_path njs;\n    js_import main from http.js;\n    js_import auth.js;\n    server {\n        location / {\n            js_content main.goodbye;\n        }\n        location /login {\n            js_content login.hello;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(args)
+            .args(["--rule-only", "njs-module-reference"])
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = run(&[]);
+    assert!(
+        stdout.contains(
+            "nginx.conf:10:24: error[interop/njs-module-reference]: js_content refers to module 'login', which no js_import declares"
+        ),
+        "got:\n{}",
+        stdout
+    );
+    assert!(!stdout.contains("does not"), "got:\n{}", stdout);
+
+    let stdout = run(&["--check-paths"]);
+    assert!(
+        stdout.contains("nginx.conf:4:5")
+            && stdout
+                .contains("js_import module 'auth.js' does not exist (also looked in js_path njs)")
+            && stdout.contains("nginx.conf:7:13")
+            && stdout.contains(
+                "js_content refers to 'main.goodbye', but http.js does not export 'goodbye'"
+            ),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// `--check-paths` turns on auth-basic-user-file-missing, which resolves
+/// relative paths against `--prefix` like includes and certificates.
+#[cfg(feature = "cli")]
+#[test]
+fn test_check_paths_resolves_against_prefix() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let prefix = temp_dir.path();
+    fs::create_dir(prefix.join("sites-enabled")).unwrap();
+    fs::write(prefix.join("htpasswd"), "admin:{PLAIN}secret\n").unwrap();
+    fs::write(prefix.join("cert.pem"), "").unwrap();
+    fs::write(prefix.join("mime.types"), "types {\n}\n").unwrap();
+    let conf = prefix.join("sites-enabled/app.conf");
+    fs::write(
+        &conf,
+        "include mime.types;\nserver {\n    listen 443 ssl;\n    ssl_certificate cert.pem;\n    location /admin/ {\n        auth_basic \"Admin\";\n        auth_basic_user_file htpasswd;\n    }\n    location /ops/ {\n        auth_basic \"Ops\";\n        auth_basic_user_file ops.htpasswd;\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(args)
+            .args(["--context", "http"])
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = run(&[]);
+    assert!(
+        !stdout.contains("auth-basic-user-file-missing"),
+        "files should only be checked with --check-paths, got:\n{}",
+        stdout
+    );
+
+    // Without a prefix, paths are relative to sites-enabled/
+    let stdout = run(&["--check-paths"]);
+    assert!(
+        stdout.contains("app.conf:7:9")
+            && stdout.contains("auth_basic_user_file 'htpasswd' does not exist")
+            && stdout.contains("Included file 'mime.types' does not exist")
+            && stdout.contains("ssl_certificate file 'cert.pem' does not exist"),
+        "got:\n{}",
+        stdout
+    );
+
+    let stdout = run(&["--check-paths", "--prefix", prefix.to_str().unwrap()]);
+    assert!(
+        stdout.contains(
+            "app.conf:11:9: error[security/auth-basic-user-file-missing]: auth_basic_user_file 'ops.htpasswd' does not exist"
+        ),
+        "got:\n{}",
+        stdout
+    );
+    assert!(
+        !stdout.contains("'htpasswd'")
+            && !stdout.contains("'cert.pem'")
+            && !stdout.contains("'mime.types'"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// `--verify-with-nginx` runs `nginx -t` in a container once the static
+/// checks pass and reports its messages on the files they name. A script
+/// standing in for docker prints what nginx would.
+#[cfg(all(feature = "cli", unix))]
+#[test]
+fn test_verify_with_nginx_reports_nginx_test_messages() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    let args_file = root.join("docker-args");
+    let docker = root.join("docker");
+    fs::write(
+        &docker,
+        format!(
+            r#"#!/bin/sh
+echo "$@" > {args}
+for conf; do :; done
+dir=$(dirname "$conf")
+echo "nginx: [warn] duplicate MIME type \"text/html\" in /etc/nginx/mime.types:3" >&2
+echo "nginx: [emerg] unknown directive \"foo\" in $dir/conf.d/app.conf:2" >&2
+echo "nginx: configuration file $conf test failed" >&2
+exit 1
+"#,
+            args = args_file.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&docker, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let conf_dir = root.join("conf");
+    fs::create_dir_all(conf_dir.join("conf.d")).unwrap();
+    let conf = conf_dir.join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server_tokens off;\n    include conf.d/*.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        conf_dir.join("conf.d/app.conf"),
+        "server {\n    foo on;\n}\n",
+    )
+    .unwrap();
+
+    let run = |args: &[&str], conf: &Path| {
+        Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .env("NGINX_LINT_DOCKER", &docker)
+            .args(args)
+            .arg(conf)
+            .output()
+            .expect("Failed to run nginx-lint")
+    };
+
+    let output = run(&["--verify-with-nginx=nginx:1.27"], &conf);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "got:\n{}", stdout);
+    assert!(
+        stdout.contains("conf.d/app.conf:2:")
+            && stdout.contains("unknown directive \"foo\"")
+            && stdout.contains("duplicate MIME type \"text/html\" in /etc/nginx/mime.types:3")
+            && stdout.contains("nginx-test"),
+        "got:\n{}",
+        stdout
+    );
+    assert_eq!(
+        fs::read_to_string(&args_file).unwrap().trim(),
+        format!(
+            "run --rm -v {dir}:{dir}:ro --entrypoint nginx nginx:1.27 -t -c {conf}",
+            dir = conf_dir.display(),
+            conf = conf.display()
+        )
+    );
+
+    // nginx is not run while the static checks report errors
+    fs::remove_file(&args_file).unwrap();
+    let broken = conf_dir.join("broken.conf");
+    fs::write(&broken, "http {\n").unwrap();
+    let output = run(&["--verify-with-nginx"], &broken);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!args_file.exists());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .env("NGINX_LINT_DOCKER", root.join("no-such-engine"))
+        .arg("--verify-with-nginx")
+        .arg(&conf)
+        .output()
+        .expect("Failed to run nginx-lint");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Error: --verify-with-nginx: failed to run"),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// `--cache` serves unchanged files from the cache, while the cross-file
+/// rules and the rules reading other files still see changes elsewhere.
+/// The cross-file rules only run again when the symbols change.
+#[cfg(feature = "cli")]
+#[test]
+fn test_cache_reruns_cross_file_and_file_reading_rules() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir(root.join("conf.d")).unwrap();
+    fs::write(
+        root.join("nginx.conf"),
+        "http {\n    server_tokens off;\n    include conf.d/*.conf;\n    include extra.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("conf.d/upstream.conf"),
+        "upstream backend {\n    server 127.0.0.1:8080;\n}\n",
+    )
+    .unwrap();
+    let site = "server {\n    location / {\n        proxy_pass http://backend;\n    }\n}\n";
+    fs::write(root.join("conf.d/site.conf"), site).unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .current_dir(root)
+            .args([
+                "--cache",
+                "--cache-dir",
+                "cache",
+                "--verbose",
+                "--rule-only",
+                "include-path-exists,unused-upstream,server-tokens-enabled",
+                "nginx.conf",
+            ])
+            .output()
+            .expect("Failed to run nginx-lint");
+        (
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+    };
+
+    let (first, stderr) = run();
+    assert!(
+        first.contains("nginx.conf:4:5: error[syntax/include-path-exists]"),
+        "got:\n{}",
+        first
+    );
+    assert!(
+        stderr.contains("(0 hit(s), 3 miss(es))"),
+        "stderr: {}",
+        stderr
+    );
+
+    let (second, stderr) = run();
+    assert_eq!(first, second);
+    assert!(
+        stderr.contains("(3 hit(s), 0 miss(es))"),
+        "stderr: {}",
+        stderr
+    );
+
+    // nginx.conf is unchanged, but the file it includes now exists
+    fs::write(root.join("extra.conf"), "").unwrap();
+    let (third, _) = run();
+    assert!(!third.contains("include-path-exists"), "got:\n{}", third);
+
+    // upstream.conf is unchanged, but its upstream is no longer used
+    fs::write(
+        root.join("conf.d/site.conf"),
+        site.replace("proxy_pass http://backend;", "return 204;"),
+    )
+    .unwrap();
+    let (fourth, stderr) = run();
+    assert!(
+        fourth.contains("conf.d/upstream.conf:1:1: warning[best-practices/unused-upstream]"),
+        "got:\n{}",
+        fourth
+    );
+    assert!(
+        stderr.contains("(3 hit(s), 1 miss(es))"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(
+        stderr.contains("dependencies changed in conf.d/site.conf\n"),
+        "stderr: {}",
+        stderr
+    );
+
+    // An edit leaving the symbols alone keeps the cross-file results
+    let mut edited = site.replace("proxy_pass http://backend;", "return 204;");
+    edited.push_str("# served by the app\n");
+    fs::write(root.join("conf.d/site.conf"), edited).unwrap();
+    let (fifth, stderr) = run();
+    assert_eq!(fourth, fifth);
+    assert!(
+        stderr.contains("cross-file results reused"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+/// `archive` and `image` lint the configuration tree inside a tarball or a
+/// container image, following includes and symlinks within it. A script
+/// standing in for docker exports a prepared filesystem.
+#[cfg(all(feature = "cli", unix))]
+#[test]
+fn test_lint_archive_and_image() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let nginx = root.join("etc/nginx");
+    fs::create_dir_all(nginx.join("conf.d")).unwrap();
+    fs::create_dir_all(nginx.join("sites-available")).unwrap();
+    fs::create_dir_all(nginx.join("sites-enabled")).unwrap();
+    fs::write(
+        nginx.join("nginx.conf"),
+        "http {\n    server_tokens off;\n    include /etc/nginx/conf.d/*.conf;\n    include sites-enabled/*;\n    include missing.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        nginx.join("conf.d/app.conf"),
+        "server {\n    server_tokens on;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        nginx.join("sites-available/default"),
+        "server {\n    autoindex on;\n}\n",
+    )
+    .unwrap();
+    std::os::unix::fs::symlink(
+        "../sites-available/default",
+        nginx.join("sites-enabled/default"),
+    )
+    .unwrap();
+
+    let tar = |archive: &Path, dir: &Path, entry: &str| {
+        let status = Command::new("tar")
+            .arg("-czf")
+            .arg(archive)
+            .arg("-C")
+            .arg(dir)
+            .arg(entry)
+            .status()
+            .expect("Failed to run tar");
+        assert!(status.success());
+    };
+    let assert_tree_linted = |output: &std::process::Output| {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(output.status.code(), Some(1), "got:\n{}", stdout);
+        assert!(
+            stdout.contains("/etc/nginx/conf.d/app.conf:2:5: warning[security/server-tokens-enabled]")
+                && stdout.contains("/etc/nginx/sites-enabled/default:2:5: warning[security/autoindex-enabled]")
+                && stdout.contains("/etc/nginx/nginx.conf:5:5: error[syntax/include-path-exists]: Included file 'missing.conf' does not exist"),
+            "got:\n{}",
+            stdout
+        );
+    };
+
+    // A backup of /etc/nginx
+    let backup = root.join("backup.tar.gz");
+    tar(&backup, &root.join("etc"), "nginx");
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .current_dir(root)
+        .args(["archive", "--mount-point", "/etc"])
+        .arg(&backup)
+        .output()
+        .expect("Failed to run nginx-lint");
+    assert_tree_linted(&output);
+
+    // The exit policy of the lint command applies
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .current_dir(root)
+        .args(["archive", "--mount-point", "/etc", "--quiet"])
+        .arg(&backup)
+        .output()
+        .expect("Failed to run nginx-lint");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "got:\n{}", stdout);
+    assert!(
+        stdout.contains("error[syntax/include-path-exists]") && !stdout.contains("warning["),
+        "got:\n{}",
+        stdout
+    );
+
+    // The filesystem of a container
+    let export = root.join("export.tar.gz");
+    tar(&export, root, "etc");
+    let removed = root.join("removed");
+    let docker = root.join("docker");
+    fs::write(
+        &docker,
+        format!(
+            "#!/bin/sh\ncase \"$1\" in\n    create) echo c0ffee ;;\n    export) cat {export} ;;\n    rm) echo \"$2\" > {removed} ;;\nesac\n",
+            export = export.display(),
+            removed = removed.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&docker, fs::Permissions::from_mode(0o755)).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .current_dir(root)
+        .env("NGINX_LINT_DOCKER", &docker)
+        .args(["image", "nginx:1.25"])
+        .output()
+        .expect("Failed to run nginx-lint");
+    assert_tree_linted(&output);
+    assert_eq!(fs::read_to_string(&removed).unwrap().trim(), "c0ffee");
+}
+
+/// --from-crossplane lints a `crossplane parse` dump, read from stdin here,
+/// at the original lines, following its includes and without the
+/// formatting rules.
+#[cfg(feature = "cli")]
+#[test]
+fn test_from_crossplane_dump() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let dump = r#"{"status": "ok", "errors": [], "config": [
+        {"file": "/etc/nginx/nginx.conf", "status": "ok", "errors": [], "parsed": [
+            {"directive": "events", "line": 1, "args": [], "block": []},
+            {"directive": "http", "line": 3, "args": [], "block": [
+                {"directive": "server_tokens", "line": 4, "args": ["off"]},
+                {"directive": "include", "line": 5, "args": ["conf.d/*.conf"], "includes": [1]}
+            ]}
+        ]},
+        {"file": "/etc/nginx/conf.d/app.conf", "status": "ok", "errors": [], "parsed": [
+            {"directive": "server", "line": 1, "args": [], "block": [
+                {"directive": "listen", "line": 2, "args": ["80"]},
+                {"directive": "location", "line": 7, "args": ["/files"], "block": [
+                    {"directive": "autoindex", "line": 8, "args": ["on"]}
+                ]}
+            ]}
+        ]}
+    ]}"#;
+
+    let dir = tempfile::tempdir().unwrap();
+    // Would report every line of the generated text
+    fs::write(
+        dir.path().join(".nginx-lint.toml"),
+        "[rules.indent]\nindent_size = 2\n",
+    )
+    .unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .current_dir(dir.path())
+        .args(["--from-crossplane", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to run nginx-lint");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(dump.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "got:\n{}", stdout);
+    assert!(
+        stdout.contains("/etc/nginx/conf.d/app.conf:8:9: warning[security/autoindex-enabled]"),
+        "got:\n{}",
+        stdout
+    );
+    assert!(
+        !stdout.contains("server-tokens-enabled"),
+        "got:\n{}",
+        stdout
+    );
+    assert!(!stdout.contains("style/indent"), "got:\n{}", stdout);
+}
+
+/// missing-security-headers is opt-in, and `required_headers` replaces the
+/// default header list.
+#[cfg(feature = "cli")]
+#[test]
+fn test_missing_security_headers_required_headers_from_config() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        listen 80;\n        add_header X-Frame-Options DENY;\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(
+        !run().contains("missing-security-headers"),
+        "rule should be disabled by default"
+    );
+
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        "[rules.missing-security-headers]\nenabled = true\nrequired_headers = [\"X-Frame-Options\", \"Referrer-Policy\"]\n",
+    )
+    .unwrap();
+    let stdout = run();
+    assert!(
+        stdout.contains("nginx.conf:2:5")
+            && stdout.contains("security header(s) 'Referrer-Policy'")
+            && !stdout.contains("X-Content-Type-Options"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// proxy-timeout-not-set is opt-in, and the configured maximums flag
+/// explicit timeouts above them.
+#[cfg(feature = "cli")]
+#[test]
+fn test_proxy_timeout_not_set_limits_from_config() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        location / {\n            proxy_connect_timeout 30s;\n            proxy_read_timeout 30s;\n            proxy_pass https://api.example.com;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(
+        !run().contains("proxy-timeout-not-set"),
+        "rule should be disabled by default"
+    );
+
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        "[rules.proxy-timeout-not-set]\nenabled = true\nmax_connect_timeout = \"5s\"\nmax_read_timeout = \"1m\"\n",
+    )
+    .unwrap();
+    let stdout = run();
+    assert!(
+        stdout.contains("nginx.conf:4:13")
+            && stdout.contains("proxy_connect_timeout 30s for external host 'api.example.com'")
+            && !stdout.contains("proxy_read_timeout 30s"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// unsafe-x-forwarded-for reports forwarding the client chain only once a
+/// `deployment` is configured.
+#[cfg(feature = "cli")]
+#[test]
+fn test_unsafe_x_forwarded_for_deployment_from_config() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        location / {\n            proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n            proxy_pass http://backend;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(
+        !run().contains("unsafe-x-forwarded-for"),
+        "forwarding the chain is fine without a deployment"
+    );
+
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        "[rules.unsafe-x-forwarded-for]\ndeployment = \"internal\"\n",
+    )
+    .unwrap();
+    let stdout = run();
+    assert!(
+        stdout.contains("nginx.conf:4:13") && stdout.contains("add set_real_ip_from"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// suspicious-proxy-settings is opt-in, and `max_read_timeout` replaces the
+/// default 300s limit.
+#[cfg(feature = "cli")]
+#[test]
+fn test_suspicious_proxy_settings_max_read_timeout_from_config() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        location / {\n            proxy_read_timeout 2m;\n            proxy_pass http://backend;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(
+        !run().contains("suspicious-proxy-settings"),
+        "rule should be disabled by default"
+    );
+
+    let config = temp_dir.path().join(".nginx-lint.toml");
+    fs::write(
+        &config,
+        "[rules.suspicious-proxy-settings]\nenabled = true\n",
+    )
+    .unwrap();
+    assert!(
+        !run().contains("suspicious-proxy-settings"),
+        "2m is within the default limit"
+    );
+
+    fs::write(
+        &config,
+        "[rules.suspicious-proxy-settings]\nenabled = true\nmax_read_timeout = \"1m\"\n",
+    )
+    .unwrap();
+    let stdout = run();
+    assert!(
+        stdout.contains("nginx.conf:4:13") && stdout.contains("proxy_read_timeout 2m exceeds 1m"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_checkstyle_output_is_one_document() {
+    use std::process::Command;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("conf.d")).unwrap();
+    let conf = dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server_tokens off;\n    client_max_body_size 1m;\n    include conf.d/*.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("conf.d/app.conf"),
+        "server {\n    listen 80;\n    server_tokens on;\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["--format", "checkstyle", conf.to_str().unwrap()])
+        .output()
+        .expect("Failed to run nginx-lint --format checkstyle");
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n"
+        ),
+        "got:\n{}",
+        stdout
+    );
+    assert!(stdout.ends_with("</checkstyle>\n"), "got:\n{}", stdout);
+    assert_eq!(stdout.matches("<checkstyle").count(), 1);
+    assert_eq!(stdout.matches("<file name=").count(), 2);
+    assert!(
+        stdout.contains("<error line=\"3\" column=\"5\" severity=\"warning\" message=\"server_tokens should be &apos;off&apos; to hide nginx version\" source=\"nginx-lint.security.server-tokens-enabled\"/>"),
+        "got:\n{}",
+        stdout
+    );
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_rdjson_output_is_one_document() {
+    use std::process::Command;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("conf.d")).unwrap();
+    let conf = dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server_tokens on;\n    client_max_body_size 1m;\n    include conf.d/*.conf;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("conf.d/app.conf"),
+        "server {\n    listen 80;\n    server_tokens on;\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["--format", "rdjson", conf.to_str().unwrap()])
+        .output()
+        .expect("Failed to run nginx-lint --format rdjson");
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let result: serde_json::Value = serde_json::from_str(&stdout)
+        .unwrap_or_else(|e| panic!("not one JSON document ({}):\n{}", e, stdout));
+    assert_eq!(result["source"]["name"], "nginx-lint");
+
+    let diagnostics = result["diagnostics"].as_array().unwrap();
+    let server_tokens: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d["code"]["value"] == "server-tokens-enabled")
+        .collect();
+    assert_eq!(server_tokens.len(), 2, "got:\n{}", stdout);
+    let app = server_tokens
+        .iter()
+        .find(|d| {
+            d["location"]["path"]
+                .as_str()
+                .unwrap()
+                .ends_with("app.conf")
+        })
+        .unwrap();
+    assert_eq!(app["severity"], "WARNING");
+    assert_eq!(
+        app["location"]["range"]["start"],
+        serde_json::json!({"line": 3, "column": 5})
+    );
+    assert_eq!(
+        app["suggestions"],
+        serde_json::json!([{
+            "range": {"start": {"line": 3, "column": 1}, "end": {"line": 3, "column": 22}},
+            "text": "    server_tokens off;"
+        }])
+    );
+}
+
+// ============================================================================
+// --trust-policy
+// ============================================================================
+
+/// Under trust policy untrusted, options reaching beyond the configuration
+/// are refused, and a config file cannot loosen the command line.
+#[cfg(feature = "cli")]
+#[test]
+fn test_untrusted_refuses_check_paths() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(&conf, "events {}\n").unwrap();
+    fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        "trust_policy = \"trusted\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["--trust-policy", "untrusted", "--check-paths"])
+        .arg(&conf)
+        .output()
+        .expect("Failed to run nginx-lint");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Error: --check-paths cannot be used with trust policy untrusted"),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// trust_policy = "untrusted" in the config file turns off rules that read
+/// files outside the configuration, and shows in `--version --verbose`.
+#[cfg(feature = "cli")]
+#[test]
+fn test_untrusted_config_disables_docroot_scan() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let public = temp_dir.path().join("public");
+    fs::create_dir_all(public.join("js")).unwrap();
+    fs::write(public.join("js/app.js"), "app();").unwrap();
+    fs::write(public.join("js/app.js.gz"), "").unwrap();
+    let conf = temp_dir.path().join("nginx.conf");
+    fs::write(
+        &conf,
+        "http {\n    server {\n        root /var/www/html;\n        location /js/ {\n        }\n    }\n}\n",
+    )
+    .unwrap();
+    let config = temp_dir.path().join(".nginx-lint.toml");
+    fs::write(
+        &config,
+        format!(
+            "trust_policy = \"untrusted\"\n\n[rules.precompressed-assets-not-served]\nenabled = true\ndocroot = \"{}\"\n",
+            public.display()
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .arg(&conf)
+        .output()
+        .expect("Failed to run nginx-lint");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        !stdout.contains("precompressed-assets-not-served"),
+        "got:\n{}",
+        stdout
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["--version", "--verbose", "--config"])
+        .arg(&config)
+        .output()
+        .expect("Failed to run nginx-lint --version");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with(concat!("nginx-lint ", env!("CARGO_PKG_VERSION"), "\n")),
+        "got:\n{}",
+        stdout
+    );
+    assert!(
+        stdout.contains(&format!("trust policy: untrusted ({})\n", config.display())),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// Diagnostics pointing at an argument are followed by the source line
+/// with the argument underlined
+#[cfg(feature = "cli")]
+#[test]
+fn test_text_output_underlines_argument() {
+    use std::process::Command;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nginx.conf");
+    std::fs::write(
+        &path,
+        "http {\n    server {\n        ssl_ciphers RC4:HIGH;\n    }\n}\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+        .args(["--no-color", path.to_str().unwrap()])
+        .output()
+        .expect("Failed to run nginx-lint");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!(
+            "Weak cipher suite 'RC4' should not be used\n{}ssl_ciphers RC4:HIGH;\n{}^^^^^^^^\n",
+            " ".repeat(12),
+            " ".repeat(24)
+        )),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// `-o pretty` shows the reported line with its surroundings, also for
+/// stdin
+#[cfg(feature = "cli")]
+#[test]
+fn test_pretty_output_shows_source() {
+    let config = "http {\n    server {\n        ssl_ciphers RC4:HIGH;\n    }\n}\n";
+    let output = run_with_stdin(&["--no-color", "-o", "pretty", "-"], config);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(
+            "warning[security/weak-ssl-ciphers]: Weak cipher suite 'RC4' should not be used
+ --> <stdin>:3:21
+  |
+1 | http {
+2 |     server {
+3 |         ssl_ciphers RC4:HIGH;
+  |                     ^^^^^^^^
+4 |     }
+5 | }
+  |
+"
+        ),
+        "got:\n{}",
+        stdout
+    );
+}
+
+/// `--template-mode` lints templates: placeholders no longer break parsing,
+/// positions refer to the template, and `--fix` leaves the placeholders
+/// alone.
+#[cfg(feature = "cli")]
+#[test]
+fn test_template_mode() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let conf = temp_dir.path().join("default.conf.j2");
+    let template = "http {\n    server {\n        {% if ssl %}\n        listen {{ port }} ssl;  \n        {% endif %}\n        server_name {{ name }};\n    }\n}\n";
+    fs::write(&conf, template).unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(args)
+            .args(["--rule-only", "trailing-whitespace,undefined-variable"])
+            .arg(&conf)
+            .output()
+            .expect("Failed to run nginx-lint");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = run(&[]);
+    assert!(stdout.contains("syntax-error"), "got:\n{}", stdout);
+
+    let stdout = run(&["--template-mode", "jinja"]);
+    assert!(
+        !stdout.contains("syntax") && !stdout.contains("undefined-variable"),
+        "got:\n{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("default.conf.j2:4:") && stdout.contains("style/trailing-whitespace"),
+        "got:\n{}",
+        stdout
+    );
+
+    run(&["--template-mode", "jinja", "--fix"]);
+    assert_eq!(
+        fs::read_to_string(&conf).unwrap(),
+        template.replace("ssl;  \n", "ssl;\n")
+    );
+}
+
+/// Rules defined in `[[custom_rules]]` report matching directives, take
+/// `[rules.<name>]` settings and ignore comments, and fix what they report
+#[cfg(feature = "cli")]
+#[test]
+fn test_custom_rules_via_cli() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        temp_dir.path().join(".nginx-lint.toml"),
+        r#"preset = "minimal"
+
+[[custom_rules]]
+name = "internal-location-allow"
+directive = "location"
+args = "^/internal"
+context = ["server"]
+require = { directive = "allow", args = "^10\\.0\\.0\\.0/8$" }
+message = "location {args} must allow 10.0.0.0/8"
+fix = "allow 10.0.0.0/8;"
+
+[rules.internal-location-allow]
+severity = "error"
+"#,
+    )
+    .unwrap();
+    let conf_path = temp_dir.path().join("nginx.conf");
+    let source = "http {\n    server {\n        location /internal {\n            deny all;\n        }\n        # nginx-lint:ignore internal-location-allow served by the sidecar\n        location /internal/metrics {\n            return 204;\n        }\n    }\n}\n";
+    std::fs::write(&conf_path, source).unwrap();
+    let lint = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(args)
+            .arg(conf_path.to_str().unwrap())
+            .output()
+            .expect("Failed to run nginx-lint")
+    };
+
+    let output = lint(&[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(
+            "3:9: error[custom/internal-location-allow]: location /internal must allow 10.0.0.0/8"
+        ),
+        "got:\n{}",
+        stdout
+    );
+    assert_eq!(
+        stdout.matches("internal-location-allow").count(),
+        1,
+        "got:\n{}",
+        stdout
+    );
+    assert!(!output.status.success());
+
+    let output = lint(&["--fix"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        std::fs::read_to_string(&conf_path).unwrap(),
+        source.replace(
+            "/internal {\n",
+            "/internal {\n            allow 10.0.0.0/8;\n"
+        )
+    );
+}
+
+/// A rule preset runs only the rules declared in it, from `--preset` or
+/// the config's `preset`, plus the rules the config has a section for
+#[cfg(feature = "cli")]
+#[test]
+fn test_rule_presets() {
+    use std::process::Command;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let conf_path = temp_dir.path().join("nginx.conf");
+    // server-tokens-enabled is in `recommended`, trailing-whitespace only
+    // in `strict`
+    std::fs::write(&conf_path, "http {\n    server_tokens on;  \n}\n").unwrap();
+    let config_path = temp_dir.path().join(".nginx-lint.toml");
+    let lint = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(args)
+            .arg(conf_path.to_str().unwrap())
+            .output()
+            .expect("Failed to run nginx-lint");
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        (
+            stdout.contains("server-tokens-enabled"),
+            stdout.contains("trailing-whitespace"),
+        )
+    };
+
+    assert_eq!(lint(&[]), (true, true));
+    assert_eq!(lint(&["--preset", "strict"]), (true, true));
+    assert_eq!(lint(&["--preset", "recommended"]), (true, false));
+    assert_eq!(lint(&["--preset", "minimal"]), (false, false));
+
+    std::fs::write(&config_path, "preset = \"minimal\"\n").unwrap();
+    assert_eq!(lint(&[]), (false, false));
+    // --preset overrides the config's preset
+    assert_eq!(lint(&["--preset", "recommended"]), (true, false));
+
+    // A configured rule runs whatever the preset
+    std::fs::write(
+        &config_path,
+        "preset = \"minimal\"\n\n[rules.trailing-whitespace]\nenabled = true\n",
+    )
+    .unwrap();
+    assert_eq!(lint(&[]), (false, true));
+}
+
+/// `--preset ingress-nginx` lints annotation snippets from stdin in the
+/// context the controller puts them in, which `--context` may name
+#[cfg(feature = "cli")]
+#[test]
+fn test_preset_ingress_nginx_snippets() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let run = |args: &[&str], snippet: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_nginx-lint"))
+            .args(["--preset", "ingress-nginx"])
+            .args(args)
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to run nginx-lint");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(snippet.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // A configuration-snippet, indented by the YAML it came from
+    let stdout = run(&[], "  proxy_pass http://10.0.0.12:8080;\n");
+    assert!(
+        stdout.contains("<stdin>:1:3: warning[best-practices/proxy-pass-hardcoded-ip]"),
+        "got:\n{}",
+        stdout
+    );
+    assert!(
+        !stdout.contains("indent") && !stdout.contains("proxy-missing-host-header"),
+        "got:\n{}",
+        stdout
+    );
+
+    let location = "location /healthz {\n    return 204;\n}\n";
+    let stdout = run(&["--context", "server-snippet"], location);
+    assert!(
+        !stdout.contains("invalid-directive-context"),
+        "got:\n{}",
+        stdout
+    );
+    let stdout = run(&["--context", "http-snippet"], location);
+    assert!(
+        stdout.contains("invalid-directive-context"),
+        "got:\n{}",
+        stdout
+    );
+}